@@ -0,0 +1,365 @@
+use elder::{
+	asset::{DependencyGraph, Texture, TextureOptions, find_references},
+	ecs::{
+		error::Result,
+		world::{Entity, World},
+	},
+};
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs, io,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+/// The largest width or height of a generated thumbnail
+pub const THUMBNAIL_SIZE: u32 = 64;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AssetKind {
+	Audio,
+	Model,
+	Scene,
+	Texture,
+	Video,
+}
+
+impl AssetKind {
+	pub fn from_path(path: &Path) -> Option<Self> {
+		let extension = path.extension()?.to_str()?.to_lowercase();
+		match extension.as_str() {
+			"flac" | "ogg" | "wav" => Some(Self::Audio),
+			"gltf" | "glb" => Some(Self::Model),
+			"ron" => Some(Self::Scene),
+			"png" | "jpg" | "jpeg" | "hdr" | "ktx2" => Some(Self::Texture),
+			"gif" | "y4m" => Some(Self::Video),
+			_ => None,
+		}
+	}
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Self::Audio => "audio",
+			Self::Model => "model",
+			Self::Scene => "scene",
+			Self::Texture => "texture",
+			Self::Video => "video",
+		}
+	}
+}
+
+/// A small sRGB preview of an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+	pub width: u32,
+	pub height: u32,
+	pub rgba: Vec<u8>,
+}
+
+impl Thumbnail {
+	/// Downsamples a texture to fit within `THUMBNAIL_SIZE`.
+	/// Returns `None` for block compressed textures, which can't be read on the CPU.
+	pub fn from_texture(texture: &Texture) -> Option<Self> {
+		let (width, height) = (texture.width(), texture.height());
+		let scale = (THUMBNAIL_SIZE as f32 / width.max(height) as f32).min(1.0);
+		let (thumbnail_width, thumbnail_height) = (((width as f32 * scale) as u32).max(1), ((height as f32 * scale) as u32).max(1));
+		let mut rgba = Vec::with_capacity((thumbnail_width * thumbnail_height * 4) as usize);
+		for y in 0..thumbnail_height {
+			for x in 0..thumbnail_width {
+				let texel = texture.texel(x * width / thumbnail_width, y * height / thumbnail_height)?;
+				rgba.extend(texel.iter().enumerate().map(|(channel, value)| {
+					let value = if channel < 3 { encode_srgb(*value) } else { *value };
+					(value.clamp(0.0, 1.0) * 255.0).round() as u8
+				}));
+			}
+		}
+		Some(Self {
+			width: thumbnail_width,
+			height: thumbnail_height,
+			rgba,
+		})
+	}
+}
+
+fn encode_srgb(value: f32) -> f32 {
+	if value <= 0.003_130_8 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetEntry {
+	/// The path relative to the browser's root
+	pub path: PathBuf,
+	pub kind: AssetKind,
+	pub modified: SystemTime,
+
+	/// Textures get a preview of their contents. Other kinds are shown with an icon for their kind.
+	pub thumbnail: Option<Thumbnail>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetChange {
+	Added(PathBuf),
+	Modified(PathBuf),
+	Removed(PathBuf),
+
+	/// Reimported because an asset it depends on changed
+	Reimported(PathBuf),
+}
+
+/// Marks an entity created by dragging an asset from the browser into the scene
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetInstance {
+	pub path: PathBuf,
+	pub kind: AssetKind,
+}
+
+/// Lists the assets in a project's assets directory.
+/// Rescanning reimports changed files along with every asset that depends on them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetBrowser {
+	root: PathBuf,
+	entries: BTreeMap<PathBuf, AssetEntry>,
+	dependencies: DependencyGraph,
+}
+
+impl AssetBrowser {
+	pub fn new(root: impl Into<PathBuf>) -> Self {
+		Self {
+			root: root.into(),
+			entries: BTreeMap::new(),
+			dependencies: DependencyGraph::new(),
+		}
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Entries sorted by path
+	pub fn entries(&self) -> impl Iterator<Item = &AssetEntry> {
+		self.entries.values()
+	}
+
+	pub fn entry(&self, path: &Path) -> Option<&AssetEntry> {
+		self.entries.get(path)
+	}
+
+	/// Which assets reference which, for showing an asset's dependencies and dependents
+	pub fn dependencies(&self) -> &DependencyGraph {
+		&self.dependencies
+	}
+
+	/// Walks the assets directory, importing new and changed assets and forgetting removed ones
+	pub fn scan(&mut self) -> io::Result<Vec<AssetChange>> {
+		let mut found = BTreeMap::new();
+		if self.root.is_dir() {
+			collect_assets(&self.root, &self.root, &mut found)?;
+		}
+		let mut changes = Vec::new();
+		let mut changed = Vec::new();
+		self.entries.retain(|path, _| {
+			let is_present = found.contains_key(path);
+			if !is_present {
+				changes.push(AssetChange::Removed(path.clone()));
+				changed.push(path.clone());
+			}
+			is_present
+		});
+		for path in changed.iter() {
+			self.dependencies.remove(path);
+		}
+		let mut imported = Vec::new();
+		for (path, (kind, modified)) in found {
+			let change = match self.entries.get(&path) {
+				None => AssetChange::Added(path.clone()),
+				Some(entry) if entry.modified != modified => AssetChange::Modified(path.clone()),
+				Some(_) => continue,
+			};
+			let thumbnail = self.import(&path, kind);
+			imported.push(path.clone());
+			self.entries.insert(path.clone(), AssetEntry { path, kind, modified, thumbnail });
+			changes.push(change);
+		}
+		// References are resolved once every entry is known, since they can point at later assets
+		for path in imported.iter() {
+			let references = self.find_dependencies(path);
+			self.dependencies.set_dependencies(path, references);
+		}
+		changed.extend(imported);
+
+		let mut reimported = BTreeSet::new();
+		for path in changed.iter() {
+			for dependent in self.dependencies.downstream(path) {
+				if !changed.contains(&dependent) && self.entries.contains_key(&dependent) && reimported.insert(dependent.clone()) {
+					self.reimport(&dependent);
+					changes.push(AssetChange::Reimported(dependent));
+				}
+			}
+		}
+		Ok(changes)
+	}
+
+	fn reimport(&mut self, path: &Path) {
+		let Some(kind) = self.entries.get(path).map(|entry| entry.kind) else {
+			return;
+		};
+		let thumbnail = self.import(path, kind);
+		if let Some(entry) = self.entries.get_mut(path) {
+			entry.thumbnail = thumbnail;
+		}
+	}
+
+	/// The assets a scene or glTF file references, relative to the file or the assets directory
+	fn find_dependencies(&self, path: &Path) -> Vec<PathBuf> {
+		let can_reference = matches!(self.entries.get(path).map(|entry| entry.kind), Some(AssetKind::Scene | AssetKind::Model));
+		let Some(contents) = can_reference.then(|| fs::read_to_string(self.root.join(path)).ok()).flatten() else {
+			return Vec::new();
+		};
+		let directory = path.parent().unwrap_or(Path::new(""));
+		find_references(&contents)
+			.into_iter()
+			.filter_map(|reference| {
+				[directory.join(&reference), PathBuf::from(&reference)]
+					.into_iter()
+					.find(|candidate| self.entries.contains_key(candidate))
+			})
+			.collect()
+	}
+
+	/// Runs an asset through the import pipeline, returning its thumbnail
+	fn import(&self, path: &Path, kind: AssetKind) -> Option<Thumbnail> {
+		if kind != AssetKind::Texture {
+			return None;
+		}
+		match Texture::load(self.root.join(path), TextureOptions::default()) {
+			Ok(texture) => Thumbnail::from_texture(&texture),
+			Err(error) => {
+				log::warn!("Failed to import {}: {}", path.display(), error);
+				None
+			},
+		}
+	}
+
+	/// Copies a file from outside the project into the assets directory, returning its new path
+	pub fn import_file(&mut self, source: &Path) -> io::Result<PathBuf> {
+		let name = source
+			.file_name()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Imported path has no file name"))?;
+		fs::create_dir_all(&self.root)?;
+		fs::copy(source, self.root.join(name))?;
+		self.scan()?;
+		Ok(PathBuf::from(name))
+	}
+
+	/// Creates an entity for an asset dragged into the scene
+	pub fn instantiate(&self, path: &Path, world: &mut World) -> Result<Option<Entity>> {
+		let Some(entry) = self.entries.get(path) else {
+			return Ok(None);
+		};
+		let entity = world.create_entity();
+		world.add_component(
+			entity,
+			AssetInstance {
+				path: entry.path.clone(),
+				kind: entry.kind,
+			},
+		)?;
+		Ok(Some(entity))
+	}
+}
+
+fn collect_assets(root: &Path, directory: &Path, found: &mut BTreeMap<PathBuf, (AssetKind, SystemTime)>) -> io::Result<()> {
+	for entry in fs::read_dir(directory)? {
+		let path = entry?.path();
+		if path.is_dir() {
+			collect_assets(root, &path, found)?;
+			continue;
+		}
+		let Some(kind) = AssetKind::from_path(&path) else {
+			continue;
+		};
+		let modified = fs::metadata(&path)?.modified()?;
+		let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+		found.insert(relative, (kind, modified));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use elder::asset::{MipLevel, TextureFormat};
+
+	fn directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("elder_asset_browser_{name}_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(directory.join("textures")).unwrap();
+		directory
+	}
+
+	#[test]
+	pub fn thumbnails() {
+		let texture = Texture {
+			format: TextureFormat::Rgba8,
+			mip_levels: vec![MipLevel {
+				width: 256,
+				height: 128,
+				data: vec![255; 256 * 128 * 4],
+			}],
+		};
+		let thumbnail = Thumbnail::from_texture(&texture).unwrap();
+		assert_eq!((thumbnail.width, thumbnail.height), (64, 32));
+		assert!(thumbnail.rgba.iter().all(|value| *value == 255));
+	}
+
+	#[test]
+	pub fn scanning() -> Result<()> {
+		let root = directory("scanning");
+		fs::write(root.join("textures/notes.txt"), "ignored")?;
+		fs::write(root.join("music.ogg"), [])?;
+		let mut browser = AssetBrowser::new(&root);
+		assert_eq!(browser.scan()?, [AssetChange::Added(PathBuf::from("music.ogg"))]);
+		assert!(browser.scan()?.is_empty());
+
+		let mut world = World::new();
+		let entity = browser.instantiate(Path::new("music.ogg"), &mut world)?.unwrap();
+		assert_eq!(world.get_component::<AssetInstance>(entity).map(|instance| instance.kind), Some(AssetKind::Audio));
+
+		fs::remove_file(root.join("music.ogg"))?;
+		assert_eq!(browser.scan()?, [AssetChange::Removed(PathBuf::from("music.ogg"))]);
+		assert_eq!(browser.entries().count(), 0);
+		fs::remove_dir_all(&root)?;
+		Ok(())
+	}
+
+	#[test]
+	pub fn dependencies() -> Result<()> {
+		let root = directory("dependencies");
+		fs::write(root.join("textures/brick.png"), [])?;
+		fs::write(root.join("wall.gltf"), r#"{ "images": [{ "uri": "textures/brick.png" }] }"#)?;
+		fs::write(root.join("level.ron"), r#"(entities: [(mesh: "wall.gltf")])"#)?;
+		let mut browser = AssetBrowser::new(&root);
+		assert_eq!(browser.scan()?.len(), 3);
+		let dependents = browser.dependencies().downstream(Path::new("textures/brick.png"));
+		assert_eq!(dependents, [PathBuf::from("wall.gltf"), PathBuf::from("level.ron")]);
+
+		// Editing the texture reimports the model and scene built from it.
+		// Mtimes can be coarse, so move it back.
+		let file = fs::File::options().write(true).open(root.join("textures/brick.png"))?;
+		file.set_modified(SystemTime::UNIX_EPOCH)?;
+		assert_eq!(
+			browser.scan()?,
+			[
+				AssetChange::Modified(PathBuf::from("textures/brick.png")),
+				AssetChange::Reimported(PathBuf::from("wall.gltf")),
+				AssetChange::Reimported(PathBuf::from("level.ron"))
+			]
+		);
+		fs::remove_dir_all(&root)?;
+		Ok(())
+	}
+}
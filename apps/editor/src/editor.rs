@@ -1,30 +1,423 @@
-use elder::state::{State, StateResult, Transition};
+use crate::{
+	assets::{AssetBrowser, AssetInstance, AssetKind},
+	gizmo::{AxisGizmo, ViewDirection},
+	hierarchy::{CopiedEntity, ENTITY_CLIPBOARD_KIND, HierarchyPanel},
+	menu::EditorAction,
+	play::{PlaySession, PlayState},
+	project::{Project, RecentProjects},
+	viewport::{OrbitCamera, enclosing_sphere},
+};
+use elder::{
+	app::{Clipboard, Events, FileDropEvent, GAMEPLAY_CHANNEL, Input, MouseButton, ScreenLayout, Time, WindowCommands},
+	ecs::{error::Result, resource::ResourceMap, world::World},
+	graphics::{DebugDraw, ReferenceGrid, RenderDebugMode, RenderTargets, RenderTextureCamera, RenderView, RenderViews, ViewLayout},
+	intern::Label,
+	math::{BoundingSphere, Camera, Extent2D, Real},
+	scene,
+	state::{State, StateResult, Transition},
+};
+use std::{
+	path::{Path, PathBuf},
+	time::{Duration, Instant},
+};
 
-#[derive(Default)]
-pub struct Editor;
+/// The length of a frame advanced with the step action while paused
+const STEP_DELTA_TIME: f32 = 1.0 / 60.0;
 
-impl State<()> for Editor {
-	fn label(&self) -> String {
-		"Elder Game Engine - Editor".to_string()
+/// How often the assets directory is checked for changed files
+const ASSET_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+const FOCUS_SELECTION_KEY: &str = "F";
+
+/// Copy and paste the selected entities while control is held
+const COPY_KEY: &str = "C";
+const PASTE_KEY: &str = "V";
+
+/// Number keys recall camera bookmarks, and save them while control is held
+const BOOKMARK_KEYS: [&str; 9] = ["Key1", "Key2", "Key3", "Key4", "Key5", "Key6", "Key7", "Key8", "Key9"];
+
+/// The sides the quad view's fixed cameras look from, after the perspective view
+const FIXED_VIEWS: [(&str, ViewDirection); 3] = [("Top", ViewDirection::Top), ("Front", ViewDirection::Front), ("Side", ViewDirection::Right)];
+
+pub struct Editor {
+	pub world: World,
+	pub play: PlaySession,
+	pub assets: AssetBrowser,
+	pub hierarchy: HierarchyPanel,
+	pub camera: OrbitCamera,
+
+	/// The orthographic top, front, and side cameras shown in the quad view
+	pub fixed_cameras: [OrbitCamera; 3],
+
+	/// The perspective view followed by the fixed views, stored as a resource every frame
+	pub views: RenderViews,
+	pub grid: ReferenceGrid,
+	pub gizmo: AxisGizmo,
+
+	/// The project being edited. Without one, the editor works in the current directory.
+	pub project: Option<Project>,
+
+	pub recent_projects: RecentProjects,
+	last_asset_scan: Option<Instant>,
+
+	/// The title last sent to the window, so it's only changed when it differs
+	window_title: String,
+}
+
+impl Default for Editor {
+	fn default() -> Self {
+		Self {
+			world: new_world(),
+			play: PlaySession::default(),
+			assets: AssetBrowser::new("assets"),
+			hierarchy: HierarchyPanel::default(),
+			camera: OrbitCamera::default(),
+			fixed_cameras: FIXED_VIEWS.map(|(_, direction)| OrbitCamera::fixed(direction)),
+			views: new_views(),
+			grid: ReferenceGrid::default(),
+			gizmo: AxisGizmo::default(),
+			project: None,
+			recent_projects: RecentProjects::default(),
+			last_asset_scan: None,
+			window_title: String::new(),
+		}
+	}
+}
+
+fn new_views() -> RenderViews {
+	let names = std::iter::once("Perspective").chain(FIXED_VIEWS.map(|(name, _)| name));
+	RenderViews::new(ViewLayout::Single, names.map(|name| RenderView::new(name, Default::default())))
+}
+
+fn new_world() -> World {
+	let mut world = World::new();
+	scene::register_components(&mut world);
+	world.register_cloneable_component::<AssetInstance>();
+	world.register_cloneable_component::<RenderTextureCamera>();
+	world
+}
+
+impl Editor {
+	pub fn apply_action(&mut self, action: EditorAction, resources: &mut ResourceMap) -> Result<()> {
+		match action {
+			EditorAction::SetRenderDebugMode(mode) => resources.insert(mode),
+			EditorAction::Play => self.play.play(&self.world)?,
+			EditorAction::Pause => self.play.pause(),
+			EditorAction::Stop => self.play.stop(&mut self.world),
+			EditorAction::Step => self.play.step(&mut self.world, STEP_DELTA_TIME)?,
+			EditorAction::NewProject { directory, name } => self.new_project(&directory, &name)?,
+			EditorAction::OpenProject(path) => self.open_project(&path)?,
+			EditorAction::CreateEntity { parent } => {
+				self.hierarchy.create_entity(&mut self.world, parent)?;
+			},
+			EditorAction::DeleteSelected => self.hierarchy.delete_selected(&mut self.world)?,
+			EditorAction::CopySelected => {
+				let copied = self.hierarchy.copy_selected(&self.world);
+				if !copied.is_empty() {
+					let clipboard = resources.get_mut::<Clipboard>().ok_or("The clipboard was not inserted")?;
+					clipboard.set_payload(ENTITY_CLIPBOARD_KIND, &copied)?;
+				}
+			},
+			EditorAction::Paste { parent } => {
+				let clipboard = resources.get_mut::<Clipboard>().ok_or("The clipboard was not inserted")?;
+				if let Some(copied) = clipboard.payload::<Vec<CopiedEntity>>(ENTITY_CLIPBOARD_KIND)? {
+					self.hierarchy.paste(&mut self.world, &copied, parent)?;
+				}
+			},
+			EditorAction::RenameEntity { entity, name } => self.hierarchy.rename(&mut self.world, entity, &name)?,
+			EditorAction::ReparentSelected(parent) => self.hierarchy.reparent_selected(&mut self.world, parent)?,
+			EditorAction::AddComponent { entity, component } => self.hierarchy.add_component(&mut self.world, entity, &component)?,
+			EditorAction::ToggleExpanded(entity) => self.hierarchy.toggle_expanded(entity),
+			EditorAction::Select { entity, mode } => self.hierarchy.select(&self.world, entity, mode),
+			EditorAction::FocusSelection => self.focus_selection(),
+			EditorAction::SnapView(direction) => self.camera.snap_to(direction),
+			EditorAction::SetViewLayout(layout) => self.set_view_layout(layout),
+			EditorAction::AssignRenderTarget { material, target } => {
+				let targets = resources.get_mut::<RenderTargets>().ok_or("The render targets were not inserted")?;
+				let handle = targets.find(&target).ok_or_else(|| format!("Failed to find render target '{target}'"))?;
+				targets.assign(material, handle)?;
+			},
+			EditorAction::UnassignRenderTarget(material) => {
+				if let Some(targets) = resources.get_mut::<RenderTargets>() {
+					targets.unassign(material);
+				}
+			},
+			EditorAction::SaveCameraBookmark(slot) => self.save_camera_bookmark(slot)?,
+			EditorAction::RecallCameraBookmark(slot) => self.recall_camera_bookmark(slot),
+		}
+		Ok(())
+	}
+
+	/// Frames the selected entities, treating each one as a unit sphere scaled by its largest axis
+	pub fn focus_selection(&mut self) {
+		let spheres = self
+			.hierarchy
+			.selection()
+			.iter()
+			.map(|entity| {
+				let transform = scene::global_transform(&self.world, *entity);
+				let scale = transform.scale;
+				BoundingSphere::new(transform.translation, scale.x().abs().max(scale.y().abs()).max(scale.z().abs()))
+			})
+			.collect::<Vec<_>>();
+		if let Some(bounds) = enclosing_sphere(&spheres) {
+			self.cameras_mut().for_each(|camera| camera.focus_on(&bounds));
+		}
+	}
+
+	/// The perspective camera followed by the fixed ones, in the same order as the views
+	fn cameras_mut(&mut self) -> impl Iterator<Item = &mut OrbitCamera> {
+		std::iter::once(&mut self.camera).chain(self.fixed_cameras.iter_mut())
+	}
+
+	/// Shows only the perspective view, or the quad view with the fixed views next to it
+	pub fn set_view_layout(&mut self, layout: ViewLayout) {
+		self.views.set_layout(match layout {
+			ViewLayout::Quad => ViewLayout::Quad,
+			_ => ViewLayout::Single,
+		});
+	}
+
+	pub fn view_layout(&self) -> ViewLayout {
+		match self.views.visible().count() {
+			1 => ViewLayout::Single,
+			_ => ViewLayout::Quad,
+		}
+	}
+
+	/// Saves the camera to the open project's file
+	pub fn save_camera_bookmark(&mut self, slot: u8) -> Result<()> {
+		let Some(project) = self.project.as_mut() else {
+			log::warn!("Open a project to save camera bookmarks");
+			return Ok(());
+		};
+		project.set_camera_bookmark(self.camera.bookmark(slot));
+		project.save()?;
+		Ok(())
+	}
+
+	pub fn recall_camera_bookmark(&mut self, slot: u8) {
+		if let Some(bookmark) = self.project.as_ref().and_then(|project| project.camera_bookmark(slot)) {
+			self.camera.apply_bookmark(bookmark);
+		}
+	}
+
+	/// Moves the camera of the view under the cursor, triggers the viewport's shortcuts, and draws
+	/// the grid and axis gizmo, which belongs to the perspective view since the others can't turn.
+	fn update_camera(&mut self, resources: &mut ResourceMap) -> Result<()> {
+		let Some(input) = resources.get::<Input>().cloned() else {
+			return Ok(());
+		};
+		let window = resources.get::<ScreenLayout>().map_or_else(Extent2D::default, ScreenLayout::extent);
+		let cursor = input.cursor().map(|cursor| (cursor.x as Real, cursor.y as Real));
+		let is_button_held = [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+			.into_iter()
+			.any(|button| input.is_button_held(button));
+		if let Some(index) = self.views.update_focus(cursor, is_button_held, window) {
+			let viewport = self.views.views[index].viewport(window);
+			let clicked = cursor.filter(|_| index == 0 && input.was_button_pressed(MouseButton::Left));
+			match clicked.and_then(|cursor| self.gizmo.pick(&self.camera.camera(), &viewport, cursor)) {
+				Some(direction) => self.apply_action(EditorAction::SnapView(direction), resources)?,
+				None => {
+					if let Some(camera) = self.cameras_mut().nth(index) {
+						camera.update(&input, &viewport);
+					}
+				},
+			}
+		}
+		if input.was_key_pressed(FOCUS_SELECTION_KEY) {
+			self.apply_action(EditorAction::FocusSelection, resources)?;
+		}
+		if input.is_control_held() && input.was_key_pressed(COPY_KEY) {
+			self.apply_action(EditorAction::CopySelected, resources)?;
+		}
+		if input.is_control_held() && input.was_key_pressed(PASTE_KEY) {
+			self.apply_action(EditorAction::Paste { parent: None }, resources)?;
+		}
+		for (slot, key) in (1..).zip(BOOKMARK_KEYS) {
+			if !input.was_key_pressed(key) {
+				continue;
+			}
+			let action = if input.is_control_held() {
+				EditorAction::SaveCameraBookmark(slot)
+			} else {
+				EditorAction::RecallCameraBookmark(slot)
+			};
+			self.apply_action(action, resources)?;
+		}
+		let cameras = self.cameras_mut().map(|camera| camera.camera()).collect::<Vec<_>>();
+		for (view, camera) in self.views.views.iter_mut().zip(cameras) {
+			view.camera = camera;
+		}
+		let camera = self.camera.camera();
+		if let Some(draw) = resources.get_mut::<DebugDraw>() {
+			self.grid.draw(draw, camera.position);
+			self.gizmo.draw(draw, &camera, &self.views.views[0].viewport(window));
+		}
+		resources.insert(camera);
+		resources.insert(self.views.clone());
+		Ok(())
+	}
+
+	/// Creates a target for each render texture camera in the scene and moves its camera along.
+	/// Targets whose camera is gone keep their last image while materials still sample them.
+	fn update_render_targets(&self, resources: &mut ResourceMap) -> Result<()> {
+		let Some(targets) = resources.get_mut::<RenderTargets>() else {
+			return Ok(());
+		};
+		let mut with_camera = Vec::new();
+		for entity in self.world.entities() {
+			let Some(component) = self.world.get_component::<RenderTextureCamera>(entity).map(|component| component.clone()) else {
+				continue;
+			};
+			let handle = targets.find_or_create(component.descriptor())?;
+			let transform = scene::global_transform(&self.world, entity);
+			targets.set_camera(handle, Some(Camera::new(transform.translation, transform.rotation, component.projection)))?;
+			with_camera.push(handle);
+		}
+		let without_camera = targets.handles().filter(|handle| !with_camera.contains(handle)).collect::<Vec<_>>();
+		for handle in without_camera {
+			targets.set_camera(handle, None)?;
+		}
+		Ok(())
+	}
+
+	pub fn new_project(&mut self, directory: &Path, name: &str) -> Result<()> {
+		let project = Project::create(directory, name)?;
+		self.set_project(project)
+	}
+
+	pub fn open_project(&mut self, path: &Path) -> Result<()> {
+		let project = Project::open(path)?;
+		self.set_project(project)
+	}
+
+	/// Leaves play mode and replaces the scene and asset browser with the project's
+	fn set_project(&mut self, project: Project) -> Result<()> {
+		self.play.stop(&mut self.world);
+		self.world = new_world();
+		self.hierarchy = HierarchyPanel::default();
+		self.assets = AssetBrowser::new(project.asset_root());
+		self.last_asset_scan = None;
+		self.recent_projects.add(project.root());
+		self.recent_projects.save()?;
+		log::info!("Opened project '{}' at {}", project.name, project.root().display());
+		self.project = Some(project);
+		Ok(())
+	}
+
+	/// Loads the recently opened projects and reopens the newest one that still exists
+	fn open_recent_project(&mut self) -> Result<()> {
+		let Some(path) = RecentProjects::default_path() else {
+			return Ok(());
+		};
+		self.recent_projects = RecentProjects::load(path)?;
+		self.recent_projects.remove_missing();
+		match self.recent_projects.projects.first().cloned() {
+			Some(project) => self.open_project(&project),
+			None => Ok(()),
+		}
+	}
+
+	pub fn recent_project_paths(&self) -> &[PathBuf] {
+		&self.recent_projects.projects
+	}
+
+	pub fn play_state(&self) -> PlayState {
+		self.play.state()
+	}
+
+	/// The project name and whether the game is running, so they're visible from the taskbar
+	pub fn window_title(&self) -> String {
+		let mut title = match self.project.as_ref() {
+			Some(project) => format!("{} - Elder Editor", project.name),
+			None => "Elder Editor".to_string(),
+		};
+		match self.play.state() {
+			PlayState::Editing => {},
+			PlayState::Playing => title.push_str(" (Playing)"),
+			PlayState::Paused => title.push_str(" (Paused)"),
+		}
+		title
+	}
+
+	fn update_window_title(&mut self, resources: &mut ResourceMap) {
+		let title = self.window_title();
+		if title == self.window_title {
+			return;
+		}
+		if let Some(commands) = resources.get_mut::<WindowCommands>() {
+			commands.set_title(title.clone());
+		}
+		self.window_title = title;
+	}
+
+	fn scan_assets(&mut self, now: Instant) {
+		if self.last_asset_scan.is_some_and(|last_scan| now - last_scan < ASSET_SCAN_INTERVAL) {
+			return;
+		}
+		self.last_asset_scan = Some(now);
+		match self.assets.scan() {
+			Ok(changes) => changes.iter().for_each(|change| log::info!("Asset changed: {:?}", change)),
+			Err(error) => log::warn!("Failed to scan the assets directory: {}", error),
+		}
+	}
+
+	fn handle_file_drop(&mut self, event: &FileDropEvent) {
+		match event {
+			FileDropEvent::Hovered(path) => log::info!("Hovering file: {}", path.display()),
+			FileDropEvent::Dropped(path) => match AssetKind::from_path(path) {
+				Some(kind) => {
+					log::info!("Importing {}: {}", kind.label(), path.display());
+					if let Err(error) = self.assets.import_file(path) {
+						log::warn!("Failed to import {}: {}", path.display(), error);
+					}
+				},
+				None => log::warn!("Unsupported file type dropped: {}", path.display()),
+			},
+			FileDropEvent::HoverCancelled => {},
+		}
+	}
+}
+
+impl State<ResourceMap> for Editor {
+	fn label(&self) -> Label {
+		Label::from_static("Elder Game Engine - Editor")
 	}
 
-	fn start(&mut self, _resources: &mut ()) -> StateResult<()> {
+	fn start(&mut self, resources: &mut ResourceMap) -> StateResult<()> {
+		resources.insert(RenderDebugMode::default());
+		resources.insert(RenderTargets::default());
+		if let Err(error) = self.open_recent_project() {
+			log::warn!("Failed to open the most recent project: {}", error);
+		}
 		Ok(())
 	}
 
-	fn stop(&mut self, _resources: &mut ()) -> StateResult<()> {
+	fn stop(&mut self, _resources: &mut ResourceMap) -> StateResult<()> {
+		self.play.stop(&mut self.world);
 		Ok(())
 	}
 
-	fn pause(&mut self, _resources: &mut ()) -> StateResult<()> {
+	fn pause(&mut self, _resources: &mut ResourceMap) -> StateResult<()> {
 		Ok(())
 	}
 
-	fn resume(&mut self, _resources: &mut ()) -> StateResult<()> {
+	fn resume(&mut self, _resources: &mut ResourceMap) -> StateResult<()> {
 		Ok(())
 	}
 
-	fn update(&mut self, _resources: &mut ()) -> StateResult<Transition<()>> {
+	fn update(&mut self, resources: &mut ResourceMap) -> StateResult<Transition<ResourceMap>> {
+		if let Some(events) = resources.get::<Events<FileDropEvent>>() {
+			events.iter().for_each(|event| self.handle_file_drop(event));
+		}
+		self.scan_assets(Instant::now());
+		self.update_camera(resources)?;
+		let delta_time = resources.get::<Time>().map_or(0.0, |time| time.delta(GAMEPLAY_CHANNEL));
+		self.play.update(&mut self.world, delta_time)?;
+		scene::update_global_transforms(&mut self.world)?;
+		self.update_render_targets(resources)?;
+		self.update_window_title(resources);
 		Ok(Transition::None)
 	}
 }
@@ -0,0 +1,199 @@
+use elder::{
+	graphics::DebugDraw,
+	math::{Camera, Color, Projection, Real, Vector3, Viewport},
+};
+
+/// How far in front of the camera the gizmo is drawn, which only needs to be past the near plane
+const GIZMO_DEPTH: Real = 1.0;
+
+/// A side of the scene the viewport camera can look from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ViewDirection {
+	Right,
+	Left,
+	Top,
+	Bottom,
+	Front,
+	Back,
+}
+
+impl ViewDirection {
+	pub const ALL: [Self; 6] = [Self::Right, Self::Left, Self::Top, Self::Bottom, Self::Front, Self::Back];
+
+	/// The direction from the focus point toward the camera
+	pub fn axis(&self) -> Vector3 {
+		match self {
+			Self::Right => Vector3::x_axis(),
+			Self::Left => Vector3::x_axis().inverse(),
+			Self::Top => Vector3::y_axis(),
+			Self::Bottom => Vector3::y_axis().inverse(),
+			Self::Front => Vector3::z_axis(),
+			Self::Back => Vector3::z_axis().inverse(),
+		}
+	}
+
+	pub fn color(&self) -> Color {
+		match self {
+			Self::Right | Self::Left => Color::RED,
+			Self::Top | Self::Bottom => Color::GREEN,
+			Self::Front | Self::Back => Color::BLUE,
+		}
+	}
+
+	/// Whether this is the positive end of its axis, which the gizmo draws a line to
+	pub fn is_positive(&self) -> bool {
+		matches!(self, Self::Right | Self::Top | Self::Front)
+	}
+}
+
+/// A handle at one end of the gizmo's axes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GizmoHandle {
+	pub direction: ViewDirection,
+
+	/// Where the handle is in the viewport's units
+	pub position: (Real, Real),
+
+	/// How far the handle points away from the camera, so nearer handles have smaller depths
+	pub depth: Real,
+}
+
+/// The orientation axes in the corner of the viewport, whose ends are clicked to look from a side
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AxisGizmo {
+	/// The length of each axis in pixels
+	pub size: Real,
+
+	/// The gap between the gizmo and the top right corner of the viewport, in pixels
+	pub margin: Real,
+
+	/// How close a click has to be to a handle to pick it, in pixels
+	pub handle_radius: Real,
+}
+
+impl Default for AxisGizmo {
+	fn default() -> Self {
+		Self {
+			size: 40.0,
+			margin: 20.0,
+			handle_radius: 8.0,
+		}
+	}
+}
+
+impl AxisGizmo {
+	pub fn center(&self, viewport: &Viewport) -> (Real, Real) {
+		let rect = viewport.rect;
+		let inset = self.size + self.margin;
+		(rect.x + rect.width - inset, rect.y + inset)
+	}
+
+	/// The handles as the camera sees them, nearest to the camera first
+	pub fn handles(&self, camera: &Camera, viewport: &Viewport) -> Vec<GizmoHandle> {
+		let (x, y) = self.center(viewport);
+		let mut handles = ViewDirection::ALL
+			.into_iter()
+			.map(|direction| {
+				let axis = direction.axis();
+				GizmoHandle {
+					direction,
+					position: (x + axis.dot(&camera.right()) * self.size, y - axis.dot(&camera.up()) * self.size),
+					depth: axis.dot(&camera.forward()),
+				}
+			})
+			.collect::<Vec<_>>();
+		handles.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+		handles
+	}
+
+	/// The side to look from when clicking at the cursor, preferring handles nearest the camera
+	pub fn pick(&self, camera: &Camera, viewport: &Viewport, cursor: (Real, Real)) -> Option<ViewDirection> {
+		self.handles(camera, viewport)
+			.into_iter()
+			.find(|handle| {
+				let (dx, dy) = (handle.position.0 - cursor.0, handle.position.1 - cursor.1);
+				dx * dx + dy * dy <= self.handle_radius * self.handle_radius
+			})
+			.map(|handle| handle.direction)
+	}
+
+	/// Queues the gizmo just in front of the camera, in its corner of the viewport
+	pub fn draw(&self, draw: &mut DebugDraw, camera: &Camera, viewport: &Viewport) {
+		let ray = camera.screen_to_ray(self.center(viewport), viewport);
+		let center = ray.point_at(GIZMO_DEPTH / ray.direction.dot(&camera.forward()));
+		let visible_height = match camera.projection {
+			Projection::Perspective { fov_y, .. } => 2.0 * GIZMO_DEPTH * (fov_y * 0.5).tan(),
+			Projection::Orthographic { height, .. } => height,
+		};
+		let units_per_pixel = visible_height / viewport.rect.height.max(1.0);
+		let depth_test = std::mem::replace(&mut draw.depth_test, false);
+		for direction in ViewDirection::ALL {
+			let end = center + direction.axis() * (self.size * units_per_pixel);
+			let color = if direction.is_positive() {
+				direction.color()
+			} else {
+				direction.color().with_alpha(0.5)
+			};
+			if direction.is_positive() {
+				draw.line(center, end, color);
+			}
+			draw.circle(end, camera.forward(), self.handle_radius * units_per_pixel, color);
+		}
+		draw.depth_test = depth_test;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::viewport::OrbitCamera;
+	use elder::math::Rect;
+
+	fn viewport() -> Viewport {
+		Viewport::new(Rect::new(0.0, 0.0, 800.0, 600.0))
+	}
+
+	#[test]
+	pub fn handles_follow_the_camera() {
+		let gizmo = AxisGizmo::default();
+		let camera = OrbitCamera {
+			pitch: 0.0,
+			..OrbitCamera::default()
+		}
+		.camera();
+		let (x, y) = gizmo.center(&viewport());
+		assert_eq!((x, y), (740.0, 60.0));
+
+		let handles = gizmo.handles(&camera, &viewport());
+		assert_eq!(handles[0].direction, ViewDirection::Front);
+		let position = |direction| handles.iter().find(|handle| handle.direction == direction).unwrap().position;
+		assert!((position(ViewDirection::Right).0 - (x + 40.0)).abs() < 1e-4);
+		assert!((position(ViewDirection::Top).1 - (y - 40.0)).abs() < 1e-4);
+
+		// The front and back handles overlap in the middle, and the front one is nearer
+		assert_eq!(gizmo.pick(&camera, &viewport(), (x + 2.0, y)), Some(ViewDirection::Front));
+		assert_eq!(gizmo.pick(&camera, &viewport(), (x + 41.0, y - 3.0)), Some(ViewDirection::Right));
+		assert_eq!(gizmo.pick(&camera, &viewport(), (x + 20.0, y + 20.0)), None);
+	}
+
+	#[test]
+	pub fn drawn_over_the_scene() {
+		let mut draw = DebugDraw::default();
+		let camera = OrbitCamera::default().camera();
+		AxisGizmo::default().draw(&mut draw, &camera, &viewport());
+		assert!(draw.depth_test);
+		draw.build();
+		assert!(draw.draw_calls().iter().all(|call| !call.depth_test));
+
+		// The positive x axis ends where its handle is on screen
+		let gizmo = AxisGizmo::default();
+		let handle = gizmo
+			.handles(&camera, &viewport())
+			.into_iter()
+			.find(|handle| handle.direction == ViewDirection::Right)
+			.unwrap();
+		let [x, y, z] = draw.vertices()[1].position;
+		let (screen_x, screen_y) = camera.world_to_screen(Vector3::new(x, y, z), &viewport()).unwrap();
+		assert!((screen_x - handle.position.0).abs() < 1.0 && (screen_y - handle.position.1).abs() < 1.0);
+	}
+}
@@ -0,0 +1,366 @@
+use elder::{
+	ecs::{
+		error::Result,
+		name::{Name, Tags},
+		world::{Entity, World},
+	},
+	graphics::RenderTextureCamera,
+	math::{Quaternion, Real, Transform, Vector3},
+	scene::{children, despawn_recursive, display_name, global_transform, is_ancestor, roots, set_parent},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// The kind copied entities are tagged with on the clipboard
+pub const ENTITY_CLIPBOARD_KIND: &str = "entities";
+
+/// How clicking a row changes the selection
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SelectionMode {
+	/// Selects only the clicked entity
+	Replace,
+
+	/// Adds or removes the clicked entity, as with ctrl+click
+	Toggle,
+
+	/// Selects every visible row between the last clicked entity and this one, as with shift+click
+	Range,
+}
+
+/// A component that can be added to an entity from the hierarchy's context menu
+pub struct ComponentEntry {
+	pub name: &'static str,
+	pub add: fn(&mut World, Entity) -> Result<()>,
+}
+
+/// Components offered by the hierarchy's Add Component context menu
+pub const ADDABLE_COMPONENTS: &[ComponentEntry] = &[
+	ComponentEntry {
+		name: "Name",
+		add: |world, entity| world.add_component(entity, Name::new(format!("Entity {}", entity.index()))),
+	},
+	ComponentEntry {
+		name: "Tags",
+		add: |world, entity| world.add_component(entity, Tags::default()),
+	},
+	ComponentEntry {
+		name: "Transform",
+		add: |world, entity| world.add_component(entity, Transform::identity()),
+	},
+	ComponentEntry {
+		name: "Render Texture Camera",
+		add: |world, entity| world.add_component(entity, RenderTextureCamera::new(format!("Entity {} Target", entity.index()), 512, 512)),
+	},
+];
+
+/// A transform as plain arrays, so it can be written to the clipboard
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CopiedTransform {
+	pub translation: [Real; 3],
+	pub rotation: [Real; 4],
+	pub scale: [Real; 3],
+}
+
+impl From<Transform> for CopiedTransform {
+	fn from(transform: Transform) -> Self {
+		let Transform { translation, rotation, scale } = transform;
+		Self {
+			translation: [translation.x(), translation.y(), translation.z()],
+			rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+			scale: [scale.x(), scale.y(), scale.z()],
+		}
+	}
+}
+
+impl From<CopiedTransform> for Transform {
+	fn from(copied: CopiedTransform) -> Self {
+		let ([x, y, z], [i, j, k, w], [width, height, depth]) = (copied.translation, copied.rotation, copied.scale);
+		Self {
+			translation: Vector3::new(x, y, z),
+			rotation: Quaternion::new(i, j, k, w),
+			scale: Vector3::new(width, height, depth),
+		}
+	}
+}
+
+/// An entity's name, tags, and transform along with its descendants, as copied to the clipboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CopiedEntity {
+	pub name: Option<String>,
+	pub tags: Option<Vec<String>>,
+
+	/// Relative to the world for a copied entity, and to the parent for its descendants
+	pub transform: Option<CopiedTransform>,
+	pub children: Vec<CopiedEntity>,
+}
+
+impl CopiedEntity {
+	fn new(world: &World, entity: Entity, transform: Option<Transform>) -> Self {
+		Self {
+			name: world.get_component::<Name>(entity).map(|name| name.to_string()),
+			tags: world.get_component::<Tags>(entity).map(|tags| tags.0.iter().cloned().collect()),
+			transform: transform.map(CopiedTransform::from),
+			children: children(world, entity)
+				.into_iter()
+				.map(|child| Self::new(world, child, world.get_component::<Transform>(child).map(|transform| *transform)))
+				.collect(),
+		}
+	}
+
+	/// Spawns the entity and its descendants, returning the new entity
+	fn spawn(&self, world: &mut World, parent: Option<Entity>) -> Result<Entity> {
+		let (entity, _id) = world.create_entity_with_id()?;
+		if let Some(name) = self.name.as_ref() {
+			world.add_component(entity, Name::new(name.clone()))?;
+		}
+		if let Some(tags) = self.tags.as_ref() {
+			world.add_component(entity, Tags::new(tags))?;
+		}
+		if let Some(transform) = self.transform {
+			world.add_component(entity, Transform::from(transform))?;
+		}
+		for child in self.children.iter() {
+			child.spawn(world, Some(entity))?;
+		}
+		if let Some(parent) = parent {
+			set_parent(world, entity, Some(parent))?;
+			// Keep the copied local transform instead of the world one `set_parent` preserves
+			if let Some(transform) = self.transform {
+				world.add_component(entity, Transform::from(transform))?;
+			}
+		}
+		Ok(entity)
+	}
+}
+
+/// A visible line in the hierarchy tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HierarchyRow {
+	pub entity: Entity,
+	pub depth: usize,
+	pub name: String,
+	pub has_children: bool,
+	pub is_expanded: bool,
+	pub is_selected: bool,
+}
+
+/// The tree of entities in the scene, built from their `Parent` and `Children` components
+#[derive(Default, Debug, Clone)]
+pub struct HierarchyPanel {
+	expanded: HashSet<Entity>,
+	selection: Vec<Entity>,
+	anchor: Option<Entity>,
+}
+
+impl HierarchyPanel {
+	/// The rows to draw, skipping the descendants of collapsed entities
+	pub fn rows(&self, world: &World) -> Vec<HierarchyRow> {
+		let mut rows = Vec::new();
+		let mut stack = roots(world).into_iter().rev().map(|root| (root, 0)).collect::<Vec<_>>();
+		while let Some((entity, depth)) = stack.pop() {
+			let children = children(world, entity);
+			let is_expanded = self.is_expanded(entity);
+			if is_expanded {
+				stack.extend(children.iter().rev().map(|child| (*child, depth + 1)));
+			}
+			rows.push(HierarchyRow {
+				entity,
+				depth,
+				name: display_name(world, entity),
+				has_children: !children.is_empty(),
+				is_expanded,
+				is_selected: self.is_selected(entity),
+			});
+		}
+		rows
+	}
+
+	pub fn is_expanded(&self, entity: Entity) -> bool {
+		self.expanded.contains(&entity)
+	}
+
+	pub fn toggle_expanded(&mut self, entity: Entity) {
+		if !self.expanded.remove(&entity) {
+			self.expanded.insert(entity);
+		}
+	}
+
+	/// Selected entities in the order they were selected
+	pub fn selection(&self) -> &[Entity] {
+		&self.selection
+	}
+
+	pub fn is_selected(&self, entity: Entity) -> bool {
+		self.selection.contains(&entity)
+	}
+
+	pub fn clear_selection(&mut self) {
+		self.selection.clear();
+		self.anchor = None;
+	}
+
+	pub fn select(&mut self, world: &World, entity: Entity, mode: SelectionMode) {
+		match mode {
+			SelectionMode::Replace => self.selection = vec![entity],
+			SelectionMode::Toggle => {
+				if self.is_selected(entity) {
+					self.selection.retain(|selected| *selected != entity);
+				} else {
+					self.selection.push(entity);
+				}
+			},
+			SelectionMode::Range => {
+				let rows = self.rows(world).into_iter().map(|row| row.entity).collect::<Vec<_>>();
+				let position = |entity| rows.iter().position(|row| *row == entity);
+				let (Some(start), Some(end)) = (self.anchor.and_then(position), position(entity)) else {
+					return self.select(world, entity, SelectionMode::Replace);
+				};
+				self.selection = rows[start.min(end)..=start.max(end)].to_vec();
+				return;
+			},
+		}
+		self.anchor = Some(entity);
+	}
+
+	/// Creates an empty entity with a persistent id, optionally under a parent, and selects it
+	pub fn create_entity(&mut self, world: &mut World, parent: Option<Entity>) -> Result<Entity> {
+		let (entity, _id) = world.create_entity_with_id()?;
+		world.add_component(entity, Name::new(format!("Entity {}", entity.index())))?;
+		world.add_component(entity, Transform::identity())?;
+		if let Some(parent) = parent {
+			set_parent(world, entity, Some(parent))?;
+			self.expanded.insert(parent);
+		}
+		self.select(world, entity, SelectionMode::Replace);
+		Ok(entity)
+	}
+
+	/// Removes the selected entities along with their descendants
+	pub fn delete_selected(&mut self, world: &mut World) -> Result<()> {
+		for entity in std::mem::take(&mut self.selection) {
+			if world.entity_exists(entity) {
+				despawn_recursive(world, entity)?;
+			}
+		}
+		self.anchor = None;
+		self.expanded.retain(|entity| world.entity_exists(*entity));
+		Ok(())
+	}
+
+	pub fn rename(&mut self, world: &mut World, entity: Entity, name: &str) -> Result<()> {
+		world.add_component(entity, Name::new(name))
+	}
+
+	/// Moves the selected entities under a new parent, or to the root, keeping world transforms.
+	/// Selected entities whose ancestor is also selected move along with that ancestor.
+	pub fn reparent_selected(&mut self, world: &mut World, parent: Option<Entity>) -> Result<()> {
+		let moved = self
+			.selection
+			.iter()
+			.copied()
+			.filter(|entity| !self.selection.iter().any(|other| is_ancestor(world, *other, *entity)))
+			.collect::<Vec<_>>();
+		for entity in moved {
+			set_parent(world, entity, parent)?;
+		}
+		if let Some(parent) = parent {
+			self.expanded.insert(parent);
+		}
+		Ok(())
+	}
+
+	/// The selected entities and their descendants, leaving out those with a selected ancestor
+	pub fn copy_selected(&self, world: &World) -> Vec<CopiedEntity> {
+		self.selection
+			.iter()
+			.copied()
+			.filter(|entity| world.entity_exists(*entity) && !self.selection.iter().any(|other| is_ancestor(world, *other, *entity)))
+			.map(|entity| {
+				let transform = world.get_component::<Transform>(entity).map(|_| global_transform(world, entity));
+				CopiedEntity::new(world, entity, transform)
+			})
+			.collect()
+	}
+
+	/// Spawns copied entities at the root or under a parent, where they were in the world
+	pub fn paste(&mut self, world: &mut World, entities: &[CopiedEntity], parent: Option<Entity>) -> Result<Vec<Entity>> {
+		let mut pasted = Vec::with_capacity(entities.len());
+		for copied in entities {
+			let entity = copied.spawn(world, None)?;
+			if parent.is_some() {
+				set_parent(world, entity, parent)?;
+			}
+			pasted.push(entity);
+		}
+		if let Some(parent) = parent {
+			self.expanded.insert(parent);
+		}
+		self.anchor = pasted.last().copied();
+		self.selection = pasted.clone();
+		Ok(pasted)
+	}
+
+	/// Adds a component listed in `ADDABLE_COMPONENTS` by name
+	pub fn add_component(&mut self, world: &mut World, entity: Entity, component: &str) -> Result<()> {
+		match ADDABLE_COMPONENTS.iter().find(|entry| entry.name == component) {
+			Some(entry) => (entry.add)(world, entity),
+			None => {
+				log::warn!("Unknown component: {}", component);
+				Ok(())
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use elder::{
+		math::Vector3,
+		scene::{global_transform, local_transform},
+	};
+
+	#[test]
+	pub fn tree_rows() -> Result<()> {
+		let mut world = World::new();
+		let mut panel = HierarchyPanel::default();
+		let parent = panel.create_entity(&mut world, None)?;
+		let child = panel.create_entity(&mut world, Some(parent))?;
+		let sibling = panel.create_entity(&mut world, None)?;
+		panel.rename(&mut world, child, "Wheel")?;
+
+		let rows = panel.rows(&world);
+		let depths = rows.iter().map(|row| (row.entity, row.depth)).collect::<Vec<_>>();
+		assert_eq!(depths, [(parent, 0), (child, 1), (sibling, 0)]);
+		assert_eq!(rows[1].name, "Wheel");
+		assert!(rows[2].is_selected);
+
+		panel.toggle_expanded(parent);
+		assert_eq!(panel.rows(&world).len(), 2);
+		Ok(())
+	}
+
+	#[test]
+	pub fn multi_select_and_reparent() -> Result<()> {
+		let mut world = World::new();
+		let mut panel = HierarchyPanel::default();
+		let entities = (0..4).map(|_| panel.create_entity(&mut world, None)).collect::<Result<Vec<_>>>()?;
+		world.get_component_mut::<Transform>(entities[0]).unwrap().translation = Vector3::new(2.0, 0.0, 0.0);
+
+		panel.select(&world, entities[1], SelectionMode::Replace);
+		panel.select(&world, entities[3], SelectionMode::Range);
+		assert_eq!(panel.selection(), &entities[1..]);
+		panel.select(&world, entities[2], SelectionMode::Toggle);
+		assert_eq!(panel.selection(), [entities[1], entities[3]]);
+
+		panel.reparent_selected(&mut world, Some(entities[0]))?;
+		assert_eq!(children(&world, entities[0]), [entities[1], entities[3]]);
+		assert_eq!(local_transform(&world, entities[1]).translation, Vector3::new(-2.0, 0.0, 0.0));
+		assert_eq!(global_transform(&world, entities[1]).translation, Vector3::new(0.0, 0.0, 0.0));
+
+		panel.select(&world, entities[0], SelectionMode::Replace);
+		panel.delete_selected(&mut world)?;
+		assert_eq!(world.entities(), [entities[2]]);
+		Ok(())
+	}
+}
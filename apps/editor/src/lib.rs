@@ -0,0 +1,10 @@
+mod assets;
+mod editor;
+mod gizmo;
+mod hierarchy;
+mod menu;
+mod play;
+mod project;
+mod viewport;
+
+pub use self::{assets::*, editor::*, gizmo::*, hierarchy::*, menu::*, play::*, project::*, viewport::*};
@@ -1,10 +1,15 @@
-mod editor;
-
 use editor::Editor;
-use elder::app::{run, AppConfig};
+use elder::app::{AppConfig, FocusThrottle, run};
 
 fn main() -> Result<(), elder::app::Error> {
 	std::env::set_var("RUST_LOG", "info");
 	env_logger::init();
-	run(AppConfig::default(), Editor::default())
+	run(
+		AppConfig {
+			settings_path: Some("settings.toml".to_string()),
+			focus_throttle: FocusThrottle::BACKGROUND,
+			..Default::default()
+		},
+		Editor::default(),
+	)
 }
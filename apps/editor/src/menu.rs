@@ -0,0 +1,351 @@
+use crate::{
+	editor::Editor,
+	gizmo::ViewDirection,
+	hierarchy::{ADDABLE_COMPONENTS, SelectionMode},
+	play::PlayState,
+};
+use elder::{
+	ecs::{resource::ResourceMap, world::Entity},
+	graphics::{MaterialHandle, RenderDebugMode, ViewLayout},
+};
+use std::path::PathBuf;
+
+/// Something the user can trigger from the editor's menus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorAction {
+	SetRenderDebugMode(RenderDebugMode),
+	Play,
+	Pause,
+	Stop,
+	Step,
+
+	/// Creates a project in an empty directory chosen by the user and opens it
+	NewProject {
+		directory: PathBuf,
+		name: String,
+	},
+
+	/// Opens a project from its directory or project file
+	OpenProject(PathBuf),
+
+	/// Creates an empty entity at the root of the scene or under a parent
+	CreateEntity {
+		parent: Option<Entity>,
+	},
+
+	DeleteSelected,
+
+	/// Copies the selected entities and their descendants to the clipboard
+	CopySelected,
+
+	/// Spawns the entities on the clipboard at the root of the scene or under a parent
+	Paste {
+		parent: Option<Entity>,
+	},
+
+	RenameEntity {
+		entity: Entity,
+		name: String,
+	},
+
+	/// Moves the selected entities under a new parent, as when dropping them onto a row
+	ReparentSelected(Option<Entity>),
+
+	/// Adds one of the hierarchy's addable components by name
+	AddComponent {
+		entity: Entity,
+		component: String,
+	},
+
+	ToggleExpanded(Entity),
+
+	Select {
+		entity: Entity,
+		mode: SelectionMode,
+	},
+
+	/// Frames the selected entities in the viewport
+	FocusSelection,
+
+	/// Saves the viewport camera to the project under a number key
+	SaveCameraBookmark(u8),
+
+	RecallCameraBookmark(u8),
+
+	/// Turns the viewport camera to look from one side, as when clicking the axis gizmo
+	SnapView(ViewDirection),
+
+	/// Shows the perspective view alone or in a quad view with the top, front, and side views
+	SetViewLayout(ViewLayout),
+
+	/// Makes a material sample a render texture camera's target, chosen by the target's name
+	AssignRenderTarget {
+		material: MaterialHandle,
+		target: String,
+	},
+
+	UnassignRenderTarget(MaterialHandle),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MenuItem {
+	pub label: String,
+	pub action: EditorAction,
+	pub is_checked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Menu {
+	pub label: String,
+	pub items: Vec<MenuItem>,
+}
+
+/// The menus shown along the top of the editor, reflecting the current state of `resources`
+pub fn menu_bar(editor: &Editor, resources: &ResourceMap) -> Vec<Menu> {
+	vec![file_menu(editor), view_menu(editor, resources), play_menu(editor.play_state())]
+}
+
+/// Lists recent projects to reopen. New and Open Project prompt for a path,
+/// so the interface sends those actions itself once the user has picked one.
+fn file_menu(editor: &Editor) -> Menu {
+	let current = editor.project.as_ref().map(|project| project.root());
+	let items = editor
+		.recent_project_paths()
+		.iter()
+		.map(|path| MenuItem {
+			label: path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string(),
+			action: EditorAction::OpenProject(path.clone()),
+			is_checked: current == Some(path.as_path()),
+		})
+		.collect();
+	Menu {
+		label: "File".to_string(),
+		items,
+	}
+}
+
+fn view_menu(editor: &Editor, resources: &ResourceMap) -> Menu {
+	let current = resources.get::<RenderDebugMode>().copied().unwrap_or_default();
+	let mut items = RenderDebugMode::ALL
+		.into_iter()
+		.map(|mode| MenuItem {
+			label: mode.label().to_string(),
+			action: EditorAction::SetRenderDebugMode(mode),
+			is_checked: mode == current,
+		})
+		.collect::<Vec<_>>();
+	items.extend([ViewLayout::Single, ViewLayout::Quad].map(|layout| MenuItem {
+		label: format!("{layout:?} View"),
+		action: EditorAction::SetViewLayout(layout),
+		is_checked: editor.view_layout() == layout,
+	}));
+	items.push(MenuItem {
+		label: "Focus Selection".to_string(),
+		action: EditorAction::FocusSelection,
+		is_checked: false,
+	});
+	Menu {
+		label: "View".to_string(),
+		items,
+	}
+}
+
+fn play_menu(state: PlayState) -> Menu {
+	let item = |label: &str, action, is_checked| MenuItem {
+		label: label.to_string(),
+		action,
+		is_checked,
+	};
+	Menu {
+		label: "Play".to_string(),
+		items: vec![
+			item("Play", EditorAction::Play, state == PlayState::Playing),
+			item("Pause", EditorAction::Pause, state == PlayState::Paused),
+			item("Stop", EditorAction::Stop, false),
+			item("Step", EditorAction::Step, false),
+		],
+	}
+}
+
+/// The menu shown when right clicking an entity in the hierarchy
+pub fn entity_context_menu(entity: Entity) -> Menu {
+	let item = |label: &str, action| MenuItem {
+		label: label.to_string(),
+		action,
+		is_checked: false,
+	};
+	let mut items = vec![
+		item("Create Child", EditorAction::CreateEntity { parent: Some(entity) }),
+		item("Delete", EditorAction::DeleteSelected),
+		item("Copy", EditorAction::CopySelected),
+		item("Paste as Child", EditorAction::Paste { parent: Some(entity) }),
+		item("Unparent", EditorAction::ReparentSelected(None)),
+	];
+	items.extend(ADDABLE_COMPONENTS.iter().map(|entry| {
+		let action = EditorAction::AddComponent {
+			entity,
+			component: entry.name.to_string(),
+		};
+		item(&format!("Add Component/{}", entry.name), action)
+	}));
+	Menu {
+		label: "Entity".to_string(),
+		items,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use elder::{
+		app::Clipboard,
+		ecs::{error::Result, name::Name},
+		graphics::RenderTargets,
+		math::{Transform, Vector3},
+		scene::{children, display_name, global_transform},
+		state::State,
+	};
+
+	fn checked(editor: &Editor, resources: &ResourceMap, menu: usize) -> Option<String> {
+		let menu = menu_bar(editor, resources).remove(menu);
+		menu.items.into_iter().find(|item| item.is_checked).map(|item| item.label)
+	}
+
+	#[test]
+	pub fn debug_view_menu() -> Result<()> {
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		assert_eq!(checked(&editor, &resources, 1).as_deref(), Some("Lit"));
+
+		editor.apply_action(EditorAction::SetRenderDebugMode(RenderDebugMode::Overdraw), &mut resources)?;
+		assert_eq!(resources.get::<RenderDebugMode>(), Some(&RenderDebugMode::Overdraw));
+		assert_eq!(checked(&editor, &resources, 1).as_deref(), Some("Overdraw"));
+
+		let layout = |editor: &Editor, resources: &ResourceMap| {
+			let menu = menu_bar(editor, resources).remove(1);
+			menu.items.into_iter().filter(|item| item.is_checked).map(|item| item.label).next_back()
+		};
+		assert_eq!(layout(&editor, &resources).as_deref(), Some("Single View"));
+		editor.apply_action(EditorAction::SetViewLayout(ViewLayout::Quad), &mut resources)?;
+		assert_eq!(layout(&editor, &resources).as_deref(), Some("Quad View"));
+		assert_eq!(editor.views.visible().count(), 4);
+		Ok(())
+	}
+
+	#[test]
+	pub fn play_menu() -> Result<()> {
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		assert_eq!(checked(&editor, &resources, 2), None);
+		editor.apply_action(EditorAction::Play, &mut resources)?;
+		editor.apply_action(EditorAction::Pause, &mut resources)?;
+		assert_eq!(checked(&editor, &resources, 2).as_deref(), Some("Pause"));
+		editor.apply_action(EditorAction::Stop, &mut resources)?;
+		assert_eq!(editor.play_state(), PlayState::Editing);
+		Ok(())
+	}
+
+	#[test]
+	pub fn file_menu() -> Result<()> {
+		let name = format!("elder_editor_file_menu_{}", std::process::id());
+		let directory = std::env::temp_dir().join(&name);
+		let _ = std::fs::remove_dir_all(&directory);
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		let action = EditorAction::NewProject {
+			directory: directory.clone(),
+			name: "Demo".to_string(),
+		};
+		editor.apply_action(action, &mut resources)?;
+		assert_eq!(editor.assets.root(), directory.join("assets"));
+		assert_eq!(checked(&editor, &resources, 0), Some(name));
+		std::fs::remove_dir_all(&directory)?;
+		Ok(())
+	}
+
+	#[test]
+	pub fn render_targets() -> Result<()> {
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		resources.insert(RenderTargets::default());
+		editor.apply_action(EditorAction::CreateEntity { parent: None }, &mut resources)?;
+		let entity = editor.hierarchy.selection()[0];
+		let component = "Render Texture Camera".to_string();
+		editor.apply_action(EditorAction::AddComponent { entity, component }, &mut resources)?;
+		editor.update(&mut resources)?;
+
+		let target = format!("Entity {} Target", entity.index());
+		let action = EditorAction::AssignRenderTarget {
+			material: MaterialHandle(7),
+			target: target.clone(),
+		};
+		editor.apply_action(action, &mut resources)?;
+		let targets = resources.get_mut::<RenderTargets>().unwrap();
+		let handle = targets.find(&target).unwrap();
+		assert_eq!(targets.target_of(MaterialHandle(7)), Some(handle));
+		assert_eq!(targets.prepare(0).passes.len(), 1);
+
+		editor.apply_action(EditorAction::UnassignRenderTarget(MaterialHandle(7)), &mut resources)?;
+		assert!(resources.get_mut::<RenderTargets>().unwrap().prepare(1).passes.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	pub fn entity_context_menu() -> Result<()> {
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		editor.apply_action(EditorAction::CreateEntity { parent: None }, &mut resources)?;
+		let entity = editor.hierarchy.selection()[0];
+
+		let menu = super::entity_context_menu(entity);
+		let item = menu.items.into_iter().find(|item| item.label == "Add Component/Transform").unwrap();
+		editor.world.remove_component::<Transform>(entity)?;
+		editor.apply_action(item.action, &mut resources)?;
+		assert!(editor.world.has_component::<Transform>(entity));
+
+		editor.apply_action(EditorAction::DeleteSelected, &mut resources)?;
+		assert!(editor.world.entities().is_empty());
+		Ok(())
+	}
+
+	#[test]
+	pub fn copy_and_paste() -> Result<()> {
+		let mut editor = Editor::default();
+		let mut resources = ResourceMap::new();
+		resources.insert(Clipboard::in_process());
+		editor.apply_action(EditorAction::CreateEntity { parent: None }, &mut resources)?;
+		let parent = editor.hierarchy.selection()[0];
+		editor.world.get_component_mut::<Transform>(parent).unwrap().translation = Vector3::new(2.0, 0.0, 0.0);
+		editor.apply_action(EditorAction::CreateEntity { parent: Some(parent) }, &mut resources)?;
+		let child = editor.hierarchy.selection()[0];
+		editor.world.get_component_mut::<Transform>(child).unwrap().translation = Vector3::new(0.0, 1.0, 0.0);
+		editor.world.add_component(child, Name::new("Wheel"))?;
+
+		// Copying the parent brings its child along, through the clipboard's text
+		editor.hierarchy.select(&editor.world, parent, SelectionMode::Replace);
+		editor.apply_action(EditorAction::CopySelected, &mut resources)?;
+		assert!(resources.get_mut::<Clipboard>().unwrap().text()?.starts_with("elder-payload:entities"));
+
+		let menu = super::entity_context_menu(child);
+		let paste = menu.items.into_iter().find(|item| item.label == "Paste as Child").unwrap();
+		editor.apply_action(paste.action, &mut resources)?;
+		let [copy] = editor.hierarchy.selection() else {
+			panic!("Expected the pasted entity to be selected");
+		};
+		let copy = *copy;
+		assert_eq!(children(&editor.world, child), [copy]);
+		assert_eq!(global_transform(&editor.world, copy).translation, Vector3::new(2.0, 0.0, 0.0));
+		let [copied_child] = children(&editor.world, copy)[..] else {
+			panic!("Expected the child to be pasted along with its parent");
+		};
+		assert_eq!(display_name(&editor.world, copied_child), "Wheel");
+		assert_eq!(global_transform(&editor.world, copied_child).translation, Vector3::new(2.0, 1.0, 0.0));
+
+		// Plain text on the clipboard isn't pasted as entities
+		resources.get_mut::<Clipboard>().unwrap().set_text("hello")?;
+		editor.apply_action(EditorAction::Paste { parent: None }, &mut resources)?;
+		assert_eq!(editor.world.entities().len(), 4);
+		Ok(())
+	}
+}
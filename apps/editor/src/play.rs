@@ -0,0 +1,132 @@
+use elder::ecs::{
+	error::Result,
+	world::{World, WorldSnapshot},
+};
+
+/// A game system run every frame while the editor is playing, given the delta time in seconds
+pub type GameSystem = Box<dyn FnMut(&mut World, f32) -> Result<()>>;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlayState {
+	#[default]
+	Editing,
+	Playing,
+	Paused,
+}
+
+/// Runs the game inside the editor. Entering play mode snapshots the edited world,
+/// and stopping restores it so nothing that happened while playing leaks into the scene.
+#[derive(Default)]
+pub struct PlaySession {
+	state: PlayState,
+	snapshot: Option<WorldSnapshot>,
+	systems: Vec<GameSystem>,
+}
+
+impl PlaySession {
+	pub fn state(&self) -> PlayState {
+		self.state
+	}
+
+	pub fn is_editing(&self) -> bool {
+		self.state == PlayState::Editing
+	}
+
+	/// Adds a system that only runs while playing, such as physics or scripts
+	pub fn add_system(&mut self, system: impl FnMut(&mut World, f32) -> Result<()> + 'static) {
+		self.systems.push(Box::new(system));
+	}
+
+	/// Starts playing from the edited world, or resumes after a pause
+	pub fn play(&mut self, world: &World) -> Result<()> {
+		if self.state == PlayState::Editing {
+			self.snapshot = Some(world.snapshot()?);
+		}
+		self.state = PlayState::Playing;
+		Ok(())
+	}
+
+	pub fn pause(&mut self) {
+		if self.state == PlayState::Playing {
+			self.state = PlayState::Paused;
+		}
+	}
+
+	/// Leaves play mode, restoring the world to how it was when play started
+	pub fn stop(&mut self, world: &mut World) {
+		if let Some(snapshot) = self.snapshot.take() {
+			world.restore(&snapshot);
+		}
+		self.state = PlayState::Editing;
+	}
+
+	/// Advances a paused game by a single frame
+	pub fn step(&mut self, world: &mut World, delta_time: f32) -> Result<()> {
+		if self.state == PlayState::Paused {
+			self.run_systems(world, delta_time)?;
+		}
+		Ok(())
+	}
+
+	pub fn update(&mut self, world: &mut World, delta_time: f32) -> Result<()> {
+		if self.state == PlayState::Playing {
+			self.run_systems(world, delta_time)?;
+		}
+		Ok(())
+	}
+
+	/// Runs each system, then applies the commands they queued
+	fn run_systems(&mut self, world: &mut World, delta_time: f32) -> Result<()> {
+		self.systems.iter_mut().try_for_each(|system| system(world, delta_time))?;
+		world.apply_commands()?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	struct Height(f32);
+
+	fn falling_world() -> (World, PlaySession) {
+		let mut world = World::new();
+		world.register_cloneable_component::<Height>();
+		let mut session = PlaySession::default();
+		session.add_system(|world, delta_time| {
+			if let Some(mut heights) = world.get_component_vec_mut::<Height>() {
+				heights.components_mut().iter_mut().for_each(|height| height.0 -= delta_time);
+			}
+			Ok(())
+		});
+		(world, session)
+	}
+
+	#[test]
+	pub fn play_and_stop() -> Result<()> {
+		let (mut world, mut session) = falling_world();
+		let entity = world.create_entity();
+		world.add_component(entity, Height(10.0))?;
+		let height = |world: &World| world.get_component::<Height>(entity).map(|height| height.0);
+
+		session.update(&mut world, 1.0)?;
+		assert_eq!(height(&world), Some(10.0));
+
+		session.play(&world)?;
+		session.update(&mut world, 1.0)?;
+		session.pause();
+		session.update(&mut world, 1.0)?;
+		session.step(&mut world, 0.5)?;
+		assert_eq!(height(&world), Some(8.5));
+
+		session.play(&world)?;
+		session.update(&mut world, 1.0)?;
+		assert_eq!(height(&world), Some(7.5));
+
+		session.stop(&mut world);
+		assert_eq!(session.state(), PlayState::Editing);
+		assert_eq!(height(&world), Some(10.0));
+		Ok(())
+	}
+}
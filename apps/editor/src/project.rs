@@ -0,0 +1,288 @@
+use crate::viewport::CameraBookmark;
+use serde::{Deserialize, Serialize};
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to create the project directory at path: {1}")]
+	CreateProjectDirectory(#[source] io::Error, String),
+
+	#[error("Failed to parse the project file at path: {1}")]
+	ParseProject(#[source] toml::de::Error, String),
+
+	#[error("Failed to parse the recent projects file at path: {1}")]
+	ParseRecentProjects(#[source] toml::de::Error, String),
+
+	#[error("A project already exists at path: {0}")]
+	ProjectExists(String),
+
+	#[error("Failed to read the project file at path: {1}")]
+	ReadProject(#[source] io::Error, String),
+
+	#[error("Failed to read the recent projects file at path: {1}")]
+	ReadRecentProjects(#[source] io::Error, String),
+
+	#[error("Failed to serialize the project!")]
+	SerializeProject(#[source] toml::ser::Error),
+
+	#[error("Failed to serialize the recent projects!")]
+	SerializeRecentProjects(#[source] toml::ser::Error),
+
+	#[error("Failed to write the project file at path: {1}")]
+	WriteProject(#[source] io::Error, String),
+
+	#[error("Failed to write the recent projects file at path: {1}")]
+	WriteRecentProjects(#[source] io::Error, String),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The file at the root of every project directory
+pub const PROJECT_FILE_NAME: &str = "elder.toml";
+
+/// The number of projects listed in the editor's Open Recent menu
+pub const MAXIMUM_RECENT_PROJECTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildSettings {
+	/// The name of the built executable
+	pub executable_name: String,
+
+	pub release: bool,
+
+	/// Cargo features enabled when building the game
+	pub features: Vec<String>,
+}
+
+impl Default for BuildSettings {
+	fn default() -> Self {
+		Self {
+			executable_name: "game".to_string(),
+			release: true,
+			features: Vec::new(),
+		}
+	}
+}
+
+/// A game being edited, described by an `elder.toml` file at the root of its directory.
+/// Paths in the file are relative to that directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Project {
+	pub name: String,
+
+	/// Directories scanned for assets, the first of which receives imported files
+	pub asset_roots: Vec<PathBuf>,
+
+	/// The scene opened when the project is opened
+	pub default_scene: Option<PathBuf>,
+
+	pub build: BuildSettings,
+
+	/// Viewport camera positions saved with control and a number key.
+	/// Left out of the file when empty, since TOML can't write a plain value after the build table.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub camera_bookmarks: Vec<CameraBookmark>,
+
+	#[serde(skip)]
+	root: PathBuf,
+}
+
+impl Default for Project {
+	fn default() -> Self {
+		Self {
+			name: "Untitled".to_string(),
+			asset_roots: vec![PathBuf::from("assets")],
+			default_scene: None,
+			build: BuildSettings::default(),
+			camera_bookmarks: Vec::new(),
+			root: PathBuf::new(),
+		}
+	}
+}
+
+impl Project {
+	/// Creates a project directory with a project file and an empty assets directory
+	pub fn create(directory: impl AsRef<Path>, name: impl Into<String>) -> Result<Self> {
+		let directory = directory.as_ref();
+		if directory.join(PROJECT_FILE_NAME).exists() {
+			return Err(Error::ProjectExists(directory.display().to_string()));
+		}
+		let project = Self {
+			name: name.into(),
+			root: directory.to_path_buf(),
+			..Default::default()
+		};
+		let assets = project.asset_root();
+		fs::create_dir_all(&assets).map_err(|error| Error::CreateProjectDirectory(error, assets.display().to_string()))?;
+		project.save()?;
+		Ok(project)
+	}
+
+	/// Opens a project from its directory or its project file
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let (root, file) = if path.is_dir() {
+			(path.to_path_buf(), path.join(PROJECT_FILE_NAME))
+		} else {
+			(path.parent().unwrap_or(Path::new("")).to_path_buf(), path.to_path_buf())
+		};
+		let contents = fs::read_to_string(&file).map_err(|error| Error::ReadProject(error, file.display().to_string()))?;
+		let mut project = toml::from_str::<Self>(&contents).map_err(|error| Error::ParseProject(error, file.display().to_string()))?;
+		project.root = root;
+		Ok(project)
+	}
+
+	pub fn save(&self) -> Result<()> {
+		let path = self.file_path();
+		let contents = toml::to_string_pretty(self).map_err(Error::SerializeProject)?;
+		fs::write(&path, contents).map_err(|error| Error::WriteProject(error, path.display().to_string()))
+	}
+
+	/// The project's directory
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	pub fn file_path(&self) -> PathBuf {
+		self.root.join(PROJECT_FILE_NAME)
+	}
+
+	/// The directory the asset browser shows and imports files into
+	pub fn asset_root(&self) -> PathBuf {
+		self.root.join(self.asset_roots.first().map(PathBuf::as_path).unwrap_or(Path::new("assets")))
+	}
+
+	pub fn default_scene_path(&self) -> Option<PathBuf> {
+		self.default_scene.as_ref().map(|scene| self.root.join(scene))
+	}
+
+	pub fn camera_bookmark(&self, slot: u8) -> Option<&CameraBookmark> {
+		self.camera_bookmarks.iter().find(|bookmark| bookmark.slot == slot)
+	}
+
+	/// Saves a bookmark, replacing the one in the same slot
+	pub fn set_camera_bookmark(&mut self, bookmark: CameraBookmark) {
+		self.camera_bookmarks.retain(|existing| existing.slot != bookmark.slot);
+		self.camera_bookmarks.push(bookmark);
+		self.camera_bookmarks.sort_by_key(|bookmark| bookmark.slot);
+	}
+}
+
+/// Projects the editor opened most recently, newest first
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecentProjects {
+	pub projects: Vec<PathBuf>,
+
+	#[serde(skip)]
+	path: Option<PathBuf>,
+}
+
+impl RecentProjects {
+	/// Where the list is stored for the current user, if the platform has a config directory
+	pub fn default_path() -> Option<PathBuf> {
+		dirs::config_dir().map(|directory| directory.join("elder").join("recent_projects.toml"))
+	}
+
+	/// Loads the list from the given path. A missing file yields an empty list.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let mut recent = if path.exists() {
+			let contents = fs::read_to_string(path).map_err(|error| Error::ReadRecentProjects(error, path.display().to_string()))?;
+			toml::from_str::<Self>(&contents).map_err(|error| Error::ParseRecentProjects(error, path.display().to_string()))?
+		} else {
+			Self::default()
+		};
+		recent.path = Some(path.to_path_buf());
+		Ok(recent)
+	}
+
+	/// Writes the list back to the path it was loaded from, if it was loaded from a file
+	pub fn save(&self) -> Result<()> {
+		let Some(path) = self.path.as_ref() else {
+			return Ok(());
+		};
+		if let Some(directory) = path.parent() {
+			fs::create_dir_all(directory).map_err(|error| Error::WriteRecentProjects(error, path.display().to_string()))?;
+		}
+		let contents = toml::to_string_pretty(self).map_err(Error::SerializeRecentProjects)?;
+		fs::write(path, contents).map_err(|error| Error::WriteRecentProjects(error, path.display().to_string()))
+	}
+
+	/// Moves a project directory to the top of the list
+	pub fn add(&mut self, project: impl Into<PathBuf>) {
+		let project = project.into();
+		self.projects.retain(|existing| *existing != project);
+		self.projects.insert(0, project);
+		self.projects.truncate(MAXIMUM_RECENT_PROJECTS);
+	}
+
+	/// Forgets projects whose directories have been deleted or moved
+	pub fn remove_missing(&mut self) {
+		self.projects.retain(|project| project.join(PROJECT_FILE_NAME).exists());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::viewport::OrbitCamera;
+
+	fn directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("elder_project_{name}_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		directory
+	}
+
+	#[test]
+	pub fn create_and_open() -> Result<()> {
+		let directory = directory("create");
+		let mut project = Project::create(&directory, "Space Game")?;
+		assert!(project.asset_root().is_dir());
+		assert!(matches!(Project::create(&directory, "Again"), Err(Error::ProjectExists(_))));
+
+		project.default_scene = Some(PathBuf::from("scenes/main.ron"));
+		project.build.features.push("steam".to_string());
+		project.set_camera_bookmark(OrbitCamera::default().bookmark(1));
+		project.set_camera_bookmark(OrbitCamera::default().bookmark(2));
+		project.set_camera_bookmark(CameraBookmark {
+			distance: 3.0,
+			..OrbitCamera::default().bookmark(1)
+		});
+		assert_eq!(project.camera_bookmarks.len(), 2);
+		assert_eq!(project.camera_bookmark(1).map(|bookmark| bookmark.distance), Some(3.0));
+		project.save()?;
+
+		let opened = Project::open(directory.join(PROJECT_FILE_NAME))?;
+		assert_eq!(opened, project);
+		assert_eq!(opened.default_scene_path(), Some(directory.join("scenes/main.ron")));
+		fs::remove_dir_all(&directory).unwrap();
+		Ok(())
+	}
+
+	#[test]
+	pub fn recent_projects() -> Result<()> {
+		let directory = directory("recent");
+		let path = directory.join("recent.toml");
+		let mut recent = RecentProjects::load(&path)?;
+		for index in 0..12 {
+			recent.add(format!("project_{index}"));
+		}
+		recent.add("project_5");
+		assert_eq!(recent.projects.len(), MAXIMUM_RECENT_PROJECTS);
+		assert_eq!(recent.projects[0], PathBuf::from("project_5"));
+		recent.save()?;
+		assert_eq!(RecentProjects::load(&path)?.projects, recent.projects);
+
+		recent.remove_missing();
+		assert!(recent.projects.is_empty());
+		fs::remove_dir_all(&directory).unwrap();
+		Ok(())
+	}
+}
@@ -0,0 +1,299 @@
+use crate::gizmo::ViewDirection;
+use elder::{
+	app::{Input, MouseButton},
+	math::{BoundingSphere, Camera, Projection, Quaternion, Real, Vector3, Viewport},
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// How far the camera can pitch before it would flip over the top or bottom of the focus point
+const MAXIMUM_PITCH: Real = FRAC_PI_2 - 0.01;
+
+const MINIMUM_DISTANCE: Real = 0.05;
+
+/// Each line scrolled moves the camera this fraction of the way to the focus point
+const ZOOM_PER_LINE: Real = 0.1;
+
+/// A saved camera position, stored in the project file and recalled with the number keys
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+	/// The number key the bookmark is saved to
+	pub slot: u8,
+	pub focus: [Real; 3],
+	pub distance: Real,
+	pub yaw: Real,
+	pub pitch: Real,
+}
+
+/// The editor's viewport camera, which orbits around a focus point.
+///
+/// The middle mouse button or alt and the left mouse button orbit, shift and the middle mouse
+/// button pan, and scrolling zooms in steps proportional to the distance from the focus point.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrbitCamera {
+	pub focus: Vector3,
+	pub distance: Real,
+
+	/// Radians around the world's up axis
+	pub yaw: Real,
+
+	/// Radians above the horizon, so positive values look down at the focus point
+	pub pitch: Real,
+
+	pub projection: Projection,
+
+	/// Radians turned per pixel dragged
+	pub orbit_speed: Real,
+
+	/// Whether the camera keeps looking from the same side, so dragging pans instead of orbiting
+	pub is_orbit_locked: bool,
+}
+
+impl Default for OrbitCamera {
+	fn default() -> Self {
+		Self {
+			focus: Vector3::zero(),
+			distance: 10.0,
+			yaw: 0.0,
+			pitch: 0.5,
+			projection: Projection::default(),
+			orbit_speed: 0.005,
+			is_orbit_locked: false,
+		}
+	}
+}
+
+impl OrbitCamera {
+	/// An orthographic camera locked to one side, as in the quad view's top, front, and side views
+	pub fn fixed(direction: ViewDirection) -> Self {
+		let mut camera = Self {
+			projection: Projection::Orthographic {
+				height: 20.0,
+				near: 0.1,
+				far: 1000.0,
+			},
+			is_orbit_locked: true,
+			..Self::default()
+		};
+		camera.snap_to(direction);
+		camera
+	}
+
+	pub fn rotation(&self) -> Quaternion {
+		Quaternion::from_axis_angle(Vector3::y_axis(), self.yaw) * Quaternion::from_axis_angle(Vector3::x_axis(), -self.pitch)
+	}
+
+	pub fn camera(&self) -> Camera {
+		let rotation = self.rotation();
+		let position = self.focus - rotation.rotate(Vector3::z_axis().inverse()) * self.distance;
+		Camera::new(position, rotation, self.projection)
+	}
+
+	/// Turns around the focus point by a drag in pixels
+	pub fn orbit(&mut self, delta: (Real, Real)) {
+		self.yaw -= delta.0 * self.orbit_speed;
+		self.pitch = (self.pitch + delta.1 * self.orbit_speed).clamp(-MAXIMUM_PITCH, MAXIMUM_PITCH);
+	}
+
+	/// Slides the camera and its focus point so the focus point follows the cursor
+	pub fn pan(&mut self, delta: (Real, Real), viewport: &Viewport) {
+		let visible_height = match self.projection {
+			Projection::Perspective { fov_y, .. } => 2.0 * self.distance * (fov_y * 0.5).tan(),
+			Projection::Orthographic { height, .. } => height,
+		};
+		let units_per_pixel = visible_height / viewport.rect.height.max(1.0);
+		let camera = self.camera();
+		self.focus = self.focus - camera.right() * (delta.0 * units_per_pixel) + camera.up() * (delta.1 * units_per_pixel);
+	}
+
+	/// Moves toward the focus point for positive lines and away for negative ones
+	pub fn zoom(&mut self, lines: Real) {
+		self.distance = (self.distance * (1.0 - ZOOM_PER_LINE).powf(lines)).max(MINIMUM_DISTANCE);
+		if let Projection::Orthographic { height, .. } = &mut self.projection {
+			*height = (*height * (1.0 - ZOOM_PER_LINE).powf(lines)).max(MINIMUM_DISTANCE);
+		}
+	}
+
+	/// Centers the view on a sphere, backing up until all of it is visible
+	pub fn focus_on(&mut self, bounds: &BoundingSphere) {
+		self.focus = bounds.center;
+		let radius = bounds.radius.max(MINIMUM_DISTANCE);
+		match &mut self.projection {
+			Projection::Perspective { fov_y, .. } => self.distance = radius / (*fov_y * 0.5).sin(),
+			Projection::Orthographic { height, .. } => {
+				*height = radius * 2.0;
+				self.distance = radius * 2.0;
+			},
+		}
+	}
+
+	/// Applies this frame's mouse input, returning whether the camera moved
+	pub fn update(&mut self, input: &Input, viewport: &Viewport) -> bool {
+		let (x, y) = input.cursor_delta();
+		let delta = (x as Real, y as Real);
+		let middle = input.is_button_held(MouseButton::Middle);
+		let panning = middle && (input.is_shift_held() || self.is_orbit_locked);
+		let orbiting = !panning && !self.is_orbit_locked && (middle || (input.is_alt_held() && input.is_button_held(MouseButton::Left)));
+		let dragged = delta != (0.0, 0.0) && (panning || orbiting);
+		if dragged && panning {
+			self.pan(delta, viewport);
+		} else if dragged {
+			self.orbit(delta);
+		}
+		let scroll = input.scroll_delta() as Real;
+		if scroll != 0.0 {
+			self.zoom(scroll);
+		}
+		dragged || scroll != 0.0
+	}
+
+	/// Looks at the focus point from one side, or as close to straight down or up as it can
+	pub fn snap_to(&mut self, direction: ViewDirection) {
+		(self.yaw, self.pitch) = match direction {
+			ViewDirection::Right => (FRAC_PI_2, 0.0),
+			ViewDirection::Left => (-FRAC_PI_2, 0.0),
+			ViewDirection::Top => (0.0, MAXIMUM_PITCH),
+			ViewDirection::Bottom => (0.0, -MAXIMUM_PITCH),
+			ViewDirection::Front => (0.0, 0.0),
+			ViewDirection::Back => (PI, 0.0),
+		};
+	}
+
+	pub fn bookmark(&self, slot: u8) -> CameraBookmark {
+		CameraBookmark {
+			slot,
+			focus: [self.focus.x(), self.focus.y(), self.focus.z()],
+			distance: self.distance,
+			yaw: self.yaw,
+			pitch: self.pitch,
+		}
+	}
+
+	pub fn apply_bookmark(&mut self, bookmark: &CameraBookmark) {
+		let [x, y, z] = bookmark.focus;
+		self.focus = Vector3::new(x, y, z);
+		self.distance = bookmark.distance.max(MINIMUM_DISTANCE);
+		self.yaw = bookmark.yaw;
+		self.pitch = bookmark.pitch.clamp(-MAXIMUM_PITCH, MAXIMUM_PITCH);
+	}
+}
+
+/// A sphere around a set of spheres, centered on their average.
+/// It is not always the smallest one, but it is close enough to frame a selection.
+pub fn enclosing_sphere(spheres: &[BoundingSphere]) -> Option<BoundingSphere> {
+	if spheres.is_empty() {
+		return None;
+	}
+	let center = spheres.iter().fold(Vector3::zero(), |sum, sphere| sum + sphere.center) * (1.0 / spheres.len() as Real);
+	let radius = spheres.iter().map(|sphere| (sphere.center - center).magnitude() + sphere.radius).fold(0.0, Real::max);
+	Some(BoundingSphere::new(center, radius))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use elder::math::Rect;
+
+	fn viewport() -> Viewport {
+		Viewport::new(Rect::new(0.0, 0.0, 800.0, 600.0))
+	}
+
+	#[test]
+	pub fn orbit() {
+		let mut camera = OrbitCamera {
+			focus: Vector3::new(1.0, 2.0, 3.0),
+			pitch: 0.0,
+			..OrbitCamera::default()
+		};
+		let view = camera.camera();
+		assert_eq!(view.position, Vector3::new(1.0, 2.0, 13.0));
+		assert_eq!(view.forward(), Vector3::new(0.0, 0.0, -1.0));
+
+		camera.orbit((0.0, 10_000.0));
+		assert_eq!(camera.pitch, MAXIMUM_PITCH);
+		let view = camera.camera();
+		assert!(((view.position - camera.focus).magnitude() - 10.0).abs() < 1e-4);
+		assert!(view.forward().y() < -0.99);
+	}
+
+	#[test]
+	pub fn pan_follows_the_cursor() {
+		let mut camera = OrbitCamera::default();
+		let before = camera.camera();
+		let grabbed = before.world_to_screen(camera.focus, &viewport()).unwrap();
+		camera.pan((40.0, -25.0), &viewport());
+		let moved = camera.camera().world_to_screen(Vector3::zero(), &viewport()).unwrap();
+		assert!((moved.0 - (grabbed.0 + 40.0)).abs() < 1e-2, "{moved:?}");
+		assert!((moved.1 - (grabbed.1 - 25.0)).abs() < 1e-2, "{moved:?}");
+	}
+
+	#[test]
+	pub fn zoom_and_focus() {
+		let mut camera = OrbitCamera::default();
+		camera.zoom(2.0);
+		assert!((camera.distance - 8.1).abs() < 1e-4);
+		camera.zoom(-2.0);
+		assert!((camera.distance - 10.0).abs() < 1e-4);
+		camera.zoom(1000.0);
+		assert_eq!(camera.distance, MINIMUM_DISTANCE);
+
+		let spheres = [
+			BoundingSphere::new(Vector3::new(-2.0, 0.0, 0.0), 1.0),
+			BoundingSphere::new(Vector3::new(2.0, 0.0, 0.0), 1.0),
+		];
+		let bounds = enclosing_sphere(&spheres).unwrap();
+		assert_eq!(bounds, BoundingSphere::new(Vector3::zero(), 3.0));
+		camera.focus_on(&bounds);
+		assert_eq!(camera.focus, Vector3::zero());
+		assert!((camera.distance - 6.0).abs() < 1e-4);
+		assert_eq!(enclosing_sphere(&[]), None);
+	}
+
+	#[test]
+	pub fn mouse_controls() {
+		let mut camera = OrbitCamera::default();
+		let mut input = Input::default();
+		input.move_cursor((0.0, 0.0).into());
+		input.move_cursor((10.0, 0.0).into());
+		assert!(!camera.update(&input, &viewport()));
+
+		input.press_button(MouseButton::Middle);
+		assert!(camera.update(&input, &viewport()));
+		assert!((camera.yaw + 0.05).abs() < 1e-6);
+
+		input.press_key("LShift");
+		let focus = camera.focus;
+		camera.update(&input, &viewport());
+		assert_ne!(camera.focus, focus);
+
+		// Fixed cameras pan without shift and never orbit
+		let mut camera = OrbitCamera::fixed(ViewDirection::Top);
+		input.release_key("LShift");
+		input.press_key("LAlt");
+		input.press_button(MouseButton::Left);
+		let (yaw, pitch, focus) = (camera.yaw, camera.pitch, camera.focus);
+		assert!(camera.update(&input, &viewport()));
+		assert_eq!((camera.yaw, camera.pitch), (yaw, pitch));
+		assert_ne!(camera.focus, focus);
+	}
+
+	#[test]
+	pub fn snap_to_views() {
+		let mut camera = OrbitCamera::default();
+		for direction in ViewDirection::ALL {
+			camera.snap_to(direction);
+			let offset = (camera.camera().position - camera.focus).normalize();
+			assert!(offset.dot(&direction.axis()) > 0.9999, "{direction:?}");
+		}
+	}
+
+	#[test]
+	pub fn bookmarks() {
+		let mut camera = OrbitCamera::default();
+		camera.orbit((100.0, 20.0));
+		let bookmark = camera.bookmark(3);
+		let mut other = OrbitCamera::default();
+		other.apply_bookmark(&bookmark);
+		assert_eq!(other, camera);
+	}
+}
@@ -0,0 +1,62 @@
+use clap::{Parser, Subcommand};
+use elder::asset::{AssetPack, PackError, pack_directory};
+use std::{path::PathBuf, process::ExitCode};
+
+/// Packs an assets directory into a `.eldpak` archive for release builds
+#[derive(Parser, Debug)]
+struct Arguments {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+	/// Packs every file under a directory
+	Pack {
+		directory: PathBuf,
+
+		/// Defaults to the directory's name with the `.eldpak` extension
+		#[arg(short, long)]
+		output: Option<PathBuf>,
+	},
+
+	/// Lists the assets in a pack
+	List { pack: PathBuf },
+
+	/// Checks every asset in a pack against its checksum
+	Verify { pack: PathBuf },
+}
+
+fn main() -> ExitCode {
+	match run(Arguments::parse().command) {
+		Ok(code) => code,
+		Err(error) => {
+			eprintln!("{error}");
+			ExitCode::FAILURE
+		},
+	}
+}
+
+fn run(command: Command) -> Result<ExitCode, PackError> {
+	match command {
+		Command::Pack { directory, output } => {
+			let output = output.unwrap_or_else(|| directory.with_extension(elder::asset::PACK_EXTENSION));
+			let count = pack_directory(&directory, &output)?;
+			println!("Packed {count} assets into {}", output.display());
+		},
+		Command::List { pack } => {
+			for (path, entry) in AssetPack::open(pack)?.entries() {
+				println!("{path}\t{} bytes\t{} packed", entry.size, entry.packed_size);
+			}
+		},
+		Command::Verify { pack } => {
+			let damaged = AssetPack::open(pack)?.verify();
+			if !damaged.is_empty() {
+				damaged.iter().for_each(|path| eprintln!("Damaged: {path}"));
+				return Ok(ExitCode::FAILURE);
+			}
+			println!("All assets are intact");
+		},
+	}
+	Ok(ExitCode::SUCCESS)
+}
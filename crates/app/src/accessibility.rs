@@ -0,0 +1,251 @@
+use std::fmt;
+
+/// How large text and UI are drawn, combining the display's scale factor with the player's scale
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UiScale {
+	scale: f32,
+
+	/// Physical pixels per logical pixel on the window's monitor
+	pub scale_factor: f64,
+}
+
+impl Default for UiScale {
+	fn default() -> Self {
+		Self { scale: 1.0, scale_factor: 1.0 }
+	}
+}
+
+impl UiScale {
+	pub const MIN: f32 = 0.5;
+	pub const MAX: f32 = 3.0;
+
+	/// The player's scale on top of the display's, from `MIN` to `MAX`
+	pub fn scale(&self) -> f32 {
+		self.scale
+	}
+
+	pub fn set_scale(&mut self, scale: f32) {
+		self.scale = scale.clamp(Self::MIN, Self::MAX);
+	}
+
+	/// Physical pixels per logical UI pixel, which debug text and UI positions are multiplied by
+	pub fn factor(&self) -> f32 {
+		self.scale * self.scale_factor as f32
+	}
+
+	pub fn to_physical(&self, position: [f32; 2]) -> [f32; 2] {
+		position.map(|coordinate| coordinate * self.factor())
+	}
+
+	/// Converts a position such as the cursor's back into the logical pixels UI is laid out in
+	pub fn to_logical(&self, position: [f32; 2]) -> [f32; 2] {
+		position.map(|coordinate| coordinate / self.factor())
+	}
+}
+
+/// What kind of widget a node describes, which screen readers speak after its label
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AccessibleRole {
+	Button,
+	CheckBox,
+	Dialog,
+	Label,
+	List,
+	ListItem,
+	Slider,
+	TextInput,
+}
+
+impl fmt::Display for AccessibleRole {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(match self {
+			Self::Button => "button",
+			Self::CheckBox => "check box",
+			Self::Dialog => "dialog",
+			Self::Label => "text",
+			Self::List => "list",
+			Self::ListItem => "list item",
+			Self::Slider => "slider",
+			Self::TextInput => "edit text",
+		})
+	}
+}
+
+/// A widget as a screen reader sees it, published each frame by whatever draws the widget
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibleNode {
+	pub role: AccessibleRole,
+
+	/// What the widget is, such as `Jump` or `Music volume`
+	pub label: String,
+
+	/// What the widget currently holds, such as a bound key, a slider's amount, or typed text
+	pub value: Option<String>,
+	pub is_focused: bool,
+}
+
+impl AccessibleNode {
+	pub fn new(role: AccessibleRole, label: impl Into<String>) -> Self {
+		Self {
+			role,
+			label: label.into(),
+			value: None,
+			is_focused: false,
+		}
+	}
+
+	pub fn with_value(mut self, value: impl Into<String>) -> Self {
+		self.value = Some(value.into());
+		self
+	}
+
+	pub fn focused(mut self) -> Self {
+		self.is_focused = true;
+		self
+	}
+
+	/// The label, role, and value as a screen reader reads them, such as `Jump, button, Space`
+	pub fn spoken_text(&self) -> String {
+		match &self.value {
+			Some(value) => format!("{}, {}, {value}", self.label, self.role),
+			None => format!("{}, {}", self.label, self.role),
+		}
+	}
+}
+
+/// A platform screen reader, so the engine doesn't depend on a particular accessibility library
+pub trait ScreenReaderBackend {
+	fn name(&self) -> &str;
+
+	/// Replaces the widgets the screen reader can navigate, called only when they changed
+	fn update(&mut self, nodes: &[AccessibleNode]);
+
+	/// Reads out a message right away, such as the result of an action
+	fn announce(&mut self, text: &str);
+}
+
+/// The widgets on screen this frame and the messages to read out, sent to the backend each frame
+#[derive(Default)]
+pub struct AccessibilityTree {
+	backend: Option<Box<dyn ScreenReaderBackend>>,
+	nodes: Vec<AccessibleNode>,
+	published: Vec<AccessibleNode>,
+	announcements: Vec<String>,
+}
+
+impl AccessibilityTree {
+	pub fn new(backend: impl ScreenReaderBackend + 'static) -> Self {
+		Self::from_boxed(Box::new(backend))
+	}
+
+	pub fn from_boxed(backend: Box<dyn ScreenReaderBackend>) -> Self {
+		Self {
+			backend: Some(backend),
+			..Default::default()
+		}
+	}
+
+	pub fn backend_name(&self) -> Option<&str> {
+		self.backend.as_ref().map(|backend| backend.name())
+	}
+
+	pub fn push(&mut self, node: AccessibleNode) {
+		self.nodes.push(node);
+	}
+
+	pub fn announce(&mut self, text: impl Into<String>) {
+		self.announcements.push(text.into());
+	}
+
+	/// The nodes pushed so far this frame
+	pub fn nodes(&self) -> &[AccessibleNode] {
+		&self.nodes
+	}
+
+	pub fn focused(&self) -> Option<&AccessibleNode> {
+		self.nodes.iter().find(|node| node.is_focused)
+	}
+
+	pub fn announcements(&self) -> &[String] {
+		&self.announcements
+	}
+
+	/// Sends the frame's nodes and announcements to the backend and starts the next frame empty
+	pub(crate) fn flush(&mut self) {
+		if let Some(backend) = self.backend.as_mut() {
+			if self.nodes != self.published {
+				backend.update(&self.nodes);
+			}
+			for announcement in &self.announcements {
+				backend.announce(announcement);
+			}
+		}
+		self.published = std::mem::take(&mut self.nodes);
+		self.announcements.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::RefCell, rc::Rc};
+
+	#[derive(Default)]
+	struct Recorder {
+		updates: Rc<RefCell<Vec<usize>>>,
+		spoken: Rc<RefCell<Vec<String>>>,
+	}
+
+	impl ScreenReaderBackend for Recorder {
+		fn name(&self) -> &str {
+			"recorder"
+		}
+
+		fn update(&mut self, nodes: &[AccessibleNode]) {
+			self.updates.borrow_mut().push(nodes.len());
+		}
+
+		fn announce(&mut self, text: &str) {
+			self.spoken.borrow_mut().push(text.to_string());
+		}
+	}
+
+	#[test]
+	pub fn ui_scale() {
+		let mut scale = UiScale {
+			scale_factor: 2.0,
+			..Default::default()
+		};
+		scale.set_scale(1.5);
+		assert_eq!(scale.factor(), 3.0);
+		assert_eq!(scale.to_physical([10.0, 4.0]), [30.0, 12.0]);
+		assert_eq!(scale.to_logical([30.0, 12.0]), [10.0, 4.0]);
+		scale.set_scale(10.0);
+		assert_eq!(scale.scale(), UiScale::MAX);
+	}
+
+	#[test]
+	pub fn publishing() {
+		let recorder = Recorder::default();
+		let (updates, spoken) = (recorder.updates.clone(), recorder.spoken.clone());
+		let mut tree = AccessibilityTree::new(recorder);
+		assert_eq!(tree.backend_name(), Some("recorder"));
+
+		let jump = AccessibleNode::new(AccessibleRole::Button, "Jump").with_value("Space").focused();
+		assert_eq!(jump.spoken_text(), "Jump, button, Space");
+		tree.push(jump.clone());
+		tree.push(AccessibleNode::new(AccessibleRole::Label, "Controls"));
+		tree.announce("Jump bound to Space");
+		assert_eq!(tree.focused(), Some(&jump));
+		tree.flush();
+		assert!(tree.nodes().is_empty() && tree.announcements().is_empty());
+
+		// The same widgets on the next frame aren't sent again
+		tree.push(jump);
+		tree.push(AccessibleNode::new(AccessibleRole::Label, "Controls"));
+		tree.flush();
+		tree.flush();
+		assert_eq!(*updates.borrow(), [2, 0]);
+		assert_eq!(*spoken.borrow(), ["Jump bound to Space"]);
+	}
+}
@@ -1,57 +1,198 @@
-use std::io;
+use std::{io, path::Path, time::Duration};
 
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+use crate::http::{HttpClient, HttpResponseEvent};
+#[cfg(feature = "inspector")]
+use crate::inspector::WorldInspector;
+use crate::{
+	accessibility::{AccessibilityTree, ScreenReaderBackend, UiScale},
+	arguments::Arguments,
+	clipboard::Clipboard,
+	console::Console,
+	cvar::{CVars, apply_cvar_changes},
+	debug_text::DebugText,
+	display::{DisplayEvent, Displays, FullscreenMode, winit_fullscreen},
+	events::{Events, FileDropEvent},
+	focus::{FocusThrottle, WindowFocus},
+	frame_stats::FrameStats,
+	gamepad::{GamepadBackend, GamepadEvent, Gamepads},
+	input::{Input, InputEvent},
+	io_runtime::{IoRuntime, deliver_io_completions},
+	lifecycle::{LifecycleEvent, ScreenLayout},
+	profiler::Profiler,
+	rebinding::KeyRebinding,
+	recording::{InputRecording, save_input_recording},
+	render::{RenderBackend, RenderFailure, Renderer, render_frame},
+	sequence::{Sequences, advance_sequences},
+	settings::Settings,
+	shutdown::{AppExit, ExitReason, ShutdownHooks, ShutdownStage, run_shutdown_hooks, save_archived_cvars},
+	time::{GAMEPLAY_CHANNEL, Time},
+	touch::{TouchEvent, Touches},
+	transition::ScreenTransitions,
+	window::{CursorImage, WindowCommand, WindowCommands, WindowHandle, set_cursor_grab},
+};
+use ecs::resource::ResourceMap;
+#[cfg(feature = "inspector")]
+use ecs::world::World;
+use graphics::{ColorPipeline, DebugDraw, EnvironmentLighting, RenderSettings, TimeOfDay, Tonemapper};
 use image::io::Reader;
-use state::state::{State, StateMachine};
+use intern::Label;
+use state::{
+	persistence::{StackSnapshot, StateRegistry},
+	state::{State, StateMachine},
+};
 use thiserror::Error;
 use winit::{
 	self,
 	dpi::PhysicalSize,
-	error::OsError,
-	event::{Event, WindowEvent},
+	error::{ExternalError, OsError},
+	event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
 	event_loop::{ControlFlow, EventLoop},
-	window::{Fullscreen, Icon, Window, WindowBuilder},
+	window::{Icon, Window, WindowBuilder},
 };
 
 #[derive(Error, Debug)]
 pub enum Error {
+	#[error("Failed to attach the window canvas to the document!")]
+	AttachCanvas,
+
+	#[error("Failed to configure the audio mixer!")]
+	ConfigureAudio(#[source] audio::Error),
+
+	#[error("Failed to copy the game module at path: {1}")]
+	CopyGameModule(#[source] io::Error, String),
+
 	#[error("Failed to create icon file!")]
 	CreateIcon(#[source] winit::window::BadIcon),
 
+	#[error("Failed to create the renderer!")]
+	CreateRenderer(#[source] Box<dyn std::error::Error>),
+
 	#[error("Failed to create a window!")]
 	CreateWindow(#[source] OsError),
 
 	// #[error("Failed to create world!")]
 	// CreateWorld(#[source] WorldError),
-
-	// #[error("Failed to create the renderer!")]
-	// CreateRenderer(#[source] Box<dyn std::error::Error>),
 	#[error("Failed to decode icon file at path: {1}")]
 	DecodeIconFile(#[source] image::ImageError, String),
 
+	#[error("Failed to decode the clipboard payload!")]
+	DeserializeClipboardPayload(#[source] ron::error::SpannedError),
+
+	#[error("Failed to fetch the asset at url: {0}")]
+	FetchAsset(String),
+
 	#[error("Failed to handle an event in the state machine!")]
 	HandleEvent(#[source] Box<dyn std::error::Error>),
 
+	#[error("Game module ABI version {0} does not match the app's version {1}.")]
+	IncompatibleGameModule(u32, u32),
+
 	// #[error("Failed to initialize the gamepad input library!")]
 	// InitializeGamepadLibrary(#[source] gilrs::Error),
+	#[error("Failed to load the cursor image at path: {1}")]
+	LoadCursorImage(#[source] asset::Error, String),
+
+	#[error("Failed to load the game module at path: {1}")]
+	LoadGameModule(#[source] Box<dyn std::error::Error>, String),
+
+	#[error("Settings were not loaded from a file and have no path to save to.")]
+	NoSettingsPath,
+
 	#[error("Failed to open icon file at path: {1}")]
 	OpenIconFile(#[source] io::Error, String),
-	// #[error("Failed to render a frame!")]
-	// RenderFrame(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to parse the input recording at path: {1}")]
+	ParseInputRecording(#[source] ron::error::SpannedError, String),
+
+	#[error("Failed to parse the rumble pattern at path: {1}")]
+	ParseRumblePattern(#[source] ron::error::SpannedError, String),
+
+	#[error("Failed to parse the settings file at path: {1}")]
+	ParseSettings(#[source] toml::de::Error, String),
+
+	#[error("Failed to pause the state machine!")]
+	PauseStateMachine(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to read from the clipboard!")]
+	ReadClipboard(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to read the game module's metadata at path: {1}")]
+	ReadGameModuleMetadata(#[source] io::Error, String),
+
+	#[error("Failed to read the input recording at path: {1}")]
+	ReadInputRecording(#[source] io::Error, String),
+
+	#[error("Failed to read the rumble pattern at path: {1}")]
+	ReadRumblePattern(#[source] io::Error, String),
+
+	#[error("Failed to read the settings file at path: {1}")]
+	ReadSettings(#[source] io::Error, String),
+
+	#[error("Failed to render a frame!")]
+	RenderFrame(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to resize the renderer!")]
+	ResizeRenderer(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to resume the state machine!")]
+	ResumeStateMachine(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to save the state stack!")]
+	SaveStateStack(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to serialize the clipboard payload!")]
+	SerializeClipboardPayload(#[source] ron::Error),
+
+	#[error("Failed to serialize the input recording!")]
+	SerializeInputRecording(#[source] ron::Error),
+
+	#[error("Failed to serialize the settings!")]
+	SerializeSettings(#[source] toml::ser::Error),
+
+	#[error("Failed to serialize the profiler trace!")]
+	SerializeTrace(#[source] serde_json::Error),
+
+	#[error("Failed to set the cursor grab mode!")]
+	SetCursorGrab(#[source] ExternalError),
+
+	#[error("Failed to set the cursor position!")]
+	SetCursorPosition(#[source] ExternalError),
+
 	#[error("Failed to start the state machine!")]
 	StartStateMachine(#[source] Box<dyn std::error::Error>),
 
 	#[error("Failed to stop the state machine!")]
 	StopStateMachine(#[source] Box<dyn std::error::Error>),
 
+	#[error("Failed to add time channel '{0}' under '{1}' because it would create a cycle!")]
+	TimeChannelCycle(String, String),
+
+	#[error("Failed to find a time channel named: {0}")]
+	UnknownTimeChannel(String),
+
+	#[error("Cursor image at path {0} is not an 8-bit RGBA image.")]
+	UnsupportedCursorImage(String),
+
+	// #[error("Failed to to update the gui!")]
+	// UpdateGui(#[source] Box<dyn std::error::Error>),
+
 	// #[error("Failed to update the renderer!")]
 	// UpdateRenderer(#[source] Box<dyn std::error::Error>),
 	#[error("Failed to update the state machine!")]
 	UpdateStateMachine(#[source] Box<dyn std::error::Error>),
-	// #[error("Failed to to update the gui!")]
-	// UpdateGui(#[source] Box<dyn std::error::Error>),
 
-	// #[error("Failed to to resize the renderer!")]
-	// ResizeRenderer(#[source] Box<dyn std::error::Error>),
+	#[error("Failed to write to the clipboard!")]
+	WriteClipboard(#[source] Box<dyn std::error::Error>),
+
+	#[error("Failed to write the input recording at path: {1}")]
+	WriteInputRecording(#[source] io::Error, String),
+
+	#[error("Failed to write the settings file at path: {1}")]
+	WriteSettings(#[source] io::Error, String),
+
+	#[error("Failed to write the profiler trace at path: {1}")]
+	WriteTrace(#[source] io::Error, String),
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -60,8 +201,69 @@ pub struct AppConfig {
 	pub width: u32,
 	pub height: u32,
 	pub is_fullscreen: bool,
+
+	/// Switches the monitor's video mode in fullscreen instead of using a borderless window
+	pub exclusive_fullscreen: bool,
+
+	/// The monitor to go fullscreen on, which is the one the window opens on when not set
+	pub monitor: Option<usize>,
+
+	/// The exclusive fullscreen refresh rate in hertz, which is the fastest available when not set
+	pub refresh_rate: Option<u32>,
+
+	/// Runs with a hidden window and no renderer, such as for tests and servers
+	pub is_headless: bool,
+
+	/// Waits for the display's refresh before presenting each frame
+	pub vsync: bool,
+
+	/// MSAA samples per pixel, rounded down to 1, 2, 4, or 8
+	pub msaa_samples: u32,
+
+	/// Renders the scene at this multiple of the window's resolution, from 0.5 to 2.0
+	pub render_scale: f32,
+
+	/// Maps the HDR scene to the screen
+	pub tonemapper: Tonemapper,
+
+	/// Exposure in stops applied before tonemapping
+	pub exposure: f32,
 	pub title: String,
 	pub icon: Option<String>,
+
+	/// Path to a TOML settings file whose values override these defaults
+	pub settings_path: Option<String>,
+
+	/// Id of the element the canvas is appended to on the web.
+	/// The canvas is appended to the document body when this is not set.
+	pub canvas_parent: Option<String>,
+
+	/// Saves the state stack on exit and restores it on the next launch
+	pub state_persistence: Option<StatePersistence>,
+
+	/// Slows down updates and stops rendering while the window is unfocused
+	pub focus_throttle: FocusThrottle,
+
+	/// The renderer the app draws each frame with, or `None` to leave drawing to the states
+	pub render_backend: Option<Box<dyn RenderBackend>>,
+
+	/// The gamepad library the app polls each frame, or `None` for no gamepads
+	pub gamepad_backend: Option<Box<dyn GamepadBackend>>,
+
+	/// The screen reader the UI's labels are sent to, or `None` to only collect them
+	pub screen_reader_backend: Option<Box<dyn ScreenReaderBackend>>,
+}
+
+/// Present while the app runs headless, with a hidden window and no renderer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Headless;
+
+pub struct StatePersistence {
+	/// Path to the RON file the state stack is saved to
+	pub path: String,
+
+	/// The persistent states that can be restored from the file
+	pub registry: StateRegistry<ResourceMap>,
 }
 
 impl Default for AppConfig {
@@ -70,19 +272,163 @@ impl Default for AppConfig {
 			width: 1024,
 			height: 768,
 			is_fullscreen: false,
+			exclusive_fullscreen: false,
+			monitor: None,
+			refresh_rate: None,
+			is_headless: false,
+			vsync: true,
+			msaa_samples: 1,
+			render_scale: 1.0,
+			tonemapper: Tonemapper::default(),
+			exposure: 0.0,
 			title: "Elder App".to_string(),
 			icon: None,
+			settings_path: None,
+			canvas_parent: None,
+			state_persistence: None,
+			focus_throttle: FocusThrottle::DISABLED,
+			render_backend: None,
+			gamepad_backend: None,
+			screen_reader_backend: None,
 		}
 	}
 }
 
-pub fn run(config: AppConfig, initial_state: impl State<()> + 'static) -> Result<()> {
+impl AppConfig {
+	pub fn apply_settings(&mut self, settings: &Settings) {
+		let window = &settings.window;
+		self.width = window.width.unwrap_or(self.width);
+		self.height = window.height.unwrap_or(self.height);
+		self.is_fullscreen = window.fullscreen.unwrap_or(self.is_fullscreen);
+		self.exclusive_fullscreen = window.exclusive_fullscreen.unwrap_or(self.exclusive_fullscreen);
+		self.monitor = window.monitor.or(self.monitor);
+		self.refresh_rate = window.refresh_rate.or(self.refresh_rate);
+		self.vsync = window.vsync.unwrap_or(self.vsync);
+		self.msaa_samples = settings.graphics.msaa_samples.unwrap_or(self.msaa_samples);
+		self.render_scale = settings.graphics.render_scale.unwrap_or(self.render_scale);
+		self.tonemapper = settings.graphics.tonemapper.unwrap_or(self.tonemapper);
+		self.exposure = settings.graphics.exposure.unwrap_or(self.exposure);
+	}
+
+	/// How the window is shown at launch
+	pub fn fullscreen_mode(&self) -> FullscreenMode {
+		match (self.is_fullscreen, self.exclusive_fullscreen) {
+			(false, _) => FullscreenMode::Windowed,
+			(true, false) => FullscreenMode::Borderless { monitor: self.monitor },
+			(true, true) => FullscreenMode::Exclusive {
+				monitor: self.monitor,
+				width: self.width,
+				height: self.height,
+				refresh_rate_millihertz: self.refresh_rate.map(|refresh_rate| refresh_rate * 1000),
+			},
+		}
+	}
+
+	/// The renderer options, with unsupported values adjusted
+	pub fn render_settings(&self) -> RenderSettings {
+		RenderSettings::new(self.msaa_samples, self.render_scale)
+	}
+
+	/// How the HDR scene is resolved to the screen
+	pub fn color_pipeline(&self) -> ColorPipeline {
+		ColorPipeline {
+			tonemapper: self.tonemapper,
+			exposure: self.exposure,
+			encode_srgb: cfg!(target_arch = "wasm32"),
+			..Default::default()
+		}
+	}
+
+	pub fn apply_arguments(&mut self, arguments: &Arguments) {
+		self.width = arguments.width.unwrap_or(self.width);
+		self.height = arguments.height.unwrap_or(self.height);
+		if arguments.windowed {
+			self.is_fullscreen = false;
+		}
+		if arguments.fullscreen {
+			self.is_fullscreen = true;
+		}
+		self.is_headless |= arguments.headless;
+	}
+}
+
+pub fn run(mut config: AppConfig, initial_state: impl State<ResourceMap> + 'static) -> Result<()> {
 	log::info!("Application started");
 
+	let settings = match config.settings_path.as_ref() {
+		Some(path) => Settings::load(path)?,
+		None => Settings::default(),
+	};
+	config.apply_settings(&settings);
+
+	let arguments = Arguments::from_env();
+	config.apply_arguments(&arguments);
+
+	let trace_path = arguments.trace.clone();
+	let record_path = arguments.record.clone();
+	let mut frame_stats = FrameStats::default();
+	frame_stats.show_overlay = settings.debug.show_frame_stats;
+
+	let mut cvars = CVars::default();
+	if let Err(error) = cvars.load_settings(&settings) {
+		log::warn!("{error}");
+	}
+
+	let mut ui_scale = UiScale::default();
+	let mut color_pipeline = config.color_pipeline();
+	settings.accessibility.apply(&mut ui_scale, &mut color_pipeline);
+
+	let mut resources = ResourceMap::new();
+	resources.insert(settings);
+	resources.insert(cvars);
+	resources.insert(arguments);
+	resources.insert(config.render_settings());
+	resources.insert(color_pipeline);
+	resources.insert(WindowCommands::default());
+	resources.insert(Clipboard::default());
+	let mut console = Console::default();
+	console.register_resource_with("settings", Settings::keep_unserialized);
+	resources.insert(console);
+	resources.insert(Events::<FileDropEvent>::default());
+	resources.insert(Events::<TouchEvent>::default());
+	resources.insert(Touches::default());
+	resources.insert(Input::default());
+	resources.insert(KeyRebinding::default());
+	resources.insert(Events::<LifecycleEvent>::default());
+	resources.insert(Events::<DisplayEvent>::default());
+	resources.insert(config.gamepad_backend.take().map(Gamepads::from_boxed).unwrap_or_default());
+	resources.insert(Events::<GamepadEvent>::default());
+	resources.insert(frame_stats);
+	resources.insert(Profiler::new(trace_path.is_some()));
+	resources.insert(DebugText::default());
+	resources.insert(config.screen_reader_backend.take().map(AccessibilityTree::from_boxed).unwrap_or_default());
+	resources.insert(DebugDraw::default());
+	resources.insert(Time::default());
+	resources.insert(IoRuntime::default());
+	#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+	{
+		resources.insert(HttpClient::default());
+		resources.insert(Events::<HttpResponseEvent>::default());
+	}
+	resources.insert(ScreenTransitions::default());
+	resources.insert(Sequences::default());
+	resources.insert(AppExit::default());
+	resources.insert(WindowFocus::new(config.focus_throttle));
+	let mut shutdown_hooks = ShutdownHooks::default();
+	shutdown_hooks.add(ShutdownStage::Saves, "archived cvars", save_archived_cvars);
+	if let Some(path) = record_path {
+		resources.insert(InputRecording::default());
+		shutdown_hooks.add(ShutdownStage::Saves, "input recording", move |resources| save_input_recording(resources, &path));
+	}
+	resources.insert(shutdown_hooks);
+	#[cfg(feature = "inspector")]
+	resources.insert(WorldInspector::default());
+
 	let event_loop = EventLoop::new();
 	let mut window_builder = WindowBuilder::new()
 		.with_title(config.title.to_string())
-		.with_inner_size(PhysicalSize::new(config.width, config.height));
+		.with_inner_size(PhysicalSize::new(config.width, config.height))
+		.with_visible(!config.is_headless);
 
 	if let Some(icon_path) = config.icon.as_ref() {
 		let image = Reader::open(icon_path)
@@ -97,41 +443,539 @@ pub fn run(config: AppConfig, initial_state: impl State<()> + 'static) -> Result
 
 	let mut window = window_builder.build(&event_loop).map_err(Error::CreateWindow)?;
 
-	if config.is_fullscreen {
-		window.set_fullscreen(Some(Fullscreen::Borderless(window.primary_monitor())));
+	#[cfg(target_arch = "wasm32")]
+	crate::web::attach_canvas(&window, config.canvas_parent.as_deref())?;
+
+	if config.is_headless {
+		resources.insert(Headless);
+	} else {
+		resources.insert(Displays::from_window(&window));
+		resources.insert(WindowHandle::from_window(&window));
+		let mut renderer = config.render_backend.take().map(Renderer::from_boxed).unwrap_or_default();
+		renderer.set_vsync(config.vsync);
+		resources.insert(renderer);
+		set_fullscreen(&window, &mut resources, config.fullscreen_mode());
 	}
 
-	let mut state_machine = StateMachine::new(initial_state);
+	resources.insert(ScreenLayout::from_window(&window));
+	ui_scale.scale_factor = window.scale_factor();
+	resources.insert(ui_scale);
+
+	let persistence = config.state_persistence.take();
+	let mut state_machine = persistence
+		.as_ref()
+		.and_then(restore_state_machine)
+		.unwrap_or_else(|| StateMachine::new(initial_state));
+	let snapshot_path = persistence.map(|persistence| persistence.path);
 
 	event_loop.run(move |event, _, control_flow| {
-		if let Err(error) = run_loop(&mut window, &mut state_machine, &event, control_flow) {
+		if let Event::LoopDestroyed = event {
+			shutdown(&window, &mut state_machine, &mut resources, snapshot_path.as_deref(), trace_path.as_deref());
+			return;
+		}
+		if let Err(error) = run_loop(&mut window, &mut state_machine, &mut resources, &event, control_flow) {
 			log::error!("Application error: {}", error);
 		}
 	});
 }
 
-fn run_loop(window: &mut Window, state_machine: &mut StateMachine<()>, event: &Event<()>, control_flow: &mut ControlFlow) -> Result<()> {
+fn run_loop(
+	window: &mut Window,
+	state_machine: &mut StateMachine<ResourceMap>,
+	resources: &mut ResourceMap,
+	event: &Event<()>,
+	control_flow: &mut ControlFlow,
+) -> Result<()> {
+	// The browser drives frames through `requestAnimationFrame`,
+	// which winit exposes as redraw requests on the web
+	#[cfg(target_arch = "wasm32")]
+	control_flow.set_wait();
+	#[cfg(not(target_arch = "wasm32"))]
 	control_flow.set_poll();
 
+	if resources.get::<AppExit>().is_some_and(AppExit::is_requested) {
+		control_flow.set_exit();
+		return Ok(());
+	}
+
 	if !state_machine.is_running() {
-		state_machine.start(&mut ()).map_err(Error::StartStateMachine)?;
+		state_machine.start(resources).map_err(Error::StartStateMachine)?;
 	}
 
 	match event {
-		Event::MainEventsCleared => {
-			state_machine.update(&mut ()).map_err(Error::UpdateStateMachine)?;
+		#[cfg(not(target_arch = "wasm32"))]
+		Event::MainEventsCleared if is_update_due(resources, control_flow) => update(window, state_machine, resources)?,
+
+		#[cfg(target_arch = "wasm32")]
+		Event::MainEventsCleared => window.request_redraw(),
+
+		#[cfg(target_arch = "wasm32")]
+		Event::RedrawRequested(window_id) if *window_id == window.id() => update(window, state_machine, resources)?,
+
+		Event::WindowEvent { ref event, window_id } if *window_id == window.id() => handle_window_event(window, event, resources, control_flow),
+
+		Event::Suspended => {
+			state_machine.pause(resources).map_err(Error::PauseStateMachine)?;
+			if let Some(renderer) = resources.get_mut::<Renderer>() {
+				renderer.release();
+			}
+			if let Some(gamepads) = resources.get_mut::<Gamepads>() {
+				gamepads.stop_all();
+			}
+			resources.remove::<WindowHandle>();
+			push_event(resources, LifecycleEvent::Suspended);
 		},
 
-		Event::WindowEvent { ref event, window_id } if *window_id == window.id() => match event {
-			WindowEvent::CloseRequested => control_flow.set_exit(),
-			_ => {},
+		Event::Resumed => {
+			state_machine.resume(resources).map_err(Error::ResumeStateMachine)?;
+			resources.insert(ScreenLayout::from_window(window));
+			if resources.get::<Headless>().is_none() {
+				resources.insert(WindowHandle::from_window(window));
+				refresh_displays(window, resources);
+			}
+			push_event(resources, LifecycleEvent::Resumed);
 		},
 
-		Event::LoopDestroyed => {
-			state_machine.stop(&mut ()).map_err(Error::StopStateMachine)?;
+		_ => {},
+	}
+
+	// Checked after the event, so an exit requested while handling it skips another frame
+	if !state_machine.is_running() {
+		request_exit(resources, ExitReason::StatesFinished);
+	}
+	if resources.get::<AppExit>().is_some_and(AppExit::is_requested) {
+		control_flow.set_exit();
+	}
+	Ok(())
+}
+
+/// Whether the next update is due, waiting for it instead while the unfocused window is throttled
+#[cfg(not(target_arch = "wasm32"))]
+fn is_update_due(resources: &mut ResourceMap, control_flow: &mut ControlFlow) -> bool {
+	let Some(focus) = resources.get_mut::<WindowFocus>() else {
+		return true;
+	};
+	let Some(interval) = focus.update_interval() else {
+		return true;
+	};
+	let now = std::time::Instant::now();
+	match focus.next_update {
+		Some(next_update) if now < next_update => {
+			control_flow.set_wait_until(next_update);
+			false
+		},
+		_ => {
+			focus.next_update = Some(now + interval);
+			control_flow.set_wait_until(now + interval);
+			true
 		},
+	}
+}
+
+/// Hides the window, saves and stops the states, runs the shutdown hooks, then writes the trace
+fn shutdown(window: &Window, state_machine: &mut StateMachine<ResourceMap>, resources: &mut ResourceMap, snapshot_path: Option<&str>, trace_path: Option<&str>) {
+	window.set_visible(false);
+	if let Some(path) = snapshot_path {
+		if let Err(error) = save_state_stack(state_machine, path) {
+			log::error!("Application error: {}", error);
+		}
+	}
+	if let Err(error) = state_machine.stop(resources) {
+		log::error!("Application error: {}", Error::StopStateMachine(error));
+	}
+	run_shutdown_hooks(resources);
+	if let Some(renderer) = resources.get_mut::<Renderer>() {
+		renderer.release();
+	}
+	if let Some(gamepads) = resources.get_mut::<Gamepads>() {
+		gamepads.stop_all();
+	}
+	resources.remove::<WindowHandle>();
+	if let (Some(path), Some(profiler)) = (trace_path, resources.get::<Profiler>()) {
+		if let Err(error) = profiler.save_chrome_trace(path) {
+			log::error!("Application error: {}", error);
+		}
+	}
+	log::info!("Application stopped");
+}
+
+fn request_exit(resources: &mut ResourceMap, reason: ExitReason) {
+	if let Some(exit) = resources.get_mut::<AppExit>() {
+		exit.request_with(reason);
+	}
+}
+
+/// The saved state stack, or `None` to begin with the initial state when it can't be restored
+fn restore_state_machine(persistence: &StatePersistence) -> Option<StateMachine<ResourceMap>> {
+	if !Path::new(&persistence.path).exists() {
+		return None;
+	}
+	let snapshot = StackSnapshot::load(&persistence.path)
+		.map_err(|error| log::warn!("Failed to load the saved state stack: {}", error))
+		.ok()
+		.filter(|snapshot| !snapshot.is_empty())?;
+	StateMachine::restore(&snapshot, &persistence.registry)
+		.map_err(|error| log::warn!("Failed to restore the saved state stack: {}", error))
+		.ok()
+}
+
+fn save_state_stack(state_machine: &StateMachine<ResourceMap>, path: &str) -> Result<()> {
+	let snapshot = state_machine.snapshot().map_err(Error::SaveStateStack)?;
+	snapshot.save(path).map_err(|error| Error::SaveStateStack(Box::new(error)))
+}
+
+fn update(window: &Window, state_machine: &mut StateMachine<ResourceMap>, resources: &mut ResourceMap) -> Result<()> {
+	begin_frame(resources);
+	profile(resources, |profiler| profiler.begin(Label::from_static("update")));
+	let update = state_machine.update(resources).map_err(Error::UpdateStateMachine);
+	profile(resources, Profiler::end);
+	update?;
+	advance_screen_transition(state_machine, resources)?;
+	advance_sequences(resources);
+	apply_window_commands(window, resources);
+	if resources.get::<Headless>().is_none() && resources.get::<WindowFocus>().is_none_or(WindowFocus::should_render) {
+		render(resources)?;
+	}
+	end_frame(resources);
+	Ok(())
+}
+
+fn render(resources: &mut ResourceMap) -> Result<()> {
+	apply_vsync_setting(resources);
+	profile(resources, |profiler| profiler.begin(Label::from_static("render")));
+	let result = render_frame(resources);
+	profile(resources, Profiler::end);
+	result.map_err(|failure| match failure {
+		RenderFailure::Init(error) => Error::CreateRenderer(error),
+		RenderFailure::Resize(error) => Error::ResizeRenderer(error),
+		RenderFailure::Frame(error) => Error::RenderFrame(error),
+	})
+}
+
+/// Follows `settings.window.vsync`, so changing it from the console or a menu takes effect
+fn apply_vsync_setting(resources: &mut ResourceMap) {
+	let Some(vsync) = resources.get::<Settings>().and_then(|settings| settings.window.vsync) else {
+		return;
+	};
+	if let Some(renderer) = resources.get_mut::<Renderer>() {
+		renderer.set_vsync(vsync);
+	}
+}
+
+/// Plays screen transitions in real time, so they keep going while gameplay is paused
+fn advance_screen_transition(state_machine: &mut StateMachine<ResourceMap>, resources: &mut ResourceMap) -> Result<()> {
+	let delta_time = resources.get::<Time>().map_or(0.0, Time::real_delta);
+	let Some(transition) = resources.get_mut::<ScreenTransitions>().and_then(|transitions| transitions.advance(delta_time)) else {
+		return Ok(());
+	};
+	state_machine.transition(transition, resources).map_err(Error::UpdateStateMachine)
+}
 
+fn handle_window_event(window: &Window, event: &WindowEvent, resources: &mut ResourceMap, control_flow: &mut ControlFlow) {
+	// Keys typed into the open console or pressed to rebind an action don't reach the game
+	let is_console_open = resources.get::<Console>().is_some_and(|console| console.visible);
+	let is_rebinding = resources.get::<KeyRebinding>().is_some_and(|rebinding| rebinding.listening().is_some());
+	let is_console_input = (is_console_open || is_rebinding) && matches!(event, WindowEvent::KeyboardInput { .. });
+	if let Some(input_event) = InputEvent::from_window_event(event).filter(|_| !is_console_input) {
+		if let Some(input) = resources.get_mut::<Input>() {
+			input.apply(&input_event);
+		}
+		if let Some(recording) = resources.get_mut::<InputRecording>() {
+			recording.record(input_event);
+		}
+	}
+	match event {
+		WindowEvent::CloseRequested => {
+			request_exit(resources, ExitReason::WindowClosed);
+			control_flow.set_exit();
+		},
+		WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+			resources.insert(ScreenLayout::from_window(window));
+			if let Some(ui_scale) = resources.get_mut::<UiScale>() {
+				ui_scale.scale_factor = window.scale_factor();
+			}
+			refresh_displays(window, resources);
+		},
+		WindowEvent::Moved(_) => refresh_displays(window, resources),
+		WindowEvent::Focused(focused) => {
+			if let Some(focus) = resources.get_mut::<WindowFocus>() {
+				focus.set_focused(*focused);
+			}
+		},
+		WindowEvent::HoveredFile(path) => push_event(resources, FileDropEvent::Hovered(path.clone())),
+		WindowEvent::DroppedFile(path) => push_event(resources, FileDropEvent::Dropped(path.clone())),
+		WindowEvent::HoveredFileCancelled => push_event(resources, FileDropEvent::HoverCancelled),
+		WindowEvent::ReceivedCharacter(character) => {
+			if let Some(console) = resources.get_mut::<Console>() {
+				console.type_character(*character);
+			}
+		},
+		WindowEvent::ModifiersChanged(modifiers) => {
+			if let Some(console) = resources.get_mut::<Console>() {
+				console.set_control_held(modifiers.ctrl());
+			}
+		},
+		WindowEvent::KeyboardInput {
+			input: KeyboardInput {
+				state: ElementState::Pressed,
+				virtual_keycode: Some(key),
+				..
+			},
+			..
+		} => handle_key_pressed(resources, *key),
+		WindowEvent::Touch(touch) => {
+			let event = TouchEvent {
+				id: touch.id,
+				phase: touch.phase.into(),
+				position: touch.location,
+			};
+			if let Some(touches) = resources.get_mut::<Touches>() {
+				touches.handle_event(&event);
+			}
+			push_event(resources, event);
+		},
 		_ => {},
 	}
+}
+
+fn handle_key_pressed(resources: &mut ResourceMap, key: VirtualKeyCode) {
+	let key = format!("{key:?}");
+	if handle_rebinding_key(resources, &key) || handle_console_key(resources, &key) {
+		return;
+	}
+	if is_bound(resources, FrameStats::TOGGLE_ACTION, FrameStats::DEFAULT_TOGGLE_KEY, &key) {
+		if let Some(stats) = resources.get_mut::<FrameStats>() {
+			stats.show_overlay = !stats.show_overlay;
+		}
+	}
+	#[cfg(feature = "inspector")]
+	handle_inspector_key(resources, &key);
+}
+
+/// Binds the key to the action a controls menu is listening for. Returns whether it was used.
+fn handle_rebinding_key(resources: &mut ResourceMap, key: &str) -> bool {
+	let Some(mut settings) = resources.get_mut::<Settings>().map(std::mem::take) else {
+		return false;
+	};
+	let announcement = resources
+		.get_mut::<KeyRebinding>()
+		.and_then(|rebinding| rebinding.capture(key, &mut settings).map(|rebind| rebinding.describe(&rebind)));
+	resources.insert(settings);
+	let Some(announcement) = announcement else {
+		return false;
+	};
+	if let Some(tree) = resources.get_mut::<AccessibilityTree>() {
+		tree.announce(announcement);
+	}
+	true
+}
+
+/// Opens or closes the console, or sends it the key while open. Returns whether it was used.
+fn handle_console_key(resources: &mut ResourceMap, key: &str) -> bool {
+	if is_bound(resources, Console::TOGGLE_ACTION, Console::DEFAULT_TOGGLE_KEY, key) {
+		if let Some(console) = resources.get_mut::<Console>() {
+			console.visible = !console.visible;
+			return true;
+		}
+	}
+	let mut console = match resources.get_mut::<Console>() {
+		Some(console) if console.visible => std::mem::take(console),
+		_ => return false,
+	};
+	console.press_key(key, resources);
+	resources.insert(console);
+	true
+}
+
+/// Whether the key is bound to the action in the settings, or is its default key when unbound
+fn is_bound(resources: &ResourceMap, action: &str, default_key: &str, key: &str) -> bool {
+	let bound_key = resources
+		.get::<Settings>()
+		.and_then(|settings| settings.keybindings.get(action))
+		.map_or(default_key, String::as_str);
+	bound_key == key
+}
+
+#[cfg(feature = "inspector")]
+fn handle_inspector_key(resources: &mut ResourceMap, key: &str) {
+	if is_bound(resources, WorldInspector::TOGGLE_ACTION, WorldInspector::DEFAULT_TOGGLE_KEY, key) {
+		if let Some(inspector) = resources.get_mut::<WorldInspector>() {
+			inspector.visible = !inspector.visible;
+		}
+		return;
+	}
+	let mut inspector = match resources.get_mut::<WorldInspector>() {
+		Some(inspector) if inspector.visible => std::mem::take(inspector),
+		_ => return,
+	};
+	if let Some(world) = resources.get::<World>() {
+		match key {
+			WorldInspector::SELECT_NEXT_KEY => inspector.select_next(world),
+			WorldInspector::SELECT_PREVIOUS_KEY => inspector.select_previous(world),
+			_ => {},
+		}
+	}
+	resources.insert(inspector);
+}
+
+fn profile(resources: &mut ResourceMap, record: impl FnOnce(&mut Profiler)) {
+	if let Some(profiler) = resources.get_mut::<Profiler>() {
+		record(profiler);
+	}
+}
+
+fn push_event<T: 'static>(resources: &mut ResourceMap, event: T) {
+	if let Some(events) = resources.get_mut::<Events<T>>() {
+		events.push(event);
+	}
+}
+
+fn clear_events<T: 'static>(resources: &mut ResourceMap) {
+	if let Some(events) = resources.get_mut::<Events<T>>() {
+		events.clear();
+	}
+}
+
+/// Advances time and delivers IO and cvar changes before the states update
+fn begin_frame(resources: &mut ResourceMap) {
+	let mut text = resources.get_mut::<DebugText>().map(std::mem::take).unwrap_or_default();
+	let mut tree = resources.get_mut::<AccessibilityTree>().map(std::mem::take).unwrap_or_default();
+	profile(resources, Profiler::begin_frame);
+	let mut frame_time = Duration::ZERO;
+	if let Some(stats) = resources.get_mut::<FrameStats>() {
+		stats.tick();
+		stats.draw_overlay(&mut text);
+		stats.describe(&mut tree);
+		frame_time = stats.last_frame_time();
+	}
+	#[cfg(feature = "inspector")]
+	if let (Some(inspector), Some(world)) = (resources.get::<WorldInspector>(), resources.get::<World>()) {
+		inspector.draw(world, resources, &mut text);
+	}
+	if let Some(console) = resources.get::<Console>() {
+		console.draw(&mut text);
+		console.describe(&mut tree);
+	}
+	resources.insert(text);
+	resources.insert(tree);
+	if let Some(time) = resources.get_mut::<Time>() {
+		time.advance(frame_time);
+	}
+	advance_time_of_day(resources);
+	update_gamepads(resources);
+	deliver_io_completions(resources);
+	apply_cvar_changes(resources);
+}
+
+/// Moves the sun with gameplay time and lights the scene, for games that insert a `TimeOfDay`
+fn advance_time_of_day(resources: &mut ResourceMap) {
+	let delta_time = resources.get::<Time>().map_or(0.0, |time| time.delta(GAMEPLAY_CHANNEL));
+	let Some(time_of_day) = resources.get_mut::<TimeOfDay>() else {
+		return;
+	};
+	time_of_day.advance(delta_time);
+	let daylight = time_of_day.daylight();
+	if let Some(environment) = resources.get_mut::<EnvironmentLighting>() {
+		daylight.apply(environment);
+	}
+	resources.insert(daylight);
+}
+
+/// Rumble plays in real time, so patterns finish on schedule while gameplay is paused or slowed
+fn update_gamepads(resources: &mut ResourceMap) {
+	let delta_time = resources.get::<Time>().map_or(0.0, Time::real_delta);
+	let Some(gamepads) = resources.get_mut::<Gamepads>() else {
+		return;
+	};
+	for event in gamepads.update(delta_time) {
+		push_event(resources, event);
+	}
+}
+
+fn end_frame(resources: &mut ResourceMap) {
+	clear_events::<FileDropEvent>(resources);
+	clear_events::<TouchEvent>(resources);
+	clear_events::<LifecycleEvent>(resources);
+	clear_events::<DisplayEvent>(resources);
+	clear_events::<GamepadEvent>(resources);
+	#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+	clear_events::<HttpResponseEvent>(resources);
+	if let Some(touches) = resources.get_mut::<Touches>() {
+		touches.end_frame();
+	}
+	if let Some(focus) = resources.get_mut::<WindowFocus>() {
+		focus.end_frame();
+	}
+	if let Some(input) = resources.get_mut::<Input>() {
+		input.end_frame();
+	}
+	let delta_time = resources.get::<Time>().map_or(0.0, Time::real_delta);
+	if let Some(recording) = resources.get_mut::<InputRecording>() {
+		recording.end_frame(delta_time);
+	}
+	if let Some(text) = resources.get_mut::<DebugText>() {
+		text.clear();
+	}
+	if let Some(tree) = resources.get_mut::<AccessibilityTree>() {
+		tree.flush();
+	}
+	if let Some(draw) = resources.get_mut::<DebugDraw>() {
+		draw.clear();
+	}
+}
+
+fn set_fullscreen(window: &Window, resources: &mut ResourceMap, mode: FullscreenMode) {
+	window.set_fullscreen(winit_fullscreen(window, mode));
+	if let Some(event) = resources.get_mut::<Displays>().and_then(|displays| displays.set_fullscreen(mode)) {
+		push_event(resources, event);
+	}
+	refresh_displays(window, resources);
+}
+
+/// Enumerates the monitors again and sends events for whatever changed since the last time
+fn refresh_displays(window: &Window, resources: &mut ResourceMap) {
+	let enumerated = Displays::from_window(window);
+	let events = resources.get_mut::<Displays>().map(|displays| displays.update(enumerated)).unwrap_or_default();
+	for event in events {
+		push_event(resources, event);
+	}
+}
+
+/// Applies each queued command, logging the ones that fail so the rest still apply
+fn apply_window_commands(window: &Window, resources: &mut ResourceMap) {
+	let commands = match resources.get_mut::<WindowCommands>() {
+		Some(commands) if !commands.is_empty() => commands.drain().collect::<Vec<_>>(),
+		_ => return,
+	};
+	for command in commands {
+		if let Err(error) = apply_window_command(window, resources, command) {
+			log::error!("Application error: {}", error);
+		}
+	}
+}
+
+fn apply_window_command(window: &Window, resources: &mut ResourceMap, command: WindowCommand) -> Result<()> {
+	match command {
+		WindowCommand::SetCursorGrab(grab) => set_cursor_grab(window, grab)?,
+		WindowCommand::SetCursorVisible(visible) => window.set_cursor_visible(visible),
+		WindowCommand::SetCursorPosition(position) => window.set_cursor_position(position).map_err(Error::SetCursorPosition)?,
+		WindowCommand::SetCursorIcon(icon) => window.set_cursor_icon(icon),
+		WindowCommand::SetCursorImage { path, hotspot } => {
+			resources.insert(CursorImage::load(&path, hotspot)?);
+			window.set_cursor_visible(!resources.get::<Renderer>().is_some_and(Renderer::draws_cursor));
+		},
+		WindowCommand::ClearCursorImage => {
+			resources.remove::<CursorImage>();
+			window.set_cursor_visible(true);
+		},
+		WindowCommand::SetFullscreen(mode) => set_fullscreen(window, resources, mode),
+		WindowCommand::SetTitle(title) => window.set_title(&title),
+		WindowCommand::SetSize(size) => window.set_inner_size(size),
+		WindowCommand::SetMinimized(minimized) => window.set_minimized(minimized),
+		WindowCommand::SetMaximized(maximized) => window.set_maximized(maximized),
+		WindowCommand::SetAlwaysOnTop(always_on_top) => window.set_always_on_top(always_on_top),
+		WindowCommand::SetDecorations(decorations) => window.set_decorations(decorations),
+		WindowCommand::SetResizable(resizable) => window.set_resizable(resizable),
+	}
 	Ok(())
 }
@@ -0,0 +1,109 @@
+use clap::{Parser, error::ErrorKind};
+
+/// Command-line flags shared by every app built on elder, applied on top of the settings file
+#[derive(Parser, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Arguments {
+	/// Run in a window, overriding the fullscreen setting
+	#[arg(long, conflicts_with = "fullscreen")]
+	pub windowed: bool,
+
+	/// Run fullscreen, overriding the fullscreen setting
+	#[arg(long)]
+	pub fullscreen: bool,
+
+	#[arg(long)]
+	pub width: Option<u32>,
+
+	#[arg(long)]
+	pub height: Option<u32>,
+
+	/// Scene to open on startup
+	#[arg(long)]
+	pub scene: Option<String>,
+
+	/// Run without showing a window
+	#[arg(long)]
+	pub headless: bool,
+
+	/// Path to write a chrome://tracing profile of the session to on exit
+	#[arg(long)]
+	pub trace: Option<String>,
+
+	/// Path to write the session's keyboard and mouse input to on exit
+	#[arg(long)]
+	pub record: Option<String>,
+
+	/// Arguments after `--`, left for the game to parse
+	#[arg(last = true)]
+	pub game: Vec<String>,
+}
+
+impl Arguments {
+	pub fn from_env() -> Self {
+		Self::or_defaults(Self::try_parse())
+	}
+
+	pub fn from_args<I, T>(args: I) -> Self
+	where
+		I: IntoIterator<Item = T>,
+		T: Into<std::ffi::OsString> + Clone,
+	{
+		Self::or_defaults(Self::try_parse_from(args))
+	}
+
+	/// Logs arguments that failed to parse and runs with none, exiting only for `--help`
+	fn or_defaults(arguments: clap::error::Result<Self>) -> Self {
+		arguments.unwrap_or_else(|error| {
+			if matches!(error.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) {
+				error.exit();
+			}
+			log::error!("Failed to parse the command-line arguments, running with the defaults instead: {error}");
+			Self::default()
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AppConfig;
+
+	#[test]
+	pub fn parse() {
+		let arguments = Arguments::from_args(["game", "--windowed", "--width", "800", "--scene", "levels/intro.ron", "--headless"]);
+		assert!(arguments.windowed);
+		assert_eq!(arguments.width, Some(800));
+		assert_eq!(arguments.height, None);
+		assert_eq!(arguments.scene.as_deref(), Some("levels/intro.ron"));
+		assert!(arguments.headless);
+		assert!(arguments.game.is_empty());
+		assert_eq!(Arguments::from_args(["game", "--trace", "session.json"]).trace.as_deref(), Some("session.json"));
+		assert_eq!(Arguments::from_args(["game", "--record", "session.ron"]).record.as_deref(), Some("session.ron"));
+	}
+
+	#[test]
+	pub fn apply() {
+		let mut config = AppConfig {
+			is_fullscreen: true,
+			..Default::default()
+		};
+		config.apply_arguments(&Arguments::from_args(["game", "--windowed", "--height", "600"]));
+		assert!(!config.is_fullscreen);
+		assert_eq!(config.width, AppConfig::default().width);
+		assert_eq!(config.height, 600);
+	}
+
+	#[test]
+	pub fn defaults() {
+		assert_eq!(Arguments::from_args(["game"]), Arguments::default());
+		assert_eq!(Arguments::from_args(["game", "--width", "wide"]), Arguments::default());
+		assert_eq!(Arguments::from_args(["game", "--windowed", "--god-mode"]), Arguments::default());
+	}
+
+	#[test]
+	pub fn game_arguments() {
+		let arguments = Arguments::from_args(["game", "--headless", "--", "--god-mode", "level3"]);
+		assert!(arguments.headless);
+		assert_eq!(arguments.game, ["--god-mode", "level3"]);
+	}
+}
@@ -0,0 +1,126 @@
+use crate::app::Error;
+#[cfg(not(target_arch = "wasm32"))]
+use arboard::Clipboard as SystemClipboard;
+use serde::{Serialize, de::DeserializeOwned};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Browsers only expose the clipboard asynchronously, so the web target stays in-process
+#[cfg(target_arch = "wasm32")]
+struct SystemClipboard;
+
+#[cfg(target_arch = "wasm32")]
+impl SystemClipboard {
+	fn new() -> std::io::Result<Self> {
+		Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "the web target has no synchronous clipboard"))
+	}
+
+	fn get_text(&mut self) -> std::io::Result<String> {
+		Ok(String::new())
+	}
+
+	fn set_text(&mut self, _text: String) -> std::io::Result<()> {
+		Ok(())
+	}
+}
+
+/// The system clipboard, or an in-process one where none is available
+pub struct Clipboard {
+	system: Option<SystemClipboard>,
+	contents: String,
+}
+
+impl Default for Clipboard {
+	fn default() -> Self {
+		let system = SystemClipboard::new()
+			.map_err(|error| log::warn!("System clipboard is unavailable, falling back to an in-process clipboard: {}", error))
+			.ok();
+		Self { system, contents: String::new() }
+	}
+}
+
+impl Clipboard {
+	/// Creates a clipboard that never touches the system clipboard
+	pub fn in_process() -> Self {
+		Self {
+			system: None,
+			contents: String::new(),
+		}
+	}
+
+	pub fn has_system_clipboard(&self) -> bool {
+		self.system.is_some()
+	}
+
+	pub fn text(&mut self) -> Result<String> {
+		match self.system.as_mut() {
+			Some(system) => system.get_text().map_err(|error| Error::ReadClipboard(Box::new(error))),
+			None => Ok(self.contents.clone()),
+		}
+	}
+
+	pub fn set_text(&mut self, text: impl Into<String>) -> Result<()> {
+		let text = text.into();
+		match self.system.as_mut() {
+			Some(system) => system.set_text(text).map_err(|error| Error::WriteClipboard(Box::new(error))),
+			None => {
+				self.contents = text;
+				Ok(())
+			},
+		}
+	}
+
+	/// Copies serializable data, such as an entity's components, to the clipboard.
+	/// The payload is stored as text tagged with `kind`
+	/// so that pasting can tell elder payloads apart from ordinary text.
+	pub fn set_payload<T: Serialize>(&mut self, kind: &str, payload: &T) -> Result<()> {
+		let serialized = ron::to_string(payload).map_err(Error::SerializeClipboardPayload)?;
+		self.set_text(format!("{}{}\n{}", PAYLOAD_PREFIX, kind, serialized))
+	}
+
+	/// Reads a payload of the given kind, returning `None`
+	/// if the clipboard holds plain text or a payload of another kind.
+	pub fn payload<T: DeserializeOwned>(&mut self, kind: &str) -> Result<Option<T>> {
+		let text = self.text()?;
+		let serialized = match text.strip_prefix(PAYLOAD_PREFIX).and_then(|text| text.split_once('\n')) {
+			Some((payload_kind, serialized)) if payload_kind == kind => serialized,
+			_ => return Ok(None),
+		};
+		ron::from_str(serialized).map(Some).map_err(Error::DeserializeClipboardPayload)
+	}
+}
+
+const PAYLOAD_PREFIX: &str = "elder-payload:";
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde::Deserialize;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Position {
+		x: f32,
+		y: f32,
+	}
+
+	#[test]
+	pub fn text() -> Result<()> {
+		let mut clipboard = Clipboard::in_process();
+		clipboard.set_text("hello")?;
+		assert_eq!(clipboard.text()?, "hello");
+		Ok(())
+	}
+
+	#[test]
+	pub fn payload() -> Result<()> {
+		let mut clipboard = Clipboard::in_process();
+		let position = Position { x: 1.0, y: 2.0 };
+		clipboard.set_payload("position", &position)?;
+		assert_eq!(clipboard.payload::<Position>("position")?, Some(position));
+		assert_eq!(clipboard.payload::<Position>("health")?, None);
+
+		clipboard.set_text("plain text")?;
+		assert_eq!(clipboard.payload::<Position>("position")?, None);
+		Ok(())
+	}
+}
@@ -0,0 +1,802 @@
+#[cfg(feature = "inspector")]
+use crate::inspector::WorldInspector;
+use crate::{
+	accessibility::{AccessibilityTree, AccessibleNode, AccessibleRole},
+	clipboard::Clipboard,
+	cvar::CVars,
+	debug_text::DebugText,
+	frame_stats::FrameStats,
+};
+use ecs::{name::Name, resource::ResourceMap, world::World};
+use math::Color;
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+	collections::{BTreeMap, VecDeque},
+	error::Error,
+	ops::Range,
+};
+
+/// The message a command prints when it succeeds, or the error it prints when it fails
+pub type ConsoleResult = Result<String, Box<dyn Error>>;
+
+type Run = Box<dyn FnMut(&mut ResourceMap, &[ConsoleArg]) -> ConsoleResult>;
+
+type Toggle = Box<dyn Fn(&mut ResourceMap) -> Option<bool>>;
+
+/// Reads or replaces the field of a resource at a path, replacing it when given a value
+type Field = Box<dyn Fn(&mut ResourceMap, &[&str], Option<&str>) -> ConsoleResult>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleArgKind {
+	Bool,
+	Float,
+	Integer,
+	Text,
+}
+
+impl ConsoleArgKind {
+	fn parse(self, token: &str) -> Option<ConsoleArg> {
+		match self {
+			Self::Bool => match token {
+				"true" | "on" | "1" => Some(ConsoleArg::Bool(true)),
+				"false" | "off" | "0" => Some(ConsoleArg::Bool(false)),
+				_ => None,
+			},
+			Self::Float => token.parse().ok().map(ConsoleArg::Float),
+			Self::Integer => token.parse().ok().map(ConsoleArg::Integer),
+			Self::Text => Some(ConsoleArg::Text(token.to_string())),
+		}
+	}
+
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Bool => "bool",
+			Self::Float => "float",
+			Self::Integer => "integer",
+			Self::Text => "text",
+		}
+	}
+}
+
+/// An argument parsed to the type its command declared
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleArg {
+	Bool(bool),
+	Float(f64),
+	Integer(i64),
+	Text(String),
+}
+
+impl ConsoleArg {
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// The argument as a float, which includes integers
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Self::Float(value) => Some(*value),
+			Self::Integer(value) => Some(*value as f64),
+			_ => None,
+		}
+	}
+
+	pub fn as_integer(&self) -> Option<i64> {
+		match self {
+			Self::Integer(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub fn as_text(&self) -> Option<&str> {
+		match self {
+			Self::Text(value) => Some(value),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleParam {
+	pub name: String,
+	pub kind: ConsoleArgKind,
+	pub optional: bool,
+}
+
+/// A named command that systems and plugins register with the `Console`
+pub struct ConsoleCommand {
+	pub description: String,
+	params: Vec<ConsoleParam>,
+	run: Run,
+}
+
+impl ConsoleCommand {
+	pub fn new(description: impl Into<String>, run: impl FnMut(&mut ResourceMap, &[ConsoleArg]) -> ConsoleResult + 'static) -> Self {
+		Self {
+			description: description.into(),
+			params: Vec::new(),
+			run: Box::new(run),
+		}
+	}
+
+	pub fn with_arg(mut self, name: impl Into<String>, kind: ConsoleArgKind) -> Self {
+		self.params.push(ConsoleParam {
+			name: name.into(),
+			kind,
+			optional: false,
+		});
+		self
+	}
+
+	/// Adds an argument that can be left out, which must come after the required ones
+	pub fn with_optional_arg(mut self, name: impl Into<String>, kind: ConsoleArgKind) -> Self {
+		self.params.push(ConsoleParam {
+			name: name.into(),
+			kind,
+			optional: true,
+		});
+		self
+	}
+
+	pub fn params(&self) -> &[ConsoleParam] {
+		&self.params
+	}
+
+	/// The command's arguments, such as `<count: integer> [name: text]`
+	pub fn usage(&self) -> String {
+		let params = self.params.iter().map(|param| match param.optional {
+			true => format!("[{}: {}]", param.name, param.kind.name()),
+			false => format!("<{}: {}>", param.name, param.kind.name()),
+		});
+		params.collect::<Vec<_>>().join(" ")
+	}
+
+	fn parse_args(&self, tokens: &[String]) -> Result<Vec<ConsoleArg>, String> {
+		let required = self.params.iter().filter(|param| !param.optional).count();
+		if tokens.len() < required || tokens.len() > self.params.len() {
+			return Err(format!("Expected arguments: {}", self.usage()));
+		}
+		tokens
+			.iter()
+			.zip(self.params.iter())
+			.map(|(token, param)| {
+				param
+					.kind
+					.parse(token)
+					.ok_or_else(|| format!("Expected {} to be of type {}, found '{token}'", param.name, param.kind.name()))
+			})
+			.collect()
+	}
+}
+
+/// A drop-down developer console, stored as a resource and drawn through the `DebugText` resource
+#[derive(Default)]
+pub struct Console {
+	pub visible: bool,
+
+	/// The line being typed
+	pub input: String,
+
+	output: VecDeque<String>,
+	history: Vec<String>,
+	history_index: Option<usize>,
+	is_control_held: bool,
+	commands: BTreeMap<String, ConsoleCommand>,
+	toggles: BTreeMap<String, Toggle>,
+	resources: BTreeMap<String, Field>,
+}
+
+impl Console {
+	/// The keybinding action that opens and closes the console
+	pub const TOGGLE_ACTION: &'static str = "toggle_console";
+
+	/// The key that opens and closes the console when the keybindings don't bind `TOGGLE_ACTION`
+	pub const DEFAULT_TOGGLE_KEY: &'static str = "Grave";
+
+	/// How many lines of output are kept
+	pub const OUTPUT_CAPACITY: usize = 256;
+
+	/// How many lines of output are drawn above the input line
+	pub const VISIBLE_LINES: usize = 12;
+
+	const BUILTINS: [(&'static str, &'static str); 7] = [
+		("clear", "Clears the output"),
+		("cvars", "Lists cvars by an optional prefix. Type a cvar name to print it, or add a value to set it"),
+		("get", "Prints a field of a registered resource, such as `get settings.window.vsync`"),
+		("help", "Lists the commands"),
+		("set", "Sets a field of a registered resource, such as `set settings.window.vsync false`"),
+		("spawn", "Spawns an entity in the world, with an optional name"),
+		("toggle", "Toggles a debug view such as `stats`, or lists them"),
+	];
+
+	pub fn register(&mut self, name: impl Into<String>, command: ConsoleCommand) {
+		self.commands.insert(name.into(), command);
+	}
+
+	/// Registers a debug view for `toggle`, which returns whether it's now shown, or `None`
+	/// if the view isn't available
+	pub fn register_toggle(&mut self, name: impl Into<String>, toggle: impl Fn(&mut ResourceMap) -> Option<bool> + 'static) {
+		self.toggles.insert(name.into(), Box::new(toggle));
+	}
+
+	/// Lets `get` and `set` read and replace the fields of a resource by serializing it
+	pub fn register_resource<T: Serialize + DeserializeOwned + 'static>(&mut self, name: impl Into<String>) {
+		self.register_resource_with::<T>(name, |_, _| {});
+	}
+
+	/// Like `register_resource`, where `keep` copies unserialized fields into the replacement
+	pub fn register_resource_with<T: Serialize + DeserializeOwned + 'static>(&mut self, name: impl Into<String>, keep: impl Fn(&T, &mut T) + 'static) {
+		let name = name.into();
+		let resource_name = name.clone();
+		let field: Field = Box::new(move |resources, path, value| {
+			let resource = resources.get::<T>().ok_or_else(|| format!("The {resource_name} resource hasn't been inserted"))?;
+			let mut text = ron::to_string(resource)?;
+			let unknown_field = || format!("Unknown field: {}", path.join("."));
+			let range = field_range(&text, path).ok_or_else(unknown_field)?;
+			let Some(value) = value else {
+				return Ok(text[range].to_string());
+			};
+			let current = &text[range.clone()];
+			let is_option = current == "None" || current.starts_with("Some(");
+			let value = match is_option && value != "None" && !value.starts_with("Some(") {
+				true => format!("Some({value})"),
+				false => value.to_string(),
+			};
+			text.replace_range(range, &value);
+			let mut replacement = ron::from_str::<T>(&text)?;
+			keep(resource, &mut replacement);
+			let text = ron::to_string(&replacement)?;
+			let range = field_range(&text, path).ok_or_else(unknown_field)?;
+			let message = format!("{}.{} = {}", resource_name, path.join("."), &text[range]);
+			resources.insert(replacement);
+			Ok(message)
+		});
+		self.resources.insert(name, field);
+	}
+
+	pub fn output(&self) -> impl Iterator<Item = &str> {
+		self.output.iter().map(String::as_str)
+	}
+
+	pub fn history(&self) -> &[String] {
+		&self.history
+	}
+
+	pub fn print(&mut self, line: impl Into<String>) {
+		if self.output.len() == Self::OUTPUT_CAPACITY {
+			self.output.pop_front();
+		}
+		self.output.push_back(line.into());
+	}
+
+	/// Adds a typed character, leaving out control characters and the backtick
+	pub fn type_character(&mut self, character: char) {
+		if self.visible && !character.is_control() && character != '`' {
+			self.input.push(character);
+		}
+	}
+
+	/// Tracks the control key, which `Input` doesn't see while the console is open
+	pub fn set_control_held(&mut self, is_held: bool) {
+		self.is_control_held = is_held;
+	}
+
+	/// Handles a key pressed while the console is open, named the way `Input` names keys
+	pub fn press_key(&mut self, key: &str, resources: &mut ResourceMap) {
+		if self.is_control_held && matches!(key, "C" | "X" | "V") {
+			if let Err(error) = self.use_clipboard(key, resources) {
+				self.print(format!("Error: {error}"));
+			}
+			return;
+		}
+		match key {
+			"Back" => {
+				self.input.pop();
+			},
+			"Return" | "NumpadEnter" => {
+				let line = std::mem::take(&mut self.input);
+				self.execute(&line, resources);
+			},
+			"Tab" => self.autocomplete(),
+			"Up" => self.browse_history(true),
+			"Down" => self.browse_history(false),
+			"Escape" => self.visible = false,
+			_ => {},
+		}
+	}
+
+	/// Copies or cuts the input line to the `Clipboard`, or pastes its text as a single line
+	fn use_clipboard(&mut self, key: &str, resources: &mut ResourceMap) -> Result<(), Box<dyn Error>> {
+		let Some(clipboard) = resources.get_mut::<Clipboard>() else {
+			return Ok(());
+		};
+		match key {
+			"C" => clipboard.set_text(self.input.clone())?,
+			"X" => clipboard.set_text(std::mem::take(&mut self.input))?,
+			_ => {
+				let text = clipboard.text()?.lines().collect::<Vec<_>>().join(" ");
+				text.chars().for_each(|character| self.type_character(character));
+			},
+		}
+		Ok(())
+	}
+
+	/// Runs a line of input and prints it along with the result
+	pub fn execute(&mut self, line: &str, resources: &mut ResourceMap) {
+		let line = line.trim();
+		self.history_index = None;
+		if line.is_empty() {
+			return;
+		}
+		if self.history.last().map(String::as_str) != Some(line) {
+			self.history.push(line.to_string());
+		}
+		self.print(format!("> {line}"));
+		let result = match tokenize(line) {
+			Ok(tokens) => self.run(&tokens, resources),
+			Err(error) => Err(error.into()),
+		};
+		match result {
+			Ok(message) if message.is_empty() => {},
+			Ok(message) => self.print(message),
+			Err(error) => self.print(format!("Error: {error}")),
+		}
+	}
+
+	/// The command names, toggle names, or resource names that could complete the input
+	pub fn completions(&self) -> Vec<String> {
+		let words = self.input.split(' ').collect::<Vec<_>>();
+		let candidates = match words.as_slice() {
+			[prefix] => self.command_names().filter(|name| name.starts_with(prefix)).map(str::to_string).collect(),
+			["toggle", prefix] => self.toggle_names().filter(|name| name.starts_with(prefix)).map(str::to_string).collect(),
+			["get" | "set", prefix] => self.resources.keys().filter(|name| name.starts_with(prefix)).cloned().collect(),
+			_ => Vec::new(),
+		};
+		let leading = words[..words.len() - 1].iter().map(|word| format!("{word} ")).collect::<String>();
+		candidates.into_iter().map(|candidate| leading.clone() + &candidate).collect()
+	}
+
+	/// Completes the input as far as every completion agrees, adding a space when only one matches
+	pub fn autocomplete(&mut self) {
+		let completions = self.completions();
+		match completions.as_slice() {
+			[] => {},
+			[completion] => self.input = format!("{completion} "),
+			[first, rest @ ..] => {
+				// Compares characters, so the prefix never ends partway through one
+				let common = rest.iter().fold(first.len(), |length, completion| {
+					let mismatch = first[..length].char_indices().zip(completion.chars()).find(|((_, a), b)| a != b);
+					mismatch.map_or(length.min(completion.len()), |((index, _), _)| index)
+				});
+				if common > self.input.len() {
+					self.input = first[..common].to_string();
+				} else {
+					self.print(completions.join("  "));
+				}
+			},
+		}
+	}
+
+	/// Steps through the history into the input, from the most recent line when `older` is true
+	pub fn browse_history(&mut self, older: bool) {
+		let last = match self.history.len().checked_sub(1) {
+			Some(last) => last,
+			None => return,
+		};
+		self.history_index = match (self.history_index, older) {
+			(None, true) => Some(last),
+			(None, false) => None,
+			(Some(index), true) => Some(index.saturating_sub(1)),
+			(Some(index), false) if index < last => Some(index + 1),
+			(Some(_), false) => None,
+		};
+		self.input = self.history_index.map(|index| self.history[index].clone()).unwrap_or_default();
+	}
+
+	/// Prints the recent output and the input line across the top of the window while open
+	pub fn draw(&self, text: &mut DebugText) {
+		if !self.visible {
+			return;
+		}
+		let skipped = self.output.len().saturating_sub(Self::VISIBLE_LINES);
+		let output = self.output.iter().skip(skipped).cloned();
+		let lines = output.chain([format!("> {}_", self.input)]).collect::<Vec<_>>();
+		let top = DebugText::LINE_HEIGHT * (Self::VISIBLE_LINES + 1 - lines.len()) as f32;
+		text.print_lines([8.0, top], lines, Color::WHITE);
+	}
+
+	/// Publishes the input line to screen readers while the console is open
+	pub fn describe(&self, tree: &mut AccessibilityTree) {
+		if self.visible {
+			tree.push(AccessibleNode::new(AccessibleRole::TextInput, "Console").with_value(self.input.clone()).focused());
+		}
+	}
+
+	fn command_names(&self) -> impl Iterator<Item = &str> {
+		let builtins = Self::BUILTINS.iter().map(|(name, _)| *name);
+		let mut names = builtins.chain(self.commands.keys().map(String::as_str)).collect::<Vec<_>>();
+		names.sort_unstable();
+		names.into_iter()
+	}
+
+	fn toggle_names(&self) -> impl Iterator<Item = &str> {
+		let mut names = BUILTIN_VIEWS.into_iter().chain(self.toggles.keys().map(String::as_str)).collect::<Vec<_>>();
+		names.sort_unstable();
+		names.into_iter()
+	}
+
+	fn run(&mut self, tokens: &[String], resources: &mut ResourceMap) -> ConsoleResult {
+		let (name, args) = tokens.split_first().ok_or("Expected a command")?;
+		match (name.as_str(), args) {
+			("clear", []) => {
+				self.output.clear();
+				Ok(String::new())
+			},
+			("help", []) => {
+				let builtins = Self::BUILTINS.iter().map(|(name, description)| format!("{name} - {description}"));
+				let commands = self
+					.commands
+					.iter()
+					.map(|(name, command)| format!("{name} {} - {}", command.usage(), command.description));
+				Ok(builtins.chain(commands).collect::<Vec<_>>().join("\n"))
+			},
+			("spawn", [] | [_]) => {
+				let world = resources.get_mut::<World>().ok_or("There is no world resource to spawn into")?;
+				let entity = world.create_entity();
+				if let Some(name) = args.first() {
+					world.add_component(entity, Name::new(name))?;
+				}
+				Ok(format!("Spawned {entity:?}"))
+			},
+			("cvars", [] | [_]) => {
+				let cvars = resources.get::<CVars>().ok_or("There is no cvars resource")?;
+				let prefix = args.first().map_or("", String::as_str);
+				let listed = cvars.iter().filter(|(name, _)| name.starts_with(prefix));
+				Ok(listed.map(|(name, cvar)| format!("{name} = {}", cvar.value())).collect::<Vec<_>>().join("\n"))
+			},
+			("get", [path]) => self.field(resources, path, None),
+			("set", [path, value]) => self.field(resources, path, Some(value)),
+			("toggle", []) => Ok(self.toggle_names().collect::<Vec<_>>().join("  ")),
+			("toggle", [view]) => {
+				let shown = match view.as_str() {
+					"stats" => toggle_stats(resources),
+					#[cfg(feature = "inspector")]
+					"inspector" => toggle_inspector(resources),
+					view => self.toggles.get(view).ok_or_else(|| format!("Unknown view: {view}"))?(resources),
+				};
+				let shown = shown.ok_or_else(|| format!("The {view} view isn't available"))?;
+				Ok(format!("{view} {}", if shown { "shown" } else { "hidden" }))
+			},
+			(builtin, _) if Self::BUILTINS.iter().any(|(name, _)| *name == builtin) => Err(format!("Unexpected arguments for {builtin}").into()),
+			(name, args) => {
+				if let Some(command) = self.commands.get_mut(name) {
+					let args = command.parse_args(args)?;
+					return (command.run)(resources, &args);
+				}
+				let cvars = resources.get_mut::<CVars>().filter(|cvars| cvars.get(name).is_some());
+				let cvars = cvars.ok_or_else(|| format!("Unknown command: {name}. Type help to list the commands."))?;
+				match args {
+					[] => {},
+					[value] => cvars.set(name, value.as_str())?,
+					_ => return Err(format!("Expected a single value for {name}").into()),
+				}
+				let cvar = cvars.get(name).ok_or("The cvar was removed")?;
+				match cvar.description.is_empty() {
+					true => Ok(format!("{name} = {}", cvar.value())),
+					false => Ok(format!("{name} = {} ({})", cvar.value(), cvar.description)),
+				}
+			},
+		}
+	}
+
+	fn field(&self, resources: &mut ResourceMap, path: &str, value: Option<&String>) -> ConsoleResult {
+		let mut path = path.split('.');
+		let name = path.next().unwrap_or_default();
+		let field = self.resources.get(name).ok_or_else(|| format!("Unknown resource: {name}"))?;
+		field(resources, &path.collect::<Vec<_>>(), value.map(String::as_str))
+	}
+}
+
+/// The debug views the `toggle` command knows without registering them
+#[cfg(feature = "inspector")]
+const BUILTIN_VIEWS: [&str; 2] = ["inspector", "stats"];
+
+#[cfg(not(feature = "inspector"))]
+const BUILTIN_VIEWS: [&str; 1] = ["stats"];
+
+/// Splits a line into words, keeping text in double quotes together
+fn tokenize(line: &str) -> Result<Vec<String>, String> {
+	let (mut tokens, mut token, mut quoted) = (Vec::new(), String::new(), false);
+	for character in line.chars() {
+		match character {
+			'"' => quoted = !quoted,
+			character if character.is_whitespace() && !quoted => {
+				if !token.is_empty() {
+					tokens.push(std::mem::take(&mut token));
+				}
+			},
+			character => token.push(character),
+		}
+	}
+	if quoted {
+		return Err("Expected a closing quote".to_string());
+	}
+	if !token.is_empty() {
+		tokens.push(token);
+	}
+	Ok(tokens)
+}
+
+/// Where the value at a path is in serialized RON, working on the text to keep enum variants
+fn field_range(text: &str, path: &[&str]) -> Option<Range<usize>> {
+	let mut range = 0..text.len();
+	for key in path {
+		while text[range.clone()].starts_with("Some(") {
+			range = range.start + "Some(".len()..range.end - 1;
+		}
+		// Skips an enum variant's name, since ron writes structs without theirs
+		let value = &text[range.clone()];
+		let name_length = value
+			.find(|character: char| !(character.is_alphanumeric() || character == '_'))
+			.unwrap_or(value.len());
+		let open = range.start + name_length;
+		if !matches!(text.as_bytes().get(open), Some(b'(' | b'[' | b'{')) {
+			return None;
+		}
+		let (mut start, end) = (open + 1, range.end - 1);
+		let mut index = 0;
+		range = loop {
+			if start >= end {
+				return None;
+			}
+			let entry_end = find_outside_brackets(text, start..end, b',').unwrap_or(end);
+			let (entry_key, value) = match find_outside_brackets(text, start..entry_end, b':') {
+				Some(colon) => (Some(text[start..colon].trim_matches('"')), colon + 1..entry_end),
+				None => (None, start..entry_end),
+			};
+			let is_match = match entry_key {
+				Some(entry_key) => entry_key == *key,
+				None => key.parse::<usize>() == Ok(index),
+			};
+			if is_match {
+				break value;
+			}
+			(start, index) = (entry_end + 1, index + 1);
+		};
+	}
+	Some(range)
+}
+
+/// The first of the byte in the range that isn't inside a string or nested brackets
+fn find_outside_brackets(text: &str, range: Range<usize>, byte: u8) -> Option<usize> {
+	let (mut depth, mut in_string, mut escaped) = (0, false, false);
+	for index in range {
+		let current = text.as_bytes()[index];
+		match current {
+			_ if escaped => escaped = false,
+			b'\\' if in_string => escaped = true,
+			b'"' => in_string = !in_string,
+			_ if in_string => {},
+			b'(' | b'[' | b'{' => depth += 1,
+			b')' | b']' | b'}' => depth -= 1,
+			_ if depth == 0 && current == byte => return Some(index),
+			_ => {},
+		}
+	}
+	None
+}
+
+fn toggle_stats(resources: &mut ResourceMap) -> Option<bool> {
+	let stats = resources.get_mut::<FrameStats>()?;
+	stats.show_overlay = !stats.show_overlay;
+	Some(stats.show_overlay)
+}
+
+#[cfg(feature = "inspector")]
+fn toggle_inspector(resources: &mut ResourceMap) -> Option<bool> {
+	let inspector = resources.get_mut::<WorldInspector>()?;
+	inspector.visible = !inspector.visible;
+	Some(inspector.visible)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{cvar::CVar, settings::Settings};
+	use graphics::{ColorFilter, ColorVisionDeficiency, Tonemapper};
+
+	fn console() -> Console {
+		let mut console = Console::default();
+		let command = ConsoleCommand::new("Adds to the score", |resources, args| {
+			let score = resources.get_mut::<i64>().ok_or("No score")?;
+			*score += args[0].as_integer().unwrap() * args.get(1).and_then(ConsoleArg::as_integer).unwrap_or(1);
+			Ok(format!("Score is {score}"))
+		});
+		console.register(
+			"add_score",
+			command
+				.with_arg("points", ConsoleArgKind::Integer)
+				.with_optional_arg("times", ConsoleArgKind::Integer),
+		);
+		console.register_resource_with("settings", Settings::keep_unserialized);
+		console
+	}
+
+	fn last_output(console: &Console) -> &str {
+		console.output().last().unwrap()
+	}
+
+	#[test]
+	pub fn commands() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		resources.insert(0_i64);
+
+		console.execute("add_score 5", &mut resources);
+		console.execute("add_score 5 \"3\"", &mut resources);
+		assert_eq!(*resources.get::<i64>().unwrap(), 20);
+		assert_eq!(last_output(&console), "Score is 20");
+
+		console.execute("add_score five", &mut resources);
+		assert_eq!(last_output(&console), "Error: Expected points to be of type integer, found 'five'");
+		console.execute("add_score", &mut resources);
+		assert_eq!(last_output(&console), "Error: Expected arguments: <points: integer> [times: integer]");
+		console.execute("jump", &mut resources);
+		assert!(last_output(&console).starts_with("Error: Unknown command: jump"));
+
+		console.execute("help", &mut resources);
+		assert!(last_output(&console).contains("add_score <points: integer> [times: integer] - Adds to the score"));
+		console.execute("clear", &mut resources);
+		assert_eq!(console.output().count(), 0);
+	}
+
+	#[test]
+	pub fn builtins() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		resources.insert(World::default());
+		resources.insert(FrameStats::default());
+		resources.insert(Settings::default());
+
+		console.execute("spawn \"Main Camera\"", &mut resources);
+		let world = resources.get::<World>().unwrap();
+		let entity = world.entities()[0];
+		assert_eq!(world.get_component::<Name>(entity).unwrap().as_str(), "Main Camera");
+
+		console.execute("toggle stats", &mut resources);
+		assert_eq!(last_output(&console), "stats shown");
+		assert!(resources.get::<FrameStats>().unwrap().show_overlay);
+		console.execute("toggle wireframe", &mut resources);
+		assert_eq!(last_output(&console), "Error: Unknown view: wireframe");
+
+		console.execute("set settings.window.vsync false", &mut resources);
+		assert_eq!(last_output(&console), "settings.window.vsync = Some(false)");
+		assert_eq!(resources.get::<Settings>().unwrap().window.vsync, Some(false));
+		console.execute("set settings.audio.music_volume 0.25", &mut resources);
+		assert_eq!(resources.get::<Settings>().unwrap().audio.music_volume, 0.25);
+		console.execute("get settings.audio.music_volume", &mut resources);
+		assert_eq!(last_output(&console), "0.25");
+		console.execute("get settings.audio.pitch", &mut resources);
+		assert_eq!(last_output(&console), "Error: Unknown field: audio.pitch");
+	}
+
+	#[test]
+	pub fn set_keeps_enums_and_unserialized_fields() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		let mut settings = Settings::default();
+		settings.set_path("settings.toml");
+		settings.graphics.tonemapper = Some(Tonemapper::Hable);
+		settings.accessibility.color_filter = Some(ColorFilter::simulate(ColorVisionDeficiency::Tritanopia));
+		settings.keybindings.insert("jump".to_string(), "Space".to_string());
+		resources.insert(settings);
+
+		console.execute("set settings.window.width 800", &mut resources);
+		assert_eq!(last_output(&console), "settings.window.width = Some(800)");
+		console.execute("get settings.graphics.tonemapper", &mut resources);
+		assert_eq!(last_output(&console), "Some(Hable)");
+		console.execute("set settings.graphics.tonemapper Reinhard", &mut resources);
+		console.execute("set settings.accessibility.color_filter.mode Correct", &mut resources);
+		assert_eq!(last_output(&console), "settings.accessibility.color_filter.mode = Correct");
+		console.execute("get settings.keybindings.jump", &mut resources);
+		assert_eq!(last_output(&console), "\"Space\"");
+
+		let settings = resources.get::<Settings>().unwrap();
+		assert_eq!(settings.window.width, Some(800));
+		assert_eq!(settings.graphics.tonemapper, Some(Tonemapper::Reinhard));
+		assert_eq!(settings.accessibility.color_filter, Some(ColorFilter::correct(ColorVisionDeficiency::Tritanopia)));
+		assert_eq!(settings.path(), Some(std::path::Path::new("settings.toml")));
+	}
+
+	#[test]
+	pub fn cvars() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		let mut cvars = CVars::default();
+		cvars.register("physics.damping", CVar::float(0.99).with_range(0.0, 1.0).with_description("Velocity kept each step"));
+		cvars.register("physics.substeps", CVar::integer(4));
+		resources.insert(cvars);
+
+		console.execute("physics.damping", &mut resources);
+		assert_eq!(last_output(&console), "physics.damping = 0.99 (Velocity kept each step)");
+		console.execute("physics.damping 2", &mut resources);
+		assert_eq!(resources.get::<CVars>().unwrap().float("physics.damping"), Some(1.0));
+		console.execute("physics.substeps fast", &mut resources);
+		assert_eq!(last_output(&console), "Error: Failed to parse 'fast' as a integer for cvar physics.substeps");
+		console.execute("cvars physics.s", &mut resources);
+		assert_eq!(last_output(&console), "physics.substeps = 4");
+	}
+
+	#[test]
+	pub fn history_and_completion() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		console.visible = true;
+		for character in "hel`p".chars() {
+			console.type_character(character);
+		}
+		console.press_key("Return", &mut resources);
+		console.execute("clear", &mut resources);
+		console.browse_history(true);
+		assert_eq!(console.input, "clear");
+		console.browse_history(true);
+		assert_eq!(console.input, "help");
+		console.browse_history(false);
+		console.browse_history(false);
+		assert_eq!(console.input, "");
+
+		console.input = "add".to_string();
+		console.autocomplete();
+		assert_eq!(console.input, "add_score ");
+		console.input = "toggle s".to_string();
+		assert_eq!(console.completions(), ["toggle stats"]);
+		console.input = "s".to_string();
+		assert_eq!(console.completions(), ["set", "spawn"]);
+		console.autocomplete();
+		assert_eq!(last_output(&console), "set  spawn");
+
+		// Names that differ partway through a character share only the characters before it
+		console.register_resource::<u32>("café");
+		console.register_resource::<u32>("cafè");
+		console.input = "get c".to_string();
+		console.autocomplete();
+		assert_eq!(console.input, "get caf");
+		console.input = "s".to_string();
+
+		let mut text = DebugText::default();
+		console.draw(&mut text);
+		assert_eq!(text.lines().last().unwrap().text, "> s_");
+	}
+
+	#[test]
+	pub fn clipboard_shortcuts() {
+		let mut console = console();
+		let mut resources = ResourceMap::new();
+		resources.insert(Clipboard::in_process());
+		console.visible = true;
+		console.input = "get settings".to_string();
+
+		// Without control held, the letters do nothing
+		console.press_key("X", &mut resources);
+		assert_eq!(console.input, "get settings");
+
+		console.set_control_held(true);
+		console.press_key("X", &mut resources);
+		assert_eq!(console.input, "");
+		console.press_key("V", &mut resources);
+		console.press_key("V", &mut resources);
+		assert_eq!(console.input, "get settingsget settings");
+
+		// Pasted lines are joined into one and control characters are left out
+		resources.get_mut::<Clipboard>().unwrap().set_text("set settings.window.vsync\r\nfalse\t").unwrap();
+		console.input.clear();
+		console.press_key("V", &mut resources);
+		assert_eq!(console.input, "set settings.window.vsync false");
+		console.press_key("C", &mut resources);
+		assert_eq!(console.input, "set settings.window.vsync false");
+		assert_eq!(resources.get_mut::<Clipboard>().unwrap().text().unwrap(), console.input);
+	}
+}
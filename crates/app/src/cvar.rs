@@ -0,0 +1,499 @@
+use crate::settings::Settings;
+use ecs::resource::ResourceMap;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, ops::BitOr};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CVarError {
+	#[error("Failed to set cvar {0}: it's a cheat and cheats are disabled.")]
+	CheatsDisabled(String),
+
+	#[error("Failed to parse '{value}' as a {expected} for cvar {name}")]
+	InvalidValue { name: String, value: String, expected: &'static str },
+
+	#[error("Failed to set cvar {0}: it's read-only.")]
+	ReadOnly(String),
+
+	#[error("No cvar is registered with the name: {0}")]
+	UnknownCVar(String),
+}
+
+type Result<T, E = CVarError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CVarValue {
+	Bool(bool),
+	Integer(i64),
+	Float(f64),
+	Text(String),
+}
+
+impl CVarValue {
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	/// The value as a float, which includes integers
+	pub fn as_float(&self) -> Option<f64> {
+		match self {
+			Self::Float(value) => Some(*value),
+			Self::Integer(value) => Some(*value as f64),
+			_ => None,
+		}
+	}
+
+	pub fn as_integer(&self) -> Option<i64> {
+		match self {
+			Self::Integer(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub fn as_text(&self) -> Option<&str> {
+		match self {
+			Self::Text(value) => Some(value),
+			_ => None,
+		}
+	}
+
+	const fn type_name(&self) -> &'static str {
+		match self {
+			Self::Bool(_) => "bool",
+			Self::Integer(_) => "integer",
+			Self::Float(_) => "float",
+			Self::Text(_) => "text",
+		}
+	}
+
+	/// Converts a value to this value's type, such as an integer to a float, or parses text for it
+	fn convert(&self, value: &Self) -> Option<Self> {
+		match (self, value) {
+			(Self::Bool(_), Self::Bool(_)) | (Self::Integer(_), Self::Integer(_)) | (Self::Float(_), Self::Float(_)) | (Self::Text(_), Self::Text(_)) => {
+				Some(value.clone())
+			},
+			(Self::Float(_), Self::Integer(value)) => Some(Self::Float(*value as f64)),
+			(Self::Text(_), value) => Some(Self::Text(value.to_string())),
+			(_, Self::Text(text)) => self.parse(text),
+			_ => None,
+		}
+	}
+
+	fn parse(&self, text: &str) -> Option<Self> {
+		match self {
+			Self::Bool(_) => match text {
+				"true" | "on" | "1" => Some(Self::Bool(true)),
+				"false" | "off" | "0" => Some(Self::Bool(false)),
+				_ => None,
+			},
+			Self::Integer(_) => text.parse().ok().map(Self::Integer),
+			Self::Float(_) => text.parse().ok().map(Self::Float),
+			Self::Text(_) => Some(Self::Text(text.to_string())),
+		}
+	}
+}
+
+impl fmt::Display for CVarValue {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Bool(value) => write!(formatter, "{value}"),
+			Self::Integer(value) => write!(formatter, "{value}"),
+			Self::Float(value) => write!(formatter, "{value}"),
+			Self::Text(value) => formatter.write_str(value),
+		}
+	}
+}
+
+/// How a cvar can be changed and whether it's saved, combined with `|`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CVarFlags {
+	/// Saved to the settings file, unless it's also read-only
+	pub archive: bool,
+
+	/// Only changed while `CVars::cheats_enabled` is set
+	pub cheat: bool,
+
+	/// Only changed by the code that registered it
+	pub read_only: bool,
+}
+
+impl CVarFlags {
+	pub const NONE: Self = Self {
+		archive: false,
+		cheat: false,
+		read_only: false,
+	};
+
+	pub const ARCHIVE: Self = Self { archive: true, ..Self::NONE };
+
+	pub const CHEAT: Self = Self { cheat: true, ..Self::NONE };
+
+	pub const READ_ONLY: Self = Self { read_only: true, ..Self::NONE };
+}
+
+impl BitOr for CVarFlags {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Self {
+			archive: self.archive || other.archive,
+			cheat: self.cheat || other.cheat,
+			read_only: self.read_only || other.read_only,
+		}
+	}
+}
+
+/// A variable that can be tuned while the game runs, such as `physics.damping` or `render.scale`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CVar {
+	pub description: String,
+	pub flags: CVarFlags,
+	value: CVarValue,
+	default: CVarValue,
+	range: Option<(f64, f64)>,
+}
+
+impl CVar {
+	pub fn new(default: CVarValue) -> Self {
+		Self {
+			description: String::new(),
+			flags: CVarFlags::NONE,
+			value: default.clone(),
+			default,
+			range: None,
+		}
+	}
+
+	pub fn bool(default: bool) -> Self {
+		Self::new(CVarValue::Bool(default))
+	}
+
+	pub fn integer(default: i64) -> Self {
+		Self::new(CVarValue::Integer(default))
+	}
+
+	pub fn float(default: f64) -> Self {
+		Self::new(CVarValue::Float(default))
+	}
+
+	pub fn text(default: impl Into<String>) -> Self {
+		Self::new(CVarValue::Text(default.into()))
+	}
+
+	pub fn with_description(mut self, description: impl Into<String>) -> Self {
+		self.description = description.into();
+		self
+	}
+
+	pub fn with_flags(mut self, flags: CVarFlags) -> Self {
+		self.flags = flags;
+		self
+	}
+
+	/// Clamps numeric values into a range
+	pub fn with_range(mut self, min: f64, max: f64) -> Self {
+		self.range = Some((min, max));
+		self.value = self.clamp(self.value.clone());
+		self
+	}
+
+	pub fn value(&self) -> &CVarValue {
+		&self.value
+	}
+
+	pub fn default_value(&self) -> &CVarValue {
+		&self.default
+	}
+
+	pub fn range(&self) -> Option<(f64, f64)> {
+		self.range
+	}
+
+	fn clamp(&self, value: CVarValue) -> CVarValue {
+		match (value, self.range) {
+			(CVarValue::Integer(value), Some((min, max))) => CVarValue::Integer(value.clamp(min.ceil() as i64, max.floor() as i64)),
+			(CVarValue::Float(value), Some((min, max))) => CVarValue::Float(value.clamp(min, max)),
+			(value, _) => value,
+		}
+	}
+}
+
+type OnChange = Box<dyn FnMut(&mut ResourceMap, &CVarValue)>;
+
+/// Named variables for tuning the game at runtime from the console, settings file, or editor
+#[derive(Default)]
+pub struct CVars {
+	/// Allows changing cvars flagged as cheats
+	pub cheats_enabled: bool,
+
+	cvars: BTreeMap<String, CVar>,
+	pending: BTreeMap<String, CVarValue>,
+	callbacks: BTreeMap<String, Vec<OnChange>>,
+	changed: Vec<String>,
+}
+
+impl CVars {
+	/// Registers a cvar, taking the settings file's value when there is one it can be changed to
+	pub fn register(&mut self, name: impl Into<String>, mut cvar: CVar) {
+		let name = name.into();
+		let pending = self
+			.pending
+			.remove(&name)
+			.filter(|_| !cvar.flags.read_only && (!cvar.flags.cheat || self.cheats_enabled));
+		if let Some(value) = pending.and_then(|value| cvar.value.convert(&value)) {
+			cvar.value = cvar.clamp(value);
+		}
+		self.cvars.insert(name, cvar);
+	}
+
+	pub fn get(&self, name: &str) -> Option<&CVar> {
+		self.cvars.get(name)
+	}
+
+	pub fn value(&self, name: &str) -> Option<&CVarValue> {
+		self.cvars.get(name).map(CVar::value)
+	}
+
+	pub fn bool(&self, name: &str) -> Option<bool> {
+		self.value(name)?.as_bool()
+	}
+
+	pub fn integer(&self, name: &str) -> Option<i64> {
+		self.value(name)?.as_integer()
+	}
+
+	pub fn float(&self, name: &str) -> Option<f64> {
+		self.value(name)?.as_float()
+	}
+
+	pub fn text(&self, name: &str) -> Option<&str> {
+		self.value(name)?.as_text()
+	}
+
+	/// Changes a cvar as the console and editor do, converting and clamping the value.
+	/// Fails if the cvar is read-only, or is a cheat while cheats are disabled.
+	pub fn set(&mut self, name: &str, value: impl Into<CVarValue>) -> Result<()> {
+		let cheats_enabled = self.cheats_enabled;
+		let cvar = self.cvars.get(name).ok_or_else(|| CVarError::UnknownCVar(name.to_string()))?;
+		if cvar.flags.read_only {
+			return Err(CVarError::ReadOnly(name.to_string()));
+		}
+		if cvar.flags.cheat && !cheats_enabled {
+			return Err(CVarError::CheatsDisabled(name.to_string()));
+		}
+		self.set_unchecked(name, value.into())
+	}
+
+	/// Changes a cvar regardless of its flags, for the code that registered it
+	pub fn set_unchecked(&mut self, name: &str, value: CVarValue) -> Result<()> {
+		let cvar = self.cvars.get_mut(name).ok_or_else(|| CVarError::UnknownCVar(name.to_string()))?;
+		let converted = cvar.value.convert(&value).ok_or_else(|| CVarError::InvalidValue {
+			name: name.to_string(),
+			value: value.to_string(),
+			expected: cvar.value.type_name(),
+		})?;
+		let value = cvar.clamp(converted);
+		if cvar.value != value {
+			cvar.value = value;
+			if !self.changed.iter().any(|changed| changed == name) {
+				self.changed.push(name.to_string());
+			}
+		}
+		Ok(())
+	}
+
+	pub fn reset(&mut self, name: &str) -> Result<()> {
+		let default = self.cvars.get(name).ok_or_else(|| CVarError::UnknownCVar(name.to_string()))?.default.clone();
+		self.set(name, default)
+	}
+
+	/// Runs `callback` with the resources at the start of each frame after the cvar changes
+	pub fn on_change(&mut self, name: impl Into<String>, callback: impl FnMut(&mut ResourceMap, &CVarValue) + 'static) {
+		self.callbacks.entry(name.into()).or_default().push(Box::new(callback));
+	}
+
+	/// Every cvar, sorted by name
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &CVar)> {
+		self.cvars.iter().map(|(name, cvar)| (name.as_str(), cvar))
+	}
+
+	/// Applies the values in the settings' `[cvars]` table, failing on the first that can't be set
+	pub fn load_settings(&mut self, settings: &Settings) -> Result<()> {
+		for (name, value) in settings.cvars.iter() {
+			match self.cvars.contains_key(name) {
+				true => self.set(name, value.clone())?,
+				false => {
+					self.pending.insert(name.clone(), value.clone());
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Writes the values of the archived cvars to the settings' `[cvars]` table, ready to save
+	pub fn save_settings(&self, settings: &mut Settings) {
+		for (name, cvar) in self.cvars.iter().filter(|(_, cvar)| cvar.flags.archive && !cvar.flags.read_only) {
+			settings.cvars.insert(name.clone(), cvar.value.clone());
+		}
+	}
+}
+
+impl From<bool> for CVarValue {
+	fn from(value: bool) -> Self {
+		Self::Bool(value)
+	}
+}
+
+impl From<i64> for CVarValue {
+	fn from(value: i64) -> Self {
+		Self::Integer(value)
+	}
+}
+
+impl From<f64> for CVarValue {
+	fn from(value: f64) -> Self {
+		Self::Float(value)
+	}
+}
+
+impl From<&str> for CVarValue {
+	fn from(value: &str) -> Self {
+		Self::Text(value.to_string())
+	}
+}
+
+impl From<String> for CVarValue {
+	fn from(value: String) -> Self {
+		Self::Text(value)
+	}
+}
+
+/// Runs the callbacks of the cvars changed since the last call, at the start of each frame
+pub fn apply_cvar_changes(resources: &mut ResourceMap) {
+	let Some(cvars) = resources.get_mut::<CVars>() else {
+		return;
+	};
+	if cvars.changed.is_empty() {
+		return;
+	}
+	let changed = std::mem::take(&mut cvars.changed)
+		.into_iter()
+		.filter_map(|name| cvars.value(&name).cloned().map(|value| (name, value)))
+		.collect::<Vec<_>>();
+	let mut callbacks = std::mem::take(&mut cvars.callbacks);
+	for (name, value) in changed.iter() {
+		for callback in callbacks.get_mut(name).into_iter().flatten() {
+			callback(resources, value);
+		}
+	}
+
+	// Callbacks registered while these ran are kept along with them
+	if let Some(cvars) = resources.get_mut::<CVars>() {
+		for (name, mut registered) in std::mem::take(&mut cvars.callbacks) {
+			callbacks.entry(name).or_default().append(&mut registered);
+		}
+		cvars.callbacks = callbacks;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn cvars() -> CVars {
+		let mut cvars = CVars::default();
+		cvars.register("physics.damping", CVar::float(0.99).with_range(0.0, 1.0).with_flags(CVarFlags::ARCHIVE));
+		cvars.register("render.scale", CVar::float(1.0).with_range(0.5, 2.0));
+		cvars.register("ai.max_enemies", CVar::integer(8).with_range(0.0, 64.0));
+		cvars.register("god_mode", CVar::bool(false).with_flags(CVarFlags::CHEAT));
+		cvars.register("build", CVar::text("dev").with_flags(CVarFlags::READ_ONLY | CVarFlags::ARCHIVE));
+		cvars
+	}
+
+	#[test]
+	pub fn setting_values() -> Result<()> {
+		let mut cvars = cvars();
+		cvars.set("render.scale", 4.0)?;
+		assert_eq!(cvars.float("render.scale"), Some(2.0));
+		cvars.set("ai.max_enemies", "100")?;
+		assert_eq!(cvars.integer("ai.max_enemies"), Some(64));
+		cvars.set("physics.damping", 0_i64)?;
+		assert_eq!(cvars.float("physics.damping"), Some(0.0));
+		cvars.reset("physics.damping")?;
+		assert_eq!(cvars.float("physics.damping"), Some(0.99));
+
+		assert_eq!(
+			cvars.set("ai.max_enemies", "many"),
+			Err(CVarError::InvalidValue {
+				name: "ai.max_enemies".to_string(),
+				value: "many".to_string(),
+				expected: "integer",
+			})
+		);
+		assert_eq!(cvars.set("gravity", 1.0), Err(CVarError::UnknownCVar("gravity".to_string())));
+		assert_eq!(cvars.set("build", "release"), Err(CVarError::ReadOnly("build".to_string())));
+		assert_eq!(cvars.set("god_mode", true), Err(CVarError::CheatsDisabled("god_mode".to_string())));
+		cvars.cheats_enabled = true;
+		cvars.set("god_mode", "on")?;
+		assert_eq!(cvars.bool("god_mode"), Some(true));
+		Ok(())
+	}
+
+	#[test]
+	pub fn change_callbacks() -> Result<()> {
+		let mut resources = ResourceMap::new();
+		let mut cvars = cvars();
+		cvars.on_change("render.scale", |resources, value| {
+			*resources.get_mut::<f64>().unwrap() = value.as_float().unwrap();
+		});
+		cvars.set("render.scale", 0.75)?;
+		cvars.set("render.scale", 0.75)?;
+		resources.insert(cvars);
+		resources.insert(1.0_f64);
+
+		apply_cvar_changes(&mut resources);
+		assert_eq!(*resources.get::<f64>().unwrap(), 0.75);
+		*resources.get_mut::<f64>().unwrap() = 1.0;
+		apply_cvar_changes(&mut resources);
+		assert_eq!(*resources.get::<f64>().unwrap(), 1.0);
+
+		// The callback is kept for later changes
+		resources.get_mut::<CVars>().unwrap().set("render.scale", 1.5)?;
+		apply_cvar_changes(&mut resources);
+		assert_eq!(*resources.get::<f64>().unwrap(), 1.5);
+		Ok(())
+	}
+
+	#[test]
+	pub fn settings_file() -> Result<()> {
+		let mut settings = toml::from_str::<Settings>(
+			r#"
+			[cvars]
+			"physics.damping" = 0.5
+			"ai.max_enemies" = 12
+			"editor.grid" = true
+			"#,
+		)
+		.unwrap();
+		let mut cvars = cvars();
+		cvars.load_settings(&settings)?;
+		assert_eq!((cvars.float("physics.damping"), cvars.integer("ai.max_enemies")), (Some(0.5), Some(12)));
+
+		// Cvars registered later take their values from the settings too
+		cvars.register("editor.grid", CVar::bool(false).with_flags(CVarFlags::ARCHIVE));
+		assert_eq!(cvars.bool("editor.grid"), Some(true));
+
+		cvars.set("physics.damping", 0.25)?;
+		settings.cvars.clear();
+		cvars.save_settings(&mut settings);
+		assert_eq!(settings.cvars.keys().collect::<Vec<_>>(), ["editor.grid", "physics.damping"]);
+		assert_eq!(settings.cvars["physics.damping"], CVarValue::Float(0.25));
+		let saved = toml::to_string(&settings).unwrap();
+		assert_eq!(toml::from_str::<Settings>(&saved).unwrap().cvars, settings.cvars);
+		Ok(())
+	}
+}
@@ -0,0 +1,65 @@
+use math::Color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugTextLine {
+	pub text: String,
+
+	/// Top left corner of the text in logical pixels, measured from the top left of the window
+	pub position: [f32; 2],
+	pub color: Color,
+}
+
+/// Text queued for a single frame and drawn on top of it, cleared once the frame has finished
+#[derive(Default)]
+pub struct DebugText {
+	lines: Vec<DebugTextLine>,
+}
+
+impl DebugText {
+	/// Height of a line of debug text in logical pixels, used to stack lines
+	pub const LINE_HEIGHT: f32 = 16.0;
+
+	pub fn print(&mut self, position: [f32; 2], text: impl Into<String>, color: Color) {
+		self.lines.push(DebugTextLine {
+			text: text.into(),
+			position,
+			color,
+		});
+	}
+
+	/// Prints each line below the one before it, starting at the given position
+	pub fn print_lines<T: Into<String>>(&mut self, position: [f32; 2], lines: impl IntoIterator<Item = T>, color: Color) {
+		for (index, line) in lines.into_iter().enumerate() {
+			self.print([position[0], position[1] + index as f32 * Self::LINE_HEIGHT], line, color);
+		}
+	}
+
+	pub fn lines(&self) -> &[DebugTextLine] {
+		&self.lines
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.lines.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.lines.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn print_lines() {
+		let mut text = DebugText::default();
+		text.print_lines([4.0, 8.0], ["first", "second"], Color::WHITE);
+		assert_eq!(text.lines().len(), 2);
+		assert_eq!(text.lines()[1].text, "second");
+		assert_eq!(text.lines()[1].position, [4.0, 8.0 + DebugText::LINE_HEIGHT]);
+
+		text.clear();
+		assert!(text.is_empty());
+	}
+}
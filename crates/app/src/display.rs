@@ -0,0 +1,277 @@
+use winit::{
+	monitor::{MonitorHandle, VideoMode},
+	window::{Fullscreen, Window},
+};
+
+/// A resolution, color depth, and refresh rate a monitor can switch to in exclusive fullscreen
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VideoModeInfo {
+	pub width: u32,
+	pub height: u32,
+	pub bit_depth: u16,
+	pub refresh_rate_millihertz: u32,
+}
+
+impl VideoModeInfo {
+	fn from_mode(mode: &VideoMode) -> Self {
+		let size = mode.size();
+		Self {
+			width: size.width,
+			height: size.height,
+			bit_depth: mode.bit_depth(),
+			refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+		}
+	}
+
+	/// The refresh rate rounded to whole hertz, for showing in a settings menu
+	pub fn refresh_rate(&self) -> u32 {
+		(self.refresh_rate_millihertz + 500) / 1000
+	}
+}
+
+/// A connected monitor, as it was when the displays were last enumerated
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+	/// The name the system gives the monitor, which is never available on the web
+	pub name: Option<String>,
+	pub width: u32,
+	pub height: u32,
+
+	/// The top left corner on the virtual desktop, in physical pixels
+	pub position: (i32, i32),
+	pub scale_factor: f64,
+	pub refresh_rate_millihertz: Option<u32>,
+	pub is_primary: bool,
+	pub video_modes: Vec<VideoModeInfo>,
+}
+
+impl MonitorInfo {
+	fn from_handle(monitor: &MonitorHandle, primary: Option<&MonitorHandle>) -> Self {
+		let (size, position) = (monitor.size(), monitor.position());
+		let mut video_modes = monitor.video_modes().map(|mode| VideoModeInfo::from_mode(&mode)).collect::<Vec<_>>();
+		video_modes.sort_by_key(|mode| std::cmp::Reverse((mode.width * mode.height, mode.refresh_rate_millihertz, mode.bit_depth)));
+		video_modes.dedup();
+		Self {
+			name: monitor.name(),
+			width: size.width,
+			height: size.height,
+			position: (position.x, position.y),
+			scale_factor: monitor.scale_factor(),
+			refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+			is_primary: primary == Some(monitor),
+			video_modes,
+		}
+	}
+
+	/// The video mode closest to the requested one, by resolution, then refresh rate, then depth.
+	/// Without a requested refresh rate the fastest one is chosen.
+	pub fn best_video_mode(&self, width: u32, height: u32, refresh_rate_millihertz: Option<u32>) -> Option<&VideoModeInfo> {
+		self.video_modes.iter().min_by_key(|mode| {
+			let size_distance = mode.width.abs_diff(width) + mode.height.abs_diff(height);
+			let refresh_distance = match refresh_rate_millihertz {
+				Some(refresh_rate) => mode.refresh_rate_millihertz.abs_diff(refresh_rate),
+				None => u32::MAX - mode.refresh_rate_millihertz,
+			};
+			(size_distance, refresh_distance, std::cmp::Reverse(mode.bit_depth))
+		})
+	}
+}
+
+/// How the window is shown, which can be changed at runtime with `WindowCommands::set_fullscreen`
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FullscreenMode {
+	#[default]
+	Windowed,
+
+	/// Covers a monitor with a borderless window at the desktop resolution, which switches quickly.
+	/// Without a monitor index the window stays on the monitor it's on.
+	Borderless { monitor: Option<usize> },
+
+	/// Switches a monitor to the closest video mode, which can lower latency but switches slowly.
+	/// Without a refresh rate the fastest one at the resolution is used.
+	Exclusive {
+		monitor: Option<usize>,
+		width: u32,
+		height: u32,
+		refresh_rate_millihertz: Option<u32>,
+	},
+}
+
+impl FullscreenMode {
+	pub fn is_fullscreen(&self) -> bool {
+		*self != Self::Windowed
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayEvent {
+	/// A monitor was connected, disconnected, or changed resolution or scale
+	MonitorsChanged,
+
+	/// The window moved to the monitor with this index in `Displays::monitors`
+	MonitorChanged(Option<usize>),
+
+	/// The window entered or left fullscreen, or switched monitors or video modes
+	FullscreenChanged(FullscreenMode),
+}
+
+/// The connected monitors and how the window is shown on them
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Displays {
+	monitors: Vec<MonitorInfo>,
+	current: Option<usize>,
+	fullscreen: FullscreenMode,
+}
+
+impl Displays {
+	pub fn from_window(window: &Window) -> Self {
+		let handles = window.available_monitors().collect::<Vec<_>>();
+		let primary = window.primary_monitor();
+		let current = window.current_monitor();
+		Self {
+			monitors: handles.iter().map(|monitor| MonitorInfo::from_handle(monitor, primary.as_ref())).collect(),
+			current: current.and_then(|current| handles.iter().position(|monitor| *monitor == current)),
+			fullscreen: FullscreenMode::Windowed,
+		}
+	}
+
+	pub fn monitors(&self) -> &[MonitorInfo] {
+		&self.monitors
+	}
+
+	pub fn monitor(&self, index: usize) -> Option<&MonitorInfo> {
+		self.monitors.get(index)
+	}
+
+	/// The index of the monitor the window is on
+	pub fn current_index(&self) -> Option<usize> {
+		self.current
+	}
+
+	pub fn current(&self) -> Option<&MonitorInfo> {
+		self.current.and_then(|index| self.monitors.get(index))
+	}
+
+	pub fn primary_index(&self) -> Option<usize> {
+		self.monitors.iter().position(|monitor| monitor.is_primary)
+	}
+
+	pub fn fullscreen(&self) -> FullscreenMode {
+		self.fullscreen
+	}
+
+	/// Replaces the monitors with a fresh enumeration, returning the events for what changed
+	pub(crate) fn update(&mut self, enumerated: Displays) -> Vec<DisplayEvent> {
+		let mut events = Vec::new();
+		if enumerated.monitors != self.monitors {
+			events.push(DisplayEvent::MonitorsChanged);
+		}
+		if enumerated.current != self.current {
+			events.push(DisplayEvent::MonitorChanged(enumerated.current));
+		}
+		self.monitors = enumerated.monitors;
+		self.current = enumerated.current;
+		events
+	}
+
+	pub(crate) fn set_fullscreen(&mut self, fullscreen: FullscreenMode) -> Option<DisplayEvent> {
+		(fullscreen != self.fullscreen).then(|| {
+			self.fullscreen = fullscreen;
+			DisplayEvent::FullscreenChanged(fullscreen)
+		})
+	}
+}
+
+/// The winit fullscreen setting for a mode, falling back to borderless without video modes
+pub(crate) fn winit_fullscreen(window: &Window, mode: FullscreenMode) -> Option<Fullscreen> {
+	let find_monitor = |index: Option<usize>| {
+		index
+			.and_then(|index| window.available_monitors().nth(index))
+			.or_else(|| window.current_monitor())
+			.or_else(|| window.primary_monitor())
+	};
+	match mode {
+		FullscreenMode::Windowed => None,
+		FullscreenMode::Borderless { monitor } => Some(Fullscreen::Borderless(find_monitor(monitor))),
+		FullscreenMode::Exclusive {
+			monitor,
+			width,
+			height,
+			refresh_rate_millihertz,
+		} => {
+			let handle = find_monitor(monitor)?;
+			let info = MonitorInfo::from_handle(&handle, None);
+			let video_mode = info
+				.best_video_mode(width, height, refresh_rate_millihertz)
+				.and_then(|best| handle.video_modes().find(|mode| VideoModeInfo::from_mode(mode) == *best));
+			match video_mode {
+				Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+				None => {
+					log::warn!("Failed to find a {width}x{height} video mode for exclusive fullscreen, using borderless fullscreen instead");
+					Some(Fullscreen::Borderless(Some(handle)))
+				},
+			}
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn mode(width: u32, height: u32, refresh_rate: u32, bit_depth: u16) -> VideoModeInfo {
+		VideoModeInfo {
+			width,
+			height,
+			bit_depth,
+			refresh_rate_millihertz: refresh_rate * 1000,
+		}
+	}
+
+	fn monitor(video_modes: Vec<VideoModeInfo>) -> MonitorInfo {
+		MonitorInfo {
+			name: Some("Test".to_string()),
+			width: 1920,
+			height: 1080,
+			position: (0, 0),
+			scale_factor: 1.0,
+			refresh_rate_millihertz: Some(60_000),
+			is_primary: true,
+			video_modes,
+		}
+	}
+
+	#[test]
+	pub fn video_mode_selection() {
+		let monitor = monitor(vec![
+			mode(1920, 1080, 144, 32),
+			mode(1920, 1080, 60, 32),
+			mode(1920, 1080, 60, 24),
+			mode(1280, 720, 60, 32),
+		]);
+		assert_eq!(monitor.best_video_mode(1920, 1080, None), Some(&mode(1920, 1080, 144, 32)));
+		assert_eq!(monitor.best_video_mode(1920, 1080, Some(59_940)), Some(&mode(1920, 1080, 60, 32)));
+		assert_eq!(monitor.best_video_mode(1366, 768, Some(60_000)), Some(&mode(1280, 720, 60, 32)));
+		assert_eq!(mode(1920, 1080, 60, 32).refresh_rate(), 60);
+		assert_eq!(self::monitor(Vec::new()).best_video_mode(1920, 1080, None), None);
+	}
+
+	#[test]
+	pub fn display_changes() {
+		let mut displays = Displays::default();
+		let enumerated = Displays {
+			monitors: vec![monitor(Vec::new())],
+			current: Some(0),
+			..Default::default()
+		};
+		let events = displays.update(enumerated.clone());
+		assert_eq!(events, [DisplayEvent::MonitorsChanged, DisplayEvent::MonitorChanged(Some(0))]);
+		assert!(displays.update(enumerated).is_empty());
+		assert_eq!(displays.primary_index(), Some(0));
+
+		let borderless = FullscreenMode::Borderless { monitor: Some(0) };
+		assert_eq!(displays.set_fullscreen(borderless), Some(DisplayEvent::FullscreenChanged(borderless)));
+		assert_eq!(displays.set_fullscreen(borderless), None);
+		assert!(displays.fullscreen().is_fullscreen());
+	}
+}
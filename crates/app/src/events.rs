@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+/// A per-frame queue of events of a single type, cleared after every update
+pub struct Events<T> {
+	events: Vec<T>,
+}
+
+impl<T> Default for Events<T> {
+	fn default() -> Self {
+		Self { events: Vec::new() }
+	}
+}
+
+impl<T> Events<T> {
+	pub fn push(&mut self, event: T) {
+		self.events.push(event);
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &T> {
+		self.events.iter()
+	}
+
+	pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+		self.events.drain(..)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.events.is_empty()
+	}
+
+	pub fn len(&self) -> usize {
+		self.events.len()
+	}
+
+	pub fn clear(&mut self) {
+		self.events.clear();
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileDropEvent {
+	/// A file is being dragged over the window.
+	/// This is emitted once per file when multiple files are dragged.
+	Hovered(PathBuf),
+
+	/// A file was dropped onto the window
+	Dropped(PathBuf),
+
+	/// The drag left the window or was cancelled without dropping
+	HoverCancelled,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn events() {
+		let mut events = Events::default();
+		assert!(events.is_empty());
+
+		events.push(FileDropEvent::Dropped(PathBuf::from("model.gltf")));
+		events.push(FileDropEvent::HoverCancelled);
+		assert_eq!(events.len(), 2);
+		assert_eq!(events.iter().next(), Some(&FileDropEvent::Dropped(PathBuf::from("model.gltf"))));
+
+		events.clear();
+		assert!(events.is_empty());
+	}
+}
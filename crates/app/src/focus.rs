@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+/// How the app slows down while its window is unfocused, to save power
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct FocusThrottle {
+	/// Updates per second while unfocused, or `None` to keep updating as fast as when focused
+	pub update_rate: Option<f32>,
+
+	/// Tells renderers to skip drawing while unfocused, through `WindowFocus::should_render`
+	pub skip_rendering: bool,
+}
+
+impl FocusThrottle {
+	/// Keeps updating and drawing at full speed while unfocused
+	pub const DISABLED: Self = Self {
+		update_rate: None,
+		skip_rendering: false,
+	};
+
+	/// Updates ten times a second without drawing, which suits tools left in the background
+	pub const BACKGROUND: Self = Self {
+		update_rate: Some(10.0),
+		skip_rendering: true,
+	};
+
+	pub fn update_interval(&self) -> Option<Duration> {
+		self.update_rate.filter(|rate| *rate > 0.0).map(|rate| Duration::from_secs_f64(1.0 / f64::from(rate)))
+	}
+}
+
+/// Whether the window has focus, updated by the app when focus changes
+#[derive(Debug, Clone)]
+pub struct WindowFocus {
+	pub throttle: FocusThrottle,
+	focused: bool,
+	changed: bool,
+
+	/// When the next update is due while throttled, which the browser handles itself on the web
+	#[cfg(not(target_arch = "wasm32"))]
+	pub(crate) next_update: Option<std::time::Instant>,
+}
+
+impl Default for WindowFocus {
+	fn default() -> Self {
+		Self::new(FocusThrottle::default())
+	}
+}
+
+impl WindowFocus {
+	pub fn new(throttle: FocusThrottle) -> Self {
+		Self {
+			throttle,
+			focused: true,
+			changed: false,
+			#[cfg(not(target_arch = "wasm32"))]
+			next_update: None,
+		}
+	}
+
+	pub fn is_focused(&self) -> bool {
+		self.focused
+	}
+
+	/// Whether the window lost focus since the last frame
+	pub fn just_lost(&self) -> bool {
+		self.changed && !self.focused
+	}
+
+	/// Whether the window regained focus since the last frame
+	pub fn just_gained(&self) -> bool {
+		self.changed && self.focused
+	}
+
+	/// Whether renderers should draw this frame
+	pub fn should_render(&self) -> bool {
+		self.focused || !self.throttle.skip_rendering
+	}
+
+	/// The time between updates right now, which is `None` while the app updates as fast as it can
+	pub fn update_interval(&self) -> Option<Duration> {
+		if self.focused {
+			return None;
+		}
+		self.throttle.update_interval()
+	}
+
+	pub(crate) fn set_focused(&mut self, focused: bool) {
+		self.changed |= focused != self.focused;
+		self.focused = focused;
+		#[cfg(not(target_arch = "wasm32"))]
+		{
+			self.next_update = None;
+		}
+	}
+
+	pub(crate) fn end_frame(&mut self) {
+		self.changed = false;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn focus_changes() {
+		let mut focus = WindowFocus::new(FocusThrottle::BACKGROUND);
+		assert!(focus.is_focused() && focus.should_render());
+		assert_eq!(focus.update_interval(), None);
+
+		focus.set_focused(false);
+		assert!(focus.just_lost() && !focus.just_gained());
+		assert!(!focus.should_render());
+		assert_eq!(focus.update_interval(), Some(Duration::from_millis(100)));
+
+		focus.end_frame();
+		assert!(!focus.just_lost());
+		focus.set_focused(true);
+		assert!(focus.just_gained());
+
+		focus.throttle = FocusThrottle::DISABLED;
+		focus.set_focused(false);
+		assert!(focus.should_render());
+		assert_eq!(focus.update_interval(), None);
+	}
+}
@@ -0,0 +1,212 @@
+use crate::{
+	accessibility::{AccessibilityTree, AccessibleNode, AccessibleRole},
+	debug_text::DebugText,
+};
+use math::Color;
+use memory::memory_overlay_lines;
+use std::{collections::VecDeque, time::Duration};
+
+/// Measures frame times with each platform's clock, since the browser has no `Instant`
+#[derive(Default)]
+struct FrameClock {
+	#[cfg(not(target_arch = "wasm32"))]
+	previous: Option<std::time::Instant>,
+
+	#[cfg(target_arch = "wasm32")]
+	previous: Option<f64>,
+}
+
+impl FrameClock {
+	/// The time since the previous tick, which is `None` on the first tick
+	fn tick(&mut self) -> Option<Duration> {
+		#[cfg(not(target_arch = "wasm32"))]
+		let (now, elapsed) = {
+			let now = std::time::Instant::now();
+			(now, self.previous.map(|previous| now - previous))
+		};
+		#[cfg(target_arch = "wasm32")]
+		let (now, elapsed) = {
+			let now = js_sys::Date::now();
+			(now, self.previous.map(|previous| Duration::from_secs_f64((now - previous).max(0.0) / 1000.0)))
+		};
+		self.previous = Some(now);
+		elapsed
+	}
+}
+
+/// Frame times over a rolling window of recent frames
+pub struct FrameStats {
+	/// Draws the statistics through the `DebugText` resource each frame
+	pub show_overlay: bool,
+
+	frame_times: VecDeque<Duration>,
+	capacity: usize,
+	clock: FrameClock,
+}
+
+impl Default for FrameStats {
+	fn default() -> Self {
+		Self::new(Self::DEFAULT_CAPACITY)
+	}
+}
+
+impl FrameStats {
+	/// Enough frames to cover a few seconds at common refresh rates
+	pub const DEFAULT_CAPACITY: usize = 300;
+
+	/// The keybinding action that toggles the overlay
+	pub const TOGGLE_ACTION: &'static str = "toggle_frame_stats";
+
+	/// The key that toggles the overlay when the keybindings don't bind `TOGGLE_ACTION`
+	pub const DEFAULT_TOGGLE_KEY: &'static str = "F3";
+
+	/// Keeps the times of the given number of most recent frames
+	pub fn new(capacity: usize) -> Self {
+		let capacity = capacity.max(1);
+		Self {
+			show_overlay: false,
+			frame_times: VecDeque::with_capacity(capacity),
+			capacity,
+			clock: FrameClock::default(),
+		}
+	}
+
+	/// Records the time since the previous call, called by the app once per frame
+	pub fn tick(&mut self) {
+		if let Some(frame_time) = self.clock.tick() {
+			self.record(frame_time);
+		}
+	}
+
+	pub fn record(&mut self, frame_time: Duration) {
+		if self.frame_times.len() == self.capacity {
+			self.frame_times.pop_front();
+		}
+		self.frame_times.push_back(frame_time);
+	}
+
+	pub fn clear(&mut self) {
+		self.frame_times.clear();
+	}
+
+	pub fn len(&self) -> usize {
+		self.frame_times.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.frame_times.is_empty()
+	}
+
+	pub fn last_frame_time(&self) -> Duration {
+		self.frame_times.back().copied().unwrap_or_default()
+	}
+
+	pub fn average_frame_time(&self) -> Duration {
+		average(self.frame_times.iter())
+	}
+
+	/// The longest frame in the window, which shows hitches that averages hide
+	pub fn max_frame_time(&self) -> Duration {
+		self.frame_times.iter().max().copied().unwrap_or_default()
+	}
+
+	pub fn average_fps(&self) -> f64 {
+		fps(self.average_frame_time())
+	}
+
+	/// The frame rate over the slowest 1% of frames, counting at least the slowest one
+	pub fn one_percent_low_fps(&self) -> f64 {
+		let mut frame_times = self.frame_times.iter().copied().collect::<Vec<_>>();
+		frame_times.sort_unstable_by(|a, b| b.cmp(a));
+		let slowest = frame_times.len().div_ceil(100);
+		fps(average(frame_times[..slowest].iter()))
+	}
+
+	/// The statistics as lines of text, as drawn by the overlay
+	pub fn overlay_lines(&self) -> [String; 3] {
+		[
+			format!("FPS {:.1} ({:.2} ms)", self.average_fps(), milliseconds(self.average_frame_time())),
+			format!("1% low {:.1} FPS", self.one_percent_low_fps()),
+			format!("Max {:.2} ms", milliseconds(self.max_frame_time())),
+		]
+	}
+
+	/// Prints the statistics in the top left corner of the window if the overlay is shown,
+	/// followed by the heap and per-tag memory usage
+	pub fn draw_overlay(&self, text: &mut DebugText) {
+		if self.show_overlay {
+			text.print_lines([8.0, 8.0], self.overlay_lines().into_iter().chain(memory_overlay_lines()), Color::YELLOW);
+		}
+	}
+
+	/// Publishes the overlay's statistics to screen readers while it's shown
+	pub fn describe(&self, tree: &mut AccessibilityTree) {
+		if self.show_overlay {
+			tree.push(AccessibleNode::new(AccessibleRole::Label, "Frame statistics").with_value(self.overlay_lines().join(", ")));
+		}
+	}
+}
+
+fn average<'a>(frame_times: impl ExactSizeIterator<Item = &'a Duration>) -> Duration {
+	match frame_times.len() {
+		0 => Duration::ZERO,
+		count => frame_times.sum::<Duration>() / count as u32,
+	}
+}
+
+fn fps(frame_time: Duration) -> f64 {
+	if frame_time.is_zero() { 0.0 } else { frame_time.as_secs_f64().recip() }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+	duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn statistics() {
+		let mut stats = FrameStats::default();
+		assert_eq!(stats.average_fps(), 0.0);
+		assert_eq!(stats.one_percent_low_fps(), 0.0);
+
+		// 198 smooth frames and two hitches, the slowest 1% of 200 frames
+		for _ in 0..198 {
+			stats.record(Duration::from_millis(10));
+		}
+		stats.record(Duration::from_millis(40));
+		stats.record(Duration::from_millis(60));
+		assert_eq!(stats.len(), 200);
+		assert_eq!(stats.average_frame_time(), Duration::from_micros(10_400));
+		assert_eq!(stats.max_frame_time(), Duration::from_millis(60));
+		assert!((stats.one_percent_low_fps() - 20.0).abs() < 1e-9);
+		assert_eq!(stats.last_frame_time(), Duration::from_millis(60));
+	}
+
+	#[test]
+	pub fn rolling_window() {
+		let mut stats = FrameStats::new(3);
+		for milliseconds in [50, 10, 10, 10] {
+			stats.record(Duration::from_millis(milliseconds));
+		}
+		assert_eq!(stats.len(), 3);
+		assert_eq!(stats.max_frame_time(), Duration::from_millis(10));
+		assert!((stats.average_fps() - 100.0).abs() < 1e-9);
+	}
+
+	#[test]
+	pub fn overlay() {
+		let mut stats = FrameStats::default();
+		stats.record(Duration::from_millis(20));
+		let mut text = DebugText::default();
+		stats.draw_overlay(&mut text);
+		assert!(text.is_empty());
+
+		stats.show_overlay = true;
+		stats.draw_overlay(&mut text);
+		assert!(text.lines().len() >= 3);
+		assert_eq!(text.lines()[0].text, "FPS 50.0 (20.00 ms)");
+	}
+}
@@ -0,0 +1,380 @@
+use crate::app::Error;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GamepadId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GamepadInfo {
+	pub id: GamepadId,
+	pub name: String,
+	pub supports_rumble: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GamepadEvent {
+	Connected(GamepadInfo),
+	Disconnected(GamepadId),
+}
+
+/// How hard a gamepad's two rumble motors spin, each from 0 to 1
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rumble {
+	/// The heavy motor, for deep rumbles such as explosions and engines
+	pub low_frequency: f32,
+
+	/// The light motor, for sharp buzzes such as gunfire and footsteps
+	pub high_frequency: f32,
+}
+
+impl Rumble {
+	pub const OFF: Self = Self::new(0.0, 0.0);
+
+	pub const fn new(low_frequency: f32, high_frequency: f32) -> Self {
+		Self { low_frequency, high_frequency }
+	}
+
+	fn lerp(&self, other: &Self, t: f32) -> Self {
+		Self::new(
+			self.low_frequency + (other.low_frequency - self.low_frequency) * t,
+			self.high_frequency + (other.high_frequency - self.high_frequency) * t,
+		)
+	}
+
+	fn scaled(&self, scale: f32) -> Self {
+		Self::new((self.low_frequency * scale).clamp(0.0, 1.0), (self.high_frequency * scale).clamp(0.0, 1.0))
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RumbleStep {
+	pub low_frequency: f32,
+	pub high_frequency: f32,
+	pub seconds: f32,
+
+	/// Ramps toward the next step's strength instead of holding, ending at rest after the last
+	#[serde(default)]
+	pub fade: bool,
+}
+
+impl RumbleStep {
+	pub fn rumble(&self) -> Rumble {
+		Rumble::new(self.low_frequency, self.high_frequency)
+	}
+}
+
+/// A timed series of motor strengths, loaded from a RON file
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RumblePattern {
+	pub steps: Vec<RumbleStep>,
+
+	/// How many more times the steps play after the first time
+	#[serde(default)]
+	pub repeat: u32,
+}
+
+impl RumblePattern {
+	/// Holds one strength for the given number of seconds
+	pub fn constant(rumble: Rumble, seconds: f32) -> Self {
+		Self {
+			steps: vec![RumbleStep {
+				low_frequency: rumble.low_frequency,
+				high_frequency: rumble.high_frequency,
+				seconds,
+				fade: false,
+			}],
+			repeat: 0,
+		}
+	}
+
+	/// A jolt that dies away, scaled by `strength` from 0 to 1, such as by an impact's speed
+	pub fn impact(strength: f32) -> Self {
+		let strength = strength.clamp(0.0, 1.0);
+		Self {
+			steps: vec![
+				RumbleStep {
+					low_frequency: strength,
+					high_frequency: strength,
+					seconds: 0.05,
+					fade: false,
+				},
+				RumbleStep {
+					low_frequency: strength,
+					high_frequency: strength * 0.5,
+					seconds: 0.15 + 0.2 * strength,
+					fade: true,
+				},
+			],
+			repeat: 0,
+		}
+	}
+
+	pub fn parse(source: &str) -> Result<Self, ron::error::SpannedError> {
+		ron::from_str(source)
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let source = fs::read_to_string(path).map_err(|error| Error::ReadRumblePattern(error, path.display().to_string()))?;
+		Self::parse(&source).map_err(|error| Error::ParseRumblePattern(error, path.display().to_string()))
+	}
+
+	/// Seconds the pattern plays for, including repeats
+	pub fn duration(&self) -> f32 {
+		self.cycle_duration() * (self.repeat + 1) as f32
+	}
+
+	/// The motor strengths a number of seconds in, or `None` once the pattern has finished
+	pub fn rumble_at(&self, seconds: f32) -> Option<Rumble> {
+		let cycle = self.cycle_duration();
+		if seconds < 0.0 || seconds >= self.duration() || cycle <= 0.0 {
+			return None;
+		}
+		let (is_last_cycle, mut remaining) = (seconds >= cycle * self.repeat as f32, seconds % cycle);
+		for (index, step) in self.steps.iter().enumerate() {
+			if remaining >= step.seconds {
+				remaining -= step.seconds;
+				continue;
+			}
+			if !step.fade {
+				return Some(step.rumble());
+			}
+			let next = match self.steps.get(index + 1) {
+				Some(next) => next.rumble(),
+				None if is_last_cycle => Rumble::OFF,
+				None => self.steps[0].rumble(),
+			};
+			return Some(step.rumble().lerp(&next, remaining / step.seconds));
+		}
+		None
+	}
+
+	fn cycle_duration(&self) -> f32 {
+		self.steps.iter().map(|step| step.seconds.max(0.0)).sum()
+	}
+}
+
+/// A gamepad library the app polls each frame, so games don't depend on a particular one
+pub trait GamepadBackend {
+	fn name(&self) -> &str;
+
+	/// The gamepads connected or disconnected since the last poll
+	fn poll(&mut self) -> Vec<GamepadEvent>;
+
+	/// Spins the motors at these strengths until they're set again
+	fn set_rumble(&mut self, gamepad: GamepadId, rumble: Rumble);
+}
+
+/// The connected gamepads and the rumble playing on them
+pub struct Gamepads {
+	/// Scales every rumble, such as from a vibration slider, where 0 turns rumble off
+	pub rumble_scale: f32,
+	backend: Option<Box<dyn GamepadBackend>>,
+	connected: Vec<GamepadInfo>,
+	playing: BTreeMap<GamepadId, (RumblePattern, f32)>,
+	motors: BTreeMap<GamepadId, Rumble>,
+}
+
+impl Default for Gamepads {
+	fn default() -> Self {
+		Self {
+			rumble_scale: 1.0,
+			backend: None,
+			connected: Vec::new(),
+			playing: BTreeMap::new(),
+			motors: BTreeMap::new(),
+		}
+	}
+}
+
+impl Gamepads {
+	pub fn new(backend: impl GamepadBackend + 'static) -> Self {
+		Self::from_boxed(Box::new(backend))
+	}
+
+	pub fn from_boxed(backend: Box<dyn GamepadBackend>) -> Self {
+		Self {
+			backend: Some(backend),
+			..Default::default()
+		}
+	}
+
+	pub fn backend_name(&self) -> Option<&str> {
+		self.backend.as_ref().map(|backend| backend.name())
+	}
+
+	pub fn connected(&self) -> &[GamepadInfo] {
+		&self.connected
+	}
+
+	pub fn gamepad(&self, id: GamepadId) -> Option<&GamepadInfo> {
+		self.connected.iter().find(|gamepad| gamepad.id == id)
+	}
+
+	/// Holds the motors at a strength for a number of seconds, replacing what was playing
+	pub fn rumble(&mut self, id: GamepadId, rumble: Rumble, seconds: f32) {
+		self.play_pattern(id, RumblePattern::constant(rumble, seconds));
+	}
+
+	/// Plays a pattern from the start, replacing whatever was playing
+	pub fn play_pattern(&mut self, id: GamepadId, pattern: RumblePattern) {
+		if self.gamepad(id).is_some_and(|gamepad| gamepad.supports_rumble) {
+			self.playing.insert(id, (pattern, 0.0));
+		}
+	}
+
+	/// Plays a pattern on every connected gamepad, for games that don't track which is in use
+	pub fn play_pattern_on_all(&mut self, pattern: &RumblePattern) {
+		let ids = self.connected.iter().map(|gamepad| gamepad.id).collect::<Vec<_>>();
+		ids.into_iter().for_each(|id| self.play_pattern(id, pattern.clone()));
+	}
+
+	pub fn stop_rumble(&mut self, id: GamepadId) {
+		self.playing.remove(&id);
+	}
+
+	pub fn is_rumbling(&self, id: GamepadId) -> bool {
+		self.playing.contains_key(&id)
+	}
+
+	/// The strength the motors were last set to
+	pub fn motors(&self, id: GamepadId) -> Rumble {
+		self.motors.get(&id).copied().unwrap_or_default()
+	}
+
+	/// Polls for connection changes, then sets every gamepad's motors from its pattern
+	pub(crate) fn update(&mut self, delta_time: f32) -> Vec<GamepadEvent> {
+		let events = self.backend.as_mut().map(|backend| backend.poll()).unwrap_or_default();
+		for event in events.iter() {
+			match event {
+				GamepadEvent::Connected(gamepad) => {
+					self.connected.retain(|connected| connected.id != gamepad.id);
+					self.connected.push(gamepad.clone());
+				},
+				GamepadEvent::Disconnected(id) => {
+					self.connected.retain(|connected| connected.id != *id);
+					self.playing.remove(id);
+					self.motors.remove(id);
+				},
+			}
+		}
+		let mut strengths = BTreeMap::new();
+		self.playing.retain(|id, (pattern, elapsed)| {
+			let Some(rumble) = pattern.rumble_at(*elapsed) else {
+				return false;
+			};
+			strengths.insert(*id, rumble);
+			*elapsed += delta_time;
+			true
+		});
+		let ids = self
+			.connected
+			.iter()
+			.filter(|gamepad| gamepad.supports_rumble)
+			.map(|gamepad| gamepad.id)
+			.collect::<Vec<_>>();
+		for id in ids {
+			let rumble = strengths.get(&id).copied().unwrap_or_default().scaled(self.rumble_scale);
+			self.set_motors(id, rumble);
+		}
+		events
+	}
+
+	/// Stops every pattern and motor right away, such as when the app is suspended or closed
+	pub(crate) fn stop_all(&mut self) {
+		self.playing.clear();
+		let ids = self.motors.keys().copied().collect::<Vec<_>>();
+		ids.into_iter().for_each(|id| self.set_motors(id, Rumble::OFF));
+	}
+
+	fn set_motors(&mut self, id: GamepadId, rumble: Rumble) {
+		if self.motors(id) == rumble {
+			return;
+		}
+		if let Some(backend) = self.backend.as_mut() {
+			backend.set_rumble(id, rumble);
+		}
+		self.motors.insert(id, rumble);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::RefCell, rc::Rc};
+
+	#[derive(Default)]
+	struct Recorder {
+		pending: Vec<GamepadEvent>,
+		rumbles: Rc<RefCell<Vec<(GamepadId, Rumble)>>>,
+	}
+
+	impl GamepadBackend for Recorder {
+		fn name(&self) -> &str {
+			"recorder"
+		}
+
+		fn poll(&mut self) -> Vec<GamepadEvent> {
+			std::mem::take(&mut self.pending)
+		}
+
+		fn set_rumble(&mut self, gamepad: GamepadId, rumble: Rumble) {
+			self.rumbles.borrow_mut().push((gamepad, rumble));
+		}
+	}
+
+	fn pad(id: usize, supports_rumble: bool) -> GamepadInfo {
+		GamepadInfo {
+			id: GamepadId(id),
+			name: format!("Pad {id}"),
+			supports_rumble,
+		}
+	}
+
+	#[test]
+	pub fn patterns() {
+		let pattern = RumblePattern::parse(
+			"(steps: [(low_frequency: 1.0, high_frequency: 0.5, seconds: 0.5), (low_frequency: 0.5, high_frequency: 0.0, seconds: 1.0, fade: true)], repeat: 1)",
+		)
+		.unwrap();
+		assert_eq!(pattern.duration(), 3.0);
+		assert_eq!(pattern.rumble_at(0.25), Some(Rumble::new(1.0, 0.5)));
+		// Fades toward the first step while repeating, then to rest at the very end
+		assert_eq!(pattern.rumble_at(1.0), Some(Rumble::new(0.75, 0.25)));
+		assert_eq!(pattern.rumble_at(2.5), Some(Rumble::new(0.25, 0.0)));
+		assert_eq!(pattern.rumble_at(3.0), None);
+		assert_eq!(RumblePattern::default().rumble_at(0.0), None);
+		assert!(RumblePattern::impact(2.0).steps.iter().all(|step| step.low_frequency <= 1.0));
+	}
+
+	#[test]
+	pub fn playback() {
+		let rumbles = Rc::new(RefCell::new(Vec::new()));
+		let backend = Recorder {
+			pending: vec![GamepadEvent::Connected(pad(0, true)), GamepadEvent::Connected(pad(1, false))],
+			rumbles: rumbles.clone(),
+		};
+		let mut gamepads = Gamepads::new(backend);
+		assert_eq!(gamepads.update(0.1).len(), 2);
+		assert_eq!(gamepads.connected().len(), 2);
+
+		gamepads.rumble_scale = 0.5;
+		gamepads.play_pattern_on_all(&RumblePattern::constant(Rumble::new(1.0, 0.5), 0.15));
+		assert!(gamepads.is_rumbling(GamepadId(0)) && !gamepads.is_rumbling(GamepadId(1)));
+		gamepads.update(0.1);
+		gamepads.update(0.1);
+		assert_eq!(gamepads.motors(GamepadId(0)), Rumble::new(0.5, 0.25));
+		gamepads.update(0.1);
+		assert!(!gamepads.is_rumbling(GamepadId(0)));
+		assert_eq!(*rumbles.borrow(), [(GamepadId(0), Rumble::new(0.5, 0.25)), (GamepadId(0), Rumble::OFF)]);
+
+		gamepads.rumble(GamepadId(0), Rumble::new(1.0, 1.0), 10.0);
+		gamepads.update(0.1);
+		gamepads.stop_all();
+		assert_eq!(gamepads.motors(GamepadId(0)), Rumble::OFF);
+		assert_eq!(rumbles.borrow().len(), 4);
+	}
+}
@@ -0,0 +1,284 @@
+use std::{ffi::c_void, slice};
+
+/// Bumped whenever `GameModuleApi` changes, so modules built against another version are rejected
+pub const GAME_MODULE_ABI_VERSION: u32 = 1;
+
+/// The function `export_game_module!` exports from a game dylib, returning its `GameModuleApi`
+pub const GAME_MODULE_SYMBOL: &str = "elder_game_module";
+
+/// Receives the bytes a game module serializes its state to
+pub type StateWriter = unsafe extern "C" fn(context: *mut c_void, data: *const u8, length: usize);
+
+/// The C ABI boundary between the app and a game module, exported with `export_game_module!`
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GameModuleApi {
+	pub abi_version: u32,
+
+	/// Creates the game, restoring it from serialized state when `length` is nonzero
+	pub create: unsafe extern "C" fn(state: *const u8, length: usize) -> *mut c_void,
+
+	pub update: unsafe extern "C" fn(game: *mut c_void, delta_time: f32),
+
+	/// Serializes the game by passing its state to `write` along with `context`
+	pub save: unsafe extern "C" fn(game: *mut c_void, write: StateWriter, context: *mut c_void),
+
+	pub destroy: unsafe extern "C" fn(game: *mut c_void),
+}
+
+/// Gameplay code that can be built as a dylib and reloaded while the app keeps running
+pub trait GameModule {
+	/// Creates the game, restoring the state saved by the previous build of the module if any
+	fn load(state: Option<&[u8]>) -> Self;
+
+	fn update(&mut self, delta_time: f32);
+
+	/// Serializes everything that should survive a reload, such as the world's entities as a scene
+	fn save(&self) -> Vec<u8>;
+}
+
+/// Exports a `GameModule` from a game crate built as a `cdylib`
+#[macro_export]
+macro_rules! export_game_module {
+	($game:ty) => {
+		#[no_mangle]
+		pub extern "C" fn elder_game_module() -> $crate::GameModuleApi {
+			unsafe extern "C" fn create(state: *const u8, length: usize) -> *mut std::ffi::c_void {
+				let state = (length > 0).then(|| std::slice::from_raw_parts(state, length));
+				Box::into_raw(Box::new(<$game as $crate::GameModule>::load(state))).cast()
+			}
+
+			unsafe extern "C" fn update(game: *mut std::ffi::c_void, delta_time: f32) {
+				<$game as $crate::GameModule>::update(&mut *game.cast::<$game>(), delta_time);
+			}
+
+			unsafe extern "C" fn save(game: *mut std::ffi::c_void, write: $crate::StateWriter, context: *mut std::ffi::c_void) {
+				let state = <$game as $crate::GameModule>::save(&*game.cast::<$game>());
+				write(context, state.as_ptr(), state.len());
+			}
+
+			unsafe extern "C" fn destroy(game: *mut std::ffi::c_void) {
+				drop(Box::from_raw(game.cast::<$game>()));
+			}
+
+			$crate::GameModuleApi {
+				abi_version: $crate::GAME_MODULE_ABI_VERSION,
+				create,
+				update,
+				save,
+				destroy,
+			}
+		}
+	};
+}
+
+/// A game created through a module's API, destroyed when dropped
+pub struct GameInstance {
+	api: GameModuleApi,
+	game: *mut c_void,
+}
+
+impl GameInstance {
+	/// Creates the game, restoring it from serialized state unless `state` is empty
+	///
+	/// # Safety
+	///
+	/// The API's functions must be the ones `export_game_module!` generates for a single game type,
+	/// and the code they point into must stay loaded until the instance is dropped or reloaded.
+	pub unsafe fn new(api: GameModuleApi, state: &[u8]) -> Self {
+		let game = unsafe { (api.create)(state.as_ptr(), state.len()) };
+		Self { api, game }
+	}
+
+	pub fn update(&mut self, delta_time: f32) {
+		unsafe { (self.api.update)(self.game, delta_time) }
+	}
+
+	pub fn save(&self) -> Vec<u8> {
+		unsafe extern "C" fn write(context: *mut c_void, data: *const u8, length: usize) {
+			if length > 0 {
+				(*context.cast::<Vec<u8>>()).extend_from_slice(slice::from_raw_parts(data, length));
+			}
+		}
+		let mut state = Vec::new();
+		unsafe { (self.api.save)(self.game, write, (&mut state as *mut Vec<u8>).cast()) };
+		state
+	}
+
+	/// Saves the game, destroys it, and recreates it from the saved state through another build
+	///
+	/// # Safety
+	///
+	/// The new API must meet the same requirements as in `new`, and the previous build's code must
+	/// stay loaded until this returns.
+	pub unsafe fn reload(&mut self, api: GameModuleApi) {
+		let state = self.save();
+		unsafe { (self.api.destroy)(self.game) };
+		self.game = unsafe { (api.create)(state.as_ptr(), state.len()) };
+		self.api = api;
+	}
+}
+
+impl Drop for GameInstance {
+	fn drop(&mut self) {
+		unsafe { (self.api.destroy)(self.game) }
+	}
+}
+
+#[cfg(feature = "hot-reload")]
+pub use self::host::*;
+
+#[cfg(feature = "hot-reload")]
+mod host {
+	use super::{GAME_MODULE_ABI_VERSION, GAME_MODULE_SYMBOL, GameInstance, GameModuleApi};
+	use crate::app::Error;
+	use libloading::Library;
+	use std::{
+		fs,
+		path::{Path, PathBuf},
+		time::SystemTime,
+	};
+
+	type Result<T, E = Error> = std::result::Result<T, E>;
+
+	/// Runs a game module from a dylib, reloading it when the dylib is rebuilt.
+	///
+	/// The dylib is copied before it is opened, so the compiler can overwrite the original.
+	pub struct GameModuleHost {
+		// Declared before the library so the game is destroyed before its code is unloaded
+		game: GameInstance,
+		library: LoadedLibrary,
+		path: PathBuf,
+		reloads: usize,
+	}
+
+	struct LoadedLibrary {
+		// Only held so the module's code stays loaded
+		_library: Library,
+		copy: PathBuf,
+		modified: SystemTime,
+		api: GameModuleApi,
+	}
+
+	impl Drop for LoadedLibrary {
+		fn drop(&mut self) {
+			let _ = fs::remove_file(&self.copy);
+		}
+	}
+
+	impl GameModuleHost {
+		pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+			let path = path.into();
+			let library = open(&path, 0)?;
+			// Safety: `open` checked the module's ABI version, and the host keeps the library
+			// loaded until after the game is dropped
+			let game = unsafe { GameInstance::new(library.api, &[]) };
+			Ok(Self { game, library, path, reloads: 0 })
+		}
+
+		pub fn path(&self) -> &Path {
+			&self.path
+		}
+
+		/// How many times the module has been reloaded
+		pub const fn reloads(&self) -> usize {
+			self.reloads
+		}
+
+		pub fn update(&mut self, delta_time: f32) {
+			self.game.update(delta_time);
+		}
+
+		/// Reloads the module if the dylib was modified since it was loaded
+		pub fn reload_if_changed(&mut self) -> Result<bool> {
+			if modified(&self.path)? == self.library.modified {
+				return Ok(false);
+			}
+			self.reload()?;
+			Ok(true)
+		}
+
+		/// Opens the dylib again and moves the game's serialized state into the new build.
+		/// If the new build fails to load, the old one keeps running.
+		pub fn reload(&mut self) -> Result<()> {
+			let library = open(&self.path, self.reloads + 1)?;
+			// Safety: as in `load`, and the old library is replaced once the game has moved over
+			unsafe { self.game.reload(library.api) };
+			self.library = library;
+			self.reloads += 1;
+			Ok(())
+		}
+	}
+
+	fn modified(path: &Path) -> Result<SystemTime> {
+		fs::metadata(path)
+			.and_then(|metadata| metadata.modified())
+			.map_err(|error| Error::ReadGameModuleMetadata(error, path.display().to_string()))
+	}
+
+	fn open(path: &Path, generation: usize) -> Result<LoadedLibrary> {
+		let modified = modified(path)?;
+		let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+		let copy = std::env::temp_dir().join(format!("elder_{}_{}_{}", std::process::id(), generation, file_name));
+		fs::copy(path, &copy).map_err(|error| Error::CopyGameModule(error, path.display().to_string()))?;
+		let load_error = |error: libloading::Error| Error::LoadGameModule(Box::new(error), path.display().to_string());
+		let library = unsafe { Library::new(&copy) }.map_err(load_error)?;
+		let api = unsafe { library.get::<extern "C" fn() -> GameModuleApi>(GAME_MODULE_SYMBOL.as_bytes()) }.map_err(load_error)?();
+		if api.abi_version != GAME_MODULE_ABI_VERSION {
+			return Err(Error::IncompatibleGameModule(api.abi_version, GAME_MODULE_ABI_VERSION));
+		}
+		Ok(LoadedLibrary {
+			_library: library,
+			copy,
+			modified,
+			api,
+		})
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use super::*;
+
+		#[test]
+		pub fn missing_module() {
+			let path = std::env::temp_dir().join("elder_missing_game_module.so");
+			assert!(matches!(GameModuleHost::load(&path), Err(Error::ReadGameModuleMetadata(..))));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, PartialEq)]
+	struct Counter(u32);
+
+	impl GameModule for Counter {
+		fn load(state: Option<&[u8]>) -> Self {
+			Self(state.map(|state| u32::from_le_bytes(state.try_into().unwrap())).unwrap_or_default())
+		}
+
+		fn update(&mut self, _delta_time: f32) {
+			self.0 += 1;
+		}
+
+		fn save(&self) -> Vec<u8> {
+			self.0.to_le_bytes().to_vec()
+		}
+	}
+
+	export_game_module!(Counter);
+
+	#[test]
+	pub fn reloading_preserves_state() {
+		let api = elder_game_module();
+		// Safety: the API is exported from this crate, so its code is always loaded
+		let mut game = unsafe { GameInstance::new(api, &[]) };
+		game.update(0.1);
+		game.update(0.1);
+		unsafe { game.reload(api) };
+		game.update(0.1);
+		assert_eq!(game.save(), 3_u32.to_le_bytes());
+	}
+}
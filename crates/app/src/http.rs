@@ -0,0 +1,275 @@
+use crate::{events::Events, io_runtime::IoRuntime};
+use ecs::resource::ResourceMap;
+use std::{
+	io::{self, Read},
+	sync::atomic::{AtomicU64, Ordering},
+	time::Duration,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HttpError {
+	#[error("Failed to read the response body from url: {1}")]
+	ReadBody(#[source] io::Error, String),
+
+	#[error("Failed to send a request to url: {1}")]
+	Send(#[source] Box<ureq::Transport>, String),
+}
+
+type Result<T, E = HttpError> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+	Get,
+	Post,
+}
+
+impl HttpMethod {
+	const fn as_str(self) -> &'static str {
+		match self {
+			Self::Get => "GET",
+			Self::Post => "POST",
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+	pub method: HttpMethod,
+	pub url: String,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+	pub fn get(url: impl Into<String>) -> Self {
+		Self {
+			method: HttpMethod::Get,
+			url: url.into(),
+			headers: Vec::new(),
+			body: Vec::new(),
+		}
+	}
+
+	pub fn post(url: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+		Self {
+			method: HttpMethod::Post,
+			body: body.into(),
+			..Self::get(url)
+		}
+	}
+
+	pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.headers.push((name.into(), value.into()));
+		self
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+	pub status: u16,
+	pub headers: Vec<(String, String)>,
+	pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+	pub fn is_success(&self) -> bool {
+		(200..300).contains(&self.status)
+	}
+
+	/// The value of the first header with the given name, ignoring case
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers
+			.iter()
+			.find(|(header, _)| header.eq_ignore_ascii_case(name))
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// The body as text, with invalid UTF-8 replaced
+	pub fn text(&self) -> String {
+		String::from_utf8_lossy(&self.body).into_owned()
+	}
+}
+
+/// Identifies a request sent with an `HttpClient`, to match it with its `HttpResponseEvent`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpRequestId(u64);
+
+/// Pushed to the `Events<HttpResponseEvent>` resource at the start of a frame once a request ends
+#[derive(Debug)]
+pub struct HttpResponseEvent {
+	pub request: HttpRequestId,
+	pub response: Result<HttpResponse>,
+}
+
+/// Sends HTTP and HTTPS requests, each on its own thread, delivering responses each frame
+pub struct HttpClient {
+	agent: ureq::Agent,
+	next_request: AtomicU64,
+}
+
+impl Default for HttpClient {
+	fn default() -> Self {
+		Self::new(Self::DEFAULT_CONNECT_TIMEOUT, Self::DEFAULT_TIMEOUT, "elder")
+	}
+}
+
+impl HttpClient {
+	pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+	pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+	/// A client that gives up connecting after `connect_timeout`, and on a request after `timeout`
+	pub fn new(connect_timeout: Duration, timeout: Duration, user_agent: &str) -> Self {
+		Self {
+			agent: ureq::AgentBuilder::new()
+				.timeout_connect(connect_timeout)
+				.timeout(timeout)
+				.user_agent(user_agent)
+				.build(),
+			next_request: AtomicU64::new(0),
+		}
+	}
+
+	pub fn get(&self, runtime: &IoRuntime, url: impl Into<String>) -> HttpRequestId {
+		self.send(runtime, HttpRequest::get(url))
+	}
+
+	pub fn post(&self, runtime: &IoRuntime, url: impl Into<String>, body: impl Into<Vec<u8>>) -> HttpRequestId {
+		self.send(runtime, HttpRequest::post(url, body))
+	}
+
+	/// Sends a request, pushing its `HttpResponseEvent` once it finishes
+	pub fn send(&self, runtime: &IoRuntime, request: HttpRequest) -> HttpRequestId {
+		let id = HttpRequestId(self.next_request.fetch_add(1, Ordering::Relaxed));
+		self.send_then(runtime, request, move |resources, response| {
+			let event = HttpResponseEvent { request: id, response };
+			match resources.get_mut::<Events<HttpResponseEvent>>() {
+				Some(events) => events.push(event),
+				None => {
+					let mut events = Events::default();
+					events.push(event);
+					resources.insert(events);
+				},
+			}
+		});
+		id
+	}
+
+	/// Sends a request, handing its response to `on_response` with the resources once it finishes
+	pub fn send_then(&self, runtime: &IoRuntime, request: HttpRequest, on_response: impl FnOnce(&mut ResourceMap, Result<HttpResponse>) + Send + 'static) {
+		let agent = self.agent.clone();
+		runtime.spawn_blocking_then(move || execute(&agent, &request), on_response);
+	}
+}
+
+/// Sends a request and waits for the whole response, treating error statuses as responses
+fn execute(agent: &ureq::Agent, request: &HttpRequest) -> Result<HttpResponse> {
+	let mut pending = agent.request(request.method.as_str(), &request.url);
+	for (name, value) in request.headers.iter() {
+		pending = pending.set(name, value);
+	}
+	let sent = match request.body.is_empty() {
+		true => pending.call(),
+		false => pending.send_bytes(&request.body),
+	};
+	let response = match sent {
+		Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+		Err(ureq::Error::Transport(transport)) => return Err(HttpError::Send(Box::new(transport), request.url.clone())),
+	};
+	let headers = response
+		.headers_names()
+		.into_iter()
+		.flat_map(|name| response.all(&name).into_iter().map(|value| (name.clone(), value.to_string())).collect::<Vec<_>>())
+		.collect();
+	let status = response.status();
+	let mut body = Vec::new();
+	response
+		.into_reader()
+		.read_to_end(&mut body)
+		.map_err(|error| HttpError::ReadBody(error, request.url.clone()))?;
+	Ok(HttpResponse { status, headers, body })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{
+		io::{BufRead, Write},
+		net::TcpListener,
+		thread,
+		time::Instant,
+	};
+
+	/// Answers one request with the given response, returning the request line and body it received
+	fn serve_once(response: &'static str) -> (String, thread::JoinHandle<(String, String)>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/scores?top=3", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			let mut reader = io::BufReader::new(stream);
+			let mut request_line = String::new();
+			reader.read_line(&mut request_line).unwrap();
+			let mut content_length = 0;
+			loop {
+				let mut line = String::new();
+				reader.read_line(&mut line).unwrap();
+				if let Some(length) = line.to_ascii_lowercase().strip_prefix("content-length: ") {
+					content_length = length.trim().parse().unwrap();
+				}
+				if line == "\r\n" {
+					break;
+				}
+			}
+			let mut body = vec![0; content_length];
+			reader.read_exact(&mut body).unwrap();
+			reader.get_mut().write_all(response.as_bytes()).unwrap();
+			(request_line.trim().to_string(), String::from_utf8(body).unwrap())
+		});
+		(url, server)
+	}
+
+	#[test]
+	pub fn error_statuses_are_responses() {
+		let (url, server) = serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 7\r\nConnection: close\r\n\r\nmissing");
+		let response = execute(&ureq::agent(), &HttpRequest::get(url).with_header("X-Build", "42")).unwrap();
+		assert_eq!(server.join().unwrap(), ("GET /scores?top=3 HTTP/1.1".to_string(), String::new()));
+		assert!(!response.is_success());
+		assert_eq!((response.status, response.text().as_str()), (404, "missing"));
+	}
+
+	#[test]
+	pub fn failed_connections() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("http://{}/", listener.local_addr().unwrap());
+		drop(listener);
+		let client = HttpClient::new(Duration::from_secs(1), Duration::from_secs(1), "elder");
+		assert!(matches!(execute(&client.agent, &HttpRequest::get(url)), Err(HttpError::Send(..))));
+		assert!(matches!(execute(&client.agent, &HttpRequest::get("example.com")), Err(HttpError::Send(..))));
+	}
+
+	#[test]
+	pub fn responses_are_delivered_as_events() {
+		let (url, server) = serve_once("HTTP/1.1 201 Created\r\nContent-Type: text/plain\r\nContent-Length: 5\r\nConnection: close\r\n\r\nsaved");
+		let mut resources = ResourceMap::new();
+		resources.insert(IoRuntime::default());
+		resources.insert(HttpClient::default());
+		let (client, runtime) = (resources.get::<HttpClient>().unwrap(), resources.get::<IoRuntime>().unwrap());
+		let request = client.post(runtime, url, "score=10");
+
+		let start = Instant::now();
+		while resources.get::<Events<HttpResponseEvent>>().is_none() && start.elapsed() < Duration::from_secs(5) {
+			crate::io_runtime::deliver_io_completions(&mut resources);
+			thread::sleep(Duration::from_millis(1));
+		}
+		assert_eq!(server.join().unwrap(), ("POST /scores?top=3 HTTP/1.1".to_string(), "score=10".to_string()));
+		let events = resources.get::<Events<HttpResponseEvent>>().unwrap();
+		let event = events.iter().next().unwrap();
+		assert_eq!(event.request, request);
+		let response = event.response.as_ref().unwrap();
+		assert!(response.is_success());
+		assert_eq!(
+			(response.status, response.header("content-type"), response.text().as_str()),
+			(201, Some("text/plain"), "saved")
+		);
+	}
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use winit::{
+	dpi::PhysicalPosition,
+	event::{ElementState, KeyboardInput, MouseScrollDelta, VirtualKeyCode, WindowEvent},
+};
+
+/// Pixels of a trackpad scroll that count as one line of a mouse wheel
+const PIXELS_PER_LINE: f64 = 20.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MouseButton {
+	Left,
+	Right,
+	Middle,
+	Other(u16),
+}
+
+impl From<winit::event::MouseButton> for MouseButton {
+	fn from(button: winit::event::MouseButton) -> Self {
+		match button {
+			winit::event::MouseButton::Left => Self::Left,
+			winit::event::MouseButton::Right => Self::Right,
+			winit::event::MouseButton::Middle => Self::Middle,
+			winit::event::MouseButton::Other(index) => Self::Other(index),
+		}
+	}
+}
+
+/// A change to the keyboard or mouse, as `Input` applies it and an `InputRecording` saves it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+	PressKey(String),
+	ReleaseKey(String),
+	PressButton(MouseButton),
+	ReleaseButton(MouseButton),
+
+	/// The cursor's position in physical pixels
+	MoveCursor(f64, f64),
+	LeaveWindow,
+
+	/// Lines scrolled, positive when scrolling up or away from the user
+	Scroll(f64),
+	ReleaseAll,
+}
+
+impl InputEvent {
+	pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+		Some(match event {
+			WindowEvent::KeyboardInput {
+				input: KeyboardInput {
+					state,
+					virtual_keycode: Some(key),
+					..
+				},
+				..
+			} => match state {
+				ElementState::Pressed => Self::PressKey(Input::key_name(*key)),
+				ElementState::Released => Self::ReleaseKey(Input::key_name(*key)),
+			},
+			WindowEvent::MouseInput { state, button, .. } => match state {
+				ElementState::Pressed => Self::PressButton((*button).into()),
+				ElementState::Released => Self::ReleaseButton((*button).into()),
+			},
+			WindowEvent::CursorMoved { position, .. } => Self::MoveCursor(position.x, position.y),
+			WindowEvent::CursorLeft { .. } => Self::LeaveWindow,
+			WindowEvent::MouseWheel { delta, .. } => Self::Scroll(scroll_lines(*delta)),
+			WindowEvent::Focused(false) => Self::ReleaseAll,
+			_ => return None,
+		})
+	}
+}
+
+fn scroll_lines(delta: MouseScrollDelta) -> f64 {
+	match delta {
+		MouseScrollDelta::LineDelta(_, lines) => lines as f64,
+		MouseScrollDelta::PixelDelta(position) => position.y / PIXELS_PER_LINE,
+	}
+}
+
+/// The keyboard and mouse as of the current frame, with keys named as the keybindings name them
+#[derive(Default, Debug, Clone)]
+pub struct Input {
+	held_keys: BTreeSet<String>,
+	pressed_keys: BTreeSet<String>,
+	held_buttons: BTreeSet<MouseButton>,
+	pressed_buttons: BTreeSet<MouseButton>,
+	cursor: Option<PhysicalPosition<f64>>,
+	cursor_delta: (f64, f64),
+	scroll: f64,
+}
+
+impl Input {
+	pub fn key_name(key: VirtualKeyCode) -> String {
+		format!("{key:?}")
+	}
+
+	pub fn apply(&mut self, event: &InputEvent) {
+		match event {
+			InputEvent::PressKey(key) => self.press_key(key.as_str()),
+			InputEvent::ReleaseKey(key) => self.release_key(key),
+			InputEvent::PressButton(button) => self.press_button(*button),
+			InputEvent::ReleaseButton(button) => self.release_button(*button),
+			InputEvent::MoveCursor(x, y) => self.move_cursor(PhysicalPosition::new(*x, *y)),
+			InputEvent::LeaveWindow => self.leave_window(),
+			InputEvent::Scroll(lines) => self.scroll += lines,
+			InputEvent::ReleaseAll => self.release_all(),
+		}
+	}
+
+	pub fn press_key(&mut self, key: impl Into<String>) {
+		let key = key.into();
+		if self.held_keys.insert(key.clone()) {
+			self.pressed_keys.insert(key);
+		}
+	}
+
+	pub fn release_key(&mut self, key: &str) {
+		self.held_keys.remove(key);
+	}
+
+	pub fn press_button(&mut self, button: MouseButton) {
+		if self.held_buttons.insert(button) {
+			self.pressed_buttons.insert(button);
+		}
+	}
+
+	pub fn release_button(&mut self, button: MouseButton) {
+		self.held_buttons.remove(&button);
+	}
+
+	/// Moves the cursor, accumulating how far it moved this frame
+	pub fn move_cursor(&mut self, position: PhysicalPosition<f64>) {
+		if let Some(previous) = self.cursor {
+			self.cursor_delta.0 += position.x - previous.x;
+			self.cursor_delta.1 += position.y - previous.y;
+		}
+		self.cursor = Some(position);
+	}
+
+	pub fn leave_window(&mut self) {
+		self.cursor = None;
+	}
+
+	pub fn scroll(&mut self, delta: MouseScrollDelta) {
+		self.scroll += scroll_lines(delta);
+	}
+
+	pub fn is_key_held(&self, key: &str) -> bool {
+		self.held_keys.contains(key)
+	}
+
+	/// Whether the key went down this frame
+	pub fn was_key_pressed(&self, key: &str) -> bool {
+		self.pressed_keys.contains(key)
+	}
+
+	pub fn is_button_held(&self, button: MouseButton) -> bool {
+		self.held_buttons.contains(&button)
+	}
+
+	/// Whether the button went down this frame
+	pub fn was_button_pressed(&self, button: MouseButton) -> bool {
+		self.pressed_buttons.contains(&button)
+	}
+
+	pub fn is_shift_held(&self) -> bool {
+		self.is_key_held("LShift") || self.is_key_held("RShift")
+	}
+
+	pub fn is_control_held(&self) -> bool {
+		self.is_key_held("LControl") || self.is_key_held("RControl")
+	}
+
+	pub fn is_alt_held(&self) -> bool {
+		self.is_key_held("LAlt") || self.is_key_held("RAlt")
+	}
+
+	/// The cursor's position in physical pixels, or `None` when it is outside the window
+	pub fn cursor(&self) -> Option<PhysicalPosition<f64>> {
+		self.cursor
+	}
+
+	/// How far the cursor moved this frame, in physical pixels
+	pub fn cursor_delta(&self) -> (f64, f64) {
+		self.cursor_delta
+	}
+
+	/// Lines scrolled this frame, positive when scrolling up or away from the user
+	pub fn scroll_delta(&self) -> f64 {
+		self.scroll
+	}
+
+	/// Releases everything, as when the window loses focus and stops receiving release events
+	pub fn release_all(&mut self) {
+		self.held_keys.clear();
+		self.held_buttons.clear();
+	}
+
+	/// Forgets this frame's presses, movement, and scrolling
+	pub fn end_frame(&mut self) {
+		self.pressed_keys.clear();
+		self.pressed_buttons.clear();
+		self.cursor_delta = (0.0, 0.0);
+		self.scroll = 0.0;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn keys_and_buttons() {
+		let mut input = Input::default();
+		input.press_key(Input::key_name(VirtualKeyCode::LShift));
+		input.press_button(MouseButton::Middle);
+		assert!(input.is_shift_held() && input.was_key_pressed("LShift"));
+		assert!(input.is_button_held(MouseButton::Middle) && input.was_button_pressed(MouseButton::Middle));
+
+		input.end_frame();
+		input.press_key("LShift");
+		assert!(input.is_shift_held() && !input.was_key_pressed("LShift"));
+		assert!(!input.was_button_pressed(MouseButton::Middle));
+
+		input.release_button(MouseButton::Middle);
+		assert!(!input.is_button_held(MouseButton::Middle));
+		input.release_all();
+		assert!(!input.is_shift_held());
+	}
+
+	#[test]
+	pub fn cursor_and_scrolling() {
+		let mut input = Input::default();
+		input.move_cursor(PhysicalPosition::new(10.0, 10.0));
+		input.move_cursor(PhysicalPosition::new(15.0, 8.0));
+		input.move_cursor(PhysicalPosition::new(20.0, 4.0));
+		input.scroll(MouseScrollDelta::LineDelta(0.0, 1.0));
+		input.scroll(MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, 40.0)));
+		assert_eq!(input.cursor_delta(), (10.0, -6.0));
+		assert_eq!(input.scroll_delta(), 3.0);
+
+		input.end_frame();
+		assert_eq!(input.cursor_delta(), (0.0, 0.0));
+		assert_eq!(input.scroll_delta(), 0.0);
+		assert_eq!(input.cursor(), Some(PhysicalPosition::new(20.0, 4.0)));
+	}
+}
@@ -0,0 +1,179 @@
+use crate::debug_text::DebugText;
+use ecs::{
+	name::Name,
+	resource::ResourceMap,
+	world::{Entity, World},
+};
+use math::Color;
+
+/// Lists the entities, components, and resources of a live world through the `DebugText` resource
+pub struct WorldInspector {
+	pub visible: bool,
+
+	/// The entity whose scene components are listed along with their values
+	pub selected: Option<Entity>,
+
+	/// The most entities listed at once, so large worlds don't cover the whole screen
+	pub max_entities: usize,
+}
+
+impl Default for WorldInspector {
+	fn default() -> Self {
+		Self {
+			visible: false,
+			selected: None,
+			max_entities: 32,
+		}
+	}
+}
+
+impl WorldInspector {
+	/// The keybinding action that toggles the inspector
+	pub const TOGGLE_ACTION: &'static str = "toggle_inspector";
+
+	/// The key that toggles the inspector when the keybindings don't bind `TOGGLE_ACTION`
+	pub const DEFAULT_TOGGLE_KEY: &'static str = "F4";
+
+	/// Keys that move the selection through the listed entities while the inspector is visible
+	pub const SELECT_NEXT_KEY: &'static str = "PageDown";
+	pub const SELECT_PREVIOUS_KEY: &'static str = "PageUp";
+
+	pub fn select_next(&mut self, world: &World) {
+		self.select_offset(world, 1);
+	}
+
+	pub fn select_previous(&mut self, world: &World) {
+		self.select_offset(world, -1);
+	}
+
+	fn select_offset(&mut self, world: &World, offset: isize) {
+		let entities = world.entities();
+		if entities.is_empty() {
+			self.selected = None;
+			return;
+		}
+		let count = entities.len() as isize;
+		let index = match self.selected.and_then(|selected| entities.iter().position(|entity| *entity == selected)) {
+			Some(index) => (index as isize + offset).rem_euclid(count),
+			None if offset < 0 => count - 1,
+			None => 0,
+		};
+		self.selected = Some(entities[index as usize]);
+	}
+
+	/// The inspector's contents, listing the app's resources followed by the world's own
+	pub fn lines(&self, world: &World, resources: &ResourceMap) -> Vec<String> {
+		let mut resource_names = resources.type_names();
+		if let Ok(world_resources) = world.resources().try_borrow() {
+			resource_names.extend(world_resources.type_names());
+		}
+		let mut lines = vec![format!("Resources ({})", resource_names.len())];
+		lines.extend(resource_names.into_iter().map(|name| format!("  {}", short_type_name(name))));
+
+		let entities = world.entities();
+		lines.push(format!("Entities ({})", entities.len()));
+		for entity in entities.iter().copied().take(self.max_entities) {
+			let is_selected = self.selected == Some(entity);
+			let components = world.component_names(entity).into_iter().map(short_type_name).collect::<Vec<_>>();
+			lines.push(format!(
+				"{} {}: {}",
+				if is_selected { ">" } else { " " },
+				entity_label(world, entity),
+				components.join(", ")
+			));
+			if is_selected {
+				match world.inspect_scene_components(entity) {
+					Ok(values) => lines.extend(values.into_iter().map(|(name, value)| format!("    {}: {}", name, value))),
+					Err(error) => lines.push(format!("    {}", error)),
+				}
+			}
+		}
+		if entities.len() > self.max_entities {
+			lines.push(format!("  ... {} more", entities.len() - self.max_entities));
+		}
+		lines
+	}
+
+	/// Prints the inspector below the frame statistics overlay if it is visible
+	pub fn draw(&self, world: &World, resources: &ResourceMap, text: &mut DebugText) {
+		if self.visible {
+			text.print_lines([8.0, 72.0], self.lines(world, resources), Color::CYAN);
+		}
+	}
+}
+
+/// The entity's index and generation, followed by its name if it has one
+fn entity_label(world: &World, entity: Entity) -> String {
+	let label = format!("{}v{}", entity.index(), entity.generation());
+	match world.get_component::<Name>(entity) {
+		Some(name) => format!("{} \"{}\"", label, name),
+		None => label,
+	}
+}
+
+/// Strips the module paths from a type name, including those of its generic parameters
+fn short_type_name(name: &str) -> String {
+	let mut short = String::with_capacity(name.len());
+	let mut path = String::new();
+	for character in name.chars() {
+		if character.is_alphanumeric() || character == '_' || character == ':' {
+			path.push(character);
+			continue;
+		}
+		short.push_str(path.rsplit("::").next().unwrap_or_default());
+		path.clear();
+		short.push(character);
+	}
+	short.push_str(path.rsplit("::").next().unwrap_or_default());
+	short
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::debug_text::DebugText;
+
+	#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+	pub struct Health(u32);
+
+	#[test]
+	pub fn short_type_names() {
+		assert_eq!(short_type_name("app::events::Events<app::touch::TouchEvent>"), "Events<TouchEvent>");
+		assert_eq!(short_type_name("(u32, alloc::string::String)"), "(u32, String)");
+	}
+
+	#[test]
+	pub fn lists_the_world() -> ecs::error::Result<()> {
+		let mut world = World::new();
+		world.register_scene_component::<Health>("Health");
+		let player = world.create_entity();
+		world.add_component(player, Name::new("Player"))?;
+		world.add_component(player, Health(10))?;
+		world.create_entity();
+
+		let mut resources = ResourceMap::new();
+		resources.insert(DebugText::default());
+
+		let mut inspector = WorldInspector::default();
+		inspector.select_next(&world);
+		assert_eq!(inspector.selected, Some(player));
+		let lines = inspector.lines(&world, &resources);
+		assert_eq!(lines[..2], ["Resources (1)", "  DebugText"]);
+		assert_eq!(lines[2], "Entities (2)");
+		assert_eq!(lines[3], "> 0v0 \"Player\": Health, Name");
+		assert_eq!(lines[4], "    Health: (10)");
+
+		inspector.select_previous(&world);
+		assert_ne!(inspector.selected, Some(player));
+		inspector.max_entities = 1;
+		assert_eq!(inspector.lines(&world, &resources).last().map(String::as_str), Some("  ... 1 more"));
+
+		let mut text = DebugText::default();
+		inspector.draw(&world, &resources, &mut text);
+		assert!(text.is_empty());
+		inspector.visible = true;
+		inspector.draw(&world, &resources, &mut text);
+		assert!(!text.is_empty());
+		Ok(())
+	}
+}
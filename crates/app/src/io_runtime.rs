@@ -0,0 +1,336 @@
+use ecs::resource::ResourceMap;
+use std::{
+	future::Future,
+	sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+	panic::{self, AssertUnwindSafe},
+	pin::Pin,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	task::{Context, Poll, Wake, Waker},
+	thread,
+	time::Duration,
+};
+
+/// Applies a finished future's output to the resources
+pub type Completion = Box<dyn FnOnce(&mut ResourceMap) + Send>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type BoxedFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A spawned future, which is polled again each time its waker is woken
+#[cfg(not(target_arch = "wasm32"))]
+struct Task {
+	future: Mutex<Option<BoxedFuture>>,
+	queue: Sender<Arc<Task>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Wake for Task {
+	fn wake(self: Arc<Self>) {
+		self.queue.send(self.clone()).ok();
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Task {
+	/// Polls the future, dropping it once it finishes or panics so the worker can keep going
+	fn poll(self: Arc<Self>) {
+		let mut slot = self.future.lock().unwrap_or_else(|error| error.into_inner());
+		let Some(future) = slot.as_mut() else {
+			return;
+		};
+		let waker = Waker::from(self.clone());
+		match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut Context::from_waker(&waker)))) {
+			Ok(Poll::Pending) => {},
+			Ok(Poll::Ready(())) => *slot = None,
+			Err(_) => {
+				log::error!("An IO future panicked and was dropped");
+				*slot = None;
+			},
+		}
+	}
+}
+
+/// The output of a future spawned with `IoRuntime::spawn_io`, which can be checked for each frame
+pub struct IoTask<T> {
+	output: Receiver<T>,
+	finished: bool,
+}
+
+impl<T> IoTask<T> {
+	/// The future's output if it has finished and hasn't been taken yet
+	pub fn try_take(&mut self) -> Option<T> {
+		match self.output.try_recv() {
+			Ok(output) => {
+				self.finished = true;
+				Some(output)
+			},
+			Err(TryRecvError::Disconnected) => {
+				self.finished = true;
+				None
+			},
+			Err(TryRecvError::Empty) => None,
+		}
+	}
+
+	/// Whether the output was taken, or can't arrive because the future panicked or was dropped
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+}
+
+/// Runs IO-bound futures off the main thread, delivering their completions each frame
+pub struct IoRuntime {
+	completions: Receiver<Completion>,
+	completion_sender: Sender<Completion>,
+
+	#[cfg(not(target_arch = "wasm32"))]
+	queue: Sender<Arc<Task>>,
+
+	#[cfg(not(target_arch = "wasm32"))]
+	shutdown: Arc<AtomicBool>,
+}
+
+impl Default for IoRuntime {
+	fn default() -> Self {
+		Self::new(Self::DEFAULT_WORKERS)
+	}
+}
+
+impl IoRuntime {
+	/// Enough threads that a slow read or request doesn't hold up the others
+	pub const DEFAULT_WORKERS: usize = 2;
+
+	/// Starts a runtime with the given number of worker threads, which is ignored on the web
+	pub fn new(workers: usize) -> Self {
+		let (completion_sender, completions) = mpsc::channel();
+
+		#[cfg(not(target_arch = "wasm32"))]
+		{
+			let (queue, tasks) = mpsc::channel::<Arc<Task>>();
+			let tasks = Arc::new(Mutex::new(tasks));
+			let shutdown = Arc::new(AtomicBool::new(false));
+			for index in 0..workers.max(1) {
+				let (tasks, shutdown) = (tasks.clone(), shutdown.clone());
+				let worker = move || run_worker(&tasks, &shutdown);
+				if let Err(error) = thread::Builder::new().name(format!("io-{index}")).spawn(worker) {
+					log::error!("Failed to spawn an IO worker thread: {error}");
+				}
+			}
+			Self {
+				completions,
+				completion_sender,
+				queue,
+				shutdown,
+			}
+		}
+
+		#[cfg(target_arch = "wasm32")]
+		{
+			let _ = workers;
+			Self { completions, completion_sender }
+		}
+	}
+
+	/// Runs a future in the background, returning a task to check for its output
+	pub fn spawn_io<T: Send + 'static>(&self, future: impl Future<Output = T> + Send + 'static) -> IoTask<T> {
+		let (sender, output) = mpsc::channel();
+		self.spawn(async move {
+			sender.send(future.await).ok();
+		});
+		IoTask { output, finished: false }
+	}
+
+	/// Runs a future in the background, then hands its output to `complete` on the main thread
+	pub fn spawn_io_then<T: Send + 'static>(&self, future: impl Future<Output = T> + Send + 'static, complete: impl FnOnce(&mut ResourceMap, T) + Send + 'static) {
+		let completions = self.completion_sender.clone();
+		self.spawn(async move {
+			let output = future.await;
+			let completion: Completion = Box::new(move |resources| complete(resources, output));
+			completions.send(completion).ok();
+		});
+	}
+
+	/// Runs a blocking call on its own thread, then hands its output to `complete`
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn spawn_blocking_then<T: Send + 'static>(&self, call: impl FnOnce() -> T + Send + 'static, complete: impl FnOnce(&mut ResourceMap, T) + Send + 'static) {
+		let completions = self.completion_sender.clone();
+		let run = move || {
+			let output = call();
+			let completion: Completion = Box::new(move |resources| complete(resources, output));
+			completions.send(completion).ok();
+		};
+		if let Err(error) = thread::Builder::new().name("io-blocking".to_string()).spawn(run) {
+			log::error!("Failed to spawn a thread for a blocking IO call: {error}");
+		}
+	}
+
+	/// Takes the completions of the futures that finished since the last call, in order
+	pub fn take_completions(&self) -> Vec<Completion> {
+		self.completions.try_iter().collect()
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+		let task = Arc::new(Task {
+			future: Mutex::new(Some(Box::pin(future))),
+			queue: self.queue.clone(),
+		});
+		self.queue.send(task).ok();
+	}
+
+	#[cfg(target_arch = "wasm32")]
+	fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+		wasm_bindgen_futures::spawn_local(future);
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for IoRuntime {
+	fn drop(&mut self) {
+		self.shutdown.store(true, Ordering::Relaxed);
+	}
+}
+
+/// Polls woken tasks until the runtime is dropped
+#[cfg(not(target_arch = "wasm32"))]
+fn run_worker(tasks: &Mutex<Receiver<Arc<Task>>>, shutdown: &AtomicBool) {
+	const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+	while !shutdown.load(Ordering::Relaxed) {
+		let task = tasks.lock().unwrap_or_else(|error| error.into_inner()).recv_timeout(SHUTDOWN_CHECK_INTERVAL);
+		match task {
+			Ok(task) => task.poll(),
+			Err(mpsc::RecvTimeoutError::Timeout) => {},
+			Err(mpsc::RecvTimeoutError::Disconnected) => break,
+		}
+	}
+}
+
+/// Hands the output of finished `spawn_io_then` futures to their completions each frame
+pub fn deliver_io_completions(resources: &mut ResourceMap) {
+	let Some(completions) = resources.get::<IoRuntime>().map(IoRuntime::take_completions) else {
+		return;
+	};
+	for complete in completions {
+		complete(resources);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Instant;
+
+	/// Returns pending once and wakes itself from another thread, like a future waiting on a socket
+	struct WokenLater {
+		woken: bool,
+	}
+
+	impl Future for WokenLater {
+		type Output = u32;
+
+		fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<u32> {
+			if self.woken {
+				return Poll::Ready(7);
+			}
+			self.woken = true;
+			let waker = context.waker().clone();
+			thread::spawn(move || {
+				thread::sleep(Duration::from_millis(5));
+				waker.wake();
+			});
+			Poll::Pending
+		}
+	}
+
+	fn wait_until(mut finished: impl FnMut() -> bool) {
+		let start = Instant::now();
+		while !finished() && start.elapsed() < Duration::from_secs(5) {
+			thread::sleep(Duration::from_millis(1));
+		}
+	}
+
+	#[test]
+	pub fn spawn_io() {
+		let runtime = IoRuntime::new(1);
+		let mut ready = runtime.spawn_io(async { 42 });
+		let mut woken = runtime.spawn_io(async { WokenLater { woken: false }.await * 2 });
+
+		let (mut first, mut second) = (None, None);
+		wait_until(|| {
+			first = first.or_else(|| ready.try_take());
+			second = second.or_else(|| woken.try_take());
+			first.is_some() && second.is_some()
+		});
+		assert_eq!((first, second), (Some(42), Some(14)));
+		assert!(ready.is_finished() && woken.is_finished());
+		assert_eq!(ready.try_take(), None);
+	}
+
+	#[test]
+	pub fn panicking_futures_leave_the_worker_running() {
+		let runtime = IoRuntime::new(1);
+		let mut panicked = runtime.spawn_io(async { panic!("Lost the connection") });
+		let mut next = runtime.spawn_io(async { 3 });
+
+		let mut output = None;
+		wait_until(|| {
+			output = output.or_else(|| next.try_take());
+			panicked.try_take();
+			output.is_some() && panicked.is_finished()
+		});
+		assert_eq!(output, Some(3));
+		assert!(panicked.is_finished());
+	}
+
+	#[test]
+	pub fn completions_are_delivered_on_the_main_thread() {
+		let mut resources = ResourceMap::new();
+		resources.insert(IoRuntime::default());
+		resources.insert(Vec::<String>::new());
+
+		let runtime = resources.get::<IoRuntime>().unwrap();
+		runtime.spawn_io_then(async { "level.ron".to_string() }, |resources, path| {
+			resources.get_mut::<Vec<String>>().unwrap().push(path);
+		});
+
+		// Nothing changes until the completion is delivered
+		let mut delivered = Vec::new();
+		wait_until(|| {
+			delivered.extend(resources.get::<IoRuntime>().unwrap().take_completions());
+			!delivered.is_empty()
+		});
+		assert!(resources.get::<Vec<String>>().unwrap().is_empty());
+		for complete in delivered {
+			complete(&mut resources);
+		}
+		assert_eq!(resources.get::<Vec<String>>().unwrap(), &["level.ron"]);
+		deliver_io_completions(&mut resources);
+		assert_eq!(resources.get::<Vec<String>>().unwrap().len(), 1);
+	}
+
+	#[test]
+	pub fn blocking_calls_run_beside_the_workers() {
+		let mut resources = ResourceMap::new();
+		resources.insert(IoRuntime::new(1));
+		resources.insert(0_u32);
+
+		// The only worker is stuck, but the blocking call still finishes
+		let runtime = resources.get::<IoRuntime>().unwrap();
+		let (_unblock, blocked) = mpsc::channel::<()>();
+		runtime.spawn_io(async move { blocked.recv().ok() });
+		runtime.spawn_blocking_then(|| 7, |resources, value| *resources.get_mut::<u32>().unwrap() = value);
+
+		wait_until(|| {
+			deliver_io_completions(&mut resources);
+			*resources.get::<u32>().unwrap() != 0
+		});
+		assert_eq!(*resources.get::<u32>().unwrap(), 7);
+	}
+}
@@ -1,3 +1,47 @@
+mod accessibility;
 mod app;
+mod arguments;
+mod clipboard;
+mod console;
+mod cvar;
+mod debug_text;
+mod display;
+mod events;
+mod focus;
+mod frame_stats;
+mod gamepad;
+mod hot_reload;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod http;
+mod input;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod io_runtime;
+mod lifecycle;
+mod loading;
+mod profiler;
+mod rebinding;
+mod recording;
+mod render;
+mod sequence;
+mod settings;
+mod shutdown;
+mod time;
+mod touch;
+mod transition;
+#[cfg(target_arch = "wasm32")]
+mod web;
+mod window;
 
-pub use self::app::*;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub use self::http::*;
+#[cfg(feature = "inspector")]
+pub use self::inspector::*;
+#[cfg(target_arch = "wasm32")]
+pub use self::web::fetch_bytes;
+
+pub use self::{
+	accessibility::*, app::*, arguments::*, clipboard::*, console::*, cvar::*, debug_text::*, display::*, events::*, focus::*, frame_stats::*, gamepad::*, hot_reload::*,
+	input::*, io_runtime::*, lifecycle::*, loading::*, profiler::*, rebinding::*, recording::*, render::*, sequence::*, settings::*, shutdown::*, time::*, touch::*,
+	transition::*, window::*,
+};
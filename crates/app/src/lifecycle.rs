@@ -0,0 +1,111 @@
+use math::{Extent2D, Real, Rect};
+use winit::{dpi::PhysicalSize, window::Window};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+	/// The app was sent to the background.
+	/// On mobile the rendering surface is destroyed and must not be drawn to.
+	Suspended,
+
+	/// The app returned to the foreground, or finished launching.
+	/// Renderers should recreate their surface in response to this event.
+	Resumed,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Orientation {
+	#[default]
+	Landscape,
+	Portrait,
+}
+
+/// Insets in physical pixels from each edge of the window, obscured by notches or system bars
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SafeArea {
+	pub top: u32,
+	pub left: u32,
+	pub bottom: u32,
+	pub right: u32,
+}
+
+/// The size, orientation, and safe area of the window
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScreenLayout {
+	pub width: u32,
+	pub height: u32,
+	pub orientation: Orientation,
+	pub safe_area: SafeArea,
+}
+
+impl ScreenLayout {
+	pub fn new(size: PhysicalSize<u32>, safe_area: SafeArea) -> Self {
+		let orientation = if size.height > size.width { Orientation::Portrait } else { Orientation::Landscape };
+		Self {
+			width: size.width,
+			height: size.height,
+			orientation,
+			safe_area,
+		}
+	}
+
+	pub(crate) fn from_window(window: &Window) -> Self {
+		Self::new(window.inner_size(), safe_area(window))
+	}
+
+	pub fn extent(&self) -> Extent2D {
+		Extent2D::new(self.width as Real, self.height as Real)
+	}
+
+	/// The part of the window that nothing obscures, in physical pixels, for laying out UI
+	pub fn safe_rect(&self) -> Rect {
+		let SafeArea { top, left, bottom, right } = self.safe_area;
+		Rect::from_extent(self.extent()).inset(left as Real, top as Real, right as Real, bottom as Real)
+	}
+}
+
+/// On iOS the inner position and size of a window describe its safe area
+#[cfg(target_os = "ios")]
+fn safe_area(window: &Window) -> SafeArea {
+	let (Ok(inner), Ok(outer)) = (window.inner_position(), window.outer_position()) else {
+		return SafeArea::default();
+	};
+	let (inner_size, outer_size) = (window.inner_size(), window.outer_size());
+	let left = (inner.x - outer.x).max(0) as u32;
+	let top = (inner.y - outer.y).max(0) as u32;
+	SafeArea {
+		top,
+		left,
+		bottom: outer_size.height.saturating_sub(inner_size.height + top),
+		right: outer_size.width.saturating_sub(inner_size.width + left),
+	}
+}
+
+#[cfg(not(target_os = "ios"))]
+fn safe_area(_window: &Window) -> SafeArea {
+	SafeArea::default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn orientation() {
+		let landscape = ScreenLayout::new(PhysicalSize::new(1920, 1080), SafeArea::default());
+		assert_eq!(landscape.orientation, Orientation::Landscape);
+		let portrait = ScreenLayout::new(PhysicalSize::new(1080, 1920), SafeArea::default());
+		assert_eq!(portrait.orientation, Orientation::Portrait);
+	}
+
+	#[test]
+	pub fn safe_rect() {
+		let safe_area = SafeArea {
+			top: 40,
+			left: 0,
+			bottom: 20,
+			right: 0,
+		};
+		let layout = ScreenLayout::new(PhysicalSize::new(1080, 1920), safe_area);
+		assert_eq!(layout.safe_rect(), Rect::new(0.0, 40.0, 1080.0, 1860.0));
+	}
+}
@@ -0,0 +1,177 @@
+use crate::{debug_text::DebugText, time::Time};
+use asset::AssetLoader;
+use ecs::resource::ResourceMap;
+use intern::Label;
+use math::Color;
+use state::state::{State, StateResult, Transition};
+
+type QueueLoads = Box<dyn FnOnce(&mut AssetLoader)>;
+
+type WarmUp = Box<dyn FnMut(&mut ResourceMap) -> StateResult<f32>>;
+
+/// A loading screen that loads the next state's assets on the `AssetLoader`, then switches to it
+pub struct LoadingState {
+	next: Option<Box<dyn State<ResourceMap>>>,
+	queue_loads: Option<QueueLoads>,
+	warm_up: Option<WarmUp>,
+	warm_up_progress: f32,
+	minimum_duration: f32,
+	elapsed: f32,
+}
+
+impl LoadingState {
+	pub fn new(next: impl State<ResourceMap> + 'static) -> Self {
+		Self {
+			next: Some(Box::new(next)),
+			queue_loads: None,
+			warm_up: None,
+			warm_up_progress: 1.0,
+			minimum_duration: 0.0,
+			elapsed: 0.0,
+		}
+	}
+
+	/// Queues the assets the next state needs when loading starts
+	pub fn with_loads(mut self, queue_loads: impl FnOnce(&mut AssetLoader) + 'static) -> Self {
+		self.queue_loads = Some(Box::new(queue_loads));
+		self
+	}
+
+	/// Runs a step of work on the main thread each frame until it reports a progress of 1,
+	/// such as compiling the next pipeline from a `PipelineCache` saved by the previous run
+	pub fn with_warm_up(mut self, warm_up: impl FnMut(&mut ResourceMap) -> StateResult<f32> + 'static) -> Self {
+		self.warm_up = Some(Box::new(warm_up));
+		self.warm_up_progress = 0.0;
+		self
+	}
+
+	/// Keeps the screen up for at least this many seconds, such as for a splash screen with a logo
+	pub fn with_minimum_duration(mut self, seconds: f32) -> Self {
+		self.minimum_duration = seconds;
+		self
+	}
+
+	/// Seconds since loading started
+	pub fn elapsed(&self) -> f32 {
+		self.elapsed
+	}
+}
+
+impl State<ResourceMap> for LoadingState {
+	fn label(&self) -> Label {
+		Label::from_static("Loading")
+	}
+
+	fn start(&mut self, resources: &mut ResourceMap) -> StateResult<()> {
+		self.elapsed = 0.0;
+		if resources.get::<AssetLoader>().is_none() {
+			resources.insert(AssetLoader::default());
+		}
+		if let (Some(queue_loads), Some(loader)) = (self.queue_loads.take(), resources.get_mut::<AssetLoader>()) {
+			queue_loads(loader);
+		}
+		Ok(())
+	}
+
+	fn update(&mut self, resources: &mut ResourceMap) -> StateResult<Transition<ResourceMap>> {
+		self.elapsed += resources.get::<Time>().map_or(0.0, Time::real_delta);
+		if let Some(warm_up) = self.warm_up.as_mut().filter(|_| self.warm_up_progress < 1.0) {
+			self.warm_up_progress = warm_up(resources)?.clamp(0.0, 1.0);
+		}
+		let Some(loader) = resources.get_mut::<AssetLoader>() else {
+			return Ok(Transition::None);
+		};
+		loader.update();
+		let progress = (loader.progress() + self.warm_up_progress) / 2.0;
+		let is_finished = loader.is_finished() && self.warm_up_progress >= 1.0;
+		if is_finished {
+			loader.failures().for_each(|(name, error)| log::warn!("Failed to load asset '{}': {}", name, error));
+		}
+		if let Some(text) = resources.get_mut::<DebugText>() {
+			text.print([8.0, 8.0], format!("Loading {:.0}%", progress * 100.0), Color::WHITE);
+		}
+		if !is_finished || self.elapsed < self.minimum_duration {
+			return Ok(Transition::None);
+		}
+		Ok(self.next.take().map_or(Transition::Pop, Transition::Switch))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use state::state::StateMachine;
+	use std::{
+		thread,
+		time::{Duration, Instant},
+	};
+
+	pub struct Level;
+	impl State<ResourceMap> for Level {
+		fn label(&self) -> Label {
+			Label::from_static("Level")
+		}
+	}
+
+	#[test]
+	pub fn switches_when_loaded() -> StateResult<()> {
+		let mut resources = ResourceMap::new();
+		let loading = LoadingState::new(Level).with_loads(|loader| {
+			loader.load("level data", || Ok(vec![1_u8, 2, 3]));
+		});
+		let mut state_machine = StateMachine::new(loading);
+		state_machine.start(&mut resources)?;
+
+		let start = Instant::now();
+		while state_machine.active_state_label().as_deref() == Some("Loading") && start.elapsed() < Duration::from_secs(5) {
+			state_machine.update(&mut resources)?;
+			thread::sleep(Duration::from_millis(1));
+		}
+		assert_eq!(state_machine.active_state_label().as_deref(), Some("Level"));
+		let loader = resources.get::<AssetLoader>().ok_or("missing asset loader")?;
+		assert_eq!(loader.progress(), 1.0);
+		assert_eq!(loader.assets().count(), 1);
+		Ok(())
+	}
+
+	#[test]
+	pub fn warm_up() -> StateResult<()> {
+		let mut resources = ResourceMap::new();
+		resources.insert(3_u32);
+		let loading = LoadingState::new(Level).with_warm_up(|resources| {
+			let remaining = resources.get_mut::<u32>().ok_or("missing pipelines")?;
+			*remaining -= 1;
+			Ok(1.0 - *remaining as f32 / 3.0)
+		});
+		let mut state_machine = StateMachine::new(loading);
+		state_machine.start(&mut resources)?;
+		for _ in 0..2 {
+			state_machine.update(&mut resources)?;
+			assert_eq!(state_machine.active_state_label().as_deref(), Some("Loading"));
+		}
+		state_machine.update(&mut resources)?;
+		assert_eq!(state_machine.active_state_label().as_deref(), Some("Level"));
+		assert_eq!(resources.get::<u32>(), Some(&0));
+		Ok(())
+	}
+
+	#[test]
+	pub fn minimum_duration() -> StateResult<()> {
+		let mut resources = ResourceMap::new();
+		resources.insert(Time::default());
+		let mut state_machine = StateMachine::new(LoadingState::new(Level).with_minimum_duration(0.15));
+		state_machine.start(&mut resources)?;
+
+		state_machine.update(&mut resources)?;
+		assert_eq!(state_machine.active_state_label().as_deref(), Some("Loading"));
+
+		for _ in 0..2 {
+			if let Some(time) = resources.get_mut::<Time>() {
+				time.advance(Duration::from_millis(100));
+			}
+			state_machine.update(&mut resources)?;
+		}
+		assert_eq!(state_machine.active_state_label().as_deref(), Some("Level"));
+		Ok(())
+	}
+}
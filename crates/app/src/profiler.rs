@@ -0,0 +1,319 @@
+use crate::app::Error;
+use graphics::PassTiming;
+use intern::Label;
+use serde::Serialize;
+use std::{fs, time::Duration};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Where a span's work happened, shown as separate rows in trace viewers
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileTrack {
+	Cpu,
+	Gpu,
+}
+
+impl ProfileTrack {
+	fn thread_id(&self) -> u32 {
+		match self {
+			Self::Cpu => 1,
+			Self::Gpu => 2,
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		match self {
+			Self::Cpu => "CPU",
+			Self::Gpu => "GPU",
+		}
+	}
+}
+
+/// A named stretch of work, timed from when the profiler started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileSpan {
+	pub name: Label,
+	pub track: ProfileTrack,
+	pub start: Duration,
+	pub duration: Duration,
+}
+
+/// An event in the chrome://tracing JSON format, which Perfetto and Speedscope also open
+#[derive(Serialize)]
+struct TraceEvent<'a> {
+	name: &'a str,
+	#[serde(skip_serializing_if = "str::is_empty")]
+	cat: &'a str,
+	ph: &'static str,
+	ts: f64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	dur: Option<f64>,
+	pid: u32,
+	tid: u32,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	s: Option<&'static str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	args: Option<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+	trace_events: Vec<TraceEvent<'a>>,
+	display_time_unit: &'static str,
+}
+
+/// Measures time since the profiler was created with the clock each platform provides
+#[derive(Default)]
+struct ProfileClock {
+	#[cfg(not(target_arch = "wasm32"))]
+	epoch: Option<std::time::Instant>,
+
+	#[cfg(target_arch = "wasm32")]
+	epoch: Option<f64>,
+}
+
+impl ProfileClock {
+	fn now(&mut self) -> Duration {
+		#[cfg(not(target_arch = "wasm32"))]
+		let elapsed = self.epoch.get_or_insert_with(std::time::Instant::now).elapsed();
+		#[cfg(target_arch = "wasm32")]
+		let elapsed = {
+			let now = js_sys::Date::now();
+			Duration::from_secs_f64((now - *self.epoch.get_or_insert(now)).max(0.0) / 1000.0)
+		};
+		elapsed
+	}
+}
+
+/// Forwards spans, frames, and GPU timings to a connected Tracy profiler
+#[cfg(feature = "tracy")]
+struct TracyClient {
+	client: tracy_client::Client,
+	spans: Vec<tracy_client::Span>,
+	plots: std::collections::HashMap<String, tracy_client::PlotName>,
+}
+
+/// Records CPU spans and GPU pass timings over a session
+#[derive(Default)]
+pub struct Profiler {
+	/// Keeps spans for the trace file, which is off by default since a session can grow large
+	pub is_recording: bool,
+
+	spans: Vec<ProfileSpan>,
+	open: Vec<(Label, Duration)>,
+	frames: Vec<Duration>,
+	clock: ProfileClock,
+
+	#[cfg(feature = "tracy")]
+	tracy: Option<TracyClient>,
+}
+
+impl Profiler {
+	pub fn new(is_recording: bool) -> Self {
+		Self {
+			is_recording,
+			#[cfg(feature = "tracy")]
+			tracy: Some(TracyClient {
+				client: tracy_client::Client::start(),
+				spans: Vec::new(),
+				plots: std::collections::HashMap::new(),
+			}),
+			..Self::default()
+		}
+	}
+
+	/// Marks the start of a frame, called by the app before the state machine updates
+	pub fn begin_frame(&mut self) {
+		let now = self.clock.now();
+		if self.is_recording {
+			self.frames.push(now);
+		}
+		#[cfg(feature = "tracy")]
+		if let Some(tracy) = self.tracy.as_ref() {
+			tracy.client.frame_mark();
+		}
+	}
+
+	/// Starts a span that lasts until the matching `end`, nested inside any span already open.
+	/// Spans are named with labels, so string literals don't allocate every frame.
+	pub fn begin(&mut self, name: impl Into<Label>) {
+		let name = name.into();
+		#[cfg(feature = "tracy")]
+		if let Some(tracy) = self.tracy.as_mut() {
+			let span = tracy.client.clone().span_alloc(Some(&name), "", file!(), line!(), 0);
+			tracy.spans.push(span);
+		}
+		let start = self.clock.now();
+		self.open.push((name, start));
+	}
+
+	/// Ends the most recently started span
+	pub fn end(&mut self) {
+		let Some((name, start)) = self.open.pop() else {
+			return;
+		};
+		#[cfg(feature = "tracy")]
+		if let Some(tracy) = self.tracy.as_mut() {
+			tracy.spans.pop();
+		}
+		let duration = self.clock.now().saturating_sub(start);
+		self.record(name, ProfileTrack::Cpu, start, duration);
+	}
+
+	/// Times a closure as a span
+	pub fn scope<T>(&mut self, name: impl Into<Label>, scope: impl FnOnce(&mut Self) -> T) -> T {
+		self.begin(name);
+		let value = scope(self);
+		self.end();
+		value
+	}
+
+	pub fn record(&mut self, name: impl Into<Label>, track: ProfileTrack, start: Duration, duration: Duration) {
+		if self.is_recording {
+			self.spans.push(ProfileSpan {
+				name: name.into(),
+				track,
+				start,
+				duration,
+			});
+		}
+	}
+
+	/// Places GPU pass timings on the GPU track, back to back from the start of their frame.
+	/// Called after `GpuTimer::resolve`, with `frames_ago` saying how late the timestamps were.
+	pub fn record_gpu(&mut self, passes: &[PassTiming], frames_ago: usize) {
+		#[cfg(feature = "tracy")]
+		if let Some(tracy) = self.tracy.as_mut() {
+			for pass in passes.iter() {
+				let plot = *tracy
+					.plots
+					.entry(pass.name.clone())
+					.or_insert_with(|| tracy_client::PlotName::new_leak(format!("GPU {} (ms)", pass.name)));
+				tracy.client.plot(plot, pass.duration.as_secs_f64() * 1000.0);
+			}
+		}
+		let Some(frame) = self.frames.len().checked_sub(frames_ago + 1) else {
+			return;
+		};
+		let mut start = self.frames[frame];
+		for pass in passes.iter() {
+			self.record(pass.name.as_str(), ProfileTrack::Gpu, start, pass.duration);
+			start += pass.duration;
+		}
+	}
+
+	pub fn spans(&self) -> &[ProfileSpan] {
+		&self.spans
+	}
+
+	pub fn frame_count(&self) -> usize {
+		self.frames.len()
+	}
+
+	pub fn clear(&mut self) {
+		self.spans.clear();
+		self.frames.clear();
+	}
+
+	/// The session in the chrome://tracing JSON format, with times in microseconds
+	pub fn chrome_trace(&self) -> Result<String> {
+		let microseconds = |duration: Duration| duration.as_secs_f64() * 1_000_000.0;
+		let event = |name, ph, ts, tid| TraceEvent {
+			name,
+			cat: "",
+			ph,
+			ts,
+			dur: None,
+			pid: 1,
+			tid,
+			s: None,
+			args: None,
+		};
+		let threads = [ProfileTrack::Cpu, ProfileTrack::Gpu].map(|track| TraceEvent {
+			args: Some(serde_json::json!({ "name": track.name() })),
+			..event("thread_name", "M", 0.0, track.thread_id())
+		});
+		let frames = self.frames.iter().map(|start| TraceEvent {
+			cat: "frame",
+			s: Some("p"),
+			..event("Frame", "i", microseconds(*start), ProfileTrack::Cpu.thread_id())
+		});
+		let spans = self.spans.iter().map(|span| TraceEvent {
+			cat: span.track.name(),
+			dur: Some(microseconds(span.duration)),
+			..event(&span.name, "X", microseconds(span.start), span.track.thread_id())
+		});
+		let trace = Trace {
+			trace_events: threads.into_iter().chain(frames).chain(spans).collect(),
+			display_time_unit: "ms",
+		};
+		serde_json::to_string(&trace).map_err(Error::SerializeTrace)
+	}
+
+	pub fn save_chrome_trace(&self, path: &str) -> Result<()> {
+		fs::write(path, self.chrome_trace()?).map_err(|error| Error::WriteTrace(error, path.to_string()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn spans() {
+		let mut profiler = Profiler::new(true);
+		profiler.begin_frame();
+		let value = profiler.scope("update", |profiler| {
+			profiler.scope("physics", |_| 2);
+			3
+		});
+		assert_eq!(value, 3);
+		profiler.end();
+
+		let names = profiler.spans().iter().map(|span| span.name.as_str()).collect::<Vec<_>>();
+		assert_eq!(names, ["physics", "update"]);
+		let (physics, update) = (&profiler.spans()[0], &profiler.spans()[1]);
+		assert!(physics.start >= update.start && physics.start + physics.duration <= update.start + update.duration);
+
+		let mut idle = Profiler::default();
+		idle.begin_frame();
+		idle.scope("update", |_| ());
+		assert!(idle.spans().is_empty() && idle.frame_count() == 0);
+	}
+
+	#[test]
+	pub fn gpu_timings() {
+		let mut profiler = Profiler::new(true);
+		let pass = |name: &str, microseconds| PassTiming {
+			name: name.to_string(),
+			duration: Duration::from_micros(microseconds),
+		};
+		profiler.record_gpu(&[pass("shadows", 100)], 0);
+		assert!(profiler.spans().is_empty());
+
+		profiler.begin_frame();
+		profiler.begin_frame();
+		profiler.record_gpu(&[pass("shadows", 100), pass("opaque", 400)], 1);
+		let spans = profiler.spans();
+		assert_eq!(spans.len(), 2);
+		assert!(spans.iter().all(|span| span.track == ProfileTrack::Gpu));
+		assert_eq!(spans[1].start, spans[0].start + Duration::from_micros(100));
+	}
+
+	#[test]
+	pub fn chrome_trace() -> Result<()> {
+		let mut profiler = Profiler::new(true);
+		profiler.begin_frame();
+		profiler.record("update", ProfileTrack::Cpu, Duration::from_millis(1), Duration::from_micros(1500));
+		let trace = serde_json::from_str::<serde_json::Value>(&profiler.chrome_trace()?).unwrap();
+		let events = trace["traceEvents"].as_array().unwrap();
+		assert_eq!(events.len(), 4);
+		assert_eq!(events[1]["args"]["name"], "GPU");
+		assert_eq!(events[2]["ph"], "i");
+		assert_eq!(events[3]["name"], "update");
+		assert_eq!((events[3]["ts"].as_f64(), events[3]["dur"].as_f64()), (Some(1000.0), Some(1500.0)));
+		assert_eq!(events[3]["tid"], 1);
+		Ok(())
+	}
+}
@@ -0,0 +1,262 @@
+use crate::{
+	accessibility::{AccessibleNode, AccessibleRole},
+	console::Console,
+	frame_stats::FrameStats,
+	settings::Settings,
+};
+
+/// An action players can bind to a key in a controls menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindableAction {
+	/// The name the settings' keybindings use, such as `jump`
+	pub action: String,
+
+	/// The name shown to players, such as `Jump`
+	pub label: String,
+	pub default_key: String,
+}
+
+/// One line of a controls menu
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingRow {
+	pub action: String,
+	pub label: String,
+	pub key: String,
+	pub is_default: bool,
+
+	/// Whether this row is waiting for the player to press a key
+	pub is_listening: bool,
+
+	/// The labels of other actions bound to the same key
+	pub conflicts: Vec<String>,
+}
+
+impl BindingRow {
+	/// The row as a button for screen readers, whose value is the bound key
+	pub fn accessible_node(&self) -> AccessibleNode {
+		let node = AccessibleNode::new(AccessibleRole::Button, self.label.clone());
+		match self.is_listening {
+			true => node.with_value("press a key").focused(),
+			false if self.conflicts.is_empty() => node.with_value(self.key.clone()),
+			false => node.with_value(format!("{}, also bound to {}", self.key, self.conflicts.join(", "))),
+		}
+	}
+}
+
+/// What happened to the key pressed while listening
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rebind {
+	Cancelled {
+		action: String,
+	},
+	Bound {
+		action: String,
+		key: String,
+
+		/// The action that had the key before and was given this action's old key in exchange
+		swapped: Option<String>,
+	},
+}
+
+/// The data behind a controls menu that rebinds keys, writing bindings to the settings' keybindings
+#[derive(Debug, Clone)]
+pub struct KeyRebinding {
+	actions: Vec<BindableAction>,
+	listening: Option<String>,
+}
+
+impl Default for KeyRebinding {
+	fn default() -> Self {
+		let mut rebinding = Self {
+			actions: Vec::new(),
+			listening: None,
+		};
+		rebinding.register(Console::TOGGLE_ACTION, "Toggle console", Console::DEFAULT_TOGGLE_KEY);
+		rebinding.register(FrameStats::TOGGLE_ACTION, "Toggle frame statistics", FrameStats::DEFAULT_TOGGLE_KEY);
+		#[cfg(feature = "inspector")]
+		rebinding.register(crate::WorldInspector::TOGGLE_ACTION, "Toggle inspector", crate::WorldInspector::DEFAULT_TOGGLE_KEY);
+		rebinding
+	}
+}
+
+impl KeyRebinding {
+	/// The key that stops listening without changing the binding
+	pub const CANCEL_KEY: &'static str = "Escape";
+
+	/// Adds an action to the menu, or replaces the label and default key of one already registered
+	pub fn register(&mut self, action: impl Into<String>, label: impl Into<String>, default_key: impl Into<String>) {
+		let action = BindableAction {
+			action: action.into(),
+			label: label.into(),
+			default_key: default_key.into(),
+		};
+		match self.actions.iter_mut().find(|existing| existing.action == action.action) {
+			Some(existing) => *existing = action,
+			None => self.actions.push(action),
+		}
+	}
+
+	pub fn actions(&self) -> &[BindableAction] {
+		&self.actions
+	}
+
+	/// The key the action is bound to in the settings, or its default key
+	pub fn key<'a>(&'a self, action: &str, settings: &'a Settings) -> Option<&'a str> {
+		let default_key = self.actions.iter().find(|bindable| bindable.action == action)?.default_key.as_str();
+		Some(settings.keybindings.get(action).map_or(default_key, String::as_str))
+	}
+
+	pub fn rows(&self, settings: &Settings) -> Vec<BindingRow> {
+		self.actions
+			.iter()
+			.map(|bindable| {
+				let key = self.key(&bindable.action, settings).unwrap_or_default();
+				let conflicts = self
+					.actions
+					.iter()
+					.filter(|other| other.action != bindable.action && self.key(&other.action, settings) == Some(key))
+					.map(|other| other.label.clone())
+					.collect();
+				BindingRow {
+					action: bindable.action.clone(),
+					label: bindable.label.clone(),
+					key: key.to_string(),
+					is_default: key == bindable.default_key,
+					is_listening: self.listening.as_deref() == Some(bindable.action.as_str()),
+					conflicts,
+				}
+			})
+			.collect()
+	}
+
+	/// Waits for the next key press to bind to the action. Returns false if it isn't registered.
+	pub fn start_listening(&mut self, action: &str) -> bool {
+		let is_registered = self.actions.iter().any(|bindable| bindable.action == action);
+		if is_registered {
+			self.listening = Some(action.to_string());
+		}
+		is_registered
+	}
+
+	pub fn cancel(&mut self) {
+		self.listening = None;
+	}
+
+	/// The action waiting for a key press
+	pub fn listening(&self) -> Option<&str> {
+		self.listening.as_deref()
+	}
+
+	/// Binds the key to the action, giving the action's old key to any action that had this one.
+	/// Returns the action that was swapped.
+	pub fn bind(&mut self, action: &str, key: &str, settings: &mut Settings) -> Option<String> {
+		let previous = self.key(action, settings)?.to_string();
+		let swapped = self
+			.actions
+			.iter()
+			.find(|other| other.action != action && self.key(&other.action, settings) == Some(key))
+			.map(|other| other.action.clone());
+		if let Some(swapped) = swapped.as_ref() {
+			settings.keybindings.insert(swapped.clone(), previous);
+		}
+		settings.keybindings.insert(action.to_string(), key.to_string());
+		swapped
+	}
+
+	/// Binds the pressed key to the action being listened for, or cancels on `CANCEL_KEY`
+	pub fn capture(&mut self, key: &str, settings: &mut Settings) -> Option<Rebind> {
+		let action = self.listening.take()?;
+		if key == Self::CANCEL_KEY {
+			return Some(Rebind::Cancelled { action });
+		}
+		let swapped = self.bind(&action, key, settings);
+		Some(Rebind::Bound {
+			action,
+			key: key.to_string(),
+			swapped,
+		})
+	}
+
+	/// What a screen reader announces after a key press was captured, such as `Jump bound to Space`
+	pub fn describe(&self, rebind: &Rebind) -> String {
+		let label = |action: &str| {
+			self.actions
+				.iter()
+				.find(|bindable| bindable.action == action)
+				.map_or_else(|| action.to_string(), |bindable| bindable.label.clone())
+		};
+		match rebind {
+			Rebind::Cancelled { action } => format!("{} unchanged", label(action)),
+			Rebind::Bound { action, key, swapped: None } => format!("{} bound to {key}", label(action)),
+			Rebind::Bound {
+				action,
+				key,
+				swapped: Some(swapped),
+			} => format!("{} bound to {key}, swapped with {}", label(action), label(swapped)),
+		}
+	}
+
+	/// Puts the action back on its default key
+	pub fn reset(&self, action: &str, settings: &mut Settings) {
+		settings.keybindings.remove(action);
+	}
+
+	pub fn reset_all(&self, settings: &mut Settings) {
+		for bindable in &self.actions {
+			settings.keybindings.remove(&bindable.action);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn controls() -> KeyRebinding {
+		let mut rebinding = KeyRebinding::default();
+		rebinding.register("jump", "Jump", "Space");
+		rebinding.register("crouch", "Crouch", "C");
+		rebinding
+	}
+
+	#[test]
+	pub fn rows() {
+		let rebinding = controls();
+		let mut settings = Settings::default();
+		settings.keybindings.insert("crouch".to_string(), "Space".to_string());
+		let rows = rebinding.rows(&settings);
+		let jump = rows.iter().find(|row| row.action == "jump").unwrap();
+		assert!(jump.is_default && !jump.is_listening);
+		assert_eq!(jump.conflicts, ["Crouch"]);
+		assert_eq!(jump.accessible_node().value.as_deref(), Some("Space, also bound to Crouch"));
+		assert_eq!(rebinding.key(Console::TOGGLE_ACTION, &settings), Some(Console::DEFAULT_TOGGLE_KEY));
+		assert_eq!(rebinding.key("fly", &settings), None);
+	}
+
+	#[test]
+	pub fn listening_and_swapping() {
+		let mut rebinding = controls();
+		let mut settings = Settings::default();
+		assert!(!rebinding.start_listening("fly"));
+		assert!(rebinding.start_listening("jump"));
+		assert!(rebinding.rows(&settings).iter().any(|row| row.is_listening && row.accessible_node().is_focused));
+
+		// Taking crouch's key gives crouch the old jump key
+		let rebind = rebinding.capture("C", &mut settings).unwrap();
+		assert_eq!(rebinding.describe(&rebind), "Jump bound to C, swapped with Crouch");
+		assert_eq!(rebinding.key("jump", &settings), Some("C"));
+		assert_eq!(rebinding.key("crouch", &settings), Some("Space"));
+		assert_eq!(rebinding.listening(), None);
+		assert_eq!(rebinding.capture("X", &mut settings), None);
+
+		rebinding.start_listening("crouch");
+		let rebind = rebinding.capture(KeyRebinding::CANCEL_KEY, &mut settings).unwrap();
+		assert_eq!(rebind, Rebind::Cancelled { action: "crouch".to_string() });
+		assert_eq!(rebinding.key("crouch", &settings), Some("Space"));
+
+		rebinding.reset("jump", &mut settings);
+		assert_eq!(rebinding.key("jump", &settings), Some("Space"));
+		rebinding.reset_all(&mut settings);
+		assert!(settings.keybindings.is_empty());
+	}
+}
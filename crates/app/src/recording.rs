@@ -0,0 +1,107 @@
+use crate::{
+	app::Error,
+	input::{Input, InputEvent},
+	shutdown::ShutdownResult,
+};
+use ecs::resource::ResourceMap;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The input events of one frame and how long the frame took in seconds
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+	pub delta_time: f32,
+	pub events: Vec<InputEvent>,
+}
+
+/// The keyboard and mouse input of every frame, which the app saves to the `--record` path on exit
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+	frames: Vec<RecordedFrame>,
+
+	#[serde(skip)]
+	current: RecordedFrame,
+}
+
+impl InputRecording {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let source = fs::read_to_string(path).map_err(|error| Error::ReadInputRecording(error, path.display().to_string()))?;
+		ron::from_str(&source).map_err(|error| Error::ParseInputRecording(error, path.display().to_string()))
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+		let path = path.as_ref();
+		let source = ron::ser::to_string_pretty(self, Default::default()).map_err(Error::SerializeInputRecording)?;
+		fs::write(path, source).map_err(|error| Error::WriteInputRecording(error, path.display().to_string()))
+	}
+
+	pub fn record(&mut self, event: InputEvent) {
+		self.current.events.push(event);
+	}
+
+	/// Stores the events recorded since the last call as a frame that took `delta_time` seconds
+	pub fn end_frame(&mut self, delta_time: f32) {
+		self.current.delta_time = delta_time;
+		self.frames.push(std::mem::take(&mut self.current));
+	}
+
+	pub fn frames(&self) -> &[RecordedFrame] {
+		&self.frames
+	}
+
+	/// Applies a recorded frame's events, returning how long the frame took or `None` past the end
+	pub fn replay(&self, frame: usize, input: &mut Input) -> Option<f32> {
+		let frame = self.frames.get(frame)?;
+		for event in frame.events.iter() {
+			input.apply(event);
+		}
+		Some(frame.delta_time)
+	}
+}
+
+/// Writes the `InputRecording` resource to a file, added as a `Saves` hook for `--record`
+pub fn save_input_recording(resources: &mut ResourceMap, path: &str) -> ShutdownResult {
+	if let Some(recording) = resources.get::<InputRecording>() {
+		recording.save(path)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::input::MouseButton;
+
+	#[test]
+	pub fn record_and_replay() -> Result<()> {
+		let mut recording = InputRecording::default();
+		recording.record(InputEvent::PressKey("Space".to_string()));
+		recording.record(InputEvent::MoveCursor(10.0, 20.0));
+		recording.end_frame(0.016);
+		recording.record(InputEvent::PressButton(MouseButton::Left));
+		recording.record(InputEvent::Scroll(2.0));
+		recording.end_frame(0.017);
+		recording.record(InputEvent::ReleaseKey("Space".to_string()));
+
+		let path = std::env::temp_dir().join(format!("elder_input_recording_{}.ron", std::process::id()));
+		recording.save(&path)?;
+		let loaded = InputRecording::load(&path)?;
+		fs::remove_file(&path).ok();
+		assert_eq!(loaded.frames(), recording.frames());
+		assert_eq!(loaded.frames().len(), 2);
+
+		let mut input = Input::default();
+		assert_eq!(loaded.replay(0, &mut input), Some(0.016));
+		assert!(input.was_key_pressed("Space"));
+		input.end_frame();
+		assert_eq!(loaded.replay(1, &mut input), Some(0.017));
+		assert!(input.is_key_held("Space") && input.was_button_pressed(MouseButton::Left));
+		assert_eq!(input.scroll_delta(), 2.0);
+		assert_eq!(input.cursor().map(|cursor| (cursor.x, cursor.y)), Some((10.0, 20.0)));
+		assert_eq!(loaded.replay(2, &mut input), None);
+		Ok(())
+	}
+}
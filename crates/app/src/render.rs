@@ -0,0 +1,468 @@
+use crate::{
+	accessibility::UiScale,
+	input::Input,
+	lifecycle::ScreenLayout,
+	window::{CursorImage, WindowHandle},
+};
+use ecs::resource::ResourceMap;
+use graphics::{ColorPipeline, DebugDraw, Fog, Frame, RenderCamera, RenderSettings, RenderViews, SoftwareRenderer};
+use math::{Color, Viewport};
+use std::error::Error;
+
+pub type RenderResult<T = ()> = Result<T, Box<dyn Error>>;
+
+/// A camera to draw and where in the window it goes
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawView {
+	pub name: String,
+	pub viewport: Viewport,
+
+	/// The pixels the view's draws are clipped to, as x, y, width, and height
+	pub scissor: Option<[u32; 4]>,
+	pub camera: RenderCamera,
+
+	/// The view's own fog or else the scene's `Fog` resource, or clear air when neither is set
+	pub fog: Fog,
+}
+
+/// A custom cursor image and the position of its hotspot, in physical pixels
+#[derive(Clone, Copy)]
+pub struct DrawCursor<'a> {
+	pub image: &'a CursorImage,
+	pub position: [f64; 2],
+}
+
+impl DrawCursor<'_> {
+	/// Where the image's top left corner goes, placing the hotspot on the cursor
+	pub fn origin(&self) -> [f64; 2] {
+		[self.position[0] - self.image.hotspot.0 as f64, self.position[1] - self.image.hotspot.1 as f64]
+	}
+}
+
+/// Everything a backend draws in a frame, gathered from the resources after the states have updated
+pub struct DrawList<'a> {
+	pub width: u32,
+	pub height: u32,
+
+	/// The visible `RenderViews`, which is empty when no state inserted any
+	pub views: Vec<DrawView>,
+
+	/// The lines and triangles queued this frame, already built into draw calls
+	pub debug_draw: Option<&'a DebugDraw>,
+	pub color_pipeline: ColorPipeline,
+	pub render_settings: RenderSettings,
+
+	/// Physical pixels per logical pixel of debug text and UI, from the `UiScale` resource
+	pub ui_scale: f32,
+
+	/// The `CursorImage` resource while the cursor is over the window, for backends that draw it
+	pub cursor: Option<DrawCursor<'a>>,
+}
+
+impl<'a> DrawList<'a> {
+	pub fn from_resources(resources: &'a ResourceMap) -> Self {
+		let layout = resources.get::<ScreenLayout>().copied().unwrap_or_default();
+		let window = layout.extent();
+		let fog = resources.get::<Fog>();
+		let views = resources.get::<RenderViews>().map_or_else(Vec::new, |views| {
+			views
+				.visible()
+				.map(|(_, view)| DrawView {
+					name: view.name.clone(),
+					viewport: view.viewport(window),
+					scissor: view.scissor(window),
+					camera: view.render_camera(window),
+					fog: view.fog(fog),
+				})
+				.collect()
+		});
+		Self {
+			width: layout.width,
+			height: layout.height,
+			views,
+			debug_draw: resources.get::<DebugDraw>(),
+			color_pipeline: resources.get::<ColorPipeline>().copied().unwrap_or_default(),
+			render_settings: resources.get::<RenderSettings>().copied().unwrap_or_default(),
+			ui_scale: resources.get::<UiScale>().map_or(1.0, UiScale::factor),
+			cursor: resources
+				.get::<CursorImage>()
+				.zip(resources.get::<Input>().and_then(Input::cursor))
+				.map(|(image, position)| DrawCursor {
+					image,
+					position: [position.x, position.y],
+				}),
+		}
+	}
+}
+
+/// A renderer the app drives, so the rest of the app doesn't depend on one graphics library.
+///
+/// `SoftwareBackend` is the default until the workspace has a wgpu renderer. kiss3d, which the
+/// physics examples use, opens and drives its own window, so it can't draw into the app's window
+/// from `init` and isn't a backend yet.
+pub trait RenderBackend {
+	fn name(&self) -> &str;
+
+	/// Creates the surface for the window and whatever the backend needs to draw to it
+	fn init(&mut self, window: &WindowHandle, width: u32, height: u32) -> RenderResult;
+
+	fn resize(&mut self, width: u32, height: u32) -> RenderResult;
+
+	/// Waits for the display's refresh when presenting, called after `init` and when it changes
+	fn set_vsync(&mut self, _vsync: bool) -> RenderResult {
+		Ok(())
+	}
+
+	/// Starts a frame, such as by acquiring the next swapchain image
+	fn begin_frame(&mut self, resources: &mut ResourceMap) -> RenderResult;
+
+	fn submit(&mut self, draw_list: &DrawList) -> RenderResult;
+
+	/// Finishes and presents the frame, and can publish statistics or captures as resources
+	fn end_frame(&mut self, resources: &mut ResourceMap) -> RenderResult;
+
+	/// Drops the surface, when the app is suspended and mobile platforms destroy the native window
+	fn release(&mut self) {}
+
+	/// Whether the backend draws the draw list's cursor, so the app can hide the system cursor
+	fn draws_cursor(&self) -> bool {
+		false
+	}
+}
+
+/// The render backend the app draws with, stored as a resource so states can replace it at runtime
+pub struct Renderer {
+	backend: Option<Box<dyn RenderBackend>>,
+	is_initialized: bool,
+	size: (u32, u32),
+	vsync: bool,
+	is_vsync_applied: bool,
+}
+
+impl Default for Renderer {
+	fn default() -> Self {
+		Self {
+			backend: None,
+			is_initialized: false,
+			size: (0, 0),
+			vsync: true,
+			is_vsync_applied: false,
+		}
+	}
+}
+
+impl Renderer {
+	pub fn new(backend: impl RenderBackend + 'static) -> Self {
+		Self::from_boxed(Box::new(backend))
+	}
+
+	pub fn from_boxed(backend: Box<dyn RenderBackend>) -> Self {
+		Self {
+			backend: Some(backend),
+			..Default::default()
+		}
+	}
+
+	pub fn backend_name(&self) -> Option<&str> {
+		self.backend.as_ref().map(|backend| backend.name())
+	}
+
+	pub fn draws_cursor(&self) -> bool {
+		self.backend.as_ref().is_some_and(|backend| backend.draws_cursor())
+	}
+
+	pub const fn vsync(&self) -> bool {
+		self.vsync
+	}
+
+	/// Turns vsync on or off, which the backend applies before the next frame
+	pub fn set_vsync(&mut self, vsync: bool) {
+		if vsync != self.vsync {
+			self.vsync = vsync;
+			self.is_vsync_applied = false;
+		}
+	}
+
+	/// Swaps in another backend, which the app initializes before the next frame
+	pub fn set_backend(&mut self, backend: impl RenderBackend + 'static) {
+		if let Some(backend) = self.backend.as_mut().filter(|_| self.is_initialized) {
+			backend.release();
+		}
+		self.backend = Some(Box::new(backend));
+		self.is_initialized = false;
+	}
+
+	pub(crate) fn release(&mut self) {
+		if let Some(backend) = self.backend.as_mut().filter(|_| self.is_initialized) {
+			backend.release();
+		}
+		self.is_initialized = false;
+	}
+}
+
+/// How a frame failed, so the app can report it with the matching error
+pub(crate) enum RenderFailure {
+	Init(Box<dyn Error>),
+	Resize(Box<dyn Error>),
+	Frame(Box<dyn Error>),
+}
+
+/// Initializes or resizes the backend when needed, then draws the frame
+pub(crate) fn render_frame(resources: &mut ResourceMap) -> Result<(), RenderFailure> {
+	let Some(mut renderer) = resources.get_mut::<Renderer>().map(std::mem::take) else {
+		return Ok(());
+	};
+	let result = draw(&mut renderer, resources);
+	resources.insert(renderer);
+	result
+}
+
+fn draw(renderer: &mut Renderer, resources: &mut ResourceMap) -> Result<(), RenderFailure> {
+	let Some(backend) = renderer.backend.as_mut() else {
+		return Ok(());
+	};
+	let size = resources.get::<ScreenLayout>().map_or((0, 0), |layout| (layout.width, layout.height));
+	if !renderer.is_initialized {
+		let Some(window) = resources.get::<WindowHandle>() else {
+			return Ok(());
+		};
+		backend.init(window, size.0, size.1).map_err(RenderFailure::Init)?;
+		(renderer.is_initialized, renderer.size, renderer.is_vsync_applied) = (true, size, false);
+	}
+	if !renderer.is_vsync_applied {
+		backend.set_vsync(renderer.vsync).map_err(RenderFailure::Init)?;
+		renderer.is_vsync_applied = true;
+	}
+	if size != renderer.size {
+		backend.resize(size.0, size.1).map_err(RenderFailure::Resize)?;
+		renderer.size = size;
+	}
+	if let Some(draw) = resources.get_mut::<DebugDraw>() {
+		draw.build();
+	}
+	backend.begin_frame(resources).map_err(RenderFailure::Frame)?;
+	backend.submit(&DrawList::from_resources(resources)).map_err(RenderFailure::Frame)?;
+	backend.end_frame(resources).map_err(RenderFailure::Frame)
+}
+
+/// Draws each view with the `SoftwareRenderer` into a `Frame` resource, for running without a GPU
+#[derive(Debug, Clone)]
+pub struct SoftwareBackend {
+	pub clear_color: Color,
+	frame: Frame,
+}
+
+impl Default for SoftwareBackend {
+	fn default() -> Self {
+		Self {
+			clear_color: Color::BLACK,
+			frame: Frame::new(0, 0, [0, 0, 0, 255]),
+		}
+	}
+}
+
+impl RenderBackend for SoftwareBackend {
+	fn name(&self) -> &str {
+		"software"
+	}
+
+	fn init(&mut self, _window: &WindowHandle, width: u32, height: u32) -> RenderResult {
+		self.resize(width, height)
+	}
+
+	fn resize(&mut self, width: u32, height: u32) -> RenderResult {
+		self.frame = Frame::new(width, height, self.clear_color.to_srgb8());
+		Ok(())
+	}
+
+	fn begin_frame(&mut self, _resources: &mut ResourceMap) -> RenderResult {
+		self.frame = Frame::new(self.frame.width(), self.frame.height(), self.clear_color.to_srgb8());
+		Ok(())
+	}
+
+	fn submit(&mut self, draw_list: &DrawList) -> RenderResult {
+		if let Some(debug_draw) = draw_list.debug_draw {
+			for view in draw_list.views.iter() {
+				let rect = view.viewport.rect;
+				let (left, top) = (rect.x.max(0.0) as u32, rect.y.max(0.0) as u32);
+				let mut renderer = SoftwareRenderer::new(rect.width as u32, rect.height as u32);
+				renderer.clear_color = self.clear_color;
+				let frame = renderer.render(debug_draw, &view.camera.view_projection, &draw_list.color_pipeline);
+				for (y, row) in frame.pixels().chunks_exact(frame.width().max(1) as usize).enumerate() {
+					for (x, pixel) in row.iter().enumerate() {
+						self.frame.set_pixel(left + x as u32, top + y as u32, *pixel);
+					}
+				}
+			}
+		}
+		if let Some(cursor) = draw_list.cursor {
+			self.draw_cursor(cursor);
+		}
+		Ok(())
+	}
+
+	fn end_frame(&mut self, resources: &mut ResourceMap) -> RenderResult {
+		resources.insert(self.frame.clone());
+		Ok(())
+	}
+
+	fn draws_cursor(&self) -> bool {
+		true
+	}
+}
+
+impl SoftwareBackend {
+	/// Blends the cursor image over the frame by its alpha
+	fn draw_cursor(&mut self, cursor: DrawCursor) {
+		let [left, top] = cursor.origin().map(|coordinate| coordinate.round() as i64);
+		let image = cursor.image;
+		for (index, source) in image.pixels.chunks_exact(4).enumerate() {
+			let (x, y) = (left + (index as u32 % image.width) as i64, top + (index as u32 / image.width) as i64);
+			let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+				continue;
+			};
+			let Some(destination) = self.frame.pixel(x, y) else {
+				continue;
+			};
+			let alpha = source[3] as u32;
+			let blend = |channel: usize| ((source[channel] as u32 * alpha + destination[channel] as u32 * (255 - alpha)) / 255) as u8;
+			self.frame.set_pixel(x, y, [blend(0), blend(1), blend(2), destination[3]]);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use graphics::{RenderView, Tonemapper, ViewLayout};
+	use math::{Camera, Vector3};
+	use raw_window_handle::{RawDisplayHandle, RawWindowHandle, WebDisplayHandle, WebWindowHandle};
+
+	#[derive(Default)]
+	struct Recorder {
+		calls: Vec<String>,
+	}
+
+	impl RenderBackend for Recorder {
+		fn name(&self) -> &str {
+			"recorder"
+		}
+
+		fn init(&mut self, _window: &WindowHandle, width: u32, height: u32) -> RenderResult {
+			self.calls.push(format!("init {width}x{height}"));
+			Ok(())
+		}
+
+		fn resize(&mut self, width: u32, height: u32) -> RenderResult {
+			self.calls.push(format!("resize {width}x{height}"));
+			Ok(())
+		}
+
+		fn set_vsync(&mut self, vsync: bool) -> RenderResult {
+			self.calls.push(format!("vsync {vsync}"));
+			Ok(())
+		}
+
+		fn begin_frame(&mut self, _resources: &mut ResourceMap) -> RenderResult {
+			self.calls.push("begin".to_string());
+			Ok(())
+		}
+
+		fn submit(&mut self, draw_list: &DrawList) -> RenderResult {
+			self.calls.push(format!("submit {} views", draw_list.views.len()));
+			Ok(())
+		}
+
+		fn end_frame(&mut self, resources: &mut ResourceMap) -> RenderResult {
+			self.calls.push("end".to_string());
+			resources.insert(std::mem::take(&mut self.calls));
+			Ok(())
+		}
+	}
+
+	fn resources(width: u32, height: u32) -> ResourceMap {
+		let mut resources = ResourceMap::new();
+		let window = RawWindowHandle::Web(WebWindowHandle::empty());
+		// Safety: empty web handles don't refer to a window
+		resources.insert(unsafe { WindowHandle::from_raw(window, RawDisplayHandle::Web(WebDisplayHandle::empty())) });
+		resources.insert(ScreenLayout {
+			width,
+			height,
+			..Default::default()
+		});
+		resources
+	}
+
+	#[test]
+	pub fn backend_lifecycle() {
+		let mut resources = resources(64, 32);
+		resources.insert(Renderer::new(Recorder::default()));
+		let split = RenderViews::new(ViewLayout::SideBySide, ["Left", "Right"].map(|name| RenderView::new(name, Camera::default())));
+		resources.insert(split);
+		assert!(render_frame(&mut resources).is_ok());
+		assert_eq!(
+			resources.get::<Vec<String>>().unwrap(),
+			&["init 64x32", "vsync true", "begin", "submit 2 views", "end"]
+		);
+
+		resources.get_mut::<ScreenLayout>().unwrap().width = 128;
+		assert!(render_frame(&mut resources).is_ok());
+		assert_eq!(resources.get::<Vec<String>>().unwrap()[0], "resize 128x32");
+
+		resources.get_mut::<Renderer>().unwrap().set_vsync(false);
+		assert!(render_frame(&mut resources).is_ok());
+		assert_eq!(resources.get::<Vec<String>>().unwrap()[0], "vsync false");
+
+		resources.get_mut::<Renderer>().unwrap().release();
+		assert!(render_frame(&mut resources).is_ok());
+		assert_eq!(resources.get::<Vec<String>>().unwrap()[0], "init 128x32");
+		assert_eq!(resources.get::<Renderer>().unwrap().backend_name(), Some("recorder"));
+	}
+
+	#[test]
+	pub fn software_backend() {
+		let mut resources = resources(32, 16);
+		resources.insert(Renderer::new(SoftwareBackend {
+			clear_color: Color::BLUE,
+			..Default::default()
+		}));
+		let camera = Camera::new(Vector3::new(0.0, 0.0, 5.0), Default::default(), Default::default());
+		resources.insert(RenderViews::new(ViewLayout::Single, [RenderView::new("Main", camera)]));
+		let mut draw = DebugDraw::default();
+		draw.triangle(Vector3::new(-1.0, -1.0, 0.0), Vector3::new(1.0, -1.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Color::RED);
+		resources.insert(draw);
+		resources.insert(ColorPipeline {
+			tonemapper: Tonemapper::Clamp,
+			..Default::default()
+		});
+
+		assert!(render_frame(&mut resources).is_ok());
+		let frame = resources.get::<Frame>().unwrap();
+		assert_eq!((frame.width(), frame.height()), (32, 16));
+		assert_eq!(frame.pixel(0, 0), Some(Color::BLUE.to_srgb8()));
+		assert_eq!(frame.pixel(16, 8), Some(Color::RED.to_srgb8()));
+	}
+
+	#[test]
+	pub fn software_cursor() {
+		let mut resources = resources(8, 8);
+		resources.insert(Renderer::new(SoftwareBackend::default()));
+		assert!(resources.get::<Renderer>().unwrap().draws_cursor());
+		assert!(!Renderer::new(Recorder::default()).draws_cursor());
+
+		// An opaque white pixel with its hotspot on the cursor, beside a transparent one
+		resources.insert(CursorImage {
+			width: 2,
+			height: 1,
+			hotspot: (1, 0),
+			pixels: vec![255, 255, 255, 255, 255, 255, 255, 0],
+		});
+		let mut input = Input::default();
+		input.move_cursor(winit::dpi::PhysicalPosition::new(4.0, 2.0));
+		resources.insert(input);
+		assert!(render_frame(&mut resources).is_ok());
+		let frame = resources.get::<Frame>().unwrap();
+		assert_eq!(frame.pixel(3, 2), Some([255, 255, 255, 255]));
+		assert_eq!(frame.pixel(4, 2), Some([0, 0, 0, 255]));
+	}
+}
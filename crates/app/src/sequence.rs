@@ -0,0 +1,286 @@
+use crate::time::{GAMEPLAY_CHANNEL, Time};
+use ecs::resource::ResourceMap;
+use math::{Easing, Real};
+use std::collections::VecDeque;
+
+type Action = Box<dyn FnOnce(&mut ResourceMap)>;
+type Condition = Box<dyn FnMut(&mut ResourceMap) -> bool>;
+type TweenUpdate = Box<dyn FnMut(&mut ResourceMap, Real)>;
+
+enum Step {
+	Wait(f32),
+	WaitUntil(Condition),
+	Run(Option<Action>),
+	Tween {
+		duration: f32,
+		elapsed: f32,
+		easing: Easing,
+		update: TweenUpdate,
+	},
+	Parallel(Vec<Sequence>),
+}
+
+impl Step {
+	/// Advances the step, returning the seconds left over once it has finished
+	fn advance(&mut self, resources: &mut ResourceMap, delta: f32) -> Option<f32> {
+		match self {
+			Self::Wait(remaining) => {
+				if delta < *remaining {
+					*remaining -= delta;
+					return None;
+				}
+				Some(delta - *remaining)
+			},
+			Self::WaitUntil(condition) => condition(resources).then_some(delta),
+			Self::Run(action) => {
+				if let Some(action) = action.take() {
+					action(resources);
+				}
+				Some(delta)
+			},
+			Self::Tween {
+				duration,
+				elapsed,
+				easing,
+				update,
+			} => {
+				*elapsed += delta;
+				let progress = if *duration > 0.0 { *elapsed / *duration } else { 1.0 };
+				update(resources, easing.apply(progress as Real));
+				(*elapsed >= *duration).then_some(*elapsed - *duration)
+			},
+			Self::Parallel(sequences) => {
+				let mut leftover = Some(delta);
+				for sequence in sequences.iter_mut() {
+					leftover = match (leftover, sequence.advance(resources, delta)) {
+						(Some(leftover), Some(remaining)) => Some(leftover.min(remaining)),
+						_ => None,
+					};
+				}
+				leftover
+			},
+		}
+	}
+}
+
+/// A scripted series of steps that plays out over many frames, such as a cutscene or a tutorial
+pub struct Sequence {
+	steps: VecDeque<Step>,
+	channel: String,
+}
+
+impl Default for Sequence {
+	fn default() -> Self {
+		Self {
+			steps: VecDeque::new(),
+			channel: GAMEPLAY_CHANNEL.to_string(),
+		}
+	}
+}
+
+impl Sequence {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Advances by another time channel than `gameplay`, such as `ui` to play while paused
+	pub fn on_channel(mut self, channel: impl Into<String>) -> Self {
+		self.channel = channel.into();
+		self
+	}
+
+	/// Waits for the given number of seconds
+	pub fn wait(mut self, seconds: f32) -> Self {
+		self.steps.push_back(Step::Wait(seconds.max(0.0)));
+		self
+	}
+
+	/// Waits until the condition returns true, checking it once per frame
+	pub fn wait_until(mut self, condition: impl FnMut(&mut ResourceMap) -> bool + 'static) -> Self {
+		self.steps.push_back(Step::WaitUntil(Box::new(condition)));
+		self
+	}
+
+	/// Runs an action once, such as spawning an entity or playing a sound
+	pub fn then(mut self, action: impl FnOnce(&mut ResourceMap) + 'static) -> Self {
+		self.steps.push_back(Step::Run(Some(Box::new(action))));
+		self
+	}
+
+	/// Calls `update` each frame for a number of seconds with the eased progress, ending with 1
+	pub fn tween(mut self, seconds: f32, easing: Easing, update: impl FnMut(&mut ResourceMap, Real) + 'static) -> Self {
+		self.steps.push_back(Step::Tween {
+			duration: seconds.max(0.0),
+			elapsed: 0.0,
+			easing,
+			update: Box::new(update),
+		});
+		self
+	}
+
+	/// Plays several sequences at once and continues when all of them have finished.
+	/// They advance with this sequence's time channel.
+	pub fn parallel(mut self, sequences: impl IntoIterator<Item = Sequence>) -> Self {
+		self.steps.push_back(Step::Parallel(sequences.into_iter().collect()));
+		self
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.steps.is_empty()
+	}
+
+	/// Advances by a number of seconds, returning the seconds left once every step has finished
+	pub fn advance(&mut self, resources: &mut ResourceMap, mut delta: f32) -> Option<f32> {
+		while let Some(step) = self.steps.front_mut() {
+			delta = step.advance(resources, delta)?;
+			self.steps.pop_front();
+		}
+		Some(delta)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SequenceId(u64);
+
+/// The sequences being played, stored as a resource
+#[derive(Default)]
+pub struct Sequences {
+	running: Vec<(SequenceId, Sequence)>,
+	stopped: Vec<SequenceId>,
+	next_id: u64,
+}
+
+impl Sequences {
+	pub fn start(&mut self, sequence: Sequence) -> SequenceId {
+		let id = SequenceId(self.next_id);
+		self.next_id += 1;
+		self.running.push((id, sequence));
+		id
+	}
+
+	/// Stops a sequence without running the rest of its steps, even from one of its own steps
+	pub fn stop(&mut self, id: SequenceId) {
+		self.running.retain(|(running, _)| *running != id);
+		self.stopped.push(id);
+	}
+
+	pub fn is_running(&self, id: SequenceId) -> bool {
+		self.running.iter().any(|(running, _)| *running == id)
+	}
+
+	pub fn len(&self) -> usize {
+		self.running.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.running.is_empty()
+	}
+}
+
+/// Advances every running sequence by its time channel
+pub fn advance_sequences(resources: &mut ResourceMap) {
+	let Some(mut running) = resources.get_mut::<Sequences>().map(|sequences| std::mem::take(&mut sequences.running)) else {
+		return;
+	};
+	for (_, sequence) in running.iter_mut() {
+		let delta = resources.get::<Time>().map_or(0.0, |time| time.delta(&sequence.channel));
+		sequence.advance(resources, delta);
+	}
+	let Some(sequences) = resources.get_mut::<Sequences>() else {
+		return;
+	};
+	let stopped = std::mem::take(&mut sequences.stopped);
+	running.retain(|(id, sequence)| !sequence.is_finished() && !stopped.contains(id));
+	running.append(&mut sequences.running);
+	sequences.running = running;
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	fn resources() -> ResourceMap {
+		let mut resources = ResourceMap::new();
+		resources.insert(Time::default());
+		resources.insert(Sequences::default());
+		resources.insert(Vec::<String>::new());
+		resources
+	}
+
+	fn log(message: impl Into<String>) -> impl FnOnce(&mut ResourceMap) {
+		let message = message.into();
+		move |resources| resources.get_mut::<Vec<String>>().unwrap().push(message)
+	}
+
+	fn frame(resources: &mut ResourceMap, seconds: f32) {
+		resources.get_mut::<Time>().unwrap().advance(Duration::from_secs_f32(seconds));
+		advance_sequences(resources);
+	}
+
+	fn logged(resources: &ResourceMap) -> &[String] {
+		resources.get::<Vec<String>>().unwrap()
+	}
+
+	#[test]
+	pub fn steps_carry_leftover_time() {
+		let mut resources = resources();
+		let sequence = Sequence::new()
+			.wait(0.25)
+			.then(log("waited"))
+			.tween(0.5, Easing::Linear, |resources, progress| log(format!("{progress:.1}"))(resources))
+			.then(log("done"));
+		let id = resources.get_mut::<Sequences>().unwrap().start(sequence);
+
+		frame(&mut resources, 0.2);
+		assert!(logged(&resources).is_empty());
+		frame(&mut resources, 0.2);
+		assert_eq!(logged(&resources), ["waited", "0.3"]);
+		frame(&mut resources, 0.2);
+		assert_eq!(logged(&resources)[2], "0.7");
+		assert!(resources.get::<Sequences>().unwrap().is_running(id));
+		frame(&mut resources, 0.2);
+		assert_eq!(logged(&resources)[3..], ["1.0", "done"]);
+		assert!(!resources.get::<Sequences>().unwrap().is_running(id));
+	}
+
+	#[test]
+	pub fn wait_until_and_parallel() {
+		let mut resources = resources();
+		resources.insert(false);
+		let sequence = Sequence::new()
+			.wait_until(|resources| *resources.get::<bool>().unwrap())
+			.parallel([Sequence::new().wait(0.1).then(log("short")), Sequence::new().wait(0.3).then(log("long"))])
+			.then(log("joined"));
+		resources.get_mut::<Sequences>().unwrap().start(sequence);
+
+		frame(&mut resources, 0.2);
+		assert!(logged(&resources).is_empty());
+		resources.insert(true);
+		frame(&mut resources, 0.2);
+		assert_eq!(logged(&resources), ["short"]);
+		frame(&mut resources, 0.2);
+		assert_eq!(logged(&resources), ["short", "long", "joined"]);
+		assert!(resources.get::<Sequences>().unwrap().is_empty());
+	}
+
+	#[test]
+	pub fn steps_start_and_stop_sequences() {
+		let mut resources = resources();
+		let looping = resources.get_mut::<Sequences>().unwrap().start(Sequence::new().wait_until(|_| false));
+		let sequence = Sequence::new().then(move |resources| {
+			let sequences = resources.get_mut::<Sequences>().unwrap();
+			sequences.stop(looping);
+			sequences.start(Sequence::new().on_channel("ui").then(log("started")));
+		});
+		resources.get_mut::<Sequences>().unwrap().start(sequence);
+
+		resources.get_mut::<Time>().unwrap().set_paused(GAMEPLAY_CHANNEL, true).unwrap();
+		frame(&mut resources, 0.1);
+		let sequences = resources.get::<Sequences>().unwrap();
+		assert!(!sequences.is_running(looping));
+		assert_eq!(sequences.len(), 1);
+		frame(&mut resources, 0.1);
+		assert_eq!(logged(&resources), ["started"]);
+	}
+}
@@ -0,0 +1,338 @@
+use crate::{accessibility::UiScale, app::Error, cvar::CVarValue, display::FullscreenMode};
+use audio::{MASTER_BUS, MUSIC_BUS, Mixer, SFX_BUS};
+use graphics::{ColorFilter, ColorPipeline, Tonemapper};
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Window options that override the `AppConfig` defaults when present.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	pub fullscreen: Option<bool>,
+
+	/// Switches the monitor's video mode in fullscreen instead of using a borderless window
+	pub exclusive_fullscreen: Option<bool>,
+
+	/// The index of the monitor to go fullscreen on, in the order of `Displays::monitors`
+	pub monitor: Option<usize>,
+
+	/// The exclusive fullscreen refresh rate in hertz
+	pub refresh_rate: Option<u32>,
+
+	/// Waits for the display's refresh before presenting each frame
+	pub vsync: Option<bool>,
+}
+
+impl WindowSettings {
+	/// Stores a mode chosen in a settings menu, so it's used again on the next launch
+	pub fn set_fullscreen_mode(&mut self, mode: FullscreenMode) {
+		self.fullscreen = Some(mode.is_fullscreen());
+		match mode {
+			FullscreenMode::Windowed => {},
+			FullscreenMode::Borderless { monitor } => {
+				self.exclusive_fullscreen = Some(false);
+				self.monitor = monitor;
+			},
+			FullscreenMode::Exclusive {
+				monitor,
+				width,
+				height,
+				refresh_rate_millihertz,
+			} => {
+				self.exclusive_fullscreen = Some(true);
+				self.monitor = monitor;
+				self.width = Some(width);
+				self.height = Some(height);
+				self.refresh_rate = refresh_rate_millihertz.map(|refresh_rate| (refresh_rate + 500) / 1000);
+			},
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+	pub master_volume: f32,
+	pub music_volume: f32,
+	pub effects_volume: f32,
+}
+
+impl Default for AudioSettings {
+	fn default() -> Self {
+		Self {
+			master_volume: 1.0,
+			music_volume: 1.0,
+			effects_volume: 1.0,
+		}
+	}
+}
+
+impl AudioSettings {
+	/// Sets the volumes of the mixer's master, music, and sfx buses
+	/// so options menus only need to edit and save the settings.
+	pub fn apply(&self, mixer: &mut Mixer) -> Result<()> {
+		mixer.set_volume(MASTER_BUS, self.master_volume).map_err(Error::ConfigureAudio)?;
+		mixer.set_volume(MUSIC_BUS, self.music_volume).map_err(Error::ConfigureAudio)?;
+		mixer.set_volume(SFX_BUS, self.effects_volume).map_err(Error::ConfigureAudio)
+	}
+}
+
+/// Renderer options that override the `AppConfig` defaults when present
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+	/// MSAA samples per pixel: 1, 2, 4, or 8
+	pub msaa_samples: Option<u32>,
+
+	/// Renders the scene at this multiple of the window's resolution, from 0.5 to 2.0
+	pub render_scale: Option<f32>,
+
+	/// One of `Clamp`, `Reinhard`, `Aces`, or `Hable`
+	pub tonemapper: Option<Tonemapper>,
+
+	/// Exposure in stops, where each stop doubles the brightness
+	pub exposure: Option<f32>,
+}
+
+/// Options for players with low vision or color blindness
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+	/// Multiplies the size of text and UI on top of the display's scale factor, from 0.5 to 3.0
+	pub ui_scale: f32,
+
+	/// Simulates or corrects for color blindness, such as `{ deficiency = "Deuteranopia" }`
+	pub color_filter: Option<ColorFilter>,
+}
+
+impl Default for AccessibilitySettings {
+	fn default() -> Self {
+		Self {
+			ui_scale: 1.0,
+			color_filter: None,
+		}
+	}
+}
+
+impl AccessibilitySettings {
+	/// Sets the UI scale and color filter, as the app does at startup and menus do after editing
+	pub fn apply(&self, ui_scale: &mut UiScale, color_pipeline: &mut ColorPipeline) {
+		ui_scale.set_scale(self.ui_scale);
+		color_pipeline.color_filter = self.color_filter.unwrap_or_default();
+	}
+}
+
+/// Developer options that are off by default
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugSettings {
+	/// Shows the frame statistics overlay at startup
+	pub show_frame_stats: bool,
+}
+
+/// User-facing options read from a TOML file at startup
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+	pub window: WindowSettings,
+	pub graphics: GraphicsSettings,
+	pub audio: AudioSettings,
+	pub accessibility: AccessibilitySettings,
+	pub debug: DebugSettings,
+
+	/// Maps action names to key names, such as `jump = "Space"`
+	pub keybindings: BTreeMap<String, String>,
+
+	/// Values of archived cvars, such as `"physics.damping" = 0.98`
+	pub cvars: BTreeMap<String, CVarValue>,
+
+	#[serde(skip)]
+	path: Option<PathBuf>,
+}
+
+impl Settings {
+	/// Loads settings from the given path.
+	/// A missing file is not an error and yields the defaults,
+	/// so that the first call to `save` creates it.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let mut settings = if path.exists() {
+			let contents = fs::read_to_string(path).map_err(|error| Error::ReadSettings(error, path.display().to_string()))?;
+			toml::from_str::<Self>(&contents).map_err(|error| Error::ParseSettings(error, path.display().to_string()))?
+		} else {
+			Self::default()
+		};
+		settings.path = Some(path.to_path_buf());
+		Ok(settings)
+	}
+
+	pub fn path(&self) -> Option<&Path> {
+		self.path.as_deref()
+	}
+
+	pub fn set_path(&mut self, path: impl Into<PathBuf>) {
+		self.path = Some(path.into());
+	}
+
+	/// Copies what isn't written to the file onto settings that replace these, such as from `set`
+	pub fn keep_unserialized(&self, replacement: &mut Self) {
+		replacement.path = self.path.clone();
+	}
+
+	/// Writes the settings back to the file they were loaded from.
+	pub fn save(&self) -> Result<()> {
+		let path = self.path.as_ref().ok_or(Error::NoSettingsPath)?;
+		let contents = toml::to_string_pretty(self).map_err(Error::SerializeSettings)?;
+		fs::write(path, contents).map_err(|error| Error::WriteSettings(error, path.display().to_string()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::AppConfig;
+	use graphics::ColorVisionDeficiency;
+
+	#[test]
+	pub fn parse() -> Result<()> {
+		let settings = toml::from_str::<Settings>(
+			r#"
+			[window]
+			width = 1920
+			fullscreen = true
+
+			[graphics]
+			msaa_samples = 4
+
+			[audio]
+			music_volume = 0.5
+
+			[accessibility]
+			ui_scale = 1.5
+			color_filter = { deficiency = "Protanopia", mode = "Simulate" }
+
+			[debug]
+			show_frame_stats = true
+
+			[keybindings]
+			jump = "Space"
+			"#,
+		)
+		.map_err(|error| Error::ParseSettings(error, String::new()))?;
+		assert_eq!(settings.window.width, Some(1920));
+		assert_eq!(settings.window.height, None);
+		assert_eq!(settings.window.fullscreen, Some(true));
+		assert_eq!(settings.graphics.msaa_samples, Some(4));
+		assert_eq!(settings.graphics.render_scale, None);
+		assert_eq!(settings.audio.music_volume, 0.5);
+		assert_eq!(settings.audio.master_volume, 1.0);
+		assert!(settings.debug.show_frame_stats);
+		assert_eq!(settings.accessibility.ui_scale, 1.5);
+		assert_eq!(settings.accessibility.color_filter, Some(ColorFilter::simulate(ColorVisionDeficiency::Protanopia)));
+		assert_eq!(settings.keybindings.get("jump").map(String::as_str), Some("Space"));
+		Ok(())
+	}
+
+	#[test]
+	pub fn save_and_load() -> Result<()> {
+		let path = std::env::temp_dir().join(format!("elder-settings-test-{}.toml", std::process::id()));
+		let mut settings = Settings::load(&path)?;
+		assert_eq!(settings.window, WindowSettings::default());
+		assert_eq!(settings.path(), Some(path.as_path()));
+
+		settings.window.width = Some(800);
+		settings.keybindings.insert("fire".to_string(), "Mouse1".to_string());
+		settings.save()?;
+
+		assert_eq!(Settings::load(&path)?, settings);
+		fs::remove_file(path).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn apply_audio() -> Result<()> {
+		let mut mixer = Mixer::new(2, 44100);
+		let audio = AudioSettings {
+			master_volume: 0.5,
+			music_volume: 0.25,
+			..Default::default()
+		};
+		audio.apply(&mut mixer)?;
+		assert_eq!(mixer.bus(MUSIC_BUS).map(|bus| bus.volume()), Some(0.25));
+		assert_eq!(mixer.effective_gain(SFX_BUS).ok(), Some(0.5));
+		Ok(())
+	}
+
+	#[test]
+	pub fn apply_graphics() {
+		let mut settings = Settings::default();
+		settings.graphics.render_scale = Some(0.75);
+		settings.graphics.tonemapper = Some(Tonemapper::Hable);
+		let mut config = AppConfig {
+			msaa_samples: 3,
+			..Default::default()
+		};
+		config.apply_settings(&settings);
+		assert_eq!(config.render_scale, 0.75);
+		let render_settings = config.render_settings();
+		assert_eq!(render_settings.msaa_samples(), 2);
+		assert_eq!(render_settings.render_size(1024, 768), (768, 576));
+		assert_eq!(config.color_pipeline().tonemapper, Tonemapper::Hable);
+		assert_eq!(config.color_pipeline().exposure, 0.0);
+	}
+
+	#[test]
+	pub fn apply_accessibility() {
+		let accessibility = AccessibilitySettings {
+			ui_scale: 5.0,
+			color_filter: Some(ColorFilter::correct(ColorVisionDeficiency::Tritanopia)),
+		};
+		let (mut ui_scale, mut color_pipeline) = (UiScale::default(), ColorPipeline::default());
+		accessibility.apply(&mut ui_scale, &mut color_pipeline);
+		assert_eq!(ui_scale.scale(), UiScale::MAX);
+		assert_eq!(Some(color_pipeline.color_filter), accessibility.color_filter);
+	}
+
+	#[test]
+	pub fn fullscreen_modes() {
+		let exclusive = FullscreenMode::Exclusive {
+			monitor: Some(1),
+			width: 1920,
+			height: 1080,
+			refresh_rate_millihertz: Some(143_856),
+		};
+		let mut settings = Settings::default();
+		settings.window.set_fullscreen_mode(exclusive);
+		assert_eq!(settings.window.refresh_rate, Some(144));
+
+		let mut config = AppConfig::default();
+		config.apply_settings(&settings);
+		let expected = FullscreenMode::Exclusive {
+			monitor: Some(1),
+			width: 1920,
+			height: 1080,
+			refresh_rate_millihertz: Some(144_000),
+		};
+		assert_eq!(config.fullscreen_mode(), expected);
+
+		settings.window.set_fullscreen_mode(FullscreenMode::Borderless { monitor: None });
+		config.apply_settings(&settings);
+		assert_eq!(config.fullscreen_mode(), FullscreenMode::Borderless { monitor: Some(1) });
+		settings.window.set_fullscreen_mode(FullscreenMode::Windowed);
+		config.apply_settings(&settings);
+		assert_eq!(config.fullscreen_mode(), FullscreenMode::Windowed);
+	}
+
+	#[test]
+	pub fn save_without_path() {
+		assert!(matches!(Settings::default().save(), Err(Error::NoSettingsPath)));
+	}
+}
@@ -0,0 +1,168 @@
+use crate::{cvar::CVars, settings::Settings};
+use ecs::resource::ResourceMap;
+use std::error::Error;
+
+/// Why the app is exiting
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitReason {
+	/// A state or system called `AppExit::request`
+	Requested,
+
+	/// The user closed the window
+	WindowClosed,
+
+	/// The state machine stopped, after a `Transition::Quit` or popping the last state
+	StatesFinished,
+}
+
+/// Asks the app to exit, which it checks after every frame
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct AppExit {
+	reason: Option<ExitReason>,
+}
+
+impl AppExit {
+	pub fn request(&mut self) {
+		self.request_with(ExitReason::Requested);
+	}
+
+	/// Requests an exit, keeping the first reason if one was already requested
+	pub fn request_with(&mut self, reason: ExitReason) {
+		self.reason.get_or_insert(reason);
+	}
+
+	pub fn is_requested(&self) -> bool {
+		self.reason.is_some()
+	}
+
+	pub fn reason(&self) -> Option<ExitReason> {
+		self.reason
+	}
+}
+
+/// The order subsystems are shut down in once the window is closed and the states have stopped
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownStage {
+	/// Writes save games, settings, and other files while everything they read from is still around
+	Saves,
+
+	/// Disconnects from peers and servers, so they hear that the player left instead of timing out
+	Network,
+
+	/// Stops music and sound streams
+	Audio,
+
+	/// Anything that should happen after everything else has shut down
+	Cleanup,
+}
+
+pub type ShutdownResult = Result<(), Box<dyn Error>>;
+
+type Hook = Box<dyn FnOnce(&mut ResourceMap) -> ShutdownResult>;
+
+/// Work to run when the app exits, by stage and then in the order it was added
+#[derive(Default)]
+pub struct ShutdownHooks {
+	hooks: Vec<(ShutdownStage, String, Hook)>,
+}
+
+impl ShutdownHooks {
+	pub fn add(&mut self, stage: ShutdownStage, name: impl Into<String>, hook: impl FnOnce(&mut ResourceMap) -> ShutdownResult + 'static) {
+		self.hooks.push((stage, name.into(), Box::new(hook)));
+	}
+
+	pub fn len(&self) -> usize {
+		self.hooks.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.hooks.is_empty()
+	}
+}
+
+/// Runs and removes the shutdown hooks, called by the app after the state machine has stopped
+pub fn run_shutdown_hooks(resources: &mut ResourceMap) {
+	let Some(mut hooks) = resources.get_mut::<ShutdownHooks>().map(|hooks| std::mem::take(&mut hooks.hooks)) else {
+		return;
+	};
+	hooks.sort_by_key(|(stage, ..)| *stage);
+	for (stage, name, hook) in hooks {
+		log::info!("Shutting down: {name}");
+		if let Err(error) = hook(resources) {
+			log::error!("Failed to shut down {name} in the {stage:?} stage: {error}");
+		}
+	}
+}
+
+/// Writes the archived cvars to the settings file if they changed, added as a `Saves` hook
+pub fn save_archived_cvars(resources: &mut ResourceMap) -> ShutdownResult {
+	let (Some(cvars), Some(settings)) = (resources.get::<CVars>(), resources.get::<Settings>().filter(|settings| settings.path().is_some())) else {
+		return Ok(());
+	};
+	let mut saved = settings.clone();
+	cvars.save_settings(&mut saved);
+	if saved.cvars == settings.cvars {
+		return Ok(());
+	}
+	saved.save()?;
+	resources.insert(saved);
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::cvar::{CVar, CVarFlags};
+
+	#[test]
+	pub fn exit_requests() {
+		let mut exit = AppExit::default();
+		assert!(!exit.is_requested());
+		exit.request_with(ExitReason::WindowClosed);
+		exit.request();
+		assert_eq!(exit.reason(), Some(ExitReason::WindowClosed));
+	}
+
+	#[test]
+	pub fn hooks_run_in_stage_order() {
+		let mut resources = ResourceMap::new();
+		resources.insert(Vec::<&str>::new());
+		let mut hooks = ShutdownHooks::default();
+		let record = |name: &'static str| {
+			move |resources: &mut ResourceMap| -> ShutdownResult {
+				resources.get_mut::<Vec<&str>>().unwrap().push(name);
+				Ok(())
+			}
+		};
+		hooks.add(ShutdownStage::Audio, "music", record("music"));
+		hooks.add(ShutdownStage::Saves, "save game", |_| Err("The disk is full".into()));
+		hooks.add(ShutdownStage::Network, "session", record("session"));
+		hooks.add(ShutdownStage::Saves, "settings", record("settings"));
+		hooks.add(ShutdownStage::Cleanup, "temporary files", record("temporary files"));
+		resources.insert(hooks);
+
+		run_shutdown_hooks(&mut resources);
+		assert_eq!(resources.get::<Vec<&str>>().unwrap(), &["settings", "session", "music", "temporary files"]);
+		assert!(resources.get::<ShutdownHooks>().unwrap().is_empty());
+	}
+
+	#[test]
+	pub fn archived_cvars_are_saved() -> ShutdownResult {
+		let path = std::env::temp_dir().join(format!("elder-shutdown-test-{}.toml", std::process::id()));
+		let mut resources = ResourceMap::new();
+		let mut cvars = CVars::default();
+		cvars.register("physics.damping", CVar::float(0.98).with_flags(CVarFlags::ARCHIVE));
+		cvars.register("debug.wireframe", CVar::bool(false));
+		cvars.set("physics.damping", 0.5)?;
+		cvars.set("debug.wireframe", true)?;
+		resources.insert(cvars);
+		resources.insert(Settings::load(&path)?);
+
+		save_archived_cvars(&mut resources)?;
+		let saved = Settings::load(&path)?;
+		std::fs::remove_file(&path).ok();
+		assert_eq!(saved.cvars.get("physics.damping").and_then(|value| value.as_float()), Some(0.5));
+		assert!(!saved.cvars.contains_key("debug.wireframe"));
+		Ok(())
+	}
+}
@@ -0,0 +1,233 @@
+use crate::app::Error;
+use std::{collections::BTreeMap, time::Duration};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+pub const GAMEPLAY_CHANNEL: &str = "gameplay";
+pub const PHYSICS_CHANNEL: &str = "physics";
+pub const UI_CHANNEL: &str = "ui";
+
+pub struct TimeChannel {
+	parent: Option<String>,
+	scale: f32,
+	is_paused: bool,
+	delta: f32,
+	elapsed: f64,
+}
+
+impl TimeChannel {
+	fn new(parent: Option<String>) -> Self {
+		Self {
+			parent,
+			scale: 1.0,
+			is_paused: false,
+			delta: 0.0,
+			elapsed: 0.0,
+		}
+	}
+
+	pub fn parent(&self) -> Option<&str> {
+		self.parent.as_deref()
+	}
+
+	pub fn scale(&self) -> f32 {
+		self.scale
+	}
+
+	/// Whether this channel itself is paused, regardless of its parents
+	pub fn is_paused(&self) -> bool {
+		self.is_paused
+	}
+
+	/// Seconds the channel advanced this frame, after scaling and pausing
+	pub fn delta(&self) -> f32 {
+		self.delta
+	}
+
+	/// Seconds the channel has advanced in total
+	pub fn elapsed(&self) -> f64 {
+		self.elapsed
+	}
+}
+
+/// Frame time split into named channels that scale and pause along with their parents
+pub struct Time {
+	/// Longest frame the channels advance by, so a hitch or breakpoint doesn't make the game jump
+	pub max_delta: f32,
+
+	real_delta: f32,
+	real_elapsed: f64,
+	channels: BTreeMap<String, TimeChannel>,
+}
+
+impl Default for Time {
+	fn default() -> Self {
+		let mut channels = BTreeMap::new();
+		channels.insert(GAMEPLAY_CHANNEL.to_string(), TimeChannel::new(None));
+		channels.insert(PHYSICS_CHANNEL.to_string(), TimeChannel::new(Some(GAMEPLAY_CHANNEL.to_string())));
+		channels.insert(UI_CHANNEL.to_string(), TimeChannel::new(None));
+		Self {
+			max_delta: 0.25,
+			real_delta: 0.0,
+			real_elapsed: 0.0,
+			channels,
+		}
+	}
+}
+
+impl Time {
+	/// Seconds since the previous frame, unscaled and never paused
+	pub fn real_delta(&self) -> f32 {
+		self.real_delta
+	}
+
+	pub fn real_elapsed(&self) -> f64 {
+		self.real_elapsed
+	}
+
+	/// Seconds the channel advanced this frame, which is zero for unknown channels
+	pub fn delta(&self, name: &str) -> f32 {
+		self.channel(name).map_or(0.0, TimeChannel::delta)
+	}
+
+	pub fn elapsed(&self, name: &str) -> f64 {
+		self.channel(name).map_or(0.0, TimeChannel::elapsed)
+	}
+
+	pub fn channel(&self, name: &str) -> Option<&TimeChannel> {
+		self.channels.get(name)
+	}
+
+	pub fn channels(&self) -> impl Iterator<Item = (&String, &TimeChannel)> {
+		self.channels.iter()
+	}
+
+	fn channel_mut(&mut self, name: &str) -> Result<&mut TimeChannel> {
+		self.channels.get_mut(name).ok_or_else(|| Error::UnknownTimeChannel(name.to_string()))
+	}
+
+	/// Adds a channel that scales and pauses with `parent`, or on its own without one.
+	/// An existing channel with the same name is replaced.
+	pub fn add_channel(&mut self, name: impl Into<String>, parent: Option<&str>) -> Result<()> {
+		let name = name.into();
+		if let Some(parent) = parent {
+			if !self.channels.contains_key(parent) {
+				return Err(Error::UnknownTimeChannel(parent.to_string()));
+			}
+			if self.ancestors(parent).any(|ancestor| ancestor == name) {
+				return Err(Error::TimeChannelCycle(name, parent.to_string()));
+			}
+		}
+		self.channels.insert(name, TimeChannel::new(parent.map(str::to_string)));
+		Ok(())
+	}
+
+	/// Speeds up or slows down a channel and its children, such as for slow motion
+	pub fn set_scale(&mut self, name: &str, scale: f32) -> Result<()> {
+		self.channel_mut(name)?.scale = scale.max(0.0);
+		Ok(())
+	}
+
+	pub fn set_paused(&mut self, name: &str, is_paused: bool) -> Result<()> {
+		self.channel_mut(name)?.is_paused = is_paused;
+		Ok(())
+	}
+
+	/// Whether the channel or any of its parents is paused
+	pub fn is_paused(&self, name: &str) -> Result<bool> {
+		if !self.channels.contains_key(name) {
+			return Err(Error::UnknownTimeChannel(name.to_string()));
+		}
+		Ok(self.chain(name).any(TimeChannel::is_paused))
+	}
+
+	/// The product of the scales from a channel up through its parents, or zero if one is paused
+	pub fn effective_scale(&self, name: &str) -> Result<f32> {
+		if !self.channels.contains_key(name) {
+			return Err(Error::UnknownTimeChannel(name.to_string()));
+		}
+		Ok(self.chain(name).map(|channel| if channel.is_paused { 0.0 } else { channel.scale }).product())
+	}
+
+	/// Advances every channel by the time since the previous frame
+	pub fn advance(&mut self, delta: Duration) {
+		let real_delta = delta.as_secs_f32().min(self.max_delta);
+		self.real_delta = real_delta;
+		self.real_elapsed += f64::from(real_delta);
+		let names = self.channels.keys().cloned().collect::<Vec<_>>();
+		for name in names {
+			let delta = real_delta * self.effective_scale(&name).unwrap_or_default();
+			if let Some(channel) = self.channels.get_mut(&name) {
+				channel.delta = delta;
+				channel.elapsed += f64::from(delta);
+			}
+		}
+	}
+
+	/// The channel followed by each of its parents
+	fn chain<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a TimeChannel> {
+		self.ancestors(name).filter_map(|name| self.channels.get(name))
+	}
+
+	/// The name of the channel followed by the names of each of its parents
+	fn ancestors<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+		std::iter::successors(Some(name), |name| self.channels.get(*name).and_then(TimeChannel::parent))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FRAME: Duration = Duration::from_millis(100);
+
+	#[test]
+	pub fn pausing_gameplay_keeps_ui_running() -> Result<()> {
+		let mut time = Time::default();
+		time.advance(FRAME);
+		assert!((time.delta(PHYSICS_CHANNEL) - 0.1).abs() < 1e-6);
+
+		time.set_paused(GAMEPLAY_CHANNEL, true)?;
+		time.advance(FRAME);
+		assert_eq!(time.delta(GAMEPLAY_CHANNEL), 0.0);
+		assert_eq!(time.delta(PHYSICS_CHANNEL), 0.0);
+		assert!((time.delta(UI_CHANNEL) - 0.1).abs() < 1e-6);
+		assert!(time.is_paused(PHYSICS_CHANNEL)?);
+		assert!(!time.channel(PHYSICS_CHANNEL).is_some_and(TimeChannel::is_paused));
+		assert!((time.elapsed(PHYSICS_CHANNEL) - 0.1).abs() < 1e-6);
+		assert!((time.real_elapsed() - 0.2).abs() < 1e-6);
+		Ok(())
+	}
+
+	#[test]
+	pub fn scales_multiply_down_the_hierarchy() -> Result<()> {
+		let mut time = Time::default();
+		time.set_scale(GAMEPLAY_CHANNEL, 0.5)?;
+		time.add_channel("cutscene", Some(PHYSICS_CHANNEL))?;
+		time.set_scale("cutscene", 0.5)?;
+		time.advance(FRAME);
+		assert!((time.delta("cutscene") - 0.025).abs() < 1e-6);
+		assert!(!time.is_paused("cutscene")?);
+
+		time.set_scale(GAMEPLAY_CHANNEL, 0.0)?;
+		assert!(!time.is_paused("cutscene")?);
+		Ok(())
+	}
+
+	#[test]
+	pub fn long_frames_are_clamped() {
+		let mut time = Time::default();
+		time.advance(Duration::from_secs(5));
+		assert_eq!(time.real_delta(), time.max_delta);
+		assert_eq!(time.delta(UI_CHANNEL), time.max_delta);
+	}
+
+	#[test]
+	pub fn invalid_channels() {
+		let mut time = Time::default();
+		assert!(matches!(time.set_paused("missing", true), Err(Error::UnknownTimeChannel(_))));
+		assert!(matches!(time.add_channel("child", Some("missing")), Err(Error::UnknownTimeChannel(_))));
+		assert!(matches!(time.add_channel(GAMEPLAY_CHANNEL, Some(PHYSICS_CHANNEL)), Err(Error::TimeChannelCycle(..))));
+		assert_eq!(time.delta("missing"), 0.0);
+	}
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use winit::dpi::PhysicalPosition;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TouchPhase {
+	Started,
+	Moved,
+	Ended,
+	Cancelled,
+}
+
+impl From<winit::event::TouchPhase> for TouchPhase {
+	fn from(phase: winit::event::TouchPhase) -> Self {
+		match phase {
+			winit::event::TouchPhase::Started => Self::Started,
+			winit::event::TouchPhase::Moved => Self::Moved,
+			winit::event::TouchPhase::Ended => Self::Ended,
+			winit::event::TouchPhase::Cancelled => Self::Cancelled,
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TouchEvent {
+	/// Identifies a finger for the duration of a single touch
+	pub id: u64,
+	pub phase: TouchPhase,
+	pub position: PhysicalPosition<f64>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TouchPoint {
+	pub start: PhysicalPosition<f64>,
+	pub previous: PhysicalPosition<f64>,
+	pub position: PhysicalPosition<f64>,
+}
+
+impl TouchPoint {
+	pub fn delta(&self) -> (f64, f64) {
+		(self.position.x - self.previous.x, self.position.y - self.previous.y)
+	}
+}
+
+/// Tracks every finger touching the window to recognize pan and pinch gestures
+#[derive(Default)]
+pub struct Touches {
+	active: BTreeMap<u64, TouchPoint>,
+}
+
+impl Touches {
+	pub fn handle_event(&mut self, event: &TouchEvent) {
+		match event.phase {
+			TouchPhase::Started => {
+				self.active.insert(
+					event.id,
+					TouchPoint {
+						start: event.position,
+						previous: event.position,
+						position: event.position,
+					},
+				);
+			},
+			TouchPhase::Moved => {
+				if let Some(point) = self.active.get_mut(&event.id) {
+					point.position = event.position;
+				}
+			},
+			TouchPhase::Ended | TouchPhase::Cancelled => {
+				self.active.remove(&event.id);
+			},
+		}
+	}
+
+	/// Marks the current positions as the baseline for the next frame's gestures.
+	pub fn end_frame(&mut self) {
+		self.active.values_mut().for_each(|point| point.previous = point.position);
+	}
+
+	pub fn get(&self, id: u64) -> Option<&TouchPoint> {
+		self.active.get(&id)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&u64, &TouchPoint)> {
+		self.active.iter()
+	}
+
+	pub fn count(&self) -> usize {
+		self.active.len()
+	}
+
+	/// The average movement of all active touches since the previous frame.
+	pub fn pan(&self) -> Option<(f64, f64)> {
+		if self.active.is_empty() {
+			return None;
+		}
+		let count = self.active.len() as f64;
+		let (x, y) = self.active.values().map(TouchPoint::delta).fold((0.0, 0.0), |(x, y), (dx, dy)| (x + dx, y + dy));
+		Some((x / count, y / count))
+	}
+
+	/// The change in distance between the first two touches since the previous frame,
+	/// as a ratio where values above `1.0` spread the fingers apart.
+	pub fn pinch(&self) -> Option<f64> {
+		let mut points = self.active.values();
+		let (first, second) = (points.next()?, points.next()?);
+		let previous = distance(first.previous, second.previous);
+		if previous <= f64::EPSILON {
+			return None;
+		}
+		Some(distance(first.position, second.position) / previous)
+	}
+}
+
+fn distance(a: PhysicalPosition<f64>, b: PhysicalPosition<f64>) -> f64 {
+	(a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> TouchEvent {
+		TouchEvent {
+			id,
+			phase,
+			position: PhysicalPosition::new(x, y),
+		}
+	}
+
+	#[test]
+	pub fn lifecycle() {
+		let mut touches = Touches::default();
+		touches.handle_event(&touch(0, TouchPhase::Started, 0.0, 0.0));
+		assert_eq!(touches.count(), 1);
+		touches.handle_event(&touch(0, TouchPhase::Moved, 5.0, 0.0));
+		assert_eq!(touches.get(0).map(TouchPoint::delta), Some((5.0, 0.0)));
+		touches.handle_event(&touch(0, TouchPhase::Ended, 5.0, 0.0));
+		assert_eq!(touches.count(), 0);
+	}
+
+	#[test]
+	pub fn pan() {
+		let mut touches = Touches::default();
+		assert_eq!(touches.pan(), None);
+		touches.handle_event(&touch(0, TouchPhase::Started, 0.0, 0.0));
+		touches.handle_event(&touch(1, TouchPhase::Started, 10.0, 0.0));
+		touches.handle_event(&touch(0, TouchPhase::Moved, 2.0, 4.0));
+		touches.handle_event(&touch(1, TouchPhase::Moved, 12.0, 4.0));
+		assert_eq!(touches.pan(), Some((2.0, 4.0)));
+		touches.end_frame();
+		assert_eq!(touches.pan(), Some((0.0, 0.0)));
+	}
+
+	#[test]
+	pub fn pinch() {
+		let mut touches = Touches::default();
+		touches.handle_event(&touch(0, TouchPhase::Started, 0.0, 0.0));
+		assert_eq!(touches.pinch(), None);
+		touches.handle_event(&touch(1, TouchPhase::Started, 10.0, 0.0));
+		touches.handle_event(&touch(1, TouchPhase::Moved, 20.0, 0.0));
+		assert_eq!(touches.pinch(), Some(2.0));
+	}
+}
@@ -0,0 +1,197 @@
+use ecs::resource::ResourceMap;
+use math::{Color, Rect};
+use state::state::Transition;
+
+/// The direction the edge of a wipe travels across the screen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WipeDirection {
+	Left,
+	Right,
+	Up,
+	Down,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransitionEffect {
+	/// Fades the outgoing state to a color, then fades the incoming state in from it
+	Fade(Color),
+
+	/// Covers the outgoing state with a color from one side, then uncovers toward the other side
+	Wipe(Color, WipeDirection),
+
+	/// Blends from a captured frame of the outgoing state to the incoming state
+	Crossfade,
+}
+
+struct ActiveTransition {
+	effect: TransitionEffect,
+	duration: f32,
+	elapsed: f32,
+	pending: Option<Transition<ResourceMap>>,
+	capture_requested: bool,
+}
+
+impl ActiveTransition {
+	/// Seconds into the effect at which the state transition is applied
+	fn switch_time(&self) -> f32 {
+		match self.effect {
+			TransitionEffect::Fade(_) | TransitionEffect::Wipe(..) => self.duration / 2.0,
+			TransitionEffect::Crossfade => 0.0,
+		}
+	}
+}
+
+/// Plays a visual effect over a state transition, applying it partway through the effect
+#[derive(Default)]
+pub struct ScreenTransitions {
+	active: Option<ActiveTransition>,
+}
+
+impl ScreenTransitions {
+	/// Plays the effect over a number of seconds and applies the transition during it.
+	/// Starting another transition replaces this one, dropping its transition if not yet applied.
+	pub fn start(&mut self, effect: TransitionEffect, duration: f32, transition: Transition<ResourceMap>) {
+		self.active = Some(ActiveTransition {
+			effect,
+			duration: duration.max(0.0),
+			elapsed: 0.0,
+			pending: Some(transition),
+			capture_requested: effect == TransitionEffect::Crossfade,
+		});
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.active.is_some()
+	}
+
+	/// Whether the state transition has yet to be applied, such as to ignore input while leaving
+	pub fn is_leaving(&self) -> bool {
+		self.active.as_ref().is_some_and(|active| active.pending.is_some())
+	}
+
+	pub fn effect(&self) -> Option<TransitionEffect> {
+		self.active.as_ref().map(|active| active.effect)
+	}
+
+	/// Whether the renderer should keep a copy of this frame to crossfade from
+	pub fn capture_requested(&self) -> bool {
+		self.active.as_ref().is_some_and(|active| active.capture_requested)
+	}
+
+	/// How much of the frame the effect covers, from 0 to 1.
+	/// This is the opacity of a fade's color, the fraction of the screen covered by a wipe,
+	/// and the opacity of the captured frame during a crossfade.
+	pub fn coverage(&self) -> f32 {
+		let Some(active) = self.active.as_ref() else {
+			return 0.0;
+		};
+		if active.pending.is_some() {
+			return match active.effect {
+				TransitionEffect::Crossfade => 1.0,
+				_ => fraction(active.elapsed, active.switch_time()),
+			};
+		}
+		let remaining = active.duration - active.switch_time();
+		1.0 - fraction(active.elapsed - active.switch_time(), remaining)
+	}
+
+	/// The part of the screen a wipe covers with its color
+	pub fn covered_rect(&self, screen: Rect) -> Option<Rect> {
+		let active = self.active.as_ref()?;
+		let TransitionEffect::Wipe(_, direction) = active.effect else {
+			return None;
+		};
+		let coverage = self.coverage();
+		// The wipe covers from the side it starts on while leaving, then uncovers from that side
+		let leaving = active.pending.is_some();
+		let rect = match (direction, leaving) {
+			(WipeDirection::Right, true) => screen.split_horizontally(coverage).0,
+			(WipeDirection::Right, false) => screen.split_horizontally(1.0 - coverage).1,
+			(WipeDirection::Left, true) => screen.split_horizontally(1.0 - coverage).1,
+			(WipeDirection::Left, false) => screen.split_horizontally(coverage).0,
+			(WipeDirection::Down, true) => screen.split_vertically(coverage).0,
+			(WipeDirection::Down, false) => screen.split_vertically(1.0 - coverage).1,
+			(WipeDirection::Up, true) => screen.split_vertically(1.0 - coverage).1,
+			(WipeDirection::Up, false) => screen.split_vertically(coverage).0,
+		};
+		Some(rect)
+	}
+
+	/// Advances the effect, returning the state transition once it is time to apply it.
+	/// A crossfade holds for one frame first so the renderer can capture the outgoing state.
+	pub fn advance(&mut self, delta_time: f32) -> Option<Transition<ResourceMap>> {
+		let active = self.active.as_mut()?;
+		if active.capture_requested {
+			active.capture_requested = false;
+			return None;
+		}
+		active.elapsed += delta_time;
+		if active.pending.is_some() {
+			if active.elapsed >= active.switch_time() {
+				active.elapsed = active.switch_time();
+				return active.pending.take();
+			}
+			return None;
+		}
+		if active.elapsed >= active.duration {
+			self.active = None;
+		}
+		None
+	}
+}
+
+fn fraction(elapsed: f32, duration: f32) -> f32 {
+	if duration <= 0.0 { 1.0 } else { (elapsed / duration).clamp(0.0, 1.0) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn fade() {
+		let mut transitions = ScreenTransitions::default();
+		transitions.start(TransitionEffect::Fade(Color::BLACK), 1.0, Transition::Pop);
+		assert!(transitions.is_leaving());
+		assert_eq!(transitions.coverage(), 0.0);
+
+		assert!(transitions.advance(0.25).is_none());
+		assert_eq!(transitions.coverage(), 0.5);
+		assert!(matches!(transitions.advance(0.25), Some(Transition::Pop)));
+		assert!(!transitions.is_leaving());
+		assert_eq!(transitions.coverage(), 1.0);
+
+		assert!(transitions.advance(0.25).is_none());
+		assert_eq!(transitions.coverage(), 0.5);
+		transitions.advance(0.25);
+		assert!(!transitions.is_active());
+		assert_eq!(transitions.coverage(), 0.0);
+	}
+
+	#[test]
+	pub fn crossfade_waits_for_a_capture() {
+		let mut transitions = ScreenTransitions::default();
+		transitions.start(TransitionEffect::Crossfade, 1.0, Transition::Quit);
+		assert!(transitions.capture_requested());
+		assert!(transitions.advance(0.1).is_none());
+		assert!(!transitions.capture_requested());
+		assert!(matches!(transitions.advance(0.1), Some(Transition::Quit)));
+		assert_eq!(transitions.coverage(), 1.0);
+		transitions.advance(0.5);
+		assert_eq!(transitions.coverage(), 0.5);
+	}
+
+	#[test]
+	pub fn wipe() {
+		let screen = Rect::new(0.0, 0.0, 100.0, 50.0);
+		let mut transitions = ScreenTransitions::default();
+		assert_eq!(transitions.covered_rect(screen), None);
+
+		transitions.start(TransitionEffect::Wipe(Color::BLACK, WipeDirection::Right), 2.0, Transition::Pop);
+		transitions.advance(0.5);
+		assert_eq!(transitions.covered_rect(screen), Some(Rect::new(0.0, 0.0, 50.0, 50.0)));
+		transitions.advance(0.5);
+		transitions.advance(0.25);
+		assert_eq!(transitions.covered_rect(screen), Some(Rect::new(25.0, 0.0, 75.0, 50.0)));
+	}
+}
@@ -0,0 +1,31 @@
+use crate::app::Error;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use winit::{platform::web::WindowExtWebSys, window::Window};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Appends the window's canvas to the element with the given id, or to the document body
+pub(crate) fn attach_canvas(window: &Window, parent_id: Option<&str>) -> Result<()> {
+	let document = web_sys::window().and_then(|window| window.document()).ok_or(Error::AttachCanvas)?;
+	let parent: web_sys::Element = match parent_id {
+		Some(id) => document.get_element_by_id(id),
+		None => document.body().map(Into::into),
+	}
+	.ok_or(Error::AttachCanvas)?;
+	parent.append_child(&window.canvas()).map_err(|_| Error::AttachCanvas)?;
+	Ok(())
+}
+
+/// Fetches an asset over HTTP, relative to the page serving the app.
+pub async fn fetch_bytes(url: &str) -> Result<Vec<u8>> {
+	let fetch_error = || Error::FetchAsset(url.to_string());
+	let window = web_sys::window().ok_or_else(fetch_error)?;
+	let response = JsFuture::from(window.fetch_with_str(url)).await.map_err(|_| fetch_error())?;
+	let response = response.dyn_into::<web_sys::Response>().map_err(|_| fetch_error())?;
+	if !response.ok() {
+		return Err(fetch_error());
+	}
+	let buffer = JsFuture::from(response.array_buffer().map_err(|_| fetch_error())?).await.map_err(|_| fetch_error())?;
+	Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
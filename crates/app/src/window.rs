@@ -0,0 +1,248 @@
+use crate::{app::Error, display::FullscreenMode};
+use asset::{Texture, TextureFormat, TextureOptions};
+use winit::{
+	dpi::{PhysicalPosition, PhysicalSize},
+	window::Window,
+};
+
+pub use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle};
+pub use winit::window::{CursorGrabMode as CursorGrab, CursorIcon};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+pub enum WindowCommand {
+	SetCursorGrab(CursorGrab),
+	SetCursorVisible(bool),
+	SetCursorPosition(PhysicalPosition<f64>),
+	SetCursorIcon(CursorIcon),
+
+	/// Sets a cursor image loaded from a path, whose hotspot is the pixel at the cursor position.
+	/// The system cursor is only hidden while the render backend draws the image.
+	SetCursorImage {
+		path: String,
+		hotspot: (u32, u32),
+	},
+
+	/// Restores the system cursor after a custom cursor image was set
+	ClearCursorImage,
+
+	/// Enters or leaves fullscreen, or moves it to another monitor or video mode
+	SetFullscreen(FullscreenMode),
+	SetTitle(String),
+
+	/// Requests a new inner size, which the window system may adjust before the resize event
+	SetSize(PhysicalSize<u32>),
+	SetMinimized(bool),
+	SetMaximized(bool),
+
+	/// Keeps the window above other windows, such as for a tool palette or a video player
+	SetAlwaysOnTop(bool),
+
+	/// Shows or hides the title bar and borders
+	SetDecorations(bool),
+	SetResizable(bool),
+}
+
+/// A queue of window changes requested by states, applied after every update
+#[derive(Default)]
+pub struct WindowCommands {
+	commands: Vec<WindowCommand>,
+}
+
+impl WindowCommands {
+	pub fn push(&mut self, command: WindowCommand) {
+		self.commands.push(command);
+	}
+
+	/// Locks the cursor in place for camera control,
+	/// falling back to confining it to the window on platforms without locking.
+	pub fn grab_cursor(&mut self) {
+		self.push(WindowCommand::SetCursorGrab(CursorGrab::Locked));
+		self.push(WindowCommand::SetCursorVisible(false));
+	}
+
+	pub fn release_cursor(&mut self) {
+		self.push(WindowCommand::SetCursorGrab(CursorGrab::None));
+		self.push(WindowCommand::SetCursorVisible(true));
+	}
+
+	pub fn set_cursor_grab(&mut self, grab: CursorGrab) {
+		self.push(WindowCommand::SetCursorGrab(grab));
+	}
+
+	pub fn set_cursor_visible(&mut self, visible: bool) {
+		self.push(WindowCommand::SetCursorVisible(visible));
+	}
+
+	pub fn set_cursor_position(&mut self, x: f64, y: f64) {
+		self.push(WindowCommand::SetCursorPosition(PhysicalPosition::new(x, y)));
+	}
+
+	pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+		self.push(WindowCommand::SetCursorIcon(icon));
+	}
+
+	pub fn set_cursor_image(&mut self, path: impl Into<String>, hotspot: (u32, u32)) {
+		self.push(WindowCommand::SetCursorImage { path: path.into(), hotspot });
+	}
+
+	pub fn clear_cursor_image(&mut self) {
+		self.push(WindowCommand::ClearCursorImage);
+	}
+
+	pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+		self.push(WindowCommand::SetFullscreen(mode));
+	}
+
+	pub fn set_title(&mut self, title: impl Into<String>) {
+		self.push(WindowCommand::SetTitle(title.into()));
+	}
+
+	pub fn set_size(&mut self, width: u32, height: u32) {
+		self.push(WindowCommand::SetSize(PhysicalSize::new(width, height)));
+	}
+
+	pub fn minimize(&mut self) {
+		self.push(WindowCommand::SetMinimized(true));
+	}
+
+	pub fn maximize(&mut self) {
+		self.push(WindowCommand::SetMaximized(true));
+	}
+
+	/// Restores a minimized or maximized window to its previous size
+	pub fn restore(&mut self) {
+		self.push(WindowCommand::SetMinimized(false));
+		self.push(WindowCommand::SetMaximized(false));
+	}
+
+	pub fn set_always_on_top(&mut self, always_on_top: bool) {
+		self.push(WindowCommand::SetAlwaysOnTop(always_on_top));
+	}
+
+	pub fn set_decorations(&mut self, decorations: bool) {
+		self.push(WindowCommand::SetDecorations(decorations));
+	}
+
+	pub fn set_resizable(&mut self, resizable: bool) {
+		self.push(WindowCommand::SetResizable(resizable));
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+
+	pub fn drain(&mut self) -> impl Iterator<Item = WindowCommand> + '_ {
+		self.commands.drain(..)
+	}
+}
+
+/// The platform handles of the app's window, removed before the window is destroyed or suspended.
+/// It is neither `Copy` nor `Clone`, so the handles can't be kept past the resource.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WindowHandle {
+	window: RawWindowHandle,
+	display: RawDisplayHandle,
+}
+
+impl WindowHandle {
+	pub(crate) fn from_window(window: &Window) -> Self {
+		Self {
+			window: window.raw_window_handle(),
+			display: window.raw_display_handle(),
+		}
+	}
+
+	/// Wraps handles from another source, such as a window the app doesn't own
+	///
+	/// # Safety
+	///
+	/// The window and display must stay valid for as long as the returned handle exists.
+	pub unsafe fn from_raw(window: RawWindowHandle, display: RawDisplayHandle) -> Self {
+		Self { window, display }
+	}
+}
+
+// Safety: the app drops the handle before its window, and `from_raw` requires the same of callers
+unsafe impl HasRawWindowHandle for WindowHandle {
+	fn raw_window_handle(&self) -> RawWindowHandle {
+		self.window
+	}
+}
+
+// Safety: as for the window handle
+unsafe impl HasRawDisplayHandle for WindowHandle {
+	fn raw_display_handle(&self) -> RawDisplayHandle {
+		self.display
+	}
+}
+
+/// A decoded custom cursor image, which backends that draw cursors draw at the cursor position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+	pub width: u32,
+	pub height: u32,
+	pub hotspot: (u32, u32),
+
+	/// Pixel data in RGBA8 format
+	pub pixels: Vec<u8>,
+}
+
+impl CursorImage {
+	pub fn load(path: &str, hotspot: (u32, u32)) -> Result<Self> {
+		let options = TextureOptions {
+			generate_mipmaps: false,
+			..Default::default()
+		};
+		let texture = Texture::load(path, options).map_err(|error| Error::LoadCursorImage(error, path.to_string()))?;
+		match (texture.format, texture.mip_levels.into_iter().next()) {
+			(TextureFormat::Rgba8 | TextureFormat::Rgba8Srgb, Some(image)) => Ok(Self {
+				width: image.width,
+				height: image.height,
+				hotspot,
+				pixels: image.data,
+			}),
+			_ => Err(Error::UnsupportedCursorImage(path.to_string())),
+		}
+	}
+}
+
+pub(crate) fn set_cursor_grab(window: &Window, grab: CursorGrab) -> Result<()> {
+	match window.set_cursor_grab(grab) {
+		Err(_) if grab == CursorGrab::Locked => window.set_cursor_grab(CursorGrab::Confined),
+		result => result,
+	}
+	.map_err(Error::SetCursorGrab)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn drain() {
+		let mut commands = WindowCommands::default();
+		commands.grab_cursor();
+		commands.set_cursor_position(10.0, 20.0);
+		assert!(!commands.is_empty());
+
+		let drained = commands.drain().collect::<Vec<_>>();
+		assert_eq!(drained.len(), 3);
+		assert!(matches!(drained[0], WindowCommand::SetCursorGrab(CursorGrab::Locked)));
+		assert!(matches!(drained[1], WindowCommand::SetCursorVisible(false)));
+		assert!(commands.is_empty());
+	}
+
+	#[test]
+	pub fn window_state() {
+		let mut commands = WindowCommands::default();
+		commands.set_title("Editor - level.scene*");
+		commands.set_size(1280, 720);
+		commands.restore();
+
+		let drained = commands.drain().collect::<Vec<_>>();
+		assert!(matches!(&drained[0], WindowCommand::SetTitle(title) if title == "Editor - level.scene*"));
+		assert!(matches!(drained[1], WindowCommand::SetSize(PhysicalSize { width: 1280, height: 720 })));
+		assert!(matches!(drained[2..], [WindowCommand::SetMinimized(false), WindowCommand::SetMaximized(false)]));
+	}
+}
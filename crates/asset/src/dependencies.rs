@@ -0,0 +1,130 @@
+use std::{
+	collections::{BTreeMap, BTreeSet, VecDeque},
+	path::{Path, PathBuf},
+};
+
+/// Which assets each asset references, such as the textures a material samples,
+/// so a change to one asset can be followed to everything built from it
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+	dependencies: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+	dependents: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl DependencyGraph {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Replaces everything `asset` depends on
+	pub fn set_dependencies(&mut self, asset: &Path, dependencies: impl IntoIterator<Item = PathBuf>) {
+		self.remove(asset);
+		let dependencies = dependencies.into_iter().filter(|dependency| dependency != asset).collect::<BTreeSet<_>>();
+		for dependency in dependencies.iter() {
+			self.dependents.entry(dependency.clone()).or_default().insert(asset.to_path_buf());
+		}
+		if !dependencies.is_empty() {
+			self.dependencies.insert(asset.to_path_buf(), dependencies);
+		}
+	}
+
+	/// Forgets what `asset` depends on. Assets that depend on it keep doing so,
+	/// so they are rebuilt if it comes back.
+	pub fn remove(&mut self, asset: &Path) {
+		for dependency in self.dependencies.remove(asset).unwrap_or_default() {
+			if let Some(dependents) = self.dependents.get_mut(&dependency) {
+				dependents.remove(asset);
+				if dependents.is_empty() {
+					self.dependents.remove(&dependency);
+				}
+			}
+		}
+	}
+
+	/// The assets `asset` references directly
+	pub fn dependencies(&self, asset: &Path) -> impl Iterator<Item = &Path> {
+		self.dependencies.get(asset).into_iter().flatten().map(PathBuf::as_path)
+	}
+
+	/// The assets that reference `asset` directly
+	pub fn dependents(&self, asset: &Path) -> impl Iterator<Item = &Path> {
+		self.dependents.get(asset).into_iter().flatten().map(PathBuf::as_path)
+	}
+
+	/// Every asset that depends on `asset` directly or indirectly, nearest first.
+	/// These are what need reimporting after `asset` changes. Cycles are only followed once.
+	pub fn downstream(&self, asset: &Path) -> Vec<PathBuf> {
+		let mut visited = BTreeSet::from([asset.to_path_buf()]);
+		let mut queue = VecDeque::from([asset.to_path_buf()]);
+		let mut downstream = Vec::new();
+		while let Some(next) = queue.pop_front() {
+			for dependent in self.dependents(&next) {
+				if visited.insert(dependent.to_path_buf()) {
+					downstream.push(dependent.to_path_buf());
+					queue.push_back(dependent.to_path_buf());
+				}
+			}
+		}
+		downstream
+	}
+}
+
+/// The quoted strings in a text asset that look like file paths, such as the `uri` of a glTF image
+/// or a mesh named in a scene. Callers keep the ones that name assets that exist.
+pub fn find_references(contents: &str) -> Vec<String> {
+	let mut references = Vec::new();
+	let mut characters = contents.chars();
+	while characters.any(|character| character == '"') {
+		let mut reference = String::new();
+		while let Some(character) = characters.next() {
+			match character {
+				'"' => break,
+				'\\' => reference.extend(characters.next()),
+				_ => reference.push(character),
+			}
+		}
+		if Path::new(&reference).extension().is_some() && !reference.contains("://") {
+			references.push(reference);
+		}
+	}
+	references
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn paths(paths: &[&str]) -> Vec<PathBuf> {
+		paths.iter().map(PathBuf::from).collect()
+	}
+
+	#[test]
+	pub fn downstream() {
+		let mut graph = DependencyGraph::new();
+		graph.set_dependencies(Path::new("brick.material"), paths(&["brick.png", "brick_normal.png"]));
+		graph.set_dependencies(Path::new("wall.gltf"), paths(&["brick.material"]));
+		graph.set_dependencies(Path::new("level.ron"), paths(&["wall.gltf", "brick.png"]));
+		assert_eq!(
+			graph.dependents(Path::new("brick.png")).collect::<Vec<_>>(),
+			[Path::new("brick.material"), Path::new("level.ron")]
+		);
+		assert_eq!(graph.downstream(Path::new("brick_normal.png")), paths(&["brick.material", "wall.gltf", "level.ron"]));
+
+		// Cycles don't loop forever
+		graph.set_dependencies(Path::new("brick.png"), paths(&["level.ron"]));
+		assert_eq!(graph.downstream(Path::new("level.ron")), paths(&["brick.png", "brick.material", "wall.gltf"]));
+
+		graph.set_dependencies(Path::new("level.ron"), paths(&["wall.gltf"]));
+		assert_eq!(graph.dependents(Path::new("brick.png")).collect::<Vec<_>>(), [Path::new("brick.material")]);
+		graph.remove(Path::new("wall.gltf"));
+		assert!(graph.downstream(Path::new("brick.material")).is_empty());
+		assert_eq!(graph.dependencies(Path::new("level.ron")).count(), 1);
+	}
+
+	#[test]
+	pub fn references() {
+		let gltf = r#"{ "images": [{ "uri": "textures/brick.png" }], "buffers": [{ "uri": "https://example.com/wall.bin" }] }"#;
+		assert_eq!(find_references(gltf), ["textures/brick.png"]);
+		assert_eq!(find_references(r#"(mesh: "meshes/\"odd\".glb", name: "Wall")"#), [r#"meshes/"odd".glb"#]);
+	}
+}
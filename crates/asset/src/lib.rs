@@ -0,0 +1,8 @@
+mod dependencies;
+mod loader;
+mod pack;
+mod texture;
+mod vfs;
+mod video;
+
+pub use self::{dependencies::*, loader::*, pack::*, texture::*, vfs::*, video::*};
@@ -0,0 +1,223 @@
+use crate::{
+	texture::{Texture, TextureOptions},
+	vfs::AssetReader,
+};
+use handle::{Handle, SlotMap};
+use std::{
+	any::Any,
+	error,
+	path::PathBuf,
+	sync::mpsc::{self, Receiver, Sender},
+	thread,
+};
+
+/// A loaded asset, or the reason it couldn't be loaded
+pub type AssetLoadResult<T> = std::result::Result<T, Box<dyn error::Error + Send + Sync>>;
+
+type LoadJob = Box<dyn FnOnce() -> AssetLoadResult<Box<dyn Any + Send>> + Send>;
+
+/// Identifies an asset queued with an `AssetLoader`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetHandle(Handle<TrackedAsset>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetStatus {
+	Loading,
+	Loaded,
+
+	/// The load failed with the given error message
+	Failed(String),
+}
+
+struct TrackedAsset {
+	name: String,
+	status: AssetStatus,
+	value: Option<Box<dyn Any + Send>>,
+}
+
+/// Loads assets on a background thread and tracks the status of each one through its handle.
+/// Finished loads are collected on `update`, so progress can be shown while they run.
+pub struct AssetLoader {
+	assets: SlotMap<TrackedAsset>,
+	requests: Sender<(AssetHandle, LoadJob)>,
+	results: Receiver<(AssetHandle, AssetLoadResult<Box<dyn Any + Send>>)>,
+}
+
+impl Default for AssetLoader {
+	fn default() -> Self {
+		let (requests, pending) = mpsc::channel::<(AssetHandle, LoadJob)>();
+		let (finished, results) = mpsc::channel();
+		thread::spawn(move || {
+			for (handle, job) in pending {
+				if finished.send((handle, job())).is_err() {
+					break;
+				}
+			}
+		});
+		Self {
+			assets: SlotMap::new(),
+			requests,
+			results,
+		}
+	}
+}
+
+impl AssetLoader {
+	/// Queues a load, where `name` identifies the asset in progress displays and error messages
+	pub fn load<T: Send + 'static>(&mut self, name: impl Into<String>, load: impl FnOnce() -> AssetLoadResult<T> + Send + 'static) -> AssetHandle {
+		let job: LoadJob = Box::new(move || load().map(|value| Box::new(value) as Box<dyn Any + Send>));
+		AssetHandle(self.assets.insert_with(|handle| {
+			let status = match self.requests.send((AssetHandle(handle), job)) {
+				Ok(()) => AssetStatus::Loading,
+				Err(_) => AssetStatus::Failed("The asset loading thread has stopped.".to_string()),
+			};
+			TrackedAsset {
+				name: name.into(),
+				status,
+				value: None,
+			}
+		}))
+	}
+
+	pub fn load_texture(&mut self, path: impl Into<PathBuf>, options: TextureOptions) -> AssetHandle {
+		let path = path.into();
+		self.load(path.display().to_string(), move || Ok(Texture::load(path, options)?))
+	}
+
+	/// Loads a texture through an asset source or virtual filesystem
+	pub fn load_texture_from(&mut self, source: &impl AssetReader, path: impl Into<String>, options: TextureOptions) -> AssetHandle {
+		let (source, path) = (source.clone(), path.into());
+		self.load(path.clone(), move || Ok(Texture::from_bytes(&source.read(&path)?, &path, options)?))
+	}
+
+	/// Collects the loads that finished since the last update
+	pub fn update(&mut self) {
+		for (handle, result) in self.results.try_iter() {
+			let Some(asset) = self.assets.get_mut(handle.0) else {
+				continue;
+			};
+			match result {
+				Ok(value) => {
+					asset.status = AssetStatus::Loaded;
+					asset.value = Some(value);
+				},
+				Err(error) => asset.status = AssetStatus::Failed(error.to_string()),
+			}
+		}
+	}
+
+	pub fn status(&self, handle: AssetHandle) -> Option<&AssetStatus> {
+		self.assets.get(handle.0).map(|asset| &asset.status)
+	}
+
+	pub fn name(&self, handle: AssetHandle) -> Option<&str> {
+		self.assets.get(handle.0).map(|asset| asset.name.as_str())
+	}
+
+	/// Every tracked asset with its name and status, in the order they were queued
+	pub fn assets(&self) -> impl Iterator<Item = (AssetHandle, &str, &AssetStatus)> {
+		self.assets.iter().map(|(handle, asset)| (AssetHandle(handle), asset.name.as_str(), &asset.status))
+	}
+
+	/// The loaded asset, if it has finished loading, is of type `T`, and hasn't been taken
+	pub fn get<T: 'static>(&self, handle: AssetHandle) -> Option<&T> {
+		self.assets.get(handle.0)?.value.as_ref()?.downcast_ref()
+	}
+
+	/// Moves the loaded asset out, such as to upload it to the GPU. Its status stays `Loaded`.
+	pub fn take<T: 'static>(&mut self, handle: AssetHandle) -> Option<T> {
+		let asset = self.assets.get_mut(handle.0)?;
+		if !asset.value.as_ref()?.is::<T>() {
+			return None;
+		}
+		asset.value.take()?.downcast().ok().map(|value| *value)
+	}
+
+	/// Stops tracking an asset, dropping it if it was loaded
+	pub fn remove(&mut self, handle: AssetHandle) {
+		self.assets.remove(handle.0);
+	}
+
+	/// The fraction of tracked assets that finished loading or failed, or one when none are tracked
+	pub fn progress(&self) -> f32 {
+		if self.assets.is_empty() {
+			return 1.0;
+		}
+		let finished = self.assets.values().iter().filter(|asset| asset.status != AssetStatus::Loading).count();
+		finished as f32 / self.assets.len() as f32
+	}
+
+	/// Whether every tracked asset has finished loading or failed
+	pub fn is_finished(&self) -> bool {
+		self.assets.values().iter().all(|asset| asset.status != AssetStatus::Loading)
+	}
+
+	pub fn failures(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.assets.values().iter().filter_map(|asset| match &asset.status {
+			AssetStatus::Failed(error) => Some((asset.name.as_str(), error.as_str())),
+			_ => None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::{Duration, Instant};
+
+	fn wait_until_finished(loader: &mut AssetLoader) {
+		let start = Instant::now();
+		while !loader.is_finished() && start.elapsed() < Duration::from_secs(5) {
+			loader.update();
+			thread::sleep(Duration::from_millis(1));
+		}
+	}
+
+	#[test]
+	pub fn load() {
+		let mut loader = AssetLoader::default();
+		assert_eq!(loader.progress(), 1.0);
+
+		let number = loader.load("number", || Ok(42_u32));
+		let missing = loader.load_texture("missing.png", TextureOptions::default());
+		assert_eq!(loader.name(number), Some("number"));
+		wait_until_finished(&mut loader);
+
+		assert_eq!(loader.status(number), Some(&AssetStatus::Loaded));
+		assert!(matches!(loader.status(missing), Some(AssetStatus::Failed(_))));
+		assert_eq!(loader.progress(), 1.0);
+		assert_eq!(loader.failures().map(|(name, _)| name).collect::<Vec<_>>(), ["missing.png"]);
+
+		assert_eq!(loader.get::<u32>(number), Some(&42));
+		assert_eq!(loader.take::<String>(number), None);
+		assert_eq!(loader.take::<u32>(number), Some(42));
+		assert_eq!(loader.take::<u32>(number), None);
+		assert_eq!(loader.status(number), Some(&AssetStatus::Loaded));
+
+		// A removed asset's handle doesn't reach the asset queued after it
+		loader.remove(number);
+		let replacement = loader.load("replacement", || Ok(7_u32));
+		assert_eq!(loader.status(number), None);
+		assert_eq!(loader.name(replacement), Some("replacement"));
+	}
+
+	#[test]
+	pub fn progress() {
+		let mut loader = AssetLoader::default();
+		let (sender, receiver) = mpsc::channel::<()>();
+		loader.load("fast", || Ok(()));
+		let slow = loader.load("slow", move || receiver.recv().map_err(Into::into));
+		let start = Instant::now();
+		while loader.progress() < 0.5 && start.elapsed() < Duration::from_secs(5) {
+			loader.update();
+			thread::sleep(Duration::from_millis(1));
+		}
+		assert_eq!(loader.progress(), 0.5);
+		assert!(!loader.is_finished());
+		assert_eq!(loader.status(slow), Some(&AssetStatus::Loading));
+
+		sender.send(()).ok();
+		wait_until_finished(&mut loader);
+		assert_eq!(loader.status(slow), Some(&AssetStatus::Loaded));
+	}
+}
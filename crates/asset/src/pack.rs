@@ -0,0 +1,382 @@
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+use std::{
+	collections::BTreeMap,
+	fs,
+	io::{self, Read, Seek, SeekFrom, Write},
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PackError {
+	#[error("Asset '{0}' failed its integrity check. The pack may be corrupted.")]
+	CorruptAsset(String),
+
+	#[error("Failed to decompress asset '{1}'")]
+	DecompressAsset(#[source] io::Error, String),
+
+	#[error("File at path '{0}' is not an asset pack.")]
+	InvalidPack(String),
+
+	#[error("Asset '{0}' does not exist.")]
+	MissingAsset(String),
+
+	#[error("Failed to read the asset at path: {1}")]
+	ReadAsset(#[source] io::Error, String),
+
+	#[error("Failed to read the asset pack at path: {1}")]
+	ReadPack(#[source] io::Error, String),
+
+	#[error("Asset pack '{1}' uses format version {0}, which is not supported.")]
+	UnsupportedPackVersion(u16, String),
+
+	#[error("Failed to write the asset pack at path: {1}")]
+	WritePack(#[source] io::Error, String),
+}
+
+type Result<T, E = PackError> = std::result::Result<T, E>;
+
+/// Starts every `.eldpak` file
+pub const PACK_MAGIC: [u8; 6] = *b"ELDPAK";
+pub const PACK_VERSION: u16 = 1;
+pub const PACK_EXTENSION: &str = "eldpak";
+
+/// Where an asset's bytes are within a pack
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PackEntry {
+	pub offset: u64,
+	pub packed_size: u64,
+	pub size: u64,
+	pub is_compressed: bool,
+
+	/// CRC-32 of the original contents, checked whenever the asset is read
+	pub checksum: u32,
+}
+
+/// An archive of assets with an index up front, so each asset can be read without the rest.
+///
+/// Layout: magic, version, entry count, then each entry's path, offset, sizes, compression flag,
+/// and checksum, then the contents. Numbers are little-endian and paths use forward slashes.
+#[derive(Debug, Clone)]
+pub struct AssetPack {
+	path: PathBuf,
+	entries: Arc<BTreeMap<String, PackEntry>>,
+}
+
+impl AssetPack {
+	/// Reads the pack's index, checking that every asset lies within the file
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let name = path.display().to_string();
+		let mut file = fs::File::open(path).map_err(|error| PackError::ReadPack(error, name.clone()))?;
+		let length = file.metadata().map_err(|error| PackError::ReadPack(error, name.clone()))?.len();
+		let entries = read_index(&mut file, &name)?;
+		if let Some((path, _)) = entries
+			.iter()
+			.find(|(_, entry)| entry.offset.checked_add(entry.packed_size).is_none_or(|end| end > length))
+		{
+			return Err(PackError::CorruptAsset(path.clone()));
+		}
+		Ok(Self {
+			path: path.to_path_buf(),
+			entries: Arc::new(entries),
+		})
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn entries(&self) -> impl Iterator<Item = (&str, &PackEntry)> {
+		self.entries.iter().map(|(path, entry)| (path.as_str(), entry))
+	}
+
+	pub fn contains(&self, path: &str) -> bool {
+		self.entries.contains_key(&normalize(path))
+	}
+
+	/// Reads, decompresses, and verifies a single asset.
+	///
+	/// The index's sizes only bound the reads, so a damaged pack can't make it allocate too much.
+	pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+		let path = normalize(path);
+		let entry = self.entries.get(&path).ok_or_else(|| PackError::MissingAsset(path.clone()))?;
+		let name = self.path.display().to_string();
+		let mut file = fs::File::open(&self.path).map_err(|error| PackError::ReadPack(error, name.clone()))?;
+		let mut packed = Vec::new();
+		file.seek(SeekFrom::Start(entry.offset))
+			.and_then(|_| file.take(entry.packed_size).read_to_end(&mut packed))
+			.map_err(|error| PackError::ReadPack(error, name))?;
+		if packed.len() as u64 != entry.packed_size {
+			return Err(PackError::CorruptAsset(path));
+		}
+		let contents = if entry.is_compressed {
+			// One byte past the size is enough to tell that the contents are too long
+			let mut contents = Vec::new();
+			DeflateDecoder::new(packed.as_slice())
+				.take(entry.size.saturating_add(1))
+				.read_to_end(&mut contents)
+				.map_err(|error| PackError::DecompressAsset(error, path.clone()))?;
+			contents
+		} else {
+			packed
+		};
+		if contents.len() as u64 != entry.size || crc32fast::hash(&contents) != entry.checksum {
+			return Err(PackError::CorruptAsset(path));
+		}
+		Ok(contents)
+	}
+
+	/// Reads and verifies every asset, returning the paths of any that are damaged
+	pub fn verify(&self) -> Vec<String> {
+		self.entries.keys().filter(|path| self.read(path).is_err()).cloned().collect()
+	}
+}
+
+/// Packs every file under `directory` into a pack at `output`, compressing those that shrink.
+/// Returns the number of assets packed.
+pub fn pack_directory(directory: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<usize> {
+	let directory = directory.as_ref();
+	let mut files = Vec::new();
+	collect_files(directory, directory, &mut files)?;
+	let output = output.as_ref();
+	// The pack may be written inside the directory being packed, which must not pack itself
+	files.retain(|(_, path)| fs::canonicalize(path).ok() != fs::canonicalize(output).ok());
+	let assets = files
+		.into_iter()
+		.map(|(name, path)| {
+			let contents = fs::read(&path).map_err(|error| PackError::ReadAsset(error, path.display().to_string()))?;
+			Ok((name, contents))
+		})
+		.collect::<Result<Vec<_>>>()?;
+	let count = assets.len();
+	let file = fs::File::create(output).map_err(|error| PackError::WritePack(error, output.display().to_string()))?;
+	write_pack(io::BufWriter::new(file), assets).map_err(|error| PackError::WritePack(error, output.display().to_string()))?;
+	Ok(count)
+}
+
+/// Writes named assets as a pack
+pub fn write_pack(mut writer: impl Write, assets: impl IntoIterator<Item = (String, Vec<u8>)>) -> io::Result<()> {
+	let mut packed = Vec::new();
+	for (name, contents) in assets {
+		let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(&contents)?;
+		let compressed = encoder.finish()?;
+		let checksum = crc32fast::hash(&contents);
+		let size = contents.len() as u64;
+		// Already compressed formats like PNG and OGG rarely shrink, so they are stored as is
+		let (data, is_compressed) = if compressed.len() < contents.len() {
+			(compressed, true)
+		} else {
+			(contents, false)
+		};
+		packed.push((normalize(&name), data, is_compressed, size, checksum));
+	}
+
+	let index_size: usize = packed.iter().map(|(name, ..)| 2 + name.len() + 8 + 8 + 8 + 1 + 4).sum();
+	let mut offset = (PACK_MAGIC.len() + 2 + 4 + index_size) as u64;
+	writer.write_all(&PACK_MAGIC)?;
+	writer.write_all(&PACK_VERSION.to_le_bytes())?;
+	writer.write_all(&(packed.len() as u32).to_le_bytes())?;
+	for (name, data, is_compressed, size, checksum) in packed.iter() {
+		let name_length = u16::try_from(name.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("Asset path is too long: {name}")))?;
+		writer.write_all(&name_length.to_le_bytes())?;
+		writer.write_all(name.as_bytes())?;
+		writer.write_all(&offset.to_le_bytes())?;
+		writer.write_all(&(data.len() as u64).to_le_bytes())?;
+		writer.write_all(&size.to_le_bytes())?;
+		writer.write_all(&[u8::from(*is_compressed)])?;
+		writer.write_all(&checksum.to_le_bytes())?;
+		offset += data.len() as u64;
+	}
+	for (_, data, ..) in packed.iter() {
+		writer.write_all(data)?;
+	}
+	writer.flush()
+}
+
+fn read_index(reader: &mut impl Read, name: &str) -> Result<BTreeMap<String, PackEntry>> {
+	let read_error = |error: io::Error| match error.kind() {
+		io::ErrorKind::UnexpectedEof => PackError::InvalidPack(name.to_string()),
+		_ => PackError::ReadPack(error, name.to_string()),
+	};
+	let mut read = |length: usize| -> Result<Vec<u8>> {
+		let mut bytes = vec![0; length];
+		reader.read_exact(&mut bytes).map_err(read_error)?;
+		Ok(bytes)
+	};
+	if read(PACK_MAGIC.len())? != PACK_MAGIC {
+		return Err(PackError::InvalidPack(name.to_string()));
+	}
+	let version = u16::from_le_bytes(read(2)?.try_into().unwrap());
+	if version != PACK_VERSION {
+		return Err(PackError::UnsupportedPackVersion(version, name.to_string()));
+	}
+	let count = u32::from_le_bytes(read(4)?.try_into().unwrap());
+	let mut entries = BTreeMap::new();
+	for _ in 0..count {
+		let name_length = u16::from_le_bytes(read(2)?.try_into().unwrap());
+		let path = String::from_utf8(read(name_length as usize)?).map_err(|_| PackError::InvalidPack(name.to_string()))?;
+		let mut number = || -> Result<u64> { Ok(u64::from_le_bytes(read(8)?.try_into().unwrap())) };
+		let (offset, packed_size, size) = (number()?, number()?, number()?);
+		let is_compressed = read(1)?[0] != 0;
+		let checksum = u32::from_le_bytes(read(4)?.try_into().unwrap());
+		entries.insert(
+			path,
+			PackEntry {
+				offset,
+				packed_size,
+				size,
+				is_compressed,
+				checksum,
+			},
+		);
+	}
+	Ok(entries)
+}
+
+/// Every file under `directory`, named by its path relative to `root`
+fn collect_files(root: &Path, directory: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+	let entries = fs::read_dir(directory).map_err(|error| PackError::ReadAsset(error, directory.display().to_string()))?;
+	for entry in entries {
+		let path = entry.map_err(|error| PackError::ReadAsset(error, directory.display().to_string()))?.path();
+		if path.is_dir() {
+			collect_files(root, &path, files)?;
+		} else if let Ok(relative) = path.strip_prefix(root) {
+			files.push((relative.to_string_lossy().into_owned(), path));
+		}
+	}
+	Ok(())
+}
+
+/// Asset paths use forward slashes and no leading `./` on every platform
+pub(crate) fn normalize(path: &str) -> String {
+	let path = path.replace('\\', "/");
+	path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+/// Where assets are read from: loose files during development, or a pack in release builds
+#[derive(Debug, Clone)]
+pub enum AssetSource {
+	Directory(PathBuf),
+	Pack(AssetPack),
+}
+
+impl AssetSource {
+	/// Reads loose files when `directory` exists, and otherwise the pack next to it,
+	/// so development builds see edits right away while shipped builds only include the pack
+	pub fn open(directory: impl AsRef<Path>) -> Result<Self> {
+		let directory = directory.as_ref();
+		if directory.is_dir() {
+			return Ok(Self::Directory(directory.to_path_buf()));
+		}
+		AssetPack::open(directory.with_extension(PACK_EXTENSION)).map(Self::Pack)
+	}
+
+	pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+		match self {
+			Self::Directory(directory) => {
+				let path = directory.join(normalize(path));
+				fs::read(&path).map_err(|error| match error.kind() {
+					io::ErrorKind::NotFound => PackError::MissingAsset(path.display().to_string()),
+					_ => PackError::ReadAsset(error, path.display().to_string()),
+				})
+			},
+			Self::Pack(pack) => pack.read(path),
+		}
+	}
+
+	pub fn contains(&self, path: &str) -> bool {
+		match self {
+			Self::Directory(directory) => directory.join(normalize(path)).is_file(),
+			Self::Pack(pack) => pack.contains(path),
+		}
+	}
+
+	/// Every asset the source holds, with forward slashes
+	pub fn paths(&self) -> Result<Vec<String>> {
+		match self {
+			Self::Directory(directory) => {
+				let mut files = Vec::new();
+				collect_files(directory, directory, &mut files)?;
+				Ok(files.into_iter().map(|(name, _)| normalize(&name)).collect())
+			},
+			Self::Pack(pack) => Ok(pack.entries().map(|(path, _)| path.to_string()).collect()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temporary_directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("elder_pack_{name}_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory).unwrap();
+		directory
+	}
+
+	#[test]
+	pub fn pack_and_read() -> Result<()> {
+		let directory = temporary_directory("round_trip");
+		let assets = directory.join("assets");
+		fs::create_dir_all(assets.join("textures")).unwrap();
+		fs::write(assets.join("scene.ron"), "(entities: [])".repeat(64)).unwrap();
+		fs::write(assets.join("textures").join("noise.bin"), [7, 1, 250, 3]).unwrap();
+
+		let output = directory.join("assets.eldpak");
+		assert_eq!(pack_directory(&assets, &output)?, 2);
+		let pack = AssetPack::open(&output)?;
+		let entries = pack.entries().map(|(path, entry)| (path, entry.is_compressed)).collect::<Vec<_>>();
+		assert_eq!(entries, [("scene.ron", true), ("textures/noise.bin", false)]);
+		assert_eq!(pack.read("./textures\\noise.bin")?, [7, 1, 250, 3]);
+		assert!(pack.verify().is_empty());
+		assert!(matches!(pack.read("missing.png"), Err(PackError::MissingAsset(_))));
+
+		// Loose files win while they exist, and the pack is used once they are gone
+		let loose = AssetSource::open(&assets)?;
+		assert!(matches!(loose, AssetSource::Directory(_)));
+		assert_eq!(loose.read("scene.ron")?, pack.read("scene.ron")?);
+		fs::remove_dir_all(&assets).unwrap();
+		let packed = AssetSource::open(&assets)?;
+		assert!(matches!(packed, AssetSource::Pack(_)));
+		assert!(packed.contains("textures/noise.bin"));
+		fs::remove_dir_all(&directory).unwrap();
+		Ok(())
+	}
+
+	#[test]
+	pub fn detects_corruption() -> Result<()> {
+		let directory = temporary_directory("corruption");
+		let output = directory.join("assets.eldpak");
+		let mut bytes = Vec::new();
+		write_pack(&mut bytes, [("data.bin".to_string(), vec![1, 2, 3])]).unwrap();
+		// Flip a bit in the stored contents, which are at the end of the file
+		*bytes.last_mut().unwrap() ^= 1;
+		fs::write(&output, &bytes).unwrap();
+		let pack = AssetPack::open(&output)?;
+		assert!(matches!(pack.read("data.bin"), Err(PackError::CorruptAsset(_))));
+		assert_eq!(pack.verify(), ["data.bin"]);
+
+		// Contents claimed to run past the end of the file are rejected before reading them
+		let packed_size = PACK_MAGIC.len() + 2 + 4 + 2 + "data.bin".len() + 8;
+		bytes[packed_size..packed_size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+		fs::write(&output, &bytes).unwrap();
+		assert!(matches!(AssetPack::open(&output), Err(PackError::CorruptAsset(_))));
+
+		// Contents that decompress past their size stop there
+		let mut bytes = Vec::new();
+		write_pack(&mut bytes, [("data.bin".to_string(), vec![0; 4096])]).unwrap();
+		let size = packed_size + 8;
+		bytes[size..size + 8].copy_from_slice(&16_u64.to_le_bytes());
+		fs::write(&output, &bytes).unwrap();
+		assert!(matches!(AssetPack::open(&output)?.read("data.bin"), Err(PackError::CorruptAsset(_))));
+
+		fs::write(&output, b"not a pack").unwrap();
+		assert!(matches!(AssetPack::open(&output), Err(PackError::InvalidPack(_))));
+		fs::remove_dir_all(&directory).unwrap();
+		Ok(())
+	}
+}
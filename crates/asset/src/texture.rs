@@ -0,0 +1,389 @@
+use image::{DynamicImage, ImageFormat};
+use std::{fs, io, path::Path};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to decode texture: {1}")]
+	DecodeTexture(#[source] image::ImageError, String),
+
+	#[error("Failed to decode video '{1}': {0}")]
+	DecodeVideo(String, String),
+
+	#[error("Failed to parse KTX2 texture: {0}")]
+	ParseKtx2(String),
+
+	#[error("Failed to read texture file at path: {1}")]
+	ReadTexture(#[source] io::Error, String),
+
+	#[error("Failed to read video file at path: {1}")]
+	ReadVideo(#[source] io::Error, String),
+
+	#[error("KTX2 texture '{0}' uses supercompression, which requires Basis Universal transcoding and is not supported.")]
+	UnsupportedSupercompression(String),
+
+	#[error("KTX2 texture '{0}' uses an unsupported pixel format.")]
+	UnsupportedTextureFormat(String),
+
+	#[error("Video '{0}' is not an 8-bit y4m or gif file.")]
+	UnsupportedVideoFormat(String),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFormat {
+	Rgba8,
+	Rgba8Srgb,
+	Rgba32Float,
+	Bc1,
+	Bc1Srgb,
+	Bc3,
+	Bc3Srgb,
+	Bc5,
+	Bc7,
+	Bc7Srgb,
+}
+
+impl TextureFormat {
+	pub const fn is_srgb(&self) -> bool {
+		matches!(self, Self::Rgba8Srgb | Self::Bc1Srgb | Self::Bc3Srgb | Self::Bc7Srgb)
+	}
+
+	pub const fn is_compressed(&self) -> bool {
+		!matches!(self, Self::Rgba8 | Self::Rgba8Srgb | Self::Rgba32Float)
+	}
+}
+
+/// How the color values of an 8-bit texture are encoded.
+/// Albedo and UI textures are usually sRGB, while normal, roughness,
+/// and other data maps must be sampled as linear values.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorSpace {
+	#[default]
+	Srgb,
+	Linear,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TextureOptions {
+	pub color_space: ColorSpace,
+	pub generate_mipmaps: bool,
+}
+
+impl Default for TextureOptions {
+	fn default() -> Self {
+		Self {
+			color_space: ColorSpace::Srgb,
+			generate_mipmaps: true,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MipLevel {
+	pub width: u32,
+	pub height: u32,
+	pub data: Vec<u8>,
+}
+
+/// Decoded texture data, ready to be uploaded by a renderer.
+/// The first mip level is the full resolution image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+	pub format: TextureFormat,
+	pub mip_levels: Vec<MipLevel>,
+}
+
+impl Texture {
+	/// Loads a PNG, JPEG, HDR, or (with the `ktx2` feature) KTX2 file.
+	pub fn load(path: impl AsRef<Path>, options: TextureOptions) -> Result<Self> {
+		let path = path.as_ref();
+		let bytes = fs::read(path).map_err(|error| Error::ReadTexture(error, path.display().to_string()))?;
+		Self::from_bytes(&bytes, &path.display().to_string(), options)
+	}
+
+	/// Decodes a texture from memory. The `name` is only used for error messages.
+	pub fn from_bytes(bytes: &[u8], name: &str, options: TextureOptions) -> Result<Self> {
+		#[cfg(feature = "ktx2")]
+		if bytes.starts_with(&KTX2_IDENTIFIER) {
+			return Self::from_ktx2(bytes, name);
+		}
+		let format = image::guess_format(bytes).map_err(|error| Error::DecodeTexture(error, name.to_string()))?;
+		let image = image::load_from_memory_with_format(bytes, format).map_err(|error| Error::DecodeTexture(error, name.to_string()))?;
+		Ok(Self::from_image(image, format == ImageFormat::Hdr, options))
+	}
+
+	pub fn from_image(image: DynamicImage, is_hdr: bool, options: TextureOptions) -> Self {
+		let mut texture = if is_hdr {
+			let image = image.into_rgba32f();
+			let (width, height) = image.dimensions();
+			let data = image.into_raw().into_iter().flat_map(f32::to_le_bytes).collect();
+			Self {
+				format: TextureFormat::Rgba32Float,
+				mip_levels: vec![MipLevel { width, height, data }],
+			}
+		} else {
+			let image = image.into_rgba8();
+			let (width, height) = image.dimensions();
+			let format = match options.color_space {
+				ColorSpace::Srgb => TextureFormat::Rgba8Srgb,
+				ColorSpace::Linear => TextureFormat::Rgba8,
+			};
+			Self {
+				format,
+				mip_levels: vec![MipLevel {
+					width,
+					height,
+					data: image.into_raw(),
+				}],
+			}
+		};
+		if options.generate_mipmaps {
+			texture.generate_mipmaps();
+		}
+		texture
+	}
+
+	pub fn width(&self) -> u32 {
+		self.mip_levels[0].width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.mip_levels[0].height
+	}
+
+	/// The size of every mip level in bytes, which is what the texture occupies in GPU memory
+	pub fn byte_size(&self) -> usize {
+		self.mip_levels.iter().map(|level| level.data.len()).sum()
+	}
+
+	/// Reads a texel of the full resolution image as linear RGBA.
+	/// Returns `None` for compressed formats or coordinates outside the image.
+	pub fn texel(&self, x: u32, y: u32) -> Option<[f32; 4]> {
+		let level = &self.mip_levels[0];
+		if x >= level.width || y >= level.height {
+			return None;
+		}
+		let index = (y * level.width + x) as usize;
+		match self.format {
+			TextureFormat::Rgba32Float => {
+				let bytes = level.data.get(index * 16..index * 16 + 16)?;
+				let mut texel = [0.0; 4];
+				for (value, bytes) in texel.iter_mut().zip(bytes.chunks_exact(4)) {
+					*value = f32::from_le_bytes(bytes.try_into().ok()?);
+				}
+				Some(texel)
+			},
+			TextureFormat::Rgba8 | TextureFormat::Rgba8Srgb => {
+				let bytes = level.data.get(index * 4..index * 4 + 4)?;
+				let is_srgb = self.format.is_srgb();
+				let mut texel = [0.0; 4];
+				for (channel, value) in texel.iter_mut().enumerate() {
+					let linear = f32::from(bytes[channel]) / 255.0;
+					*value = if is_srgb && channel < 3 { srgb_to_linear(linear) } else { linear };
+				}
+				Some(texel)
+			},
+			_ => None,
+		}
+	}
+
+	/// Replaces any existing mip chain with one generated by a 2x2 box filter.
+	/// sRGB textures are filtered in linear space so that mips don't darken.
+	/// Compressed textures keep the mip levels stored in their file.
+	pub fn generate_mipmaps(&mut self) {
+		if self.format.is_compressed() {
+			return;
+		}
+		self.mip_levels.truncate(1);
+		while let Some(level) = self.mip_levels.last().filter(|level| level.width > 1 || level.height > 1) {
+			let next = match self.format {
+				TextureFormat::Rgba32Float => downsample_rgba32f(level),
+				format => downsample_rgba8(level, format.is_srgb()),
+			};
+			self.mip_levels.push(next);
+		}
+	}
+
+	#[cfg(feature = "ktx2")]
+	fn from_ktx2(bytes: &[u8], name: &str) -> Result<Self> {
+		let reader = ktx2::Reader::new(bytes).map_err(|error| Error::ParseKtx2(format!("{}: {:?}", name, error)))?;
+		let header = reader.header();
+		if header.supercompression_scheme.is_some() {
+			return Err(Error::UnsupportedSupercompression(name.to_string()));
+		}
+		let format = match header.format {
+			Some(ktx2::Format::R8G8B8A8_UNORM) => TextureFormat::Rgba8,
+			Some(ktx2::Format::R8G8B8A8_SRGB) => TextureFormat::Rgba8Srgb,
+			Some(ktx2::Format::R32G32B32A32_SFLOAT) => TextureFormat::Rgba32Float,
+			Some(ktx2::Format::BC1_RGBA_UNORM_BLOCK) => TextureFormat::Bc1,
+			Some(ktx2::Format::BC1_RGBA_SRGB_BLOCK) => TextureFormat::Bc1Srgb,
+			Some(ktx2::Format::BC3_UNORM_BLOCK) => TextureFormat::Bc3,
+			Some(ktx2::Format::BC3_SRGB_BLOCK) => TextureFormat::Bc3Srgb,
+			Some(ktx2::Format::BC5_UNORM_BLOCK) => TextureFormat::Bc5,
+			Some(ktx2::Format::BC7_UNORM_BLOCK) => TextureFormat::Bc7,
+			Some(ktx2::Format::BC7_SRGB_BLOCK) => TextureFormat::Bc7Srgb,
+			_ => return Err(Error::UnsupportedTextureFormat(name.to_string())),
+		};
+		let mip_levels = reader
+			.levels()
+			.enumerate()
+			.map(|(index, data)| MipLevel {
+				width: (header.pixel_width >> index).max(1),
+				height: (header.pixel_height.max(1) >> index).max(1),
+				data: data.to_vec(),
+			})
+			.collect();
+		Ok(Self { format, mip_levels })
+	}
+}
+
+#[cfg(feature = "ktx2")]
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The number of mip levels in a full chain for a texture of the given size.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+	u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+fn downsample<const STRIDE: usize>(level: &MipLevel, average: impl Fn(&[&[u8]]) -> [u8; STRIDE]) -> MipLevel {
+	let (width, height) = ((level.width / 2).max(1), (level.height / 2).max(1));
+	let texel = |x: u32, y: u32| {
+		let (x, y) = (x.min(level.width - 1), y.min(level.height - 1));
+		let offset = (y * level.width + x) as usize * STRIDE;
+		&level.data[offset..offset + STRIDE]
+	};
+	let mut data = Vec::with_capacity((width * height) as usize * STRIDE);
+	for y in 0..height {
+		for x in 0..width {
+			let (sx, sy) = (x * 2, y * 2);
+			data.extend_from_slice(&average(&[texel(sx, sy), texel(sx + 1, sy), texel(sx, sy + 1), texel(sx + 1, sy + 1)]));
+		}
+	}
+	MipLevel { width, height, data }
+}
+
+fn downsample_rgba8(level: &MipLevel, is_srgb: bool) -> MipLevel {
+	downsample::<4>(level, |texels| {
+		let mut result = [0; 4];
+		for (channel, value) in result.iter_mut().enumerate() {
+			// Alpha is always stored linearly
+			let is_color = is_srgb && channel < 3;
+			let sum = texels
+				.iter()
+				.map(|texel| {
+					let value = f32::from(texel[channel]) / 255.0;
+					if is_color { srgb_to_linear(value) } else { value }
+				})
+				.sum::<f32>();
+			let average = sum / texels.len() as f32;
+			let average = if is_color { linear_to_srgb(average) } else { average };
+			*value = (average * 255.0).round() as u8;
+		}
+		result
+	})
+}
+
+fn downsample_rgba32f(level: &MipLevel) -> MipLevel {
+	downsample::<16>(level, |texels| {
+		let mut result = [0; 16];
+		for channel in 0..4 {
+			let range = channel * 4..channel * 4 + 4;
+			let sum = texels
+				.iter()
+				.map(|texel| f32::from_le_bytes(texel[range.clone()].try_into().unwrap_or_default()))
+				.sum::<f32>();
+			result[range].copy_from_slice(&(sum / texels.len() as f32).to_le_bytes());
+		}
+		result
+	})
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+	if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+	if value <= 0.003_130_8 {
+		value * 12.92
+	} else {
+		1.055 * value.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use image::{Rgba, RgbaImage};
+
+	fn checkerboard() -> DynamicImage {
+		DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 2, |x, y| {
+			if (x + y) % 2 == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 255]) }
+		}))
+	}
+
+	#[test]
+	pub fn mip_count() {
+		assert_eq!(mip_level_count(1, 1), 1);
+		assert_eq!(mip_level_count(4, 2), 3);
+		assert_eq!(mip_level_count(1024, 512), 11);
+	}
+
+	#[test]
+	pub fn mipmaps() {
+		let texture = Texture::from_image(checkerboard(), false, TextureOptions::default());
+		assert_eq!(texture.format, TextureFormat::Rgba8Srgb);
+		assert_eq!(texture.mip_levels.len() as u32, mip_level_count(4, 2));
+		let sizes = texture.mip_levels.iter().map(|level| (level.width, level.height)).collect::<Vec<_>>();
+		assert_eq!(sizes, vec![(4, 2), (2, 1), (1, 1)]);
+		assert!(texture.mip_levels.iter().all(|level| level.data.len() == (level.width * level.height * 4) as usize));
+		assert_eq!(texture.byte_size(), (8 + 2 + 1) * 4);
+	}
+
+	#[test]
+	pub fn srgb_filtering() {
+		let linear = Texture::from_image(
+			checkerboard(),
+			false,
+			TextureOptions {
+				color_space: ColorSpace::Linear,
+				generate_mipmaps: true,
+			},
+		);
+		let srgb = Texture::from_image(checkerboard(), false, TextureOptions::default());
+
+		// Averaging black and white in linear space gives 50% intensity,
+		// which encodes to a brighter value in sRGB
+		assert_eq!(linear.mip_levels[1].data[0], 128);
+		assert_eq!(srgb.mip_levels[1].data[0], 188);
+		assert_eq!(srgb.mip_levels[1].data[3], 255);
+	}
+
+	#[test]
+	pub fn texels() {
+		let texture = Texture::from_image(checkerboard(), false, TextureOptions::default());
+		assert_eq!(texture.texel(0, 0), Some([1.0; 4]));
+		assert_eq!(texture.texel(1, 0), Some([0.0, 0.0, 0.0, 1.0]));
+		assert_eq!(texture.texel(4, 0), None);
+	}
+
+	#[test]
+	pub fn decode_png() -> Result<()> {
+		let mut bytes = io::Cursor::new(Vec::new());
+		checkerboard()
+			.write_to(&mut bytes, ImageFormat::Png)
+			.map_err(|error| Error::DecodeTexture(error, "checkerboard".to_string()))?;
+		let texture = Texture::from_bytes(
+			bytes.get_ref(),
+			"checkerboard",
+			TextureOptions {
+				generate_mipmaps: false,
+				..Default::default()
+			},
+		)?;
+		assert_eq!((texture.width(), texture.height()), (4, 2));
+		assert_eq!(texture.mip_levels.len(), 1);
+		Ok(())
+	}
+}
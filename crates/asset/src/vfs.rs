@@ -0,0 +1,172 @@
+use crate::pack::{AssetSource, PackError, normalize};
+use std::collections::BTreeSet;
+
+type Result<T, E = PackError> = std::result::Result<T, E>;
+
+/// Anything assets can be read from by path
+pub trait AssetReader: Clone + Send + 'static {
+	fn read(&self, path: &str) -> Result<Vec<u8>>;
+}
+
+impl AssetReader for AssetSource {
+	fn read(&self, path: &str) -> Result<Vec<u8>> {
+		AssetSource::read(self, path)
+	}
+}
+
+/// A source of assets mounted into a `VirtualFileSystem`
+#[derive(Debug, Clone)]
+pub struct Mount {
+	pub name: String,
+	pub source: AssetSource,
+
+	/// Mounts with a higher priority shadow the same paths in lower ones
+	pub priority: i32,
+
+	/// The directory the source appears under, such as `mods/castle`, or empty for the root
+	pub mount_point: String,
+}
+
+impl Mount {
+	/// The path within this mount's source, if `path` is under its mount point
+	fn source_path(&self, path: &str) -> Option<String> {
+		if self.mount_point.is_empty() {
+			return Some(path.to_string());
+		}
+		let relative = path.strip_prefix(self.mount_point.as_str())?.strip_prefix('/')?;
+		Some(relative.to_string())
+	}
+}
+
+/// Combines asset sources into one tree, such as the engine's assets, the game's, DLC, and mods.
+/// A path is read from the highest priority mount that has it, so later content shadows base
+/// assets by providing a file at the same path. Equal priorities prefer the last mounted.
+#[derive(Debug, Default, Clone)]
+pub struct VirtualFileSystem {
+	/// Sorted from the highest priority down
+	mounts: Vec<Mount>,
+}
+
+impl VirtualFileSystem {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn mount(&mut self, name: impl Into<String>, source: AssetSource, priority: i32) {
+		self.mount_at(name, "", source, priority);
+	}
+
+	/// Mounts a source under a directory, replacing any mount with the same name
+	pub fn mount_at(&mut self, name: impl Into<String>, mount_point: &str, source: AssetSource, priority: i32) {
+		let name = name.into();
+		self.unmount(&name);
+		let index = self.mounts.partition_point(|mount| mount.priority > priority);
+		let mount = Mount {
+			name,
+			source,
+			priority,
+			mount_point: normalize(mount_point).trim_end_matches('/').to_string(),
+		};
+		self.mounts.insert(index, mount);
+	}
+
+	pub fn unmount(&mut self, name: &str) -> Option<Mount> {
+		let index = self.mounts.iter().position(|mount| mount.name == name)?;
+		Some(self.mounts.remove(index))
+	}
+
+	/// Mounts from the highest priority down
+	pub fn mounts(&self) -> impl Iterator<Item = &Mount> {
+		self.mounts.iter()
+	}
+
+	/// The mount a path is read from
+	pub fn resolve(&self, path: &str) -> Option<&Mount> {
+		let path = normalize(path);
+		self.mounts
+			.iter()
+			.find(|mount| mount.source_path(&path).is_some_and(|source_path| mount.source.contains(&source_path)))
+	}
+
+	pub fn contains(&self, path: &str) -> bool {
+		self.resolve(path).is_some()
+	}
+
+	pub fn read(&self, path: &str) -> Result<Vec<u8>> {
+		let path = normalize(path);
+		let mount = self.resolve(&path).ok_or_else(|| PackError::MissingAsset(path.clone()))?;
+		let source_path = mount.source_path(&path).ok_or_else(|| PackError::MissingAsset(path.clone()))?;
+		mount.source.read(&source_path)
+	}
+
+	/// Every path in the combined tree, each listed once no matter how many mounts provide it
+	pub fn paths(&self) -> Result<BTreeSet<String>> {
+		let mut paths = BTreeSet::new();
+		for mount in self.mounts.iter() {
+			for path in mount.source.paths()? {
+				paths.insert(match mount.mount_point.as_str() {
+					"" => path,
+					mount_point => format!("{mount_point}/{path}"),
+				});
+			}
+		}
+		Ok(paths)
+	}
+}
+
+impl AssetReader for VirtualFileSystem {
+	fn read(&self, path: &str) -> Result<Vec<u8>> {
+		VirtualFileSystem::read(self, path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pack::write_pack;
+	use std::{fs, path::PathBuf};
+
+	fn temporary_directory(name: &str) -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("elder_vfs_{name}_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory).unwrap();
+		directory
+	}
+
+	#[test]
+	pub fn overrides() -> Result<()> {
+		let root = temporary_directory("overrides");
+		let (engine, game, modded) = (root.join("engine"), root.join("game"), root.join("mod"));
+		for directory in [&engine, &game, &modded] {
+			fs::create_dir_all(directory.join("textures")).unwrap();
+		}
+		fs::write(engine.join("textures/missing.png"), "engine").unwrap();
+		fs::write(engine.join("textures/grass.png"), "engine").unwrap();
+		fs::write(game.join("textures/grass.png"), "game").unwrap();
+		fs::write(modded.join("textures/grass.png"), "mod").unwrap();
+		let dlc = root.join("dlc.eldpak");
+		write_pack(fs::File::create(&dlc).unwrap(), [("castle.ron".to_string(), b"dlc".to_vec())]).unwrap();
+
+		let mut vfs = VirtualFileSystem::new();
+		vfs.mount("game", AssetSource::Directory(game), 10);
+		vfs.mount("engine", AssetSource::Directory(engine), 0);
+		vfs.mount_at("dlc", "dlc/", AssetSource::open(root.join("dlc"))?, 10);
+		assert_eq!(vfs.read("textures/grass.png")?, b"game");
+		assert_eq!(vfs.read("./textures/missing.png")?, b"engine");
+		assert_eq!(vfs.read("dlc/castle.ron")?, b"dlc");
+		assert!(!vfs.contains("castle.ron"));
+		assert!(matches!(vfs.read("textures/sky.png"), Err(PackError::MissingAsset(_))));
+
+		// A mod at the same priority as the game was mounted later, so it wins until unmounted
+		vfs.mount("mod", AssetSource::Directory(modded), 10);
+		assert_eq!(vfs.resolve("textures/grass.png").map(|mount| mount.name.as_str()), Some("mod"));
+		assert_eq!(vfs.read("textures/grass.png")?, b"mod");
+		assert!(vfs.unmount("mod").is_some());
+		assert_eq!(vfs.read("textures/grass.png")?, b"game");
+
+		let paths = vfs.paths()?.into_iter().collect::<Vec<_>>();
+		assert_eq!(paths, ["dlc/castle.ron", "textures/grass.png", "textures/missing.png"]);
+		fs::remove_dir_all(&root).unwrap();
+		Ok(())
+	}
+}
@@ -0,0 +1,340 @@
+use crate::texture::{Error, MipLevel, Texture, TextureFormat};
+use image::{AnimationDecoder, Frames, ImageDecoder, codecs::gif::GifDecoder};
+use std::{
+	fs::File,
+	io::BufReader,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+use y4m::Colorspace;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A decoded video frame in RGBA8 and how long it stays on screen
+pub struct VideoFrame {
+	pub rgba: Vec<u8>,
+	pub duration: Duration,
+}
+
+/// A stream of video frames decoded on demand
+pub trait VideoSource {
+	fn width(&self) -> u32;
+	fn height(&self) -> u32;
+
+	/// Decodes the next frame, returning `None` at the end of the video
+	fn next_frame(&mut self) -> Result<Option<VideoFrame>>;
+
+	/// Restarts decoding from the first frame
+	fn rewind(&mut self) -> Result<()>;
+}
+
+/// Opens a video source based on the file extension.
+/// Uncompressed `.y4m` video is suited to cutscenes transcoded ahead of time,
+/// and animated `.gif` files to small looping menu backgrounds.
+pub fn open_video(path: impl AsRef<Path>) -> Result<Box<dyn VideoSource>> {
+	let path = path.as_ref();
+	let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_lowercase();
+	match extension.as_str() {
+		"y4m" => Ok(Box::new(Y4mVideo::open(path)?)),
+		"gif" => Ok(Box::new(GifVideo::open(path)?)),
+		_ => Err(Error::UnsupportedVideoFormat(path.display().to_string())),
+	}
+}
+
+fn open_file(path: &Path) -> Result<BufReader<File>> {
+	let file = File::open(path).map_err(|error| Error::ReadVideo(error, path.display().to_string()))?;
+	Ok(BufReader::new(file))
+}
+
+/// Reads 8-bit YUV4MPEG2 video, converting each frame from BT.601 YUV to RGBA
+pub struct Y4mVideo {
+	path: PathBuf,
+	decoder: y4m::Decoder<BufReader<File>>,
+	frame_duration: Duration,
+}
+
+impl Y4mVideo {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let decoder = y4m::decode(open_file(&path)?).map_err(|error| Error::DecodeVideo(error.to_string(), path.display().to_string()))?;
+		if decoder.get_bit_depth() != 8 {
+			return Err(Error::UnsupportedVideoFormat(path.display().to_string()));
+		}
+		let framerate = decoder.get_framerate();
+		let frame_duration = Duration::from_secs_f64(framerate.den as f64 / framerate.num.max(1) as f64);
+		Ok(Self { path, decoder, frame_duration })
+	}
+}
+
+impl VideoSource for Y4mVideo {
+	fn width(&self) -> u32 {
+		self.decoder.get_width() as u32
+	}
+
+	fn height(&self) -> u32 {
+		self.decoder.get_height() as u32
+	}
+
+	fn next_frame(&mut self) -> Result<Option<VideoFrame>> {
+		let (width, height) = (self.decoder.get_width(), self.decoder.get_height());
+		let colorspace = self.decoder.get_colorspace();
+		let frame = match self.decoder.read_frame() {
+			Ok(frame) => frame,
+			Err(y4m::Error::EOF) => return Ok(None),
+			Err(error) => return Err(Error::DecodeVideo(error.to_string(), self.path.display().to_string())),
+		};
+		let rgba = yuv_to_rgba(width, height, colorspace, [frame.get_y_plane(), frame.get_u_plane(), frame.get_v_plane()]);
+		Ok(Some(VideoFrame {
+			rgba,
+			duration: self.frame_duration,
+		}))
+	}
+
+	fn rewind(&mut self) -> Result<()> {
+		*self = Self::open(&self.path)?;
+		Ok(())
+	}
+}
+
+fn yuv_to_rgba(width: usize, height: usize, colorspace: Colorspace, [y_plane, u_plane, v_plane]: [&[u8]; 3]) -> Vec<u8> {
+	let (x_shift, y_shift) = match colorspace {
+		Colorspace::C444 => (0, 0),
+		Colorspace::C422 => (1, 0),
+		_ => (1, 1),
+	};
+	let chroma_width = (width + x_shift) >> x_shift;
+	let is_mono = matches!(colorspace, Colorspace::Cmono);
+	let mut rgba = Vec::with_capacity(width * height * 4);
+	for y in 0..height {
+		for x in 0..width {
+			let luma = f32::from(y_plane[y * width + x]) - 16.0;
+			let (u, v) = if is_mono {
+				(0.0, 0.0)
+			} else {
+				let index = (y >> y_shift) * chroma_width + (x >> x_shift);
+				(f32::from(u_plane[index]) - 128.0, f32::from(v_plane[index]) - 128.0)
+			};
+			let luma = 1.164 * luma;
+			let red = luma + 1.596 * v;
+			let green = luma - 0.392 * u - 0.813 * v;
+			let blue = luma + 2.017 * u;
+			rgba.extend([red, green, blue].map(|channel| channel.round().clamp(0.0, 255.0) as u8));
+			rgba.push(255);
+		}
+	}
+	rgba
+}
+
+/// Plays the frames of an animated GIF, which are already composited to the full canvas
+pub struct GifVideo {
+	path: PathBuf,
+	frames: Frames<'static>,
+	width: u32,
+	height: u32,
+}
+
+impl GifVideo {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let decoder = GifDecoder::new(open_file(&path)?).map_err(|error| Error::DecodeVideo(error.to_string(), path.display().to_string()))?;
+		let (width, height) = decoder.dimensions();
+		Ok(Self {
+			path,
+			frames: decoder.into_frames(),
+			width,
+			height,
+		})
+	}
+}
+
+impl VideoSource for GifVideo {
+	fn width(&self) -> u32 {
+		self.width
+	}
+
+	fn height(&self) -> u32 {
+		self.height
+	}
+
+	fn next_frame(&mut self) -> Result<Option<VideoFrame>> {
+		let Some(frame) = self.frames.next() else {
+			return Ok(None);
+		};
+		let frame = frame.map_err(|error| Error::DecodeVideo(error.to_string(), self.path.display().to_string()))?;
+		let (numerator, denominator) = frame.delay().numer_denom_ms();
+		Ok(Some(VideoFrame {
+			duration: Duration::from_secs_f64(f64::from(numerator) / f64::from(denominator.max(1)) / 1000.0),
+			rgba: frame.into_buffer().into_raw(),
+		}))
+	}
+
+	fn rewind(&mut self) -> Result<()> {
+		*self = Self::open(&self.path)?;
+		Ok(())
+	}
+}
+
+/// Advances a video source in real time and exposes the current frame as a texture,
+/// so renderers can draw it on a sprite, UI image, or fullscreen quad.
+pub struct VideoPlayer {
+	source: Box<dyn VideoSource>,
+	texture: Texture,
+	remaining: Duration,
+	is_looping: bool,
+	is_paused: bool,
+	is_finished: bool,
+	has_frames: bool,
+}
+
+impl VideoPlayer {
+	pub fn new(source: Box<dyn VideoSource>) -> Self {
+		let (width, height) = (source.width(), source.height());
+		Self {
+			source,
+			texture: Texture {
+				format: TextureFormat::Rgba8Srgb,
+				mip_levels: vec![MipLevel {
+					width,
+					height,
+					data: vec![0; width as usize * height as usize * 4],
+				}],
+			},
+			remaining: Duration::ZERO,
+			is_looping: false,
+			is_paused: false,
+			is_finished: false,
+			has_frames: false,
+		}
+	}
+
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		Ok(Self::new(open_video(path)?))
+	}
+
+	/// The current frame. Its contents change whenever `update` returns `true`.
+	pub fn texture(&self) -> &Texture {
+		&self.texture
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.is_looping
+	}
+
+	pub fn set_looping(&mut self, is_looping: bool) {
+		self.is_looping = is_looping;
+	}
+
+	pub fn is_paused(&self) -> bool {
+		self.is_paused
+	}
+
+	pub fn pause(&mut self) {
+		self.is_paused = true;
+	}
+
+	pub fn play(&mut self) {
+		self.is_paused = false;
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.is_finished
+	}
+
+	/// Restarts playback from the first frame
+	pub fn restart(&mut self) -> Result<()> {
+		self.source.rewind()?;
+		self.remaining = Duration::ZERO;
+		self.is_finished = false;
+		Ok(())
+	}
+
+	/// Advances playback by `delta`, skipping frames if the game falls behind.
+	/// Returns whether the texture now holds a new frame.
+	pub fn update(&mut self, delta: Duration) -> Result<bool> {
+		if self.is_paused || self.is_finished {
+			return Ok(false);
+		}
+		let mut frame = None;
+		while self.remaining <= delta {
+			match self.source.next_frame()? {
+				Some(next) => {
+					self.remaining += next.duration.max(MINIMUM_FRAME_DURATION);
+					self.has_frames = true;
+					frame = Some(next);
+				},
+				// A video without any frames would otherwise rewind forever
+				None if self.is_looping && self.has_frames => self.source.rewind()?,
+				None => {
+					self.is_finished = true;
+					break;
+				},
+			}
+		}
+		self.remaining = self.remaining.saturating_sub(delta);
+		let Some(frame) = frame else {
+			return Ok(false);
+		};
+		self.texture.mip_levels[0].data = frame.rgba;
+		Ok(true)
+	}
+}
+
+/// Frames with no delay would otherwise be decoded in an endless loop
+const MINIMUM_FRAME_DURATION: Duration = Duration::from_millis(10);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn write_y4m(name: &str, frames: u8) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("elder-video-{}-{}.y4m", name, std::process::id()));
+		let file = File::create(&path).unwrap();
+		let builder = y4m::encode(2, 2, y4m::Ratio::new(10, 1)).with_colorspace(Colorspace::C444);
+		let mut encoder = builder.write_header(file).unwrap();
+		for frame in 0..frames {
+			let luma = [16 + frame * 100; 4];
+			let chroma = [128; 4];
+			encoder.write_frame(&y4m::Frame::new([&luma, &chroma, &chroma], None)).unwrap();
+		}
+		path
+	}
+
+	#[test]
+	pub fn yuv_conversion() {
+		let black = yuv_to_rgba(1, 1, Colorspace::C444, [&[16], &[128], &[128]]);
+		assert_eq!(black, [0, 0, 0, 255]);
+		let white = yuv_to_rgba(1, 1, Colorspace::C444, [&[235], &[128], &[128]]);
+		assert_eq!(white, [255, 255, 255, 255]);
+	}
+
+	#[test]
+	pub fn playback() -> Result<()> {
+		let path = write_y4m("playback", 2);
+		let mut player = VideoPlayer::open(&path)?;
+		assert_eq!((player.texture().width(), player.texture().height()), (2, 2));
+
+		assert!(player.update(Duration::ZERO)?);
+		assert_eq!(player.texture().mip_levels[0].data[..4], [0, 0, 0, 255]);
+		assert!(!player.update(Duration::from_millis(50))?);
+		assert!(player.update(Duration::from_millis(50))?);
+		assert!(player.texture().mip_levels[0].data[0] > 0);
+
+		assert!(!player.update(Duration::from_millis(100))?);
+		assert!(player.is_finished());
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn looping() -> Result<()> {
+		let path = write_y4m("looping", 2);
+		let mut player = VideoPlayer::open(&path)?;
+		player.set_looping(true);
+		for _ in 0..5 {
+			assert!(player.update(Duration::from_millis(100))?);
+		}
+		assert!(!player.is_finished());
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+}
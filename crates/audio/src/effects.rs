@@ -0,0 +1,141 @@
+/// Processes a bus's interleaved samples in place before its volume is applied
+pub trait Effect: Send {
+	fn process(&mut self, samples: &mut [f32], channels: usize, sample_rate: u32);
+}
+
+/// A one-pole low-pass filter, useful for muffling sounds underwater or behind walls
+pub struct LowPassFilter {
+	cutoff_hz: f32,
+	previous: Vec<f32>,
+}
+
+impl LowPassFilter {
+	pub fn new(cutoff_hz: f32) -> Self {
+		Self { cutoff_hz, previous: Vec::new() }
+	}
+
+	pub fn cutoff_hz(&self) -> f32 {
+		self.cutoff_hz
+	}
+
+	pub fn set_cutoff_hz(&mut self, cutoff_hz: f32) {
+		self.cutoff_hz = cutoff_hz.max(0.0);
+	}
+}
+
+impl Effect for LowPassFilter {
+	fn process(&mut self, samples: &mut [f32], channels: usize, sample_rate: u32) {
+		let alpha = 1.0 - (-std::f32::consts::TAU * self.cutoff_hz / sample_rate as f32).exp();
+		self.previous.resize(channels, 0.0);
+		for frame in samples.chunks_exact_mut(channels) {
+			for (sample, previous) in frame.iter_mut().zip(self.previous.iter_mut()) {
+				*previous += alpha * (*sample - *previous);
+				*sample = *previous;
+			}
+		}
+	}
+}
+
+/// Comb filter delays in frames at 44.1kHz, taken from Freeverb
+const COMB_DELAYS: [usize; 4] = [1116, 1188, 1277, 1356];
+
+struct Comb {
+	buffer: Vec<f32>,
+	position: usize,
+}
+
+impl Comb {
+	fn process(&mut self, input: f32, feedback: f32) -> f32 {
+		let output = self.buffer[self.position];
+		self.buffer[self.position] = input + output * feedback;
+		self.position = (self.position + 1) % self.buffer.len();
+		output
+	}
+}
+
+/// A small Schroeder-style reverb built from parallel feedback comb filters.
+/// Place it on a dedicated bus and route other buses to it with sends.
+pub struct Reverb {
+	room_size: f32,
+	wet: f32,
+	combs: Vec<Vec<Comb>>,
+	sample_rate: u32,
+}
+
+impl Reverb {
+	/// `room_size` controls how long the tail rings, from `0.0` to just below `1.0`,
+	/// and `wet` is the level of the reverberated signal mixed with the dry signal.
+	pub fn new(room_size: f32, wet: f32) -> Self {
+		Self {
+			room_size: room_size.clamp(0.0, 0.98),
+			wet,
+			combs: Vec::new(),
+			sample_rate: 0,
+		}
+	}
+
+	pub fn room_size(&self) -> f32 {
+		self.room_size
+	}
+
+	pub fn wet(&self) -> f32 {
+		self.wet
+	}
+
+	fn prepare(&mut self, channels: usize, sample_rate: u32) {
+		if self.combs.len() == channels && self.sample_rate == sample_rate {
+			return;
+		}
+		self.sample_rate = sample_rate;
+		self.combs = (0..channels)
+			.map(|channel| {
+				COMB_DELAYS
+					.iter()
+					// Offsetting each channel's delays keeps the stereo image wide
+					.map(|delay| (delay + channel * 23) * sample_rate as usize / 44100)
+					.map(|length| Comb {
+						buffer: vec![0.0; length.max(1)],
+						position: 0,
+					})
+					.collect()
+			})
+			.collect();
+	}
+}
+
+impl Effect for Reverb {
+	fn process(&mut self, samples: &mut [f32], channels: usize, sample_rate: u32) {
+		self.prepare(channels, sample_rate);
+		for frame in samples.chunks_exact_mut(channels) {
+			for (sample, combs) in frame.iter_mut().zip(self.combs.iter_mut()) {
+				let input = *sample;
+				let reverberated: f32 = combs.iter_mut().map(|comb| comb.process(input, self.room_size)).sum::<f32>() / COMB_DELAYS.len() as f32;
+				*sample = input + reverberated * self.wet;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn low_pass_smooths_steps() {
+		let mut filter = LowPassFilter::new(1000.0);
+		let mut samples = vec![1.0; 8];
+		filter.process(&mut samples, 1, 44100);
+		assert!(samples[0] > 0.0 && samples[0] < 1.0);
+		assert!(samples.windows(2).all(|pair| pair[1] > pair[0]));
+	}
+
+	#[test]
+	pub fn reverb_tail() {
+		let mut reverb = Reverb::new(0.5, 1.0);
+		let mut samples = vec![0.0; 2000];
+		samples[0] = 1.0;
+		reverb.process(&mut samples, 1, 44100);
+		assert_eq!(samples[0], 1.0);
+		assert_eq!(samples[COMB_DELAYS[0]], 0.25);
+	}
+}
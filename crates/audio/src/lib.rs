@@ -1,14 +1,7 @@
-pub fn add(left: usize, right: usize) -> usize {
-	left + right
-}
+mod effects;
+mod mixer;
+mod music;
+mod spatial;
+mod stream;
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-
-	#[test]
-	fn it_works() {
-		let result = add(2, 2);
-		assert_eq!(result, 4);
-	}
-}
+pub use self::{effects::*, mixer::*, music::*, spatial::*, stream::*};
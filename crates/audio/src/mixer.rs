@@ -0,0 +1,301 @@
+use crate::{effects::Effect, stream::Error};
+use std::collections::BTreeMap;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+pub const MASTER_BUS: &str = "master";
+pub const MUSIC_BUS: &str = "music";
+pub const SFX_BUS: &str = "sfx";
+
+/// Routes a copy of a bus's output to another bus, such as a shared reverb
+pub struct BusSend {
+	pub bus: String,
+	pub level: f32,
+}
+
+pub struct Bus {
+	parent: Option<String>,
+	volume: f32,
+	is_muted: bool,
+	effects: Vec<Box<dyn Effect>>,
+	sends: Vec<BusSend>,
+	buffer: Vec<f32>,
+}
+
+impl Bus {
+	fn new(parent: Option<String>) -> Self {
+		Self {
+			parent,
+			volume: 1.0,
+			is_muted: false,
+			effects: Vec::new(),
+			sends: Vec::new(),
+			buffer: Vec::new(),
+		}
+	}
+
+	pub fn parent(&self) -> Option<&str> {
+		self.parent.as_deref()
+	}
+
+	pub fn volume(&self) -> f32 {
+		self.volume
+	}
+
+	pub fn is_muted(&self) -> bool {
+		self.is_muted
+	}
+
+	pub fn sends(&self) -> &[BusSend] {
+		&self.sends
+	}
+
+	/// The gain applied to the bus after its effects
+	pub fn gain(&self) -> f32 {
+		if self.is_muted { 0.0 } else { self.volume }
+	}
+
+	fn outputs(&self) -> impl Iterator<Item = &str> {
+		self.parent.as_deref().into_iter().chain(self.sends.iter().map(|send| send.bus.as_str()))
+	}
+}
+
+/// A graph of named buses that sounds are mixed into.
+/// Every bus feeds its parent and ends at the master bus,
+/// which starts with `music` and `sfx` buses routed into it.
+pub struct Mixer {
+	channels: usize,
+	sample_rate: u32,
+	buses: BTreeMap<String, Bus>,
+}
+
+impl Mixer {
+	pub fn new(channels: u16, sample_rate: u32) -> Self {
+		let mut buses = BTreeMap::new();
+		buses.insert(MASTER_BUS.to_string(), Bus::new(None));
+		buses.insert(MUSIC_BUS.to_string(), Bus::new(Some(MASTER_BUS.to_string())));
+		buses.insert(SFX_BUS.to_string(), Bus::new(Some(MASTER_BUS.to_string())));
+		Self {
+			channels: usize::from(channels.max(1)),
+			sample_rate,
+			buses,
+		}
+	}
+
+	pub fn channels(&self) -> u16 {
+		self.channels as u16
+	}
+
+	pub fn sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	/// Adds a bus that feeds `parent`, replacing any existing bus with the same name
+	pub fn add_bus(&mut self, name: impl Into<String>, parent: &str) -> Result<()> {
+		let name = name.into();
+		if name == MASTER_BUS {
+			return Err(Error::BusCycle(name, parent.to_string()));
+		}
+		if !self.buses.contains_key(parent) {
+			return Err(Error::UnknownBus(parent.to_string()));
+		}
+		if self.routes_to(parent, &name) {
+			return Err(Error::BusCycle(name, parent.to_string()));
+		}
+		self.buses.insert(name, Bus::new(Some(parent.to_string())));
+		Ok(())
+	}
+
+	pub fn bus(&self, name: &str) -> Option<&Bus> {
+		self.buses.get(name)
+	}
+
+	pub fn buses(&self) -> impl Iterator<Item = (&String, &Bus)> {
+		self.buses.iter()
+	}
+
+	fn bus_mut(&mut self, name: &str) -> Result<&mut Bus> {
+		self.buses.get_mut(name).ok_or_else(|| Error::UnknownBus(name.to_string()))
+	}
+
+	pub fn set_volume(&mut self, name: &str, volume: f32) -> Result<()> {
+		self.bus_mut(name)?.volume = volume.max(0.0);
+		Ok(())
+	}
+
+	pub fn set_muted(&mut self, name: &str, is_muted: bool) -> Result<()> {
+		self.bus_mut(name)?.is_muted = is_muted;
+		Ok(())
+	}
+
+	pub fn add_effect(&mut self, name: &str, effect: impl Effect + 'static) -> Result<()> {
+		self.bus_mut(name)?.effects.push(Box::new(effect));
+		Ok(())
+	}
+
+	pub fn clear_effects(&mut self, name: &str) -> Result<()> {
+		self.bus_mut(name)?.effects.clear();
+		Ok(())
+	}
+
+	/// Sends a copy of `from`'s output to `to` at the given level,
+	/// updating the level if the send already exists.
+	pub fn set_send(&mut self, from: &str, to: &str, level: f32) -> Result<()> {
+		if !self.buses.contains_key(to) {
+			return Err(Error::UnknownBus(to.to_string()));
+		}
+		if from == to || self.routes_to(to, from) {
+			return Err(Error::BusCycle(from.to_string(), to.to_string()));
+		}
+		let bus = self.bus_mut(from)?;
+		match bus.sends.iter_mut().find(|send| send.bus == to) {
+			Some(send) => send.level = level,
+			None => bus.sends.push(BusSend { bus: to.to_string(), level }),
+		}
+		Ok(())
+	}
+
+	pub fn remove_send(&mut self, from: &str, to: &str) -> Result<()> {
+		self.bus_mut(from)?.sends.retain(|send| send.bus != to);
+		Ok(())
+	}
+
+	/// The product of the gains from a bus up to the master bus, ignoring sends
+	pub fn effective_gain(&self, name: &str) -> Result<f32> {
+		let mut gain = 1.0;
+		let mut current = Some(name);
+		while let Some(name) = current {
+			let bus = self.bus(name).ok_or_else(|| Error::UnknownBus(name.to_string()))?;
+			gain *= bus.gain();
+			current = bus.parent();
+		}
+		Ok(gain)
+	}
+
+	/// Clears every bus and sizes it for the next block of `frames` frames
+	pub fn begin(&mut self, frames: usize) {
+		let length = frames * self.channels;
+		for bus in self.buses.values_mut() {
+			bus.buffer.clear();
+			bus.buffer.resize(length, 0.0);
+		}
+	}
+
+	/// The interleaved input buffer for a bus, which sounds are added to between `begin` and `mix`
+	pub fn input(&mut self, name: &str) -> Result<&mut [f32]> {
+		Ok(&mut self.bus_mut(name)?.buffer)
+	}
+
+	/// Processes every bus in routing order and writes the master bus into `output`
+	pub fn mix(&mut self, output: &mut [f32]) {
+		for name in self.routing_order() {
+			let Some(bus) = self.buses.get_mut(&name) else {
+				continue;
+			};
+			let mut buffer = std::mem::take(&mut bus.buffer);
+			for effect in bus.effects.iter_mut() {
+				effect.process(&mut buffer, self.channels, self.sample_rate);
+			}
+			let gain = bus.gain();
+			buffer.iter_mut().for_each(|sample| *sample *= gain);
+
+			let mut targets = bus.sends.iter().map(|send| (send.bus.clone(), send.level)).collect::<Vec<_>>();
+			match bus.parent.clone() {
+				Some(parent) => targets.push((parent, 1.0)),
+				None => output.iter_mut().zip(buffer.iter()).for_each(|(output, sample)| *output = *sample),
+			}
+			for (target, level) in targets {
+				if let Some(target) = self.buses.get_mut(&target) {
+					target.buffer.iter_mut().zip(buffer.iter()).for_each(|(target, sample)| *target += sample * level);
+				}
+			}
+			if let Some(bus) = self.buses.get_mut(&name) {
+				bus.buffer = buffer;
+			}
+		}
+	}
+
+	/// Whether audio from `from` eventually reaches `to` through parents or sends
+	fn routes_to(&self, from: &str, to: &str) -> bool {
+		let mut pending = vec![from];
+		while let Some(name) = pending.pop() {
+			if name == to {
+				return true;
+			}
+			if let Some(bus) = self.buses.get(name) {
+				pending.extend(bus.outputs());
+			}
+		}
+		false
+	}
+
+	/// Orders buses so that each one is processed after every bus feeding into it
+	fn routing_order(&self) -> Vec<String> {
+		let mut inputs = self.buses.keys().map(|name| (name.as_str(), 0)).collect::<BTreeMap<_, _>>();
+		for bus in self.buses.values() {
+			for output in bus.outputs() {
+				if let Some(count) = inputs.get_mut(output) {
+					*count += 1;
+				}
+			}
+		}
+		let mut ready = inputs.iter().filter(|(_, count)| **count == 0).map(|(name, _)| *name).collect::<Vec<_>>();
+		let mut order = Vec::with_capacity(self.buses.len());
+		while let Some(name) = ready.pop() {
+			order.push(name.to_string());
+			for output in self.buses[name].outputs() {
+				if let Some(count) = inputs.get_mut(output) {
+					*count -= 1;
+					if *count == 0 {
+						ready.push(output);
+					}
+				}
+			}
+		}
+		order
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn routing() -> Result<()> {
+		let mut mixer = Mixer::new(1, 44100);
+		mixer.add_bus("footsteps", SFX_BUS)?;
+		mixer.set_volume(SFX_BUS, 0.5)?;
+		mixer.set_volume(MASTER_BUS, 0.5)?;
+		assert_eq!(mixer.effective_gain("footsteps")?, 0.25);
+
+		mixer.begin(2);
+		mixer.input("footsteps")?.copy_from_slice(&[1.0, 1.0]);
+		mixer.input(MUSIC_BUS)?.copy_from_slice(&[1.0, 0.0]);
+		let mut output = vec![0.0; 2];
+		mixer.mix(&mut output);
+		assert_eq!(output, [0.75, 0.25]);
+
+		mixer.set_muted(MUSIC_BUS, true)?;
+		mixer.begin(2);
+		mixer.input(MUSIC_BUS)?.copy_from_slice(&[1.0, 1.0]);
+		mixer.mix(&mut output);
+		assert_eq!(output, [0.0, 0.0]);
+		Ok(())
+	}
+
+	#[test]
+	pub fn sends() -> Result<()> {
+		let mut mixer = Mixer::new(1, 44100);
+		mixer.add_bus("reverb", MASTER_BUS)?;
+		mixer.set_send(SFX_BUS, "reverb", 0.5)?;
+		assert!(matches!(mixer.set_send("reverb", SFX_BUS, 1.0), Err(Error::BusCycle(..))));
+		assert!(matches!(mixer.add_bus("echo", "missing"), Err(Error::UnknownBus(..))));
+
+		mixer.begin(1);
+		mixer.input(SFX_BUS)?[0] = 1.0;
+		let mut output = vec![0.0; 1];
+		mixer.mix(&mut output);
+		assert_eq!(output, [1.5]);
+		Ok(())
+	}
+}
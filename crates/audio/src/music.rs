@@ -0,0 +1,191 @@
+use crate::stream::{AudioStream, Error};
+use std::time::Duration;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+struct Crossfade {
+	outgoing: AudioStream,
+	elapsed_frames: usize,
+	duration_frames: usize,
+}
+
+/// Plays one streamed music track at a time and crossfades between tracks.
+/// Streams are mixed into the output's channel layout,
+/// but are expected to already match the output sample rate.
+pub struct MusicPlayer {
+	channels: u16,
+	sample_rate: u32,
+	volume: f32,
+	current: Option<AudioStream>,
+	crossfade: Option<Crossfade>,
+	scratch: Vec<f32>,
+}
+
+impl MusicPlayer {
+	pub fn new(channels: u16, sample_rate: u32) -> Self {
+		Self {
+			channels,
+			sample_rate,
+			volume: 1.0,
+			current: None,
+			crossfade: None,
+			scratch: Vec::new(),
+		}
+	}
+
+	pub fn volume(&self) -> f32 {
+		self.volume
+	}
+
+	pub fn set_volume(&mut self, volume: f32) {
+		self.volume = volume.max(0.0);
+	}
+
+	pub fn is_playing(&self) -> bool {
+		self.current.is_some()
+	}
+
+	pub fn is_crossfading(&self) -> bool {
+		self.crossfade.is_some()
+	}
+
+	pub fn current(&self) -> Option<&AudioStream> {
+		self.current.as_ref()
+	}
+
+	/// Switches to a track immediately, cancelling any crossfade in progress
+	pub fn play(&mut self, track: AudioStream) {
+		self.crossfade = None;
+		self.current = Some(track);
+	}
+
+	/// Fades the current track out while `track` fades in over `duration`.
+	/// If nothing is playing the new track fades in from silence.
+	pub fn crossfade_to(&mut self, track: AudioStream, duration: Duration) {
+		let duration_frames = (duration.as_secs_f64() * f64::from(self.sample_rate)).round() as usize;
+		self.crossfade = match self.current.replace(track) {
+			Some(outgoing) if duration_frames > 0 => Some(Crossfade {
+				outgoing,
+				elapsed_frames: 0,
+				duration_frames,
+			}),
+			_ => None,
+		};
+	}
+
+	pub fn stop(&mut self) {
+		self.current = None;
+		self.crossfade = None;
+	}
+
+	/// Mixes the next `output.len() / channels` frames of music into `output`,
+	/// adding to whatever the buffer already contains.
+	pub fn mix(&mut self, output: &mut [f32]) -> Result<()> {
+		let channels = usize::from(self.channels.max(1));
+		let frames = output.len() / channels;
+
+		let (fade_start, fade_frames) = match self.crossfade.as_mut() {
+			Some(crossfade) => {
+				let start = crossfade.elapsed_frames;
+				crossfade.elapsed_frames += frames;
+				let duration = crossfade.duration_frames;
+				mix_stream(&mut crossfade.outgoing, &mut self.scratch, output, channels, |frame| {
+					self.volume * fade_out_gain(start + frame, duration)
+				})?;
+				(start, Some(duration))
+			},
+			None => (0, None),
+		};
+
+		if let Some(current) = self.current.as_mut() {
+			let volume = self.volume;
+			mix_stream(current, &mut self.scratch, output, channels, |frame| match fade_frames {
+				Some(duration) => volume * fade_in_gain(fade_start + frame, duration),
+				None => volume,
+			})?;
+			if current.is_finished() {
+				self.current = None;
+			}
+		}
+
+		let is_faded = |crossfade: &Crossfade| crossfade.elapsed_frames >= crossfade.duration_frames || crossfade.outgoing.is_finished();
+		if self.crossfade.as_ref().is_some_and(is_faded) {
+			self.crossfade = None;
+		}
+		Ok(())
+	}
+}
+
+/// Equal-power gains keep the perceived loudness steady through the middle of a crossfade
+fn fade_in_gain(frame: usize, duration: usize) -> f32 {
+	let progress = (frame as f32 / duration as f32).min(1.0);
+	(progress * std::f32::consts::FRAC_PI_2).sin()
+}
+
+fn fade_out_gain(frame: usize, duration: usize) -> f32 {
+	let progress = (frame as f32 / duration as f32).min(1.0);
+	(progress * std::f32::consts::FRAC_PI_2).cos()
+}
+
+fn mix_stream(stream: &mut AudioStream, scratch: &mut Vec<f32>, output: &mut [f32], channels: usize, gain: impl Fn(usize) -> f32) -> Result<()> {
+	let stream_channels = usize::from(stream.channels().max(1));
+	let frames = output.len() / channels;
+	scratch.resize(frames * stream_channels, 0.0);
+	let read_frames = stream.read(scratch)? / stream_channels;
+	for frame in 0..read_frames {
+		let gain = gain(frame);
+		let input = &scratch[frame * stream_channels..(frame + 1) * stream_channels];
+		for (channel, sample) in output[frame * channels..(frame + 1) * channels].iter_mut().enumerate() {
+			// Mono sources are spread across every output channel
+			*sample += input[channel.min(stream_channels - 1)] * gain;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::stream::tests::write_wav;
+
+	#[test]
+	pub fn crossfade() -> Result<()> {
+		let first = write_wav("crossfade-first", 100);
+		let second = write_wav("crossfade-second", 100);
+
+		let mut player = MusicPlayer::new(2, 8000);
+		player.play(AudioStream::open(&first)?);
+		let mut output = vec![0.0; 20];
+		player.mix(&mut output)?;
+		assert_eq!(output[2], 1.0 / 32768.0);
+
+		player.crossfade_to(AudioStream::open(&second)?, Duration::from_millis(5));
+		assert!(player.is_crossfading());
+		let mut output = vec![0.0; 2];
+		player.mix(&mut output)?;
+		// The first frame of the fade is entirely the outgoing track, which is at frame 10
+		assert_eq!(output[0], 10.0 / 32768.0);
+
+		let mut output = vec![0.0; 80];
+		player.mix(&mut output)?;
+		assert!(!player.is_crossfading());
+		assert!(player.is_playing());
+
+		std::fs::remove_file(first).ok();
+		std::fs::remove_file(second).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn finished_track_stops() -> Result<()> {
+		let path = write_wav("finished", 4);
+		let mut player = MusicPlayer::new(1, 8000);
+		player.play(AudioStream::open(&path)?);
+		let mut output = vec![0.0; 8];
+		player.mix(&mut output)?;
+		assert!(!player.is_playing());
+		assert_eq!(output[4..], [0.0; 4]);
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+}
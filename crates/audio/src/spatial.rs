@@ -0,0 +1,364 @@
+use crate::{
+	effects::{Effect, LowPassFilter},
+	stream::SoundBuffer,
+};
+use math::{Real, Vector3};
+use physics::{Ray, Raycast};
+use std::f32::consts::FRAC_PI_4;
+
+/// How a sound gets quieter with distance from the listener
+#[derive(Debug, Clone, PartialEq)]
+pub enum Attenuation {
+	/// Full volume at any distance, such as for a narrator
+	None,
+
+	/// Full volume up to `min_distance`, fading evenly to silence at `max_distance`
+	Linear { min_distance: Real, max_distance: Real },
+
+	/// Halves the volume each time the distance doubles past `reference_distance`, as in open air.
+	/// Higher `rolloff` fades faster, and the volume stops falling past `max_distance`.
+	Logarithmic { reference_distance: Real, rolloff: Real, max_distance: Real },
+
+	/// Volumes at distances, interpolated between points and held past the ends
+	Custom(Vec<(Real, Real)>),
+}
+
+impl Default for Attenuation {
+	fn default() -> Self {
+		Self::Logarithmic {
+			reference_distance: 1.0,
+			rolloff: 1.0,
+			max_distance: 100.0,
+		}
+	}
+}
+
+impl Attenuation {
+	pub fn gain(&self, distance: Real) -> Real {
+		match self {
+			Self::None => 1.0,
+			Self::Linear { min_distance, max_distance } => {
+				let range = (max_distance - min_distance).max(Real::EPSILON);
+				(1.0 - (distance - min_distance) / range).clamp(0.0, 1.0)
+			},
+			Self::Logarithmic {
+				reference_distance,
+				rolloff,
+				max_distance,
+			} => {
+				let distance = distance.clamp(*reference_distance, max_distance.max(*reference_distance));
+				reference_distance / (reference_distance + rolloff * (distance - reference_distance)).max(Real::EPSILON)
+			},
+			Self::Custom(points) => {
+				let Some(after) = points.iter().position(|(point, _)| *point > distance) else {
+					return points.last().map_or(1.0, |(_, gain)| *gain);
+				};
+				if after == 0 {
+					return points[0].1;
+				}
+				let ((start, start_gain), (end, end_gain)) = (points[after - 1], points[after]);
+				start_gain + (end_gain - start_gain) * (distance - start) / (end - start)
+			},
+		}
+	}
+}
+
+/// Where sounds are heard from, usually following the camera or the player
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AudioListener {
+	pub position: Vector3,
+
+	/// The listener's velocity in units per second, such as from the player's rigid body
+	pub velocity: Vector3,
+	pub forward: Vector3,
+	pub up: Vector3,
+}
+
+impl Default for AudioListener {
+	fn default() -> Self {
+		Self {
+			position: Vector3::zero(),
+			velocity: Vector3::zero(),
+			forward: Vector3::new(0.0, 0.0, -1.0),
+			up: Vector3::y_axis(),
+		}
+	}
+}
+
+impl AudioListener {
+	pub fn right(&self) -> Vector3 {
+		self.forward.cross(&self.up).normalize()
+	}
+}
+
+/// A sound playing at a point in the world
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SpatialSound {
+	pub position: Vector3,
+
+	/// The source's velocity in units per second, such as from a passing car's rigid body
+	pub velocity: Vector3,
+	pub attenuation: Attenuation,
+}
+
+/// How a voice should sound this frame, worked out by `SpatialAudio::parameters`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpatialParameters {
+	pub gain: Real,
+
+	/// From -1 for fully left to 1 for fully right
+	pub pan: Real,
+
+	/// The playback speed from the doppler effect, above 1 while the sound approaches
+	pub pitch: Real,
+
+	/// The low-pass cutoff muffling a sound heard through a wall, or `None` with a clear line
+	pub low_pass_cutoff_hz: Option<Real>,
+}
+
+impl Default for SpatialParameters {
+	fn default() -> Self {
+		Self {
+			gain: 1.0,
+			pan: 0.0,
+			pitch: 1.0,
+			low_pass_cutoff_hz: None,
+		}
+	}
+}
+
+/// Settings shared by every spatial sound
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpatialAudio {
+	/// In world units per second, which is 343 for meters
+	pub speed_of_sound: Real,
+
+	/// Scales the doppler effect, where 0 turns it off and higher values exaggerate it
+	pub doppler_factor: Real,
+
+	/// The volume of a sound heard through geometry
+	pub occluded_gain: Real,
+	pub occluded_cutoff_hz: Real,
+}
+
+impl Default for SpatialAudio {
+	fn default() -> Self {
+		Self {
+			speed_of_sound: 343.0,
+			doppler_factor: 1.0,
+			occluded_gain: 0.5,
+			occluded_cutoff_hz: 800.0,
+		}
+	}
+}
+
+impl SpatialAudio {
+	/// Attenuates, pans, and applies doppler, muffling the sound when geometry is in the way.
+	/// The geometry is usually the level's static colliders, and can be left out to skip occlusion.
+	pub fn parameters(&self, listener: &AudioListener, sound: &SpatialSound, geometry: Option<&dyn Raycast>) -> SpatialParameters {
+		let offset = sound.position - listener.position;
+		let distance = offset.magnitude();
+		if distance <= Real::EPSILON {
+			return SpatialParameters {
+				gain: sound.attenuation.gain(0.0),
+				..Default::default()
+			};
+		}
+		let direction = offset * (1.0 / distance);
+
+		// Speeds are measured along the line between them and kept well below the speed of sound,
+		// so the pitch stays finite for objects moving unrealistically fast
+		let limit = self.speed_of_sound * 0.5;
+		let listener_speed = (listener.velocity.dot(&direction) * self.doppler_factor).clamp(-limit, limit);
+		let sound_speed = (sound.velocity.dot(&direction) * self.doppler_factor).clamp(-limit, limit);
+		let pitch = (self.speed_of_sound + listener_speed) / (self.speed_of_sound + sound_speed);
+
+		let is_occluded = geometry.is_some_and(|geometry| geometry.raycast(&Ray::new(listener.position, direction), distance).is_some());
+		let occlusion = if is_occluded { self.occluded_gain } else { 1.0 };
+		SpatialParameters {
+			gain: sound.attenuation.gain(distance) * occlusion,
+			pan: direction.dot(&listener.right()).clamp(-1.0, 1.0),
+			pitch,
+			low_pass_cutoff_hz: is_occluded.then_some(self.occluded_cutoff_hz),
+		}
+	}
+}
+
+/// Plays a sound buffer with spatial parameters, resampled for doppler and mixed down to mono
+pub struct SpatialVoice {
+	sound: SoundBuffer,
+	parameters: SpatialParameters,
+	filter: LowPassFilter,
+	scratch: Vec<f32>,
+	position: f64,
+	is_looping: bool,
+}
+
+impl SpatialVoice {
+	pub fn new(sound: SoundBuffer) -> Self {
+		Self {
+			sound,
+			parameters: SpatialParameters::default(),
+			filter: LowPassFilter::new(0.0),
+			scratch: Vec::new(),
+			position: 0.0,
+			is_looping: false,
+		}
+	}
+
+	pub fn parameters(&self) -> &SpatialParameters {
+		&self.parameters
+	}
+
+	/// Updates the voice, usually once per frame from `SpatialAudio::parameters`
+	pub fn set_parameters(&mut self, parameters: SpatialParameters) {
+		self.parameters = parameters;
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.is_looping
+	}
+
+	pub fn set_looping(&mut self, is_looping: bool) {
+		self.is_looping = is_looping;
+	}
+
+	pub fn is_finished(&self) -> bool {
+		!self.is_looping && self.position >= self.sound.frames() as f64
+	}
+
+	/// Adds the next frames into interleaved samples, such as a mixer bus's input
+	pub fn mix(&mut self, output: &mut [f32], channels: usize, sample_rate: u32) {
+		let channels = channels.max(1);
+		let frames = output.len() / channels;
+		let mut mono = std::mem::take(&mut self.scratch);
+		mono.clear();
+		let step = f64::from(self.parameters.pitch.max(0.0)) * f64::from(self.sound.sample_rate) / f64::from(sample_rate.max(1));
+		for _ in 0..frames {
+			if self.is_finished() {
+				break;
+			}
+			mono.push(self.sample(self.position));
+			self.position += step;
+			if self.is_looping && self.sound.frames() > 0 {
+				self.position %= self.sound.frames() as f64;
+			}
+		}
+		if let Some(cutoff_hz) = self.parameters.low_pass_cutoff_hz {
+			self.filter.set_cutoff_hz(cutoff_hz);
+			self.filter.process(&mut mono, 1, sample_rate);
+		}
+
+		// Constant power panning keeps the loudness even as a sound moves across the stereo field
+		let angle = (self.parameters.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+		let gains = match channels {
+			1 => [self.parameters.gain, 0.0],
+			_ => [angle.cos() * self.parameters.gain, angle.sin() * self.parameters.gain],
+		};
+		for (frame, sample) in output.chunks_exact_mut(channels).zip(mono.iter()) {
+			frame.iter_mut().zip(gains).for_each(|(output, gain)| *output += sample * gain);
+		}
+		self.scratch = mono;
+	}
+
+	/// The mono downmix at a fractional frame, interpolating between the frames around it
+	fn sample(&self, position: f64) -> f32 {
+		let channels = usize::from(self.sound.channels.max(1));
+		let frames = self.sound.frames();
+		let frame = |index: usize| {
+			let index = if self.is_looping { index % frames.max(1) } else { index };
+			self.sound
+				.samples
+				.get(index * channels..(index + 1) * channels)
+				.map_or(0.0, |frame| frame.iter().sum::<f32>() / channels as f32)
+		};
+		let (index, fraction) = (position as usize, position.fract() as f32);
+		frame(index) + (frame(index + 1) - frame(index)) * fraction
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use physics::Plane;
+
+	#[test]
+	pub fn attenuation_curves() {
+		let linear = Attenuation::Linear {
+			min_distance: 2.0,
+			max_distance: 10.0,
+		};
+		assert_eq!([1.0, 2.0, 6.0, 20.0].map(|distance| linear.gain(distance)), [1.0, 1.0, 0.5, 0.0]);
+
+		let logarithmic = Attenuation::default();
+		assert_eq!([0.5, 2.0, 4.0, 1000.0].map(|distance| logarithmic.gain(distance)), [1.0, 0.5, 0.25, 0.01]);
+
+		let custom = Attenuation::Custom(vec![(1.0, 1.0), (3.0, 0.2), (5.0, 0.0)]);
+		assert_eq!([0.0, 2.0, 4.0, 9.0].map(|distance| custom.gain(distance)), [1.0, 0.6, 0.1, 0.0]);
+		assert_eq!(Attenuation::Custom(Vec::new()).gain(5.0), 1.0);
+	}
+
+	#[test]
+	pub fn doppler_and_panning() {
+		let audio = SpatialAudio::default();
+		let listener = AudioListener::default();
+		let mut sound = SpatialSound {
+			position: Vector3::new(10.0, 0.0, 0.0),
+			velocity: Vector3::new(-34.3, 0.0, 0.0),
+			attenuation: Attenuation::None,
+		};
+		let approaching = audio.parameters(&listener, &sound, None);
+		assert!((approaching.pitch - 343.0 / 308.7).abs() < 1e-4);
+		assert_eq!((approaching.pan, approaching.gain, approaching.low_pass_cutoff_hz), (1.0, 1.0, None));
+
+		sound.position = Vector3::new(-10.0, 0.0, 0.0);
+		let receding = audio.parameters(&listener, &sound, None);
+		assert!(receding.pitch < 1.0 && receding.pan == -1.0);
+
+		let still = SpatialAudio { doppler_factor: 0.0, ..audio };
+		assert_eq!(still.parameters(&listener, &sound, None).pitch, 1.0);
+	}
+
+	#[test]
+	pub fn occlusion() {
+		let audio = SpatialAudio::default();
+		let listener = AudioListener::default();
+		let sound = SpatialSound {
+			position: Vector3::new(0.0, 0.0, -10.0),
+			attenuation: Attenuation::None,
+			..Default::default()
+		};
+		let wall = Plane {
+			normal: Vector3::z_axis(),
+			distance: -5.0,
+		};
+		let muffled = audio.parameters(&listener, &sound, Some(&wall));
+		assert_eq!((muffled.gain, muffled.low_pass_cutoff_hz), (audio.occluded_gain, Some(audio.occluded_cutoff_hz)));
+
+		let behind_the_sound = Plane { distance: -15.0, ..wall };
+		assert_eq!(audio.parameters(&listener, &sound, Some(&behind_the_sound)).low_pass_cutoff_hz, None);
+	}
+
+	#[test]
+	pub fn voice_mixing() {
+		let sound = SoundBuffer {
+			channels: 1,
+			sample_rate: 100,
+			samples: vec![1.0; 8].into(),
+		};
+		let mut voice = SpatialVoice::new(sound);
+		voice.set_parameters(SpatialParameters {
+			pan: 1.0,
+			pitch: 2.0,
+			..Default::default()
+		});
+		let mut output = vec![0.0; 16];
+		voice.mix(&mut output, 2, 100);
+		assert!(output[..8].chunks_exact(2).all(|frame| frame[0].abs() < 1e-6 && (frame[1] - 1.0).abs() < 1e-6));
+		assert!(output[8..].iter().all(|sample| *sample == 0.0));
+		assert!(voice.is_finished());
+
+		voice.set_looping(true);
+		assert!(!voice.is_finished());
+	}
+}
@@ -0,0 +1,339 @@
+use claxon::FlacReader;
+use hound::{SampleFormat, WavReader};
+use lewton::inside_ogg::OggStreamReader;
+use std::{
+	fs::File,
+	io::BufReader,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Routing bus '{0}' to '{1}' would create a cycle")]
+	BusCycle(String, String),
+
+	#[error("Failed to decode flac audio")]
+	DecodeFlac(#[source] claxon::Error),
+
+	#[error("Failed to decode ogg vorbis audio")]
+	DecodeOgg(#[source] lewton::VorbisError),
+
+	#[error("Failed to decode wav audio")]
+	DecodeWav(#[source] hound::Error),
+
+	#[error("Failed to open audio file")]
+	OpenAudioFile(#[source] std::io::Error),
+
+	#[error("Unknown audio bus: {0}")]
+	UnknownBus(String),
+
+	#[error("Unsupported audio format: {0}")]
+	UnsupportedAudioFormat(String),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AudioFormat {
+	Flac,
+	Ogg,
+	Wav,
+}
+
+impl AudioFormat {
+	pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+		let extension = path.as_ref().extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_lowercase();
+		match extension.as_str() {
+			"flac" => Ok(Self::Flac),
+			"ogg" | "oga" => Ok(Self::Ogg),
+			"wav" | "wave" => Ok(Self::Wav),
+			_ => Err(Error::UnsupportedAudioFormat(extension)),
+		}
+	}
+}
+
+enum Decoder {
+	Flac(FlacReader<BufReader<File>>),
+	Ogg(Box<OggStreamReader<BufReader<File>>>),
+	Wav(WavReader<BufReader<File>>),
+}
+
+impl Decoder {
+	fn open(path: &Path) -> Result<Self> {
+		let format = AudioFormat::from_path(path)?;
+		let reader = BufReader::new(File::open(path).map_err(Error::OpenAudioFile)?);
+		Ok(match format {
+			AudioFormat::Flac => Self::Flac(FlacReader::new(reader).map_err(Error::DecodeFlac)?),
+			AudioFormat::Ogg => Self::Ogg(Box::new(OggStreamReader::new(reader).map_err(Error::DecodeOgg)?)),
+			AudioFormat::Wav => Self::Wav(WavReader::new(reader).map_err(Error::DecodeWav)?),
+		})
+	}
+
+	fn channels(&self) -> u16 {
+		match self {
+			Self::Flac(reader) => reader.streaminfo().channels as u16,
+			Self::Ogg(reader) => u16::from(reader.ident_hdr.audio_channels),
+			Self::Wav(reader) => reader.spec().channels,
+		}
+	}
+
+	fn sample_rate(&self) -> u32 {
+		match self {
+			Self::Flac(reader) => reader.streaminfo().sample_rate,
+			Self::Ogg(reader) => reader.ident_hdr.audio_sample_rate,
+			Self::Wav(reader) => reader.spec().sample_rate,
+		}
+	}
+
+	/// Decodes the next chunk of interleaved samples, returning `false` at the end of the stream
+	fn decode_next(&mut self, samples: &mut Vec<f32>) -> Result<bool> {
+		match self {
+			Self::Flac(reader) => {
+				let scale = (1_i64 << (reader.streaminfo().bits_per_sample - 1)) as f32;
+				let Some(block) = reader.blocks().read_next_or_eof(Vec::new()).map_err(Error::DecodeFlac)? else {
+					return Ok(false);
+				};
+				for frame in 0..block.duration() {
+					samples.extend((0..block.channels()).map(|channel| block.sample(channel, frame) as f32 / scale));
+				}
+				Ok(true)
+			},
+			Self::Ogg(reader) => {
+				// Vorbis packets may decode to no samples, so keep reading until audio is produced
+				loop {
+					match reader.read_dec_packet_itl().map_err(Error::DecodeOgg)? {
+						Some(packet) if packet.is_empty() => continue,
+						Some(packet) => {
+							samples.extend(packet.into_iter().map(|sample| f32::from(sample) / 32768.0));
+							return Ok(true);
+						},
+						None => return Ok(false),
+					}
+				}
+			},
+			Self::Wav(reader) => {
+				let spec = reader.spec();
+				let chunk = WAV_CHUNK_FRAMES * usize::from(spec.channels);
+				let start = samples.len();
+				match spec.sample_format {
+					SampleFormat::Float => {
+						for sample in reader.samples::<f32>().take(chunk) {
+							samples.push(sample.map_err(Error::DecodeWav)?);
+						}
+					},
+					SampleFormat::Int => {
+						let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+						for sample in reader.samples::<i32>().take(chunk) {
+							samples.push(sample.map_err(Error::DecodeWav)? as f32 / scale);
+						}
+					},
+				}
+				Ok(samples.len() > start)
+			},
+		}
+	}
+}
+
+/// The number of frames decoded at a time from wav files, which have no natural packet size
+const WAV_CHUNK_FRAMES: usize = 4096;
+
+/// Decodes an audio file incrementally as samples are read,
+/// so long music tracks are never held in memory all at once.
+pub struct AudioStream {
+	path: PathBuf,
+	decoder: Decoder,
+	channels: u16,
+	sample_rate: u32,
+	buffer: Vec<f32>,
+	position: usize,
+	is_looping: bool,
+	is_finished: bool,
+	has_audio: bool,
+}
+
+impl AudioStream {
+	pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref().to_path_buf();
+		let decoder = Decoder::open(&path)?;
+		Ok(Self {
+			channels: decoder.channels(),
+			sample_rate: decoder.sample_rate(),
+			path,
+			decoder,
+			buffer: Vec::new(),
+			position: 0,
+			is_looping: false,
+			is_finished: false,
+			has_audio: false,
+		})
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	pub fn channels(&self) -> u16 {
+		self.channels
+	}
+
+	pub fn sample_rate(&self) -> u32 {
+		self.sample_rate
+	}
+
+	pub fn is_looping(&self) -> bool {
+		self.is_looping
+	}
+
+	/// When looping, the stream restarts from the beginning instead of finishing
+	pub fn set_looping(&mut self, is_looping: bool) {
+		self.is_looping = is_looping;
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.is_finished
+	}
+
+	/// Restarts decoding from the beginning of the file
+	pub fn rewind(&mut self) -> Result<()> {
+		self.decoder = Decoder::open(&self.path)?;
+		self.buffer.clear();
+		self.position = 0;
+		self.is_finished = false;
+		Ok(())
+	}
+
+	/// Fills `output` with interleaved samples in the stream's channel layout,
+	/// returning how many were written. Fewer samples than requested means the stream finished.
+	pub fn read(&mut self, output: &mut [f32]) -> Result<usize> {
+		let mut written = 0;
+		while written < output.len() && !self.is_finished {
+			if self.position == self.buffer.len() {
+				self.buffer.clear();
+				self.position = 0;
+				if self.decoder.decode_next(&mut self.buffer)? {
+					self.has_audio = true;
+				} else if self.is_looping && self.has_audio {
+					self.rewind()?;
+				} else {
+					// Rewinding a stream without any audio would loop forever
+					self.is_finished = true;
+				}
+				continue;
+			}
+			let count = (self.buffer.len() - self.position).min(output.len() - written);
+			output[written..written + count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+			self.position += count;
+			written += count;
+		}
+		Ok(written)
+	}
+}
+
+/// A fully decoded sound, suited to short effects that are played often
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundBuffer {
+	pub channels: u16,
+	pub sample_rate: u32,
+	pub samples: Arc<[f32]>,
+}
+
+impl SoundBuffer {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let mut stream = AudioStream::open(path)?;
+		let mut samples = Vec::new();
+		while stream.decoder.decode_next(&mut samples)? {}
+		Ok(Self {
+			channels: stream.channels,
+			sample_rate: stream.sample_rate,
+			samples: samples.into(),
+		})
+	}
+
+	pub fn frames(&self) -> usize {
+		self.samples.len() / usize::from(self.channels.max(1))
+	}
+
+	pub fn duration_secs(&self) -> f32 {
+		self.frames() as f32 / self.sample_rate as f32
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use hound::{WavSpec, WavWriter};
+
+	/// Writes a stereo 16-bit wav whose left channel counts up and right channel counts down
+	pub(crate) fn write_wav(name: &str, frames: usize) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("elder-audio-{}-{}.wav", name, std::process::id()));
+		let spec = WavSpec {
+			channels: 2,
+			sample_rate: 8000,
+			bits_per_sample: 16,
+			sample_format: SampleFormat::Int,
+		};
+		let mut writer = WavWriter::create(&path, spec).unwrap();
+		for frame in 0..frames as i16 {
+			writer.write_sample(frame).unwrap();
+			writer.write_sample(-frame).unwrap();
+		}
+		writer.finalize().unwrap();
+		path
+	}
+
+	#[test]
+	pub fn format_from_path() -> Result<()> {
+		assert_eq!(AudioFormat::from_path("music/theme.OGG")?, AudioFormat::Ogg);
+		assert_eq!(AudioFormat::from_path("sfx/jump.wav")?, AudioFormat::Wav);
+		assert_eq!(AudioFormat::from_path("music/ambience.flac")?, AudioFormat::Flac);
+		assert!(AudioFormat::from_path("music/theme.mp3").is_err());
+		Ok(())
+	}
+
+	#[test]
+	pub fn stream_in_chunks() -> Result<()> {
+		let frames = WAV_CHUNK_FRAMES * 2 + 10;
+		let path = write_wav("stream", frames);
+		let mut stream = AudioStream::open(&path)?;
+		assert_eq!((stream.channels(), stream.sample_rate()), (2, 8000));
+
+		let mut output = vec![0.0; 6];
+		assert_eq!(stream.read(&mut output)?, 6);
+		assert_eq!(output, [0.0, 0.0, 1.0 / 32768.0, -1.0 / 32768.0, 2.0 / 32768.0, -2.0 / 32768.0]);
+
+		let mut total = 6;
+		let mut output = vec![0.0; 1000];
+		while !stream.is_finished() {
+			total += stream.read(&mut output)?;
+		}
+		assert_eq!(total, frames * 2);
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn looping() -> Result<()> {
+		let path = write_wav("looping", 4);
+		let mut stream = AudioStream::open(&path)?;
+		stream.set_looping(true);
+		let mut output = vec![0.0; 20];
+		assert_eq!(stream.read(&mut output)?, 20);
+		assert!(!stream.is_finished());
+		assert_eq!(output[8], 0.0);
+		assert_eq!(output[10], 1.0 / 32768.0);
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn sound_buffer() -> Result<()> {
+		let path = write_wav("buffer", 8000);
+		let sound = SoundBuffer::load(&path)?;
+		assert_eq!(sound.frames(), 8000);
+		assert_eq!(sound.duration_secs(), 1.0);
+		std::fs::remove_file(path).ok();
+		Ok(())
+	}
+}
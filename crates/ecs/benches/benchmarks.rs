@@ -1,5 +1,5 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use ecs::{izip, system, world::World};
+use criterion::{Criterion, criterion_group, criterion_main};
+use ecs::{query::Changed, system, world::World};
 use std::time::Duration;
 
 fn insertion(c: &mut Criterion) {
@@ -128,6 +128,45 @@ fn complex_entity_system(c: &mut Criterion) {
 	});
 }
 
+fn component_iteration(c: &mut Criterion) {
+	c.bench_function("iterating 1 million components with a system", |b| {
+		let mut world = World::new();
+		let entities = world.create_entities(1_000_000);
+		for entity in entities.iter() {
+			world.add_component(*entity, Position::default()).unwrap();
+		}
+		b.iter(|| {
+			movement_system(&mut world).expect("Failed to execute movement system!");
+		})
+	});
+}
+
+system!(movement_system, [_resources, _entity], (), (position: Position) -> anyhow::Result<()> {
+	position.0 += 1.0;
+	Ok(())
+});
+
+fn changed_component_iteration(c: &mut Criterion) {
+	c.bench_function("finding 1 thousand changed components among 1 million", |b| {
+		let mut world = World::new();
+		let entities = world.create_entities(1_000_000);
+		for entity in entities.iter() {
+			world.add_component(*entity, Position::default()).unwrap();
+		}
+		b.iter(|| {
+			for entity in entities.iter().step_by(1_000) {
+				world.get_component_mut::<Position>(*entity).unwrap().1 = 1.0;
+			}
+			changed_position_system(&mut world).expect("Failed to execute changed position system!");
+		})
+	});
+}
+
+system!(changed_position_system, [_resources, _entity], (), (position: Position), [Changed<Position>] -> anyhow::Result<()> {
+	position.0 = position.1;
+	Ok(())
+});
+
 criterion_group!(
 	name = benches;
 	config = Criterion::default().measurement_time(Duration::from_secs(20));
@@ -139,7 +178,9 @@ criterion_group!(
 		component_removal,
 		component_mutation,
 		complex_entities,
-		complex_entity_system
+		complex_entity_system,
+		component_iteration,
+		changed_component_iteration
 );
 
 criterion_main!(benches);
@@ -0,0 +1,163 @@
+use crate::{
+	error::Result,
+	world::{Entity, World},
+};
+
+type Command = Box<dyn FnOnce(&mut World, &mut Vec<Entity>) -> Result<()>>;
+
+/// An entity targeted by a queued command.
+/// Entities spawned by the same buffer don't exist until it is applied, so they are numbered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CommandEntity {
+	Existing(Entity),
+	Spawned(usize),
+}
+
+impl From<Entity> for CommandEntity {
+	fn from(entity: Entity) -> Self {
+		Self::Existing(entity)
+	}
+}
+
+/// Structural changes queued while the world is borrowed, and applied later at a sync point.
+///
+/// Systems reach the buffer through the resources, and `World::apply_commands` runs it.
+#[derive(Default)]
+pub struct Commands {
+	commands: Vec<Command>,
+	spawned: usize,
+}
+
+impl Commands {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn len(&self) -> usize {
+		self.commands.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.commands.is_empty()
+	}
+
+	/// Queues the creation of an entity, returning a handle other commands in the buffer can use
+	pub fn spawn(&mut self) -> CommandEntity {
+		let index = self.spawned;
+		self.spawned += 1;
+		self.push(move |world, spawned| {
+			spawned.push(world.create_entity());
+			debug_assert_eq!(spawned.len(), index + 1);
+			Ok(())
+		});
+		CommandEntity::Spawned(index)
+	}
+
+	/// Queues removing an entity and its components. Entities that no longer exist are ignored.
+	pub fn despawn(&mut self, entity: impl Into<CommandEntity>) {
+		let entity = entity.into();
+		self.push(move |world, spawned| {
+			world.remove_entity(resolve(entity, spawned));
+			Ok(())
+		});
+	}
+
+	pub fn add_component<T: 'static>(&mut self, entity: impl Into<CommandEntity>, component: T) {
+		let entity = entity.into();
+		self.push(move |world, spawned| world.add_component(resolve(entity, spawned), component));
+	}
+
+	pub fn remove_component<T: 'static>(&mut self, entity: impl Into<CommandEntity>) {
+		let entity = entity.into();
+		self.push(move |world, spawned| world.remove_component::<T>(resolve(entity, spawned)));
+	}
+
+	/// Queues arbitrary access to the world
+	pub fn push(&mut self, command: impl FnOnce(&mut World, &mut Vec<Entity>) -> Result<()> + 'static) {
+		self.commands.push(Box::new(command));
+	}
+
+	/// Runs the queued commands in order, returning the entities they spawned.
+	/// Stops at the first command that fails, discarding the rest.
+	pub fn apply(&mut self, world: &mut World) -> Result<Vec<Entity>> {
+		let mut spawned = Vec::with_capacity(self.spawned);
+		self.spawned = 0;
+		for command in self.commands.drain(..) {
+			command(world, &mut spawned)?;
+		}
+		Ok(spawned)
+	}
+}
+
+fn resolve(entity: CommandEntity, spawned: &[Entity]) -> Entity {
+	match entity {
+		CommandEntity::Existing(entity) => entity,
+		CommandEntity::Spawned(index) => spawned[index],
+	}
+}
+
+impl World {
+	/// Applies the `Commands` queued in the world's resources, returning the entities they spawned
+	pub fn apply_commands(&mut self) -> Result<Vec<Entity>> {
+		let commands = self.resources().borrow_mut().get_mut::<Commands>().map(std::mem::take);
+		commands.map_or_else(|| Ok(Vec::new()), |mut commands| commands.apply(self))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::system;
+
+	#[derive(Debug, Default, PartialEq, Copy, Clone)]
+	struct Health(u32);
+
+	#[derive(Debug, Default, PartialEq, Copy, Clone)]
+	struct Corpse;
+
+	system!(death_system, [resources, entity], (), (health: Health) -> Result<()> {
+		if health.0 == 0 {
+			let mut resources = resources.borrow_mut();
+			let commands = resources.get_mut::<Commands>().unwrap();
+			commands.despawn(entity);
+			let corpse = commands.spawn();
+			commands.add_component(corpse, Corpse);
+		}
+		Ok(())
+	});
+
+	#[test]
+	fn deferred_commands() -> Result<()> {
+		let mut world = World::new();
+		world.resources().borrow_mut().insert(Commands::new());
+		let entities = world.create_entities(3);
+		for (entity, health) in entities.iter().zip([10, 0, 5]) {
+			world.add_component(*entity, Health(health))?;
+		}
+
+		death_system(&mut world)?;
+		assert!(world.entity_exists(entities[1]));
+
+		let spawned = world.apply_commands()?;
+		assert!(!world.entity_exists(entities[1]));
+		assert_eq!(spawned.len(), 1);
+		assert!(world.has_component::<Corpse>(spawned[0]));
+		assert!(world.resources().borrow().get::<Commands>().is_some_and(Commands::is_empty));
+		Ok(())
+	}
+
+	#[test]
+	fn component_commands() -> Result<()> {
+		let mut world = World::new();
+		let entity = world.create_entity();
+		let mut commands = Commands::new();
+		commands.add_component(entity, Health(1));
+		commands.remove_component::<Health>(entity);
+		commands.add_component(entity, Corpse);
+		assert_eq!(commands.len(), 3);
+		commands.apply(&mut world)?;
+		assert!(!world.has_component::<Health>(entity));
+		assert!(world.has_component::<Corpse>(entity));
+		Ok(())
+	}
+}
@@ -0,0 +1,129 @@
+use crate::{
+	error::Result,
+	world::{Entity, Error, World},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
+use uuid::Uuid;
+
+/// A persistent identifier for an entity.
+///
+/// Unlike an `Entity` handle, it stays the same when a scene is saved and loaded again,
+/// so it can be used for references that outlive the world.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityId(pub Uuid);
+
+impl EntityId {
+	/// A new random identifier
+	pub fn generate() -> Self {
+		Self(Uuid::new_v4())
+	}
+}
+
+// Written by hand since serde's derives allow lints the crate forbids
+impl Serialize for EntityId {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for EntityId {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Uuid::deserialize(deserializer).map(Self)
+	}
+}
+
+impl fmt::Display for EntityId {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.0.fmt(formatter)
+	}
+}
+
+impl FromStr for EntityId {
+	type Err = uuid::Error;
+
+	fn from_str(id: &str) -> Result<Self, Self::Err> {
+		Uuid::parse_str(id).map(Self)
+	}
+}
+
+impl World {
+	/// Creates an entity with a new random id
+	pub fn create_entity_with_id(&mut self) -> Result<(Entity, EntityId)> {
+		let entity = self.create_entity();
+		let id = EntityId::generate();
+		self.set_entity_id(entity, id)?;
+		Ok((entity, id))
+	}
+
+	pub fn entity_id(&self, entity: Entity) -> Option<EntityId> {
+		self.get_component::<EntityId>(entity).map(|id| *id)
+	}
+
+	/// The entity's id, assigning a new one if it has none, such as when saving a scene
+	pub fn ensure_entity_id(&mut self, entity: Entity) -> Result<EntityId> {
+		if let Some(id) = self.entity_id(entity) {
+			return Ok(id);
+		}
+		let id = EntityId::generate();
+		self.set_entity_id(entity, id)?;
+		Ok(id)
+	}
+
+	/// Gives an entity a specific id, such as one read from a saved scene.
+	/// Fails if another living entity already has that id.
+	pub fn set_entity_id(&mut self, entity: Entity, id: EntityId) -> Result<()> {
+		if let Some(existing) = self.find_by_id(id).filter(|existing| *existing != entity) {
+			return Err(Box::new(Error::DuplicateEntityId { id, entity: existing }));
+		}
+		self.register_cloneable_component::<EntityId>();
+		self.add_component(entity, id)?;
+		self.entity_ids.insert(id, entity);
+		Ok(())
+	}
+
+	/// The living entity with the given id.
+	/// Ids given with `set_entity_id` are found through an index, and others by scanning.
+	pub fn find_by_id(&self, id: EntityId) -> Option<Entity> {
+		let indexed = self.entity_ids.get(&id).copied().filter(|entity| self.entity_id(*entity) == Some(id));
+		indexed.or_else(|| self.entities_matching::<EntityId>(|component| *component == id).into_iter().next())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn entity_ids() -> Result<()> {
+		let mut world = World::new();
+		let (entity, id) = world.create_entity_with_id()?;
+		assert_eq!(world.find_by_id(id), Some(entity));
+		assert_eq!(id.to_string().parse::<EntityId>()?, id);
+
+		let other = world.create_entity();
+		assert!(world.set_entity_id(other, id).is_err());
+		let other_id = world.ensure_entity_id(other)?;
+		assert_eq!(world.ensure_entity_id(other)?, other_id);
+		assert_ne!(other_id, id);
+
+		world.remove_entity(entity);
+		assert_eq!(world.find_by_id(id), None);
+		let reused = world.create_entity();
+		assert_eq!(world.find_by_id(id), None);
+		world.set_entity_id(reused, id)?;
+		assert_eq!(world.find_by_id(id), Some(reused));
+		Ok(())
+	}
+
+	#[test]
+	fn ids_survive_snapshots() -> Result<()> {
+		let mut world = World::new();
+		let (entity, id) = world.create_entity_with_id()?;
+		let snapshot = world.snapshot()?;
+		world.remove_entity(entity);
+		world.restore(&snapshot);
+		assert_eq!(world.find_by_id(id), Some(entity));
+		Ok(())
+	}
+}
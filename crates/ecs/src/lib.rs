@@ -1,7 +1,14 @@
 #![forbid(unsafe_code)]
 #![forbid(clippy::all, clippy::nursery, clippy::cargo)]
 
+pub mod commands;
+pub mod id;
+pub mod name;
+pub mod pool;
+pub mod query;
 pub mod resource;
+pub mod scene;
+pub mod storage;
 pub mod vec;
 pub mod world;
 
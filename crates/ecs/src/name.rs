@@ -0,0 +1,144 @@
+use crate::{
+	error::Result,
+	world::{Entity, World},
+};
+use intern::Label;
+use std::{collections::BTreeSet, fmt};
+
+/// A human readable name for an entity, used to find it from the editor, saved scenes, and scripts.
+/// Names are interned labels, so cloning and comparing them while searching doesn't allocate.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Name(pub Label);
+
+impl Name {
+	pub fn new(name: impl Into<Label>) -> Self {
+		Self(name.into())
+	}
+
+	pub fn as_str(&self) -> &str {
+		self.0.as_str()
+	}
+}
+
+impl fmt::Display for Name {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(self.as_str())
+	}
+}
+
+/// Labels that group entities, such as "enemy" or "pickup"
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tags(pub BTreeSet<String>);
+
+impl Tags {
+	pub fn new<T: Into<String>>(tags: impl IntoIterator<Item = T>) -> Self {
+		Self(tags.into_iter().map(Into::into).collect())
+	}
+
+	pub fn contains(&self, tag: &str) -> bool {
+		self.0.contains(tag)
+	}
+
+	pub fn insert(&mut self, tag: impl Into<String>) -> bool {
+		self.0.insert(tag.into())
+	}
+
+	pub fn remove(&mut self, tag: &str) -> bool {
+		self.0.remove(tag)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &str> {
+		self.0.iter().map(String::as_str)
+	}
+}
+
+impl World {
+	/// The lowest indexed living entity with the given name
+	pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+		self.entities_matching::<Name>(|component| component.0 == name).into_iter().next()
+	}
+
+	/// Every living entity with the given name, in index order
+	pub fn find_all_by_name(&self, name: &str) -> Vec<Entity> {
+		self.entities_matching::<Name>(|component| component.0 == name)
+	}
+
+	/// Every living entity with the given tag, in index order
+	pub fn entities_with_tag(&self, tag: &str) -> Vec<Entity> {
+		self.entities_matching::<Tags>(|tags| tags.contains(tag))
+	}
+
+	pub fn has_tag(&self, entity: Entity, tag: &str) -> bool {
+		self.get_component::<Tags>(entity).is_some_and(|tags| tags.contains(tag))
+	}
+
+	/// Tags an entity, adding a `Tags` component if it has none
+	pub fn add_tag(&mut self, entity: Entity, tag: impl Into<String>) -> Result<()> {
+		let mut tags = self.get_component::<Tags>(entity).map(|tags| tags.clone()).unwrap_or_default();
+		tags.insert(tag);
+		self.add_component(entity, tags)
+	}
+
+	pub fn remove_tag(&mut self, entity: Entity, tag: &str) {
+		if let Some(mut tags) = self.get_component_mut::<Tags>(entity) {
+			tags.remove(tag);
+		}
+	}
+
+	/// Runs `action` on the `T` component of every entity with the given tag
+	pub fn for_each_tagged<T: 'static>(&self, tag: &str, mut action: impl FnMut(Entity, &mut T) -> Result<()>) -> Result<()> {
+		for entity in self.entities_with_tag(tag) {
+			if let Some(mut component) = self.get_component_mut::<T>(entity) {
+				action(entity, &mut component)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Default, PartialEq, Clone, Copy)]
+	struct Health(u32);
+
+	#[test]
+	fn find_by_name() -> Result<()> {
+		let mut world = World::new();
+		let entities = world.create_entities(3);
+		world.add_component(entities[0], Name::new("Camera"))?;
+		world.add_component(entities[1], Name::new("Player"))?;
+		world.add_component(entities[2], Name::new("Player"))?;
+		assert_eq!(world.find_by_name("Player"), Some(entities[1]));
+		assert_eq!(world.find_all_by_name("Player"), &entities[1..]);
+
+		world.remove_entity(entities[1]);
+		assert_eq!(world.find_by_name("Player"), Some(entities[2]));
+		assert_eq!(world.find_by_name("Light"), None);
+		Ok(())
+	}
+
+	#[test]
+	fn tags() -> Result<()> {
+		let mut world = World::new();
+		let entities = world.create_entities(3);
+		for entity in entities.iter() {
+			world.add_component(*entity, Health(10))?;
+		}
+		world.add_tag(entities[0], "enemy")?;
+		world.add_tag(entities[2], "enemy")?;
+		world.add_tag(entities[2], "boss")?;
+		assert_eq!(world.entities_with_tag("enemy"), [entities[0], entities[2]]);
+		assert!(world.has_tag(entities[2], "boss"));
+
+		world.remove_tag(entities[0], "enemy");
+		world.for_each_tagged::<Health>("enemy", |_entity, health| {
+			health.0 = 0;
+			Ok(())
+		})?;
+		let health = entities.iter().map(|entity| world.get_component::<Health>(*entity).unwrap().0).collect::<Vec<_>>();
+		assert_eq!(health, [10, 10, 0]);
+		Ok(())
+	}
+}
@@ -0,0 +1,177 @@
+use crate::{
+	error::Result,
+	world::{Entity, Error, World},
+};
+
+/// Marks a pooled entity that has been released and is waiting to be acquired again.
+/// Systems skip released entities with the `[Without<Pooled>]` filter.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pooled;
+
+type Reset = Box<dyn FnMut(&mut World, Entity) -> Result<()>>;
+
+/// Entities spawned up front and reused, for things spawned constantly such as projectiles.
+///
+/// Acquiring an entity resets its components to their starting values, and releasing it
+/// marks it `Pooled` instead of removing it, so nothing is allocated while entities are free.
+pub struct EntityPool {
+	entities: Vec<Entity>,
+	free: Vec<Entity>,
+	reset: Reset,
+}
+
+impl EntityPool {
+	/// Spawns `count` released entities, with `reset` adding the components each one starts with
+	pub fn new(world: &mut World, count: usize, reset: impl FnMut(&mut World, Entity) -> Result<()> + 'static) -> Result<Self> {
+		let mut pool = Self {
+			entities: Vec::with_capacity(count),
+			free: Vec::with_capacity(count),
+			reset: Box::new(reset),
+		};
+		for _ in 0..count {
+			pool.spawn(world)?;
+		}
+		Ok(pool)
+	}
+
+	/// A released entity with its components reset, or `None` when every entity is in use
+	pub fn acquire(&mut self, world: &mut World) -> Result<Option<Entity>> {
+		let Some(entity) = self.free.pop() else {
+			return Ok(None);
+		};
+		(self.reset)(world, entity)?;
+		world.remove_component::<Pooled>(entity)?;
+		Ok(Some(entity))
+	}
+
+	/// Acquires an entity, spawning another one when every entity is in use
+	pub fn acquire_or_grow(&mut self, world: &mut World) -> Result<Entity> {
+		if self.free.is_empty() {
+			self.spawn(world)?;
+		}
+		Ok(self.acquire(world)?.expect("An entity was just spawned"))
+	}
+
+	/// Returns an entity to the pool. Releasing an entity that's already released does nothing.
+	pub fn release(&mut self, world: &mut World, entity: Entity) -> Result<()> {
+		if !self.entities.contains(&entity) {
+			return Err(Box::new(Error::EntityNotPooled { entity }));
+		}
+		if !self.free.contains(&entity) {
+			world.add_component(entity, Pooled)?;
+			self.free.push(entity);
+		}
+		Ok(())
+	}
+
+	/// Releases every entity in use
+	pub fn release_all(&mut self, world: &mut World) -> Result<()> {
+		for entity in self.entities.clone() {
+			self.release(world, entity)?;
+		}
+		Ok(())
+	}
+
+	pub fn is_active(&self, entity: Entity) -> bool {
+		self.entities.contains(&entity) && !self.free.contains(&entity)
+	}
+
+	/// The entities in use, in the order they were spawned
+	pub fn active(&self) -> impl Iterator<Item = Entity> + '_ {
+		self.entities.iter().copied().filter(|entity| !self.free.contains(entity))
+	}
+
+	/// Every entity in the pool, whether in use or not
+	pub fn entities(&self) -> &[Entity] {
+		&self.entities
+	}
+
+	pub const fn capacity(&self) -> usize {
+		self.entities.len()
+	}
+
+	/// How many entities can be acquired without spawning more
+	pub const fn available(&self) -> usize {
+		self.free.len()
+	}
+
+	/// Removes the pool's entities from the world
+	pub fn despawn(self, world: &mut World) {
+		world.remove_entities(&self.entities);
+	}
+
+	fn spawn(&mut self, world: &mut World) -> Result<()> {
+		let entity = world.create_entity();
+		(self.reset)(world, entity)?;
+		world.add_component(entity, Pooled)?;
+		self.entities.push(entity);
+		self.free.insert(0, entity);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::query::Query;
+
+	#[derive(Debug, Default, Clone, Copy, PartialEq)]
+	struct Lifetime(f32);
+
+	#[derive(Debug, Clone, Copy, PartialEq)]
+	struct Velocity(f32);
+
+	fn pool(world: &mut World, count: usize) -> Result<EntityPool> {
+		EntityPool::new(world, count, |world, entity| {
+			world.add_component(entity, Lifetime(2.0))?;
+			world.add_component(entity, Velocity(0.0))
+		})
+	}
+
+	#[test]
+	fn acquire_and_release() -> Result<()> {
+		let mut world = World::new();
+		let mut pool = pool(&mut world, 2)?;
+		assert_eq!((pool.capacity(), pool.available()), (2, 2));
+		assert!(Query::new().without::<Pooled>().with::<Lifetime>().entities(&world).is_empty());
+
+		let first = pool.acquire(&mut world)?.unwrap();
+		assert_eq!(first, pool.entities()[0]);
+		world.get_component_mut::<Lifetime>(first).unwrap().0 = 0.5;
+		world.get_component_mut::<Velocity>(first).unwrap().0 = 30.0;
+		let second = pool.acquire(&mut world)?.unwrap();
+		assert_eq!(pool.acquire(&mut world)?, None);
+		assert_eq!(pool.active().collect::<Vec<_>>(), [first, second]);
+		assert_eq!(Query::new().without::<Pooled>().with::<Lifetime>().entities(&world), [first, second]);
+
+		// Releasing keeps the entity around, and acquiring it again resets its components
+		pool.release(&mut world, first)?;
+		pool.release(&mut world, first)?;
+		assert!(world.entity_exists(first) && world.has_component::<Pooled>(first));
+		assert_eq!((pool.available(), pool.is_active(first)), (1, false));
+		assert_eq!(pool.acquire(&mut world)?, Some(first));
+		assert_eq!(*world.get_component::<Lifetime>(first).unwrap(), Lifetime(2.0));
+		assert_eq!(*world.get_component::<Velocity>(first).unwrap(), Velocity(0.0));
+		assert!(!world.has_component::<Pooled>(first));
+		Ok(())
+	}
+
+	#[test]
+	fn growing() -> Result<()> {
+		let mut world = World::new();
+		let mut pool = pool(&mut world, 1)?;
+		pool.acquire_or_grow(&mut world)?;
+		let grown = pool.acquire_or_grow(&mut world)?;
+		assert_eq!((pool.capacity(), pool.available()), (2, 0));
+
+		pool.release_all(&mut world)?;
+		assert_eq!(pool.available(), 2);
+		let outsider = world.create_entity();
+		assert!(pool.release(&mut world, outsider).is_err());
+
+		pool.despawn(&mut world);
+		assert!(!world.entity_exists(grown));
+		assert_eq!(world.entities(), [outsider]);
+		Ok(())
+	}
+}
@@ -0,0 +1,249 @@
+use crate::{
+	storage::Ticks,
+	world::{Entity, World},
+};
+use std::{
+	any::TypeId,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+};
+
+/// A mutable component borrowed by a system.
+/// Writing through it marks the component as changed, while only reading it does not.
+pub struct Mut<'a, T> {
+	value: &'a mut T,
+	ticks: &'a mut Ticks,
+	tick: u64,
+}
+
+impl<'a, T> Mut<'a, T> {
+	/// Wraps a component and its ticks, stamping it with `tick` if it is written to
+	pub const fn new((value, ticks): (&'a mut T, &'a mut Ticks), tick: u64) -> Self {
+		Self { value, ticks, tick }
+	}
+
+	pub const fn ticks(&self) -> Ticks {
+		*self.ticks
+	}
+}
+
+impl<T> Deref for Mut<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		self.value
+	}
+}
+
+impl<T> DerefMut for Mut<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.ticks.changed = self.tick;
+		self.value
+	}
+}
+
+/// Narrows a query, used by the filter list of the `system!` macro
+pub trait QueryFilter {
+	fn apply(query: Query) -> Query;
+}
+
+/// Matches entities that have a `T` component
+pub struct With<T>(PhantomData<T>);
+
+/// Matches entities that don't have a `T` component
+pub struct Without<T>(PhantomData<T>);
+
+/// Matches entities whose `T` component was added since the query's tick
+pub struct Added<T>(PhantomData<T>);
+
+/// Matches entities whose `T` component was added or written to since the query's tick
+pub struct Changed<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for With<T> {
+	fn apply(query: Query) -> Query {
+		query.with::<T>()
+	}
+}
+
+impl<T: 'static> QueryFilter for Without<T> {
+	fn apply(query: Query) -> Query {
+		query.without::<T>()
+	}
+}
+
+impl<T: 'static> QueryFilter for Added<T> {
+	fn apply(query: Query) -> Query {
+		query.added::<T>()
+	}
+}
+
+impl<T: 'static> QueryFilter for Changed<T> {
+	fn apply(query: Query) -> Query {
+		query.changed::<T>()
+	}
+}
+
+/// Finds entities by the components they have and how recently those components changed
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+	with: Vec<TypeId>,
+	without: Vec<TypeId>,
+	added: Vec<TypeId>,
+	changed: Vec<TypeId>,
+	since: Option<u64>,
+}
+
+impl Query {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	#[must_use]
+	pub fn with<T: 'static>(mut self) -> Self {
+		self.with.push(TypeId::of::<T>());
+		self
+	}
+
+	#[must_use]
+	pub fn without<T: 'static>(mut self) -> Self {
+		self.without.push(TypeId::of::<T>());
+		self
+	}
+
+	#[must_use]
+	pub fn added<T: 'static>(mut self) -> Self {
+		self.added.push(TypeId::of::<T>());
+		self
+	}
+
+	#[must_use]
+	pub fn changed<T: 'static>(mut self) -> Self {
+		self.changed.push(TypeId::of::<T>());
+		self
+	}
+
+	#[must_use]
+	pub fn filter<F: QueryFilter>(self) -> Self {
+		F::apply(self)
+	}
+
+	/// Only counts additions and changes made after `tick`.
+	/// Without a tick, every existing component counts as added and changed.
+	#[must_use]
+	pub const fn since(mut self, tick: Option<u64>) -> Self {
+		self.since = tick;
+		self
+	}
+
+	pub fn matches(&self, world: &World, entity: Entity) -> bool {
+		let is_newer = |tick: u64| self.since.is_none_or(|since| tick > since);
+		let ticks = |type_id: &TypeId| world.component_ticks_by_id(*type_id, entity);
+		self.with.iter().all(|type_id| ticks(type_id).is_some())
+			&& self.without.iter().all(|type_id| ticks(type_id).is_none())
+			&& self.added.iter().all(|type_id| ticks(type_id).is_some_and(|ticks| is_newer(ticks.added)))
+			&& self.changed.iter().all(|type_id| ticks(type_id).is_some_and(|ticks| is_newer(ticks.changed)))
+	}
+
+	/// Every living entity matching the query, in index order.
+	/// Only entities with the query's smallest required component are checked.
+	pub fn entities(&self, world: &World) -> Vec<Entity> {
+		let required = self.with.iter().chain(self.added.iter()).chain(self.changed.iter());
+		let candidates = required.map(|type_id| world.component_entities_by_id(*type_id)).collect::<Option<Vec<_>>>();
+		let mut entities = match candidates {
+			Some(candidates) if !candidates.is_empty() => {
+				let smallest = candidates.iter().min_by_key(|entities| entities.len()).expect("Candidates are not empty");
+				smallest.iter().copied().filter(|entity| self.matches(world, *entity)).collect::<Vec<_>>()
+			},
+			Some(_) => world.entities().into_iter().filter(|entity| self.matches(world, *entity)).collect(),
+			None => return Vec::new(),
+		};
+		entities.sort_by_key(|entity| *entity.index());
+		entities
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{error::Result, system};
+
+	#[derive(Debug, Default, PartialEq, Copy, Clone)]
+	struct Position(f32);
+
+	#[derive(Debug, Default, PartialEq, Copy, Clone)]
+	struct Velocity(f32);
+
+	#[derive(Debug, Default, PartialEq, Copy, Clone)]
+	struct Frozen;
+
+	#[derive(Debug, Default, PartialEq, Clone)]
+	struct Synced(Vec<Entity>);
+
+	system!(movement_system, [_resources, _entity], (), (position: Position, velocity: Velocity) -> Result<()> {
+		if velocity.0 != 0.0 {
+			position.0 += velocity.0;
+		}
+		Ok(())
+	});
+
+	system!(sync_system, [resources, entity], (), (_position: Position), [Changed<Position>, Without<Frozen>], optional(velocity: Velocity) -> Result<()> {
+		if let Some(synced) = resources.borrow_mut().get_mut::<Synced>() {
+			synced.0.push(entity);
+		}
+		if let Some(velocity) = velocity.as_mut() {
+			velocity.0 *= 2.0;
+		}
+		Ok(())
+	});
+
+	fn synced(world: &mut World) -> Result<Vec<Entity>> {
+		world.resources().borrow_mut().insert(Synced::default());
+		sync_system(world)?;
+		let synced = world.resources().borrow().get::<Synced>().cloned().unwrap_or_default();
+		Ok(synced.0)
+	}
+
+	#[test]
+	fn queries() -> Result<()> {
+		let mut world = World::new();
+		let entities = world.create_entities(3);
+		world.add_component(entities[0], Position(0.0))?;
+		world.add_component(entities[1], Position(0.0))?;
+		world.add_component(entities[1], Frozen)?;
+		world.add_component(entities[2], Velocity(1.0))?;
+
+		assert_eq!(Query::new().with::<Position>().entities(&world), &entities[..2]);
+		assert_eq!(Query::new().with::<Position>().without::<Frozen>().entities(&world), [entities[0]]);
+		assert_eq!(Query::new().filter::<With<Velocity>>().entities(&world), [entities[2]]);
+
+		let tick = world.change_tick();
+		world.begin_system_run("test");
+		assert!(Query::new().added::<Position>().since(Some(tick)).entities(&world).is_empty());
+		world.get_component_mut::<Velocity>(entities[2]).unwrap().0 = 2.0;
+		assert_eq!(Query::new().changed::<Velocity>().since(Some(tick)).entities(&world), [entities[2]]);
+		Ok(())
+	}
+
+	#[test]
+	fn change_detection() -> Result<()> {
+		let mut world = World::new();
+		let entities = world.create_entities(3);
+		for (entity, velocity) in entities.iter().zip([0.0, 1.0, 1.0]) {
+			world.add_component(*entity, Position(0.0))?;
+			world.add_component(*entity, Velocity(velocity))?;
+		}
+		world.add_component(entities[2], Frozen)?;
+
+		assert_eq!(synced(&mut world)?, &entities[..2]);
+		assert_eq!(world.get_component::<Velocity>(entities[1]).as_deref(), Some(&Velocity(2.0)));
+		assert!(synced(&mut world)?.is_empty());
+
+		movement_system(&mut world)?;
+		assert_eq!(synced(&mut world)?, [entities[1]]);
+		assert!(world.component_ticks::<Position>(entities[0]).is_some_and(|ticks| ticks.changed == 0));
+
+		world.get_component_mut::<Position>(entities[0]).unwrap().0 = 5.0;
+		assert_eq!(synced(&mut world)?, [entities[0]]);
+		Ok(())
+	}
+}
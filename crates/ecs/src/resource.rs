@@ -1,11 +1,31 @@
 use std::{
-	any::{Any, TypeId},
+	any::{Any, TypeId, type_name},
+	cell::{Cell, Ref, RefCell, RefMut},
 	collections::HashMap,
+	fmt,
+	ops::{Deref, DerefMut},
+	panic::Location,
 };
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to borrow {requested}: {held} is still active.")]
+	ConflictingBorrow { requested: Borrow, held: Borrow },
+
+	#[error("Failed to fetch resources: '{}' was requested more than once.", name)]
+	DuplicateResource { name: &'static str },
+
+	#[error("Failed to fetch resource '{}' at {}: it has not been inserted.", name, location)]
+	MissingResource { name: &'static str, location: &'static Location<'static> },
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Default)]
 pub struct ResourceMap {
 	data: HashMap<TypeId, Box<dyn Any + 'static>>,
+	names: HashMap<TypeId, &'static str>,
 }
 
 impl ResourceMap {
@@ -29,17 +49,292 @@ impl ResourceMap {
 	/// This will override any previous value stored.
 	pub fn insert<T: 'static>(&mut self, value: T) {
 		self.data.insert(TypeId::of::<T>(), Box::new(value) as _);
+		self.names.insert(TypeId::of::<T>(), type_name::<T>());
 	}
 
 	/// Remove the value for the type `T` if it existed.
 	pub fn remove<T: 'static>(&mut self) {
 		self.data.remove(&TypeId::of::<T>());
+		self.names.remove(&TypeId::of::<T>());
+	}
+
+	/// The type names of every resource in the map, sorted, such as for an inspector
+	pub fn type_names(&self) -> Vec<&'static str> {
+		let mut names = self.names.values().copied().collect::<Vec<_>>();
+		names.sort_unstable();
+		names
+	}
+}
+
+impl ResourceMap {
+	/// Mutably borrows several distinct resources at once
+	#[track_caller]
+	pub fn get_disjoint_mut<const N: usize>(&mut self, types: [(TypeId, &'static str); N]) -> Result<[&mut Box<dyn Any>; N]> {
+		for (index, (type_id, name)) in types.iter().enumerate() {
+			if types[..index].iter().any(|(other, _)| other == type_id) {
+				return Err(Error::DuplicateResource { name });
+			}
+		}
+		let keys = types.each_ref().map(|(type_id, _)| type_id);
+		let values = self.data.get_disjoint_mut(keys);
+		if let Some(missing) = values.iter().position(Option::is_none) {
+			return Err(Error::MissingResource {
+				name: types[missing].1,
+				location: Location::caller(),
+			});
+		}
+		Ok(values.map(|value| value.expect("Missing resources were checked")))
+	}
+}
+
+/// A record of an active borrow of a world's resources, used to explain conflicting borrows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Borrow {
+	/// The resources borrowed, or "resources" for the whole map
+	pub resource: &'static str,
+	pub is_mutable: bool,
+	pub location: &'static Location<'static>,
+
+	/// The system that was running when the borrow was taken
+	pub system: Option<&'static str>,
+}
+
+impl fmt::Display for Borrow {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let kind = if self.is_mutable { "mutable" } else { "shared" };
+		write!(formatter, "the {} borrow of {} at {}", kind, self.resource, self.location)?;
+		if let Some(system) = self.system {
+			write!(formatter, " in system '{}'", system)?;
+		}
+		Ok(())
+	}
+}
+
+/// A world's resources, shared with systems while they run.
+///
+/// Borrows are tracked, so a conflicting borrow reports which resource was requested and
+/// which system already holds it, instead of a bare `RefCell` panic.
+#[derive(Default)]
+pub struct Resources {
+	map: RefCell<ResourceMap>,
+	borrows: RefCell<Vec<(usize, Borrow)>>,
+	next_borrow: Cell<usize>,
+	system: Cell<Option<&'static str>>,
+}
+
+impl Resources {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Records the system that is running, so borrows taken while it runs are attributed to it
+	pub fn set_running_system(&self, system: Option<&'static str>) {
+		self.system.set(system);
+	}
+
+	pub const fn running_system(&self) -> Option<&'static str> {
+		self.system.get()
+	}
+
+	/// Borrows the whole map, panicking with both borrow sites if it is already borrowed mutably
+	#[track_caller]
+	pub fn borrow(&self) -> ResourcesRef<'_> {
+		self.try_borrow().unwrap_or_else(|error| panic!("{}", error))
+	}
+
+	/// Mutably borrows the whole map, panicking with both borrow sites if it is already borrowed
+	#[track_caller]
+	pub fn borrow_mut(&self) -> ResourcesMut<'_> {
+		self.try_borrow_mut().unwrap_or_else(|error| panic!("{}", error))
+	}
+
+	#[track_caller]
+	pub fn try_borrow(&self) -> Result<ResourcesRef<'_>> {
+		let record = self.track("resources", false)?;
+		Ok(ResourcesRef {
+			map: self.map.borrow(),
+			_record: record,
+		})
+	}
+
+	#[track_caller]
+	pub fn try_borrow_mut(&self) -> Result<ResourcesMut<'_>> {
+		let record = self.track("resources", true)?;
+		Ok(ResourcesMut {
+			map: self.map.borrow_mut(),
+			_record: record,
+		})
+	}
+
+	/// Borrows a single resource
+	#[track_caller]
+	pub fn fetch<T: 'static>(&self) -> Result<Res<'_, T>> {
+		let location = Location::caller();
+		let record = self.track(type_name::<T>(), false)?;
+		let value = Ref::filter_map(self.map.borrow(), |map| map.get::<T>()).map_err(|_| missing::<T>(location))?;
+		Ok(Res { value, _record: record })
+	}
+
+	/// Mutably borrows a single resource
+	#[track_caller]
+	pub fn fetch_mut<T: 'static>(&self) -> Result<ResMut<'_, T>> {
+		let location = Location::caller();
+		let record = self.track(type_name::<T>(), true)?;
+		let value = RefMut::filter_map(self.map.borrow_mut(), |map| map.get_mut::<T>()).map_err(|_| missing::<T>(location))?;
+		Ok(ResMut { value, _record: record })
+	}
+
+	/// Mutably borrows several distinct resources for the duration of `scope`.
+	/// Every resource is fetched up front, so nothing inside the scope can conflict with them.
+	#[track_caller]
+	pub fn scope<S: ResourceSet, R>(&self, scope: impl for<'a> FnOnce(S::Item<'a>) -> R) -> Result<R> {
+		let _record = self.track(S::names(), true)?;
+		let mut map = self.map.borrow_mut();
+		let resources = S::fetch(&mut map)?;
+		Ok(scope(resources))
+	}
+
+	#[track_caller]
+	fn track(&self, resource: &'static str, is_mutable: bool) -> Result<BorrowRecord<'_>> {
+		let requested = Borrow {
+			resource,
+			is_mutable,
+			location: Location::caller(),
+			system: self.system.get(),
+		};
+		let mut borrows = self.borrows.borrow_mut();
+		if let Some((_, held)) = borrows.iter().find(|(_, held)| is_mutable || held.is_mutable) {
+			return Err(Error::ConflictingBorrow { requested, held: *held });
+		}
+		let id = self.next_borrow.get();
+		self.next_borrow.set(id + 1);
+		borrows.push((id, requested));
+		Ok(BorrowRecord { resources: self, id })
+	}
+}
+
+fn missing<T>(location: &'static Location<'static>) -> Error {
+	Error::MissingResource {
+		name: type_name::<T>(),
+		location,
+	}
+}
+
+/// Removes a borrow from the active borrows when its guard is dropped
+struct BorrowRecord<'a> {
+	resources: &'a Resources,
+	id: usize,
+}
+
+impl Drop for BorrowRecord<'_> {
+	fn drop(&mut self) {
+		self.resources.borrows.borrow_mut().retain(|(id, _)| *id != self.id);
+	}
+}
+
+pub struct ResourcesRef<'a> {
+	map: Ref<'a, ResourceMap>,
+	_record: BorrowRecord<'a>,
+}
+
+impl Deref for ResourcesRef<'_> {
+	type Target = ResourceMap;
+
+	fn deref(&self) -> &Self::Target {
+		&self.map
+	}
+}
+
+pub struct ResourcesMut<'a> {
+	map: RefMut<'a, ResourceMap>,
+	_record: BorrowRecord<'a>,
+}
+
+impl Deref for ResourcesMut<'_> {
+	type Target = ResourceMap;
+
+	fn deref(&self) -> &Self::Target {
+		&self.map
+	}
+}
+
+impl DerefMut for ResourcesMut<'_> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.map
 	}
 }
 
+/// A borrowed resource
+pub struct Res<'a, T> {
+	value: Ref<'a, T>,
+	_record: BorrowRecord<'a>,
+}
+
+impl<T> Deref for Res<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+/// A mutably borrowed resource
+pub struct ResMut<'a, T> {
+	value: RefMut<'a, T>,
+	_record: BorrowRecord<'a>,
+}
+
+impl<T> Deref for ResMut<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<T> DerefMut for ResMut<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.value
+	}
+}
+
+/// A tuple of distinct resource types fetched together by `Resources::scope`
+pub trait ResourceSet {
+	type Item<'a>;
+
+	fn names() -> &'static str;
+
+	#[track_caller]
+	fn fetch(map: &mut ResourceMap) -> Result<Self::Item<'_>>;
+}
+
+macro_rules! resource_set {
+	($($resource:ident),*) => {
+		impl<$($resource: 'static),*> ResourceSet for ($($resource,)*) {
+			type Item<'a> = ($(&'a mut $resource,)*);
+
+			fn names() -> &'static str {
+				type_name::<($($resource,)*)>()
+			}
+
+			#[allow(non_snake_case)]
+			#[track_caller]
+			fn fetch(map: &mut ResourceMap) -> Result<Self::Item<'_>> {
+				let [$($resource),*] = map.get_disjoint_mut([$((TypeId::of::<$resource>(), type_name::<$resource>())),*])?;
+				Ok(($($resource.downcast_mut::<$resource>().expect("Resource map contained the wrong type"),)*))
+			}
+		}
+	};
+}
+
+resource_set!(A);
+resource_set!(A, B);
+resource_set!(A, B, C);
+resource_set!(A, B, C, D);
+
 #[cfg(test)]
 mod tests {
-	use super::ResourceMap;
+	use super::*;
 
 	#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
 	pub struct Viewport {
@@ -53,6 +348,7 @@ mod tests {
 
 		resources.insert(Viewport::default());
 		assert_eq!(resources.get::<Viewport>(), Some(&Viewport::default()));
+		assert_eq!(resources.type_names(), ["ecs::resource::tests::Viewport"]);
 
 		let (width, height) = (1920, 1080);
 		let mut viewport = resources.get_mut::<Viewport>().unwrap();
@@ -62,5 +358,39 @@ mod tests {
 
 		resources.remove::<Viewport>();
 		assert_eq!(resources.get::<Viewport>(), None);
+		assert!(resources.type_names().is_empty());
+	}
+
+	#[test]
+	fn conflicting_borrows() {
+		let resources = Resources::new();
+		resources.borrow_mut().insert(Viewport::default());
+		resources.set_running_system(Some("render_system"));
+
+		let viewport = resources.fetch::<Viewport>().unwrap();
+		let shared = resources.fetch::<Viewport>().unwrap();
+		assert_eq!(viewport.width, shared.width);
+		let error = resources.fetch_mut::<Viewport>().err().unwrap().to_string();
+		assert!(error.contains("mutable borrow of ecs::resource::tests::Viewport"));
+		assert!(error.contains("in system 'render_system'"));
+		assert!(error.contains(file!()));
+
+		drop((viewport, shared));
+		assert!(resources.fetch_mut::<Viewport>().is_ok());
+		assert!(matches!(resources.fetch::<u32>(), Err(Error::MissingResource { .. })));
+	}
+
+	#[test]
+	fn scoped_fetch() -> Result<()> {
+		let resources = Resources::new();
+		resources.borrow_mut().insert(Viewport::default());
+		resources.borrow_mut().insert(1920_u32);
+
+		resources.scope::<(Viewport, u32), _>(|(viewport, width)| viewport.width = *width)?;
+		assert_eq!(resources.fetch::<Viewport>()?.width, 1920);
+
+		assert!(matches!(resources.scope::<(u32, u32), _>(|_| ()), Err(Error::DuplicateResource { .. })));
+		assert!(matches!(resources.scope::<(u32, f32), _>(|_| ()), Err(Error::MissingResource { .. })));
+		Ok(())
 	}
 }
@@ -0,0 +1,291 @@
+use crate::{
+	error::Result,
+	id::EntityId,
+	world::{Entity, Error, World},
+};
+use serde::{
+	Deserialize, Deserializer, Serialize, Serializer,
+	de::{self, DeserializeOwned, IgnoredAny, MapAccess, SeqAccess, Visitor},
+	ser::SerializeStruct,
+};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt,
+};
+
+/// An entity in a serialized scene, with its components as RON text keyed by registered name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SceneEntity {
+	pub id: EntityId,
+	pub components: BTreeMap<String, String>,
+}
+
+/// A serialized group of entities, such as a level or a prefab.
+///
+/// It can be inserted into a world any number of times, each with its own entities and ids.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SceneData {
+	pub entities: Vec<SceneEntity>,
+}
+
+/// Identifies the entities spawned by a single call to `World::insert_scene`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SceneInstance(handle::Handle<Vec<Entity>>);
+
+/// Implemented by components that refer to other entities by id.
+///
+/// When a scene is inserted, the ids are rewritten to point at that instance's entities.
+pub trait MapEntityIds {
+	fn map_entity_ids(&mut self, ids: &HashMap<EntityId, EntityId>);
+}
+
+type ComponentSerializer = fn(&World, Entity) -> Option<ron::Result<String>>;
+type ComponentInserter = fn(&mut World, Entity, &str, &HashMap<EntityId, EntityId>) -> Result<(), ron::Error>;
+
+/// How a registered component type is written to and read from scenes
+pub(crate) struct SceneComponent {
+	serialize: ComponentSerializer,
+	insert: ComponentInserter,
+}
+
+impl World {
+	/// Registers a component type that is saved in scenes under the given name
+	pub fn register_scene_component<T: Serialize + DeserializeOwned + 'static>(&mut self, name: &'static str) {
+		self.register_scene_component_with(
+			name,
+			SceneComponent {
+				serialize: serialize_component::<T>,
+				insert: insert_component::<T>,
+			},
+		);
+	}
+
+	/// Registers a scene component type that refers to other entities in the scene by id
+	pub fn register_mapped_scene_component<T: Serialize + DeserializeOwned + MapEntityIds + 'static>(&mut self, name: &'static str) {
+		self.register_scene_component_with(
+			name,
+			SceneComponent {
+				serialize: serialize_component::<T>,
+				insert: insert_mapped_component::<T>,
+			},
+		);
+	}
+
+	fn register_scene_component_with(&mut self, name: &'static str, component: SceneComponent) {
+		self.scene_components.insert(name, component);
+	}
+
+	/// Serializes entities and their registered scene components.
+	/// Entities without an id are given one.
+	pub fn extract_scene(&mut self, entities: &[Entity]) -> Result<SceneData> {
+		let mut scene = SceneData::default();
+		for entity in entities.iter().copied() {
+			let id = self.ensure_entity_id(entity)?;
+			let mut components = BTreeMap::new();
+			for (name, component) in self.scene_components.iter() {
+				if let Some(value) = (component.serialize)(self, entity) {
+					let value = value.map_err(|source| Error::SerializeSceneComponent { name, source })?;
+					components.insert(name.to_string(), value);
+				}
+			}
+			scene.entities.push(SceneEntity { id, components });
+		}
+		Ok(scene)
+	}
+
+	/// The entity's registered scene components as RON text, keyed by registered name
+	pub fn inspect_scene_components(&self, entity: Entity) -> Result<BTreeMap<&'static str, String>> {
+		let mut components = BTreeMap::new();
+		for (name, component) in self.scene_components.iter() {
+			if let Some(text) = (component.serialize)(self, entity) {
+				let text = text.map_err(|source| Error::SerializeSceneComponent { name, source })?;
+				components.insert(*name, text);
+			}
+		}
+		Ok(components)
+	}
+
+	/// Spawns a copy of a scene's entities alongside the ones already in the world.
+	///
+	/// Each spawned entity gets a new id, and ids that components hold are remapped to match.
+	/// If any component fails to load, the entities spawned so far are removed.
+	pub fn insert_scene(&mut self, scene: &SceneData) -> Result<SceneInstance> {
+		let ids = scene.entities.iter().map(|entity| (entity.id, EntityId::generate())).collect::<HashMap<_, _>>();
+		let mut entities = Vec::with_capacity(scene.entities.len());
+		for scene_entity in scene.entities.iter() {
+			let entity = self.create_entity();
+			entities.push(entity);
+			if let Err(error) = self.insert_scene_entity(entity, scene_entity, &ids) {
+				self.remove_entities(&entities);
+				return Err(error);
+			}
+		}
+		Ok(SceneInstance(self.scene_instances.insert(entities)))
+	}
+
+	fn insert_scene_entity(&mut self, entity: Entity, scene_entity: &SceneEntity, ids: &HashMap<EntityId, EntityId>) -> Result<()> {
+		self.set_entity_id(entity, ids[&scene_entity.id])?;
+		for (name, text) in scene_entity.components.iter() {
+			let Some(insert) = self.scene_components.get(name.as_str()).map(|component| component.insert) else {
+				return Err(Box::new(Error::UnregisteredSceneComponent { name: name.to_string() }));
+			};
+			insert(self, entity, text, ids).map_err(|source| Error::DeserializeSceneComponent { name: name.to_string(), source })?;
+		}
+		Ok(())
+	}
+
+	/// The entities spawned for a scene instance, in the order they appear in the scene
+	pub fn scene_entities(&self, instance: SceneInstance) -> Option<&[Entity]> {
+		self.scene_instances.get(instance.0).map(Vec::as_slice)
+	}
+
+	/// Removes every entity spawned for a scene instance, leaving the rest of the world untouched
+	pub fn despawn_scene(&mut self, instance: SceneInstance) -> Result<()> {
+		let Some(entities) = self.scene_instances.remove(instance.0) else {
+			return Err(Box::new(Error::SceneInstanceNotFound { instance }));
+		};
+		let entities = entities.into_iter().filter(|entity| self.entity_exists(*entity)).collect::<Vec<_>>();
+		self.remove_entities(&entities);
+		Ok(())
+	}
+}
+
+fn serialize_component<T: Serialize + 'static>(world: &World, entity: Entity) -> Option<ron::Result<String>> {
+	let component = world.get_component::<T>(entity)?;
+	Some(ron::to_string(&*component))
+}
+
+fn insert_component<T: DeserializeOwned + 'static>(world: &mut World, entity: Entity, text: &str, _ids: &HashMap<EntityId, EntityId>) -> Result<(), ron::Error> {
+	let component = ron::from_str::<T>(text).map_err(|error| error.code)?;
+	world.add_component(entity, component).expect("Scene entity was just created");
+	Ok(())
+}
+
+fn insert_mapped_component<T: DeserializeOwned + MapEntityIds + 'static>(
+	world: &mut World,
+	entity: Entity,
+	text: &str,
+	ids: &HashMap<EntityId, EntityId>,
+) -> Result<(), ron::Error> {
+	let mut component = ron::from_str::<T>(text).map_err(|error| error.code)?;
+	component.map_entity_ids(ids);
+	world.add_component(entity, component).expect("Scene entity was just created");
+	Ok(())
+}
+
+// The scene types implement serde by hand since its derives allow lints the crate forbids.
+// They have the same shape as derived impls, so a scene is a struct of structs in RON.
+
+impl Serialize for SceneEntity {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut entity = serializer.serialize_struct("SceneEntity", 2)?;
+		entity.serialize_field("id", &self.id)?;
+		entity.serialize_field("components", &self.components)?;
+		entity.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for SceneEntity {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_struct("SceneEntity", &["id", "components"], SceneEntityVisitor)
+	}
+}
+
+struct SceneEntityVisitor;
+
+impl<'de> Visitor<'de> for SceneEntityVisitor {
+	type Value = SceneEntity;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str("a scene entity")
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<SceneEntity, A::Error> {
+		let id = sequence.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+		let components = sequence.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+		Ok(SceneEntity { id, components })
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SceneEntity, A::Error> {
+		let (mut id, mut components) = (None, None);
+		while let Some(FieldName(name)) = map.next_key()? {
+			match name.as_str() {
+				"id" => id = Some(map.next_value()?),
+				"components" => components = Some(map.next_value()?),
+				_ => {
+					map.next_value::<IgnoredAny>()?;
+				},
+			}
+		}
+		Ok(SceneEntity {
+			id: id.ok_or_else(|| de::Error::missing_field("id"))?,
+			components: components.ok_or_else(|| de::Error::missing_field("components"))?,
+		})
+	}
+}
+
+impl Serialize for SceneData {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut scene = serializer.serialize_struct("SceneData", 1)?;
+		scene.serialize_field("entities", &self.entities)?;
+		scene.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for SceneData {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_struct("SceneData", &["entities"], SceneDataVisitor)
+	}
+}
+
+struct SceneDataVisitor;
+
+impl<'de> Visitor<'de> for SceneDataVisitor {
+	type Value = SceneData;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str("scene data")
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut sequence: A) -> Result<SceneData, A::Error> {
+		let entities = sequence.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+		Ok(SceneData { entities })
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SceneData, A::Error> {
+		let mut entities = None;
+		while let Some(FieldName(name)) = map.next_key()? {
+			if name == "entities" {
+				entities = Some(map.next_value()?);
+			} else {
+				map.next_value::<IgnoredAny>()?;
+			}
+		}
+		Ok(SceneData {
+			entities: entities.ok_or_else(|| de::Error::missing_field("entities"))?,
+		})
+	}
+}
+
+/// A struct field's name, read as an identifier as formats like RON expect
+struct FieldName(String);
+
+impl<'de> Deserialize<'de> for FieldName {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_identifier(FieldNameVisitor)
+	}
+}
+
+struct FieldNameVisitor;
+
+impl Visitor<'_> for FieldNameVisitor {
+	type Value = FieldName;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str("a field name")
+	}
+
+	fn visit_str<E: de::Error>(self, name: &str) -> Result<FieldName, E> {
+		Ok(FieldName(name.to_string()))
+	}
+}
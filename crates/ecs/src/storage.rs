@@ -0,0 +1,243 @@
+use crate::{error::Result, vec::Handle};
+use std::any::Any;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Handle '{:?}' is older than the entity whose component is stored at its index.", handle)]
+	Generation { handle: Handle },
+}
+
+/// The ticks a component was added and last changed at, used for change detection
+#[derive(Default, Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Ticks {
+	pub added: u64,
+	pub changed: u64,
+}
+
+/// A sparse set holding every component of one type.
+///
+/// Components, their entities, and their ticks are packed into parallel arrays for iteration,
+/// while a sparse array indexed by entity index finds one entity's component in constant time.
+/// Removing a component moves the last one into its place, so order is not insertion order.
+pub struct ComponentVec<T> {
+	sparse: Vec<Option<usize>>,
+	entities: Vec<Handle>,
+	components: Vec<T>,
+	ticks: Vec<Ticks>,
+}
+
+impl<T> Default for ComponentVec<T> {
+	fn default() -> Self {
+		Self {
+			sparse: Vec::new(),
+			entities: Vec::new(),
+			components: Vec::new(),
+			ticks: Vec::new(),
+		}
+	}
+}
+
+impl<T: Clone> Clone for ComponentVec<T> {
+	fn clone(&self) -> Self {
+		Self {
+			sparse: self.sparse.clone(),
+			entities: self.entities.clone(),
+			components: self.components.clone(),
+			ticks: self.ticks.clone(),
+		}
+	}
+}
+
+impl<T> ComponentVec<T> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub const fn len(&self) -> usize {
+		self.components.len()
+	}
+
+	pub const fn is_empty(&self) -> bool {
+		self.components.is_empty()
+	}
+
+	/// The entities with a component, in the same order as `components`
+	pub fn entities(&self) -> &[Handle] {
+		&self.entities
+	}
+
+	pub fn components(&self) -> &[T] {
+		&self.components
+	}
+
+	pub fn components_mut(&mut self) -> &mut [T] {
+		&mut self.components
+	}
+
+	pub fn insert(&mut self, handle: Handle, value: T) -> Result<()> {
+		self.insert_at_tick(handle, value, 0)
+	}
+
+	/// Inserts a component, recording the tick it was added or changed at.
+	/// Replacing the component of the same entity counts as a change rather than an addition.
+	pub fn insert_at_tick(&mut self, handle: Handle, value: T, tick: u64) -> Result<()> {
+		let Some(dense) = self.dense_index(*handle.index()) else {
+			if self.sparse.len() <= *handle.index() {
+				self.sparse.resize(handle.index() + 1, None);
+			}
+			self.sparse[*handle.index()] = Some(self.components.len());
+			self.entities.push(handle);
+			self.components.push(value);
+			self.ticks.push(Ticks { added: tick, changed: tick });
+			return Ok(());
+		};
+
+		let previous = self.entities[dense];
+		if previous.generation() > handle.generation() {
+			return Err(Box::new(Error::Generation { handle }));
+		}
+		let added = if previous == handle { self.ticks[dense].added } else { tick };
+		self.entities[dense] = handle;
+		self.components[dense] = value;
+		self.ticks[dense] = Ticks { added, changed: tick };
+		Ok(())
+	}
+
+	pub fn remove(&mut self, handle: Handle) -> Option<T> {
+		let dense = self.dense_index_of(handle)?;
+		self.sparse[*handle.index()] = None;
+		self.entities.swap_remove(dense);
+		self.ticks.swap_remove(dense);
+		let component = self.components.swap_remove(dense);
+		if let Some(moved) = self.entities.get(dense) {
+			self.sparse[*moved.index()] = Some(dense);
+		}
+		Some(component)
+	}
+
+	pub fn contains(&self, handle: Handle) -> bool {
+		self.dense_index_of(handle).is_some()
+	}
+
+	pub fn get(&self, handle: Handle) -> Option<&T> {
+		self.dense_index_of(handle).map(|dense| &self.components[dense])
+	}
+
+	pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+		self.dense_index_of(handle).map(|dense| &mut self.components[dense])
+	}
+
+	pub fn ticks(&self, handle: Handle) -> Option<Ticks> {
+		self.dense_index_of(handle).map(|dense| self.ticks[dense])
+	}
+
+	/// Borrows a component along with its ticks, so a change to it can be recorded
+	pub fn get_with_ticks_mut(&mut self, handle: Handle) -> Option<(&mut T, &mut Ticks)> {
+		let dense = self.dense_index_of(handle)?;
+		Some((&mut self.components[dense], &mut self.ticks[dense]))
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (Handle, &T)> {
+		self.entities.iter().copied().zip(self.components.iter())
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = (Handle, &mut T)> {
+		self.entities.iter().copied().zip(self.components.iter_mut())
+	}
+
+	pub fn iter_with_ticks_mut(&mut self) -> impl Iterator<Item = (Handle, &mut T, &mut Ticks)> {
+		self.entities
+			.iter()
+			.copied()
+			.zip(self.components.iter_mut().zip(self.ticks.iter_mut()))
+			.map(|(entity, (component, ticks))| (entity, component, ticks))
+	}
+
+	pub fn clear(&mut self) {
+		self.sparse.clear();
+		self.entities.clear();
+		self.components.clear();
+		self.ticks.clear();
+	}
+
+	fn dense_index(&self, index: usize) -> Option<usize> {
+		self.sparse.get(index).copied().flatten()
+	}
+
+	fn dense_index_of(&self, handle: Handle) -> Option<usize> {
+		self.dense_index(*handle.index()).filter(|dense| self.entities[*dense] == handle)
+	}
+}
+
+/// A `ComponentVec` with its component type erased, so the world can store every type together
+pub trait ComponentStorage: Any {
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+	fn entities(&self) -> &[Handle];
+	fn contains(&self, handle: Handle) -> bool;
+	fn ticks(&self, handle: Handle) -> Option<Ticks>;
+	fn remove_entity(&mut self, handle: Handle);
+	fn clear(&mut self);
+}
+
+impl<T: 'static> ComponentStorage for ComponentVec<T> {
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
+
+	fn entities(&self) -> &[Handle] {
+		Self::entities(self)
+	}
+
+	fn contains(&self, handle: Handle) -> bool {
+		Self::contains(self, handle)
+	}
+
+	fn ticks(&self, handle: Handle) -> Option<Ticks> {
+		Self::ticks(self, handle)
+	}
+
+	fn remove_entity(&mut self, handle: Handle) {
+		self.remove(handle);
+	}
+
+	fn clear(&mut self) {
+		Self::clear(self);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::vec::HandleAllocator;
+
+	#[test]
+	fn insertion_and_removal() -> Result<()> {
+		let mut allocator = HandleAllocator::new();
+		let handles = (0..3).map(|_| allocator.allocate()).collect::<Vec<_>>();
+		let mut components = ComponentVec::new();
+		for (value, handle) in handles.iter().enumerate() {
+			components.insert_at_tick(*handle, value, 1)?;
+		}
+		components.insert_at_tick(handles[1], 10, 2)?;
+		assert_eq!(components.ticks(handles[1]), Some(Ticks { added: 1, changed: 2 }));
+
+		assert_eq!(components.remove(handles[0]), Some(0));
+		assert_eq!(components.components(), [2, 10]);
+		assert_eq!(components.get(handles[2]), Some(&2));
+		assert_eq!(components.get(handles[0]), None);
+
+		allocator.deallocate(&handles[2]);
+		let reused = allocator.allocate();
+		assert_eq!(components.get(reused), None);
+		components.insert(reused, 20)?;
+		assert!(components.insert(handles[2], 30).is_err());
+		assert_eq!(components.iter().collect::<Vec<_>>(), [(reused, &20), (handles[1], &10)]);
+		Ok(())
+	}
+}
@@ -31,7 +31,7 @@ pub struct GenerationalVec<T> {
 }
 
 impl<T> GenerationalVec<T> {
-	pub fn new(elements: SlotVec<T>) -> Self {
+	pub const fn new(elements: SlotVec<T>) -> Self {
 		Self { elements }
 	}
 
@@ -127,12 +127,13 @@ impl<T> DerefMut for Slot<T> {
 	}
 }
 
+#[derive(Clone)]
 pub struct Allocation {
 	allocated: bool,
 	generation: usize,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct HandleAllocator {
 	allocations: Vec<Allocation>,
 	available_handles: Vec<usize>,
@@ -175,7 +176,7 @@ impl HandleAllocator {
 		self.handle_exists(handle) && self.allocations[handle.index].generation == handle.generation && self.allocations[handle.index].allocated
 	}
 
-	pub fn handle_exists(&self, handle: &Handle) -> bool {
+	pub const fn handle_exists(&self, handle: &Handle) -> bool {
 		handle.index < self.allocations.len()
 	}
 
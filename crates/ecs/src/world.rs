@@ -1,59 +1,66 @@
 use crate::{
 	error::Result,
-	resource::ResourceMap,
-	vec::{GenerationalVec, Handle, HandleAllocator, SlotVec},
+	id::EntityId,
+	resource::Resources,
+	scene::{SceneComponent, SceneInstance},
+	storage::{ComponentStorage, ComponentVec, Ticks},
+	vec::{Handle, HandleAllocator},
 };
 use std::{
 	any::TypeId,
 	cell::{Ref, RefCell, RefMut},
 	collections::HashMap,
-	ops::Deref,
 	rc::Rc,
 };
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
+	#[error("Component '{}' was not registered as cloneable, so the world cannot be snapshotted.", name)]
+	ComponentNotCloneable { name: &'static str },
+
+	#[error("Entity id '{}' is already used by entity '{:?}'.", id, entity)]
+	DuplicateEntityId { id: EntityId, entity: Entity },
+
+	#[error("Failed to deserialize scene component '{}'!", name)]
+	DeserializeSceneComponent {
+		name: String,
+		#[source]
+		source: ron::Error,
+	},
+
 	#[error("Entity '{:?}' does not exist.", entity)]
 	EntityNotFound { entity: Entity },
+
+	#[error("Entity '{:?}' does not belong to the pool.", entity)]
+	EntityNotPooled { entity: Entity },
+
+	#[error("Scene instance '{:?}' does not exist.", instance)]
+	SceneInstanceNotFound { instance: SceneInstance },
+
+	#[error("Failed to serialize scene component '{}'!", name)]
+	SerializeSceneComponent {
+		name: &'static str,
+		#[source]
+		source: ron::Error,
+	},
+
+	#[error("Scene component '{}' was not registered with `register_scene_component`.", name)]
+	UnregisteredSceneComponent { name: String },
 }
 
 /*
-   Entities:                    Entity 0                       Entity 1   Entity 2                         Entity 3
-   Physics Components   -> Vec( Some(Physics { vel: 3 }),      None,      None,                            Some(Physics { vel: 04 }) )
-   Position Components  -> Vec( Some(Position { x: 3, y: 3 }), None,      Some(Position { x: 10, y: -2 }), Some(Position { x: 100, y: -20 }) )
+   Each component type is stored in a sparse set, with its components packed together:
+
+   Position Sparse      -> Vec( Some(0),              None,  Some(1),                 Some(2) )
+   Position Entities    -> Vec( Entity 0,             Entity 2,                Entity 3 )
+   Position Components  -> Vec( Position { x: 3, y: 3 }, Position { x: 10, y: -2 }, Position { x: 100, y: -20 } )
 */
 pub type ComponentMap = HashMap<TypeId, ComponentVecHandle>;
 
 pub type Entity = Handle;
-pub type ComponentVecHandle = Rc<RefCell<ComponentVec>>;
-pub type Component = Box<dyn std::any::Any + 'static>;
-pub type ComponentVec = GenerationalVec<Component>;
-
-impl Default for ComponentVec {
-	fn default() -> Self {
-		GenerationalVec::new(SlotVec::<Component>::default())
-	}
-}
-
-#[macro_export]
-macro_rules! component_vec {
-    () => {
-        {
-			use std::{rc::Rc, cell::RefCell};
-			use $crate::world::ComponentVec;
-            Rc::new(RefCell::new(ComponentVec::new(vec![])))
-        }
-    };
-
-    ($($component:expr),*) => {
-        {
-			use std::{rc::Rc, cell::RefCell};
-			use $crate::world::ComponentVec;
-            Rc::new(RefCell::new(ComponentVec::new(vec![$(Some($crate::vec::Slot::new(Box::new($component), 0)),)*])))
-        }
-    }
-}
+pub type ComponentVecHandle = Rc<RefCell<Box<dyn ComponentStorage>>>;
+pub type ComponentCloner = fn(&dyn ComponentStorage) -> Box<dyn ComponentStorage>;
 
 // from itertools
 #[macro_export]
@@ -97,55 +104,138 @@ macro_rules! izip {
 // types)
 #[macro_export]
 macro_rules! system {
-	($fn:tt, [$resources:ident, $entity:ident], ($($arg:ident: $arg_type:ty),*), ($component_name:ident: $component_type:ty) -> $result:ty {$($body:tt)*}) => {
+	// Visits every entity with all of the listed components.
+	// Iteration walks the first component's packed array, so list the rarest component first.
+	(
+		$fn:tt, [$resources:ident, $entity:ident], ($($arg:ident: $arg_type:ty),*),
+		($first_name:ident: $first_type:ty $(, $component_name:ident: $component_type:ty)*) -> $result:ty {$($body:tt)*}
+	) => {
 		pub fn $fn($($arg: $arg_type,)* world: &mut World) -> $result {
-			world
-			.get_component_vec_mut::<$component_type>()
-			.unwrap_or_else(|| panic!("System accessed an unregistered component type: {:?}", stringify!($component_type)))
-			.iter_mut()
-			.enumerate()
-			.filter_map(|(entity, $component_name)| match ($component_name) {
-				Some($component_name) => {
-					let $component_name = $component_name.downcast_mut::<$component_type>().unwrap();
-					Some((world.resources().clone(), entity, $component_name))
-				},
-				_ => None,
-			})
-			.try_for_each(|($resources, $entity, mut $component_name)| {
-				$($body)*
-			})
+			let system_run = world.begin_system_run(concat!(module_path!(), "::", stringify!($fn)));
+			let result = {
+				let resources = world.resources().clone();
+				let mut $first_name = world
+					.get_component_vec_mut::<$first_type>()
+					.unwrap_or_else(|| panic!("System accessed an unregistered component type: {:?}", stringify!($first_type)));
+				$(
+					let mut $component_name = world
+						.get_component_vec_mut::<$component_type>()
+						.unwrap_or_else(|| panic!("System accessed an unregistered component type: {:?}", stringify!($component_type)));
+				)*
+				#[allow(unused_mut)]
+				let mut run = |$resources: &std::rc::Rc<$crate::resource::Resources>,
+				               $entity: $crate::world::Entity,
+				               mut $first_name: $crate::query::Mut<$first_type>
+				               $(, mut $component_name: $crate::query::Mut<$component_type>)*|
+				 -> $result { $($body)* };
+				(|| -> $result {
+					for (entity, component, ticks) in $first_name.iter_with_ticks_mut() {
+						$(
+							let Some($component_name) = $component_name.get_with_ticks_mut(entity) else {
+								continue;
+							};
+						)*
+						run(
+							&resources,
+							entity,
+							$crate::query::Mut::new((component, ticks), system_run.this_run)
+							$(, $crate::query::Mut::new($component_name, system_run.this_run))*
+						)?;
+					}
+					Ok(())
+				})()
+			};
+			world.finish_system_run(concat!(module_path!(), "::", stringify!($fn)), system_run);
+			result
 		}
-    };
-
-    ($fn:tt, [$resources:ident, $entity:ident], ($($arg:ident: $arg_type:ty),*), ($($component_name:ident: $component_type:ty),*) -> $result:ty {$($body:tt)*}) => {
+	};
+
+	// Filtered systems visit entities matching every filter, such as `Changed<Particle>`.
+	// Components listed after `optional` are passed as `Option`s.
+	(
+		$fn:tt, [$resources:ident, $entity:ident], ($($arg:ident: $arg_type:ty),*),
+		($($component_name:ident: $component_type:ty),*), [$($filter:ident<$filter_type:ty>),*]
+		$(, optional($($optional_name:ident: $optional_type:ty),*))? -> $result:ty {$($body:tt)*}
+	) => {
 		pub fn $fn($($arg: $arg_type,)* world: &mut World) -> $result {
-			izip!(
+			let system_run = world.begin_system_run(concat!(module_path!(), "::", stringify!($fn)));
+			let entities = $crate::query::Query::new()
+				$(.with::<$component_type>())*
+				$(.filter::<$filter<$filter_type>>())*
+				.since(system_run.last_run)
+				.entities(world);
+			let result = {
+				let resources = world.resources().clone();
 				$(
-					world.get_component_vec_mut::<$component_type>().unwrap_or_else(|| panic!("System accessed an unregistered component type: {:?}", stringify!($component_type))).iter_mut()
-				),*
-			)
-			.enumerate()
-			.filter_map(|(entity, ($($component_name),*))| match ($($component_name,)*) {
-				($(Some($component_name),)*) => {
-					$(
-						let $component_name = $component_name.downcast_mut::<$component_type>().unwrap();
-					)*
-					Some((world.resources().clone(), entity, $( $component_name,)*))
-				},
-				_ => None,
-			})
-			.try_for_each(|($resources, $entity, $(mut $component_name,)*)| {
-				$($body)*
-			})
+					let mut $component_name = world
+						.get_component_vec_mut::<$component_type>()
+						.unwrap_or_else(|| panic!("System accessed an unregistered component type: {:?}", stringify!($component_type)));
+				)*
+				$($(
+					let mut $optional_name = world.get_component_vec_mut::<$optional_type>();
+				)*)?
+				#[allow(unused_mut)]
+				let mut run = |$resources: &std::rc::Rc<$crate::resource::Resources>,
+				               $entity: $crate::world::Entity
+				               $(, mut $component_name: $crate::query::Mut<$component_type>)*
+				               $($(, mut $optional_name: Option<$crate::query::Mut<$optional_type>>)*)?|
+				 -> $result { $($body)* };
+				(|| -> $result {
+					for entity in entities {
+						$(
+							let Some($component_name) = $component_name.get_with_ticks_mut(entity) else {
+								continue;
+							};
+						)*
+						$($(
+							let $optional_name = $optional_name
+								.as_mut()
+								.and_then(|components| components.get_with_ticks_mut(entity))
+								.map(|parts| $crate::query::Mut::new(parts, system_run.this_run));
+						)*)?
+						run(
+							&resources,
+							entity
+							$(, $crate::query::Mut::new($component_name, system_run.this_run))*
+							$($(, $optional_name)*)?
+						)?;
+					}
+					Ok(())
+				})()
+			};
+			world.finish_system_run(concat!(module_path!(), "::", stringify!($fn)), system_run);
+			result
 		}
-    }
+	};
 }
 
 #[derive(Default)]
 pub struct World {
-	resources: Rc<RefCell<ResourceMap>>,
+	resources: Rc<Resources>,
 	components: ComponentMap,
 	allocator: HandleAllocator,
+	component_names: HashMap<TypeId, &'static str>,
+	cloners: HashMap<TypeId, ComponentCloner>,
+	pub(crate) entity_ids: HashMap<EntityId, Entity>,
+	pub(crate) scene_components: HashMap<&'static str, SceneComponent>,
+	pub(crate) scene_instances: handle::SlotMap<Vec<Entity>>,
+	change_tick: u64,
+	system_runs: HashMap<&'static str, u64>,
+}
+
+/// The ticks bounding a single run of a system, used to find components changed since it last ran
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SystemRun {
+	/// The tick of the system's previous run, or `None` if this is its first
+	pub last_run: Option<u64>,
+	pub this_run: u64,
+}
+
+/// A copy of a world's entities and components, taken with `World::snapshot`.
+/// Resources are not part of the snapshot.
+pub struct WorldSnapshot {
+	components: HashMap<TypeId, Box<dyn ComponentStorage>>,
+	allocator: HandleAllocator,
 }
 
 impl World {
@@ -153,7 +243,7 @@ impl World {
 		Self::default()
 	}
 
-	pub const fn resources(&self) -> &Rc<RefCell<ResourceMap>> {
+	pub const fn resources(&self) -> &Rc<Resources> {
 		&self.resources
 	}
 
@@ -162,19 +252,29 @@ impl World {
 	}
 
 	pub fn create_entities(&mut self, count: usize) -> Vec<Entity> {
-		(0..count).into_iter().map(|_index| self.allocator.allocate()).collect()
+		(0..count).map(|_index| self.allocator.allocate()).collect()
+	}
+
+	/// Every entity that currently exists, in index order
+	pub fn entities(&self) -> Vec<Entity> {
+		self.allocator.allocated_handles()
 	}
 
 	pub fn remove_entity(&mut self, entity: Entity) {
 		self.remove_entities(&[entity]);
 	}
 
+	/// Removes entities along with all of their components
 	pub fn remove_entities(&mut self, entities: &[Entity]) {
+		for components in self.components.values() {
+			let mut components = components.borrow_mut();
+			entities.iter().for_each(|entity| components.remove_entity(*entity));
+		}
 		entities.iter().for_each(|entity| self.allocator.deallocate(entity))
 	}
 
 	pub fn add_component<T: 'static>(&mut self, entity: Entity, component: T) -> Result<()> {
-		self.assign_component::<T>(entity, Some(Box::new(component)))
+		self.assign_component::<T>(entity, Some(component))
 	}
 
 	pub fn has_component<T: 'static>(&mut self, entity: Entity) -> bool {
@@ -185,20 +285,17 @@ impl World {
 		self.assign_component::<T>(entity, None)
 	}
 
-	fn assign_component<T: 'static>(&mut self, entity: Entity, value: Option<Component>) -> Result<()> {
+	fn assign_component<T: 'static>(&mut self, entity: Entity, value: Option<T>) -> Result<()> {
 		if !self.allocator.handle_exists(&entity) {
 			return Err(Box::new(Error::EntityNotFound { entity }));
 		}
 
-		let mut components = self
-			.components
-			.entry(TypeId::of::<T>())
-			.or_insert_with(|| Rc::new(RefCell::new(ComponentVec::default())))
-			.borrow_mut();
+		self.register_component::<T>();
+		let mut components = self.get_component_vec_mut::<T>().expect("Component was just registered");
 
 		match value {
 			Some(component) => {
-				components.insert(entity, component)?;
+				components.insert_at_tick(entity, component, self.change_tick)?;
 			},
 			None => {
 				components.remove(entity);
@@ -209,54 +306,169 @@ impl World {
 	}
 
 	#[must_use]
-	pub fn get_component<T: 'static>(&self, entity: Entity) -> Option<Ref<T>> {
+	pub fn get_component<T: 'static>(&self, entity: Entity) -> Option<Ref<'_, T>> {
 		if !self.entity_exists(entity) {
 			return None;
 		}
-		self.components.get(&TypeId::of::<T>()).and_then(|component_vec| {
-			if !entity_has_component(entity, component_vec) {
-				return None;
-			}
-			Some(Ref::map(component_vec.borrow(), |t| {
-				t.get(entity).and_then(|component| component.downcast_ref::<T>()).unwrap()
-			}))
-		})
+		let components = self.get_component_vec::<T>()?;
+		Ref::filter_map(components, |components| components.get(entity)).ok()
 	}
 
+	/// Mutably borrows a component, which marks it as changed for change detection
 	#[must_use]
-	pub fn get_component_mut<T: 'static>(&self, entity: Entity) -> Option<RefMut<T>> {
+	pub fn get_component_mut<T: 'static>(&self, entity: Entity) -> Option<RefMut<'_, T>> {
 		if !self.entity_exists(entity) {
 			return None;
 		}
-		self.components.get(&TypeId::of::<T>()).and_then(|component_vec| {
-			if !entity_has_component(entity, component_vec) {
-				return None;
-			}
-			Some(RefMut::map(component_vec.borrow_mut(), |t| {
-				t.get_mut(entity).and_then(|c| c.downcast_mut::<T>()).unwrap()
-			}))
+		let components = self.get_component_vec_mut::<T>()?;
+		RefMut::filter_map(components, |components| {
+			let (component, ticks) = components.get_with_ticks_mut(entity)?;
+			ticks.changed = self.change_tick;
+			Some(component)
 		})
+		.ok()
+	}
+
+	/// The ticks an entity's component was added and last changed at
+	pub fn component_ticks<T: 'static>(&self, entity: Entity) -> Option<Ticks> {
+		self.component_ticks_by_id(TypeId::of::<T>(), entity)
+	}
+
+	/// The entities with a component of the given type, in storage order
+	pub(crate) fn component_entities_by_id(&self, type_id: TypeId) -> Option<Ref<'_, [Entity]>> {
+		let components = self.components.get(&type_id)?.borrow();
+		Some(Ref::map(components, |components| components.entities()))
+	}
+
+	pub(crate) fn component_ticks_by_id(&self, type_id: TypeId, entity: Entity) -> Option<Ticks> {
+		if !self.entity_exists(entity) {
+			return None;
+		}
+		self.components.get(&type_id)?.borrow().ticks(entity)
+	}
+
+	/// The current tick, which components added or changed right now are stamped with
+	pub const fn change_tick(&self) -> u64 {
+		self.change_tick
+	}
+
+	/// Starts a run of the named system, advancing the change tick.
+	/// Systems created with `system!` call this automatically.
+	pub fn begin_system_run(&mut self, name: &'static str) -> SystemRun {
+		self.change_tick += 1;
+		self.resources.set_running_system(Some(name));
+		SystemRun {
+			last_run: self.system_runs.get(name).copied(),
+			this_run: self.change_tick,
+		}
+	}
+
+	/// Records that the named system finished, so its next run only sees later changes
+	pub fn finish_system_run(&mut self, name: &'static str, run: SystemRun) {
+		self.system_runs.insert(name, run.this_run);
+		self.resources.set_running_system(None);
+		self.change_tick += 1;
 	}
 
-	pub fn get_component_vec<T: 'static>(&self) -> Option<Ref<ComponentVec>> {
-		self.components.get(&TypeId::of::<T>()).map(|component_vec| component_vec.deref().borrow())
+	pub fn get_component_vec<T: 'static>(&self) -> Option<Ref<'_, ComponentVec<T>>> {
+		let components = self.components.get(&TypeId::of::<T>())?.borrow();
+		Some(Ref::map(components, |components| {
+			components.as_any().downcast_ref::<ComponentVec<T>>().expect("Component vec contained the wrong type")
+		}))
 	}
 
-	pub fn get_component_vec_mut<T: 'static>(&self) -> Option<RefMut<ComponentVec>> {
-		self.components.get(&TypeId::of::<T>()).map(|component_vec| component_vec.deref().borrow_mut())
+	pub fn get_component_vec_mut<T: 'static>(&self) -> Option<RefMut<'_, ComponentVec<T>>> {
+		let components = self.components.get(&TypeId::of::<T>())?.borrow_mut();
+		Some(RefMut::map(components, |components| {
+			components
+				.as_any_mut()
+				.downcast_mut::<ComponentVec<T>>()
+				.expect("Component vec contained the wrong type")
+		}))
 	}
 
 	pub fn register_component<T: 'static>(&mut self) {
-		self.components.entry(TypeId::of::<T>()).or_insert(component_vec!());
+		self.component_names.insert(TypeId::of::<T>(), std::any::type_name::<T>());
+		self.components
+			.entry(TypeId::of::<T>())
+			.or_insert_with(|| Rc::new(RefCell::new(Box::new(ComponentVec::<T>::new()))));
+	}
+
+	/// Registers a component type that is copied when the world is snapshotted
+	pub fn register_cloneable_component<T: Clone + 'static>(&mut self) {
+		self.register_component::<T>();
+		self.cloners.insert(TypeId::of::<T>(), |components| {
+			let components = components.as_any().downcast_ref::<ComponentVec<T>>().expect("Component vec contained the wrong type");
+			Box::new(components.clone())
+		});
+	}
+
+	/// Copies every entity and component so the world can be restored later,
+	/// such as when leaving play mode in the editor.
+	/// Every component type in the world must be registered with `register_cloneable_component`.
+	pub fn snapshot(&self) -> Result<WorldSnapshot> {
+		let mut components = HashMap::new();
+		for (type_id, component_vec) in self.components.iter() {
+			let Some(cloner) = self.cloners.get(type_id) else {
+				let name = self.component_names.get(type_id).copied().unwrap_or("unknown");
+				return Err(Box::new(Error::ComponentNotCloneable { name }));
+			};
+			components.insert(*type_id, cloner(component_vec.borrow().as_ref()));
+		}
+		Ok(WorldSnapshot {
+			components,
+			allocator: self.allocator.clone(),
+		})
+	}
+
+	/// Replaces every entity and component with those in the snapshot, leaving resources untouched.
+	/// Component types added since the snapshot was taken are emptied.
+	pub fn restore(&mut self, snapshot: &WorldSnapshot) {
+		self.allocator = snapshot.allocator.clone();
+		for (type_id, component_vec) in self.components.iter() {
+			match (snapshot.components.get(type_id), self.cloners.get(type_id)) {
+				(Some(saved), Some(cloner)) => *component_vec.borrow_mut() = cloner(saved.as_ref()),
+				_ => component_vec.borrow_mut().clear(),
+			}
+		}
+	}
+
+	/// The type names of every component the entity has, sorted, such as for an inspector
+	pub fn component_names(&self, entity: Entity) -> Vec<&'static str> {
+		if !self.entity_exists(entity) {
+			return Vec::new();
+		}
+		let mut names = self
+			.components
+			.iter()
+			.filter(|(_type_id, components)| entity_has_component(entity, components))
+			.map(|(type_id, _components)| self.component_names.get(type_id).copied().unwrap_or("unknown"))
+			.collect::<Vec<_>>();
+		names.sort_unstable();
+		names
 	}
 
 	pub fn entity_exists(&self, entity: Entity) -> bool {
 		self.allocator.is_allocated(&entity)
 	}
+
+	/// Scans a component vec's packed array for components matching the predicate
+	pub(crate) fn entities_matching<T: 'static>(&self, predicate: impl Fn(&T) -> bool) -> Vec<Entity> {
+		let Some(components) = self.get_component_vec::<T>() else {
+			return Vec::new();
+		};
+		let mut entities = components
+			.iter()
+			.filter(|(_entity, component)| predicate(component))
+			.map(|(entity, _component)| entity)
+			.collect::<Vec<_>>();
+		entities.sort_by_key(|entity| *entity.index());
+		entities
+	}
 }
 
 pub fn entity_has_component(entity: Entity, components: &ComponentVecHandle) -> bool {
-	components.borrow().get(entity).is_some()
+	components.borrow().contains(entity)
 }
 
 #[cfg(test)]
@@ -304,6 +516,14 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn entities() {
+		let mut world = World::default();
+		let entities = world.create_entities(3);
+		world.remove_entity(entities[1]);
+		assert_eq!(world.entities(), [entities[0], entities[2]]);
+	}
+
 	#[test]
 	fn add_component() -> Result<()> {
 		let mut world = World::default();
@@ -370,8 +590,9 @@ mod tests {
 		let mut entity_allocator = HandleAllocator::new();
 		let entity = entity_allocator.allocate();
 
-		let components = component_vec!();
-		components.borrow_mut().insert(entity, Box::new(Name("Elliot Alderson".to_string())))?;
+		let mut names = ComponentVec::new();
+		names.insert(entity, Name("Elliot Alderson".to_string()))?;
+		let components: ComponentVecHandle = Rc::new(RefCell::new(Box::new(names)));
 
 		assert!(entity_has_component(entity, &components));
 
@@ -415,6 +636,30 @@ mod tests {
 		translation_system(0.14, &mut world).unwrap();
 	}
 
+	#[test]
+	fn snapshot() -> Result<()> {
+		let mut world = World::default();
+		world.register_cloneable_component::<Position>();
+		world.register_cloneable_component::<Health>();
+		let entity = world.create_entity();
+		world.add_component(entity, Position { x: 1.0, y: 2.0 })?;
+		let snapshot = world.snapshot()?;
+
+		world.get_component_mut::<Position>(entity).unwrap().x = 10.0;
+		world.add_component(entity, Health { value: 3 })?;
+		let spawned = world.create_entity();
+		world.add_component(spawned, Position::default())?;
+
+		world.restore(&snapshot);
+		assert_eq!(world.get_component::<Position>(entity).as_deref(), Some(&Position { x: 1.0, y: 2.0 }));
+		assert!(world.get_component::<Health>(entity).is_none());
+		assert!(!world.entity_exists(spawned));
+
+		world.add_component(entity, Name("Darlene".to_string()))?;
+		assert!(world.snapshot().is_err());
+		Ok(())
+	}
+
 	#[test]
 	fn component_registration() -> Result<()> {
 		let mut world = World::default();
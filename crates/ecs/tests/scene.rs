@@ -0,0 +1,107 @@
+//! Saves entities to scenes and inserts them into worlds again. The components live here
+//! rather than in the crate, since the crate forbids the lints serde's derives allow.
+
+use ecs::{
+	error::Result,
+	id::EntityId,
+	scene::{MapEntityIds, SceneData},
+	world::World,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Position {
+	x: f32,
+	y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Faction {
+	Neutral,
+	Hostile { aggression: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Target(Option<EntityId>);
+
+impl MapEntityIds for Target {
+	fn map_entity_ids(&mut self, ids: &HashMap<EntityId, EntityId>) {
+		if let Some(id) = self.0.as_mut() {
+			*id = ids.get(id).copied().unwrap_or(*id);
+		}
+	}
+}
+
+fn world() -> World {
+	let mut world = World::new();
+	world.register_scene_component::<Position>("Position");
+	world.register_mapped_scene_component::<Target>("Target");
+	world.register_scene_component::<Faction>("Faction");
+	world
+}
+
+fn prefab() -> Result<SceneData> {
+	let mut world = world();
+	let (turret, turret_id) = world.create_entity_with_id()?;
+	let base = world.create_entity();
+	world.add_component(turret, Position { x: 1.0, y: 2.0 })?;
+	world.add_component(turret, Faction::Hostile { aggression: 3 })?;
+	world.add_component(base, Target(Some(turret_id)))?;
+	world.add_component(base, Faction::Neutral)?;
+	let scene = world.extract_scene(&[turret, base])?;
+	Ok(ron::from_str(&ron::to_string(&scene)?)?)
+}
+
+#[test]
+fn insert_scene() -> Result<()> {
+	let scene = prefab()?;
+	let mut world = world();
+	let existing = world.create_entity();
+	let first = world.insert_scene(&scene)?;
+	let second = world.insert_scene(&scene)?;
+
+	for instance in [first, second] {
+		let [turret, base] = world.scene_entities(instance).unwrap() else {
+			panic!("Expected two entities");
+		};
+		assert_eq!(*world.get_component::<Position>(*turret).unwrap(), Position { x: 1.0, y: 2.0 });
+		assert_eq!(*world.get_component::<Faction>(*turret).unwrap(), Faction::Hostile { aggression: 3 });
+		assert_eq!(*world.get_component::<Faction>(*base).unwrap(), Faction::Neutral);
+		let target = world.get_component::<Target>(*base).unwrap().0.unwrap();
+		assert_eq!(world.find_by_id(target), Some(*turret));
+	}
+	assert_ne!(world.scene_entities(first), world.scene_entities(second));
+
+	world.despawn_scene(first)?;
+	assert_eq!(world.entities().len(), 3);
+	assert!(world.entity_exists(existing));
+	assert!(world.despawn_scene(first).is_err());
+
+	// A later instance reuses the slot, but the despawned instance doesn't reach it
+	let third = world.insert_scene(&scene)?;
+	assert!(world.scene_entities(first).is_none());
+	assert!(world.scene_entities(third).is_some());
+	Ok(())
+}
+
+#[test]
+fn inspect() -> Result<()> {
+	let mut world = world();
+	let instance = world.insert_scene(&prefab()?)?;
+	let turret = world.scene_entities(instance).unwrap()[0];
+	let components = world.inspect_scene_components(turret)?;
+	assert_eq!(components.get("Position").map(String::as_str), Some("(x:1.0,y:2.0)"));
+	assert!(world.component_names(turret).iter().any(|name| name.ends_with("::Position")));
+	Ok(())
+}
+
+#[test]
+fn unregistered_components() -> Result<()> {
+	let scene = prefab()?;
+	let mut world = World::new();
+	world.register_scene_component::<Position>("Position");
+	assert!(world.insert_scene(&scene).is_err());
+	assert!(world.entities().is_empty());
+	Ok(())
+}
@@ -0,0 +1,322 @@
+use crate::instancing::{MaterialHandle, Matrix4};
+use bytemuck::{Pod, Zeroable};
+use math::{BoundingSphere, Color, Frustum, Real, Vector3};
+use std::f32::consts::TAU;
+
+/// Draws textured quads that face the camera, with one instance per billboard
+pub const BILLBOARD_SHADER: &str = include_str!("shaders/billboard.wgsl");
+
+/// How a billboard turns to face the camera
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BillboardMode {
+	/// Always parallel to the screen, for sprites, particles, and labels
+	#[default]
+	Spherical,
+
+	/// Only turns around the world's up axis, for trees, grass, and other things that stand upright
+	Cylindrical,
+}
+
+/// Which cell of the atlas a billboard shows
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AtlasFrame {
+	Fixed(u32),
+
+	/// Plays the frames in order, holding the last one when the animation doesn't loop
+	Animated {
+		frames_per_second: Real,
+		looping: bool,
+	},
+
+	/// Picks the frame rendered from the direction the camera is looking from.
+	/// Impostors use this with an atlas of a mesh captured from angles around its up axis.
+	ViewAngle,
+}
+
+/// A texture split into a grid of equally sized frames, read left to right and top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpriteAtlas {
+	pub columns: u32,
+	pub rows: u32,
+
+	/// Frames in use, which can leave the last row partially empty
+	pub frame_count: u32,
+	pub frame: AtlasFrame,
+}
+
+impl SpriteAtlas {
+	pub fn new(columns: u32, rows: u32) -> Self {
+		let (columns, rows) = (columns.max(1), rows.max(1));
+		Self {
+			columns,
+			rows,
+			frame_count: columns * rows,
+			frame: AtlasFrame::Fixed(0),
+		}
+	}
+
+	pub fn animated(mut self, frames_per_second: Real, looping: bool) -> Self {
+		self.frame = AtlasFrame::Animated { frames_per_second, looping };
+		self
+	}
+
+	/// The frame to show at `time` seconds, looking from `to_camera`
+	pub fn frame_index(&self, time: Real, to_camera: Vector3) -> u32 {
+		let count = self.frame_count.max(1);
+		match self.frame {
+			AtlasFrame::Fixed(frame) => frame.min(count - 1),
+			AtlasFrame::Animated { frames_per_second, looping } => {
+				let frame = (time * frames_per_second).max(0.0) as u32;
+				if looping { frame % count } else { frame.min(count - 1) }
+			},
+			AtlasFrame::ViewAngle => {
+				let turns = (to_camera.x().atan2(to_camera.z()) / TAU).rem_euclid(1.0);
+				(turns * count as Real).round() as u32 % count
+			},
+		}
+	}
+
+	/// The offset and scale of a frame in texture coordinates
+	pub fn uv_rect(&self, frame: u32) -> ([f32; 2], [f32; 2]) {
+		let scale = [1.0 / self.columns as f32, 1.0 / self.rows as f32];
+		let (column, row) = (frame % self.columns, (frame / self.columns).min(self.rows - 1));
+		([column as f32 * scale[0], row as f32 * scale[1]], scale)
+	}
+}
+
+/// A textured quad that faces the camera, drawn at its entity's position
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Billboard {
+	pub material: MaterialHandle,
+	pub mode: BillboardMode,
+
+	/// Width and height in world units
+	pub size: [Real; 2],
+
+	/// The point on the quad placed at the position, from `[0, 0]` at the bottom left.
+	/// Upright foliage uses `[0.5, 0.0]` so it stands on the ground.
+	pub pivot: [Real; 2],
+
+	pub color: Color,
+	pub atlas: Option<SpriteAtlas>,
+}
+
+impl Billboard {
+	pub fn new(material: MaterialHandle, width: Real, height: Real) -> Self {
+		Self {
+			material,
+			mode: BillboardMode::default(),
+			size: [width, height],
+			pivot: [0.5, 0.5],
+			color: Color::WHITE,
+			atlas: None,
+		}
+	}
+
+	/// An upright billboard showing a mesh from the atlas' captured angle nearest the camera
+	pub fn impostor(material: MaterialHandle, width: Real, height: Real, atlas: SpriteAtlas) -> Self {
+		Self {
+			mode: BillboardMode::Cylindrical,
+			pivot: [0.5, 0.0],
+			atlas: Some(SpriteAtlas {
+				frame: AtlasFrame::ViewAngle,
+				..atlas
+			}),
+			..Self::new(material, width, height)
+		}
+	}
+
+	/// The right and up axes of the quad, matching the shader
+	pub fn axes(&self, position: Vector3, camera: &BillboardCamera) -> (Vector3, Vector3) {
+		match self.mode {
+			BillboardMode::Spherical => (camera.right, camera.up),
+			BillboardMode::Cylindrical => {
+				let up = Vector3::y_axis();
+				let to_camera = camera.position - position;
+				let horizontal = Vector3::new(to_camera.x(), 0.0, to_camera.z());
+				let right = if horizontal.magnitude_squared() > 1e-8 {
+					up.cross(&horizontal).normalize()
+				} else {
+					camera.right
+				};
+				(right, up)
+			},
+		}
+	}
+
+	/// The bottom left, bottom right, top right, and top left corners in world space
+	pub fn corners(&self, position: Vector3, camera: &BillboardCamera) -> [Vector3; 4] {
+		let (right, up) = self.axes(position, camera);
+		[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]].map(|[x, y]| position + right * ((x - self.pivot[0]) * self.size[0]) + up * ((y - self.pivot[1]) * self.size[1]))
+	}
+
+	pub fn bounds(&self, position: Vector3) -> BoundingSphere {
+		let [width, height] = self.size;
+		let [x, y] = self.pivot;
+		let reach = |extent: Real, pivot: Real| extent * pivot.max(1.0 - pivot);
+		BoundingSphere::new(position, reach(width, x).hypot(reach(height, y)))
+	}
+
+	pub fn instance(&self, position: Vector3, camera: &BillboardCamera, time: Real) -> BillboardInstance {
+		let (uv_offset, uv_scale) = match self.atlas {
+			Some(atlas) => atlas.uv_rect(atlas.frame_index(time, camera.position - position)),
+			None => ([0.0; 2], [1.0; 2]),
+		};
+		BillboardInstance {
+			position: [position.x(), position.y(), position.z()],
+			mode: self.mode as u32,
+			size: self.size,
+			pivot: self.pivot,
+			uv_offset,
+			uv_scale,
+			color: self.color.into(),
+		}
+	}
+}
+
+/// The view the billboards face
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BillboardCamera {
+	pub position: Vector3,
+	pub right: Vector3,
+	pub up: Vector3,
+	pub view_projection: Matrix4,
+}
+
+impl BillboardCamera {
+	pub fn uniform(&self) -> BillboardCameraUniform {
+		let vector = |vector: Vector3| [vector.x(), vector.y(), vector.z()];
+		BillboardCameraUniform {
+			view_projection: self.view_projection,
+			position: vector(self.position),
+			padding0: 0.0,
+			right: vector(self.right),
+			padding1: 0.0,
+			up: vector(self.up),
+			padding2: 0.0,
+		}
+	}
+
+	/// The billboards inside the frustum, sorted by material and then from far to near
+	pub fn visible_instances<'a>(
+		&self,
+		billboards: impl IntoIterator<Item = (Vector3, &'a Billboard)>,
+		time: Real,
+		output: &mut Vec<(MaterialHandle, BillboardInstance)>,
+	) {
+		let frustum = Frustum::from_view_projection(&self.view_projection);
+		let mut sorted = billboards
+			.into_iter()
+			.filter(|(position, billboard)| frustum.intersects_sphere(&billboard.bounds(*position)))
+			.map(|(position, billboard)| {
+				(
+					(position - self.position).magnitude_squared(),
+					billboard.material,
+					billboard.instance(position, self, time),
+				)
+			})
+			.collect::<Vec<_>>();
+		sorted.sort_by(|a, b| a.1.cmp(&b.1).then(b.0.total_cmp(&a.0)));
+		output.clear();
+		output.extend(sorted.into_iter().map(|(_, material, instance)| (material, instance)));
+	}
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct BillboardCameraUniform {
+	pub view_projection: Matrix4,
+	pub position: [f32; 3],
+	pub padding0: f32,
+	pub right: [f32; 3],
+	pub padding1: f32,
+	pub up: [f32; 3],
+	pub padding2: f32,
+}
+
+/// Per-instance data stored in the billboard storage buffer
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct BillboardInstance {
+	pub position: [f32; 3],
+	pub mode: u32,
+	pub size: [f32; 2],
+	pub pivot: [f32; 2],
+	pub uv_offset: [f32; 2],
+	pub uv_scale: [f32; 2],
+	pub color: [f32; 4],
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::instancing::IDENTITY;
+
+	fn camera(position: Vector3) -> BillboardCamera {
+		BillboardCamera {
+			position,
+			right: Vector3::x_axis(),
+			up: Vector3::y_axis(),
+			view_projection: IDENTITY,
+		}
+	}
+
+	#[test]
+	pub fn facing() {
+		let mut billboard = Billboard::new(MaterialHandle(0), 2.0, 1.0);
+		let camera = camera(Vector3::new(5.0, 3.0, 0.0));
+		let corners = billboard.corners(Vector3::zero(), &camera);
+		assert_eq!(corners[0], Vector3::new(-1.0, -0.5, 0.0));
+		assert_eq!(corners[2], Vector3::new(1.0, 0.5, 0.0));
+
+		// Looking along -x, an upright billboard turns to face +x but keeps its up axis
+		billboard.mode = BillboardMode::Cylindrical;
+		billboard.pivot = [0.5, 0.0];
+		let (right, up) = billboard.axes(Vector3::zero(), &camera);
+		assert_eq!(up, Vector3::y_axis());
+		assert_eq!(right, Vector3::new(0.0, 0.0, -1.0));
+		assert_eq!(billboard.corners(Vector3::zero(), &camera)[3], Vector3::new(0.0, 1.0, 1.0));
+	}
+
+	#[test]
+	pub fn atlas_frames() {
+		let atlas = SpriteAtlas::new(4, 2).animated(10.0, false);
+		assert_eq!(atlas.frame_index(0.25, Vector3::z_axis()), 2);
+		assert_eq!(atlas.frame_index(5.0, Vector3::z_axis()), 7);
+		assert_eq!(atlas.animated(10.0, true).frame_index(1.0, Vector3::z_axis()), 2);
+		assert_eq!(atlas.uv_rect(5), ([0.25, 0.5], [0.25, 0.5]));
+
+		let impostor = Billboard::impostor(MaterialHandle(0), 1.0, 2.0, SpriteAtlas::new(8, 1));
+		let atlas = impostor.atlas.unwrap();
+		assert_eq!(atlas.frame_index(0.0, Vector3::z_axis()), 0);
+		assert_eq!(atlas.frame_index(0.0, Vector3::x_axis()), 2);
+		assert_eq!(atlas.frame_index(0.0, Vector3::new(-1.0, 0.0, 0.0)), 6);
+		let instance = impostor.instance(Vector3::zero(), &camera(Vector3::new(-3.0, 0.0, 0.0)), 0.0);
+		assert_eq!((instance.mode, instance.uv_offset), (1, [0.75, 0.0]));
+	}
+
+	#[test]
+	pub fn visible_instances() {
+		let near = Billboard::new(MaterialHandle(1), 0.1, 0.1);
+		let far = Billboard::new(MaterialHandle(1), 0.1, 0.1);
+		let other = Billboard::new(MaterialHandle(0), 0.1, 0.1);
+		let billboards = [
+			(Vector3::new(0.0, 0.0, 0.2), &near),
+			(Vector3::new(0.0, 0.0, 0.8), &far),
+			(Vector3::new(0.5, 0.0, 0.5), &other),
+			(Vector3::new(4.0, 0.0, 0.5), &other),
+		];
+		let mut instances = Vec::new();
+		camera(Vector3::zero()).visible_instances(billboards, 0.0, &mut instances);
+		let depths = instances.iter().map(|(material, instance)| (material.0, instance.position[2])).collect::<Vec<_>>();
+		assert_eq!(depths, [(0, 0.5), (1, 0.8), (1, 0.2)]);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(BILLBOARD_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
@@ -0,0 +1,437 @@
+use crate::{
+	compute::{BindingResource, BufferId, ComputeDispatch, ComputeError, ComputePass, CpuCompute, workgroup_count},
+	instancing::Matrix4,
+};
+use bytemuck::{Pod, Zeroable};
+use math::{Aabb, Camera, Color, Projection, Real, Vector3, transform_point};
+
+/// Structs and cluster math shared by the light culling pass and the clustered lighting include
+pub const CLUSTERS_SHADER: &str = include_str!("shaders/clusters.wgsl");
+
+/// The compute pass that lists the lights reaching each cluster, at entry point `assign_lights`
+pub const LIGHT_CULLING_SHADER: &str = include_str!("shaders/light_culling.wgsl");
+
+/// Included by mesh shaders for `clustered_lighting`, which shades with the cluster's lights
+pub const CLUSTERED_LIGHTING_SHADER: &str = include_str!("shaders/clustered_lighting.wgsl");
+
+pub const LIGHT_CULLING_WORKGROUP_SIZE: u32 = 64;
+
+type Result<T, E = ComputeError> = std::result::Result<T, E>;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LightKind {
+	Point,
+	Spot {
+		direction: Vector3,
+
+		/// Radians from the direction to where the light starts to fade
+		inner_angle: Real,
+
+		/// Radians from the direction to where the light is gone
+		outer_angle: Real,
+	},
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Light {
+	pub kind: LightKind,
+	pub position: Vector3,
+
+	/// The distance past which the light has no effect, which is what lets it be culled
+	pub range: Real,
+	pub color: Color,
+	pub intensity: Real,
+}
+
+impl Light {
+	pub fn point(position: Vector3, range: Real, color: Color, intensity: Real) -> Self {
+		Self {
+			kind: LightKind::Point,
+			position,
+			range,
+			color,
+			intensity,
+		}
+	}
+
+	/// A spot light reaching 30 degrees from its direction, fading out from 22.5 degrees
+	pub fn spot(position: Vector3, direction: Vector3, range: Real, color: Color, intensity: Real) -> Self {
+		Self {
+			kind: LightKind::Spot {
+				direction: direction.normalize(),
+				inner_angle: std::f32::consts::FRAC_PI_8,
+				outer_angle: std::f32::consts::FRAC_PI_6,
+			},
+			..Self::point(position, range, color, intensity)
+		}
+	}
+
+	/// Sets a spot light's cone, in radians from its direction to where it fades and ends
+	pub fn with_cone(mut self, inner_angle: Real, outer_angle: Real) -> Self {
+		if let LightKind::Spot {
+			inner_angle: inner,
+			outer_angle: outer,
+			..
+		} = &mut self.kind
+		{
+			(*inner, *outer) = (inner_angle, outer_angle);
+		}
+		self
+	}
+
+	pub fn gpu(&self) -> GpuLight {
+		let (direction, cos_inner, cos_outer, kind) = match self.kind {
+			LightKind::Point => (Vector3::zero(), 1.0, 1.0, 0),
+			LightKind::Spot {
+				direction,
+				inner_angle,
+				outer_angle,
+			} => (direction, inner_angle.cos(), outer_angle.cos(), 1),
+		};
+		GpuLight {
+			position: [self.position.x(), self.position.y(), self.position.z()],
+			range: self.range,
+			color: [self.color.r, self.color.g, self.color.b],
+			intensity: self.intensity,
+			direction: [direction.x(), direction.y(), direction.z()],
+			cos_outer,
+			cos_inner,
+			kind,
+			padding: [0.0; 2],
+		}
+	}
+}
+
+/// A light as stored in the lights storage buffer
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct GpuLight {
+	pub position: [f32; 3],
+	pub range: f32,
+	pub color: [f32; 3],
+	pub intensity: f32,
+	pub direction: [f32; 3],
+	pub cos_outer: f32,
+	pub cos_inner: f32,
+	pub kind: u32,
+	pub padding: [f32; 2],
+}
+
+/// How finely the view frustum is split into clusters
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClusterConfig {
+	/// Tiles across and down the screen, and slices from the near plane to the far plane
+	pub dimensions: [u32; 3],
+
+	/// Lights past this many in one cluster are left out of it
+	pub max_lights_per_cluster: u32,
+}
+
+impl Default for ClusterConfig {
+	fn default() -> Self {
+		Self {
+			dimensions: [16, 9, 24],
+			max_lights_per_cluster: 128,
+		}
+	}
+}
+
+impl ClusterConfig {
+	pub fn cluster_count(&self) -> u32 {
+		self.dimensions.iter().product()
+	}
+}
+
+/// The camera and cluster layout as the `Clusters` uniform
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct ClusterUniform {
+	pub view: Matrix4,
+	pub dimensions: [u32; 4],
+	pub near: f32,
+	pub far: f32,
+
+	/// The tangent of half the field of view, or half the view's height when orthographic
+	pub half_height: f32,
+	pub aspect_ratio: f32,
+	pub light_count: u32,
+	pub perspective: u32,
+	pub screen_size: [f32; 2],
+}
+
+impl ClusterUniform {
+	/// Depth slices get thicker with distance, so clusters stay roughly cube shaped
+	fn slice_depth(&self, slice: u32) -> Real {
+		self.near * (self.far / self.near).powf(slice as Real / self.dimensions[2] as Real)
+	}
+
+	fn cluster_coordinates(&self, index: u32) -> [u32; 3] {
+		let tiles = self.dimensions[0] * self.dimensions[1];
+		[index % self.dimensions[0], (index % tiles) / self.dimensions[0], index / tiles]
+	}
+
+	/// The view space box around a cluster, matching `cluster_bounds` in the shader
+	fn cluster_bounds(&self, index: u32) -> Aabb {
+		let [x, y, z] = self.cluster_coordinates(index);
+		let tile = |coordinate: u32, count: u32| coordinate as Real / count as Real * 2.0 - 1.0;
+		let (near_depth, far_depth) = (self.slice_depth(z), self.slice_depth(z + 1));
+		let scale = |depth: Real| if self.perspective != 0 { self.half_height * depth } else { self.half_height };
+		let (mut min, mut max) = ([Real::MAX; 2], [Real::MIN; 2]);
+		for depth in [near_depth, far_depth] {
+			for (axis, (coordinate, aspect)) in [(x, self.aspect_ratio), (y, 1.0)].into_iter().enumerate() {
+				let count = self.dimensions[axis];
+				for edge in [tile(coordinate, count), tile(coordinate + 1, count)] {
+					let value = edge * aspect * scale(depth);
+					min[axis] = min[axis].min(value);
+					max[axis] = max[axis].max(value);
+				}
+			}
+		}
+		Aabb {
+			min: Vector3::new(min[0], min[1], -far_depth),
+			max: Vector3::new(max[0], max[1], -near_depth),
+		}
+	}
+}
+
+/// The buffers the light culling pass writes, which the lighting pass then binds read only
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClusterBuffers {
+	pub lights: BufferId,
+
+	/// How many lights each cluster has
+	pub light_counts: BufferId,
+
+	/// Each cluster's light indices, starting at the cluster's index times `max_lights_per_cluster`
+	pub light_indices: BufferId,
+}
+
+/// Assigns lights to the clusters of a camera's view, so fragments only loop over nearby lights.
+/// This is what lets scenes use hundreds of point and spot lights with forward rendering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightClusters {
+	pub config: ClusterConfig,
+	uniform: ClusterUniform,
+	lights: Vec<GpuLight>,
+}
+
+impl LightClusters {
+	/// Clusters for a camera rendering to a target of `screen_size` pixels
+	pub fn new(config: ClusterConfig, camera: &Camera, screen_size: (u32, u32), lights: &[Light]) -> Self {
+		let (half_height, near, far, perspective) = match camera.projection {
+			Projection::Perspective { fov_y, near, far } => ((fov_y * 0.5).tan(), near, far, 1),
+			Projection::Orthographic { height, near, far } => (height * 0.5, near, far, 0),
+		};
+		let (width, height) = (screen_size.0.max(1) as f32, screen_size.1.max(1) as f32);
+		let [x, y, z] = config.dimensions.map(|dimension| dimension.max(1));
+		let uniform = ClusterUniform {
+			view: camera.view_matrix(),
+			dimensions: [x, y, z, config.max_lights_per_cluster],
+			near: near.max(Real::EPSILON),
+			far,
+			half_height,
+			aspect_ratio: width / height,
+			light_count: lights.len() as u32,
+			perspective,
+			screen_size: [width, height],
+		};
+		Self {
+			config: ClusterConfig { dimensions: [x, y, z], ..config },
+			uniform,
+			lights: lights.iter().map(Light::gpu).collect(),
+		}
+	}
+
+	pub fn uniform(&self) -> &ClusterUniform {
+		&self.uniform
+	}
+
+	/// Records the light culling dispatch, returning the buffers to bind for the lighting pass
+	pub fn record(&self, pass: &mut ComputePass) -> Result<ClusterBuffers> {
+		let cluster_count = self.config.cluster_count();
+		// Storage buffers can't be empty, so a scene without lights still gets one unused slot
+		let unused = [GpuLight::zeroed()];
+		let buffers = ClusterBuffers {
+			lights: pass.create_buffer_init("lights", if self.lights.is_empty() { &unused } else { &self.lights[..] }),
+			light_counts: pass.create_buffer("cluster light counts", u64::from(cluster_count) * 4),
+			light_indices: pass.create_buffer(
+				"cluster light indices",
+				u64::from(cluster_count) * u64::from(self.config.max_lights_per_cluster.max(1)) * 4,
+			),
+		};
+		pass.dispatch(
+			ComputeDispatch::new("light_culling.wgsl", "assign_lights")
+				.uniform(0, 0, &self.uniform)
+				.storage_read(0, 1, buffers.lights)
+				.storage(0, 2, buffers.light_counts)
+				.storage(0, 3, buffers.light_indices)
+				.workgroups(workgroup_count(cluster_count, LIGHT_CULLING_WORKGROUP_SIZE), 1, 1),
+		)?;
+		Ok(buffers)
+	}
+
+	/// The light counts and indices for every cluster, computed the same way as the compute shader
+	pub fn assign(&self) -> (Vec<u32>, Vec<u32>) {
+		assign_lights(&self.uniform, &self.lights)
+	}
+
+	/// The lights reaching a fragment, given its pixel position and its depth from the camera
+	pub fn lights_at(&self, pixel: (Real, Real), depth: Real, assignment: &(Vec<u32>, Vec<u32>)) -> Vec<u32> {
+		let uniform = &self.uniform;
+		let [x, y, z, capacity] = uniform.dimensions;
+		let tile = |position: Real, size: Real, count: u32| ((position / size * count as Real).max(0.0) as u32).min(count - 1);
+		let slice = ((depth.max(uniform.near) / uniform.near).ln() / (uniform.far / uniform.near).ln() * z as Real).max(0.0) as u32;
+		let (column, row) = (tile(pixel.0, uniform.screen_size[0], x), y - 1 - tile(pixel.1, uniform.screen_size[1], y));
+		let index = (column + (row + slice.min(z - 1) * y) * x) as usize;
+		let (counts, indices) = assignment;
+		let first = index * capacity as usize;
+		indices[first..first + counts[index] as usize].to_vec()
+	}
+
+	/// Runs `assign_lights` on the CPU, for headless runs and for checking the GPU's results
+	pub fn register_cpu_kernel(backend: &mut CpuCompute) {
+		backend.register("light_culling.wgsl", "assign_lights", |dispatch, memory| {
+			let Some(BindingResource::Uniform(uniform)) = dispatch.resource(0, 0) else {
+				return Err(ComputeError::ExecutePass("the clusters uniform is not bound".to_string(), dispatch.shader.clone()));
+			};
+			let uniform: ClusterUniform = bytemuck::pod_read_unaligned(uniform);
+			let buffer = |binding| {
+				dispatch
+					.buffer(0, binding)
+					.ok_or_else(|| ComputeError::ExecutePass(format!("binding {binding} is not a storage buffer"), dispatch.shader.clone()))
+			};
+			let lights = memory.read::<GpuLight>(buffer(1)?)?;
+			let (counts, indices) = assign_lights(&uniform, &lights[..uniform.light_count as usize]);
+			memory.write(buffer(2)?, &counts)?;
+			memory.write(buffer(3)?, &indices)
+		});
+	}
+}
+
+fn assign_lights(uniform: &ClusterUniform, lights: &[GpuLight]) -> (Vec<u32>, Vec<u32>) {
+	let cluster_count = uniform.dimensions[..3].iter().product::<u32>() as usize;
+	let capacity = uniform.dimensions[3] as usize;
+	let centers = lights
+		.iter()
+		.map(|light| {
+			let [x, y, z] = light.position;
+			transform_point(&uniform.view, Vector3::new(x, y, z))
+		})
+		.collect::<Vec<_>>();
+	let (mut counts, mut indices) = (vec![0; cluster_count], vec![0; cluster_count * capacity]);
+	for (cluster, count) in counts.iter_mut().enumerate() {
+		let bounds = uniform.cluster_bounds(cluster as u32);
+		let touching = lights.iter().zip(centers.iter()).enumerate().filter(|(_, (light, center))| {
+			let closest = Vector3::new(
+				center.x().clamp(bounds.min.x(), bounds.max.x()),
+				center.y().clamp(bounds.min.y(), bounds.max.y()),
+				center.z().clamp(bounds.min.z(), bounds.max.z()),
+			);
+			(closest - **center).magnitude_squared() <= light.range * light.range
+		});
+		for (index, _) in touching.take(capacity) {
+			indices[cluster * capacity + *count as usize] = index as u32;
+			*count += 1;
+		}
+	}
+	(counts, indices)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{compute::ComputeBackend, shader::ShaderLibrary};
+	use math::Quaternion;
+
+	fn camera() -> Camera {
+		Camera::new(Vector3::new(0.0, 2.0, 10.0), Quaternion::default(), Projection::default())
+	}
+
+	/// A row of small lights down the middle of the view, from near the camera to far away
+	fn lights() -> Vec<Light> {
+		(0..200)
+			.map(|index| {
+				let position = Vector3::new((index % 10) as Real - 4.5, 2.0, 9.0 - index as Real * 0.5);
+				match index % 2 {
+					0 => Light::point(position, 1.0, Color::WHITE, 1.0),
+					_ => Light::spot(position, Vector3::new(0.0, -1.0, 0.0), 1.0, Color::RED, 2.0).with_cone(0.3, 0.5),
+				}
+			})
+			.collect()
+	}
+
+	#[test]
+	pub fn clusters_only_hold_nearby_lights() {
+		let lights = lights();
+		let clusters = LightClusters::new(ClusterConfig::default(), &camera(), (1600, 900), &lights);
+		let assignment = clusters.assign();
+		assert_eq!(assignment.0.len(), 16 * 9 * 24);
+		let total = assignment.0.iter().sum::<u32>();
+		assert!(total > 200 && (total as usize) < lights.len() * 16, "{total}");
+
+		// A fragment at the center of the screen 10 units away is in the slice from 10 to
+		// about 14.7 units, so it only sees the lights reaching that slab
+		let nearby = clusters.lights_at((800.0, 450.0), 10.0, &assignment);
+		assert!(!nearby.is_empty() && nearby.len() < 20, "{nearby:?}");
+		for index in nearby.iter() {
+			let depth = 10.0 - lights[*index as usize].position.z();
+			assert!((9.0..15.7).contains(&depth), "{depth}");
+		}
+
+		// Every light reaching the fragment's position is in its cluster
+		let position = Vector3::new(0.0, 2.0, 0.0);
+		for (index, light) in lights.iter().enumerate() {
+			if (light.position - position).magnitude() < light.range {
+				assert!(nearby.contains(&(index as u32)), "light {index} is missing");
+			}
+		}
+	}
+
+	#[test]
+	pub fn clusters_are_capped() {
+		let lights = (0..10)
+			.map(|_| Light::point(Vector3::new(0.0, 2.0, 0.0), 100.0, Color::WHITE, 1.0))
+			.collect::<Vec<_>>();
+		let config = ClusterConfig {
+			dimensions: [4, 4, 4],
+			max_lights_per_cluster: 3,
+		};
+		let clusters = LightClusters::new(config, &camera(), (64, 64), &lights);
+		let (counts, indices) = clusters.assign();
+		assert!(counts.iter().all(|count| *count == 3));
+		assert_eq!(&indices[..3], &[0, 1, 2]);
+	}
+
+	#[test]
+	pub fn cpu_kernel() -> Result<()> {
+		let lights = lights();
+		let clusters = LightClusters::new(ClusterConfig::default(), &camera(), (1280, 720), &lights);
+		let mut pass = ComputePass::new("light culling");
+		let buffers = clusters.record(&mut pass)?;
+		let (counts, indices) = (pass.read_buffer(buffers.light_counts)?, pass.read_buffer(buffers.light_indices)?);
+		let mut backend = CpuCompute::new();
+		LightClusters::register_cpu_kernel(&mut backend);
+		let results = backend.execute(&pass)?;
+		assert_eq!((results.read::<u32>(counts)?, results.read::<u32>(indices)?), clusters.assign());
+
+		let empty = LightClusters::new(ClusterConfig::default(), &camera(), (1280, 720), &[]);
+		let mut pass = ComputePass::new("no lights");
+		let buffers = empty.record(&mut pass)?;
+		let counts = pass.read_buffer(buffers.light_counts)?;
+		assert!(backend.execute(&pass)?.read::<u32>(counts)?.iter().all(|count| *count == 0));
+		Ok(())
+	}
+
+	#[test]
+	pub fn shaders_are_valid() {
+		let library = ShaderLibrary::default();
+		let lighting = library.preprocess("clustered_lighting.wgsl", &Default::default()).unwrap();
+		let fragment = "@fragment\nfn main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {\n\treturn vec4<f32>(clustered_lighting(vec3<f32>(0.0), vec3<f32>(0.0, 1.0, 0.0), position.xy), 1.0);\n}\n";
+		for source in [library.preprocess("light_culling.wgsl", &Default::default()).unwrap(), lighting + fragment] {
+			let module = naga::front::wgsl::parse_str(&source).unwrap();
+			naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+				.validate(&module)
+				.unwrap();
+		}
+	}
+}
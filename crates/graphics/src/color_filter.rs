@@ -0,0 +1,201 @@
+use math::Real;
+use serde::{Deserialize, Serialize};
+
+type Matrix3 = [[Real; 3]; 3];
+
+const IDENTITY: Matrix3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Machado, Oliveira, and Fernandes' full severity simulations in linear RGB, as rows
+const PROTANOPIA: Matrix3 = [[0.152286, 1.052583, -0.204868], [0.114503, 0.786281, 0.099216], [-0.003882, -0.048116, 1.051998]];
+const DEUTERANOPIA: Matrix3 = [[0.367322, 0.860646, -0.227968], [0.280085, 0.672501, 0.047413], [-0.011820, 0.042940, 0.968881]];
+const TRITANOPIA: Matrix3 = [[1.255528, -0.076749, -0.178779], [-0.078411, 0.930809, 0.147602], [0.004733, 0.691367, 0.303900]];
+
+/// Moves the color a red-green deficiency loses into green and blue, which it can still tell apart
+const RED_GREEN_SHIFT: Matrix3 = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+/// Moves the color a blue-yellow deficiency loses into red and green
+const BLUE_YELLOW_SHIFT: Matrix3 = [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]];
+
+/// A kind of color blindness, where one of the three cone types in the eye is missing
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorVisionDeficiency {
+	/// No red cones, so reds look dark and are confused with greens
+	Protanopia,
+
+	/// No green cones, the most common kind, where reds and greens are confused
+	Deuteranopia,
+
+	/// No blue cones, where blues are confused with greens and yellows with pinks
+	Tritanopia,
+}
+
+impl ColorVisionDeficiency {
+	pub const ALL: [Self; 3] = [Self::Protanopia, Self::Deuteranopia, Self::Tritanopia];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Self::Protanopia => "Protanopia",
+			Self::Deuteranopia => "Deuteranopia",
+			Self::Tritanopia => "Tritanopia",
+		}
+	}
+
+	fn simulation(&self) -> Matrix3 {
+		match self {
+			Self::Protanopia => PROTANOPIA,
+			Self::Deuteranopia => DEUTERANOPIA,
+			Self::Tritanopia => TRITANOPIA,
+		}
+	}
+
+	fn error_shift(&self) -> Matrix3 {
+		match self {
+			Self::Protanopia | Self::Deuteranopia => RED_GREEN_SHIFT,
+			Self::Tritanopia => BLUE_YELLOW_SHIFT,
+		}
+	}
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ColorFilterMode {
+	/// Shows the screen as a player with the deficiency sees it, to check a game stays readable
+	Simulate,
+
+	/// Shifts colors the player can't tell apart into ones they can, known as daltonization
+	#[default]
+	Correct,
+}
+
+/// A color blindness filter the tonemap pass applies after tonemapping and before sRGB encoding.
+/// The default filters nothing.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorFilter {
+	pub deficiency: Option<ColorVisionDeficiency>,
+	pub mode: ColorFilterMode,
+
+	/// How much of the filter to apply, from 0 for none to 1 for the full simulation or correction
+	pub strength: Real,
+}
+
+impl Default for ColorFilter {
+	fn default() -> Self {
+		Self {
+			deficiency: None,
+			mode: ColorFilterMode::default(),
+			strength: 1.0,
+		}
+	}
+}
+
+impl ColorFilter {
+	pub fn simulate(deficiency: ColorVisionDeficiency) -> Self {
+		Self {
+			deficiency: Some(deficiency),
+			mode: ColorFilterMode::Simulate,
+			..Default::default()
+		}
+	}
+
+	pub fn correct(deficiency: ColorVisionDeficiency) -> Self {
+		Self {
+			deficiency: Some(deficiency),
+			mode: ColorFilterMode::Correct,
+			..Default::default()
+		}
+	}
+
+	pub fn is_enabled(&self) -> bool {
+		self.deficiency.is_some() && self.strength > 0.0
+	}
+
+	/// The filter as a single linear transform, with each row giving an output channel
+	pub fn matrix(&self) -> Matrix3 {
+		let Some(deficiency) = self.deficiency.filter(|_| self.strength > 0.0) else {
+			return IDENTITY;
+		};
+		let simulation = deficiency.simulation();
+		let full = match self.mode {
+			ColorFilterMode::Simulate => simulation,
+			ColorFilterMode::Correct => {
+				// The color plus the shifted difference between what should be seen and what is
+				let error = subtract(IDENTITY, simulation);
+				add(IDENTITY, multiply(deficiency.error_shift(), error))
+			},
+		};
+		let strength = self.strength.min(1.0);
+		std::array::from_fn(|row| std::array::from_fn(|column| IDENTITY[row][column] + (full[row][column] - IDENTITY[row][column]) * strength))
+	}
+
+	/// Filters a linear color on the CPU as the tonemap pass does, clamped to the displayable range
+	pub fn apply(&self, color: [Real; 3]) -> [Real; 3] {
+		if !self.is_enabled() {
+			return color;
+		}
+		self.matrix().map(|row| (row[0] * color[0] + row[1] * color[1] + row[2] * color[2]).clamp(0.0, 1.0))
+	}
+
+	/// The matrix as the columns of a WGSL `mat3x3<f32>`, each padded to four floats
+	pub fn uniform(&self) -> [[f32; 4]; 3] {
+		let matrix = self.matrix();
+		std::array::from_fn(|column| [matrix[0][column], matrix[1][column], matrix[2][column], 0.0])
+	}
+}
+
+fn multiply(a: Matrix3, b: Matrix3) -> Matrix3 {
+	std::array::from_fn(|row| std::array::from_fn(|column| (0..3).map(|index| a[row][index] * b[index][column]).sum()))
+}
+
+fn add(a: Matrix3, b: Matrix3) -> Matrix3 {
+	std::array::from_fn(|row| std::array::from_fn(|column| a[row][column] + b[row][column]))
+}
+
+fn subtract(a: Matrix3, b: Matrix3) -> Matrix3 {
+	std::array::from_fn(|row| std::array::from_fn(|column| a[row][column] - b[row][column]))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn simulation_and_correction() {
+		let (red, green) = ([0.8, 0.1, 0.1], [0.1, 0.6, 0.1]);
+		assert_eq!(ColorFilter::default().apply(red), red);
+
+		// Red and green look much alike without green cones
+		let simulate = ColorFilter::simulate(ColorVisionDeficiency::Deuteranopia);
+		let distance = |a: [Real; 3], b: [Real; 3]| (0..3).map(|channel| (a[channel] - b[channel]).powi(2)).sum::<Real>().sqrt();
+		let seen = (simulate.apply(red), simulate.apply(green));
+		assert!(distance(seen.0, seen.1) < distance(red, green));
+
+		// Correcting first keeps them further apart once the deficiency is simulated
+		let correct = ColorFilter::correct(ColorVisionDeficiency::Deuteranopia);
+		let corrected = (simulate.apply(correct.apply(red)), simulate.apply(correct.apply(green)));
+		assert!(distance(corrected.0, corrected.1) > distance(seen.0, seen.1));
+
+		// Every filter keeps greys grey
+		for deficiency in ColorVisionDeficiency::ALL {
+			for filter in [ColorFilter::simulate(deficiency), ColorFilter::correct(deficiency)] {
+				let [r, g, b] = filter.apply([0.5; 3]);
+				assert!((r - 0.5).abs() < 1e-2 && (g - 0.5).abs() < 1e-2 && (b - 0.5).abs() < 1e-2, "{filter:?}");
+			}
+		}
+	}
+
+	#[test]
+	pub fn strength() {
+		let none = ColorFilter {
+			strength: 0.0,
+			..ColorFilter::simulate(ColorVisionDeficiency::Protanopia)
+		};
+		assert_eq!(none.matrix(), IDENTITY);
+		let half = ColorFilter {
+			strength: 0.5,
+			..ColorFilter::simulate(ColorVisionDeficiency::Protanopia)
+		};
+		assert!((half.matrix()[0][0] - (1.0 + PROTANOPIA[0][0]) / 2.0).abs() < 1e-6);
+		assert_eq!(ColorFilter::default().uniform(), [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0]]);
+		assert_eq!(ColorFilter::simulate(ColorVisionDeficiency::Tritanopia).uniform()[1][0], TRITANOPIA[0][1]);
+	}
+}
@@ -0,0 +1,486 @@
+use crate::shader::ShaderDefines;
+use bytemuck::Pod;
+use handle::{Handle, SlotMap};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ComputeError {
+	#[error("Failed to write {size} bytes at offset {offset} to storage buffer '{label}' of {capacity} bytes")]
+	BufferOutOfRange { label: String, offset: u64, size: u64, capacity: u64 },
+
+	#[error("Failed to dispatch '{0}' with zero workgroups")]
+	EmptyDispatch(String),
+
+	#[error("Failed to execute compute pass '{1}': {0}")]
+	ExecutePass(String, String),
+
+	#[error("Failed to read back {0} bytes as a slice of {1}-byte values")]
+	MisalignedReadback(usize, usize),
+
+	#[error("Failed to find compute kernel '{1}' in shader '{0}'")]
+	MissingKernel(String, String),
+
+	#[error("Failed to find readback {0:?}")]
+	MissingReadback(ReadbackId),
+
+	#[error("Failed to find storage buffer {0:?}")]
+	UnknownBuffer(BufferId),
+
+	#[error("Failed to find storage texture {0:?}")]
+	UnknownTexture(TextureId),
+}
+
+type Result<T, E = ComputeError> = std::result::Result<T, E>;
+
+/// Refers to a storage buffer created in a `ComputePass`
+pub type BufferId = Handle<StorageBufferDescriptor>;
+
+/// Refers to a storage texture created in a `ComputePass`
+pub type TextureId = Handle<StorageTextureDescriptor>;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReadbackId(usize);
+
+/// The number of workgroups needed to cover `items` invocations
+pub fn workgroup_count(items: u32, workgroup_size: u32) -> u32 {
+	items.div_ceil(workgroup_size.max(1))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageBufferDescriptor {
+	pub label: String,
+	pub size: u64,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StorageTextureFormat {
+	#[default]
+	Rgba8Unorm,
+	Rgba16Float,
+	Rgba32Float,
+	R32Float,
+	R32Uint,
+}
+
+impl StorageTextureFormat {
+	pub fn bytes_per_texel(&self) -> u64 {
+		match self {
+			Self::Rgba8Unorm | Self::R32Float | Self::R32Uint => 4,
+			Self::Rgba16Float => 8,
+			Self::Rgba32Float => 16,
+		}
+	}
+
+	/// The format's name in a WGSL `texture_storage_2d` declaration
+	pub fn wgsl_name(&self) -> &'static str {
+		match self {
+			Self::Rgba8Unorm => "rgba8unorm",
+			Self::Rgba16Float => "rgba16float",
+			Self::Rgba32Float => "rgba32float",
+			Self::R32Float => "r32float",
+			Self::R32Uint => "r32uint",
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageTextureDescriptor {
+	pub label: String,
+	pub width: u32,
+	pub height: u32,
+	pub format: StorageTextureFormat,
+}
+
+impl StorageTextureDescriptor {
+	pub fn size(&self) -> u64 {
+		u64::from(self.width) * u64::from(self.height) * self.format.bytes_per_texel()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BindingResource {
+	/// Uniform data uploaded with the dispatch
+	Uniform(Vec<u8>),
+	StorageBuffer {
+		buffer: BufferId,
+		read_only: bool,
+	},
+	StorageTexture(TextureId),
+
+	/// A storage texture bound for sampling, such as one written by an earlier dispatch
+	Texture(TextureId),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputeBinding {
+	pub group: u32,
+	pub binding: u32,
+	pub resource: BindingResource,
+}
+
+/// One compute shader invocation, built up with its shader, bind groups, and workgroup count
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputeDispatch {
+	/// The shader's name in the `ShaderLibrary`
+	pub shader: String,
+	pub entry_point: String,
+	pub defines: ShaderDefines,
+	pub bindings: Vec<ComputeBinding>,
+	pub workgroups: [u32; 3],
+}
+
+impl ComputeDispatch {
+	pub fn new(shader: impl Into<String>, entry_point: impl Into<String>) -> Self {
+		Self {
+			shader: shader.into(),
+			entry_point: entry_point.into(),
+			defines: ShaderDefines::new(),
+			bindings: Vec::new(),
+			workgroups: [1, 1, 1],
+		}
+	}
+
+	pub fn define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.defines.insert(name.into(), value.into());
+		self
+	}
+
+	pub fn bind(mut self, group: u32, binding: u32, resource: BindingResource) -> Self {
+		self.bindings.retain(|existing| (existing.group, existing.binding) != (group, binding));
+		self.bindings.push(ComputeBinding { group, binding, resource });
+		self
+	}
+
+	pub fn uniform<T: Pod>(self, group: u32, binding: u32, value: &T) -> Self {
+		self.bind(group, binding, BindingResource::Uniform(bytemuck::bytes_of(value).to_vec()))
+	}
+
+	pub fn storage(self, group: u32, binding: u32, buffer: BufferId) -> Self {
+		self.bind(group, binding, BindingResource::StorageBuffer { buffer, read_only: false })
+	}
+
+	pub fn storage_read(self, group: u32, binding: u32, buffer: BufferId) -> Self {
+		self.bind(group, binding, BindingResource::StorageBuffer { buffer, read_only: true })
+	}
+
+	pub fn workgroups(mut self, x: u32, y: u32, z: u32) -> Self {
+		self.workgroups = [x, y, z];
+		self
+	}
+
+	pub fn resource(&self, group: u32, binding: u32) -> Option<&BindingResource> {
+		self.bindings
+			.iter()
+			.find(|existing| (existing.group, existing.binding) == (group, binding))
+			.map(|existing| &existing.resource)
+	}
+
+	/// The storage buffer bound at the given slot
+	pub fn buffer(&self, group: u32, binding: u32) -> Option<BufferId> {
+		match self.resource(group, binding) {
+			Some(BindingResource::StorageBuffer { buffer, .. }) => Some(*buffer),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputeCommand {
+	WriteBuffer { buffer: BufferId, offset: u64, data: Vec<u8> },
+	Dispatch(ComputeDispatch),
+	ReadBuffer { buffer: BufferId, readback: ReadbackId },
+}
+
+/// A recorded list of compute work that the renderer runs before drawing the frame.
+///
+/// Storage resources and commands are validated as they are recorded,
+/// so a backend only has to translate them into GPU calls.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ComputePass {
+	label: String,
+	buffers: SlotMap<StorageBufferDescriptor>,
+	textures: SlotMap<StorageTextureDescriptor>,
+	commands: Vec<ComputeCommand>,
+	readbacks: usize,
+}
+
+impl ComputePass {
+	pub fn new(label: impl Into<String>) -> Self {
+		Self {
+			label: label.into(),
+			..Default::default()
+		}
+	}
+
+	pub fn label(&self) -> &str {
+		&self.label
+	}
+
+	pub fn buffers(&self) -> impl Iterator<Item = (BufferId, &StorageBufferDescriptor)> {
+		self.buffers.iter()
+	}
+
+	pub fn textures(&self) -> impl Iterator<Item = (TextureId, &StorageTextureDescriptor)> {
+		self.textures.iter()
+	}
+
+	pub fn commands(&self) -> &[ComputeCommand] {
+		&self.commands
+	}
+
+	pub fn buffer(&self, id: BufferId) -> Result<&StorageBufferDescriptor> {
+		self.buffers.get(id).ok_or(ComputeError::UnknownBuffer(id))
+	}
+
+	pub fn texture(&self, id: TextureId) -> Result<&StorageTextureDescriptor> {
+		self.textures.get(id).ok_or(ComputeError::UnknownTexture(id))
+	}
+
+	/// Creates a zeroed storage buffer
+	pub fn create_buffer(&mut self, label: impl Into<String>, size: u64) -> BufferId {
+		self.buffers.insert(StorageBufferDescriptor { label: label.into(), size })
+	}
+
+	/// Creates a storage buffer that starts with the given contents
+	pub fn create_buffer_init<T: Pod>(&mut self, label: impl Into<String>, contents: &[T]) -> BufferId {
+		let data = bytemuck::cast_slice::<T, u8>(contents).to_vec();
+		let buffer = self.create_buffer(label, data.len() as u64);
+		self.commands.push(ComputeCommand::WriteBuffer { buffer, offset: 0, data });
+		buffer
+	}
+
+	pub fn create_texture(&mut self, descriptor: StorageTextureDescriptor) -> TextureId {
+		self.textures.insert(descriptor)
+	}
+
+	pub fn write_buffer<T: Pod>(&mut self, buffer: BufferId, offset: u64, contents: &[T]) -> Result<()> {
+		let data = bytemuck::cast_slice::<T, u8>(contents).to_vec();
+		let descriptor = self.buffer(buffer)?;
+		let size = data.len() as u64;
+		if offset.saturating_add(size) > descriptor.size {
+			return Err(ComputeError::BufferOutOfRange {
+				label: descriptor.label.clone(),
+				offset,
+				size,
+				capacity: descriptor.size,
+			});
+		}
+		self.commands.push(ComputeCommand::WriteBuffer { buffer, offset, data });
+		Ok(())
+	}
+
+	pub fn dispatch(&mut self, dispatch: ComputeDispatch) -> Result<()> {
+		if dispatch.workgroups.contains(&0) {
+			return Err(ComputeError::EmptyDispatch(dispatch.entry_point));
+		}
+		for binding in dispatch.bindings.iter() {
+			match &binding.resource {
+				BindingResource::Uniform(_) => {},
+				BindingResource::StorageBuffer { buffer, .. } => {
+					self.buffer(*buffer)?;
+				},
+				BindingResource::StorageTexture(texture) | BindingResource::Texture(texture) => {
+					self.texture(*texture)?;
+				},
+			}
+		}
+		self.commands.push(ComputeCommand::Dispatch(dispatch));
+		Ok(())
+	}
+
+	/// Copies a buffer back to the CPU once the pass has run, returned in the `ComputeResults`
+	pub fn read_buffer(&mut self, buffer: BufferId) -> Result<ReadbackId> {
+		self.buffer(buffer)?;
+		let readback = ReadbackId(self.readbacks);
+		self.readbacks += 1;
+		self.commands.push(ComputeCommand::ReadBuffer { buffer, readback });
+		Ok(readback)
+	}
+}
+
+/// The buffers read back by a compute pass
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ComputeResults {
+	readbacks: BTreeMap<ReadbackId, Vec<u8>>,
+}
+
+impl ComputeResults {
+	pub fn insert(&mut self, readback: ReadbackId, data: Vec<u8>) {
+		self.readbacks.insert(readback, data);
+	}
+
+	pub fn bytes(&self, readback: ReadbackId) -> Result<&[u8]> {
+		self.readbacks.get(&readback).map(Vec::as_slice).ok_or(ComputeError::MissingReadback(readback))
+	}
+
+	pub fn read<T: Pod>(&self, readback: ReadbackId) -> Result<Vec<T>> {
+		let bytes = self.bytes(readback)?;
+		let size = std::mem::size_of::<T>();
+		if size == 0 || bytes.len() % size != 0 {
+			return Err(ComputeError::MisalignedReadback(bytes.len(), size));
+		}
+		Ok(bytemuck::pod_collect_to_vec(bytes))
+	}
+}
+
+/// Runs compute passes, implemented by the GPU renderer and by `CpuCompute` for headless runs
+pub trait ComputeBackend {
+	fn execute(&mut self, pass: &ComputePass) -> Result<ComputeResults>;
+}
+
+/// Storage buffer contents as seen by a CPU kernel
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ComputeMemory {
+	buffers: BTreeMap<BufferId, Vec<u8>>,
+}
+
+impl ComputeMemory {
+	pub fn bytes(&self, buffer: BufferId) -> Result<&[u8]> {
+		self.buffers.get(&buffer).map(Vec::as_slice).ok_or(ComputeError::UnknownBuffer(buffer))
+	}
+
+	pub fn bytes_mut(&mut self, buffer: BufferId) -> Result<&mut [u8]> {
+		self.buffers.get_mut(&buffer).map(Vec::as_mut_slice).ok_or(ComputeError::UnknownBuffer(buffer))
+	}
+
+	pub fn read<T: Pod>(&self, buffer: BufferId) -> Result<Vec<T>> {
+		Ok(bytemuck::pod_collect_to_vec(self.bytes(buffer)?))
+	}
+
+	/// Overwrites the start of the buffer, leaving anything past the written values untouched
+	pub fn write<T: Pod>(&mut self, buffer: BufferId, values: &[T]) -> Result<()> {
+		let data = bytemuck::cast_slice::<T, u8>(values);
+		let bytes = self.bytes_mut(buffer)?;
+		let length = data.len().min(bytes.len());
+		bytes[..length].copy_from_slice(&data[..length]);
+		Ok(())
+	}
+}
+
+type Kernel = Box<dyn FnMut(&ComputeDispatch, &mut ComputeMemory) -> Result<()>>;
+
+/// Runs compute passes on the CPU with Rust implementations of each kernel.
+/// Storage textures are not supported.
+#[derive(Default)]
+pub struct CpuCompute {
+	kernels: HashMap<(String, String), Kernel>,
+}
+
+impl CpuCompute {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers the function run in place of a shader's entry point
+	pub fn register(
+		&mut self,
+		shader: impl Into<String>,
+		entry_point: impl Into<String>,
+		kernel: impl FnMut(&ComputeDispatch, &mut ComputeMemory) -> Result<()> + 'static,
+	) {
+		self.kernels.insert((shader.into(), entry_point.into()), Box::new(kernel));
+	}
+}
+
+impl ComputeBackend for CpuCompute {
+	fn execute(&mut self, pass: &ComputePass) -> Result<ComputeResults> {
+		if !pass.textures.is_empty() {
+			return Err(ComputeError::ExecutePass(
+				"storage textures are not supported on the CPU".to_string(),
+				pass.label.clone(),
+			));
+		}
+		let mut memory = ComputeMemory {
+			buffers: pass.buffers().map(|(buffer, descriptor)| (buffer, vec![0; descriptor.size as usize])).collect(),
+		};
+		let mut results = ComputeResults::default();
+		for command in pass.commands.iter() {
+			match command {
+				ComputeCommand::WriteBuffer { buffer, offset, data } => {
+					let offset = *offset as usize;
+					memory.bytes_mut(*buffer)?[offset..offset + data.len()].copy_from_slice(data);
+				},
+				ComputeCommand::Dispatch(dispatch) => {
+					let kernel = self
+						.kernels
+						.get_mut(&(dispatch.shader.clone(), dispatch.entry_point.clone()))
+						.ok_or_else(|| ComputeError::MissingKernel(dispatch.shader.clone(), dispatch.entry_point.clone()))?;
+					kernel(dispatch, &mut memory)?;
+				},
+				ComputeCommand::ReadBuffer { buffer, readback } => {
+					results.insert(*readback, memory.bytes(*buffer)?.to_vec());
+				},
+			}
+		}
+		Ok(results)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn records_commands() -> Result<()> {
+		let mut pass = ComputePass::new("culling");
+		let input = pass.create_buffer_init("input", &[1.0f32, 2.0, 3.0]);
+		let output = pass.create_buffer("output", 12);
+		assert!(matches!(pass.write_buffer(input, 8, &[1.0f32, 2.0]), Err(ComputeError::BufferOutOfRange { .. })));
+		assert!(matches!(
+			pass.dispatch(ComputeDispatch::new("cull.wgsl", "main").workgroups(0, 1, 1)),
+			Err(ComputeError::EmptyDispatch(_))
+		));
+		let mut other = ComputePass::default();
+		let elsewhere = ["first", "second", "third"].map(|label| other.create_buffer(label, 4))[2];
+		assert!(matches!(
+			pass.dispatch(ComputeDispatch::new("cull.wgsl", "main").storage(0, 0, elsewhere)),
+			Err(ComputeError::UnknownBuffer(buffer)) if buffer == elsewhere
+		));
+		let dispatch = ComputeDispatch::new("cull.wgsl", "main")
+			.storage_read(0, 0, input)
+			.storage(0, 1, input)
+			.storage(0, 1, output)
+			.workgroups(workgroup_count(3, 64), 1, 1);
+		assert_eq!(dispatch.buffer(0, 1), Some(output));
+		assert_eq!(dispatch.bindings.len(), 2);
+		pass.dispatch(dispatch)?;
+		pass.read_buffer(output)?;
+		assert_eq!(pass.commands().len(), 3);
+		assert_eq!(pass.buffers().count(), 2);
+		Ok(())
+	}
+
+	#[test]
+	pub fn cpu_compute() -> Result<()> {
+		let mut pass = ComputePass::new("double");
+		let input = pass.create_buffer_init("input", &[1u32, 2, 3, 4]);
+		let output = pass.create_buffer("output", 16);
+		pass.dispatch(
+			ComputeDispatch::new("double.wgsl", "main")
+				.uniform(0, 2, &2u32)
+				.storage_read(0, 0, input)
+				.storage(0, 1, output),
+		)?;
+		let readback = pass.read_buffer(output)?;
+
+		let mut backend = CpuCompute::new();
+		backend.register("double.wgsl", "main", |dispatch, memory| {
+			let Some(BindingResource::Uniform(factor)) = dispatch.resource(0, 2) else {
+				return Ok(());
+			};
+			let factor: u32 = bytemuck::pod_read_unaligned(factor);
+			let (input, output) = (dispatch.buffer(0, 0).unwrap(), dispatch.buffer(0, 1).unwrap());
+			let values = memory.read::<u32>(input)?.into_iter().map(|value| value * factor).collect::<Vec<_>>();
+			memory.write(output, &values)
+		});
+		let results = backend.execute(&pass)?;
+		assert_eq!(results.read::<u32>(readback)?, vec![2, 4, 6, 8]);
+		assert!(matches!(results.read::<[u8; 3]>(readback), Err(ComputeError::MisalignedReadback(16, 3))));
+
+		let mut missing = ComputePass::new("missing");
+		missing.dispatch(ComputeDispatch::new("missing.wgsl", "main"))?;
+		assert!(matches!(backend.execute(&missing), Err(ComputeError::MissingKernel(..))));
+		Ok(())
+	}
+}
@@ -0,0 +1,49 @@
+use crate::instancing::Matrix4;
+use math::{BoundingSphere, Frustum, Real, Vector3};
+
+/// The camera used to cull and select LODs for a frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderCamera {
+	pub position: Vector3,
+	pub view_projection: Matrix4,
+
+	/// Scales the distances used to pick LOD levels.
+	/// Values above `1.0` switch to simpler meshes sooner, which suits lower quality settings.
+	pub lod_bias: Real,
+}
+
+impl RenderCamera {
+	pub fn new(position: Vector3, view_projection: Matrix4) -> Self {
+		Self {
+			position,
+			view_projection,
+			lod_bias: 1.0,
+		}
+	}
+
+	pub fn frustum(&self) -> Frustum {
+		Frustum::from_view_projection(&self.view_projection)
+	}
+
+	/// The biased distance from the camera to the surface of a world space sphere
+	pub fn lod_distance(&self, sphere: &BoundingSphere) -> Real {
+		((sphere.center - self.position).magnitude() - sphere.radius).max(0.0) * self.lod_bias
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::instancing::IDENTITY;
+
+	#[test]
+	pub fn lod_distance() {
+		let mut camera = RenderCamera::new(Vector3::zero(), IDENTITY);
+		let sphere = BoundingSphere::new(Vector3::new(0.0, 0.0, 10.0), 2.0);
+		assert_eq!(camera.lod_distance(&sphere), 8.0);
+		camera.lod_bias = 2.0;
+		assert_eq!(camera.lod_distance(&sphere), 16.0);
+		assert_eq!(camera.lod_distance(&BoundingSphere::new(Vector3::zero(), 1.0)), 0.0);
+		assert!(camera.frustum().intersects_sphere(&BoundingSphere::new(Vector3::new(0.0, 0.0, 0.5), 0.1)));
+	}
+}
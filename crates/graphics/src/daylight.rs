@@ -0,0 +1,220 @@
+use crate::environment::EnvironmentLighting;
+use math::{Color, Real, Vector3};
+use std::f32::consts::TAU;
+
+pub const HOURS_PER_DAY: Real = 24.0;
+pub const DAYS_PER_YEAR: u32 = 365;
+
+/// The tilt of the earth's axis, which moves the sun north and south over the year
+const AXIAL_TILT: Real = 23.44;
+
+const NIGHT_SKY: Color = Color::rgb(0.04, 0.06, 0.14);
+const TWILIGHT_SKY: Color = Color::rgb(0.9, 0.5, 0.35);
+const HORIZON_SUN: Color = Color::rgb(1.0, 0.45, 0.15);
+const NOON_SUN: Color = Color::rgb(1.0, 0.96, 0.9);
+const MOONLIGHT: Color = Color::rgb(0.55, 0.65, 0.9);
+const MOON_INTENSITY: Real = 0.08;
+const NIGHT_AMBIENT: Real = 0.05;
+
+/// A light shining from infinitely far away, such as the sun or the moon
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DirectionalLight {
+	/// The direction the light travels, pointing away from the sun
+	pub direction: Vector3,
+	pub color: Color,
+	pub intensity: Real,
+}
+
+/// The unit direction from the ground toward the sun, with +y up, -z north, and +x east.
+///
+/// Hours are local solar time, so the sun is highest at 12. The day of the year runs from 1 to 365,
+/// and the latitude is in degrees, positive in the northern hemisphere.
+pub fn sun_direction(hours: Real, day_of_year: u32, latitude: Real) -> Vector3 {
+	let declination = (-AXIAL_TILT * ((day_of_year as Real + 10.0) * TAU / DAYS_PER_YEAR as Real).cos()).to_radians();
+	let hour_angle = (hours - 12.0) * TAU / HOURS_PER_DAY;
+	let latitude = latitude.to_radians();
+	let east = -declination.cos() * hour_angle.sin();
+	let north = declination.sin() * latitude.cos() - declination.cos() * hour_angle.cos() * latitude.sin();
+	let up = declination.sin() * latitude.sin() + declination.cos() * hour_angle.cos() * latitude.cos();
+	Vector3::new(east, up, -north).normalize()
+}
+
+/// The sun or moon light, sky tint, and ambient light for a sun position, stored as a resource
+/// while a `TimeOfDay` is present. Renderers light the scene with `light` and tint the skybox.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Daylight {
+	/// The sun while it's up, then the moon opposite it, crossing over while both are dark
+	pub light: DirectionalLight,
+
+	/// From dark blue at night through orange at dawn and dusk to white during the day
+	pub sky_tint: Color,
+
+	/// How bright the image-based ambient light is, from 0 to 1
+	pub ambient_intensity: Real,
+
+	/// The sun's angle above the horizon in degrees, which is negative at night
+	pub sun_elevation: Real,
+}
+
+impl Daylight {
+	pub fn new(sun_direction: Vector3) -> Self {
+		let elevation = sun_direction.y().clamp(-1.0, 1.0).asin().to_degrees();
+		let light = if elevation > -2.0 {
+			DirectionalLight {
+				direction: sun_direction.inverse(),
+				color: HORIZON_SUN.lerp(&NOON_SUN, smoothstep(0.0, 30.0, elevation)),
+				intensity: smoothstep(-2.0, 10.0, elevation),
+			}
+		} else {
+			DirectionalLight {
+				direction: sun_direction,
+				color: MOONLIGHT,
+				intensity: MOON_INTENSITY * smoothstep(-2.0, -12.0, elevation),
+			}
+		};
+		let sky_tint = match elevation < 0.0 {
+			true => NIGHT_SKY.lerp(&TWILIGHT_SKY, smoothstep(-12.0, 0.0, elevation)),
+			false => TWILIGHT_SKY.lerp(&Color::WHITE, smoothstep(0.0, 20.0, elevation)),
+		};
+		Self {
+			light,
+			sky_tint,
+			ambient_intensity: NIGHT_AMBIENT + (1.0 - NIGHT_AMBIENT) * smoothstep(-12.0, 20.0, elevation),
+			sun_elevation: elevation,
+		}
+	}
+
+	pub fn is_night(&self) -> bool {
+		self.sun_elevation < -6.0
+	}
+
+	/// Dims the image-based lighting to match
+	pub fn apply(&self, environment: &mut EnvironmentLighting) {
+		environment.intensity = self.ambient_intensity;
+	}
+}
+
+/// The time of day and where on earth the scene is, stored as a resource.
+/// The app advances it by gameplay time and stores the resulting `Daylight` every frame,
+/// and gameplay can set the hour directly, such as to skip to morning after sleeping.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimeOfDay {
+	/// Local solar time, from 0 up to 24
+	pub hours: Real,
+
+	/// From 1 to 365, which moves the sun higher in summer and lower in winter
+	pub day_of_year: u32,
+
+	/// In degrees, positive in the northern hemisphere
+	pub latitude: Real,
+
+	/// Real seconds a full day takes, such as 1200 for a twenty minute day
+	pub day_length: Real,
+	pub is_paused: bool,
+}
+
+impl Default for TimeOfDay {
+	fn default() -> Self {
+		Self {
+			hours: 12.0,
+			day_of_year: 172,
+			latitude: 45.0,
+			day_length: 1200.0,
+			is_paused: false,
+		}
+	}
+}
+
+impl TimeOfDay {
+	/// Moves the clock forward, rolling over into the next day and year
+	pub fn advance(&mut self, seconds: Real) {
+		if self.is_paused || self.day_length <= 0.0 {
+			return;
+		}
+		self.hours += seconds * HOURS_PER_DAY / self.day_length;
+		while self.hours >= HOURS_PER_DAY {
+			self.hours -= HOURS_PER_DAY;
+			self.day_of_year = self.day_of_year % DAYS_PER_YEAR + 1;
+		}
+	}
+
+	/// Sets the hour, wrapped into the day without changing the date
+	pub fn set_hours(&mut self, hours: Real) {
+		self.hours = hours.rem_euclid(HOURS_PER_DAY);
+	}
+
+	pub fn sun_direction(&self) -> Vector3 {
+		sun_direction(self.hours, self.day_of_year, self.latitude)
+	}
+
+	pub fn daylight(&self) -> Daylight {
+		Daylight::new(self.sun_direction())
+	}
+}
+
+/// Eases from 0 at `edge0` to 1 at `edge1`, which can be in either order
+fn smoothstep(edge0: Real, edge1: Real, x: Real) -> Real {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn approx_eq(a: Real, b: Real, tolerance: Real) -> bool {
+		(a - b).abs() <= tolerance
+	}
+
+	#[test]
+	pub fn sun_positions() {
+		// At the equator on an equinox the sun rises in the east, passes overhead, and sets west
+		let equinox = 80;
+		assert!(approx_eq(sun_direction(12.0, equinox, 0.0).y(), 1.0, 0.01));
+		assert!(sun_direction(6.5, equinox, 0.0).x() > 0.9);
+		assert!(sun_direction(17.5, equinox, 0.0).x() < -0.9);
+		assert!(sun_direction(0.0, equinox, 0.0).y() < -0.99);
+
+		// The northern summer sun is higher than the winter sun, and due south at noon
+		let (summer, winter) = (sun_direction(12.0, 172, 45.0), sun_direction(12.0, 355, 45.0));
+		assert!(approx_eq(summer.y().asin().to_degrees(), 68.44, 0.1));
+		assert!(approx_eq(winter.y().asin().to_degrees(), 21.56, 0.1));
+		assert!(summer.z() > 0.0 && approx_eq(summer.x(), 0.0, 0.001));
+	}
+
+	#[test]
+	pub fn daylight_through_the_day() {
+		let mut time = TimeOfDay::default();
+		let noon = time.daylight();
+		assert!(noon.light.intensity > 0.99 && noon.light.direction.y() < 0.0);
+		assert_eq!(noon.sky_tint, Color::WHITE);
+		assert!(!noon.is_night());
+
+		time.set_hours(-1.0);
+		assert_eq!(time.hours, 23.0);
+		let night = time.daylight();
+		assert!(night.is_night());
+		assert_eq!(night.light.color, MOONLIGHT);
+		assert!(night.light.intensity <= MOON_INTENSITY && night.light.direction.y() < 0.0);
+		assert!(night.ambient_intensity < noon.ambient_intensity);
+
+		let mut environment = EnvironmentLighting::new(crate::Cubemap::new(4, |_| [1.0; 3]));
+		night.apply(&mut environment);
+		assert_eq!(environment.intensity, night.ambient_intensity);
+	}
+
+	#[test]
+	pub fn clock() {
+		let mut time = TimeOfDay {
+			hours: 23.0,
+			day_of_year: DAYS_PER_YEAR,
+			day_length: 240.0,
+			..Default::default()
+		};
+		time.advance(20.0);
+		assert_eq!((time.hours, time.day_of_year), (1.0, 1));
+		time.is_paused = true;
+		time.advance(20.0);
+		assert_eq!(time.hours, 1.0);
+	}
+}
@@ -0,0 +1,268 @@
+use bytemuck::{Pod, Zeroable};
+use math::{Color, Real, Vector3};
+use memory::{MemoryTag, TrackedBytes};
+
+/// Draws the vertex colors of debug lines and triangles with the camera's view projection
+pub const DEBUG_DRAW_SHADER: &str = include_str!("shaders/debug_draw.wgsl");
+
+/// The smallest vertex buffer debug draws allocate, in vertices
+pub const MIN_DEBUG_VERTEX_CAPACITY: usize = 1024;
+
+const CIRCLE_SEGMENTS: usize = 24;
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct DebugVertex {
+	pub position: [f32; 3],
+	pub color: [f32; 4],
+}
+
+impl DebugVertex {
+	pub fn new(position: Vector3, color: Color) -> Self {
+		Self {
+			position: [position.x(), position.y(), position.z()],
+			color: [color.r, color.g, color.b, color.a],
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DebugPrimitive {
+	Lines,
+	Triangles,
+}
+
+/// A draw call covering a contiguous range of the debug vertex buffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DebugDrawCall {
+	pub primitive: DebugPrimitive,
+
+	/// Overlay draws skip the depth test so they show through geometry
+	pub depth_test: bool,
+	pub first_vertex: u32,
+	pub vertex_count: u32,
+}
+
+/// Lines and triangles queued for a single frame, stored as a resource.
+/// Everything is batched into one vertex buffer drawn with at most four draw calls,
+/// one per primitive with and without the depth test. The app clears it after each frame.
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+	/// Whether shapes queued from now on are hidden behind geometry
+	pub depth_test: bool,
+
+	/// The vertices queued for each of `BUCKETS`
+	buckets: [Vec<DebugVertex>; 4],
+	vertices: Vec<DebugVertex>,
+	draw_calls: Vec<DebugDrawCall>,
+	capacity: usize,
+
+	/// The queued vertices counted under the "debug draw" memory tag
+	memory: TrackedBytes,
+}
+
+impl Default for DebugDraw {
+	fn default() -> Self {
+		Self {
+			depth_test: true,
+			buckets: Default::default(),
+			vertices: Vec::new(),
+			draw_calls: Vec::new(),
+			capacity: MIN_DEBUG_VERTEX_CAPACITY,
+			memory: TrackedBytes::new(MemoryTag::new("debug draw")),
+		}
+	}
+}
+
+impl DebugDraw {
+	const BUCKETS: [(DebugPrimitive, bool); 4] = [
+		(DebugPrimitive::Lines, true),
+		(DebugPrimitive::Triangles, true),
+		(DebugPrimitive::Lines, false),
+		(DebugPrimitive::Triangles, false),
+	];
+
+	fn bucket(&mut self, primitive: DebugPrimitive) -> &mut Vec<DebugVertex> {
+		let index = Self::BUCKETS.iter().position(|bucket| *bucket == (primitive, self.depth_test)).unwrap_or_default();
+		&mut self.buckets[index]
+	}
+
+	pub fn line(&mut self, start: Vector3, end: Vector3, color: Color) {
+		self.bucket(DebugPrimitive::Lines)
+			.extend([DebugVertex::new(start, color), DebugVertex::new(end, color)]);
+	}
+
+	/// A line whose color blends from one end to the other
+	pub fn gradient_line(&mut self, start: Vector3, end: Vector3, start_color: Color, end_color: Color) {
+		self.bucket(DebugPrimitive::Lines)
+			.extend([DebugVertex::new(start, start_color), DebugVertex::new(end, end_color)]);
+	}
+
+	pub fn ray(&mut self, origin: Vector3, direction: Vector3, color: Color) {
+		self.line(origin, origin + direction, color);
+	}
+
+	pub fn triangle(&mut self, a: Vector3, b: Vector3, c: Vector3, color: Color) {
+		self.bucket(DebugPrimitive::Triangles).extend([a, b, c].map(|point| DebugVertex::new(point, color)));
+	}
+
+	/// The edges of an axis-aligned box
+	pub fn cuboid(&mut self, min: Vector3, max: Vector3, color: Color) {
+		let corner = |index: usize| {
+			Vector3::new(
+				if index & 1 == 0 { min.x() } else { max.x() },
+				if index & 2 == 0 { min.y() } else { max.y() },
+				if index & 4 == 0 { min.z() } else { max.z() },
+			)
+		};
+		for (a, b) in [(0, 1), (2, 3), (4, 5), (6, 7), (0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7)] {
+			self.line(corner(a), corner(b), color);
+		}
+	}
+
+	/// A circle facing along `normal`
+	pub fn circle(&mut self, center: Vector3, normal: Vector3, radius: Real, color: Color) {
+		let normal = normal.normalize();
+		let reference = if normal.x().abs() < 0.9 { Vector3::x_axis() } else { Vector3::y_axis() };
+		let tangent = normal.cross(&reference).normalize();
+		let bitangent = normal.cross(&tangent);
+		let point = |segment: usize| {
+			let angle = segment as Real / CIRCLE_SEGMENTS as Real * std::f32::consts::TAU;
+			center + (tangent * angle.cos() + bitangent * angle.sin()) * radius
+		};
+		for segment in 0..CIRCLE_SEGMENTS {
+			self.line(point(segment), point(segment + 1), color);
+		}
+	}
+
+	/// Three circles around the axes
+	pub fn sphere(&mut self, center: Vector3, radius: Real, color: Color) {
+		for axis in [Vector3::x_axis(), Vector3::y_axis(), Vector3::z_axis()] {
+			self.circle(center, axis, radius, color);
+		}
+	}
+
+	/// Red, green, and blue lines along the x, y, and z axes
+	pub fn axes(&mut self, origin: Vector3, length: Real) {
+		self.ray(origin, Vector3::x_axis() * length, Color::RED);
+		self.ray(origin, Vector3::y_axis() * length, Color::GREEN);
+		self.ray(origin, Vector3::z_axis() * length, Color::BLUE);
+	}
+
+	/// Packs everything queued into the vertex buffer, returning whether the GPU buffer must grow.
+	/// The buffer doubles until it fits and never shrinks, so it settles after a few frames.
+	pub fn build(&mut self) -> bool {
+		self.vertices.clear();
+		self.draw_calls.clear();
+		for ((primitive, depth_test), bucket) in Self::BUCKETS.into_iter().zip(self.buckets.iter()) {
+			if bucket.is_empty() {
+				continue;
+			}
+			self.draw_calls.push(DebugDrawCall {
+				primitive,
+				depth_test,
+				first_vertex: self.vertices.len() as u32,
+				vertex_count: bucket.len() as u32,
+			});
+			self.vertices.extend_from_slice(bucket);
+		}
+		let vertex_bytes = self.buckets.iter().chain([&self.vertices]).map(Vec::capacity).sum::<usize>() * size_of::<DebugVertex>();
+		self.memory.set(vertex_bytes + self.draw_calls.capacity() * size_of::<DebugDrawCall>());
+		let required = self.vertices.len().max(MIN_DEBUG_VERTEX_CAPACITY).next_power_of_two();
+		let resized = required > self.capacity;
+		self.capacity = self.capacity.max(required);
+		resized
+	}
+
+	/// The vertex buffer's size in vertices
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	pub fn vertices(&self) -> &[DebugVertex] {
+		&self.vertices
+	}
+
+	/// The vertices as raw bytes, ready to be written to the vertex buffer
+	pub fn vertex_bytes(&self) -> &[u8] {
+		bytemuck::cast_slice(&self.vertices)
+	}
+
+	pub fn draw_calls(&self) -> &[DebugDrawCall] {
+		&self.draw_calls
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.buckets.iter().all(Vec::is_empty)
+	}
+
+	/// Removes everything queued, keeping the allocations for the next frame
+	pub fn clear(&mut self) {
+		self.buckets.iter_mut().for_each(Vec::clear);
+		self.vertices.clear();
+		self.draw_calls.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn batching() {
+		let mut draw = DebugDraw::default();
+		for index in 0..10_000 {
+			let x = index as Real;
+			draw.line(Vector3::new(x, 0.0, 0.0), Vector3::new(x, 1.0, 0.0), Color::WHITE);
+		}
+		draw.depth_test = false;
+		draw.axes(Vector3::zero(), 1.0);
+		draw.triangle(Vector3::zero(), Vector3::x_axis(), Vector3::y_axis(), Color::RED);
+		draw.depth_test = true;
+		draw.cuboid(Vector3::zero(), Vector3::new(1.0, 1.0, 1.0), Color::GREEN);
+
+		assert!(draw.build());
+		assert_eq!(draw.capacity(), 32768);
+		let calls = draw.draw_calls();
+		assert_eq!(calls.len(), 3);
+		assert_eq!(
+			(calls[0].primitive, calls[0].depth_test, calls[0].vertex_count),
+			(DebugPrimitive::Lines, true, 20_024)
+		);
+		assert_eq!(
+			(calls[1].primitive, calls[1].depth_test, calls[1].first_vertex),
+			(DebugPrimitive::Lines, false, 20_024)
+		);
+		assert_eq!((calls[2].primitive, calls[2].vertex_count), (DebugPrimitive::Triangles, 3));
+		assert_eq!(draw.vertices().len(), 20_033);
+		assert_eq!(draw.vertex_bytes().len(), 20_033 * std::mem::size_of::<DebugVertex>());
+		assert!(MemoryTag::new("debug draw").stats().bytes >= 2 * 20_033 * std::mem::size_of::<DebugVertex>());
+
+		draw.clear();
+		assert!(draw.is_empty());
+		draw.sphere(Vector3::zero(), 1.0, Color::WHITE);
+		assert!(!draw.build());
+		assert_eq!(draw.vertices().len(), 3 * CIRCLE_SEGMENTS * 2);
+		assert_eq!(draw.capacity(), 32768);
+	}
+
+	#[test]
+	pub fn circle() {
+		let mut draw = DebugDraw::default();
+		draw.circle(Vector3::new(0.0, 2.0, 0.0), Vector3::y_axis(), 3.0, Color::WHITE);
+		draw.build();
+		for vertex in draw.vertices() {
+			let [x, y, z] = vertex.position;
+			assert!((y - 2.0).abs() < 1e-5);
+			assert!(((x * x + z * z).sqrt() - 3.0).abs() < 1e-4);
+		}
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(DEBUG_DRAW_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
@@ -0,0 +1,139 @@
+use bytemuck::{Pod, Zeroable};
+use math::Vector3;
+use std::{fmt, str::FromStr};
+
+/// Replaces the material shading of meshes when a debug mode other than `Lit` is active.
+/// The `overdraw_resolve` entry point maps the accumulated overdraw counts to a heatmap.
+pub const DEBUG_VIEW_SHADER: &str = include_str!("shaders/debug_view.wgsl");
+
+/// How the renderer draws the scene. Insert this as a resource to switch modes at runtime;
+/// renderers draw normally when it is missing.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderDebugMode {
+	#[default]
+	Lit,
+	Wireframe,
+	Normals,
+	UvChecker,
+	Overdraw,
+	Depth,
+}
+
+impl RenderDebugMode {
+	/// Every mode, in the order an editor menu lists them
+	pub const ALL: [Self; 6] = [Self::Lit, Self::Wireframe, Self::Normals, Self::UvChecker, Self::Overdraw, Self::Depth];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Self::Lit => "Lit",
+			Self::Wireframe => "Wireframe",
+			Self::Normals => "Normals",
+			Self::UvChecker => "UV Checker",
+			Self::Overdraw => "Overdraw",
+			Self::Depth => "Depth",
+		}
+	}
+
+	/// The next mode in `ALL`, wrapping around, for cycling through modes with a single key
+	pub fn next(&self) -> Self {
+		let index = Self::ALL.iter().position(|mode| mode == self).unwrap_or_default();
+		Self::ALL[(index + 1) % Self::ALL.len()]
+	}
+
+	/// Wireframe draws mesh edges as lines instead of filled triangles
+	pub fn is_wireframe(&self) -> bool {
+		*self == Self::Wireframe
+	}
+
+	/// Overdraw disables depth testing and blends additively, so every shaded fragment is counted
+	pub fn is_overdraw(&self) -> bool {
+		*self == Self::Overdraw
+	}
+
+	/// The mode as the `debug_view` shader's uniform
+	pub fn uniform(&self, near: f32, far: f32) -> DebugViewUniform {
+		DebugViewUniform {
+			mode: *self as u32,
+			near,
+			far,
+			checker_scale: 8.0,
+		}
+	}
+}
+
+impl fmt::Display for RenderDebugMode {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(self.label())
+	}
+}
+
+impl FromStr for RenderDebugMode {
+	type Err = String;
+
+	/// Parses a mode's label, ignoring case, spaces, and underscores
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		let normalize = |text: &str| text.chars().filter(|character| !matches!(character, ' ' | '_')).collect::<String>().to_lowercase();
+		Self::ALL
+			.into_iter()
+			.find(|mode| normalize(mode.label()) == normalize(text))
+			.ok_or_else(|| format!("Unknown render debug mode: {text}"))
+	}
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct DebugViewUniform {
+	pub mode: u32,
+	pub near: f32,
+	pub far: f32,
+
+	/// Checker squares per UV unit
+	pub checker_scale: f32,
+}
+
+/// Line segments from each vertex along its normal, drawn as a line list in the `Normals` mode
+pub fn normal_lines(positions: &[Vector3], normals: &[Vector3], length: f32) -> Vec<[f32; 3]> {
+	positions
+		.iter()
+		.zip(normals)
+		.flat_map(|(position, normal)| {
+			let end = *position + normal.normalize() * length;
+			[[position.x(), position.y(), position.z()], [end.x(), end.y(), end.z()]]
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn cycling() {
+		assert_eq!(RenderDebugMode::Lit.next(), RenderDebugMode::Wireframe);
+		assert_eq!(RenderDebugMode::Depth.next(), RenderDebugMode::Lit);
+	}
+
+	#[test]
+	pub fn parsing() {
+		assert_eq!("uv_checker".parse(), Ok(RenderDebugMode::UvChecker));
+		assert_eq!("UV Checker".parse(), Ok(RenderDebugMode::UvChecker));
+		assert!("shiny".parse::<RenderDebugMode>().is_err());
+		for mode in RenderDebugMode::ALL {
+			assert_eq!(mode.to_string().parse(), Ok(mode));
+		}
+	}
+
+	#[test]
+	pub fn normals() {
+		let lines = normal_lines(&[Vector3::new(1.0, 0.0, 0.0)], &[Vector3::new(0.0, 2.0, 0.0)], 0.5);
+		assert_eq!(lines, [[1.0, 0.0, 0.0], [1.0, 0.5, 0.0]]);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(DEBUG_VIEW_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
@@ -0,0 +1,263 @@
+use crate::instancing::{MaterialHandle, Matrix4};
+use bytemuck::{Pod, Zeroable};
+use math::{BoundingSphere, Color, Frustum, Real, Vector3};
+
+/// Projects decal textures onto whatever scene depth lies inside each decal's box.
+/// Decals are drawn as instanced unit cubes after opaque geometry and before transparent geometry.
+pub const DECAL_SHADER: &str = include_str!("shaders/decal.wgsl");
+
+/// A texture projected onto surfaces, such as a bullet hole, blood splat, or road marking.
+/// The projector is a box centered on `position` that extends along `direction` into the surface.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Decal {
+	pub material: MaterialHandle,
+	pub position: Vector3,
+	pub direction: Vector3,
+
+	/// Rotation around `direction` in radians
+	pub rotation: Real,
+
+	/// Half the width and height of the projected texture, and half the depth it projects through
+	pub half_extents: Vector3,
+
+	pub color: Color,
+
+	/// Seconds until the decal is removed, or `None` to keep it until it is recycled
+	pub lifetime: Option<Real>,
+
+	/// Seconds spent fading out at the end of the decal's lifetime
+	pub fade_duration: Real,
+}
+
+impl Decal {
+	pub fn new(material: MaterialHandle, position: Vector3, direction: Vector3, half_extents: Vector3) -> Self {
+		Self {
+			material,
+			position,
+			direction,
+			rotation: 0.0,
+			half_extents,
+			color: Color::WHITE,
+			lifetime: None,
+			fade_duration: 0.0,
+		}
+	}
+
+	/// The right, up, and forward axes of the projector box
+	fn axes(&self) -> [Vector3; 3] {
+		let forward = self.direction.normalize();
+		let reference = if forward.y().abs() < 0.999 {
+			Vector3::new(0.0, 1.0, 0.0)
+		} else {
+			Vector3::new(0.0, 0.0, 1.0)
+		};
+		let right = reference.cross(&forward).normalize();
+		let up = forward.cross(&right);
+		let (sin, cos) = self.rotation.sin_cos();
+		[right * cos + up * sin, up * cos - right * sin, forward]
+	}
+
+	/// Transforms a unit cube spanning `-1.0..1.0` into the projector box
+	pub fn model(&self) -> Matrix4 {
+		let [right, up, forward] = self.axes();
+		let column = |axis: Vector3, scale: Real| [axis.x() * scale, axis.y() * scale, axis.z() * scale, 0.0];
+		[
+			column(right, self.half_extents.x()),
+			column(up, self.half_extents.y()),
+			column(forward, self.half_extents.z()),
+			[self.position.x(), self.position.y(), self.position.z(), 1.0],
+		]
+	}
+
+	/// Transforms world space into the projector box, which covers `-1.0..1.0` on each axis
+	pub fn world_to_decal(&self) -> Matrix4 {
+		let [right, up, forward] = self.axes();
+		let scaled = [
+			right * (1.0 / self.half_extents.x()),
+			up * (1.0 / self.half_extents.y()),
+			forward * (1.0 / self.half_extents.z()),
+		];
+		let mut matrix = [[0.0; 4]; 4];
+		for (row, axis) in scaled.iter().enumerate() {
+			matrix[0][row] = axis.x();
+			matrix[1][row] = axis.y();
+			matrix[2][row] = axis.z();
+			matrix[3][row] = -axis.dot(&self.position);
+		}
+		matrix[3][3] = 1.0;
+		matrix
+	}
+
+	pub fn bounds(&self) -> BoundingSphere {
+		BoundingSphere::new(self.position, self.half_extents.magnitude())
+	}
+}
+
+/// Per-instance data for the decal shader
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct DecalInstance {
+	pub model: Matrix4,
+	pub world_to_decal: Matrix4,
+	pub color: [f32; 4],
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct ActiveDecal {
+	decal: Decal,
+	age: Real,
+	sequence: u64,
+}
+
+impl ActiveDecal {
+	fn opacity(&self) -> Real {
+		match self.decal.lifetime {
+			Some(lifetime) if self.decal.fade_duration > 0.0 => ((lifetime - self.age) / self.decal.fade_duration).clamp(0.0, 1.0),
+			_ => 1.0,
+		}
+	}
+}
+
+/// A fixed number of decals. Spawning into a full pool recycles the oldest decal,
+/// so games can spawn decals freely without their cost growing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecalPool {
+	capacity: usize,
+	decals: Vec<ActiveDecal>,
+	spawned: u64,
+}
+
+impl DecalPool {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity: capacity.max(1),
+			decals: Vec::new(),
+			spawned: 0,
+		}
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	pub fn len(&self) -> usize {
+		self.decals.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.decals.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.decals.clear();
+	}
+
+	pub fn spawn(&mut self, decal: Decal) {
+		let active = ActiveDecal {
+			decal,
+			age: 0.0,
+			sequence: self.spawned,
+		};
+		self.spawned += 1;
+		if self.decals.len() < self.capacity {
+			self.decals.push(active);
+			return;
+		}
+		if let Some(oldest) = self.decals.iter_mut().min_by_key(|active| active.sequence) {
+			*oldest = active;
+		}
+	}
+
+	/// Ages decals and removes the ones whose lifetime has run out
+	pub fn update(&mut self, delta_time: Real) {
+		self.decals.retain_mut(|active| {
+			active.age += delta_time;
+			active.decal.lifetime.is_none_or(|lifetime| active.age < lifetime)
+		});
+	}
+
+	pub fn decals(&self) -> impl Iterator<Item = &Decal> {
+		self.decals.iter().map(|active| &active.decal)
+	}
+
+	/// The decals inside the frustum, sorted by material so each material is one draw call
+	pub fn visible_instances(&self, frustum: &Frustum, output: &mut Vec<(MaterialHandle, DecalInstance)>) {
+		output.clear();
+		output.extend(self.decals.iter().filter(|active| frustum.intersects_sphere(&active.decal.bounds())).map(|active| {
+			let decal = &active.decal;
+			let instance = DecalInstance {
+				model: decal.model(),
+				world_to_decal: decal.world_to_decal(),
+				color: decal.color.with_alpha(decal.color.a * active.opacity()).into(),
+			};
+			(decal.material, instance)
+		}));
+		output.sort_by_key(|(material, _)| *material);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::instancing::IDENTITY;
+	use math::transform_point;
+
+	fn decal(x: Real) -> Decal {
+		Decal::new(MaterialHandle(0), Vector3::new(x, 0.0, 0.5), Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.1, 0.2, 0.3))
+	}
+
+	#[test]
+	pub fn projection() {
+		let mut decal = decal(0.0);
+		decal.rotation = 0.3;
+		let world_to_decal = decal.world_to_decal();
+		for corner in [Vector3::new(1.0, 1.0, 1.0), Vector3::new(-1.0, 0.5, -0.25)] {
+			let world = transform_point(&decal.model(), corner);
+			let local = transform_point(&world_to_decal, world);
+			assert!((local - corner).magnitude() < 1e-5, "{:?} {:?}", local, corner);
+		}
+	}
+
+	#[test]
+	pub fn recycling() {
+		let mut pool = DecalPool::new(2);
+		pool.spawn(decal(1.0));
+		pool.spawn(decal(2.0));
+		pool.spawn(decal(3.0));
+		let positions = pool.decals().map(|decal| decal.position.x()).collect::<Vec<_>>();
+		assert_eq!(positions, [3.0, 2.0]);
+		pool.spawn(decal(4.0));
+		let positions = pool.decals().map(|decal| decal.position.x()).collect::<Vec<_>>();
+		assert_eq!(positions, [3.0, 4.0]);
+	}
+
+	#[test]
+	pub fn lifetime() {
+		let mut pool = DecalPool::new(4);
+		let mut fading = decal(0.0);
+		fading.lifetime = Some(1.0);
+		fading.fade_duration = 0.5;
+		pool.spawn(fading);
+		pool.spawn(decal(0.2));
+
+		let frustum = Frustum::from_view_projection(&IDENTITY);
+		let mut instances = Vec::new();
+		pool.update(0.75);
+		pool.visible_instances(&frustum, &mut instances);
+		assert_eq!(instances.len(), 2);
+		assert!((instances[0].1.color[3] - 0.5).abs() < 1e-5);
+
+		pool.update(0.5);
+		assert_eq!(pool.len(), 1);
+		pool.visible_instances(&frustum, &mut instances);
+		assert_eq!(instances[0].1.color[3], 1.0);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(DECAL_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
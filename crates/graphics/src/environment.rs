@@ -0,0 +1,390 @@
+use asset::Texture;
+use bytemuck::{Pod, Zeroable};
+use math::{Real, Vector3};
+use std::f32::consts::PI;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Cubemap faces must be square and all the same size")]
+	MismatchedCubemapFaces,
+
+	#[error("Environment maps must be uncompressed RGBA8 or RGBA32F textures")]
+	UnsupportedEnvironmentFormat,
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Draws a cubemap behind everything else in the scene
+pub const SKYBOX_SHADER: &str = include_str!("shaders/skybox.wgsl");
+
+/// Functions for the PBR material shader to add image-based ambient lighting
+pub const IBL_SHADER: &str = include_str!("shaders/ibl.wgsl");
+
+/// Faces in the order graphics APIs expect cubemap layers: +X, -X, +Y, -Y, +Z, -Z
+pub const CUBEMAP_FACES: usize = 6;
+
+/// A cubemap of linear RGB radiance values
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cubemap {
+	pub size: usize,
+	pub faces: [Vec<[f32; 3]>; CUBEMAP_FACES],
+}
+
+impl Cubemap {
+	pub fn new(size: usize, direction_to_color: impl Fn(Vector3) -> [f32; 3]) -> Self {
+		let faces = std::array::from_fn(|face| {
+			(0..size * size)
+				.map(|index| direction_to_color(texel_direction(face, index % size, index / size, size)))
+				.collect()
+		});
+		Self { size, faces }
+	}
+
+	/// Builds a cubemap from six square textures in `CUBEMAP_FACES` order
+	pub fn from_faces(textures: &[Texture; CUBEMAP_FACES]) -> Result<Self> {
+		let size = textures[0].width() as usize;
+		if textures.iter().any(|texture| texture.width() as usize != size || texture.height() as usize != size) {
+			return Err(Error::MismatchedCubemapFaces);
+		}
+		let mut faces: [Vec<[f32; 3]>; CUBEMAP_FACES] = Default::default();
+		for (face, texture) in faces.iter_mut().zip(textures.iter()) {
+			for index in 0..size * size {
+				let [red, green, blue, _] = texture
+					.texel((index % size) as u32, (index / size) as u32)
+					.ok_or(Error::UnsupportedEnvironmentFormat)?;
+				face.push([red, green, blue]);
+			}
+		}
+		Ok(Self { size, faces })
+	}
+
+	/// Projects an equirectangular panorama, such as an HDR sky, onto a cubemap
+	pub fn from_equirectangular(texture: &Texture, size: usize) -> Result<Self> {
+		texture.texel(0, 0).ok_or(Error::UnsupportedEnvironmentFormat)?;
+		let (width, height) = (texture.width(), texture.height());
+		Ok(Self::new(size, |direction| {
+			let u = direction.z().atan2(direction.x()) / (2.0 * PI) + 0.5;
+			let v = 0.5 - direction.y().clamp(-1.0, 1.0).asin() / PI;
+			let x = ((u * width as Real) as u32).min(width - 1);
+			let y = ((v * height as Real) as u32).min(height - 1);
+			let [red, green, blue, _] = texture.texel(x, y).unwrap_or_default();
+			[red, green, blue]
+		}))
+	}
+
+	/// Samples the texel a direction points at
+	pub fn sample(&self, direction: Vector3) -> [f32; 3] {
+		let (face, u, v) = direction_face(direction);
+		let texel = |coordinate: Real| (((coordinate + 1.0) * 0.5 * self.size as Real) as usize).min(self.size - 1);
+		self.faces[face][texel(v) * self.size + texel(u)]
+	}
+
+	/// Flattens the faces into RGBA32F layers for uploading as a cube texture
+	pub fn to_rgba(&self) -> Vec<f32> {
+		self.faces.iter().flatten().flat_map(|[red, green, blue]| [*red, *green, *blue, 1.0]).collect()
+	}
+}
+
+/// The direction through the center of a cubemap texel
+pub fn texel_direction(face: usize, x: usize, y: usize, size: usize) -> Vector3 {
+	let u = 2.0 * (x as Real + 0.5) / size as Real - 1.0;
+	let v = 2.0 * (y as Real + 0.5) / size as Real - 1.0;
+	let direction = match face {
+		0 => Vector3::new(1.0, -v, -u),
+		1 => Vector3::new(-1.0, -v, u),
+		2 => Vector3::new(u, 1.0, v),
+		3 => Vector3::new(u, -1.0, -v),
+		4 => Vector3::new(u, -v, 1.0),
+		_ => Vector3::new(-u, -v, -1.0),
+	};
+	direction.normalize()
+}
+
+/// The face a direction points at and its coordinates on that face, from `-1.0` to `1.0`
+fn direction_face(direction: Vector3) -> (usize, Real, Real) {
+	let (x, y, z) = (direction.x(), direction.y(), direction.z());
+	let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+	let (face, major, u, v) = if ax >= ay && ax >= az {
+		if x > 0.0 { (0, ax, -z, -y) } else { (1, ax, z, -y) }
+	} else if ay >= az {
+		if y > 0.0 { (2, ay, x, z) } else { (3, ay, x, -z) }
+	} else if z > 0.0 {
+		(4, az, x, -y)
+	} else {
+		(5, az, -x, -y)
+	};
+	(face, u / major, v / major)
+}
+
+/// The solid angle a cubemap texel covers, used to weight integrals over the sphere
+fn texel_solid_angle(x: usize, y: usize, size: usize) -> Real {
+	let u = 2.0 * (x as Real + 0.5) / size as Real - 1.0;
+	let v = 2.0 * (y as Real + 0.5) / size as Real - 1.0;
+	let texel_area = (2.0 / size as Real).powi(2);
+	texel_area / (1.0 + u * u + v * v).powf(1.5)
+}
+
+/// Diffuse irradiance stored as nine spherical harmonic coefficients per color channel,
+/// which is far smaller than an irradiance cubemap and just as accurate for diffuse light.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct IrradianceSh {
+	pub coefficients: [[f32; 3]; 9],
+}
+
+impl IrradianceSh {
+	pub fn from_cubemap(cubemap: &Cubemap) -> Self {
+		let mut coefficients = [[0.0; 3]; 9];
+		for (face, texels) in cubemap.faces.iter().enumerate() {
+			for (index, color) in texels.iter().enumerate() {
+				let (x, y) = (index % cubemap.size, index / cubemap.size);
+				let weight = texel_solid_angle(x, y, cubemap.size);
+				let basis = sh_basis(texel_direction(face, x, y, cubemap.size));
+				for (coefficient, basis) in coefficients.iter_mut().zip(basis) {
+					for channel in 0..3 {
+						coefficient[channel] += color[channel] * basis * weight;
+					}
+				}
+			}
+		}
+		// Convolving with the cosine lobe scales each band, and dividing by pi
+		// gives the outgoing radiance of a white Lambertian surface
+		let bands = [1.0, 2.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 0.25, 0.25, 0.25, 0.25, 0.25];
+		for (coefficient, band) in coefficients.iter_mut().zip(bands) {
+			coefficient.iter_mut().for_each(|value| *value *= band);
+		}
+		Self { coefficients }
+	}
+
+	/// The diffuse light arriving at a surface with the given normal, to multiply by the albedo
+	pub fn irradiance(&self, normal: Vector3) -> [f32; 3] {
+		let basis = sh_basis(normal.normalize());
+		let mut result = [0.0; 3];
+		for (coefficient, basis) in self.coefficients.iter().zip(basis) {
+			for channel in 0..3 {
+				result[channel] += coefficient[channel] * basis;
+			}
+		}
+		result.map(|value| value.max(0.0))
+	}
+
+	/// The coefficients padded to `vec4` for the `ibl` shader's uniform
+	pub fn uniform(&self) -> [[f32; 4]; 9] {
+		self.coefficients.map(|[red, green, blue]| [red, green, blue, 0.0])
+	}
+}
+
+fn sh_basis(direction: Vector3) -> [Real; 9] {
+	let (x, y, z) = (direction.x(), direction.y(), direction.z());
+	[
+		0.282_095,
+		0.488_603 * y,
+		0.488_603 * z,
+		0.488_603 * x,
+		1.092_548 * x * y,
+		1.092_548 * y * z,
+		0.315_392 * (3.0 * z * z - 1.0),
+		1.092_548 * x * z,
+		0.546_274 * (x * x - y * y),
+	]
+}
+
+/// Low discrepancy sample points for importance sampling
+fn hammersley(index: u32, count: u32) -> (Real, Real) {
+	(index as Real / count as Real, index.reverse_bits() as Real * 2.328_306_4e-10)
+}
+
+/// A half vector around `normal` distributed by the GGX lobe for the given roughness
+fn importance_sample_ggx(sample: (Real, Real), normal: Vector3, roughness: Real) -> Vector3 {
+	let alpha = roughness * roughness;
+	let phi = 2.0 * PI * sample.0;
+	let cos_theta = ((1.0 - sample.1) / (1.0 + (alpha * alpha - 1.0) * sample.1)).sqrt();
+	let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+	let up = if normal.z().abs() < 0.999 {
+		Vector3::new(0.0, 0.0, 1.0)
+	} else {
+		Vector3::new(1.0, 0.0, 0.0)
+	};
+	let tangent = up.cross(&normal).normalize();
+	let bitangent = normal.cross(&tangent);
+	(tangent * (phi.cos() * sin_theta) + bitangent * (phi.sin() * sin_theta) + normal * cos_theta).normalize()
+}
+
+fn reflect(incident: Vector3, normal: Vector3) -> Vector3 {
+	incident - normal * (2.0 * incident.dot(&normal))
+}
+
+/// Builds the specular mip chain for split-sum image-based lighting.
+/// Each level halves the size and is convolved with a rougher GGX lobe,
+/// from mirror-like reflections at level 0 to fully rough at the last level.
+pub fn prefilter_specular(cubemap: &Cubemap, levels: usize, sample_count: u32) -> Vec<Cubemap> {
+	let levels = levels.max(1);
+	(0..levels)
+		.map(|level| {
+			let size = (cubemap.size >> level).max(1);
+			if level == 0 {
+				return Cubemap::new(size, |direction| cubemap.sample(direction));
+			}
+			let roughness = level as Real / (levels - 1) as Real;
+			Cubemap::new(size, |normal| {
+				let mut total = [0.0; 3];
+				let mut total_weight = 0.0;
+				for index in 0..sample_count {
+					let half = importance_sample_ggx(hammersley(index, sample_count), normal, roughness);
+					let light = reflect(normal.inverse(), half);
+					let weight = normal.dot(&light);
+					if weight > 0.0 {
+						let color = cubemap.sample(light);
+						(0..3).for_each(|channel| total[channel] += color[channel] * weight);
+						total_weight += weight;
+					}
+				}
+				total.map(|value| if total_weight > 0.0 { value / total_weight } else { 0.0 })
+			})
+		})
+		.collect()
+}
+
+/// Precomputes the split-sum BRDF lookup table indexed by `n dot v` (x) and roughness (y),
+/// storing the scale and bias applied to the specular color.
+pub fn brdf_lut(size: usize, sample_count: u32) -> Vec<[f32; 2]> {
+	let mut lut = Vec::with_capacity(size * size);
+	for y in 0..size {
+		let roughness = (y as Real + 0.5) / size as Real;
+		for x in 0..size {
+			let n_dot_v = (x as Real + 0.5) / size as Real;
+			let view = Vector3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+			let normal = Vector3::new(0.0, 0.0, 1.0);
+			let (mut scale, mut bias) = (0.0, 0.0);
+			for index in 0..sample_count {
+				let half = importance_sample_ggx(hammersley(index, sample_count), normal, roughness);
+				let light = reflect(view.inverse(), half);
+				let (n_dot_l, n_dot_h, v_dot_h) = (light.z(), half.z().max(0.0), view.dot(&half).max(0.0));
+				if n_dot_l > 0.0 {
+					let k = roughness * roughness / 2.0;
+					let geometry = |n_dot_x: Real| n_dot_x / (n_dot_x * (1.0 - k) + k);
+					let visibility = geometry(n_dot_v) * geometry(n_dot_l) * v_dot_h / (n_dot_h * n_dot_v).max(Real::EPSILON);
+					let fresnel = (1.0 - v_dot_h).powi(5);
+					scale += (1.0 - fresnel) * visibility;
+					bias += fresnel * visibility;
+				}
+			}
+			lut.push([scale / sample_count as Real, bias / sample_count as Real]);
+		}
+	}
+	lut
+}
+
+/// Everything needed to draw a skybox and light PBR materials from it.
+/// Insert this as a resource and renderers upload it when it changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentLighting {
+	pub skybox: Cubemap,
+	pub irradiance: IrradianceSh,
+	pub specular: Vec<Cubemap>,
+	pub brdf_lut: Vec<[f32; 2]>,
+	pub brdf_lut_size: usize,
+	pub intensity: Real,
+}
+
+impl EnvironmentLighting {
+	pub fn new(skybox: Cubemap) -> Self {
+		let levels = asset::mip_level_count(skybox.size as u32, skybox.size as u32).min(6) as usize;
+		Self {
+			irradiance: IrradianceSh::from_cubemap(&skybox),
+			specular: prefilter_specular(&skybox, levels, 64),
+			brdf_lut: brdf_lut(BRDF_LUT_SIZE, 128),
+			brdf_lut_size: BRDF_LUT_SIZE,
+			intensity: 1.0,
+			skybox,
+		}
+	}
+
+	pub fn uniform(&self) -> EnvironmentUniform {
+		EnvironmentUniform {
+			irradiance: self.irradiance.uniform(),
+			intensity: self.intensity,
+			specular_levels: self.specular.len() as f32,
+			padding: [0.0; 2],
+		}
+	}
+}
+
+const BRDF_LUT_SIZE: usize = 32;
+
+/// The uniform read by the `ibl` shader
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct EnvironmentUniform {
+	pub irradiance: [[f32; 4]; 9],
+	pub intensity: f32,
+	pub specular_levels: f32,
+	pub padding: [f32; 2],
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn face_mapping() {
+		for face in 0..CUBEMAP_FACES {
+			let direction = texel_direction(face, 1, 2, 4);
+			let (sampled_face, u, v) = direction_face(direction);
+			assert_eq!(sampled_face, face);
+			assert!((u - -0.25).abs() < 1e-5 && (v - 0.25).abs() < 1e-5);
+		}
+	}
+
+	#[test]
+	pub fn uniform_irradiance() {
+		let cubemap = Cubemap::new(16, |_| [1.0, 0.5, 0.0]);
+		let irradiance = IrradianceSh::from_cubemap(&cubemap).irradiance(Vector3::new(0.0, 1.0, 0.0));
+		// A uniformly lit white surface reflects exactly the environment's radiance
+		assert!((irradiance[0] - 1.0).abs() < 0.01, "{:?}", irradiance);
+		assert!((irradiance[1] - 0.5).abs() < 0.01);
+	}
+
+	#[test]
+	pub fn directional_irradiance() {
+		let cubemap = Cubemap::new(16, |direction| if direction.y() > 0.0 { [1.0; 3] } else { [0.0; 3] });
+		let irradiance = IrradianceSh::from_cubemap(&cubemap);
+		assert!(irradiance.irradiance(Vector3::new(0.0, 1.0, 0.0))[0] > irradiance.irradiance(Vector3::new(0.0, 1.0, 0.0).inverse())[0]);
+	}
+
+	#[test]
+	pub fn environment_lighting() {
+		let lighting = EnvironmentLighting::new(Cubemap::new(8, |_| [1.0; 3]));
+		assert_eq!(lighting.specular.len(), 4);
+		let uniform = lighting.uniform();
+		assert_eq!(uniform.specular_levels, 4.0);
+		assert_eq!(std::mem::size_of::<EnvironmentUniform>(), 160);
+	}
+
+	#[test]
+	pub fn prefiltered_levels() {
+		let cubemap = Cubemap::new(8, |_| [2.0; 3]);
+		let levels = prefilter_specular(&cubemap, 3, 16);
+		assert_eq!(levels.iter().map(|level| level.size).collect::<Vec<_>>(), [8, 4, 2]);
+		assert!(levels[2].faces[0].iter().all(|texel| (texel[0] - 2.0).abs() < 1e-4));
+	}
+
+	#[test]
+	pub fn brdf_lookup() {
+		let lut = brdf_lut(4, 64);
+		assert_eq!(lut.len(), 16);
+		// Smooth surfaces viewed head on reflect almost all energy through the scale term
+		let [scale, bias] = lut[3];
+		assert!(scale > 0.8 && bias < 0.1, "{} {}", scale, bias);
+	}
+
+	#[test]
+	pub fn shaders_are_valid() {
+		for source in [SKYBOX_SHADER, IBL_SHADER] {
+			let module = naga::front::wgsl::parse_str(source).unwrap();
+			naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+				.validate(&module)
+				.unwrap();
+		}
+	}
+}
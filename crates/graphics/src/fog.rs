@@ -0,0 +1,327 @@
+use crate::{daylight::DirectionalLight, environment::Cubemap};
+use bytemuck::{Pod, Zeroable};
+use math::{Color, Real, Vector3};
+use std::f32::consts::PI;
+
+/// Functions for the forward pass to fade lit surfaces into distance and height fog
+pub const FOG_SHADER: &str = include_str!("shaders/fog.wgsl");
+
+/// Distance and height fog, stored as a resource and overridden per camera by `RenderView::fog`.
+/// The default is clear air, so a view can turn fog off with `Some(Fog::default())`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Fog {
+	pub color: Color,
+
+	/// How much of the light is scattered per unit of distance at `height`, where 0 is clear air
+	pub density: Real,
+
+	/// The distance from the camera where fog begins
+	pub start: Real,
+
+	/// The height the density is measured at, where fog is thicker below and thinner above
+	pub height: Real,
+
+	/// How quickly fog thins out with height, where 0 is the same density at every height
+	pub height_falloff: Real,
+
+	/// Caps how much fog hides, so distant mountains stay faintly visible
+	pub max_opacity: Real,
+
+	/// How much the fog glows in the sun's color when looking toward the sun, from 0 to 1
+	pub sun_scattering: Real,
+}
+
+impl Default for Fog {
+	fn default() -> Self {
+		Self {
+			color: Color::rgb(0.6, 0.7, 0.8),
+			density: 0.0,
+			start: 0.0,
+			height: 0.0,
+			height_falloff: 0.0,
+			max_opacity: 1.0,
+			sun_scattering: 0.0,
+		}
+	}
+}
+
+impl Fog {
+	/// Fog that thickens with distance alone
+	pub fn new(color: Color, density: Real) -> Self {
+		Self {
+			color,
+			density,
+			..Default::default()
+		}
+	}
+
+	/// Fog that pools in valleys, with the given density at `height`
+	pub fn height(color: Color, density: Real, height: Real, height_falloff: Real) -> Self {
+		Self {
+			height,
+			height_falloff,
+			..Self::new(color, density)
+		}
+	}
+
+	/// How much of a surface fog hides, from 0 to `max_opacity`, matching the shader
+	pub fn opacity(&self, camera_position: Vector3, world_position: Vector3) -> Real {
+		let ray = world_position - camera_position;
+		let distance = ray.magnitude();
+		if distance <= self.start {
+			return 0.0;
+		}
+		let camera_density = self.density * (-self.height_falloff * (camera_position.y() - self.height)).min(80.0).exp();
+		let falloff = self.height_falloff * ray.y();
+		let height_factor = if falloff.abs() > 1e-4 { (1.0 - (-falloff).exp()) / falloff } else { 1.0 };
+		let amount = camera_density * height_factor * (distance - self.start);
+		(1.0 - (-amount).exp()).min(self.max_opacity)
+	}
+
+	/// Fades a surface color into the fog, for rendering on the CPU
+	pub fn apply(&self, color: Color, camera_position: Vector3, world_position: Vector3) -> Color {
+		color.lerp(&self.color, self.opacity(camera_position, world_position))
+	}
+
+	/// The uniform read by the `fog` shader, where the sun light is usually the `Daylight` light
+	pub fn uniform(&self, camera_position: Vector3, sun: Option<&DirectionalLight>) -> FogUniform {
+		let (sun_color, sun_direction) = sun.map_or(([0.0; 3], [0.0, 1.0, 0.0]), |sun| {
+			let toward_sun = sun.direction.inverse().normalize();
+			let color = [sun.color.r, sun.color.g, sun.color.b].map(|channel| channel * sun.intensity);
+			(color, [toward_sun.x(), toward_sun.y(), toward_sun.z()])
+		});
+		FogUniform {
+			color: [self.color.r, self.color.g, self.color.b],
+			max_opacity: self.max_opacity,
+			sun_color,
+			sun_scattering: self.sun_scattering,
+			sun_direction,
+			density: self.density,
+			camera_position: [camera_position.x(), camera_position.y(), camera_position.z()],
+			start: self.start,
+			height_falloff: self.height_falloff,
+			height: self.height,
+			padding: [0.0; 2],
+		}
+	}
+}
+
+/// The uniform read by the `fog` shader, which is all zeros and so clear air by default
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct FogUniform {
+	pub color: [f32; 3],
+	pub max_opacity: f32,
+	pub sun_color: [f32; 3],
+	pub sun_scattering: f32,
+	pub sun_direction: [f32; 3],
+	pub density: f32,
+	pub camera_position: [f32; 3],
+	pub start: f32,
+	pub height_falloff: f32,
+	pub height: f32,
+	pub padding: [f32; 2],
+}
+
+const PRIMARY_SAMPLES: usize = 16;
+const LIGHT_SAMPLES: usize = 8;
+
+/// A physically based sky from single Rayleigh and Mie scattering in an atmosphere, in meters.
+///
+/// Rendering it into a cubemap for a sun position gives a skybox with a blue zenith, a hazy
+/// horizon, and red sunsets, which can light the scene through `EnvironmentLighting::new`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Atmosphere {
+	pub planet_radius: Real,
+	pub atmosphere_radius: Real,
+
+	/// How strongly air scatters red, green, and blue light at sea level, which makes the sky blue
+	pub rayleigh_scattering: [Real; 3],
+	pub rayleigh_scale_height: Real,
+
+	/// How strongly haze and dust scatter light at sea level, brightening the sky around the sun
+	pub mie_scattering: Real,
+	pub mie_scale_height: Real,
+
+	/// How much haze scatters light forward rather than back, from 0 to just below 1
+	pub mie_anisotropy: Real,
+	pub sun_intensity: Real,
+
+	/// The viewer's height above the ground
+	pub altitude: Real,
+}
+
+impl Default for Atmosphere {
+	fn default() -> Self {
+		Self {
+			planet_radius: 6_360_000.0,
+			atmosphere_radius: 6_420_000.0,
+			rayleigh_scattering: [5.8e-6, 13.5e-6, 33.1e-6],
+			rayleigh_scale_height: 8000.0,
+			mie_scattering: 21e-6,
+			mie_scale_height: 1200.0,
+			mie_anisotropy: 0.76,
+			sun_intensity: 20.0,
+			altitude: 1.0,
+		}
+	}
+}
+
+impl Atmosphere {
+	/// The linear radiance of the sky seen in a direction, with the sun in `sun_direction`.
+	/// Below the horizon the view ray ends at the ground, leaving only the haze in front of it.
+	pub fn sky_color(&self, direction: Vector3, sun_direction: Vector3) -> [f32; 3] {
+		let (direction, sun_direction) = (direction.normalize(), sun_direction.normalize());
+		let origin = Vector3::new(0.0, self.planet_radius + self.altitude, 0.0);
+		let length = match ray_sphere(origin, direction, self.planet_radius) {
+			Some(ground) if ground > 0.0 => ground,
+			_ => ray_sphere(origin, direction, self.atmosphere_radius).unwrap_or(0.0),
+		};
+		let step = length / PRIMARY_SAMPLES as Real;
+		let (mut rayleigh, mut mie) = ([0.0; 3], [0.0; 3]);
+		let (mut rayleigh_depth, mut mie_depth) = (0.0, 0.0);
+		for sample in 0..PRIMARY_SAMPLES {
+			let position = origin + direction * ((sample as Real + 0.5) * step);
+			let height = position.magnitude() - self.planet_radius;
+			let (rayleigh_density, mie_density) = self.densities(height);
+			rayleigh_depth += rayleigh_density * step;
+			mie_depth += mie_density * step;
+			let Some((rayleigh_light, mie_light)) = self.optical_depth_to_sun(position, sun_direction) else {
+				continue;
+			};
+			for channel in 0..3 {
+				let optical_depth = self.rayleigh_scattering[channel] * (rayleigh_depth + rayleigh_light) + self.mie_scattering * 1.1 * (mie_depth + mie_light);
+				let transmittance = (-optical_depth).exp();
+				rayleigh[channel] += rayleigh_density * step * transmittance;
+				mie[channel] += mie_density * step * transmittance;
+			}
+		}
+		let cos_theta = direction.dot(&sun_direction);
+		let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta);
+		let g = self.mie_anisotropy;
+		let mie_phase = 3.0 / (8.0 * PI) * (1.0 - g * g) * (1.0 + cos_theta * cos_theta) / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5));
+		std::array::from_fn(|channel| {
+			self.sun_intensity * (rayleigh[channel] * self.rayleigh_scattering[channel] * rayleigh_phase + mie[channel] * self.mie_scattering * mie_phase)
+		})
+	}
+
+	/// Renders the sky into a cubemap for the skybox and image-based lighting.
+	/// This ray marches every texel, so render it again only when the sun has moved noticeably.
+	pub fn cubemap(&self, size: usize, sun_direction: Vector3) -> Cubemap {
+		Cubemap::new(size, |direction| self.sky_color(direction, sun_direction))
+	}
+
+	/// The sky color just above the horizon facing away from the sun, which distant fog blends into
+	pub fn horizon_color(&self, sun_direction: Vector3) -> Color {
+		let away = Vector3::new(-sun_direction.x(), 0.05, -sun_direction.z());
+		let [red, green, blue] = self.sky_color(away, sun_direction);
+		Color::rgb(red, green, blue)
+	}
+
+	fn densities(&self, height: Real) -> (Real, Real) {
+		let height = height.max(0.0);
+		((-height / self.rayleigh_scale_height).exp(), (-height / self.mie_scale_height).exp())
+	}
+
+	/// The Rayleigh and Mie optical depths from a point to the top of the atmosphere toward the
+	/// sun, or nothing when the planet is in the way
+	fn optical_depth_to_sun(&self, position: Vector3, sun_direction: Vector3) -> Option<(Real, Real)> {
+		if ray_sphere(position, sun_direction, self.planet_radius).is_some_and(|ground| ground > 0.0) {
+			return None;
+		}
+		let step = ray_sphere(position, sun_direction, self.atmosphere_radius)? / LIGHT_SAMPLES as Real;
+		let (mut rayleigh, mut mie) = (0.0, 0.0);
+		for sample in 0..LIGHT_SAMPLES {
+			let height = (position + sun_direction * ((sample as Real + 0.5) * step)).magnitude() - self.planet_radius;
+			let (rayleigh_density, mie_density) = self.densities(height);
+			rayleigh += rayleigh_density * step;
+			mie += mie_density * step;
+		}
+		Some((rayleigh, mie))
+	}
+}
+
+/// The distance along a ray to where it enters a sphere around the origin, or leaves it inside.
+/// Computed in double precision, since planet-sized squares lose small differences in single.
+fn ray_sphere(origin: Vector3, direction: Vector3, radius: Real) -> Option<Real> {
+	let origin = [origin.x(), origin.y(), origin.z()].map(f64::from);
+	let direction = [direction.x(), direction.y(), direction.z()].map(f64::from);
+	let b = (0..3).map(|axis| origin[axis] * direction[axis]).sum::<f64>();
+	let c = origin.iter().map(|value| value * value).sum::<f64>() - f64::from(radius) * f64::from(radius);
+	let discriminant = b * b - c;
+	if discriminant < 0.0 {
+		return None;
+	}
+	let (near, far) = (-b - discriminant.sqrt(), -b + discriminant.sqrt());
+	let distance = if near > 0.0 { near } else { far };
+	(distance > 0.0).then_some(distance as Real)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn distance_and_height_fog() {
+		let camera = Vector3::new(0.0, 0.0, 0.0);
+		let fog = Fog {
+			start: 10.0,
+			max_opacity: 0.9,
+			..Fog::new(Color::WHITE, 0.05)
+		};
+		assert_eq!(fog.opacity(camera, Vector3::new(0.0, 0.0, -5.0)), 0.0);
+		let near = fog.opacity(camera, Vector3::new(0.0, 0.0, -20.0));
+		assert!((near - (1.0 - (-0.5_f32).exp())).abs() < 1e-5);
+		assert_eq!(fog.opacity(camera, Vector3::new(0.0, 0.0, -1000.0)), 0.9);
+		let far = Vector3::new(0.0, 0.0, -1000.0);
+		assert_eq!(fog.apply(Color::BLACK, camera, far), Color::BLACK.lerp(&Color::WHITE, 0.9));
+		assert_eq!(Fog::default().opacity(camera, Vector3::new(0.0, 0.0, -1000.0)), 0.0);
+
+		// Looking down into a valley crosses thicker fog than looking up at an equally far peak
+		let valley = Fog::height(Color::WHITE, 0.02, 0.0, 0.1);
+		let camera = Vector3::new(0.0, 10.0, 0.0);
+		let below = valley.opacity(camera, Vector3::new(0.0, 0.0, -40.0));
+		let above = valley.opacity(camera, Vector3::new(0.0, 20.0, -40.0));
+		assert!(below > above, "{below} {above}");
+	}
+
+	#[test]
+	pub fn uniform() {
+		let sun = DirectionalLight {
+			direction: Vector3::new(0.0, -1.0, 0.0),
+			color: Color::WHITE,
+			intensity: 0.5,
+		};
+		let uniform = Fog::new(Color::RED, 0.1).uniform(Vector3::new(1.0, 2.0, 3.0), Some(&sun));
+		assert_eq!(uniform.sun_direction, [0.0, 1.0, 0.0]);
+		assert_eq!(uniform.sun_color, [0.5; 3]);
+		assert_eq!((uniform.camera_position, uniform.density), ([1.0, 2.0, 3.0], 0.1));
+		assert_eq!(std::mem::size_of::<FogUniform>(), 80);
+	}
+
+	#[test]
+	pub fn atmosphere() {
+		let atmosphere = Atmosphere::default();
+		let up = Vector3::new(0.0, 1.0, 0.0);
+		let noon = atmosphere.sky_color(up, up);
+		assert!(noon[2] > noon[1] && noon[1] > noon[0], "{noon:?}");
+
+		// At sunset the light crosses far more air, so the sky toward the sun turns red
+		let sunset = Vector3::new(1.0, 0.02, 0.0);
+		let [red, _, blue] = atmosphere.sky_color(Vector3::new(1.0, 0.1, 0.0), sunset);
+		assert!(red / blue > noon[0] / noon[2]);
+
+		let midnight = atmosphere.sky_color(up, up.inverse());
+		assert!(midnight.iter().all(|channel| *channel < 1e-3), "{midnight:?}");
+		assert_eq!(atmosphere.cubemap(2, up).size, 2);
+		assert!(atmosphere.horizon_color(up).b > 0.0);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(FOG_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
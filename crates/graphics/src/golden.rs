@@ -0,0 +1,303 @@
+use math::{Color, Real};
+use std::{
+	fs, io,
+	path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GoldenError {
+	#[error("Failed to read or write image {0:?}: {1}")]
+	Image(PathBuf, image::ImageError),
+
+	#[error("Failed to create the goldens directory: {0}")]
+	Io(#[from] io::Error),
+
+	#[error("Failed to match golden '{name}': {different_pixels} of {total_pixels} pixels differ, by up to {max_delta_e:.1} delta E")]
+	Mismatch {
+		name: String,
+		different_pixels: usize,
+		total_pixels: usize,
+		max_delta_e: Real,
+	},
+
+	#[error("Failed to find golden {0:?}. Run the test with BLESS_GOLDENS=1 to create it")]
+	MissingGolden(PathBuf),
+
+	#[error("Failed to compare a {0}x{1} frame with a {2}x{3} golden")]
+	SizeMismatch(u32, u32, u32, u32),
+}
+
+type Result<T, E = GoldenError> = std::result::Result<T, E>;
+
+/// A captured frame of 8-bit sRGB pixels, such as from the `SoftwareRenderer` or a GPU readback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+	width: u32,
+	height: u32,
+	pixels: Vec<[u8; 4]>,
+}
+
+impl Frame {
+	pub fn new(width: u32, height: u32, color: [u8; 4]) -> Self {
+		Self {
+			width,
+			height,
+			pixels: vec![color; width as usize * height as usize],
+		}
+	}
+
+	/// Wraps tightly packed RGBA bytes, or `None` if there aren't exactly enough for the size
+	pub fn from_rgba8(width: u32, height: u32, bytes: &[u8]) -> Option<Self> {
+		if bytes.len() != width as usize * height as usize * 4 {
+			return None;
+		}
+		let pixels = bytes.chunks_exact(4).map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]]).collect();
+		Some(Self { width, height, pixels })
+	}
+
+	pub(crate) fn from_pixels(width: u32, height: u32, pixels: Vec<[u8; 4]>) -> Self {
+		debug_assert_eq!(pixels.len(), width as usize * height as usize);
+		Self { width, height, pixels }
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let image = image::open(path).map_err(|error| GoldenError::Image(path.to_path_buf(), error))?.to_rgba8();
+		Ok(Self::from_pixels(image.width(), image.height(), image.pixels().map(|pixel| pixel.0).collect()))
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+		let path = path.as_ref();
+		image::save_buffer(path, self.as_bytes(), self.width, self.height, image::ColorType::Rgba8).map_err(|error| GoldenError::Image(path.to_path_buf(), error))
+	}
+
+	pub fn width(&self) -> u32 {
+		self.width
+	}
+
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	pub fn pixels(&self) -> &[[u8; 4]] {
+		&self.pixels
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		bytemuck::cast_slice(&self.pixels)
+	}
+
+	pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+		(x < self.width && y < self.height).then(|| self.pixels[(y * self.width + x) as usize])
+	}
+
+	pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+		if x < self.width && y < self.height {
+			self.pixels[(y * self.width + x) as usize] = color;
+		}
+	}
+
+	/// How far each pixel is from the same pixel of another frame, as it would look to a viewer
+	pub fn compare(&self, other: &Frame) -> Result<FrameDiff> {
+		if (self.width, self.height) != (other.width, other.height) {
+			return Err(GoldenError::SizeMismatch(self.width, self.height, other.width, other.height));
+		}
+		let deltas = self.pixels.iter().zip(other.pixels.iter()).map(|(a, b)| delta_e(*a, *b)).collect();
+		Ok(FrameDiff {
+			width: self.width,
+			height: self.height,
+			deltas,
+		})
+	}
+}
+
+/// The perceptual difference of each pixel between two frames of the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameDiff {
+	width: u32,
+	height: u32,
+	deltas: Vec<Real>,
+}
+
+impl FrameDiff {
+	/// The CIE76 delta E of each pixel, where about 2.3 is the smallest difference people notice
+	pub fn deltas(&self) -> &[Real] {
+		&self.deltas
+	}
+
+	pub fn max_delta_e(&self) -> Real {
+		self.deltas.iter().copied().fold(0.0, Real::max)
+	}
+
+	/// How many pixels differ by more than `threshold`
+	pub fn count_above(&self, threshold: Real) -> usize {
+		self.deltas.iter().filter(|delta| **delta > threshold).count()
+	}
+
+	/// The differing pixels in red over a dimmed grayscale of how much every pixel changed
+	pub fn image(&self, threshold: Real) -> Frame {
+		let mut frame = Frame::new(self.width, self.height, [0, 0, 0, 255]);
+		for (pixel, delta) in frame.pixels.iter_mut().zip(self.deltas.iter()) {
+			*pixel = match *delta > threshold {
+				true => [255, 0, 0, 255],
+				false => {
+					let gray = (delta / threshold.max(Real::EPSILON) * 96.0) as u8;
+					[gray, gray, gray, 255]
+				},
+			};
+		}
+		frame
+	}
+}
+
+/// How different a frame can be from its golden before the test fails.
+/// GPUs and drivers differ slightly, such as in rasterization rules and rounding at triangle edges.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GoldenTolerance {
+	/// The delta E a pixel can differ by without counting as different
+	pub max_delta_e: Real,
+
+	/// The fraction of the pixels that can differ, from 0 to 1
+	pub max_different_fraction: Real,
+}
+
+impl Default for GoldenTolerance {
+	fn default() -> Self {
+		Self {
+			max_delta_e: 2.3,
+			max_different_fraction: 0.002,
+		}
+	}
+}
+
+impl GoldenTolerance {
+	pub fn accepts(&self, diff: &FrameDiff) -> bool {
+		diff.count_above(self.max_delta_e) as Real <= diff.deltas.len() as Real * self.max_different_fraction
+	}
+}
+
+/// A directory of golden frames that rendered frames are checked against.
+///
+/// Setting the `BLESS_GOLDENS` environment variable writes the frames as the new goldens instead.
+/// A frame that doesn't match is written next to its golden as `<name>.actual.png`,
+/// along with a `<name>.diff.png`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoldenImages {
+	directory: PathBuf,
+	pub tolerance: GoldenTolerance,
+	pub bless: bool,
+}
+
+impl GoldenImages {
+	pub const BLESS_VARIABLE: &'static str = "BLESS_GOLDENS";
+
+	pub fn new(directory: impl Into<PathBuf>) -> Self {
+		Self {
+			directory: directory.into(),
+			tolerance: GoldenTolerance::default(),
+			bless: std::env::var_os(Self::BLESS_VARIABLE).is_some(),
+		}
+	}
+
+	pub fn path(&self, name: &str) -> PathBuf {
+		self.directory.join(format!("{name}.png"))
+	}
+
+	/// Checks a frame against the golden with the same name, or replaces the golden when blessing
+	pub fn check(&self, name: &str, frame: &Frame) -> Result<()> {
+		let path = self.path(name);
+		if self.bless {
+			fs::create_dir_all(&self.directory)?;
+			return frame.save(path);
+		}
+		if !path.exists() {
+			return Err(GoldenError::MissingGolden(path));
+		}
+		let diff = frame.compare(&Frame::load(&path)?)?;
+		if self.tolerance.accepts(&diff) {
+			return Ok(());
+		}
+		frame.save(self.directory.join(format!("{name}.actual.png")))?;
+		diff.image(self.tolerance.max_delta_e).save(self.directory.join(format!("{name}.diff.png")))?;
+		Err(GoldenError::Mismatch {
+			name: name.to_string(),
+			different_pixels: diff.count_above(self.tolerance.max_delta_e),
+			total_pixels: diff.deltas.len(),
+			max_delta_e: diff.max_delta_e(),
+		})
+	}
+}
+
+/// The CIE76 color difference, which is the distance between the colors in CIELAB space
+fn delta_e(a: [u8; 4], b: [u8; 4]) -> Real {
+	let ([l1, a1, b1], [l2, a2, b2]) = (lab(a), lab(b));
+	((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+/// Converts an sRGB pixel to CIELAB with the D65 white point, ignoring alpha
+fn lab(pixel: [u8; 4]) -> [Real; 3] {
+	let Color { r, g, b, .. } = Color::from_srgb8(pixel[0], pixel[1], pixel[2], 255);
+	let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.950_47;
+	let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+	let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.088_83;
+	let f = |t: Real| if t > 0.008_856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+	let (fx, fy, fz) = (f(x), f(y), f(z));
+	[116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn perceptual_tolerance() {
+		let golden = Frame::new(10, 10, [40, 120, 200, 255]);
+		let mut frame = golden.clone();
+		assert_eq!(frame.compare(&golden).unwrap().max_delta_e(), 0.0);
+
+		// Off-by-one rounding everywhere is invisible, so it passes
+		frame.pixels.iter_mut().for_each(|pixel| pixel[1] += 1);
+		let diff = frame.compare(&golden).unwrap();
+		assert!(diff.max_delta_e() < 1.0);
+		assert!(GoldenTolerance::default().accepts(&diff));
+
+		// A visibly wrong pixel fails until enough pixels are allowed to differ
+		frame.set_pixel(3, 4, [255, 0, 0, 255]);
+		let diff = frame.compare(&golden).unwrap();
+		assert_eq!(diff.count_above(2.3), 1);
+		assert!(!GoldenTolerance::default().accepts(&diff));
+		let loose = GoldenTolerance {
+			max_different_fraction: 0.01,
+			..Default::default()
+		};
+		assert!(loose.accepts(&diff));
+		assert_eq!(diff.image(2.3).pixel(3, 4), Some([255, 0, 0, 255]));
+
+		assert!(matches!(Frame::new(4, 4, [0; 4]).compare(&golden), Err(GoldenError::SizeMismatch(4, 4, 10, 10))));
+		assert_eq!(Frame::from_rgba8(2, 1, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap().pixel(1, 0), Some([5, 6, 7, 8]));
+		assert_eq!(Frame::from_rgba8(2, 2, &[0; 8]), None);
+	}
+
+	#[test]
+	pub fn goldens() -> Result<()> {
+		let directory = std::env::temp_dir().join(format!("elder-goldens-{}", std::process::id()));
+		let mut goldens = GoldenImages::new(&directory);
+		let frame = Frame::new(8, 8, [10, 20, 30, 255]);
+
+		goldens.bless = false;
+		assert!(matches!(goldens.check("square", &frame), Err(GoldenError::MissingGolden(_))));
+		goldens.bless = true;
+		goldens.check("square", &frame)?;
+		goldens.bless = false;
+		goldens.check("square", &frame)?;
+
+		let mut changed = frame.clone();
+		(0..8).for_each(|x| changed.set_pixel(x, 0, [255, 255, 255, 255]));
+		assert!(matches!(goldens.check("square", &changed), Err(GoldenError::Mismatch { different_pixels: 8, .. })));
+		assert_eq!(Frame::load(directory.join("square.actual.png"))?, changed);
+		assert!(directory.join("square.diff.png").exists());
+		fs::remove_dir_all(directory)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,200 @@
+use crate::{debug_draw::DebugDraw, instancing::Matrix4};
+use bytemuck::{Pod, Zeroable};
+use math::{Camera, Color, Projection, Real, Vector3};
+
+/// Draws the reference grid across the whole ground plane in a fullscreen pass
+pub const GRID_SHADER: &str = include_str!("shaders/grid.wgsl");
+
+/// A reference grid on the ground plane at y = 0, with major lines every few minor ones.
+/// The spacing grows with the camera's height, so zooming out replaces the minor lines with the
+/// major ones, and the lines fade out with their distance from the camera.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReferenceGrid {
+	/// World units between minor lines when the camera is close to the ground
+	pub spacing: Real,
+
+	/// Minor lines per major line, which is also how much the spacing grows as the camera rises
+	pub major_every: u32,
+
+	/// How far from the camera the lines fade out completely at the closest spacing
+	pub fade_distance: Real,
+
+	pub minor_color: Color,
+	pub major_color: Color,
+	pub x_axis_color: Color,
+	pub z_axis_color: Color,
+}
+
+impl Default for ReferenceGrid {
+	fn default() -> Self {
+		Self {
+			spacing: 1.0,
+			major_every: 10,
+			fade_distance: 50.0,
+			minor_color: Color::new(0.5, 0.5, 0.5, 0.3),
+			major_color: Color::new(0.6, 0.6, 0.6, 0.6),
+			x_axis_color: Color::RED.with_alpha(0.8),
+			z_axis_color: Color::BLUE.with_alpha(0.8),
+		}
+	}
+}
+
+impl ReferenceGrid {
+	/// The minor line spacing seen from a height above the ground
+	pub fn spacing_at(&self, height: Real) -> Real {
+		let growth = self.major_every.max(2) as Real;
+		let mut spacing = self.spacing.max(Real::EPSILON);
+		while spacing * growth * 2.0 < height.abs() {
+			spacing *= growth;
+		}
+		spacing
+	}
+
+	/// How far from a camera at this height the lines reach
+	pub fn fade_distance_at(&self, height: Real) -> Real {
+		self.fade_distance * self.spacing_at(height) / self.spacing.max(Real::EPSILON)
+	}
+
+	/// Queues the grid as debug lines around the camera, for when the fullscreen pass isn't there.
+	/// Lines are split where they pass closest to the camera, so vertex colors fade both ends.
+	pub fn draw(&self, draw: &mut DebugDraw, camera_position: Vector3) {
+		let height = camera_position.y();
+		let (spacing, radius) = (self.spacing_at(height), self.fade_distance_at(height));
+		let line_count = (radius / spacing).ceil() as i64;
+		let major_every = i64::from(self.major_every.max(1));
+		for (axis, center) in [(0, camera_position.z()), (2, camera_position.x())] {
+			let nearest = (center / spacing).round() as i64;
+			for index in nearest - line_count..=nearest + line_count {
+				let offset = index as Real * spacing;
+				let distance = (offset - center).abs();
+				if distance >= radius {
+					continue;
+				}
+				let color = match (index, index % major_every) {
+					(0, _) if axis == 0 => self.x_axis_color,
+					(0, _) => self.z_axis_color,
+					(_, 0) => self.major_color,
+					_ => self.minor_color,
+				};
+				let half_length = (radius * radius - distance * distance).sqrt();
+				let along = if axis == 0 { camera_position.x() } else { camera_position.z() };
+				let point = |position: Real| {
+					if axis == 0 {
+						Vector3::new(position, 0.0, offset)
+					} else {
+						Vector3::new(offset, 0.0, position)
+					}
+				};
+				let (faded, nearest_color) = (color.with_alpha(0.0), color.with_alpha(color.a * (1.0 - distance / radius)));
+				draw.gradient_line(point(along - half_length), point(along), faded, nearest_color);
+				draw.gradient_line(point(along), point(along + half_length), nearest_color, faded);
+			}
+		}
+	}
+
+	/// The settings and camera as the `grid` shader's uniform
+	pub fn uniform(&self, camera: &Camera, aspect_ratio: Real) -> GridUniform {
+		let (half_height, perspective) = match camera.projection {
+			Projection::Perspective { fov_y, .. } => ((fov_y * 0.5).tan(), true),
+			Projection::Orthographic { height, .. } => (height * 0.5, false),
+		};
+		let vector = |vector: Vector3| [vector.x(), vector.y(), vector.z(), 0.0];
+		let color = |color: Color| [color.r, color.g, color.b, color.a];
+		let height = camera.position.y();
+		GridUniform {
+			view_projection: camera.view_projection(aspect_ratio),
+			position: vector(camera.position),
+			forward: vector(camera.forward()),
+			right: vector(camera.right() * (half_height * aspect_ratio)),
+			up: vector(camera.up() * half_height),
+			minor_color: color(self.minor_color),
+			major_color: color(self.major_color),
+			x_axis_color: color(self.x_axis_color),
+			z_axis_color: color(self.z_axis_color),
+			spacing: self.spacing_at(height),
+			major_every: self.major_every.max(1) as f32,
+			fade_distance: self.fade_distance_at(height),
+			perspective: u32::from(perspective),
+		}
+	}
+}
+
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct GridUniform {
+	pub view_projection: Matrix4,
+	pub position: [f32; 4],
+	pub forward: [f32; 4],
+
+	/// The camera's right vector scaled to reach the right edge of the view
+	pub right: [f32; 4],
+
+	/// The camera's up vector scaled to reach the top of the view
+	pub up: [f32; 4],
+	pub minor_color: [f32; 4],
+	pub major_color: [f32; 4],
+	pub x_axis_color: [f32; 4],
+	pub z_axis_color: [f32; 4],
+	pub spacing: f32,
+	pub major_every: f32,
+	pub fade_distance: f32,
+	pub perspective: u32,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn adaptive_spacing() {
+		let grid = ReferenceGrid::default();
+		assert_eq!(grid.spacing_at(2.0), 1.0);
+		assert_eq!(grid.spacing_at(-15.0), 1.0);
+		assert_eq!(grid.spacing_at(50.0), 10.0);
+		assert_eq!(grid.spacing_at(500.0), 100.0);
+		assert_eq!(grid.fade_distance_at(50.0), 500.0);
+	}
+
+	#[test]
+	pub fn lines_fade_with_distance() {
+		let grid = ReferenceGrid::default();
+		let mut draw = DebugDraw::default();
+		grid.draw(&mut draw, Vector3::new(0.3, 2.0, 0.0));
+		draw.build();
+		let vertices = draw.vertices();
+		// 99 lines along x and 100 along z since the camera is off center, with two segments each
+		assert_eq!(vertices.len(), 199 * 4);
+		assert!(vertices.iter().all(|vertex| vertex.position[1] == 0.0));
+
+		let x_axis = vertices.iter().filter(|vertex| vertex.color[..3] == [1.0, 0.0, 0.0]).collect::<Vec<_>>();
+		assert_eq!(x_axis.len(), 4);
+		assert_eq!((x_axis[0].position, x_axis[0].color[3]), ([-49.7, 0.0, 0.0], 0.0));
+		assert_eq!((x_axis[1].position, x_axis[1].color[3]), ([0.3, 0.0, 0.0], 0.8));
+		assert_eq!((x_axis[3].position, x_axis[3].color[3]), ([50.3, 0.0, 0.0], 0.0));
+
+		let nearest_alpha = |z: Real| vertices.iter().find(|vertex| vertex.position == [0.3, 0.0, z]).unwrap().color[3];
+		assert!((nearest_alpha(25.0) - 0.3 * 0.5).abs() < 1e-6);
+		assert!((nearest_alpha(40.0) - 0.6 * 0.2).abs() < 1e-6);
+		let major = vertices.iter().filter(|vertex| vertex.color[..3] == [0.6, 0.6, 0.6]).count();
+		assert_eq!(major, 17 * 4);
+	}
+
+	#[test]
+	pub fn uniform_reaches_the_edges() {
+		let camera = Camera::new(Vector3::new(0.0, 5.0, 0.0), math::Quaternion::default(), Projection::default());
+		let uniform = ReferenceGrid::default().uniform(&camera, 2.0);
+		let half_height = (std::f32::consts::FRAC_PI_3 * 0.5).tan();
+		assert!((uniform.up[1] - half_height).abs() < 1e-6);
+		assert!((uniform.right[0] - half_height * 2.0).abs() < 1e-6);
+		assert_eq!(uniform.perspective, 1);
+		assert_eq!(uniform.spacing, 1.0);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(GRID_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
@@ -0,0 +1,162 @@
+use bytemuck::{Pod, Zeroable};
+
+pub use math::Matrix4;
+
+/// Identifies a mesh uploaded to the renderer
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MeshHandle(pub u32);
+
+/// Identifies a material uploaded to the renderer
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaterialHandle(pub u32);
+
+pub const IDENTITY: Matrix4 = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]];
+
+/// The mesh and material an entity is drawn with.
+/// Entities sharing both are drawn together in a single instanced draw call.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MeshRenderer {
+	pub mesh: MeshHandle,
+	pub material: MaterialHandle,
+}
+
+/// A mesh to draw this frame with its world transform
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderObject {
+	pub renderer: MeshRenderer,
+	pub model: Matrix4,
+
+	/// Opacity used to dither between LOD levels, where `1.0` is fully visible
+	pub fade: f32,
+}
+
+impl RenderObject {
+	pub fn new(renderer: MeshRenderer, model: Matrix4) -> Self {
+		Self { renderer, model, fade: 1.0 }
+	}
+}
+
+/// Per-instance data stored in the instance storage buffer
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct InstanceData {
+	pub model: Matrix4,
+	pub fade: f32,
+	pub padding: [f32; 3],
+}
+
+/// A single instanced draw call covering a contiguous range of the instance buffer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DrawBatch {
+	pub mesh: MeshHandle,
+	pub material: MaterialHandle,
+	pub first_instance: u32,
+	pub instance_count: u32,
+}
+
+/// Groups everything visible this frame into one draw call per mesh and material.
+/// Batches are sorted by material first, so renderers switch pipelines as little as possible.
+#[derive(Default, Debug, Clone)]
+pub struct RenderBatches {
+	batches: Vec<DrawBatch>,
+	instances: Vec<InstanceData>,
+	sorted: Vec<(MeshRenderer, InstanceData)>,
+}
+
+impl RenderBatches {
+	/// Rebuilds the batches, reusing the previous frame's allocations
+	pub fn build(&mut self, objects: impl IntoIterator<Item = RenderObject>) {
+		self.sorted.clear();
+		self.sorted.extend(objects.into_iter().map(|object| {
+			let instance = InstanceData {
+				model: object.model,
+				fade: object.fade,
+				padding: [0.0; 3],
+			};
+			(object.renderer, instance)
+		}));
+		self.sorted.sort_by_key(|(renderer, _)| (renderer.material, renderer.mesh));
+
+		self.batches.clear();
+		self.instances.clear();
+		for (renderer, instance) in self.sorted.iter() {
+			let first_instance = self.instances.len() as u32;
+			self.instances.push(*instance);
+			match self.batches.last_mut() {
+				Some(batch) if batch.mesh == renderer.mesh && batch.material == renderer.material => batch.instance_count += 1,
+				_ => self.batches.push(DrawBatch {
+					mesh: renderer.mesh,
+					material: renderer.material,
+					first_instance,
+					instance_count: 1,
+				}),
+			}
+		}
+	}
+
+	pub fn batches(&self) -> &[DrawBatch] {
+		&self.batches
+	}
+
+	pub fn instances(&self) -> &[InstanceData] {
+		&self.instances
+	}
+
+	/// The instance data as raw bytes, ready to be written to a storage buffer
+	pub fn instance_bytes(&self) -> &[u8] {
+		bytemuck::cast_slice(&self.instances)
+	}
+
+	pub fn draw_call_count(&self) -> usize {
+		self.batches.len()
+	}
+
+	pub fn instance_count(&self) -> usize {
+		self.instances.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn translation(x: f32) -> Matrix4 {
+		let mut matrix = IDENTITY;
+		matrix[3][0] = x;
+		matrix
+	}
+
+	#[test]
+	pub fn batching() {
+		let rock = MeshRenderer {
+			mesh: MeshHandle(0),
+			material: MaterialHandle(0),
+		};
+		let tree = MeshRenderer {
+			mesh: MeshHandle(1),
+			material: MaterialHandle(0),
+		};
+		let mut batches = RenderBatches::default();
+		batches.build([
+			RenderObject::new(rock, translation(0.0)),
+			RenderObject::new(tree, translation(1.0)),
+			RenderObject::new(rock, translation(2.0)),
+		]);
+
+		assert_eq!(batches.draw_call_count(), 2);
+		assert_eq!(
+			batches.batches()[0],
+			DrawBatch {
+				mesh: MeshHandle(0),
+				material: MaterialHandle(0),
+				first_instance: 0,
+				instance_count: 2,
+			}
+		);
+		assert_eq!(batches.instances()[1].model[3][0], 2.0);
+		assert_eq!(batches.instance_bytes().len(), 3 * 80);
+
+		batches.build([RenderObject::new(tree, IDENTITY)]);
+		assert_eq!((batches.draw_call_count(), batches.instance_count()), (1, 1));
+	}
+}
@@ -1,14 +1,31 @@
-pub fn add(left: usize, right: usize) -> usize {
-	left + right
-}
+mod billboard;
+mod clustered;
+mod color_filter;
+mod compute;
+mod culling;
+mod daylight;
+mod debug_draw;
+mod debug_view;
+mod decals;
+mod environment;
+mod fog;
+mod golden;
+mod grid;
+mod instancing;
+mod lod;
+mod occlusion;
+mod particles;
+mod pipeline;
+mod render_settings;
+mod render_target;
+mod shader;
+mod software;
+mod stats;
+mod tonemap;
+mod views;
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-
-	#[test]
-	fn it_works() {
-		let result = add(2, 2);
-		assert_eq!(result, 4);
-	}
-}
+pub use self::{
+	billboard::*, clustered::*, color_filter::*, compute::*, culling::*, daylight::*, debug_draw::*, debug_view::*, decals::*, environment::*, fog::*, golden::*,
+	grid::*, instancing::*, lod::*, occlusion::*, particles::*, pipeline::*, render_settings::*, render_target::*, shader::*, software::*, stats::*, tonemap::*,
+	views::*,
+};
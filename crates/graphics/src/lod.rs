@@ -0,0 +1,188 @@
+use crate::{
+	culling::RenderCamera,
+	instancing::{MaterialHandle, Matrix4, MeshHandle, MeshRenderer, RenderObject},
+	occlusion::OcclusionBuffer,
+};
+use math::{BoundingSphere, Real};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LodLevel {
+	pub mesh: MeshHandle,
+
+	/// The level is used until the camera is farther away than this
+	pub max_distance: Real,
+}
+
+/// Swaps between progressively simpler meshes as an entity moves away from the camera.
+/// Levels are ordered from the most to the least detailed,
+/// and nothing is drawn beyond the last level's distance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lod {
+	pub levels: Vec<LodLevel>,
+	pub material: MaterialHandle,
+
+	/// The distance over which neighboring levels are dithered together,
+	/// hiding the pop when a level switches. Zero switches instantly.
+	pub cross_fade_distance: Real,
+}
+
+/// The levels chosen for a distance, with the opacity to draw each at
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LodSelection {
+	pub mesh: MeshHandle,
+	pub fade: Real,
+	pub next: Option<(MeshHandle, Real)>,
+}
+
+impl Lod {
+	pub fn new(material: MaterialHandle, levels: Vec<LodLevel>) -> Self {
+		Self {
+			levels,
+			material,
+			cross_fade_distance: 0.0,
+		}
+	}
+
+	pub fn select(&self, distance: Real) -> Option<LodSelection> {
+		let index = self.levels.iter().position(|level| distance <= level.max_distance)?;
+		let level = self.levels[index];
+		let fade_start = level.max_distance - self.cross_fade_distance;
+		if self.cross_fade_distance <= 0.0 || distance <= fade_start {
+			return Some(LodSelection {
+				mesh: level.mesh,
+				fade: 1.0,
+				next: None,
+			});
+		}
+		let blend = (distance - fade_start) / self.cross_fade_distance;
+		// The last level fades out to nothing
+		let next = self.levels.get(index + 1).map(|next| (next.mesh, blend));
+		Some(LodSelection {
+			mesh: level.mesh,
+			fade: 1.0 - blend,
+			next,
+		})
+	}
+}
+
+/// What an entity draws: a single mesh, or a set of LOD levels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MeshSource<'a> {
+	Mesh(MeshRenderer),
+	Lod(&'a Lod),
+}
+
+/// An entity to consider for drawing, with its local space bounds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SceneObject<'a> {
+	pub source: MeshSource<'a>,
+	pub model: Matrix4,
+	pub bounds: BoundingSphere,
+}
+
+/// Culls objects outside the camera's frustum or hidden behind occluders
+/// and selects LOD levels for the rest, producing the objects to hand to `RenderBatches::build`.
+pub fn gather_render_objects<'a>(
+	camera: &RenderCamera,
+	occlusion: Option<&OcclusionBuffer>,
+	objects: impl IntoIterator<Item = SceneObject<'a>>,
+	output: &mut Vec<RenderObject>,
+) {
+	let frustum = camera.frustum();
+	for object in objects {
+		let bounds = object.bounds.transformed(&object.model);
+		if !frustum.intersects_sphere(&bounds) {
+			continue;
+		}
+		if occlusion.is_some_and(|occlusion| occlusion.is_occluded(&camera.view_projection, &bounds)) {
+			continue;
+		}
+		let lod = match object.source {
+			MeshSource::Mesh(renderer) => {
+				output.push(RenderObject::new(renderer, object.model));
+				continue;
+			},
+			MeshSource::Lod(lod) => lod,
+		};
+		let Some(selection) = lod.select(camera.lod_distance(&bounds)) else {
+			continue;
+		};
+		let object = |mesh, fade| RenderObject {
+			renderer: MeshRenderer { mesh, material: lod.material },
+			model: object.model,
+			fade,
+		};
+		output.push(object(selection.mesh, selection.fade));
+		if let Some((mesh, fade)) = selection.next {
+			output.push(object(mesh, fade));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{instancing::IDENTITY, occlusion::Occluder};
+	use math::Vector3;
+
+	fn lod() -> Lod {
+		Lod::new(
+			MaterialHandle(0),
+			vec![
+				LodLevel {
+					mesh: MeshHandle(0),
+					max_distance: 10.0,
+				},
+				LodLevel {
+					mesh: MeshHandle(1),
+					max_distance: 50.0,
+				},
+			],
+		)
+	}
+
+	#[test]
+	pub fn selection() {
+		let mut lod = lod();
+		assert_eq!(lod.select(5.0).map(|selection| selection.mesh), Some(MeshHandle(0)));
+		assert_eq!(lod.select(20.0).map(|selection| selection.mesh), Some(MeshHandle(1)));
+		assert_eq!(lod.select(60.0), None);
+
+		lod.cross_fade_distance = 4.0;
+		let selection = lod.select(9.0).unwrap();
+		assert_eq!((selection.mesh, selection.fade), (MeshHandle(0), 0.25));
+		assert_eq!(selection.next, Some((MeshHandle(1), 0.75)));
+	}
+
+	#[test]
+	pub fn gather() {
+		let lod = lod();
+		let mut camera = RenderCamera::new(Vector3::new(0.0, 0.0, -20.0), IDENTITY);
+		let object = |x: Real| {
+			let mut model = IDENTITY;
+			model[3][0] = x;
+			model[3][2] = 0.5;
+			SceneObject {
+				source: MeshSource::Lod(&lod),
+				model,
+				bounds: BoundingSphere::new(Vector3::zero(), 0.1),
+			}
+		};
+		let mut output = Vec::new();
+		gather_render_objects(&camera, None, [object(0.0), object(5.0)], &mut output);
+		assert_eq!(output.len(), 1);
+		assert_eq!(output[0].renderer.mesh, MeshHandle(1));
+
+		output.clear();
+		let mut occlusion = OcclusionBuffer::new(16, 16);
+		let mut wall = IDENTITY;
+		wall[3][2] = 0.25;
+		occlusion.rasterize(&camera.view_projection, &wall, &Occluder::cuboid(Vector3::new(1.0, 1.0, 0.01)));
+		gather_render_objects(&camera, Some(&occlusion), [object(0.0)], &mut output);
+		assert!(output.is_empty());
+
+		camera.lod_bias = 10.0;
+		gather_render_objects(&camera, None, [object(0.0)], &mut output);
+		assert!(output.is_empty());
+	}
+}
@@ -0,0 +1,185 @@
+use crate::instancing::Matrix4;
+use math::{BoundingSphere, Real, Vector3, transform_point};
+
+/// A simplified, closed mesh used to hide what is behind it, such as the walls of a building.
+/// Occluders should lie inside the visible geometry they stand in for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occluder {
+	pub vertices: Vec<Vector3>,
+	pub indices: Vec<u32>,
+}
+
+impl Occluder {
+	pub fn cuboid(half_extents: Vector3) -> Self {
+		let (x, y, z) = (half_extents.x(), half_extents.y(), half_extents.z());
+		let vertices = [(-x, -y, -z), (x, -y, -z), (x, y, -z), (-x, y, -z), (-x, -y, z), (x, -y, z), (x, y, z), (-x, y, z)]
+			.into_iter()
+			.map(|(x, y, z)| Vector3::new(x, y, z))
+			.collect();
+		#[rustfmt::skip]
+		let indices = vec![
+			0, 1, 2, 0, 2, 3,
+			4, 6, 5, 4, 7, 6,
+			0, 4, 5, 0, 5, 1,
+			3, 2, 6, 3, 6, 7,
+			0, 3, 7, 0, 7, 4,
+			1, 5, 6, 1, 6, 2,
+		];
+		Self { vertices, indices }
+	}
+}
+
+/// A low resolution depth buffer that big occluders are rasterized into on the CPU,
+/// then tested against to skip drawing objects that are completely hidden.
+/// Depth runs from `0.0` at the near plane to `1.0` at the far plane.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcclusionBuffer {
+	width: usize,
+	height: usize,
+	depth: Vec<Real>,
+}
+
+impl OcclusionBuffer {
+	pub fn new(width: usize, height: usize) -> Self {
+		Self {
+			width,
+			height,
+			depth: vec![1.0; width * height],
+		}
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn depth(&self) -> &[Real] {
+		&self.depth
+	}
+
+	pub fn clear(&mut self) {
+		self.depth.fill(1.0);
+	}
+
+	/// Rasterizes an occluder. Each triangle writes its farthest depth,
+	/// so an occluder never hides anything that is actually in front of it.
+	pub fn rasterize(&mut self, view_projection: &Matrix4, model: &Matrix4, occluder: &Occluder) {
+		let projected = occluder
+			.vertices
+			.iter()
+			.map(|vertex| self.project(view_projection, transform_point(model, *vertex)))
+			.collect::<Vec<_>>();
+		for triangle in occluder.indices.chunks_exact(3) {
+			// Triangles crossing the near plane are skipped rather than clipped
+			let (Some(a), Some(b), Some(c)) = (projected[triangle[0] as usize], projected[triangle[1] as usize], projected[triangle[2] as usize]) else {
+				continue;
+			};
+			self.rasterize_triangle(a, b, c);
+		}
+	}
+
+	fn rasterize_triangle(&mut self, a: [Real; 3], b: [Real; 3], c: [Real; 3]) {
+		let area = edge(a, b, c);
+		if area.abs() <= Real::EPSILON {
+			return;
+		}
+		let depth = a[2].max(b[2]).max(c[2]);
+		let (min_x, max_x) = self.clamp_x(a[0].min(b[0]).min(c[0]), a[0].max(b[0]).max(c[0]));
+		let (min_y, max_y) = self.clamp_y(a[1].min(b[1]).min(c[1]), a[1].max(b[1]).max(c[1]));
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				let point = [x as Real + 0.5, y as Real + 0.5, 0.0];
+				let weights = [edge(b, c, point), edge(c, a, point), edge(a, b, point)];
+				let is_inside = weights.iter().all(|weight| weight * area.signum() >= 0.0);
+				let texel = &mut self.depth[y * self.width + x];
+				if is_inside && depth < *texel {
+					*texel = depth;
+				}
+			}
+		}
+	}
+
+	/// Whether a world space sphere is hidden behind the rasterized occluders
+	pub fn is_occluded(&self, view_projection: &Matrix4, sphere: &BoundingSphere) -> bool {
+		let radius = sphere.radius;
+		let mut bounds = [Real::MAX, Real::MAX, Real::MAX, Real::MIN, Real::MIN];
+		for corner in 0..8 {
+			let offset = |bit: usize| if corner & bit == 0 { -radius } else { radius };
+			let point = sphere.center + Vector3::new(offset(1), offset(2), offset(4));
+			let Some([x, y, depth]) = self.project(view_projection, point) else {
+				return false;
+			};
+			bounds = [bounds[0].min(x), bounds[1].min(y), bounds[2].min(depth), bounds[3].max(x), bounds[4].max(y)];
+		}
+		let [min_x, min_y, nearest, max_x, max_y] = bounds;
+		let (min_x, max_x) = self.clamp_x(min_x, max_x);
+		let (min_y, max_y) = self.clamp_y(min_y, max_y);
+		if min_x >= max_x || min_y >= max_y {
+			// Entirely off screen, which frustum culling handles
+			return false;
+		}
+		(min_y..max_y).all(|y| self.depth[y * self.width + min_x..y * self.width + max_x].iter().all(|depth| *depth < nearest))
+	}
+
+	/// Projects a world space point to pixel coordinates and depth,
+	/// or `None` if it is behind the near plane
+	fn project(&self, view_projection: &Matrix4, point: Vector3) -> Option<[Real; 3]> {
+		let clip = |row: usize| view_projection[0][row] * point.x() + view_projection[1][row] * point.y() + view_projection[2][row] * point.z() + view_projection[3][row];
+		let w = clip(3);
+		if w <= Real::EPSILON {
+			return None;
+		}
+		let (x, y, depth) = (clip(0) / w, clip(1) / w, clip(2) / w);
+		if depth < 0.0 {
+			return None;
+		}
+		Some([(x * 0.5 + 0.5) * self.width as Real, (0.5 - y * 0.5) * self.height as Real, depth])
+	}
+
+	fn clamp_x(&self, min: Real, max: Real) -> (usize, usize) {
+		clamp_span(min, max, self.width)
+	}
+
+	fn clamp_y(&self, min: Real, max: Real) -> (usize, usize) {
+		clamp_span(min, max, self.height)
+	}
+}
+
+/// Converts a span in pixels to the range of texels it touches
+fn clamp_span(min: Real, max: Real, size: usize) -> (usize, usize) {
+	let size = size as Real;
+	(min.floor().clamp(0.0, size) as usize, max.ceil().clamp(0.0, size) as usize)
+}
+
+fn edge(a: [Real; 3], b: [Real; 3], point: [Real; 3]) -> Real {
+	(b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::instancing::IDENTITY;
+
+	#[test]
+	pub fn occlusion() {
+		let mut buffer = OcclusionBuffer::new(32, 32);
+		let mut model = IDENTITY;
+		// A wall covering the left half of the screen at a depth of 0.5
+		model[3][0] = -0.5;
+		model[3][2] = 0.5;
+		let wall = Occluder::cuboid(Vector3::new(0.5, 1.0, 0.01));
+		buffer.rasterize(&IDENTITY, &model, &wall);
+
+		let sphere = |x: Real, z: Real| BoundingSphere::new(Vector3::new(x, 0.0, z), 0.1);
+		assert!(buffer.is_occluded(&IDENTITY, &sphere(-0.5, 0.8)));
+		assert!(!buffer.is_occluded(&IDENTITY, &sphere(-0.5, 0.2)));
+		assert!(!buffer.is_occluded(&IDENTITY, &sphere(0.5, 0.8)));
+		assert!(!buffer.is_occluded(&IDENTITY, &sphere(0.0, 0.8)));
+
+		buffer.clear();
+		assert!(!buffer.is_occluded(&IDENTITY, &sphere(-0.5, 0.8)));
+	}
+}
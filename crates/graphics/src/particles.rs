@@ -0,0 +1,461 @@
+use crate::decals::{Decal, DecalPool};
+use bytemuck::{Pod, Zeroable};
+use math::{Color, Real, Vector3};
+use physics::{Particle, Ray, Raycast};
+
+/// Compute shader entry points `simulate`, `compute_depths`, and `sort_step`
+pub const PARTICLE_SIMULATION_SHADER: &str = include_str!("shaders/particle_simulation.wgsl");
+
+/// Billboard vertex and fragment entry points `vertex_main` and `fragment_main`
+pub const PARTICLE_RENDER_SHADER: &str = include_str!("shaders/particle_render.wgsl");
+
+pub const PARTICLE_WORKGROUP_SIZE: u32 = 64;
+
+/// A particle as stored in the GPU particle buffer
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct GpuParticle {
+	pub position: [f32; 3],
+	pub age: f32,
+	pub velocity: [f32; 3],
+	pub lifetime: f32,
+	pub color: [f32; 4],
+	pub size: f32,
+	pub padding: [f32; 3],
+}
+
+impl GpuParticle {
+	pub fn is_alive(&self) -> bool {
+		self.age < self.lifetime
+	}
+}
+
+/// Emitter parameters uploaded to the simulation shader each frame
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct EmitterUniform {
+	pub position: [f32; 3],
+	pub spawn_count: u32,
+	pub velocity: [f32; 3],
+	pub spread: f32,
+	pub gravity: [f32; 3],
+	pub lifetime: f32,
+	pub start_color: [f32; 4],
+	pub end_color: [f32; 4],
+	pub start_size: f32,
+	pub end_size: f32,
+	pub delta_time: f32,
+	pub seed: u32,
+	pub damping: f32,
+	pub particle_count: u32,
+	pub padding: [u32; 2],
+}
+
+/// What a particle does when it hits the scene's geometry
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub enum ParticleCollision {
+	/// Particles pass through everything
+	#[default]
+	None,
+
+	/// Particles disappear where they hit, such as rain
+	Die,
+
+	/// Particles rebound, keeping `restitution` of their speed into the surface and
+	/// `1.0 - friction` of their speed along it
+	Bounce { restitution: Real, friction: Real },
+
+	/// Particles stop where they hit for the rest of their lifetime, such as sparks or blood,
+	/// optionally spawning a copy of the decal placed at the hit and projected into the surface
+	Stick { decal: Option<Decal> },
+}
+
+/// Where a particle hit the scene, for playing sounds or spawning effects
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParticleImpact {
+	pub point: Vector3,
+	pub normal: Vector3,
+	pub velocity: Vector3,
+}
+
+/// Parameters for the depth and bitonic sort dispatches
+#[repr(C)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct SortParams {
+	pub camera_position: [f32; 3],
+	pub block_size: u32,
+	pub compare_distance: u32,
+	pub padding: [u32; 3],
+}
+
+/// Spawns particles continuously from a point.
+/// Particles are simulated on the GPU when a renderer is available,
+/// or with `CpuParticles` in headless runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEmitter {
+	pub position: Vector3,
+
+	/// Particles spawned per second
+	pub rate: Real,
+
+	pub velocity: Vector3,
+
+	/// The largest random offset added to each axis of the initial velocity
+	pub spread: Real,
+
+	pub gravity: Vector3,
+	pub damping: Real,
+	pub lifetime: Real,
+	pub start_color: Color,
+	pub end_color: Color,
+	pub start_size: Real,
+	pub end_size: Real,
+
+	/// Handled by `CpuParticles::update_with_collisions`, as compute shaders can't see geometry
+	pub collision: ParticleCollision,
+	capacity: u32,
+	spawn_remainder: Real,
+	frame: u32,
+}
+
+impl ParticleEmitter {
+	/// The capacity is rounded up to a power of two, which the bitonic sort requires
+	pub fn new(capacity: u32) -> Self {
+		Self {
+			position: Vector3::zero(),
+			rate: 10.0,
+			velocity: Vector3::new(0.0, 1.0, 0.0),
+			spread: 0.0,
+			gravity: Vector3::zero(),
+			damping: 1.0,
+			lifetime: 1.0,
+			start_color: Color::WHITE,
+			end_color: Color::WHITE.with_alpha(0.0),
+			start_size: 0.1,
+			end_size: 0.1,
+			collision: ParticleCollision::None,
+			capacity: capacity.max(1).next_power_of_two(),
+			spawn_remainder: 0.0,
+			frame: 0,
+		}
+	}
+
+	pub fn capacity(&self) -> u32 {
+		self.capacity
+	}
+
+	/// Advances the emitter by `delta_time` seconds and returns this frame's simulation parameters
+	pub fn uniform(&mut self, delta_time: Real) -> EmitterUniform {
+		let spawns = self.rate * delta_time + self.spawn_remainder;
+		self.spawn_remainder = spawns.fract();
+		self.frame = self.frame.wrapping_add(1);
+		let vector = |vector: Vector3| [vector.x(), vector.y(), vector.z()];
+		EmitterUniform {
+			position: vector(self.position),
+			spawn_count: spawns as u32,
+			velocity: vector(self.velocity),
+			spread: self.spread,
+			gravity: vector(self.gravity),
+			lifetime: self.lifetime,
+			start_color: self.start_color.into(),
+			end_color: self.end_color.into(),
+			start_size: self.start_size,
+			end_size: self.end_size,
+			delta_time,
+			seed: hash(self.frame),
+			damping: self.damping,
+			particle_count: self.capacity,
+			padding: [0; 2],
+		}
+	}
+
+	pub fn workgroup_count(&self) -> u32 {
+		self.capacity.div_ceil(PARTICLE_WORKGROUP_SIZE)
+	}
+
+	/// The sequence of `sort_step` dispatches that sorts the particles from back to front
+	pub fn sort_steps(&self, camera_position: Vector3) -> Vec<SortParams> {
+		let camera_position = [camera_position.x(), camera_position.y(), camera_position.z()];
+		let mut steps = Vec::new();
+		let mut block_size = 2;
+		while block_size <= self.capacity {
+			let mut compare_distance = block_size / 2;
+			while compare_distance > 0 {
+				steps.push(SortParams {
+					camera_position,
+					block_size,
+					compare_distance,
+					padding: [0; 3],
+				});
+				compare_distance /= 2;
+			}
+			block_size *= 2;
+		}
+		steps
+	}
+}
+
+/// PCG hash, matching the one in the simulation shader
+fn hash(value: u32) -> u32 {
+	let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+	let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+	(word >> 22) ^ word
+}
+
+fn random(seed: u32) -> Real {
+	hash(seed) as Real / u32::MAX as Real
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+struct CpuParticle {
+	body: Particle,
+	age: Real,
+	lifetime: Real,
+	color: [f32; 4],
+	size: Real,
+}
+
+/// Simulates particles on the CPU with the same rules as the compute shader,
+/// for headless runs and gameplay code that needs to read particle positions.
+#[derive(Default, Debug, Clone)]
+pub struct CpuParticles {
+	particles: Vec<CpuParticle>,
+}
+
+impl CpuParticles {
+	pub fn update(&mut self, emitter: &EmitterUniform) {
+		self.simulate(emitter, |_, _| {});
+	}
+
+	/// Simulates a frame like `update`, then responds to particles that crossed the geometry,
+	/// returning where they hit. Stuck particles' decals are spawned into `decals`.
+	pub fn update_with_collisions(
+		&mut self,
+		emitter: &EmitterUniform,
+		collision: &ParticleCollision,
+		geometry: &(impl Raycast + ?Sized),
+		decals: &mut DecalPool,
+	) -> Vec<ParticleImpact> {
+		let mut impacts = Vec::new();
+		if *collision == ParticleCollision::None {
+			self.update(emitter);
+			return impacts;
+		}
+		self.simulate(emitter, |particle, previous| {
+			let Some(impact) = collide(particle, previous, collision, geometry) else {
+				return;
+			};
+			if let ParticleCollision::Stick { decal: Some(decal) } = collision {
+				decals.spawn(Decal {
+					position: impact.point,
+					direction: impact.normal.inverse(),
+					..*decal
+				});
+			}
+			impacts.push(impact);
+		});
+		impacts
+	}
+
+	/// Spawns, moves, and ages the particles, calling `moved` with each one and its last position
+	fn simulate(&mut self, emitter: &EmitterUniform, mut moved: impl FnMut(&mut CpuParticle, Vector3)) {
+		self.particles.resize(emitter.particle_count as usize, CpuParticle::default());
+		let mut spawned = 0;
+		for particle in self.particles.iter_mut() {
+			if particle.age >= particle.lifetime {
+				if spawned >= emitter.spawn_count {
+					continue;
+				}
+				*particle = spawn(emitter, spawned);
+				spawned += 1;
+			}
+			let previous = particle.body.position;
+			particle.body.integrate(emitter.delta_time);
+			moved(particle, previous);
+			particle.age += emitter.delta_time;
+
+			let progress = (particle.age / particle.lifetime).clamp(0.0, 1.0);
+			let mix = |start: f32, end: f32| start + (end - start) * progress;
+			particle.color = [0, 1, 2, 3].map(|channel| mix(emitter.start_color[channel], emitter.end_color[channel]));
+			particle.size = mix(emitter.start_size, emitter.end_size);
+		}
+	}
+
+	pub fn alive_count(&self) -> usize {
+		self.particles.iter().filter(|particle| particle.age < particle.lifetime).count()
+	}
+
+	/// Living particles sorted from back to front, in the layout the billboard shader reads
+	pub fn sorted(&self, camera_position: Vector3) -> Vec<GpuParticle> {
+		let mut particles = self
+			.particles
+			.iter()
+			.filter(|particle| particle.age < particle.lifetime)
+			.map(|particle| {
+				let (position, velocity) = (particle.body.position, particle.body.velocity);
+				GpuParticle {
+					position: [position.x(), position.y(), position.z()],
+					age: particle.age,
+					velocity: [velocity.x(), velocity.y(), velocity.z()],
+					lifetime: particle.lifetime,
+					color: particle.color,
+					size: particle.size,
+					padding: [0.0; 3],
+				}
+			})
+			.collect::<Vec<_>>();
+		let distance = |particle: &GpuParticle| (Vector3::new(particle.position[0], particle.position[1], particle.position[2]) - camera_position).magnitude();
+		particles.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+		particles
+	}
+}
+
+/// Moves a particle back to where it first hit the geometry and applies the response
+fn collide(particle: &mut CpuParticle, previous: Vector3, collision: &ParticleCollision, geometry: &(impl Raycast + ?Sized)) -> Option<ParticleImpact> {
+	let movement = particle.body.position - previous;
+	let distance = movement.magnitude();
+	if distance <= Real::EPSILON {
+		return None;
+	}
+	let hit = geometry.raycast(&Ray::new(previous, movement), distance)?;
+	let velocity = particle.body.velocity;
+	particle.body.position = hit.point + hit.normal * COLLISION_OFFSET;
+	match *collision {
+		ParticleCollision::None => {},
+		ParticleCollision::Die => particle.age = particle.lifetime,
+		ParticleCollision::Bounce { restitution, friction } => {
+			let normal_velocity = hit.normal * velocity.dot(&hit.normal);
+			let tangent_velocity = velocity - normal_velocity;
+			particle.body.velocity = tangent_velocity * (1.0 - friction) - normal_velocity * restitution;
+		},
+		ParticleCollision::Stick { .. } => {
+			particle.body.position = hit.point;
+			particle.body.velocity = Vector3::zero();
+			particle.body.acceleration = Vector3::zero();
+		},
+	}
+	Some(ParticleImpact {
+		point: hit.point,
+		normal: hit.normal,
+		velocity,
+	})
+}
+
+/// How far bounced particles are lifted off the surface, so they don't hit it again from inside
+const COLLISION_OFFSET: Real = 1e-3;
+
+fn spawn(emitter: &EmitterUniform, slot: u32) -> CpuParticle {
+	let seed = hash(emitter.seed ^ hash(slot)).wrapping_mul(3);
+	let direction = Vector3::new(random(seed), random(seed.wrapping_add(1)), random(seed.wrapping_add(2))) * 2.0 - Vector3::new(1.0, 1.0, 1.0);
+	let [x, y, z] = emitter.position;
+	let [velocity_x, velocity_y, velocity_z] = emitter.velocity;
+	let [gravity_x, gravity_y, gravity_z] = emitter.gravity;
+	CpuParticle {
+		body: Particle {
+			position: Vector3::new(x, y, z),
+			velocity: Vector3::new(velocity_x, velocity_y, velocity_z) + direction * emitter.spread,
+			acceleration: Vector3::new(gravity_x, gravity_y, gravity_z),
+			damping: emitter.damping,
+			inverse_mass: 1.0,
+			..Default::default()
+		},
+		age: 0.0,
+		lifetime: emitter.lifetime,
+		color: emitter.start_color,
+		size: emitter.start_size,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn spawning() {
+		let mut emitter = ParticleEmitter::new(100);
+		assert_eq!(emitter.capacity(), 128);
+		emitter.rate = 15.0;
+		emitter.lifetime = 1.0;
+		let mut particles = CpuParticles::default();
+
+		particles.update(&emitter.uniform(0.1));
+		assert_eq!(particles.alive_count(), 1);
+		particles.update(&emitter.uniform(0.1));
+		assert_eq!(particles.alive_count(), 3);
+
+		emitter.rate = 0.0;
+		for _ in 0..10 {
+			particles.update(&emitter.uniform(0.1));
+		}
+		assert_eq!(particles.alive_count(), 0);
+	}
+
+	#[test]
+	pub fn back_to_front() {
+		let mut emitter = ParticleEmitter::new(4);
+		emitter.rate = 40.0;
+		emitter.spread = 1.0;
+		let mut particles = CpuParticles::default();
+		particles.update(&emitter.uniform(0.1));
+
+		let camera = Vector3::new(0.0, 0.0, 10.0);
+		let sorted = particles.sorted(camera);
+		assert_eq!(sorted.len(), 4);
+		let distance = |particle: &GpuParticle| (Vector3::new(particle.position[0], particle.position[1], particle.position[2]) - camera).magnitude();
+		assert!(sorted.windows(2).all(|pair| distance(&pair[0]) >= distance(&pair[1])));
+	}
+
+	#[test]
+	pub fn collisions() {
+		let ground = physics::Plane::ground(0.0);
+		let mut emitter = ParticleEmitter::new(1);
+		emitter.position = Vector3::new(0.0, 0.5, 0.0);
+		emitter.velocity = Vector3::new(1.0, -10.0, 0.0);
+		emitter.rate = 10.0;
+		let mut decals = DecalPool::new(4);
+
+		let mut particles = CpuParticles::default();
+		emitter.collision = ParticleCollision::Bounce { restitution: 0.5, friction: 0.0 };
+		let impacts = particles.update_with_collisions(&emitter.uniform(0.1), &emitter.collision, &ground, &mut decals);
+		assert_eq!(impacts.len(), 1);
+		assert_eq!(impacts[0].normal, Vector3::y_axis());
+		let bounced = particles.particles[0].body;
+		assert!(bounced.position.y() > 0.0);
+		assert_eq!(bounced.velocity, Vector3::new(1.0, 5.0, 0.0));
+
+		let mut particles = CpuParticles::default();
+		emitter.collision = ParticleCollision::Die;
+		particles.update_with_collisions(&emitter.uniform(0.1), &emitter.collision, &ground, &mut decals);
+		assert_eq!(particles.alive_count(), 0);
+
+		let mut particles = CpuParticles::default();
+		let decal = Decal::new(crate::MaterialHandle(0), Vector3::zero(), Vector3::zero(), Vector3::new(0.1, 0.1, 0.1));
+		emitter.collision = ParticleCollision::Stick { decal: Some(decal) };
+		particles.update_with_collisions(&emitter.uniform(0.1), &emitter.collision, &ground, &mut decals);
+		emitter.rate = 0.0;
+		particles.update_with_collisions(&emitter.uniform(0.1), &emitter.collision, &ground, &mut decals);
+		assert_eq!(particles.alive_count(), 1);
+		assert_eq!(particles.particles[0].body.position.y(), 0.0);
+		let spawned = decals.decals().next().unwrap();
+		assert_eq!(spawned.direction, Vector3::new(0.0, -1.0, 0.0));
+		assert_eq!(decals.len(), 1);
+	}
+
+	#[test]
+	pub fn sort_steps() {
+		let emitter = ParticleEmitter::new(8);
+		let steps = emitter.sort_steps(Vector3::zero());
+		let pairs = steps.iter().map(|step| (step.block_size, step.compare_distance)).collect::<Vec<_>>();
+		assert_eq!(pairs, [(2, 1), (4, 2), (4, 1), (8, 4), (8, 2), (8, 1)]);
+	}
+
+	#[test]
+	pub fn shaders_are_valid() {
+		for source in [PARTICLE_SIMULATION_SHADER, PARTICLE_RENDER_SHADER] {
+			let module = naga::front::wgsl::parse_str(source).unwrap();
+			naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+				.validate(&module)
+				.unwrap();
+		}
+	}
+}
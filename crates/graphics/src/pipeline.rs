@@ -0,0 +1,360 @@
+use crate::shader::{ShaderCache, ShaderDefines, ShaderError, ShaderLibrary};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::{BTreeMap, HashMap},
+	error::Error,
+	fs, io,
+	path::Path,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PipelineError {
+	#[error("Failed to compile pipeline for shader '{1}'")]
+	CompilePipeline(#[source] Box<dyn Error>, String),
+
+	#[error("Failed to parse the pipeline cache at path: {1}")]
+	ParseCache(#[source] ron::error::SpannedError, String),
+
+	#[error("Failed to read the pipeline cache at path: {1}")]
+	ReadCache(#[source] io::Error, String),
+
+	#[error("Failed to serialize the pipeline cache!")]
+	SerializeCache(#[source] ron::Error),
+
+	#[error(transparent)]
+	Shader(#[from] ShaderError),
+
+	#[error("Failed to write the pipeline cache at path: {1}")]
+	WriteCache(#[source] io::Error, String),
+}
+
+type Result<T, E = PipelineError> = std::result::Result<T, E>;
+
+/// Bumped whenever the cache file changes shape, which discards caches from older builds
+pub const PIPELINE_CACHE_VERSION: u32 = 1;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum BlendMode {
+	#[default]
+	Opaque,
+	Alpha,
+	Additive,
+	Premultiplied,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CullMode {
+	None,
+	#[default]
+	Back,
+	Front,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DepthTest {
+	Disabled,
+	#[default]
+	Less,
+	LessEqual,
+	Greater,
+	Always,
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PrimitiveTopology {
+	#[default]
+	TriangleList,
+	TriangleStrip,
+	LineList,
+	PointList,
+}
+
+/// The fixed-function state baked into a pipeline
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RenderState {
+	pub blend: BlendMode,
+	pub cull: CullMode,
+	pub depth_test: DepthTest,
+	pub depth_write: bool,
+	pub topology: PrimitiveTopology,
+	pub sample_count: u32,
+}
+
+impl Default for RenderState {
+	fn default() -> Self {
+		Self {
+			blend: BlendMode::default(),
+			cull: CullMode::default(),
+			depth_test: DepthTest::default(),
+			depth_write: true,
+			topology: PrimitiveTopology::default(),
+			sample_count: 1,
+		}
+	}
+}
+
+/// Everything needed to build a pipeline, saved with the cache so it can be rebuilt next launch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineDescriptor {
+	/// The shader's name in the `ShaderLibrary`
+	pub shader: String,
+	pub defines: ShaderDefines,
+
+	/// Names the bind group and vertex layouts, such as `skinned_mesh`
+	pub layout: String,
+	pub state: RenderState,
+}
+
+/// Identifies a pipeline by its preprocessed shader's content hash, layout, and render state,
+/// so editing a shader or any of its includes produces a new key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PipelineKey {
+	pub shader_hash: u64,
+	pub layout: String,
+	pub state: RenderState,
+}
+
+/// What a backend needs to compile a pipeline
+pub struct PipelineSource<'a> {
+	/// The preprocessed WGSL
+	pub source: &'a str,
+	pub descriptor: &'a PipelineDescriptor,
+
+	/// Data the backend returned when this pipeline was compiled on a previous run, such as a
+	/// driver's pipeline cache blob, which lets it skip most of the work. Empty the first time.
+	pub cached_data: &'a [u8],
+}
+
+/// Compiles a pipeline, returning it along with any data that would speed up compiling it next time
+pub type CompiledPipeline<P> = std::result::Result<(P, Vec<u8>), Box<dyn Error>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PipelineRecord {
+	descriptor: PipelineDescriptor,
+	data: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PipelineCacheFile {
+	version: u32,
+	records: Vec<(PipelineKey, PipelineRecord)>,
+}
+
+/// Compiled pipelines, along with a record of every pipeline built that is saved between runs.
+///
+/// Loading the saved cache queues each recorded pipeline for warm-up, which a loading screen can
+/// work through a little at a time so the first frames of gameplay don't stall compiling.
+pub struct PipelineCache<P> {
+	pipelines: HashMap<PipelineKey, P>,
+	records: BTreeMap<PipelineKey, PipelineRecord>,
+	warm_up: Vec<PipelineKey>,
+	warm_up_total: usize,
+}
+
+impl<P> Default for PipelineCache<P> {
+	fn default() -> Self {
+		Self {
+			pipelines: HashMap::new(),
+			records: BTreeMap::new(),
+			warm_up: Vec::new(),
+			warm_up_total: 0,
+		}
+	}
+}
+
+impl<P> PipelineCache<P> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Loads the cache saved by a previous run. A missing or outdated cache starts empty.
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let contents = match fs::read_to_string(path) {
+			Ok(contents) => contents,
+			Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+			Err(error) => return Err(PipelineError::ReadCache(error, path.display().to_string())),
+		};
+		let file: PipelineCacheFile = ron::from_str(&contents).map_err(|error| PipelineError::ParseCache(error, path.display().to_string()))?;
+		if file.version != PIPELINE_CACHE_VERSION {
+			return Ok(Self::new());
+		}
+		let records = file.records.into_iter().collect::<BTreeMap<_, _>>();
+		let warm_up = records.keys().rev().cloned().collect::<Vec<_>>();
+		Ok(Self {
+			pipelines: HashMap::new(),
+			warm_up_total: warm_up.len(),
+			warm_up,
+			records,
+		})
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+		let path = path.as_ref();
+		let file = PipelineCacheFile {
+			version: PIPELINE_CACHE_VERSION,
+			records: self.records.iter().map(|(key, record)| (key.clone(), record.clone())).collect(),
+		};
+		let contents = ron::ser::to_string_pretty(&file, PrettyConfig::default()).map_err(PipelineError::SerializeCache)?;
+		fs::write(path, contents).map_err(|error| PipelineError::WriteCache(error, path.display().to_string()))
+	}
+
+	pub fn key(library: &ShaderLibrary, descriptor: &PipelineDescriptor) -> Result<(PipelineKey, String)> {
+		let source = library.preprocess(&descriptor.shader, &descriptor.defines)?;
+		let key = PipelineKey {
+			shader_hash: ShaderCache::<()>::content_hash(&source),
+			layout: descriptor.layout.clone(),
+			state: descriptor.state,
+		};
+		Ok((key, source))
+	}
+
+	pub fn get(&self, key: &PipelineKey) -> Option<&P> {
+		self.pipelines.get(key)
+	}
+
+	pub fn len(&self) -> usize {
+		self.pipelines.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.pipelines.is_empty()
+	}
+
+	/// The pipeline for a descriptor, compiling it if it hasn't been built during this run
+	pub fn get_or_create(
+		&mut self,
+		library: &ShaderLibrary,
+		descriptor: &PipelineDescriptor,
+		compile: impl FnOnce(&PipelineSource) -> CompiledPipeline<P>,
+	) -> Result<&P> {
+		let (key, source) = Self::key(library, descriptor)?;
+		if !self.pipelines.contains_key(&key) {
+			let cached_data = self.records.get(&key).map(|record| record.data.clone()).unwrap_or_default();
+			let pipeline_source = PipelineSource {
+				source: &source,
+				descriptor,
+				cached_data: &cached_data,
+			};
+			let (pipeline, data) = compile(&pipeline_source).map_err(|error| PipelineError::CompilePipeline(error, descriptor.shader.clone()))?;
+			self.records.insert(
+				key.clone(),
+				PipelineRecord {
+					descriptor: descriptor.clone(),
+					data,
+				},
+			);
+			self.pipelines.insert(key.clone(), pipeline);
+		}
+		Ok(&self.pipelines[&key])
+	}
+
+	/// Pipelines from the saved cache that haven't been compiled yet
+	pub fn warm_up_remaining(&self) -> usize {
+		self.warm_up.len()
+	}
+
+	/// How much of the saved cache has been compiled, from 0 to 1
+	pub fn warm_up_progress(&self) -> f32 {
+		if self.warm_up_total == 0 {
+			return 1.0;
+		}
+		1.0 - self.warm_up.len() as f32 / self.warm_up_total as f32
+	}
+
+	/// Compiles the next pipeline from the saved cache and returns the warm-up progress.
+	/// Records whose shader changed since they were saved are rebuilt under their new key.
+	pub fn warm_up_next(&mut self, library: &ShaderLibrary, compile: impl FnOnce(&PipelineSource) -> CompiledPipeline<P>) -> Result<f32> {
+		let Some(key) = self.warm_up.pop() else {
+			return Ok(1.0);
+		};
+		if let Some(descriptor) = self.records.get(&key).map(|record| record.descriptor.clone()) {
+			let (current, _) = Self::key(library, &descriptor)?;
+			if current != key {
+				self.records.remove(&key);
+			}
+			self.get_or_create(library, &descriptor, compile)?;
+		}
+		Ok(self.warm_up_progress())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn descriptor(shader: &str) -> PipelineDescriptor {
+		PipelineDescriptor {
+			shader: shader.to_string(),
+			defines: ShaderDefines::new(),
+			layout: "mesh".to_string(),
+			state: RenderState::default(),
+		}
+	}
+
+	/// Compiles to the source's length and remembers what it compiled
+	fn compile(compiled: &mut Vec<(usize, Vec<u8>)>) -> impl FnMut(&PipelineSource) -> CompiledPipeline<usize> + '_ {
+		|source| {
+			compiled.push((source.source.len(), source.cached_data.to_vec()));
+			Ok((source.source.len(), vec![source.source.len() as u8]))
+		}
+	}
+
+	#[test]
+	pub fn persists_between_runs() -> Result<()> {
+		let path = std::env::temp_dir().join(format!("elder_pipeline_cache_{}.ron", std::process::id()));
+		let mut library = ShaderLibrary::empty();
+		library.add("opaque.wgsl", "fn opaque() {}\n");
+		library.add("sky.wgsl", "fn sky() {}\n");
+
+		let mut compiled = Vec::new();
+		let mut cache = PipelineCache::<usize>::load(&path)?;
+		assert_eq!(cache.warm_up_progress(), 1.0);
+		cache.get_or_create(&library, &descriptor("opaque.wgsl"), compile(&mut compiled))?;
+		cache.get_or_create(&library, &descriptor("opaque.wgsl"), compile(&mut compiled))?;
+		cache.get_or_create(&library, &descriptor("sky.wgsl"), compile(&mut compiled))?;
+		assert_eq!(compiled.len(), 2);
+		cache.save(&path)?;
+
+		// The next launch warms up both pipelines, handing back the data saved with them.
+		// The sky shader was edited in between, so its stale data is dropped.
+		library.add("sky.wgsl", "fn sky() { let edited = 1.0; }\n");
+		let mut compiled = Vec::new();
+		let mut cache = PipelineCache::<usize>::load(&path)?;
+		assert_eq!(cache.warm_up_remaining(), 2);
+		assert_eq!(cache.warm_up_next(&library, compile(&mut compiled))?, 0.5);
+		assert_eq!(cache.warm_up_next(&library, compile(&mut compiled))?, 1.0);
+		assert_eq!(cache.warm_up_next(&library, compile(&mut compiled))?, 1.0);
+		compiled.sort();
+		assert_eq!(compiled, [(15, vec![15]), (31, vec![])]);
+		assert_eq!(cache.len(), 2);
+
+		cache.save(&path)?;
+		assert_eq!(PipelineCache::<usize>::load(&path)?.warm_up_remaining(), 2);
+		fs::remove_file(&path).unwrap();
+		Ok(())
+	}
+
+	#[test]
+	pub fn keys() -> Result<()> {
+		let library = ShaderLibrary::default();
+		let mut library_with_mesh = library.clone();
+		library_with_mesh.add("mesh.wgsl", "#include \"ibl.wgsl\"\n");
+		let (opaque, _) = PipelineCache::<()>::key(&library_with_mesh, &descriptor("mesh.wgsl"))?;
+		let transparent = PipelineDescriptor {
+			state: RenderState {
+				blend: BlendMode::Alpha,
+				depth_write: false,
+				..Default::default()
+			},
+			..descriptor("mesh.wgsl")
+		};
+		let (transparent, _) = PipelineCache::<()>::key(&library_with_mesh, &transparent)?;
+		assert_eq!(opaque.shader_hash, transparent.shader_hash);
+		assert_ne!(opaque, transparent);
+		assert!(matches!(PipelineCache::<()>::key(&library, &descriptor("mesh.wgsl")), Err(PipelineError::Shader(_))));
+		Ok(())
+	}
+}
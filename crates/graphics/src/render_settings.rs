@@ -0,0 +1,99 @@
+use math::Real;
+
+pub const MIN_RENDER_SCALE: Real = 0.5;
+pub const MAX_RENDER_SCALE: Real = 2.0;
+
+/// The MSAA sample counts every backend supports for color and depth targets
+pub const SUPPORTED_SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// Anti-aliasing and resolution options for the renderer.
+///
+/// The scene is rendered at the window size times the render scale and resampled to the window,
+/// so scales above 1 supersample and scales below 1 trade sharpness for speed.
+/// UI is expected to be drawn at the window's own resolution after upsampling.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderSettings {
+	msaa_samples: u32,
+	render_scale: Real,
+}
+
+impl Default for RenderSettings {
+	fn default() -> Self {
+		Self {
+			msaa_samples: 1,
+			render_scale: 1.0,
+		}
+	}
+}
+
+impl RenderSettings {
+	/// Rounds sample counts down to a supported count and clamps the scale
+	pub fn new(msaa_samples: u32, render_scale: Real) -> Self {
+		let mut settings = Self::default();
+		settings.set_msaa_samples(msaa_samples);
+		settings.set_render_scale(render_scale);
+		settings
+	}
+
+	pub fn msaa_samples(&self) -> u32 {
+		self.msaa_samples
+	}
+
+	pub fn set_msaa_samples(&mut self, samples: u32) {
+		self.msaa_samples = SUPPORTED_SAMPLE_COUNTS.into_iter().rev().find(|supported| *supported <= samples).unwrap_or(1);
+	}
+
+	pub fn is_multisampled(&self) -> bool {
+		self.msaa_samples > 1
+	}
+
+	pub fn render_scale(&self) -> Real {
+		self.render_scale
+	}
+
+	pub fn set_render_scale(&mut self, scale: Real) {
+		self.render_scale = if scale.is_finite() { scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE) } else { 1.0 };
+	}
+
+	/// The size of the targets the scene is rendered into for a window of the given size
+	pub fn render_size(&self, width: u32, height: u32) -> (u32, u32) {
+		let scale = |size: u32| ((size as Real * self.render_scale).round() as u32).max(1);
+		(scale(width), scale(height))
+	}
+
+	/// Whether the scene has to be resampled to the window's resolution after rendering
+	pub fn needs_resample(&self) -> bool {
+		self.render_scale != 1.0
+	}
+
+	/// Applies the sample count to a pipeline, which must match its target's sample count
+	pub fn apply(&self, state: crate::RenderState) -> crate::RenderState {
+		crate::RenderState {
+			sample_count: self.msaa_samples,
+			..state
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::RenderState;
+
+	#[test]
+	pub fn sanitizes() {
+		let settings = RenderSettings::new(6, 3.0);
+		assert_eq!((settings.msaa_samples(), settings.render_scale()), (4, MAX_RENDER_SCALE));
+		assert_eq!(RenderSettings::new(0, 0.1).msaa_samples(), 1);
+		assert_eq!(RenderSettings::new(16, Real::NAN), RenderSettings::new(8, 1.0));
+		assert!(!RenderSettings::default().needs_resample());
+		assert_eq!(RenderSettings::new(4, 1.0).apply(RenderState::default()).sample_count, 4);
+	}
+
+	#[test]
+	pub fn render_size() {
+		assert_eq!(RenderSettings::new(1, 0.5).render_size(1920, 1080), (960, 540));
+		assert_eq!(RenderSettings::new(1, 1.5).render_size(1280, 720), (1920, 1080));
+		assert_eq!(RenderSettings::new(1, 0.5).render_size(1, 0), (1, 1));
+	}
+}
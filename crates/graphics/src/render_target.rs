@@ -0,0 +1,336 @@
+use crate::{culling::RenderCamera, instancing::MaterialHandle};
+use handle::{Handle, SlotMap};
+use math::{Camera, Extent2D, Projection, Viewport};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Frames a target can go without a camera or a material sampling it before its texture is released
+pub const RELEASE_AFTER_FRAMES: u32 = 120;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum RenderTargetError {
+	#[error("Failed to create render target '{0}' because a render target can't be {1}x{2}!")]
+	InvalidSize(String, u32, u32),
+
+	#[error("Failed to find render target {0:?}")]
+	UnknownTarget(RenderTargetHandle),
+}
+
+type Result<T, E = RenderTargetError> = std::result::Result<T, E>;
+
+/// Identifies an offscreen texture a camera renders into
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderTargetHandle(Handle<RenderTarget>);
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RenderTargetFormat {
+	/// For targets sampled like any other color texture, such as security monitors and minimaps
+	#[default]
+	Rgba8Srgb,
+
+	/// For targets that keep HDR values, such as mirrors and portals shown before tonemapping
+	Rgba16Float,
+}
+
+impl RenderTargetFormat {
+	pub fn bytes_per_pixel(&self) -> u32 {
+		match self {
+			Self::Rgba8Srgb => 4,
+			Self::Rgba16Float => 8,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderTargetDescriptor {
+	pub name: String,
+	pub width: u32,
+	pub height: u32,
+	pub format: RenderTargetFormat,
+	pub has_depth: bool,
+
+	/// Frames between renders, so distant monitors can update less often than every frame
+	pub refresh_interval: u32,
+}
+
+impl RenderTargetDescriptor {
+	pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+		Self {
+			name: name.into(),
+			width,
+			height,
+			format: RenderTargetFormat::default(),
+			has_depth: true,
+			refresh_interval: 1,
+		}
+	}
+
+	pub fn extent(&self) -> Extent2D {
+		Extent2D::new(self.width as f32, self.height as f32)
+	}
+
+	/// The memory the texture and its depth buffer take up on the GPU
+	pub fn byte_size(&self) -> u64 {
+		let depth = if self.has_depth { 4 } else { 0 };
+		u64::from(self.width) * u64::from(self.height) * u64::from(self.format.bytes_per_pixel() + depth)
+	}
+}
+
+/// A camera that renders into the render target named by `target` instead of the window.
+/// The editor creates the target when the component is added and moves the camera along.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderTextureCamera {
+	pub target: String,
+	pub width: u32,
+	pub height: u32,
+	pub projection: Projection,
+	pub refresh_interval: u32,
+}
+
+impl RenderTextureCamera {
+	pub fn new(target: impl Into<String>, width: u32, height: u32) -> Self {
+		Self {
+			target: target.into(),
+			width,
+			height,
+			projection: Projection::default(),
+			refresh_interval: 1,
+		}
+	}
+
+	pub fn descriptor(&self) -> RenderTargetDescriptor {
+		RenderTargetDescriptor {
+			refresh_interval: self.refresh_interval,
+			..RenderTargetDescriptor::new(self.target.clone(), self.width, self.height)
+		}
+	}
+}
+
+/// Renders the world from a camera into a target before the frame's main views
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderTargetPass {
+	pub target: RenderTargetHandle,
+	pub camera: RenderCamera,
+	pub viewport: Viewport,
+}
+
+/// What the renderer does with its offscreen targets this frame
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RenderTargetFrame {
+	pub passes: Vec<RenderTargetPass>,
+
+	/// Targets whose textures can be destroyed, since they went unused for a while or were removed
+	pub released: Vec<RenderTargetHandle>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RenderTarget {
+	descriptor: RenderTargetDescriptor,
+	camera: Option<Camera>,
+	last_rendered: Option<u64>,
+	unused_frames: u32,
+}
+
+/// The offscreen targets and the materials that sample them, stored as a resource.
+/// A target only renders while it has a camera and a material samples it.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RenderTargets {
+	targets: SlotMap<RenderTarget>,
+	materials: BTreeMap<MaterialHandle, RenderTargetHandle>,
+	released: Vec<RenderTargetHandle>,
+}
+
+impl RenderTargets {
+	pub fn create(&mut self, descriptor: RenderTargetDescriptor) -> Result<RenderTargetHandle> {
+		if descriptor.width == 0 || descriptor.height == 0 {
+			return Err(RenderTargetError::InvalidSize(descriptor.name, descriptor.width, descriptor.height));
+		}
+		Ok(RenderTargetHandle(self.targets.insert(RenderTarget {
+			descriptor,
+			camera: None,
+			last_rendered: None,
+			unused_frames: 0,
+		})))
+	}
+
+	/// Destroys a target, leaving the materials that sampled it without one
+	pub fn remove(&mut self, handle: RenderTargetHandle) -> Result<()> {
+		self.targets.remove(handle.0).ok_or(RenderTargetError::UnknownTarget(handle))?;
+		self.materials.retain(|_, target| *target != handle);
+		self.released.push(handle);
+		Ok(())
+	}
+
+	pub fn descriptor(&self, handle: RenderTargetHandle) -> Option<&RenderTargetDescriptor> {
+		self.targets.get(handle.0).map(|target| &target.descriptor)
+	}
+
+	pub fn find(&self, name: &str) -> Option<RenderTargetHandle> {
+		self.targets
+			.iter()
+			.find(|(_, target)| target.descriptor.name == name)
+			.map(|(handle, _)| RenderTargetHandle(handle))
+	}
+
+	/// The target with a descriptor's name, created or resized to match it
+	pub fn find_or_create(&mut self, descriptor: RenderTargetDescriptor) -> Result<RenderTargetHandle> {
+		match self.find(&descriptor.name) {
+			Some(handle) => {
+				if let Some(target) = self.targets.get_mut(handle.0) {
+					target.descriptor = descriptor;
+				}
+				Ok(handle)
+			},
+			None => self.create(descriptor),
+		}
+	}
+
+	pub fn set_camera(&mut self, handle: RenderTargetHandle, camera: Option<Camera>) -> Result<()> {
+		self.target_mut(handle)?.camera = camera;
+		Ok(())
+	}
+
+	/// Makes a material sample the target, replacing the target it sampled before
+	pub fn assign(&mut self, material: MaterialHandle, handle: RenderTargetHandle) -> Result<()> {
+		self.target_mut(handle)?.unused_frames = 0;
+		self.materials.insert(material, handle);
+		Ok(())
+	}
+
+	pub fn unassign(&mut self, material: MaterialHandle) -> Option<RenderTargetHandle> {
+		self.materials.remove(&material)
+	}
+
+	/// The target a material samples
+	pub fn target_of(&self, material: MaterialHandle) -> Option<RenderTargetHandle> {
+		self.materials.get(&material).copied()
+	}
+
+	pub fn materials_sampling(&self, handle: RenderTargetHandle) -> impl Iterator<Item = MaterialHandle> + '_ {
+		self.materials.iter().filter(move |(_, target)| **target == handle).map(|(material, _)| *material)
+	}
+
+	pub fn handles(&self) -> impl Iterator<Item = RenderTargetHandle> + '_ {
+		self.targets.handles().iter().copied().map(RenderTargetHandle)
+	}
+
+	pub fn len(&self) -> usize {
+		self.targets.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.targets.is_empty()
+	}
+
+	/// Picks the targets to render this frame and releases those unused for `RELEASE_AFTER_FRAMES`.
+	/// Due targets render at their interval, counted from the frame they last rendered.
+	pub fn prepare(&mut self, frame: u64) -> RenderTargetFrame {
+		let sampled = self.materials.values().copied().collect::<Vec<_>>();
+		let mut output = RenderTargetFrame {
+			released: std::mem::take(&mut self.released),
+			..RenderTargetFrame::default()
+		};
+		for (handle, target) in self.targets.iter_mut() {
+			let handle = RenderTargetHandle(handle);
+			let is_sampled = sampled.contains(&handle);
+			if target.camera.is_none() && !is_sampled {
+				target.unused_frames += 1;
+				if target.unused_frames >= RELEASE_AFTER_FRAMES {
+					output.released.push(handle);
+				}
+				continue;
+			}
+			target.unused_frames = 0;
+			let interval = u64::from(target.descriptor.refresh_interval.max(1));
+			let is_due = target.last_rendered.is_none_or(|last| frame.saturating_sub(last) >= interval);
+			let Some(camera) = target.camera.filter(|_| is_sampled && is_due) else {
+				continue;
+			};
+			target.last_rendered = Some(frame);
+			let viewport = Viewport::from_extent(target.descriptor.extent());
+			output.passes.push(RenderTargetPass {
+				target: handle,
+				camera: RenderCamera::new(camera.position, camera.view_projection(viewport.aspect_ratio())),
+				viewport,
+			});
+		}
+		for handle in output.released.iter() {
+			self.targets.remove(handle.0);
+			self.materials.retain(|_, target| target != handle);
+		}
+		output
+	}
+
+	fn target_mut(&mut self, handle: RenderTargetHandle) -> Result<&mut RenderTarget> {
+		self.targets.get_mut(handle.0).ok_or(RenderTargetError::UnknownTarget(handle))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn only_sampled_targets_render() -> Result<()> {
+		let mut targets = RenderTargets::default();
+		let monitor = targets.create(RenderTargetDescriptor::new("Monitor", 256, 128))?;
+		let minimap = targets.create(RenderTargetDescriptor {
+			refresh_interval: 3,
+			..RenderTargetDescriptor::new("Minimap", 128, 128)
+		})?;
+		targets.set_camera(monitor, Some(Camera::default()))?;
+		targets.set_camera(minimap, Some(Camera::default()))?;
+		assert!(targets.prepare(0).passes.is_empty());
+
+		targets.assign(MaterialHandle(1), monitor)?;
+		targets.assign(MaterialHandle(2), minimap)?;
+		targets.assign(MaterialHandle(3), minimap)?;
+		assert_eq!(targets.materials_sampling(minimap).collect::<Vec<_>>(), [MaterialHandle(2), MaterialHandle(3)]);
+		let rendered = |frame: &RenderTargetFrame| frame.passes.iter().map(|pass| pass.target).collect::<Vec<_>>();
+		let frame = targets.prepare(1);
+		assert_eq!(rendered(&frame), [monitor, minimap]);
+		assert_eq!(frame.passes[0].camera.view_projection, Camera::default().view_projection(2.0));
+		assert_eq!(frame.passes[0].viewport.rect.extent(), Extent2D::new(256.0, 128.0));
+		assert_eq!(rendered(&targets.prepare(2)), [monitor]);
+		assert_eq!(rendered(&targets.prepare(3)), [monitor]);
+		assert_eq!(rendered(&targets.prepare(4)), [monitor, minimap]);
+		Ok(())
+	}
+
+	#[test]
+	pub fn lifetime() -> Result<()> {
+		let mut targets = RenderTargets::default();
+		let mirror = targets.create(RenderTargetDescriptor::new("Mirror", 64, 64))?;
+		let portal = targets.create(RenderTargetDescriptor::new("Portal", 64, 64))?;
+		targets.assign(MaterialHandle(0), mirror)?;
+		for frame in 0..u64::from(RELEASE_AFTER_FRAMES) - 1 {
+			assert!(targets.prepare(frame).released.is_empty());
+		}
+		assert_eq!(targets.prepare(1000).released, [portal]);
+		assert_eq!(targets.find("Portal"), None);
+		assert_eq!(targets.find("Mirror"), Some(mirror));
+
+		targets.remove(mirror)?;
+		assert_eq!(targets.target_of(MaterialHandle(0)), None);
+		assert_eq!(targets.prepare(1001).released, [mirror]);
+		assert!(targets.is_empty());
+		assert_eq!(targets.set_camera(mirror, None), Err(RenderTargetError::UnknownTarget(mirror)));
+		assert_eq!(
+			targets.create(RenderTargetDescriptor::new("Empty", 0, 64)),
+			Err(RenderTargetError::InvalidSize("Empty".to_string(), 0, 64))
+		);
+		Ok(())
+	}
+
+	#[test]
+	pub fn find_or_create() -> Result<()> {
+		let mut targets = RenderTargets::default();
+		let camera = RenderTextureCamera::new("Security", 320, 240);
+		let handle = targets.find_or_create(camera.descriptor())?;
+		let resized = RenderTextureCamera { width: 640, ..camera };
+		assert_eq!(targets.find_or_create(resized.descriptor())?, handle);
+		assert_eq!(targets.descriptor(handle).map(|descriptor| descriptor.width), Some(640));
+		assert_eq!(targets.descriptor(handle).map(RenderTargetDescriptor::byte_size), Some(640 * 240 * 8));
+		Ok(())
+	}
+}
@@ -0,0 +1,315 @@
+use crate::{
+	clustered::{CLUSTERED_LIGHTING_SHADER, CLUSTERS_SHADER, LIGHT_CULLING_SHADER},
+	environment::IBL_SHADER,
+	fog::FOG_SHADER,
+};
+use std::{
+	collections::{
+		BTreeMap, BTreeSet, HashMap,
+		hash_map::{DefaultHasher, Entry},
+	},
+	hash::{Hash, Hasher},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ShaderError {
+	#[error("Shader '{0}' includes itself through '{1}'")]
+	IncludeCycle(String, String),
+
+	#[error("Shader '{0}' has a malformed directive on line {1}: {2}")]
+	MalformedDirective(String, usize, String),
+
+	#[error("Shader '{1}' includes '{0}', which does not exist")]
+	MissingInclude(String, String),
+
+	#[error("Shader '{0}' does not exist")]
+	MissingShader(String),
+
+	#[error("Shader '{0}' has an #ifdef or #ifndef without a matching #endif")]
+	UnterminatedConditional(String),
+}
+
+type Result<T, E = ShaderError> = std::result::Result<T, E>;
+
+/// Defines for one variant of a pipeline, such as `SHADOWS` or `MAX_LIGHTS = 16`.
+/// Directives test whether a define is set, and defines with a value replace that name in the code.
+pub type ShaderDefines = BTreeMap<String, String>;
+
+/// WGSL sources by name, preprocessed before they are compiled.
+///
+/// Sources can use `#include "name"` to pull in shared code once per shader, `#define NAME value`,
+/// and `#ifdef NAME`, `#ifndef NAME`, `#else`, and `#endif` to build variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderLibrary {
+	sources: BTreeMap<String, String>,
+}
+
+/// Includes the engine's shared shader code, such as `ibl.wgsl` and `clustered_lighting.wgsl`
+impl Default for ShaderLibrary {
+	fn default() -> Self {
+		let mut library = Self::empty();
+		library.add("ibl.wgsl", IBL_SHADER);
+		library.add("fog.wgsl", FOG_SHADER);
+		library.add("clusters.wgsl", CLUSTERS_SHADER);
+		library.add("light_culling.wgsl", LIGHT_CULLING_SHADER);
+		library.add("clustered_lighting.wgsl", CLUSTERED_LIGHTING_SHADER);
+		library
+	}
+}
+
+impl ShaderLibrary {
+	pub fn empty() -> Self {
+		Self { sources: BTreeMap::new() }
+	}
+
+	/// Adds or replaces a source, such as after a shader file changes on disk
+	pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) {
+		self.sources.insert(name.into(), source.into());
+	}
+
+	pub fn get(&self, name: &str) -> Option<&str> {
+		self.sources.get(name).map(String::as_str)
+	}
+
+	/// Resolves includes and directives, producing WGSL ready to compile
+	pub fn preprocess(&self, name: &str, defines: &ShaderDefines) -> Result<String> {
+		let source = self.get(name).ok_or_else(|| ShaderError::MissingShader(name.to_string()))?;
+		let mut preprocessor = Preprocessor {
+			library: self,
+			defines: defines.clone(),
+			included: BTreeSet::from([name.to_string()]),
+			stack: vec![name.to_string()],
+			output: String::new(),
+		};
+		preprocessor.process(name, source)?;
+		Ok(preprocessor.output)
+	}
+}
+
+struct Preprocessor<'a> {
+	library: &'a ShaderLibrary,
+	defines: ShaderDefines,
+	included: BTreeSet<String>,
+
+	/// The chain of files currently being included, for reporting cycles
+	stack: Vec<String>,
+	output: String,
+}
+
+impl Preprocessor<'_> {
+	fn process(&mut self, name: &str, source: &str) -> Result<()> {
+		// Whether each enclosing conditional's active branch is being emitted
+		let mut conditions: Vec<bool> = Vec::new();
+		for (index, line) in source.lines().enumerate() {
+			let malformed = || ShaderError::MalformedDirective(name.to_string(), index + 1, line.trim().to_string());
+			let is_active = conditions.iter().all(|condition| *condition);
+			let Some(directive) = line.trim_start().strip_prefix('#') else {
+				if is_active {
+					self.output.push_str(&self.substitute(line));
+					self.output.push('\n');
+				}
+				continue;
+			};
+			let (keyword, argument) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+			let argument = argument.trim();
+			match keyword {
+				"ifdef" | "ifndef" if !argument.is_empty() => conditions.push(self.defines.contains_key(argument) == (keyword == "ifdef")),
+				"else" => {
+					let condition = conditions.last_mut().ok_or_else(malformed)?;
+					*condition = !*condition;
+				},
+				"endif" => {
+					conditions.pop().ok_or_else(malformed)?;
+				},
+				_ if !is_active => {},
+				"define" if !argument.is_empty() => {
+					let (define, value) = argument.split_once(char::is_whitespace).unwrap_or((argument, ""));
+					self.defines.insert(define.to_string(), value.trim().to_string());
+				},
+				"include" => {
+					let include = argument.strip_prefix('"').and_then(|argument| argument.strip_suffix('"')).ok_or_else(malformed)?;
+					self.include(name, include)?;
+				},
+				_ => return Err(malformed()),
+			}
+		}
+		if !conditions.is_empty() {
+			return Err(ShaderError::UnterminatedConditional(name.to_string()));
+		}
+		Ok(())
+	}
+
+	fn include(&mut self, name: &str, include: &str) -> Result<()> {
+		if self.stack.iter().any(|parent| parent == include) {
+			return Err(ShaderError::IncludeCycle(self.stack[0].clone(), include.to_string()));
+		}
+		if !self.included.insert(include.to_string()) {
+			return Ok(());
+		}
+		let library = self.library;
+		let source = library
+			.get(include)
+			.ok_or_else(|| ShaderError::MissingInclude(include.to_string(), name.to_string()))?;
+		self.stack.push(include.to_string());
+		self.process(include, source)?;
+		self.stack.pop();
+		Ok(())
+	}
+
+	/// Replaces whole identifiers that name a define with a value
+	fn substitute(&self, line: &str) -> String {
+		let mut output = String::with_capacity(line.len());
+		let mut rest = line;
+		while let Some(start) = rest.find(|character: char| character.is_alphabetic() || character == '_') {
+			output.push_str(&rest[..start]);
+			rest = &rest[start..];
+			let end = rest.find(|character: char| !(character.is_alphanumeric() || character == '_')).unwrap_or(rest.len());
+			let identifier = &rest[..end];
+			match self.defines.get(identifier).filter(|value| !value.is_empty()) {
+				Some(value) => output.push_str(value),
+				None => output.push_str(identifier),
+			}
+			rest = &rest[end..];
+		}
+		output.push_str(rest);
+		output
+	}
+}
+
+/// Compiled shader modules keyed by a hash of their preprocessed source,
+/// so variants with the same code share a module and unchanged shaders are never recompiled
+pub struct ShaderCache<M> {
+	modules: HashMap<u64, M>,
+}
+
+impl<M> Default for ShaderCache<M> {
+	fn default() -> Self {
+		Self { modules: HashMap::new() }
+	}
+}
+
+impl<M> ShaderCache<M> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn content_hash(source: &str) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		source.hash(&mut hasher);
+		hasher.finish()
+	}
+
+	/// The module compiled from `source`, compiling it if this is the first time it is seen
+	pub fn get_or_compile<E>(&mut self, source: &str, compile: impl FnOnce(&str) -> Result<M, E>) -> Result<&M, E> {
+		match self.modules.entry(Self::content_hash(source)) {
+			Entry::Occupied(entry) => Ok(entry.into_mut()),
+			Entry::Vacant(entry) => Ok(entry.insert(compile(source)?)),
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.modules.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.modules.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.modules.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn defines(names: &[(&str, &str)]) -> ShaderDefines {
+		names.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+	}
+
+	fn library() -> ShaderLibrary {
+		let mut library = ShaderLibrary::default();
+		library.add("common.wgsl", "const PI: f32 = 3.14159;\n");
+		library.add(
+			"lighting.wgsl",
+			"#include \"common.wgsl\"\nfn attenuation(distance: f32) -> f32 {\n    return 1.0 / (PI * distance * distance);\n}\n",
+		);
+		library.add(
+			"mesh.wgsl",
+			concat!(
+				"#include \"common.wgsl\"\n",
+				"#include \"lighting.wgsl\"\n",
+				"const MAX_LIGHTS: u32 = LIGHT_COUNT;\n",
+				"@fragment\n",
+				"fn main() -> @location(0) vec4<f32> {\n",
+				"#ifdef SHADOWS\n",
+				"    let light = attenuation(2.0) * 0.5;\n",
+				"#else\n",
+				"    let light = attenuation(2.0);\n",
+				"#endif\n",
+				"    return vec4<f32>(light, light, light, f32(MAX_LIGHTS));\n",
+				"}\n",
+			),
+		);
+		library
+	}
+
+	fn validate(source: &str) {
+		let module = naga::front::wgsl::parse_str(source).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+
+	#[test]
+	pub fn variants() -> Result<()> {
+		let library = library();
+		let lit = library.preprocess("mesh.wgsl", &defines(&[("LIGHT_COUNT", "4u")]))?;
+		let shadowed = library.preprocess("mesh.wgsl", &defines(&[("LIGHT_COUNT", "4u"), ("SHADOWS", "")]))?;
+		assert_eq!(lit.matches("const PI").count(), 1);
+		assert!(lit.contains("const MAX_LIGHTS: u32 = 4u;"));
+		assert!(!lit.contains("* 0.5") && shadowed.contains("* 0.5"));
+		validate(&lit);
+		validate(&shadowed);
+		Ok(())
+	}
+
+	#[test]
+	pub fn errors() {
+		let mut library = ShaderLibrary::empty();
+		library.add("a.wgsl", "#include \"b.wgsl\"\n");
+		library.add("b.wgsl", "#include \"a.wgsl\"\n");
+		library.add("missing.wgsl", "#include \"nowhere.wgsl\"\n");
+		library.add("open.wgsl", "#ifdef X\n");
+		library.add("typo.wgsl", "\n#inclde \"a.wgsl\"\n");
+		let none = ShaderDefines::new();
+		assert_eq!(
+			library.preprocess("a.wgsl", &none),
+			Err(ShaderError::IncludeCycle("a.wgsl".to_string(), "a.wgsl".to_string()))
+		);
+		assert!(matches!(library.preprocess("missing.wgsl", &none), Err(ShaderError::MissingInclude(..))));
+		assert!(matches!(library.preprocess("open.wgsl", &none), Err(ShaderError::UnterminatedConditional(_))));
+		assert!(matches!(library.preprocess("typo.wgsl", &none), Err(ShaderError::MalformedDirective(_, 2, _))));
+		assert!(matches!(library.preprocess("other.wgsl", &none), Err(ShaderError::MissingShader(_))));
+	}
+
+	#[test]
+	pub fn cache() -> Result<()> {
+		let library = library();
+		let mut cache = ShaderCache::new();
+		let mut compiles = 0;
+		// The shadow define is unused by the lighting file, so both variants share one module
+		for variant in [defines(&[]), defines(&[("SHADOWS", "")]), defines(&[])] {
+			let source = library.preprocess("lighting.wgsl", &variant)?;
+			let module = cache.get_or_compile(&source, |source| {
+				compiles += 1;
+				Ok::<_, ()>(source.len())
+			});
+			assert_eq!(module, Ok(&source.len()));
+		}
+		assert_eq!((compiles, cache.len()), (1, 1));
+		Ok(())
+	}
+}
@@ -0,0 +1,82 @@
+// `mode` is the index of a `BillboardMode` variant
+struct Billboard {
+	position: vec3<f32>,
+	mode: u32,
+	size: vec2<f32>,
+	pivot: vec2<f32>,
+	uv_offset: vec2<f32>,
+	uv_scale: vec2<f32>,
+	color: vec4<f32>,
+}
+
+struct Camera {
+	view_projection: mat4x4<f32>,
+	position: vec3<f32>,
+	padding0: f32,
+	right: vec3<f32>,
+	padding1: f32,
+	up: vec3<f32>,
+	padding2: f32,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) color: vec4<f32>,
+	@location(1) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<storage, read> billboards: array<Billboard>;
+
+@group(0) @binding(1)
+var<uniform> camera: Camera;
+
+@group(1) @binding(0)
+var sprite_texture: texture_2d<f32>;
+
+@group(1) @binding(1)
+var sprite_sampler: sampler;
+
+// Draw six vertices per billboard, with one instance per billboard
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32, @builtin(instance_index) instance_index: u32) -> VertexOutput {
+	var corners = array<vec2<f32>, 6>(
+		vec2<f32>(0.0, 0.0),
+		vec2<f32>(1.0, 0.0),
+		vec2<f32>(1.0, 1.0),
+		vec2<f32>(0.0, 0.0),
+		vec2<f32>(1.0, 1.0),
+		vec2<f32>(0.0, 1.0),
+	);
+	let corner = corners[vertex_index];
+	let billboard = billboards[instance_index];
+
+	var right = camera.right;
+	var up = camera.up;
+	if billboard.mode == 1u {
+		// Cylindrical billboards only turn around the world's up axis, so trees stay upright
+		up = vec3<f32>(0.0, 1.0, 0.0);
+		let to_camera = camera.position - billboard.position;
+		let horizontal = vec3<f32>(to_camera.x, 0.0, to_camera.z);
+		if dot(horizontal, horizontal) > 1e-8 {
+			right = normalize(cross(up, horizontal));
+		}
+	}
+	let offset = (corner - billboard.pivot) * billboard.size;
+	let world_position = billboard.position + right * offset.x + up * offset.y;
+
+	var output: VertexOutput;
+	output.position = camera.view_projection * vec4<f32>(world_position, 1.0);
+	output.color = billboard.color;
+	output.uv = billboard.uv_offset + vec2<f32>(corner.x, 1.0 - corner.y) * billboard.uv_scale;
+	return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let color = textureSample(sprite_texture, sprite_sampler, input.uv) * input.color;
+	if color.a < 0.01 {
+		discard;
+	}
+	return color;
+}
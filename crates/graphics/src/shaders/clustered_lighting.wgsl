@@ -0,0 +1,44 @@
+#include "clusters.wgsl"
+
+@group(2) @binding(0)
+var<uniform> clusters: Clusters;
+
+@group(2) @binding(1)
+var<storage, read> lights: array<Light>;
+
+@group(2) @binding(2)
+var<storage, read> light_counts: array<u32>;
+
+@group(2) @binding(3)
+var<storage, read> light_indices: array<u32>;
+
+// Smoothly reaches zero at the light's range instead of cutting off
+fn range_attenuation(distance: f32, range: f32) -> f32 {
+	let ratio = distance / range;
+	let window = clamp(1.0 - ratio * ratio * ratio * ratio, 0.0, 1.0);
+	return window * window / (distance * distance + 1.0);
+}
+
+// The diffuse light reaching a surface from the lights in its cluster, where `frag_coord` is the fragment's position in pixels
+fn clustered_lighting(world_position: vec3<f32>, normal: vec3<f32>, frag_coord: vec2<f32>) -> vec3<f32> {
+	let view_position = (clusters.view * vec4<f32>(world_position, 1.0)).xyz;
+	let tiles = vec2<f32>(clusters.dimensions.xy);
+	let tile = min(vec2<u32>(frag_coord / clusters.screen_size * tiles), clusters.dimensions.xy - 1u);
+	let cluster = vec3<u32>(tile.x, clusters.dimensions.y - 1u - tile.y, depth_slice(clusters, -view_position.z));
+	let index = cluster_index(clusters, cluster);
+	let first = index * clusters.dimensions.w;
+
+	var total = vec3<f32>(0.0);
+	for (var offset = 0u; offset < light_counts[index]; offset = offset + 1u) {
+		let light = lights[light_indices[first + offset]];
+		let to_light = light.position - world_position;
+		let distance = length(to_light);
+		let direction = to_light / max(distance, 1e-4);
+		var attenuation = range_attenuation(distance, light.range);
+		if light.kind == 1u {
+			attenuation = attenuation * smoothstep(light.cos_outer, light.cos_inner, dot(-direction, light.direction));
+		}
+		total = total + light.color * light.intensity * attenuation * max(dot(normal, direction), 0.0);
+	}
+	return total;
+}
@@ -0,0 +1,82 @@
+// Shared by the light culling pass and the clustered lighting include.
+// `kind` is 0 for point lights and 1 for spot lights, whose cone is described by the cosines of its angles.
+struct Light {
+	position: vec3<f32>,
+	range: f32,
+	color: vec3<f32>,
+	intensity: f32,
+	direction: vec3<f32>,
+	cos_outer: f32,
+	cos_inner: f32,
+	kind: u32,
+	padding: vec2<f32>,
+}
+
+// The view frustum split into `dimensions.xyz` clusters, holding at most `dimensions.w` lights each.
+// `half_height` is the tangent of half the field of view for perspective cameras and half the view's height for orthographic ones.
+struct Clusters {
+	view: mat4x4<f32>,
+	dimensions: vec4<u32>,
+	near: f32,
+	far: f32,
+	half_height: f32,
+	aspect_ratio: f32,
+	light_count: u32,
+	perspective: u32,
+	screen_size: vec2<f32>,
+}
+
+struct ClusterBounds {
+	min: vec3<f32>,
+	max: vec3<f32>,
+}
+
+// Depth slices get thicker with distance, so clusters stay roughly cube shaped
+fn slice_depth(clusters: Clusters, slice: f32) -> f32 {
+	return clusters.near * pow(clusters.far / clusters.near, slice / f32(clusters.dimensions.z));
+}
+
+fn depth_slice(clusters: Clusters, depth: f32) -> u32 {
+	let slice = log(max(depth, clusters.near) / clusters.near) / log(clusters.far / clusters.near) * f32(clusters.dimensions.z);
+	return min(u32(max(slice, 0.0)), clusters.dimensions.z - 1u);
+}
+
+fn cluster_index(clusters: Clusters, cluster: vec3<u32>) -> u32 {
+	return cluster.x + (cluster.y + cluster.z * clusters.dimensions.y) * clusters.dimensions.x;
+}
+
+fn cluster_coordinates(clusters: Clusters, index: u32) -> vec3<u32> {
+	let tiles = clusters.dimensions.x * clusters.dimensions.y;
+	return vec3<u32>(index % clusters.dimensions.x, (index % tiles) / clusters.dimensions.x, index / tiles);
+}
+
+// The view space box around a cluster, where the camera looks down -z and tile y counts up from the bottom of the screen
+fn cluster_bounds(clusters: Clusters, cluster: vec3<u32>) -> ClusterBounds {
+	let tiles = vec2<f32>(clusters.dimensions.xy);
+	let tile_min = vec2<f32>(cluster.xy) / tiles * 2.0 - 1.0;
+	let tile_max = vec2<f32>(cluster.xy + 1u) / tiles * 2.0 - 1.0;
+	let near_depth = slice_depth(clusters, f32(cluster.z));
+	let far_depth = slice_depth(clusters, f32(cluster.z + 1u));
+	var near_scale = clusters.half_height;
+	var far_scale = clusters.half_height;
+	if clusters.perspective != 0u {
+		near_scale = near_scale * near_depth;
+		far_scale = far_scale * far_depth;
+	}
+	let scale = vec2<f32>(clusters.aspect_ratio, 1.0);
+	let near_min = tile_min * scale * near_scale;
+	let near_max = tile_max * scale * near_scale;
+	let far_min = tile_min * scale * far_scale;
+	let far_max = tile_max * scale * far_scale;
+	var bounds: ClusterBounds;
+	bounds.min = vec3<f32>(min(near_min, far_min), -far_depth);
+	bounds.max = vec3<f32>(max(near_max, far_max), -near_depth);
+	return bounds;
+}
+
+// Spot lights are tested with the sphere around their cone, which only costs a few extra lights at the edges of the cone
+fn light_touches_cluster(bounds: ClusterBounds, center: vec3<f32>, range: f32) -> bool {
+	let closest = clamp(center, bounds.min, bounds.max);
+	let offset = closest - center;
+	return dot(offset, offset) <= range * range;
+}
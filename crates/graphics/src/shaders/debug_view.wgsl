@@ -0,0 +1,108 @@
+// `mode` is the index of a `RenderDebugMode` variant
+struct DebugView {
+	mode: u32,
+	near: f32,
+	far: f32,
+	checker_scale: f32,
+}
+
+struct Camera {
+	view_projection: mat4x4<f32>,
+}
+
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) normal: vec3<f32>,
+	@location(2) uv: vec2<f32>,
+	@location(3) model0: vec4<f32>,
+	@location(4) model1: vec4<f32>,
+	@location(5) model2: vec4<f32>,
+	@location(6) model3: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) normal: vec3<f32>,
+	@location(1) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@group(0) @binding(1)
+var<uniform> debug_view: DebugView;
+
+@group(0) @binding(2)
+var overdraw_texture: texture_2d<f32>;
+
+@vertex
+fn vertex_main(input: VertexInput) -> VertexOutput {
+	let model = mat4x4<f32>(input.model0, input.model1, input.model2, input.model3);
+	var output: VertexOutput;
+	output.position = camera.view_projection * model * vec4<f32>(input.position, 1.0);
+	output.normal = normalize((model * vec4<f32>(input.normal, 0.0)).xyz);
+	output.uv = input.uv;
+	return output;
+}
+
+fn linear_depth(depth: f32) -> f32 {
+	let distance = debug_view.near * debug_view.far / (debug_view.far - depth * (debug_view.far - debug_view.near));
+	return (distance - debug_view.near) / (debug_view.far - debug_view.near);
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	switch debug_view.mode {
+		case 1u: {
+			return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+		}
+		case 2u: {
+			return vec4<f32>(normalize(input.normal) * 0.5 + 0.5, 1.0);
+		}
+		case 3u: {
+			let cell = floor(input.uv * debug_view.checker_scale);
+			let parity = abs(cell.x + cell.y) % 2.0;
+			let shade = mix(0.2, 0.9, parity);
+			// Tint by UV so stretched or flipped mapping is visible too
+			return vec4<f32>(vec3<f32>(shade) * vec3<f32>(fract(input.uv), 1.0), 1.0);
+		}
+		case 4u: {
+			// Each fragment adds one to the red channel of an additive float target
+			return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+		}
+		case 5u: {
+			let depth = 1.0 - linear_depth(input.position.z);
+			return vec4<f32>(vec3<f32>(depth), 1.0);
+		}
+		default: {
+			return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+		}
+	}
+}
+
+fn heatmap(value: f32) -> vec3<f32> {
+	let t = clamp(value, 0.0, 1.0);
+	return clamp(vec3<f32>(t * 3.0 - 1.0, 1.5 - abs(t * 4.0 - 2.0), 2.0 - t * 3.0), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+struct FullscreenOutput {
+	@builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn fullscreen_main(@builtin(vertex_index) vertex_index: u32) -> FullscreenOutput {
+	let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+	var output: FullscreenOutput;
+	output.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+	return output;
+}
+
+// Ten or more layers of overdraw show as fully red
+@fragment
+fn overdraw_resolve(input: FullscreenOutput) -> @location(0) vec4<f32> {
+	let count = textureLoad(overdraw_texture, vec2<i32>(input.position.xy), 0).r;
+	if count <= 0.0 {
+		return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+	}
+	return vec4<f32>(heatmap(count / 10.0), 1.0);
+}
@@ -0,0 +1,76 @@
+struct Camera {
+	view_projection: mat4x4<f32>,
+	inverse_view_projection: mat4x4<f32>,
+	viewport_size: vec2<f32>,
+	padding0: f32,
+	padding1: f32,
+}
+
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) model0: vec4<f32>,
+	@location(2) model1: vec4<f32>,
+	@location(3) model2: vec4<f32>,
+	@location(4) model3: vec4<f32>,
+	@location(5) world_to_decal0: vec4<f32>,
+	@location(6) world_to_decal1: vec4<f32>,
+	@location(7) world_to_decal2: vec4<f32>,
+	@location(8) world_to_decal3: vec4<f32>,
+	@location(9) color: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) world_to_decal0: vec4<f32>,
+	@location(1) world_to_decal1: vec4<f32>,
+	@location(2) world_to_decal2: vec4<f32>,
+	@location(3) world_to_decal3: vec4<f32>,
+	@location(4) color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@group(0) @binding(1)
+var scene_depth: texture_depth_2d;
+
+@group(1) @binding(0)
+var decal_texture: texture_2d<f32>;
+
+@group(1) @binding(1)
+var decal_sampler: sampler;
+
+// Draws the unit cube of each projector box, with back faces so the decal still shows when the camera is inside it
+@vertex
+fn vertex_main(input: VertexInput) -> VertexOutput {
+	let model = mat4x4<f32>(input.model0, input.model1, input.model2, input.model3);
+	var output: VertexOutput;
+	output.position = camera.view_projection * model * vec4<f32>(input.position, 1.0);
+	output.world_to_decal0 = input.world_to_decal0;
+	output.world_to_decal1 = input.world_to_decal1;
+	output.world_to_decal2 = input.world_to_decal2;
+	output.world_to_decal3 = input.world_to_decal3;
+	output.color = input.color;
+	return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let pixel = vec2<i32>(input.position.xy);
+	let depth = textureLoad(scene_depth, pixel, 0);
+	let ndc = vec2<f32>(input.position.x / camera.viewport_size.x * 2.0 - 1.0, 1.0 - input.position.y / camera.viewport_size.y * 2.0);
+	let world = camera.inverse_view_projection * vec4<f32>(ndc, depth, 1.0);
+
+	let world_to_decal = mat4x4<f32>(input.world_to_decal0, input.world_to_decal1, input.world_to_decal2, input.world_to_decal3);
+	let local = (world_to_decal * vec4<f32>(world.xyz / world.w, 1.0)).xyz;
+	if any(abs(local) > vec3<f32>(1.0)) {
+		discard;
+	}
+
+	let uv = vec2<f32>(local.x * 0.5 + 0.5, 0.5 - local.y * 0.5);
+	let texel = textureSampleLevel(decal_texture, decal_sampler, uv, 0.0);
+
+	// Fade toward the ends of the projector so decals don't cut off sharply on curved surfaces
+	let depth_fade = 1.0 - smoothstep(0.8, 1.0, abs(local.z));
+	return vec4<f32>(texel.rgb * input.color.rgb, texel.a * input.color.a * depth_fade);
+}
@@ -0,0 +1,46 @@
+struct Fog {
+	color: vec3<f32>,
+	max_opacity: f32,
+	sun_color: vec3<f32>,
+	sun_scattering: f32,
+	sun_direction: vec3<f32>,
+	density: f32,
+	camera_position: vec3<f32>,
+	start: f32,
+	height_falloff: f32,
+	height: f32,
+	padding0: f32,
+	padding1: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> fog: Fog;
+
+// How much of a surface is hidden, from the fog integrated along the view ray as it thins out with height
+fn fog_opacity(world_position: vec3<f32>) -> f32 {
+	let ray = world_position - fog.camera_position;
+	let distance = length(ray);
+	if distance <= fog.start {
+		return 0.0;
+	}
+	let camera_density = fog.density * exp(min(-fog.height_falloff * (fog.camera_position.y - fog.height), 80.0));
+	let falloff = fog.height_falloff * ray.y;
+	var height_factor = 1.0;
+	if abs(falloff) > 1e-4 {
+		height_factor = (1.0 - exp(-falloff)) / falloff;
+	}
+	let amount = camera_density * height_factor * (distance - fog.start);
+	return min(1.0 - exp(-amount), fog.max_opacity);
+}
+
+// The fog color, brightened toward the sun color when looking into the sun
+fn fog_color(view_direction: vec3<f32>) -> vec3<f32> {
+	let glow = pow(max(dot(view_direction, fog.sun_direction), 0.0), 8.0) * fog.sun_scattering;
+	return mix(fog.color, fog.sun_color, glow);
+}
+
+// Fades a lit surface color into the fog, called at the end of the forward pass fragment shader
+fn apply_fog(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+	let view_direction = normalize(world_position - fog.camera_position);
+	return mix(color, fog_color(view_direction), fog_opacity(world_position));
+}
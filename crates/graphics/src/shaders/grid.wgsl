@@ -0,0 +1,84 @@
+// The camera's basis is scaled so that `right * x + up * y` reaches the edge of the view at the NDC coordinates `x` and `y`
+struct Grid {
+	view_projection: mat4x4<f32>,
+	position: vec4<f32>,
+	forward: vec4<f32>,
+	right: vec4<f32>,
+	up: vec4<f32>,
+	minor_color: vec4<f32>,
+	major_color: vec4<f32>,
+	x_axis_color: vec4<f32>,
+	z_axis_color: vec4<f32>,
+	spacing: f32,
+	major_every: f32,
+	fade_distance: f32,
+	perspective: u32,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) ndc: vec2<f32>,
+}
+
+struct FragmentOutput {
+	@location(0) color: vec4<f32>,
+	@builtin(frag_depth) depth: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> grid: Grid;
+
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+	let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+	var output: VertexOutput;
+	output.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+	output.ndc = uv * 2.0 - 1.0;
+	return output;
+}
+
+// How much of a line the fragment covers, for lines every `spacing` units that are one pixel wide on screen
+fn line_coverage(coordinates: vec2<f32>, spacing: f32) -> vec2<f32> {
+	let scaled = coordinates / spacing;
+	let width = fwidth(scaled);
+	let distance = abs(fract(scaled - 0.5) - 0.5) / max(width, vec2<f32>(1e-6));
+	return 1.0 - min(distance, vec2<f32>(1.0));
+}
+
+// Casts a ray through the pixel onto the ground plane and draws the grid lines where it lands
+@fragment
+fn fragment_main(input: VertexOutput) -> FragmentOutput {
+	let offset = grid.right.xyz * input.ndc.x + grid.up.xyz * input.ndc.y;
+	var origin = grid.position.xyz;
+	var direction = grid.forward.xyz;
+	if grid.perspective != 0u {
+		direction = direction + offset;
+	} else {
+		origin = origin + offset;
+	}
+	let distance = -origin.y / direction.y;
+	if abs(direction.y) < 1e-6 || distance <= 0.0 {
+		discard;
+	}
+	let point = origin + direction * distance;
+
+	let minor = line_coverage(point.xz, grid.spacing);
+	let major = line_coverage(point.xz, grid.spacing * grid.major_every);
+	var color = grid.minor_color * max(minor.x, minor.y);
+	color = mix(color, grid.major_color, max(major.x, major.y));
+	let axis = line_coverage(point.xz, 1e9);
+	color = mix(color, grid.z_axis_color, axis.x);
+	color = mix(color, grid.x_axis_color, axis.y);
+
+	let fade = 1.0 - smoothstep(0.0, grid.fade_distance, length(point.xz - grid.position.xz));
+	color.a = color.a * fade;
+	if color.a <= 0.0 {
+		discard;
+	}
+
+	let clip = grid.view_projection * vec4<f32>(point, 1.0);
+	var output: FragmentOutput;
+	output.color = color;
+	output.depth = clip.z / clip.w;
+	return output;
+}
@@ -0,0 +1,54 @@
+struct Environment {
+	irradiance: array<vec4<f32>, 9>,
+	intensity: f32,
+	specular_levels: f32,
+	padding0: f32,
+	padding1: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> environment: Environment;
+
+@group(1) @binding(1)
+var specular_texture: texture_cube<f32>;
+
+@group(1) @binding(2)
+var brdf_lut: texture_2d<f32>;
+
+@group(1) @binding(3)
+var environment_sampler: sampler;
+
+fn sh_irradiance(normal: vec3<f32>) -> vec3<f32> {
+	let n = normal;
+	var result = environment.irradiance[0].rgb * 0.282095;
+	result += environment.irradiance[1].rgb * 0.488603 * n.y;
+	result += environment.irradiance[2].rgb * 0.488603 * n.z;
+	result += environment.irradiance[3].rgb * 0.488603 * n.x;
+	result += environment.irradiance[4].rgb * 1.092548 * n.x * n.y;
+	result += environment.irradiance[5].rgb * 1.092548 * n.y * n.z;
+	result += environment.irradiance[6].rgb * 0.315392 * (3.0 * n.z * n.z - 1.0);
+	result += environment.irradiance[7].rgb * 1.092548 * n.x * n.z;
+	result += environment.irradiance[8].rgb * 0.546274 * (n.x * n.x - n.y * n.y);
+	return max(result, vec3<f32>(0.0));
+}
+
+fn fresnel_schlick_roughness(n_dot_v: f32, f0: vec3<f32>, roughness: f32) -> vec3<f32> {
+	return f0 + (max(vec3<f32>(1.0 - roughness), f0) - f0) * pow(1.0 - n_dot_v, 5.0);
+}
+
+// Ambient light for a metallic-roughness PBR material, added to its direct lighting.
+// `normal` and `view` are normalized world space vectors, with `view` pointing toward the camera.
+fn ibl_ambient(normal: vec3<f32>, view: vec3<f32>, albedo: vec3<f32>, metallic: f32, roughness: f32, occlusion: f32) -> vec3<f32> {
+	let n_dot_v = clamp(dot(normal, view), 0.0, 1.0);
+	let f0 = mix(vec3<f32>(0.04), albedo, metallic);
+	let fresnel = fresnel_schlick_roughness(n_dot_v, f0, roughness);
+	let diffuse = (vec3<f32>(1.0) - fresnel) * (1.0 - metallic) * albedo * sh_irradiance(normal);
+
+	let reflected = reflect(-view, normal);
+	let level = roughness * (environment.specular_levels - 1.0);
+	let prefiltered = textureSampleLevel(specular_texture, environment_sampler, reflected, level).rgb;
+	let brdf = textureSampleLevel(brdf_lut, environment_sampler, vec2<f32>(n_dot_v, roughness), 0.0).rg;
+	let specular = prefiltered * (fresnel * brdf.x + brdf.y);
+
+	return (diffuse + specular) * occlusion * environment.intensity;
+}
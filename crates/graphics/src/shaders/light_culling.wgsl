@@ -0,0 +1,35 @@
+#include "clusters.wgsl"
+
+@group(0) @binding(0)
+var<uniform> clusters: Clusters;
+
+@group(0) @binding(1)
+var<storage, read> lights: array<Light>;
+
+@group(0) @binding(2)
+var<storage, read_write> light_counts: array<u32>;
+
+// Each cluster's lights start at `cluster * dimensions.w`
+@group(0) @binding(3)
+var<storage, read_write> light_indices: array<u32>;
+
+// One invocation per cluster, listing every light whose range reaches it
+@compute @workgroup_size(64)
+fn assign_lights(@builtin(global_invocation_id) id: vec3<u32>) {
+	let cluster_count = clusters.dimensions.x * clusters.dimensions.y * clusters.dimensions.z;
+	if id.x >= cluster_count {
+		return;
+	}
+	let bounds = cluster_bounds(clusters, cluster_coordinates(clusters, id.x));
+	let capacity = clusters.dimensions.w;
+	var count = 0u;
+	for (var index = 0u; index < clusters.light_count && count < capacity; index = index + 1u) {
+		let light = lights[index];
+		let center = (clusters.view * vec4<f32>(light.position, 1.0)).xyz;
+		if light_touches_cluster(bounds, center, light.range) {
+			light_indices[id.x * capacity + count] = index;
+			count = count + 1u;
+		}
+	}
+	light_counts[id.x] = count;
+}
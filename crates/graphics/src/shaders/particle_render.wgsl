@@ -0,0 +1,68 @@
+struct Particle {
+	position: vec3<f32>,
+	age: f32,
+	velocity: vec3<f32>,
+	lifetime: f32,
+	color: vec4<f32>,
+	size: f32,
+	padding0: f32,
+	padding1: f32,
+	padding2: f32,
+}
+
+struct SortEntry {
+	index: u32,
+	depth: f32,
+}
+
+struct Camera {
+	view_projection: mat4x4<f32>,
+	right: vec3<f32>,
+	padding0: f32,
+	up: vec3<f32>,
+	padding1: f32,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) color: vec4<f32>,
+	@location(1) offset: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<storage, read> particles: array<Particle>;
+
+@group(0) @binding(1)
+var<storage, read> sort_entries: array<SortEntry>;
+
+@group(0) @binding(2)
+var<uniform> camera: Camera;
+
+// Draw six vertices per particle, with one instance per particle
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32, @builtin(instance_index) instance_index: u32) -> VertexOutput {
+	var corners = array<vec2<f32>, 6>(
+		vec2<f32>(-1.0, -1.0),
+		vec2<f32>(1.0, -1.0),
+		vec2<f32>(1.0, 1.0),
+		vec2<f32>(-1.0, -1.0),
+		vec2<f32>(1.0, 1.0),
+		vec2<f32>(-1.0, 1.0),
+	);
+	let corner = corners[vertex_index];
+	let particle = particles[sort_entries[instance_index].index];
+	let size = select(0.0, particle.size * 0.5, particle.age < particle.lifetime);
+	let world_position = particle.position + (camera.right * corner.x + camera.up * corner.y) * size;
+
+	var output: VertexOutput;
+	output.position = camera.view_projection * vec4<f32>(world_position, 1.0);
+	output.color = particle.color;
+	output.offset = corner;
+	return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let falloff = 1.0 - smoothstep(0.5, 1.0, length(input.offset));
+	return vec4<f32>(input.color.rgb, input.color.a * falloff);
+}
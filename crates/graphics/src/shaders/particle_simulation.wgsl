@@ -0,0 +1,141 @@
+struct Particle {
+	position: vec3<f32>,
+	age: f32,
+	velocity: vec3<f32>,
+	lifetime: f32,
+	color: vec4<f32>,
+	size: f32,
+	padding0: f32,
+	padding1: f32,
+	padding2: f32,
+}
+
+struct Emitter {
+	position: vec3<f32>,
+	spawn_count: u32,
+	velocity: vec3<f32>,
+	spread: f32,
+	gravity: vec3<f32>,
+	lifetime: f32,
+	start_color: vec4<f32>,
+	end_color: vec4<f32>,
+	start_size: f32,
+	end_size: f32,
+	delta_time: f32,
+	seed: u32,
+	damping: f32,
+	particle_count: u32,
+	padding0: u32,
+	padding1: u32,
+}
+
+struct SortEntry {
+	index: u32,
+	depth: f32,
+}
+
+struct SortParams {
+	camera_position: vec3<f32>,
+	block_size: u32,
+	compare_distance: u32,
+	padding0: u32,
+	padding1: u32,
+	padding2: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> particles: array<Particle>;
+
+@group(0) @binding(1)
+var<uniform> emitter: Emitter;
+
+// Cleared to zero by the CPU before each simulation dispatch
+@group(0) @binding(2)
+var<storage, read_write> spawned: atomic<u32>;
+
+@group(0) @binding(3)
+var<storage, read_write> sort_entries: array<SortEntry>;
+
+@group(0) @binding(4)
+var<uniform> sort_params: SortParams;
+
+// PCG hash, mirrored on the CPU so both simulation paths spawn identical particles
+fn hash(value: u32) -> u32 {
+	let state = value * 747796405u + 2891336453u;
+	let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+	return (word >> 22u) ^ word;
+}
+
+fn random(seed: u32) -> f32 {
+	return f32(hash(seed)) / 4294967295.0;
+}
+
+fn spawn(slot: u32) -> Particle {
+	let seed = hash(emitter.seed ^ hash(slot)) * 3u;
+	let direction = vec3<f32>(random(seed), random(seed + 1u), random(seed + 2u)) * 2.0 - 1.0;
+	var particle: Particle;
+	particle.position = emitter.position;
+	particle.velocity = emitter.velocity + direction * emitter.spread;
+	particle.age = 0.0;
+	particle.lifetime = emitter.lifetime;
+	return particle;
+}
+
+@compute @workgroup_size(64)
+fn simulate(@builtin(global_invocation_id) id: vec3<u32>) {
+	let index = id.x;
+	if index >= emitter.particle_count {
+		return;
+	}
+	var particle = particles[index];
+	if particle.age >= particle.lifetime {
+		let slot = atomicAdd(&spawned, 1u);
+		if slot >= emitter.spawn_count {
+			return;
+		}
+		particle = spawn(slot);
+	}
+
+	// Matches the integration order of `physics::Particle::integrate`
+	let delta_time = emitter.delta_time;
+	particle.position = particle.position + particle.velocity * delta_time;
+	particle.velocity = (particle.velocity + emitter.gravity * delta_time) * pow(emitter.damping, delta_time);
+	particle.age = particle.age + delta_time;
+
+	let progress = clamp(particle.age / particle.lifetime, 0.0, 1.0);
+	particle.color = mix(emitter.start_color, emitter.end_color, progress);
+	particle.size = mix(emitter.start_size, emitter.end_size, progress);
+	particles[index] = particle;
+}
+
+@compute @workgroup_size(64)
+fn compute_depths(@builtin(global_invocation_id) id: vec3<u32>) {
+	let index = id.x;
+	if index >= emitter.particle_count {
+		return;
+	}
+	let particle = particles[index];
+	var entry: SortEntry;
+	entry.index = index;
+	// Dead particles sort to the end, where they are drawn with no size
+	entry.depth = select(-1.0, distance(particle.position, sort_params.camera_position), particle.age < particle.lifetime);
+	sort_entries[index] = entry;
+}
+
+// One step of a bitonic sort ordering particles from back to front.
+// The CPU dispatches this once for every (block_size, compare_distance) pair.
+@compute @workgroup_size(64)
+fn sort_step(@builtin(global_invocation_id) id: vec3<u32>) {
+	let index = id.x;
+	let partner = index ^ sort_params.compare_distance;
+	if partner <= index || partner >= emitter.particle_count {
+		return;
+	}
+	let first = sort_entries[index];
+	let second = sort_entries[partner];
+	let is_descending = (index & sort_params.block_size) == 0u;
+	if select(first.depth > second.depth, first.depth < second.depth, is_descending) {
+		sort_entries[index] = second;
+		sort_entries[partner] = first;
+	}
+}
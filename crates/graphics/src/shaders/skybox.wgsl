@@ -0,0 +1,41 @@
+struct Skybox {
+	inverse_view_projection: mat4x4<f32>,
+	intensity: f32,
+	padding0: f32,
+	padding1: f32,
+	padding2: f32,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) clip: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> skybox: Skybox;
+
+@group(0) @binding(1)
+var skybox_texture: texture_cube<f32>;
+
+@group(0) @binding(2)
+var skybox_sampler: sampler;
+
+// A fullscreen triangle drawn at the far plane, so it only fills pixels nothing else covered
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+	let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+	let clip = uv * 2.0 - 1.0;
+	var output: VertexOutput;
+	output.position = vec4<f32>(clip, 1.0, 1.0);
+	output.clip = clip;
+	return output;
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let near = skybox.inverse_view_projection * vec4<f32>(input.clip, 0.0, 1.0);
+	let far = skybox.inverse_view_projection * vec4<f32>(input.clip, 1.0, 1.0);
+	let direction = normalize(far.xyz / far.w - near.xyz / near.w);
+	let color = textureSample(skybox_texture, skybox_sampler, direction).rgb * skybox.intensity;
+	return vec4<f32>(color, 1.0);
+}
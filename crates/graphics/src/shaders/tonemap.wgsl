@@ -0,0 +1,90 @@
+// `tonemapper` is the index of a `Tonemapper` variant
+struct Tonemap {
+	exposure: f32,
+	tonemapper: u32,
+	encode_srgb: u32,
+	padding: f32,
+	// Simulates or corrects for color blindness, which is the identity when no filter is set
+	color_filter: mat3x3<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> tonemap: Tonemap;
+
+@group(0) @binding(1)
+var hdr_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var hdr_sampler: sampler;
+
+// A fullscreen triangle that resamples the HDR target, which also upsamples it when the render scale isn't 1
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+	let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+	var output: VertexOutput;
+	output.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+	output.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+	return output;
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+	return color / (1.0 + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+	return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn hable_partial(x: vec3<f32>) -> vec3<f32> {
+	let a = 0.15;
+	let b = 0.50;
+	let c = 0.10;
+	let d = 0.20;
+	let e = 0.02;
+	let f = 0.30;
+	return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+// John Hable's Uncharted 2 filmic curve
+fn hable(color: vec3<f32>) -> vec3<f32> {
+	let white = 11.2;
+	return clamp(hable_partial(color * 2.0) / hable_partial(vec3<f32>(white)), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn encode_srgb(color: vec3<f32>) -> vec3<f32> {
+	let low = color * 12.92;
+	let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+	return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fragment_main(input: VertexOutput) -> @location(0) vec4<f32> {
+	let hdr = textureSample(hdr_texture, hdr_sampler, input.uv);
+	let exposed = max(hdr.rgb * tonemap.exposure, vec3<f32>(0.0));
+	var color: vec3<f32>;
+	switch tonemap.tonemapper {
+		case 1u: {
+			color = reinhard(exposed);
+		}
+		case 2u: {
+			color = aces(exposed);
+		}
+		case 3u: {
+			color = hable(exposed);
+		}
+		default: {
+			color = min(exposed, vec3<f32>(1.0));
+		}
+	}
+	color = clamp(tonemap.color_filter * color, vec3<f32>(0.0), vec3<f32>(1.0));
+	if tonemap.encode_srgb != 0u {
+		color = encode_srgb(color);
+	}
+	return vec4<f32>(color, 1.0);
+}
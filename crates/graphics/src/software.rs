@@ -0,0 +1,201 @@
+use crate::{
+	debug_draw::{DebugDraw, DebugPrimitive, DebugVertex},
+	golden::Frame,
+	instancing::Matrix4,
+	tonemap::ColorPipeline,
+};
+use math::{Color, Real};
+
+/// A vertex after projection, in pixels with the depth in the `0..1` range
+#[derive(Debug, Copy, Clone)]
+struct ScreenVertex {
+	x: Real,
+	y: Real,
+	depth: Real,
+
+	/// One over the clip space w, for interpolating colors with perspective
+	inverse_w: Real,
+	color: [Real; 4],
+}
+
+/// The headless renderer, which draws the `DebugDraw` batches on the CPU and resolves them
+/// through the `ColorPipeline` like the tonemap pass. Golden tests use it without a GPU.
+///
+/// Triangles are filled at pixel centers and lines are one pixel wide. Colors are blended by
+/// alpha in linear space, depth-tested draws keep the nearest depth, and overlays draw on top.
+#[derive(Debug, Clone)]
+pub struct SoftwareRenderer {
+	pub clear_color: Color,
+	width: u32,
+	height: u32,
+	color: Vec<[Real; 3]>,
+	depth: Vec<Real>,
+}
+
+impl SoftwareRenderer {
+	pub fn new(width: u32, height: u32) -> Self {
+		let pixels = width as usize * height as usize;
+		Self {
+			clear_color: Color::BLACK,
+			width,
+			height,
+			color: vec![[0.0; 3]; pixels],
+			depth: vec![1.0; pixels],
+		}
+	}
+
+	pub fn aspect_ratio(&self) -> Real {
+		self.width as Real / self.height.max(1) as Real
+	}
+
+	/// Draws the batches built by `DebugDraw::build` from a view projection and captures the frame
+	pub fn render(&mut self, draw: &DebugDraw, view_projection: &Matrix4, pipeline: &ColorPipeline) -> Frame {
+		let Color { r, g, b, .. } = self.clear_color;
+		self.color.fill([r, g, b]);
+		self.depth.fill(1.0);
+
+		for call in draw.draw_calls() {
+			let range = call.first_vertex as usize..(call.first_vertex + call.vertex_count) as usize;
+			let Some(vertices) = draw.vertices().get(range) else {
+				continue;
+			};
+			let vertices = vertices.iter().map(|vertex| self.project(vertex, view_projection)).collect::<Vec<_>>();
+			match call.primitive {
+				DebugPrimitive::Lines => vertices.chunks_exact(2).for_each(|line| self.line(line, call.depth_test)),
+				DebugPrimitive::Triangles => vertices.chunks_exact(3).for_each(|triangle| self.triangle(triangle, call.depth_test)),
+			}
+		}
+
+		let pixels = self.color.iter().map(|color| {
+			let [r, g, b] = pipeline.resolve(*color);
+			// Without `encode_srgb` the swapchain's sRGB format encodes, so it's sRGB anyway
+			match pipeline.encode_srgb {
+				true => [r, g, b, 1.0].map(|channel| (channel.clamp(0.0, 1.0) * 255.0 + 0.5) as u8),
+				false => Color::rgb(r, g, b).to_srgb8(),
+			}
+		});
+		Frame::from_pixels(self.width, self.height, pixels.collect())
+	}
+
+	/// Projects a vertex into pixels, or returns `None` when it's behind the camera
+	fn project(&self, vertex: &DebugVertex, view_projection: &Matrix4) -> Option<ScreenVertex> {
+		let [x, y, z] = vertex.position;
+		let clip = [0, 1, 2, 3].map(|row| view_projection[0][row] * x + view_projection[1][row] * y + view_projection[2][row] * z + view_projection[3][row]);
+		if clip[3] <= Real::EPSILON {
+			return None;
+		}
+		let inverse_w = 1.0 / clip[3];
+		Some(ScreenVertex {
+			x: (clip[0] * inverse_w * 0.5 + 0.5) * self.width as Real,
+			y: (0.5 - clip[1] * inverse_w * 0.5) * self.height as Real,
+			depth: clip[2] * inverse_w,
+			inverse_w,
+			color: vertex.color,
+		})
+	}
+
+	fn triangle(&mut self, triangle: &[Option<ScreenVertex>], depth_test: bool) {
+		let [Some(a), Some(b), Some(c)] = [triangle[0], triangle[1], triangle[2]] else {
+			return;
+		};
+		let edge = |from: &ScreenVertex, to: &ScreenVertex, x: Real, y: Real| (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x);
+		let area = edge(&a, &b, c.x, c.y);
+		if area.abs() <= Real::EPSILON {
+			return;
+		}
+		let (min_x, max_x) = (a.x.min(b.x).min(c.x).max(0.0), a.x.max(b.x).max(c.x).min(self.width as Real));
+		let (min_y, max_y) = (a.y.min(b.y).min(c.y).max(0.0), a.y.max(b.y).max(c.y).min(self.height as Real));
+		for y in min_y.floor() as u32..max_y.ceil() as u32 {
+			for x in min_x.floor() as u32..max_x.ceil() as u32 {
+				let (center_x, center_y) = (x as Real + 0.5, y as Real + 0.5);
+				let weights = [
+					edge(&b, &c, center_x, center_y) / area,
+					edge(&c, &a, center_x, center_y) / area,
+					edge(&a, &b, center_x, center_y) / area,
+				];
+				if weights.iter().any(|weight| *weight < 0.0) {
+					continue;
+				}
+				self.shade((x, y), &[a, b, c], weights, depth_test);
+			}
+		}
+	}
+
+	fn line(&mut self, line: &[Option<ScreenVertex>], depth_test: bool) {
+		let [Some(start), Some(end)] = [line[0], line[1]] else {
+			return;
+		};
+		let steps = (end.x - start.x).abs().max((end.y - start.y).abs()).ceil().max(1.0) as u32;
+		for step in 0..=steps {
+			let t = step as Real / steps as Real;
+			let (x, y) = (start.x + (end.x - start.x) * t, start.y + (end.y - start.y) * t);
+			if x < 0.0 || y < 0.0 || x >= self.width as Real || y >= self.height as Real {
+				continue;
+			}
+			self.shade((x as u32, y as u32), &[start, end], [1.0 - t, t, 0.0], depth_test);
+		}
+	}
+
+	/// Blends the interpolated color into a pixel, with `weights` its barycentric coordinates
+	fn shade(&mut self, (x, y): (u32, u32), vertices: &[ScreenVertex], weights: [Real; 3], depth_test: bool) {
+		let index = (y * self.width + x) as usize;
+		let depth = vertices.iter().zip(weights).map(|(vertex, weight)| vertex.depth * weight).sum::<Real>();
+		if depth_test && (depth >= self.depth[index] || !(0.0..=1.0).contains(&depth)) {
+			return;
+		}
+		let inverse_w = vertices.iter().zip(weights).map(|(vertex, weight)| vertex.inverse_w * weight).sum::<Real>();
+		let mut color = [0.0; 4];
+		for (vertex, weight) in vertices.iter().zip(weights) {
+			let weight = weight * vertex.inverse_w / inverse_w;
+			color.iter_mut().zip(vertex.color).for_each(|(channel, value)| *channel += value * weight);
+		}
+		let alpha = color[3].clamp(0.0, 1.0);
+		for (channel, value) in self.color[index].iter_mut().zip(color) {
+			*channel = value * alpha + *channel * (1.0 - alpha);
+		}
+		if depth_test {
+			self.depth[index] = depth;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{instancing::IDENTITY, tonemap::Tonemapper};
+	use math::Vector3;
+
+	fn linear() -> ColorPipeline {
+		ColorPipeline {
+			tonemapper: Tonemapper::Clamp,
+			..Default::default()
+		}
+	}
+
+	#[test]
+	pub fn depth_and_overlays() {
+		let mut draw = DebugDraw::default();
+		let quad = |draw: &mut DebugDraw, depth: Real, color: Color| {
+			let corner = |x: Real, y: Real| Vector3::new(x, y, depth);
+			draw.triangle(corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), color);
+			draw.triangle(corner(-1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0), color);
+		};
+		// The nearer red quad hides the green one drawn after it, and the overlay shows through
+		quad(&mut draw, 0.25, Color::RED);
+		quad(&mut draw, 0.75, Color::GREEN);
+		draw.depth_test = false;
+		draw.line(Vector3::new(-1.0, 0.0, 0.9), Vector3::new(1.0, 0.0, 0.9), Color::BLUE);
+		draw.build();
+
+		let mut renderer = SoftwareRenderer::new(8, 8);
+		let frame = renderer.render(&draw, &IDENTITY, &linear());
+		assert_eq!(frame.pixel(1, 1), Some([255, 0, 0, 255]));
+		assert_eq!(frame.pixel(3, 4), Some([0, 0, 255, 255]));
+
+		draw.clear();
+		quad(&mut draw, 0.5, Color::WHITE.with_alpha(0.5));
+		draw.build();
+		renderer.clear_color = Color::BLACK;
+		assert_eq!(renderer.render(&draw, &IDENTITY, &linear()).pixel(4, 4), Some(Color::rgb(0.5, 0.5, 0.5).to_srgb8()));
+	}
+}
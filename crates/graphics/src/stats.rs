@@ -0,0 +1,140 @@
+use crate::instancing::{MeshHandle, RenderBatches};
+use asset::Texture;
+use std::time::Duration;
+
+/// Time the GPU spent on a render pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassTiming {
+	pub name: String,
+	pub duration: Duration,
+}
+
+/// What the renderer drew last frame. Renderers update this resource every frame
+/// for the profiler overlay and editor stats panel to display.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct RenderStats {
+	pub frame: u64,
+	pub draw_calls: usize,
+	pub instances: usize,
+	pub triangles: usize,
+
+	/// Bytes used by every uploaded texture, which carries over between frames
+	pub texture_memory: usize,
+
+	/// GPU timings, which lag a few frames behind because timestamp queries resolve asynchronously
+	pub passes: Vec<PassTiming>,
+}
+
+impl RenderStats {
+	/// Resets the per-frame counters, keeping texture memory and the last resolved pass timings
+	pub fn begin_frame(&mut self) {
+		self.frame += 1;
+		self.draw_calls = 0;
+		self.instances = 0;
+		self.triangles = 0;
+	}
+
+	pub fn record_draw(&mut self, instances: usize, triangles_per_instance: usize) {
+		self.draw_calls += 1;
+		self.instances += instances;
+		self.triangles += instances * triangles_per_instance;
+	}
+
+	/// Records every batch as one instanced draw call, looking up each mesh's triangle count
+	pub fn record_batches(&mut self, batches: &RenderBatches, triangle_count: impl Fn(MeshHandle) -> usize) {
+		for batch in batches.batches() {
+			self.record_draw(batch.instance_count as usize, triangle_count(batch.mesh));
+		}
+	}
+
+	pub fn texture_uploaded(&mut self, texture: &Texture) {
+		self.texture_memory += texture.byte_size();
+	}
+
+	pub fn texture_released(&mut self, texture: &Texture) {
+		self.texture_memory = self.texture_memory.saturating_sub(texture.byte_size());
+	}
+
+	pub fn gpu_time(&self) -> Duration {
+		self.passes.iter().map(|pass| pass.duration).sum()
+	}
+}
+
+/// Hands out timestamp query slots to render passes and turns the timestamps into pass timings.
+/// Each pass writes its begin slot when it starts and its end slot when it finishes.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct GpuTimer {
+	passes: Vec<String>,
+}
+
+impl GpuTimer {
+	pub fn begin_frame(&mut self) {
+		self.passes.clear();
+	}
+
+	/// Returns the begin and end query indices for a pass
+	pub fn pass(&mut self, name: impl Into<String>) -> (u32, u32) {
+		let begin = self.query_count();
+		self.passes.push(name.into());
+		(begin, begin + 1)
+	}
+
+	/// The number of queries the frame's query set needs
+	pub fn query_count(&self) -> u32 {
+		self.passes.len() as u32 * 2
+	}
+
+	/// Converts resolved timestamps, in ticks of `period` nanoseconds, into pass timings
+	pub fn resolve(&self, timestamps: &[u64], period: f32, stats: &mut RenderStats) {
+		stats.passes.clear();
+		stats.passes.extend(self.passes.iter().zip(timestamps.chunks_exact(2)).map(|(name, range)| {
+			let ticks = range[1].saturating_sub(range[0]);
+			PassTiming {
+				name: name.clone(),
+				duration: Duration::from_nanos((ticks as f64 * period as f64) as u64),
+			}
+		}));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::instancing::{IDENTITY, MaterialHandle, MeshRenderer, RenderObject};
+
+	#[test]
+	pub fn counting() {
+		let object = |mesh| {
+			let renderer = MeshRenderer {
+				mesh: MeshHandle(mesh),
+				material: MaterialHandle(0),
+			};
+			RenderObject::new(renderer, IDENTITY)
+		};
+		let mut batches = RenderBatches::default();
+		batches.build([object(0), object(0), object(1)]);
+
+		let mut stats = RenderStats::default();
+		stats.begin_frame();
+		stats.record_batches(&batches, |mesh| if mesh == MeshHandle(0) { 12 } else { 100 });
+		assert_eq!((stats.draw_calls, stats.instances, stats.triangles), (2, 3, 124));
+
+		stats.begin_frame();
+		assert_eq!((stats.frame, stats.draw_calls, stats.triangles), (2, 0, 0));
+	}
+
+	#[test]
+	pub fn pass_timings() {
+		let mut timer = GpuTimer::default();
+		timer.begin_frame();
+		assert_eq!(timer.pass("shadows"), (0, 1));
+		assert_eq!(timer.pass("opaque"), (2, 3));
+		assert_eq!(timer.query_count(), 4);
+
+		let mut stats = RenderStats::default();
+		timer.resolve(&[100, 300, 300, 1300], 2.0, &mut stats);
+		assert_eq!(stats.passes[0].duration, Duration::from_nanos(400));
+		assert_eq!(stats.passes[1].name, "opaque");
+		assert_eq!(stats.gpu_time(), Duration::from_nanos(2400));
+	}
+}
@@ -0,0 +1,204 @@
+use crate::color_filter::ColorFilter;
+use bytemuck::{Pod, Zeroable};
+use math::{Color, Real};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Resolves the HDR target to the swapchain with exposure, tonemapping, filter, and sRGB encoding
+pub const TONEMAP_SHADER: &str = include_str!("shaders/tonemap.wgsl");
+
+/// Maps the unbounded linear radiance lighting produces into the 0 to 1 range a display can show
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Tonemapper {
+	/// Clips anything brighter than white, which only suits scenes lit to stay in range
+	Clamp,
+	Reinhard,
+	#[default]
+	Aces,
+	Hable,
+}
+
+impl Tonemapper {
+	pub const ALL: [Self; 4] = [Self::Clamp, Self::Reinhard, Self::Aces, Self::Hable];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Self::Clamp => "Clamp",
+			Self::Reinhard => "Reinhard",
+			Self::Aces => "ACES",
+			Self::Hable => "Hable",
+		}
+	}
+
+	/// Tonemaps a linear color on the CPU the same way the shader does, such as for screenshots
+	pub fn apply(&self, color: [Real; 3]) -> [Real; 3] {
+		match self {
+			Self::Clamp => color.map(|channel| channel.clamp(0.0, 1.0)),
+			Self::Reinhard => color.map(|channel| channel / (1.0 + channel)),
+			Self::Aces => color.map(|x| ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)),
+			Self::Hable => {
+				const WHITE: Real = 11.2;
+				let white = hable_partial(WHITE);
+				color.map(|channel| (hable_partial(channel * 2.0) / white).clamp(0.0, 1.0))
+			},
+		}
+	}
+}
+
+fn hable_partial(x: Real) -> Real {
+	let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+	((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+impl fmt::Display for Tonemapper {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(self.label())
+	}
+}
+
+impl FromStr for Tonemapper {
+	type Err = String;
+
+	/// Parses a tonemapper's label, ignoring case
+	fn from_str(text: &str) -> Result<Self, Self::Err> {
+		Self::ALL
+			.into_iter()
+			.find(|tonemapper| tonemapper.label().eq_ignore_ascii_case(text.trim()))
+			.ok_or_else(|| format!("Unknown tonemapper: {text}"))
+	}
+}
+
+/// How the scene gets from the HDR target to the screen.
+///
+/// Lighting is done in linear space into an `rgba16float` target, so values above 1 survive.
+/// Color textures are sampled through sRGB formats, which decode them to linear as they are read,
+/// and the final pass encodes to sRGB unless the swapchain's sRGB format does it in hardware.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorPipeline {
+	pub tonemapper: Tonemapper,
+
+	/// In stops, where each stop doubles the brightness
+	pub exposure: Real,
+
+	/// Set when the swapchain uses a non-sRGB format, as WebGPU canvases often do
+	pub encode_srgb: bool,
+
+	/// Simulates or corrects for color blindness
+	pub color_filter: ColorFilter,
+}
+
+impl Default for ColorPipeline {
+	fn default() -> Self {
+		Self {
+			tonemapper: Tonemapper::default(),
+			exposure: 0.0,
+			encode_srgb: false,
+			color_filter: ColorFilter::default(),
+		}
+	}
+}
+
+impl ColorPipeline {
+	/// The HDR target's format, which is half-float so lighting has headroom above white
+	pub const HDR_FORMAT: &'static str = "rgba16float";
+
+	pub fn exposure_scale(&self) -> Real {
+		(2.0 as Real).powf(self.exposure)
+	}
+
+	/// The settings as the `tonemap` shader's uniform
+	pub fn uniform(&self) -> TonemapUniform {
+		TonemapUniform {
+			exposure: self.exposure_scale(),
+			tonemapper: self.tonemapper as u32,
+			encode_srgb: u32::from(self.encode_srgb),
+			padding: 0.0,
+			color_filter: self.color_filter.uniform(),
+		}
+	}
+
+	/// Resolves one linear HDR color the way the tonemap pass does, as written to the swapchain
+	pub fn resolve(&self, color: [Real; 3]) -> [Real; 3] {
+		let exposure = self.exposure_scale();
+		let tonemapped = self.tonemapper.apply(color.map(|channel| (channel * exposure).max(0.0)));
+		let [r, g, b] = self.color_filter.apply(tonemapped);
+		if !self.encode_srgb {
+			return [r, g, b];
+		}
+		let [r, g, b, _] = Color::rgb(r, g, b).to_srgb();
+		[r, g, b]
+	}
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Pod, Zeroable)]
+pub struct TonemapUniform {
+	/// The linear multiplier for the exposure
+	pub exposure: f32,
+	pub tonemapper: u32,
+	pub encode_srgb: u32,
+	pub padding: f32,
+
+	/// The color filter's matrix as padded columns
+	pub color_filter: [[f32; 4]; 3],
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ColorVisionDeficiency;
+
+	#[test]
+	pub fn tonemappers() {
+		for tonemapper in Tonemapper::ALL {
+			let [black, ..] = tonemapper.apply([0.0; 3]);
+			let [bright, ..] = tonemapper.apply([100.0; 3]);
+			assert!(black.abs() < 1e-3, "{tonemapper} doesn't keep black");
+			assert!(bright <= 1.0 && bright > 0.9, "{tonemapper} maps bright values to {bright}");
+			let [low, ..] = tonemapper.apply([0.2; 3]);
+			let [high, ..] = tonemapper.apply([0.4; 3]);
+			assert!(low < high, "{tonemapper} isn't increasing");
+			assert_eq!(tonemapper.to_string().parse(), Ok(tonemapper));
+		}
+		assert_eq!(Tonemapper::Reinhard.apply([1.0, 3.0, 0.0]), [0.5, 0.75, 0.0]);
+		assert!("filmic".parse::<Tonemapper>().is_err());
+	}
+
+	#[test]
+	pub fn resolve() {
+		let pipeline = ColorPipeline {
+			tonemapper: Tonemapper::Reinhard,
+			exposure: 1.0,
+			..Default::default()
+		};
+		assert_eq!(pipeline.resolve([0.5, -1.0, 1.5]), [0.5, 0.0, 0.75]);
+		assert_eq!(pipeline.uniform().exposure, 2.0);
+
+		// Mid-grey in linear space is encoded to roughly half brightness
+		let encoded = ColorPipeline {
+			tonemapper: Tonemapper::Clamp,
+			encode_srgb: true,
+			..Default::default()
+		};
+		assert!((encoded.resolve([0.214; 3])[0] - 0.5).abs() < 1e-2);
+		assert_eq!(encoded.uniform().encode_srgb, 1);
+
+		// The color filter runs on the tonemapped color, so it can't push values out of range
+		let filtered = ColorPipeline {
+			tonemapper: Tonemapper::Clamp,
+			color_filter: ColorFilter::simulate(ColorVisionDeficiency::Protanopia),
+			..Default::default()
+		};
+		assert_eq!(filtered.resolve([4.0, 4.0, 4.0]).map(|channel| (channel * 100.0).round()), [100.0; 3]);
+		assert_eq!(filtered.resolve([1.0, 0.0, 0.0]), filtered.color_filter.apply([1.0, 0.0, 0.0]));
+		assert_eq!(std::mem::size_of::<TonemapUniform>(), 64);
+	}
+
+	#[test]
+	pub fn shader_is_valid() {
+		let module = naga::front::wgsl::parse_str(TONEMAP_SHADER).unwrap();
+		naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+			.validate(&module)
+			.unwrap();
+	}
+}
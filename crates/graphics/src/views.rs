@@ -0,0 +1,242 @@
+use crate::{culling::RenderCamera, fog::Fog};
+use math::{Camera, Extent2D, Real, Rect, Viewport};
+
+/// How the window is divided between views
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ViewLayout {
+	#[default]
+	Single,
+
+	/// Two views next to each other, as in two player split-screen
+	SideBySide,
+
+	/// Two views, one above the other
+	Stacked,
+
+	/// Four views in a grid, as in the editor's perspective, top, front, and side views
+	Quad,
+}
+
+impl ViewLayout {
+	pub fn view_count(&self) -> usize {
+		match self {
+			Self::Single => 1,
+			Self::SideBySide | Self::Stacked => 2,
+			Self::Quad => 4,
+		}
+	}
+
+	/// The area of each view as fractions of the window, in reading order
+	pub fn areas(&self) -> Vec<Rect> {
+		let window = Rect::new(0.0, 0.0, 1.0, 1.0);
+		match self {
+			Self::Single => vec![window],
+			Self::SideBySide => {
+				let (left, right) = window.split_horizontally(0.5);
+				vec![left, right]
+			},
+			Self::Stacked => {
+				let (top, bottom) = window.split_vertically(0.5);
+				vec![top, bottom]
+			},
+			Self::Quad => {
+				let (top, bottom) = window.split_vertically(0.5);
+				let ((top_left, top_right), (bottom_left, bottom_right)) = (top.split_horizontally(0.5), bottom.split_horizontally(0.5));
+				vec![top_left, top_right, bottom_left, bottom_right]
+			},
+		}
+	}
+}
+
+/// A camera drawing into part of the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderView {
+	pub name: String,
+	pub camera: Camera,
+
+	/// The part of the window the view covers, as fractions of its size, so it follows resizes
+	pub area: Rect,
+
+	/// Hidden views aren't drawn and don't receive input
+	pub is_visible: bool,
+
+	/// Replaces the scene's `Fog` resource for this view, such as clear air for a map camera
+	pub fog: Option<Fog>,
+}
+
+impl RenderView {
+	pub fn new(name: impl Into<String>, camera: Camera) -> Self {
+		Self {
+			name: name.into(),
+			camera,
+			area: Rect::new(0.0, 0.0, 1.0, 1.0),
+			is_visible: true,
+			fog: None,
+		}
+	}
+
+	/// Where the view is in a window of the given size
+	pub fn viewport(&self, window: Extent2D) -> Viewport {
+		Viewport::new(Rect::new(
+			self.area.x * window.width,
+			self.area.y * window.height,
+			self.area.width * window.width,
+			self.area.height * window.height,
+		))
+	}
+
+	/// The pixels the view's draws are clipped to, so nothing spills into its neighbors
+	pub fn scissor(&self, window: Extent2D) -> Option<[u32; 4]> {
+		self.viewport(window).rect.scissor(window)
+	}
+
+	/// The camera to bind while drawing the view, with the projection fit to its aspect ratio
+	pub fn render_camera(&self, window: Extent2D) -> RenderCamera {
+		RenderCamera::new(self.camera.position, self.camera.view_projection(self.viewport(window).aspect_ratio()))
+	}
+
+	/// The fog to draw the view with, from its override or else the scene's fog
+	pub fn fog(&self, scene: Option<&Fog>) -> Fog {
+		self.fog.or(scene.copied()).unwrap_or_default()
+	}
+}
+
+/// The views drawn each frame, stored as a resource, along with which one receives input.
+/// The renderer draws the world once per visible view with its camera, viewport, and scissor.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct RenderViews {
+	pub views: Vec<RenderView>,
+	focused: Option<usize>,
+	is_captured: bool,
+}
+
+impl RenderViews {
+	/// Lays out views for the cameras in order
+	pub fn new(layout: ViewLayout, views: impl IntoIterator<Item = RenderView>) -> Self {
+		let mut views = Self {
+			views: views.into_iter().collect(),
+			..Self::default()
+		};
+		views.set_layout(layout);
+		views
+	}
+
+	/// Fits the first views to the layout and hides the rest
+	pub fn set_layout(&mut self, layout: ViewLayout) {
+		let areas = layout.areas();
+		for (index, view) in self.views.iter_mut().enumerate() {
+			view.is_visible = index < areas.len();
+			if let Some(area) = areas.get(index) {
+				view.area = *area;
+			}
+		}
+		if self.focused.is_some_and(|index| !self.views[index].is_visible) {
+			self.focused = None;
+			self.is_captured = false;
+		}
+	}
+
+	pub fn visible(&self) -> impl Iterator<Item = (usize, &RenderView)> {
+		self.views.iter().enumerate().filter(|(_, view)| view.is_visible)
+	}
+
+	pub fn get(&self, index: usize) -> Option<&RenderView> {
+		self.views.get(index)
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut RenderView> {
+		self.views.get_mut(index)
+	}
+
+	/// The visible view under a point in the window
+	pub fn view_at(&self, position: (Real, Real), window: Extent2D) -> Option<usize> {
+		self.visible()
+			.find(|(_, view)| view.viewport(window).rect.contains(position.0, position.1))
+			.map(|(index, _)| index)
+	}
+
+	/// The view receiving input
+	pub fn focused(&self) -> Option<usize> {
+		self.focused
+	}
+
+	/// Routes input to the view under the cursor.
+	/// While a button is held the view keeps the focus, so drags that leave it keep working.
+	pub fn update_focus(&mut self, cursor: Option<(Real, Real)>, is_button_held: bool, window: Extent2D) -> Option<usize> {
+		if !(is_button_held && self.is_captured) {
+			if let Some(cursor) = cursor {
+				self.focused = self.view_at(cursor, window);
+			}
+		}
+		self.is_captured = is_button_held && self.focused.is_some();
+		self.focused
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use math::{Projection, Quaternion, Vector3};
+
+	fn window() -> Extent2D {
+		Extent2D::new(1000.0, 500.0)
+	}
+
+	fn views(layout: ViewLayout) -> RenderViews {
+		let names = ["Perspective", "Top", "Front", "Side"];
+		RenderViews::new(layout, names.map(|name| RenderView::new(name, Camera::default())))
+	}
+
+	#[test]
+	pub fn layouts() {
+		let mut views = views(ViewLayout::Quad);
+		assert_eq!(views.visible().count(), 4);
+		assert_eq!(views.views[3].viewport(window()).rect, Rect::new(500.0, 250.0, 500.0, 250.0));
+		assert_eq!(views.views[1].scissor(window()), Some([500, 0, 500, 250]));
+
+		views.set_layout(ViewLayout::SideBySide);
+		assert_eq!(views.visible().map(|(index, _)| index).collect::<Vec<_>>(), [0, 1]);
+		assert_eq!(views.views[0].viewport(window()).aspect_ratio(), 1.0);
+		assert_eq!(views.view_at((999.0, 499.0), window()), Some(1));
+
+		views.set_layout(ViewLayout::Stacked);
+		assert_eq!(views.view_at((999.0, 499.0), window()), Some(1));
+		assert_eq!(views.view_at((999.0, 100.0), window()), Some(0));
+		assert_eq!(views.view_at((1200.0, 100.0), window()), None);
+	}
+
+	#[test]
+	pub fn cameras_fit_their_views() {
+		let camera = Camera::new(Vector3::new(0.0, 0.0, 5.0), Quaternion::default(), Projection::default());
+		let views = RenderViews::new(ViewLayout::SideBySide, [RenderView::new("Left", camera), RenderView::new("Right", camera)]);
+		let render_camera = views.views[1].render_camera(window());
+		assert_eq!(render_camera.position, camera.position);
+		assert_eq!(render_camera.view_projection, camera.view_projection(1.0));
+	}
+
+	#[test]
+	pub fn input_routing() {
+		let mut views = views(ViewLayout::Quad);
+		assert_eq!(views.update_focus(Some((100.0, 100.0)), false, window()), Some(0));
+		assert_eq!(views.update_focus(Some((900.0, 100.0)), false, window()), Some(1));
+
+		// Dragging out of a view keeps the focus until the button is released
+		assert_eq!(views.update_focus(Some((900.0, 100.0)), true, window()), Some(1));
+		assert_eq!(views.update_focus(Some((100.0, 400.0)), true, window()), Some(1));
+		assert_eq!(views.update_focus(None, true, window()), Some(1));
+		assert_eq!(views.update_focus(Some((100.0, 400.0)), false, window()), Some(2));
+
+		views.set_layout(ViewLayout::Single);
+		assert_eq!(views.focused(), None);
+		assert_eq!(views.update_focus(Some((900.0, 400.0)), false, window()), Some(0));
+	}
+	#[test]
+	pub fn fog_overrides() {
+		let mut view = RenderView::new("Main", Camera::default());
+		let scene = Fog::new(math::Color::WHITE, 0.1);
+		assert_eq!(view.fog(None), Fog::default());
+		assert_eq!(view.fog(Some(&scene)), scene);
+		view.fog = Some(Fog::default());
+		assert_eq!(view.fog(Some(&scene)).density, 0.0);
+	}
+}
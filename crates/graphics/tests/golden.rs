@@ -0,0 +1,90 @@
+//! Draws canonical scenes with the headless renderer and compares them to `tests/goldens`.
+//!
+//! A mismatch means the output visibly changed, and the frame and a diff are written next to the
+//! golden. If the change is intended, regenerate the goldens with
+//! `BLESS_GOLDENS=1 cargo test -p graphics --test golden` and commit the images.
+
+use graphics::{ColorPipeline, DebugDraw, Frame, GoldenImages, SoftwareRenderer, Tonemapper};
+use math::{Camera, Color, Projection, Quaternion, Real, Vector3};
+use std::path::PathBuf;
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 90;
+
+type Scene = fn(&mut DebugDraw);
+
+/// Looks down at the origin from above and to the right, so all three axes are visible
+fn camera() -> Camera {
+	let rotation = Quaternion::from_axis_angle(Vector3::y_axis(), 0.6) * Quaternion::from_axis_angle(Vector3::x_axis(), -0.45);
+	Camera::new(rotation.rotate(Vector3::new(0.0, 0.0, 6.0)), rotation, Projection::default())
+}
+
+/// Intersecting triangles that only look right with the depth test, and an overlay through them
+fn triangles(draw: &mut DebugDraw) {
+	draw.triangle(Vector3::new(-2.0, -1.0, -1.0), Vector3::new(2.0, -1.0, 1.0), Vector3::new(0.0, 2.0, 0.0), Color::RED);
+	draw.triangle(Vector3::new(-2.0, -1.0, 1.0), Vector3::new(2.0, -1.0, -1.0), Vector3::new(0.0, 2.0, 0.0), Color::BLUE);
+	let floor = Color::rgb(0.2, 0.2, 0.2);
+	draw.triangle(Vector3::new(-3.0, -1.0, -3.0), Vector3::new(3.0, -1.0, -3.0), Vector3::new(0.0, -1.0, 3.0), floor);
+	draw.depth_test = false;
+	let overlay = Color::YELLOW.with_alpha(0.5);
+	draw.triangle(Vector3::new(0.5, 0.0, 0.0), Vector3::new(1.5, 0.0, 0.0), Vector3::new(1.0, 1.0, 0.0), overlay);
+}
+
+fn wireframe(draw: &mut DebugDraw) {
+	for index in -4..=4 {
+		let offset = index as Real * 0.5;
+		draw.line(Vector3::new(offset, 0.0, -2.0), Vector3::new(offset, 0.0, 2.0), Color::rgb(0.3, 0.3, 0.3));
+		draw.line(Vector3::new(-2.0, 0.0, offset), Vector3::new(2.0, 0.0, offset), Color::rgb(0.3, 0.3, 0.3));
+	}
+	draw.cuboid(Vector3::new(-1.5, 0.0, -1.5), Vector3::new(-0.5, 1.0, -0.5), Color::GREEN);
+	draw.sphere(Vector3::new(1.0, 0.75, 0.5), 0.75, Color::CYAN);
+	draw.gradient_line(Vector3::new(-2.0, 2.0, 0.0), Vector3::new(2.0, 2.0, 0.0), Color::MAGENTA, Color::YELLOW);
+	draw.depth_test = false;
+	draw.axes(Vector3::zero(), 1.5);
+}
+
+/// A row of quads doubling in brightness, showing how the tonemapper rolls off values above white
+fn tonemapping(draw: &mut DebugDraw) {
+	for index in 0..8 {
+		let (left, brightness) = (index as Real * 0.5 - 2.0, (2.0 as Real).powi(index - 3));
+		let corner = |x: Real, y: Real| Vector3::new(left + x, y, 0.0);
+		let color = Color::rgb(brightness, brightness * 0.6, brightness * 0.3);
+		draw.triangle(corner(0.0, -0.5), corner(0.5, -0.5), corner(0.5, 0.5), color);
+		draw.triangle(corner(0.0, -0.5), corner(0.5, 0.5), corner(0.0, 0.5), color);
+	}
+}
+
+fn render(scene: Scene, pipeline: &ColorPipeline) -> Frame {
+	let mut draw = DebugDraw::default();
+	scene(&mut draw);
+	draw.build();
+	let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+	renderer.clear_color = Color::rgb(0.02, 0.02, 0.05);
+	renderer.render(&draw, &camera().view_projection(renderer.aspect_ratio()), pipeline)
+}
+
+#[test]
+pub fn scenes_match_goldens() {
+	let goldens = GoldenImages::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("goldens"));
+	let hdr = ColorPipeline {
+		tonemapper: Tonemapper::Aces,
+		exposure: 1.0,
+		encode_srgb: true,
+		..Default::default()
+	};
+	let scenes: [(&str, Scene, ColorPipeline); 3] = [
+		("triangles", triangles, ColorPipeline::default()),
+		("wireframe", wireframe, ColorPipeline::default()),
+		("tonemapping", tonemapping, hdr),
+	];
+
+	// Rendering the same scene twice must give the same frame
+	assert_eq!(render(triangles, &ColorPipeline::default()), render(triangles, &ColorPipeline::default()));
+
+	let failures = scenes
+		.iter()
+		.filter_map(|(name, scene, pipeline)| goldens.check(name, &render(*scene, pipeline)).err())
+		.map(|error| error.to_string())
+		.collect::<Vec<_>>();
+	assert!(failures.is_empty(), "Rendered frames no longer match their goldens:\n{}", failures.join("\n"));
+}
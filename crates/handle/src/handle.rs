@@ -0,0 +1,80 @@
+use core::{
+	cmp::Ordering,
+	fmt,
+	hash::{Hash, Hasher},
+	marker::PhantomData,
+};
+
+/// Refers to a value in a `SlotMap<T>`.
+///
+/// Each slot counts how many times it has been reused, and a handle only reaches the value it
+/// was made for, so a stale handle can't reach whatever is stored in the slot next.
+pub struct Handle<T> {
+	index: u32,
+	generation: u32,
+	marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+	pub(crate) const fn new(index: u32, generation: u32) -> Self {
+		Self {
+			index,
+			generation,
+			marker: PhantomData,
+		}
+	}
+
+	/// The slot the value is stored in, which is reused after the value is removed
+	pub const fn index(&self) -> usize {
+		self.index as usize
+	}
+
+	/// How many times the slot had been reused when the value was inserted
+	pub const fn generation(&self) -> u32 {
+		self.generation
+	}
+}
+
+impl<T> Clone for Handle<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+	fn eq(&self, other: &Self) -> bool {
+		(self.index, self.generation) == (other.index, other.generation)
+	}
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> PartialOrd for Handle<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T> Ord for Handle<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		(self.index, self.generation).cmp(&(other.index, other.generation))
+	}
+}
+
+impl<T> Hash for Handle<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		(self.index, self.generation).hash(state);
+	}
+}
+
+impl<T> fmt::Debug for Handle<T> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter
+			.debug_struct("Handle")
+			.field("index", &self.index)
+			.field("generation", &self.generation)
+			.finish()
+	}
+}
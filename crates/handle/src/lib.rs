@@ -0,0 +1,8 @@
+#![no_std]
+
+extern crate alloc;
+
+mod handle;
+mod slot_map;
+
+pub use self::{handle::*, slot_map::*};
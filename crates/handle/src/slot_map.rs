@@ -0,0 +1,250 @@
+use crate::Handle;
+use alloc::vec::Vec;
+use core::{iter::Zip, slice};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Slot {
+	generation: u32,
+
+	/// Where the slot's value is in the dense values, or `None` while the slot is vacant
+	dense: Option<usize>,
+}
+
+/// Values reached through generation-checked handles and stored contiguously for fast iteration.
+///
+/// Removing a value moves the last value into its place, so iteration order isn't insertion
+/// order, but every other handle stays valid. Handles to removed values never reach new ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotMap<T> {
+	values: Vec<T>,
+	handles: Vec<Handle<T>>,
+	slots: Vec<Slot>,
+	vacant: Vec<u32>,
+}
+
+impl<T> Default for SlotMap<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> SlotMap<T> {
+	pub const fn new() -> Self {
+		Self {
+			values: Vec::new(),
+			handles: Vec::new(),
+			slots: Vec::new(),
+			vacant: Vec::new(),
+		}
+	}
+
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			values: Vec::with_capacity(capacity),
+			handles: Vec::with_capacity(capacity),
+			slots: Vec::with_capacity(capacity),
+			vacant: Vec::new(),
+		}
+	}
+
+	pub fn insert(&mut self, value: T) -> Handle<T> {
+		self.insert_with(|_| value)
+	}
+
+	/// Inserts a value built from its own handle, for values that need to know their handle
+	pub fn insert_with(&mut self, value: impl FnOnce(Handle<T>) -> T) -> Handle<T> {
+		let dense = Some(self.values.len());
+		let handle = match self.vacant.pop() {
+			Some(index) => {
+				let slot = &mut self.slots[index as usize];
+				slot.generation += 1;
+				slot.dense = dense;
+				Handle::new(index, slot.generation)
+			},
+			None => {
+				let index = u32::try_from(self.slots.len()).expect("A slot map can hold at most u32::MAX slots");
+				self.slots.push(Slot { generation: 0, dense });
+				Handle::new(index, 0)
+			},
+		};
+		self.values.push(value(handle));
+		self.handles.push(handle);
+		handle
+	}
+
+	/// Removes a value, returning `None` if the handle's value was already removed
+	pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+		let dense = self.dense_index(handle)?;
+		let slot = &mut self.slots[handle.index()];
+		slot.dense = None;
+
+		// A slot whose generation can't count higher is retired rather than reuse a handle
+		if slot.generation < u32::MAX {
+			self.vacant.push(handle.index() as u32);
+		}
+
+		self.handles.swap_remove(dense);
+		if let Some(moved) = self.handles.get(dense) {
+			self.slots[moved.index()].dense = Some(dense);
+		}
+		Some(self.values.swap_remove(dense))
+	}
+
+	/// Removes every value for which `keep` returns false
+	pub fn retain(&mut self, mut keep: impl FnMut(Handle<T>, &mut T) -> bool) {
+		for dense in (0..self.values.len()).rev() {
+			let handle = self.handles[dense];
+			if !keep(handle, &mut self.values[dense]) {
+				self.remove(handle);
+			}
+		}
+	}
+
+	pub fn clear(&mut self) {
+		for handle in self.handles.drain(..) {
+			let slot = &mut self.slots[handle.index()];
+			slot.dense = None;
+			if slot.generation < u32::MAX {
+				self.vacant.push(handle.index() as u32);
+			}
+		}
+		self.values.clear();
+	}
+
+	pub fn contains(&self, handle: Handle<T>) -> bool {
+		self.dense_index(handle).is_some()
+	}
+
+	pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+		self.dense_index(handle).map(|dense| &self.values[dense])
+	}
+
+	pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+		self.dense_index(handle).map(|dense| &mut self.values[dense])
+	}
+
+	/// Where the handle's value is in `values`, which changes when other values are removed
+	pub fn dense_index(&self, handle: Handle<T>) -> Option<usize> {
+		self.slots.get(handle.index()).filter(|slot| slot.generation == handle.generation())?.dense
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	/// Every value, in the same order as `handles`
+	pub fn values(&self) -> &[T] {
+		&self.values
+	}
+
+	pub fn values_mut(&mut self) -> &mut [T] {
+		&mut self.values
+	}
+
+	/// The handle of every value, in the same order as `values`
+	pub fn handles(&self) -> &[Handle<T>] {
+		&self.handles
+	}
+
+	pub fn iter(&self) -> Iter<'_, T> {
+		self.handles.iter().copied().zip(self.values.iter())
+	}
+
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		self.handles.iter().copied().zip(self.values.iter_mut())
+	}
+}
+
+pub type Iter<'a, T> = Zip<core::iter::Copied<slice::Iter<'a, Handle<T>>>, slice::Iter<'a, T>>;
+
+pub type IterMut<'a, T> = Zip<core::iter::Copied<slice::Iter<'a, Handle<T>>>, slice::IterMut<'a, T>>;
+
+impl<'a, T> IntoIterator for &'a SlotMap<T> {
+	type Item = (Handle<T>, &'a T);
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut SlotMap<T> {
+	type Item = (Handle<T>, &'a mut T);
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloc::vec;
+
+	#[test]
+	pub fn stale_handles() {
+		let mut map = SlotMap::new();
+		let first = map.insert("first");
+		assert_eq!(map.remove(first), Some("first"));
+		assert_eq!(map.remove(first), None);
+
+		// The slot is reused, but the old handle doesn't reach the new value
+		let second = map.insert("second");
+		assert_eq!(second.index(), first.index());
+		assert_ne!(second, first);
+		assert_eq!(map.get(first), None);
+		assert_eq!(map.get_mut(first), None);
+		assert!(!map.contains(first));
+		assert_eq!(map.get(second), Some(&"second"));
+	}
+
+	#[test]
+	pub fn dense_iteration() {
+		let mut map = SlotMap::with_capacity(3);
+		let [a, b, c] = [1, 2, 3].map(|value| map.insert(value));
+		assert_eq!(map.values(), [1, 2, 3]);
+
+		// The last value fills the gap, and its handle follows it
+		map.remove(a);
+		assert_eq!(map.values(), [3, 2]);
+		assert_eq!(map.handles(), [c, b]);
+		assert_eq!(map.dense_index(c), Some(0));
+		*map.get_mut(c).unwrap() = 30;
+		for (_, value) in &mut map {
+			*value += 1;
+		}
+		assert_eq!(map.iter().collect::<Vec<_>>(), [(c, &31), (b, &3)]);
+		assert_eq!(map.len(), 2);
+	}
+
+	#[test]
+	pub fn retain_and_clear() {
+		let mut map = SlotMap::new();
+		let handles = (0..6).map(|value| map.insert(value)).collect::<Vec<_>>();
+		map.retain(|_, value| *value % 2 == 0);
+		assert_eq!(map.len(), 3);
+		assert!(handles.iter().all(|handle| map.contains(*handle) == (handle.index() % 2 == 0)));
+
+		map.clear();
+		assert!(map.is_empty());
+		assert!(handles.iter().all(|handle| !map.contains(*handle)));
+		let value = map.insert_with(|handle| handle.generation());
+		assert_eq!(map.get(value), Some(&1));
+	}
+
+	#[test]
+	pub fn exhausted_slots_are_retired() {
+		let mut map = SlotMap::new();
+		let handle = map.insert(());
+		map.slots[handle.index()].generation = u32::MAX;
+		assert_eq!(map.remove(Handle::new(0, u32::MAX)), Some(()));
+		let next = map.insert(());
+		assert_eq!((next.index(), next.generation()), (1, 0));
+		assert_eq!(map.handles(), vec![next]);
+	}
+}
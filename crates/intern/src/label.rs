@@ -0,0 +1,215 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+	borrow::Borrow,
+	cmp::Ordering,
+	collections::HashSet,
+	fmt,
+	hash::{Hash, Hasher},
+	ops::Deref,
+	sync::{Arc, Mutex, OnceLock},
+};
+
+fn interner() -> &'static Mutex<HashSet<Arc<str>>> {
+	static INTERNER: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+	INTERNER.get_or_init(Default::default)
+}
+
+/// Shares one copy of a string between every label made from it.
+/// Interned strings live for the rest of the program, so only intern names from a bounded set.
+pub fn intern(text: &str) -> Arc<str> {
+	let mut interner = interner().lock().unwrap_or_else(|error| error.into_inner());
+	if let Some(interned) = interner.get(text) {
+		return interned.clone();
+	}
+	let interned = Arc::<str>::from(text);
+	interner.insert(interned.clone());
+	interned
+}
+
+#[derive(Clone)]
+enum Repr {
+	Static(&'static str),
+	Interned(Arc<str>),
+}
+
+/// An immutable string for names and labels that are read far more often than they're made,
+/// such as state labels, entity names, and profiling scopes. Literals never allocate,
+/// other labels are interned, and cloning one only bumps a reference count.
+#[derive(Clone)]
+pub struct Label(Repr);
+
+impl Label {
+	pub const fn from_static(text: &'static str) -> Self {
+		Self(Repr::Static(text))
+	}
+
+	pub fn new(text: &str) -> Self {
+		Self(Repr::Interned(intern(text)))
+	}
+
+	pub fn as_str(&self) -> &str {
+		match &self.0 {
+			Repr::Static(text) => text,
+			Repr::Interned(text) => text,
+		}
+	}
+}
+
+impl Default for Label {
+	fn default() -> Self {
+		Self::from_static("")
+	}
+}
+
+impl Deref for Label {
+	type Target = str;
+
+	fn deref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl AsRef<str> for Label {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl Borrow<str> for Label {
+	fn borrow(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl From<&str> for Label {
+	fn from(text: &str) -> Self {
+		Self::new(text)
+	}
+}
+
+impl From<String> for Label {
+	fn from(text: String) -> Self {
+		Self::new(&text)
+	}
+}
+
+impl From<&String> for Label {
+	fn from(text: &String) -> Self {
+		Self::new(text)
+	}
+}
+
+impl From<Label> for String {
+	fn from(label: Label) -> Self {
+		label.as_str().to_string()
+	}
+}
+
+impl PartialEq for Label {
+	fn eq(&self, other: &Self) -> bool {
+		match (&self.0, &other.0) {
+			(Repr::Interned(a), Repr::Interned(b)) if Arc::ptr_eq(a, b) => true,
+			_ => self.as_str() == other.as_str(),
+		}
+	}
+}
+
+impl Eq for Label {}
+
+impl PartialEq<str> for Label {
+	fn eq(&self, other: &str) -> bool {
+		self.as_str() == other
+	}
+}
+
+impl PartialEq<&str> for Label {
+	fn eq(&self, other: &&str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl PartialEq<String> for Label {
+	fn eq(&self, other: &String) -> bool {
+		self.as_str() == other
+	}
+}
+
+impl PartialOrd for Label {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Label {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.as_str().cmp(other.as_str())
+	}
+}
+
+/// Hashes like `str`, so maps keyed by labels can be looked up with a `&str`
+impl Hash for Label {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.as_str().hash(state);
+	}
+}
+
+impl fmt::Display for Label {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(self.as_str())
+	}
+}
+
+impl fmt::Debug for Label {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(self.as_str(), formatter)
+	}
+}
+
+impl Serialize for Label {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.as_str())
+	}
+}
+
+impl<'de> Deserialize<'de> for Label {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+		Ok(Self::new(&text))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	#[test]
+	pub fn interning() {
+		let (first, second) = (Label::new("Player"), Label::from(String::from("Player")));
+		let (Repr::Interned(a), Repr::Interned(b)) = (&first.0, &second.0) else {
+			panic!("Labels made at runtime are interned");
+		};
+		assert!(Arc::ptr_eq(a, b));
+		assert_eq!(first, Label::from_static("Player"));
+		assert_eq!(first, "Player");
+		assert_ne!(first, Label::new("Camera"));
+		assert!(Label::new("Camera") < first);
+		assert_eq!(Label::default().len(), 0);
+	}
+
+	#[test]
+	pub fn lookup_by_str() {
+		let mut scores = HashMap::new();
+		scores.insert(Label::from_static("Level"), 3);
+		assert_eq!(scores.get("Level"), Some(&3));
+		assert_eq!(format!("{} {:?}", Label::new("Level"), Label::new("Level")), "Level \"Level\"");
+	}
+
+	#[test]
+	pub fn serialization() {
+		let label = Label::from_static("Main Menu");
+		let text = ron::to_string(&label).unwrap();
+		assert_eq!(text, "\"Main Menu\"");
+		assert_eq!(ron::from_str::<Label>(&text).unwrap(), label);
+	}
+}
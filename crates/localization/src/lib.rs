@@ -0,0 +1,3 @@
+mod localization;
+
+pub use self::localization::*;
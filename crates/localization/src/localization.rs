@@ -0,0 +1,199 @@
+use fluent::{FluentArgs, FluentBundle, FluentResource};
+use std::{collections::HashMap, fs, io, path::Path};
+use thiserror::Error;
+
+pub use unic_langid::LanguageIdentifier;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to add a Fluent resource to the bundle for language: {0}")]
+	AddResource(LanguageIdentifier),
+
+	#[error("Failed to parse the language identifier: {0}")]
+	ParseLanguage(String),
+
+	#[error("Failed to parse the Fluent resource for language: {0}")]
+	ParseResource(LanguageIdentifier),
+
+	#[error("Failed to read the Fluent resource at path: {1}")]
+	ReadResource(#[source] io::Error, String),
+
+	#[error("Language '{0}' has no loaded bundle.")]
+	UnknownLanguage(LanguageIdentifier),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Parses a language identifier such as `en-US` or `fr`.
+pub fn language(identifier: &str) -> Result<LanguageIdentifier> {
+	identifier.parse().map_err(|_| Error::ParseLanguage(identifier.to_string()))
+}
+
+/// Holds one Fluent bundle per loaded language
+/// and resolves message keys against the active language,
+/// falling back through the fallback chain when a key is missing.
+pub struct Localization {
+	bundles: HashMap<LanguageIdentifier, FluentBundle<FluentResource>>,
+	language: LanguageIdentifier,
+	fallbacks: Vec<LanguageIdentifier>,
+}
+
+impl Localization {
+	pub fn new(language: LanguageIdentifier) -> Self {
+		Self {
+			bundles: HashMap::new(),
+			language,
+			fallbacks: Vec::new(),
+		}
+	}
+
+	pub const fn language(&self) -> &LanguageIdentifier {
+		&self.language
+	}
+
+	/// Switches the active language at runtime.
+	/// The language must already have a loaded bundle.
+	pub fn set_language(&mut self, language: LanguageIdentifier) -> Result<()> {
+		if !self.bundles.contains_key(&language) {
+			return Err(Error::UnknownLanguage(language));
+		}
+		self.language = language;
+		Ok(())
+	}
+
+	/// Sets the languages consulted, in order,
+	/// when a key is missing from the active language.
+	pub fn set_fallbacks(&mut self, fallbacks: Vec<LanguageIdentifier>) {
+		self.fallbacks = fallbacks;
+	}
+
+	pub fn fallbacks(&self) -> &[LanguageIdentifier] {
+		&self.fallbacks
+	}
+
+	pub fn languages(&self) -> Vec<&LanguageIdentifier> {
+		self.bundles.keys().collect()
+	}
+
+	/// Adds FTL source to the bundle for the given language,
+	/// creating the bundle if it does not exist yet.
+	pub fn add_source(&mut self, language: LanguageIdentifier, source: impl Into<String>) -> Result<()> {
+		let resource = FluentResource::try_new(source.into()).map_err(|_| Error::ParseResource(language.clone()))?;
+		let bundle = self.bundles.entry(language.clone()).or_insert_with(|| {
+			let mut bundle = FluentBundle::new(vec![language.clone()]);
+			bundle.set_use_isolating(false);
+			bundle
+		});
+		bundle.add_resource(resource).map_err(|_| Error::AddResource(language))
+	}
+
+	/// Loads an FTL file from disk into the bundle for the given language.
+	pub fn load_file(&mut self, language: LanguageIdentifier, path: impl AsRef<Path>) -> Result<()> {
+		let path = path.as_ref();
+		let source = fs::read_to_string(path).map_err(|error| Error::ReadResource(error, path.display().to_string()))?;
+		self.add_source(language, source)
+	}
+
+	pub fn has_text(&self, key: &str) -> bool {
+		self.search_order().any(|bundle| bundle.has_message(key))
+	}
+
+	/// Looks up a message, returning the key itself if no language in the chain defines it.
+	pub fn text(&self, key: &str) -> String {
+		self.text_with_args(key, None)
+	}
+
+	pub fn text_with_args(&self, key: &str, args: Option<&FluentArgs>) -> String {
+		self.search_order()
+			.find_map(|bundle| {
+				let pattern = bundle.get_message(key)?.value()?;
+				let mut errors = Vec::new();
+				Some(bundle.format_pattern(pattern, args, &mut errors).into_owned())
+			})
+			.unwrap_or_else(|| key.to_string())
+	}
+
+	fn search_order(&self) -> impl Iterator<Item = &FluentBundle<FluentResource>> {
+		std::iter::once(&self.language)
+			.chain(self.fallbacks.iter())
+			.filter_map(|language| self.bundles.get(language))
+	}
+}
+
+/// A component that refers to localized text by key,
+/// so that displayed strings follow the active language.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LocalizedText {
+	pub key: String,
+	pub args: Vec<(String, String)>,
+}
+
+impl LocalizedText {
+	pub fn new(key: impl Into<String>) -> Self {
+		Self {
+			key: key.into(),
+			args: Vec::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn with_arg(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+		self.args.push((name.into(), value.into()));
+		self
+	}
+
+	pub fn resolve(&self, localization: &Localization) -> String {
+		if self.args.is_empty() {
+			return localization.text(&self.key);
+		}
+		let mut args = FluentArgs::new();
+		self.args.iter().for_each(|(name, value)| args.set(name.as_str(), value.as_str()));
+		localization.text_with_args(&self.key, Some(&args))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn localization() -> Result<Localization> {
+		let mut localization = Localization::new(language("fr")?);
+		localization.add_source(language("en-US")?, "greeting = Hello\nfarewell = Goodbye\nwelcome = Welcome, { $name }!")?;
+		localization.add_source(language("fr")?, "greeting = Bonjour")?;
+		localization.set_fallbacks(vec![language("en-US")?]);
+		Ok(localization)
+	}
+
+	#[test]
+	pub fn lookup() -> Result<()> {
+		let localization = localization()?;
+		assert_eq!(localization.text("greeting"), "Bonjour");
+		Ok(())
+	}
+
+	#[test]
+	pub fn fallback() -> Result<()> {
+		let localization = localization()?;
+		assert_eq!(localization.text("farewell"), "Goodbye");
+		assert_eq!(localization.text("missing"), "missing");
+		assert!(!localization.has_text("missing"));
+		Ok(())
+	}
+
+	#[test]
+	pub fn switch_language() -> Result<()> {
+		let mut localization = localization()?;
+		localization.set_language(language("en-US")?)?;
+		assert_eq!(localization.text("greeting"), "Hello");
+		assert!(localization.set_language(language("de")?).is_err());
+		Ok(())
+	}
+
+	#[test]
+	pub fn localized_text() -> Result<()> {
+		let localization = localization()?;
+		let text = LocalizedText::new("welcome").with_arg("name", "Elliot");
+		assert_eq!(text.resolve(&localization), "Welcome, Elliot!");
+		Ok(())
+	}
+}
@@ -0,0 +1,93 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use math::{Quaternion, Real, Transform, Vector3};
+use std::time::Duration;
+
+const COUNT: usize = 1_000_000;
+
+fn vectors() -> Vec<Vector3> {
+	(0..COUNT)
+		.map(|index| {
+			let value = index as Real * 0.001;
+			Vector3::new(value.sin(), value.cos(), value)
+		})
+		.collect()
+}
+
+fn vector_addition(c: &mut Criterion) {
+	c.bench_function("adding 1 million vectors", |b| {
+		let vectors = vectors();
+		b.iter(|| vectors.iter().fold(Vector3::zero(), |sum, vector| sum + *vector))
+	});
+}
+
+fn vector_products(c: &mut Criterion) {
+	c.bench_function("taking dot and cross products of 1 million vectors", |b| {
+		let vectors = vectors();
+		let axis = Vector3::new(1.0, 2.0, 3.0);
+		b.iter(|| vectors.iter().map(|vector| vector.cross(&axis).dot(vector)).sum::<Real>())
+	});
+}
+
+fn vector_normalization(c: &mut Criterion) {
+	c.bench_function("normalizing 1 million vectors", |b| {
+		let vectors = vectors();
+		b.iter(|| {
+			for vector in vectors.iter() {
+				black_box(vector.normalize());
+			}
+		})
+	});
+}
+
+fn quaternion_rotation(c: &mut Criterion) {
+	c.bench_function("rotating 1 million vectors by a quaternion", |b| {
+		let vectors = vectors();
+		let rotation = Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0).normalize(), 0.5);
+		b.iter(|| {
+			for vector in vectors.iter() {
+				black_box(rotation.rotate(*vector));
+			}
+		})
+	});
+}
+
+fn quaternion_integration(c: &mut Criterion) {
+	c.bench_function("integrating 1 million angular velocities", |b| {
+		let vectors = vectors();
+		b.iter(|| {
+			vectors
+				.iter()
+				.fold(Quaternion::identity(), |orientation, velocity| orientation.integrate(*velocity, 0.001))
+		})
+	});
+}
+
+fn transform_composition(c: &mut Criterion) {
+	c.bench_function("composing and applying 1 million transforms", |b| {
+		let vectors = vectors();
+		let parent = Transform {
+			translation: Vector3::new(1.0, 2.0, 3.0),
+			rotation: Quaternion::from_axis_angle(Vector3::y_axis(), 0.3),
+			..Transform::identity()
+		};
+		b.iter(|| {
+			for vector in vectors.iter() {
+				black_box((parent * Transform::from_translation(*vector)).transform_point(*vector));
+			}
+		})
+	});
+}
+
+criterion_group!(
+	name = benches;
+	config = Criterion::default().measurement_time(Duration::from_secs(10));
+	targets =
+		vector_addition,
+		vector_products,
+		vector_normalization,
+		quaternion_rotation,
+		quaternion_integration,
+		transform_composition
+);
+
+criterion_main!(benches);
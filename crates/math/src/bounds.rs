@@ -0,0 +1,134 @@
+use crate::{Matrix4, Real, Vector3};
+
+/// Transforms a point by a column-major matrix, ignoring any projection
+#[must_use]
+pub fn transform_point(matrix: &Matrix4, point: Vector3) -> Vector3 {
+	let row = |row: usize| matrix[0][row] * point.x() + matrix[1][row] * point.y() + matrix[2][row] * point.z() + matrix[3][row];
+	Vector3::new(row(0), row(1), row(2))
+}
+
+/// A box aligned with the world axes, such as a curb, a step, or the bounds of a mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+	pub min: Vector3,
+	pub max: Vector3,
+}
+
+impl Aabb {
+	#[must_use]
+	pub fn from_center(center: Vector3, half_extents: Vector3) -> Self {
+		Self {
+			min: center - half_extents,
+			max: center + half_extents,
+		}
+	}
+
+	#[must_use]
+	pub fn center(&self) -> Vector3 {
+		(self.min + self.max) * 0.5
+	}
+
+	#[must_use]
+	pub fn half_extents(&self) -> Vector3 {
+		(self.max - self.min) * 0.5
+	}
+
+	#[must_use]
+	pub fn contains(&self, point: Vector3) -> bool {
+		(0..3).all(|axis| (self.min[axis]..=self.max[axis]).contains(&point[axis]))
+	}
+
+	#[must_use]
+	pub fn intersects(&self, other: &Self) -> bool {
+		(0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+	}
+
+	/// The box enclosing this one after it has been transformed
+	#[must_use]
+	pub fn transformed(&self, matrix: &Matrix4) -> Self {
+		let center = transform_point(matrix, self.center());
+		let half_extents = self.half_extents();
+		let mut extents = Vector3::zero();
+		for row in 0..3 {
+			extents[row] = (0..3).map(|column| matrix[column][row].abs() * half_extents[column]).sum();
+		}
+		Self::from_center(center, extents)
+	}
+
+	#[must_use]
+	pub fn bounding_sphere(&self) -> BoundingSphere {
+		BoundingSphere::new(self.center(), self.half_extents().magnitude())
+	}
+}
+
+/// A sphere enclosing a mesh in its local space
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingSphere {
+	pub center: Vector3,
+	pub radius: Real,
+}
+
+impl BoundingSphere {
+	#[must_use]
+	pub fn new(center: Vector3, radius: Real) -> Self {
+		Self { center, radius }
+	}
+
+	/// Moves the sphere into world space, growing it by the largest axis scale of the transform
+	#[must_use]
+	pub fn transformed(&self, model: &Matrix4) -> Self {
+		let scale = (0..3)
+			.map(|column| Vector3::new(model[column][0], model[column][1], model[column][2]).magnitude())
+			.fold(0.0, Real::max);
+		Self {
+			center: transform_point(model, self.center),
+			radius: self.radius * scale,
+		}
+	}
+
+	#[must_use]
+	pub fn intersects(&self, other: &Self) -> bool {
+		let radius = self.radius + other.radius;
+		(self.center - other.center).magnitude_squared() <= radius * radius
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Transform;
+
+	#[test]
+	pub fn aabb() {
+		let aabb = Aabb::from_center(Vector3::new(1.0, 0.0, 0.0), Vector3::new(1.0, 2.0, 3.0));
+		assert_eq!(aabb.min, Vector3::new(0.0, -2.0, -3.0));
+		assert!(aabb.contains(Vector3::new(2.0, 2.0, 0.0)));
+		assert!(!aabb.contains(Vector3::new(2.1, 0.0, 0.0)));
+		assert!(aabb.intersects(&Aabb::from_center(Vector3::new(3.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0))));
+		assert!(!aabb.intersects(&Aabb::from_center(Vector3::new(3.5, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0))));
+		assert_eq!(aabb.bounding_sphere().radius, (14.0 as Real).sqrt());
+	}
+
+	#[test]
+	pub fn transformed_aabb() {
+		let transform = Transform {
+			translation: Vector3::new(0.0, 5.0, 0.0),
+			rotation: crate::Quaternion::from_axis_angle(Vector3::y_axis(), core::f32::consts::FRAC_PI_2),
+			scale: Vector3::new(2.0, 1.0, 1.0),
+		};
+		let aabb = Aabb::from_center(Vector3::zero(), Vector3::new(1.0, 1.0, 3.0)).transformed(&transform.to_matrix());
+		assert_eq!(aabb.center(), Vector3::new(0.0, 5.0, 0.0));
+		assert_eq!(aabb.half_extents(), Vector3::new(3.0, 1.0, 2.0));
+	}
+
+	#[test]
+	pub fn transformed_sphere() {
+		let mut model = Transform::default().to_matrix();
+		model[0][0] = 2.0;
+		model[3][1] = 5.0;
+		let sphere = BoundingSphere::new(Vector3::zero(), 1.0).transformed(&model);
+		assert_eq!(sphere, BoundingSphere::new(Vector3::new(0.0, 5.0, 0.0), 2.0));
+		assert!(sphere.intersects(&BoundingSphere::new(Vector3::new(0.0, 8.0, 0.0), 1.0)));
+		assert!(!sphere.intersects(&BoundingSphere::new(Vector3::new(0.0, 8.1, 0.0), 1.0)));
+	}
+}
@@ -0,0 +1,217 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::{Matrix4, Quaternion, Ray, Real, Vector3, Viewport};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+	Perspective {
+		/// Vertical field of view in radians
+		fov_y: Real,
+		near: Real,
+		far: Real,
+	},
+	Orthographic {
+		/// World units visible from the bottom of the view to the top
+		height: Real,
+		near: Real,
+		far: Real,
+	},
+}
+
+impl Default for Projection {
+	fn default() -> Self {
+		Self::Perspective {
+			fov_y: core::f32::consts::FRAC_PI_3,
+			near: 0.1,
+			far: 1000.0,
+		}
+	}
+}
+
+impl Projection {
+	/// A right-handed projection matrix with a `0..1` depth range
+	#[must_use]
+	pub fn matrix(&self, aspect_ratio: Real) -> Matrix4 {
+		match *self {
+			Self::Perspective { fov_y, near, far } => {
+				let focal_length = 1.0 / (fov_y * 0.5).tan();
+				let depth = far / (near - far);
+				[
+					[focal_length / aspect_ratio, 0.0, 0.0, 0.0],
+					[0.0, focal_length, 0.0, 0.0],
+					[0.0, 0.0, depth, -1.0],
+					[0.0, 0.0, near * depth, 0.0],
+				]
+			},
+			Self::Orthographic { height, near, far } => {
+				let depth = 1.0 / (near - far);
+				[
+					[2.0 / (height * aspect_ratio), 0.0, 0.0, 0.0],
+					[0.0, 2.0 / height, 0.0, 0.0],
+					[0.0, 0.0, depth, 0.0],
+					[0.0, 0.0, near * depth, 1.0],
+				]
+			},
+		}
+	}
+}
+
+/// A point of view into the world, looking down its local -z axis with +y up
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+	pub position: Vector3,
+	pub rotation: Quaternion,
+	pub projection: Projection,
+}
+
+impl Camera {
+	#[must_use]
+	pub fn new(position: Vector3, rotation: Quaternion, projection: Projection) -> Self {
+		Self { position, rotation, projection }
+	}
+
+	#[must_use]
+	pub fn forward(&self) -> Vector3 {
+		self.rotation.rotate(Vector3::z_axis().inverse())
+	}
+
+	#[must_use]
+	pub fn right(&self) -> Vector3 {
+		self.rotation.rotate(Vector3::x_axis())
+	}
+
+	#[must_use]
+	pub fn up(&self) -> Vector3 {
+		self.rotation.rotate(Vector3::y_axis())
+	}
+
+	/// Transforms world space into the camera's space
+	#[must_use]
+	pub fn view_matrix(&self) -> Matrix4 {
+		let (right, up, back) = (self.right(), self.up(), self.forward().inverse());
+		[
+			[right.x(), up.x(), back.x(), 0.0],
+			[right.y(), up.y(), back.y(), 0.0],
+			[right.z(), up.z(), back.z(), 0.0],
+			[-right.dot(&self.position), -up.dot(&self.position), -back.dot(&self.position), 1.0],
+		]
+	}
+
+	#[must_use]
+	pub fn view_projection(&self, aspect_ratio: Real) -> Matrix4 {
+		let (projection, view) = (self.projection.matrix(aspect_ratio), self.view_matrix());
+		let mut matrix = [[0.0; 4]; 4];
+		for (column, output) in matrix.iter_mut().enumerate() {
+			for (row, value) in output.iter_mut().enumerate() {
+				*value = (0..4).map(|index| projection[index][row] * view[column][index]).sum();
+			}
+		}
+		matrix
+	}
+
+	/// The ray from the camera through a point on the screen, for picking things in the world.
+	/// The cursor and viewport must be in the same units, so convert a logical viewport with
+	/// `Viewport::to_physical` before using physical cursor positions like winit's.
+	#[must_use]
+	pub fn screen_to_ray(&self, cursor: (Real, Real), viewport: &Viewport) -> Ray {
+		let (x, y) = viewport.window_to_ndc(cursor.0, cursor.1);
+		let aspect_ratio = viewport.aspect_ratio();
+		match self.projection {
+			Projection::Perspective { fov_y, .. } => {
+				let half_height = (fov_y * 0.5).tan();
+				let direction = self.forward() + self.right() * (x * half_height * aspect_ratio) + self.up() * (y * half_height);
+				Ray::new(self.position, direction)
+			},
+			Projection::Orthographic { height, .. } => {
+				let half_height = height * 0.5;
+				let origin = self.position + self.right() * (x * half_height * aspect_ratio) + self.up() * (y * half_height);
+				Ray::new(origin, self.forward())
+			},
+		}
+	}
+
+	/// Where a point appears on the screen, in the viewport's units, or `None` behind the camera.
+	/// Points outside the view are still returned, so markers can be clamped to the screen's edge.
+	#[must_use]
+	pub fn world_to_screen(&self, point: Vector3, viewport: &Viewport) -> Option<(Real, Real)> {
+		let offset = point - self.position;
+		let (x, y, depth) = (offset.dot(&self.right()), offset.dot(&self.up()), offset.dot(&self.forward()));
+		let aspect_ratio = viewport.aspect_ratio();
+		let half_height = match self.projection {
+			Projection::Perspective { fov_y, near, .. } => {
+				if depth < near {
+					return None;
+				}
+				(fov_y * 0.5).tan() * depth
+			},
+			Projection::Orthographic { height, near, .. } => {
+				if depth < near {
+					return None;
+				}
+				height * 0.5
+			},
+		};
+		Some(viewport.ndc_to_window(x / (half_height * aspect_ratio), y / half_height))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Extent2D, Frustum, Rect};
+
+	fn viewport() -> Viewport {
+		Viewport::new(Rect::new(100.0, 50.0, 800.0, 600.0))
+	}
+
+	fn camera(projection: Projection) -> Camera {
+		Camera::new(
+			Vector3::new(1.0, 2.0, 3.0),
+			Quaternion::from_axis_angle(Vector3::y_axis(), 0.5) * Quaternion::from_axis_angle(Vector3::x_axis(), -0.3),
+			projection,
+		)
+	}
+
+	#[test]
+	pub fn round_trip() {
+		let orthographic = Projection::Orthographic {
+			height: 10.0,
+			near: 0.1,
+			far: 100.0,
+		};
+		for camera in [camera(Projection::default()), camera(orthographic)] {
+			let center = camera.screen_to_ray((500.0, 350.0), &viewport());
+			assert_eq!(center.direction, camera.forward());
+			for cursor in [(150.0, 80.0), (500.0, 350.0), (850.0, 600.0)] {
+				let point = camera.screen_to_ray(cursor, &viewport()).point_at(7.0);
+				let (x, y) = camera.world_to_screen(point, &viewport()).unwrap();
+				assert!((x - cursor.0).abs() < 1e-2 && (y - cursor.1).abs() < 1e-2, "{cursor:?} became {:?}", (x, y));
+			}
+			assert_eq!(camera.world_to_screen(camera.position - camera.forward(), &viewport()), None);
+		}
+	}
+
+	#[test]
+	pub fn matches_view_projection() {
+		let camera = camera(Projection::default());
+		let matrix = camera.view_projection(viewport().aspect_ratio());
+		let point = camera.screen_to_ray((300.0, 200.0), &viewport()).point_at(20.0);
+		let clip = (0..4)
+			.map(|row| (0..3).map(|column| matrix[column][row] * point[column]).sum::<Real>() + matrix[3][row])
+			.collect::<alloc::vec::Vec<_>>();
+		let (x, y) = viewport().ndc_to_window(clip[0] / clip[3], clip[1] / clip[3]);
+		assert!((x - 300.0).abs() < 1e-2 && (y - 200.0).abs() < 1e-2);
+		assert!((0.0..=1.0).contains(&(clip[2] / clip[3])));
+		assert!(Frustum::from_view_projection(&matrix).contains_point(point));
+		assert!(!Frustum::from_view_projection(&matrix).contains_point(camera.position - camera.forward()));
+	}
+
+	#[test]
+	pub fn scale_factor() {
+		// A cursor in physical pixels on a high DPI display hits the same point as a logical one
+		let camera = camera(Projection::default());
+		let logical = Viewport::from_extent(Extent2D::new(800.0, 600.0));
+		let ray = camera.screen_to_ray((200.0, 150.0), &logical);
+		assert_eq!(camera.screen_to_ray((400.0, 300.0), &logical.to_physical(2.0)), ray);
+	}
+}
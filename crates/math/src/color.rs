@@ -0,0 +1,187 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::Real;
+
+/// A color with linear RGB components and straight alpha, as lighting and blending expect.
+/// Colors from image editors and color pickers are sRGB, so use `from_srgb` or `from_hsv`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+	pub r: Real,
+	pub g: Real,
+	pub b: Real,
+	pub a: Real,
+}
+
+/// Defaults to white, which leaves whatever it tints unchanged
+impl Default for Color {
+	fn default() -> Self {
+		Self::WHITE
+	}
+}
+
+impl Color {
+	pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+	pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+	pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+	pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+	pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+	pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+	pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+	pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+	pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+
+	#[must_use]
+	pub const fn new(r: Real, g: Real, b: Real, a: Real) -> Self {
+		Self { r, g, b, a }
+	}
+
+	/// An opaque color
+	#[must_use]
+	pub const fn rgb(r: Real, g: Real, b: Real) -> Self {
+		Self::new(r, g, b, 1.0)
+	}
+
+	#[must_use]
+	pub const fn with_alpha(&self, a: Real) -> Self {
+		Self::new(self.r, self.g, self.b, a)
+	}
+
+	/// Decodes sRGB components in the range 0 to 1. Alpha is never encoded, so it is kept as is.
+	#[must_use]
+	pub fn from_srgb(r: Real, g: Real, b: Real, a: Real) -> Self {
+		Self::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a)
+	}
+
+	/// Decodes 8-bit sRGB components, such as those of `#ff8000ff` or a pixel of a color texture
+	#[must_use]
+	pub fn from_srgb8(r: u8, g: u8, b: u8, a: u8) -> Self {
+		let unit = |component: u8| Real::from(component) / 255.0;
+		Self::from_srgb(unit(r), unit(g), unit(b), unit(a))
+	}
+
+	/// Encodes the color as sRGB components in the range 0 to 1
+	#[must_use]
+	pub fn to_srgb(&self) -> [Real; 4] {
+		[linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b), self.a]
+	}
+
+	/// Encodes the color as 8-bit sRGB components, clamping any outside the range 0 to 1
+	#[must_use]
+	pub fn to_srgb8(&self) -> [u8; 4] {
+		self.to_srgb().map(|component| (component.clamp(0.0, 1.0) * 255.0 + 0.5) as u8)
+	}
+
+	/// An opaque color from a hue in degrees, and saturation and value from 0 to 1.
+	/// HSV describes sRGB colors the way color pickers show them, so the result is decoded.
+	#[must_use]
+	pub fn from_hsv(hue: Real, saturation: Real, value: Real) -> Self {
+		let hue = ((hue % 360.0) + 360.0) % 360.0 / 60.0;
+		let chroma = value * saturation;
+		let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+		let (r, g, b) = match hue as u32 {
+			0 => (chroma, x, 0.0),
+			1 => (x, chroma, 0.0),
+			2 => (0.0, chroma, x),
+			3 => (0.0, x, chroma),
+			4 => (x, 0.0, chroma),
+			_ => (chroma, 0.0, x),
+		};
+		let minimum = value - chroma;
+		Self::from_srgb(r + minimum, g + minimum, b + minimum, 1.0)
+	}
+
+	/// The hue in degrees, and saturation and value from 0 to 1, of the color's sRGB encoding
+	#[must_use]
+	pub fn to_hsv(&self) -> (Real, Real, Real) {
+		let [r, g, b, _] = self.to_srgb();
+		let maximum = r.max(g).max(b);
+		let chroma = maximum - r.min(g).min(b);
+		let hue = if chroma == 0.0 {
+			0.0
+		} else if maximum == r {
+			60.0 * ((g - b) / chroma)
+		} else if maximum == g {
+			60.0 * ((b - r) / chroma + 2.0)
+		} else {
+			60.0 * ((r - g) / chroma + 4.0)
+		};
+		let saturation = if maximum == 0.0 { 0.0 } else { chroma / maximum };
+		(if hue < 0.0 { hue + 360.0 } else { hue }, saturation, maximum)
+	}
+
+	/// Blends linearly from this color at `t = 0` to `rhs` at `t = 1`
+	#[must_use]
+	pub fn lerp(&self, rhs: &Self, t: Real) -> Self {
+		let mix = |start: Real, end: Real| start + (end - start) * t;
+		Self::new(mix(self.r, rhs.r), mix(self.g, rhs.g), mix(self.b, rhs.b), mix(self.a, rhs.a))
+	}
+}
+
+impl From<[Real; 4]> for Color {
+	fn from([r, g, b, a]: [Real; 4]) -> Self {
+		Self::new(r, g, b, a)
+	}
+}
+
+impl From<Color> for [Real; 4] {
+	fn from(color: Color) -> Self {
+		[color.r, color.g, color.b, color.a]
+	}
+}
+
+fn srgb_to_linear(component: Real) -> Real {
+	if component <= 0.04045 {
+		component / 12.92
+	} else {
+		((component + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+fn linear_to_srgb(component: Real) -> Real {
+	if component <= 0.003_130_8 {
+		component * 12.92
+	} else {
+		1.055 * component.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(actual: Real, expected: Real) {
+		assert!((actual - expected).abs() < 1e-4, "left: {actual:?} not close to right: {expected:?}");
+	}
+
+	#[test]
+	pub fn srgb() {
+		let orange = Color::from_srgb8(255, 128, 0, 255);
+		assert_close(orange.r, 1.0);
+		assert_close(orange.g, 0.215_861);
+		assert_close(orange.b, 0.0);
+		assert_eq!(orange.to_srgb8(), [255, 128, 0, 255]);
+		assert_eq!(Color::rgb(0.5, 0.0, 2.0).to_srgb8(), [188, 0, 255, 255]);
+		for value in [0.0, 0.001, 0.2, 0.5, 1.0] {
+			assert_close(srgb_to_linear(linear_to_srgb(value)), value);
+		}
+	}
+
+	#[test]
+	pub fn hsv() {
+		assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+		assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+		assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::BLUE);
+		let (hue, saturation, value) = Color::from_hsv(200.0, 0.5, 0.8).to_hsv();
+		assert_close(hue, 200.0);
+		assert_close(saturation, 0.5);
+		assert_close(value, 0.8);
+		assert_eq!(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+	}
+
+	#[test]
+	pub fn lerp() {
+		let halfway = Color::BLACK.lerp(&Color::WHITE.with_alpha(0.0), 0.5);
+		assert_eq!(halfway, Color::new(0.5, 0.5, 0.5, 0.5));
+		assert_eq!(<[Real; 4]>::from(halfway), [0.5; 4]);
+	}
+}
@@ -0,0 +1,245 @@
+//! Conversions to and from other math libraries' types, each behind a feature named after it
+
+#[cfg(any(feature = "mint", feature = "nalgebra"))]
+use crate::Real;
+#[cfg(any(feature = "glam", feature = "nalgebra"))]
+use crate::Transform;
+use crate::{Quaternion, Vector3};
+
+#[cfg(feature = "mint")]
+impl From<mint::Vector3<Real>> for Vector3 {
+	fn from(vector: mint::Vector3<Real>) -> Self {
+		Self::new(vector.x, vector.y, vector.z)
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector3> for mint::Vector3<Real> {
+	fn from(vector: Vector3) -> Self {
+		Self {
+			x: vector.x(),
+			y: vector.y(),
+			z: vector.z(),
+		}
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::Point3<Real>> for Vector3 {
+	fn from(point: mint::Point3<Real>) -> Self {
+		Self::new(point.x, point.y, point.z)
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Vector3> for mint::Point3<Real> {
+	fn from(vector: Vector3) -> Self {
+		Self {
+			x: vector.x(),
+			y: vector.y(),
+			z: vector.z(),
+		}
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<mint::Quaternion<Real>> for Quaternion {
+	fn from(quaternion: mint::Quaternion<Real>) -> Self {
+		Self {
+			x: quaternion.v.x,
+			y: quaternion.v.y,
+			z: quaternion.v.z,
+			w: quaternion.s,
+		}
+	}
+}
+
+#[cfg(feature = "mint")]
+impl From<Quaternion> for mint::Quaternion<Real> {
+	fn from(quaternion: Quaternion) -> Self {
+		Self {
+			v: mint::Vector3 {
+				x: quaternion.x,
+				y: quaternion.y,
+				z: quaternion.z,
+			},
+			s: quaternion.w,
+		}
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Vec3> for Vector3 {
+	fn from(vector: glam::Vec3) -> Self {
+		Self::new(vector.x, vector.y, vector.z)
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<Vector3> for glam::Vec3 {
+	fn from(vector: Vector3) -> Self {
+		Self::new(vector.x(), vector.y(), vector.z())
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<glam::Quat> for Quaternion {
+	fn from(quaternion: glam::Quat) -> Self {
+		Self {
+			x: quaternion.x,
+			y: quaternion.y,
+			z: quaternion.z,
+			w: quaternion.w,
+		}
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<Quaternion> for glam::Quat {
+	fn from(quaternion: Quaternion) -> Self {
+		Self::from_xyzw(quaternion.x, quaternion.y, quaternion.z, quaternion.w)
+	}
+}
+
+/// Decomposes the matrix, which only round trips for matrices without shear
+#[cfg(feature = "glam")]
+impl From<glam::Mat4> for Transform {
+	fn from(matrix: glam::Mat4) -> Self {
+		let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+		Self {
+			translation: translation.into(),
+			rotation: rotation.into(),
+			scale: scale.into(),
+		}
+	}
+}
+
+#[cfg(feature = "glam")]
+impl From<Transform> for glam::Mat4 {
+	fn from(transform: Transform) -> Self {
+		Self::from_scale_rotation_translation(transform.scale.into(), transform.rotation.into(), transform.translation.into())
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Vector3<Real>> for Vector3 {
+	fn from(vector: nalgebra::Vector3<Real>) -> Self {
+		Self::new(vector.x, vector.y, vector.z)
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Vector3> for nalgebra::Vector3<Real> {
+	fn from(vector: Vector3) -> Self {
+		Self::new(vector.x(), vector.y(), vector.z())
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Point3<Real>> for Vector3 {
+	fn from(point: nalgebra::Point3<Real>) -> Self {
+		Self::new(point.x, point.y, point.z)
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Vector3> for nalgebra::Point3<Real> {
+	fn from(vector: Vector3) -> Self {
+		Self::new(vector.x(), vector.y(), vector.z())
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Translation3<Real>> for Vector3 {
+	fn from(translation: nalgebra::Translation3<Real>) -> Self {
+		Self::new(translation.x, translation.y, translation.z)
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Vector3> for nalgebra::Translation3<Real> {
+	fn from(vector: Vector3) -> Self {
+		Self::new(vector.x(), vector.y(), vector.z())
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::UnitQuaternion<Real>> for Quaternion {
+	fn from(quaternion: nalgebra::UnitQuaternion<Real>) -> Self {
+		Self {
+			x: quaternion.i,
+			y: quaternion.j,
+			z: quaternion.k,
+			w: quaternion.w,
+		}
+	}
+}
+
+/// Expects the quaternion to be normalized, as elder keeps its rotations
+#[cfg(feature = "nalgebra")]
+impl From<Quaternion> for nalgebra::UnitQuaternion<Real> {
+	fn from(quaternion: Quaternion) -> Self {
+		Self::new_unchecked(nalgebra::Quaternion::new(quaternion.w, quaternion.x, quaternion.y, quaternion.z))
+	}
+}
+
+#[cfg(feature = "nalgebra")]
+impl From<Transform> for nalgebra::Matrix4<Real> {
+	fn from(transform: Transform) -> Self {
+		let translation = nalgebra::Translation3::from(transform.translation).to_homogeneous();
+		let rotation = nalgebra::UnitQuaternion::from(transform.rotation).to_homogeneous();
+		translation * rotation * Self::new_nonuniform_scaling(&transform.scale.into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(any(feature = "glam", feature = "nalgebra"))]
+	fn transform() -> Transform {
+		Transform {
+			translation: Vector3::new(1.0, 2.0, 3.0),
+			rotation: Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0).normalize(), 0.5),
+			scale: Vector3::new(2.0, 2.0, 2.0),
+		}
+	}
+
+	#[cfg(feature = "mint")]
+	#[test]
+	pub fn mint() {
+		let vector = Vector3::new(1.0, 2.0, 3.0);
+		assert_eq!(Vector3::from(mint::Vector3::from(vector)), vector);
+		assert_eq!(Vector3::from(mint::Point3::from(vector)), vector);
+		let rotation = Quaternion::from_axis_angle(Vector3::y_axis(), 0.5);
+		assert_eq!(Quaternion::from(mint::Quaternion::from(rotation)), rotation);
+	}
+
+	#[cfg(feature = "glam")]
+	#[test]
+	pub fn glam() {
+		let transform = transform();
+		assert_eq!(glam::Vec3::from(transform.translation), glam::Vec3::new(1.0, 2.0, 3.0));
+		let point = Vector3::new(-1.0, 0.5, 4.0);
+		let expected = transform.transform_point(point);
+		let actual = Vector3::from(glam::Mat4::from(transform).transform_point3(point.into()));
+		assert!((actual - expected).magnitude() < 1e-5);
+		let round_trip = Transform::from(glam::Mat4::from(transform));
+		assert!((round_trip.translation - transform.translation).magnitude() < 1e-5);
+		assert!(round_trip.rotation.approximately_equals(&transform.rotation, 1e-5));
+	}
+
+	#[cfg(feature = "nalgebra")]
+	#[test]
+	pub fn nalgebra() {
+		let transform = transform();
+		assert_eq!(nalgebra::Translation3::from(transform.translation).vector, nalgebra::Vector3::new(1.0, 2.0, 3.0));
+		let point = Vector3::new(-1.0, 0.5, 4.0);
+		let expected = transform.transform_point(point);
+		let actual = Vector3::from(nalgebra::Matrix4::from(transform).transform_point(&point.into()));
+		assert!((actual - expected).magnitude() < 1e-5);
+		let rotated = nalgebra::UnitQuaternion::from(transform.rotation) * nalgebra::Vector3::from(point);
+		assert!((Vector3::from(rotated) - transform.rotation.rotate(point)).magnitude() < 1e-5);
+		assert_eq!(Quaternion::from(nalgebra::UnitQuaternion::from(transform.rotation)), transform.rotation);
+	}
+}
@@ -0,0 +1,215 @@
+use crate::{Real, Vector3};
+use alloc::vec::Vec;
+
+/// A path through space, traced from `t = 0` to `t = 1`
+pub trait Curve {
+	fn point(&self, t: Real) -> Vector3;
+
+	/// The derivative of the point with respect to `t`, whose length is how fast it's traced
+	fn tangent(&self, t: Real) -> Vector3;
+}
+
+/// A cubic Bezier curve, starting and ending at its endpoints and pulled toward its controls
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct CubicBezier {
+	pub start: Vector3,
+	pub control_a: Vector3,
+	pub control_b: Vector3,
+	pub end: Vector3,
+}
+
+impl Curve for CubicBezier {
+	fn point(&self, t: Real) -> Vector3 {
+		let u = 1.0 - t;
+		self.start * (u * u * u) + self.control_a * (3.0 * u * u * t) + self.control_b * (3.0 * u * t * t) + self.end * (t * t * t)
+	}
+
+	fn tangent(&self, t: Real) -> Vector3 {
+		let u = 1.0 - t;
+		(self.control_a - self.start) * (3.0 * u * u) + (self.control_b - self.control_a) * (6.0 * u * t) + (self.end - self.control_b) * (3.0 * t * t)
+	}
+}
+
+/// A cubic curve between two points, leaving and arriving with the given tangents
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Hermite {
+	pub start: Vector3,
+	pub start_tangent: Vector3,
+	pub end: Vector3,
+	pub end_tangent: Vector3,
+}
+
+impl Curve for Hermite {
+	fn point(&self, t: Real) -> Vector3 {
+		let (t2, t3) = (t * t, t * t * t);
+		self.start * (2.0 * t3 - 3.0 * t2 + 1.0) + self.start_tangent * (t3 - 2.0 * t2 + t) + self.end * (3.0 * t2 - 2.0 * t3) + self.end_tangent * (t3 - t2)
+	}
+
+	fn tangent(&self, t: Real) -> Vector3 {
+		let t2 = t * t;
+		self.start * (6.0 * t2 - 6.0 * t) + self.start_tangent * (3.0 * t2 - 4.0 * t + 1.0) + self.end * (6.0 * t - 6.0 * t2) + self.end_tangent * (3.0 * t2 - 2.0 * t)
+	}
+}
+
+/// A smooth curve passing through every point, such as a camera path through waypoints.
+/// Each point gets the same share of `t`, and the curve runs straight into its ends.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CatmullRom {
+	pub points: Vec<Vector3>,
+}
+
+impl CatmullRom {
+	pub fn new(points: Vec<Vector3>) -> Self {
+		Self { points }
+	}
+
+	/// The segment `t` falls in as a Hermite curve, along with how far into that segment it is
+	fn segment(&self, t: Real) -> Option<(Hermite, Real)> {
+		let segments = self.points.len().checked_sub(1).filter(|segments| *segments > 0)?;
+		let scaled = t.clamp(0.0, 1.0) * segments as Real;
+		let index = (scaled as usize).min(segments - 1);
+		let point = |index: usize| self.points[index.min(segments)];
+		let previous = if index == 0 { point(0) } else { point(index - 1) };
+		let segment = Hermite {
+			start: point(index),
+			start_tangent: (point(index + 1) - previous) * 0.5,
+			end: point(index + 1),
+			end_tangent: (point(index + 2) - point(index)) * 0.5,
+		};
+		Some((segment, scaled - index as Real))
+	}
+}
+
+impl Curve for CatmullRom {
+	fn point(&self, t: Real) -> Vector3 {
+		match self.segment(t) {
+			Some((segment, local)) => segment.point(local),
+			None => self.points.first().copied().unwrap_or_default(),
+		}
+	}
+
+	fn tangent(&self, t: Real) -> Vector3 {
+		match self.segment(t) {
+			Some((segment, local)) => segment.tangent(local) * (self.points.len() - 1) as Real,
+			None => Vector3::zero(),
+		}
+	}
+}
+
+/// Distances along a curve at evenly spaced values of `t`, for tracing it at a constant speed.
+/// Curves generally speed up and slow down as `t` changes, so moving by distance avoids that.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct ArcLength {
+	distances: Vec<Real>,
+}
+
+impl ArcLength {
+	/// Measures the curve as `samples` straight pieces, where more samples are more accurate
+	pub fn new(curve: &impl Curve, samples: usize) -> Self {
+		let samples = samples.max(1);
+		let mut distances = Vec::with_capacity(samples + 1);
+		distances.push(0.0);
+		let mut previous = curve.point(0.0);
+		for sample in 1..=samples {
+			let point = curve.point(sample as Real / samples as Real);
+			distances.push(distances[sample - 1] + (point - previous).magnitude());
+			previous = point;
+		}
+		Self { distances }
+	}
+
+	pub fn length(&self) -> Real {
+		self.distances.last().copied().unwrap_or_default()
+	}
+
+	/// The `t` that is `distance` along the curve, clamped to its ends
+	pub fn parameter(&self, distance: Real) -> Real {
+		let samples = self.distances.len() - 1;
+		if distance <= 0.0 || self.length() <= 0.0 {
+			return 0.0;
+		}
+		if distance >= self.length() {
+			return 1.0;
+		}
+		let index = self.distances.partition_point(|sample| *sample <= distance).clamp(1, samples);
+		let (before, after) = (self.distances[index - 1], self.distances[index]);
+		let fraction = if after > before { (distance - before) / (after - before) } else { 0.0 };
+		(index as Real - 1.0 + fraction) / samples as Real
+	}
+
+	/// The point `distance` along the curve
+	pub fn point(&self, curve: &impl Curve, distance: Real) -> Vector3 {
+		curve.point(self.parameter(distance))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloc::vec;
+
+	fn close(a: Vector3, b: Vector3) -> bool {
+		(a - b).magnitude() < 1e-4
+	}
+
+	#[test]
+	pub fn bezier() {
+		let curve = CubicBezier {
+			start: Vector3::zero(),
+			control_a: Vector3::new(0.0, 1.0, 0.0),
+			control_b: Vector3::new(1.0, 1.0, 0.0),
+			end: Vector3::new(1.0, 0.0, 0.0),
+		};
+		assert!(close(curve.point(0.0), curve.start));
+		assert!(close(curve.point(1.0), curve.end));
+		assert!(close(curve.point(0.5), Vector3::new(0.5, 0.75, 0.0)));
+		assert!(close(curve.tangent(0.0), Vector3::new(0.0, 3.0, 0.0)));
+		assert!(close(curve.tangent(1.0), Vector3::new(0.0, -3.0, 0.0)));
+	}
+
+	#[test]
+	pub fn hermite() {
+		let curve = Hermite {
+			start: Vector3::zero(),
+			start_tangent: Vector3::new(1.0, 0.0, 0.0),
+			end: Vector3::new(1.0, 1.0, 0.0),
+			end_tangent: Vector3::new(0.0, 1.0, 0.0),
+		};
+		assert!(close(curve.point(0.0), curve.start));
+		assert!(close(curve.point(1.0), curve.end));
+		assert!(close(curve.tangent(0.0), curve.start_tangent));
+		assert!(close(curve.tangent(1.0), curve.end_tangent));
+	}
+
+	#[test]
+	pub fn catmull_rom_passes_through_its_points() {
+		let points = vec![Vector3::zero(), Vector3::new(1.0, 1.0, 0.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(3.0, 1.0, 1.0)];
+		let curve = CatmullRom::new(points.clone());
+		for (index, point) in points.iter().enumerate() {
+			assert!(close(curve.point(index as Real / 3.0), *point));
+		}
+		// The tangent at an inner point faces from its neighbor before to its neighbor after
+		assert!(close(curve.tangent(1.0 / 3.0), (points[2] - points[0]) * 1.5));
+		assert!(close(CatmullRom::new(vec![Vector3::x_axis()]).point(0.5), Vector3::x_axis()));
+		assert!(close(CatmullRom::default().point(0.5), Vector3::zero()));
+	}
+
+	#[test]
+	pub fn arc_length() {
+		// Uneven control points make the curve speed up, but it is still a straight line
+		let curve = CubicBezier {
+			start: Vector3::zero(),
+			control_a: Vector3::zero(),
+			control_b: Vector3::new(1.0, 0.0, 0.0),
+			end: Vector3::new(10.0, 0.0, 0.0),
+		};
+		let arc_length = ArcLength::new(&curve, 256);
+		assert!((arc_length.length() - 10.0).abs() < 1e-3);
+		assert!(curve.point(0.5).x() < 3.0);
+		for distance in [0.0, 2.5, 5.0, 7.5, 10.0] {
+			assert!((arc_length.point(&curve, distance).x() - distance).abs() < 0.05);
+		}
+		assert_eq!(arc_length.parameter(-1.0), 0.0);
+		assert_eq!(arc_length.parameter(20.0), 1.0);
+	}
+}
@@ -0,0 +1,200 @@
+use crate::{Quaternion, Real, Transform, Vector3};
+use core::ops::Mul;
+
+/// A rotation and translation without scale, stored as a dual quaternion.
+///
+/// Blending dual quaternions keeps the result rigid, so skinned vertices influenced by several
+/// bones don't collapse at twisting joints the way blended matrices make them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DualQuaternion {
+	pub real: Quaternion,
+	pub dual: Quaternion,
+}
+
+impl Default for DualQuaternion {
+	fn default() -> Self {
+		Self::identity()
+	}
+}
+
+impl DualQuaternion {
+	#[must_use]
+	pub const fn identity() -> Self {
+		Self {
+			real: Quaternion::identity(),
+			dual: Quaternion::new(0.0, 0.0, 0.0, 0.0),
+		}
+	}
+
+	/// Rotates and then translates
+	#[must_use]
+	pub fn from_rotation_translation(rotation: Quaternion, translation: Vector3) -> Self {
+		let translation = Quaternion::new(translation.x(), translation.y(), translation.z(), 0.0);
+		Self {
+			real: rotation,
+			dual: scale(translation * rotation, 0.5),
+		}
+	}
+
+	/// The transform's rotation and translation. Scale can't be represented and is dropped.
+	#[must_use]
+	pub fn from_transform(transform: &Transform) -> Self {
+		Self::from_rotation_translation(transform.rotation, transform.translation)
+	}
+
+	#[must_use]
+	pub fn rotation(&self) -> Quaternion {
+		self.real
+	}
+
+	#[must_use]
+	pub fn translation(&self) -> Vector3 {
+		let translation = scale(self.dual * self.real.conjugate(), 2.0);
+		Vector3::new(translation.x, translation.y, translation.z)
+	}
+
+	/// A transform with the same rotation and translation and a scale of one
+	#[must_use]
+	pub fn to_transform(&self) -> Transform {
+		Transform {
+			translation: self.translation(),
+			rotation: self.rotation(),
+			..Transform::identity()
+		}
+	}
+
+	#[must_use]
+	pub fn transform_point(&self, point: Vector3) -> Vector3 {
+		self.real.rotate(point) + self.translation()
+	}
+
+	/// Scales back to a unit dual quaternion, which blending and composition drift away from
+	#[must_use]
+	pub fn normalize(&self) -> Self {
+		let length = self.real.magnitude();
+		if length <= 0.0 {
+			return Self::identity();
+		}
+		let real = scale(self.real, length.recip());
+		let dual = scale(self.dual, length.recip());
+		Self {
+			real,
+			dual: add(dual, scale(real, -real.dot(&dual))),
+		}
+	}
+
+	/// Blends any number of weighted rigid transforms, such as the bones influencing a vertex.
+	/// The weights don't need to add up to one. With no weight at all, the identity is returned.
+	#[must_use]
+	pub fn blend(weighted: &[(Self, Real)]) -> Self {
+		let Some((first, _)) = weighted.first() else {
+			return Self::identity();
+		};
+		let zero = Quaternion::new(0.0, 0.0, 0.0, 0.0);
+		let (real, dual) = weighted.iter().fold((zero, zero), |(real, dual), (transform, weight)| {
+			// q and -q are the same rotation, so flip any facing away from the first
+			let weight = if transform.real.dot(&first.real) < 0.0 { -weight } else { *weight };
+			(add(real, scale(transform.real, weight)), add(dual, scale(transform.dual, weight)))
+		});
+		Self { real, dual }.normalize()
+	}
+
+	/// Blends from this transform at `t = 0` to `rhs` at `t = 1`
+	#[must_use]
+	pub fn lerp(&self, rhs: &Self, t: Real) -> Self {
+		Self::blend(&[(*self, 1.0 - t), (*rhs, t)])
+	}
+}
+
+impl Mul for DualQuaternion {
+	type Output = Self;
+
+	/// Composes two transforms, applying `rhs` first
+	fn mul(self, rhs: Self) -> Self::Output {
+		Self {
+			real: self.real * rhs.real,
+			dual: add(self.real * rhs.dual, self.dual * rhs.real),
+		}
+	}
+}
+
+impl From<Transform> for DualQuaternion {
+	fn from(transform: Transform) -> Self {
+		Self::from_transform(&transform)
+	}
+}
+
+fn add(a: Quaternion, b: Quaternion) -> Quaternion {
+	Quaternion::new(a.x + b.x, a.y + b.y, a.z + b.z, a.w + b.w)
+}
+
+fn scale(quaternion: Quaternion, factor: Real) -> Quaternion {
+	Quaternion::new(quaternion.x * factor, quaternion.y * factor, quaternion.z * factor, quaternion.w * factor)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::f32::consts::FRAC_PI_2;
+
+	fn assert_vectors_equal(actual: Vector3, expected: Vector3) {
+		assert!((actual - expected).magnitude() < 1e-5, "left: {:?} not equal right: {:?}", actual, expected);
+	}
+
+	fn transform() -> Transform {
+		Transform {
+			translation: Vector3::new(1.0, 2.0, 3.0),
+			rotation: Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0), 0.7),
+			..Transform::identity()
+		}
+	}
+
+	#[test]
+	pub fn round_trip() {
+		let transform = transform();
+		let dual_quaternion = DualQuaternion::from(transform);
+		assert_vectors_equal(dual_quaternion.translation(), transform.translation);
+		assert!(dual_quaternion.rotation().approximately_equals(&transform.rotation, 1e-6));
+		let point = Vector3::new(-1.0, 0.5, 2.0);
+		assert_vectors_equal(dual_quaternion.transform_point(point), transform.transform_point(point));
+		assert_eq!(DualQuaternion::identity().to_transform(), Transform::identity());
+	}
+
+	#[test]
+	pub fn compose() {
+		let parent = transform();
+		let child = Transform {
+			translation: Vector3::new(0.0, 1.0, 0.0),
+			rotation: Quaternion::from_axis_angle(Vector3::z_axis(), 0.3),
+			..Transform::identity()
+		};
+		let composed = DualQuaternion::from(parent) * DualQuaternion::from(child);
+		let point = Vector3::new(1.0, 0.0, 0.0);
+		assert_vectors_equal(composed.transform_point(point), (parent * child).transform_point(point));
+	}
+
+	#[test]
+	pub fn blending_stays_rigid() {
+		let start = DualQuaternion::from_rotation_translation(Quaternion::identity(), Vector3::new(1.0, 0.0, 0.0));
+		let end = DualQuaternion::from_rotation_translation(Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2), Vector3::new(0.0, 0.0, -1.0));
+		let halfway = start.lerp(&end, 0.5);
+		assert!(
+			halfway
+				.rotation()
+				.approximately_equals(&Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2 * 0.5), 1e-6)
+		);
+		assert!((halfway.real.magnitude() - 1.0).abs() < 1e-6);
+
+		// Both ends turn the point about the origin, so the blend keeps it on the circle
+		let point = halfway.transform_point(Vector3::zero());
+		assert!((point.magnitude() - 1.0).abs() < 1e-5);
+
+		// Flipping the sign of one end describes the same transform and must not change the blend
+		let flipped = DualQuaternion {
+			real: scale(end.real, -1.0),
+			dual: scale(end.dual, -1.0),
+		};
+		assert_vectors_equal(start.lerp(&flipped, 0.5).transform_point(point), halfway.transform_point(point));
+		assert_eq!(DualQuaternion::blend(&[]), DualQuaternion::identity());
+	}
+}
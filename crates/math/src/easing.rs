@@ -0,0 +1,314 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::Real;
+use core::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// An easing function that can be stored in data, such as a tween loaded from a file.
+/// Each variant maps progress from 0 to 1 onto the matching free function.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Easing {
+	#[default]
+	Linear,
+	InQuad,
+	OutQuad,
+	InOutQuad,
+	InCubic,
+	OutCubic,
+	InOutCubic,
+	InQuart,
+	OutQuart,
+	InOutQuart,
+	InSine,
+	OutSine,
+	InOutSine,
+	InExpo,
+	OutExpo,
+	InOutExpo,
+	InBack,
+	OutBack,
+	InOutBack,
+	InElastic,
+	OutElastic,
+	InOutElastic,
+	InBounce,
+	OutBounce,
+	InOutBounce,
+}
+
+impl Easing {
+	/// Eases progress, which is clamped to the range 0 to 1.
+	/// Back and elastic easings overshoot, so their results can fall outside that range.
+	#[must_use]
+	pub fn apply(&self, t: Real) -> Real {
+		let function: fn(Real) -> Real = match self {
+			Self::Linear => linear,
+			Self::InQuad => ease_in_quad,
+			Self::OutQuad => ease_out_quad,
+			Self::InOutQuad => ease_in_out_quad,
+			Self::InCubic => ease_in_cubic,
+			Self::OutCubic => ease_out_cubic,
+			Self::InOutCubic => ease_in_out_cubic,
+			Self::InQuart => ease_in_quart,
+			Self::OutQuart => ease_out_quart,
+			Self::InOutQuart => ease_in_out_quart,
+			Self::InSine => ease_in_sine,
+			Self::OutSine => ease_out_sine,
+			Self::InOutSine => ease_in_out_sine,
+			Self::InExpo => ease_in_expo,
+			Self::OutExpo => ease_out_expo,
+			Self::InOutExpo => ease_in_out_expo,
+			Self::InBack => ease_in_back,
+			Self::OutBack => ease_out_back,
+			Self::InOutBack => ease_in_out_back,
+			Self::InElastic => ease_in_elastic,
+			Self::OutElastic => ease_out_elastic,
+			Self::InOutElastic => ease_in_out_elastic,
+			Self::InBounce => ease_in_bounce,
+			Self::OutBounce => ease_out_bounce,
+			Self::InOutBounce => ease_in_out_bounce,
+		};
+		function(t.clamp(0.0, 1.0))
+	}
+}
+
+const BACK: Real = 1.70158;
+const BACK_IN_OUT: Real = BACK * 1.525;
+
+#[must_use]
+pub fn linear(t: Real) -> Real {
+	t
+}
+
+/// Eases in with the given power, where out and in-out easings mirror it
+fn power_in_out(t: Real, power: i32) -> Real {
+	if t < 0.5 {
+		(2.0 as Real).powi(power - 1) * t.powi(power)
+	} else {
+		1.0 - (2.0 - 2.0 * t).powi(power) / 2.0
+	}
+}
+
+#[must_use]
+pub fn ease_in_quad(t: Real) -> Real {
+	t * t
+}
+
+#[must_use]
+pub fn ease_out_quad(t: Real) -> Real {
+	1.0 - ease_in_quad(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_quad(t: Real) -> Real {
+	power_in_out(t, 2)
+}
+
+#[must_use]
+pub fn ease_in_cubic(t: Real) -> Real {
+	t * t * t
+}
+
+#[must_use]
+pub fn ease_out_cubic(t: Real) -> Real {
+	1.0 - ease_in_cubic(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_cubic(t: Real) -> Real {
+	power_in_out(t, 3)
+}
+
+#[must_use]
+pub fn ease_in_quart(t: Real) -> Real {
+	t.powi(4)
+}
+
+#[must_use]
+pub fn ease_out_quart(t: Real) -> Real {
+	1.0 - ease_in_quart(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_quart(t: Real) -> Real {
+	power_in_out(t, 4)
+}
+
+#[must_use]
+pub fn ease_in_sine(t: Real) -> Real {
+	1.0 - (t * FRAC_PI_2).cos()
+}
+
+#[must_use]
+pub fn ease_out_sine(t: Real) -> Real {
+	(t * FRAC_PI_2).sin()
+}
+
+#[must_use]
+pub fn ease_in_out_sine(t: Real) -> Real {
+	(1.0 - (t * PI).cos()) / 2.0
+}
+
+#[must_use]
+pub fn ease_in_expo(t: Real) -> Real {
+	if t <= 0.0 { 0.0 } else { (2.0 as Real).powf(10.0 * t - 10.0) }
+}
+
+#[must_use]
+pub fn ease_out_expo(t: Real) -> Real {
+	1.0 - ease_in_expo(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_expo(t: Real) -> Real {
+	if t < 0.5 {
+		ease_in_expo(2.0 * t) / 2.0
+	} else {
+		1.0 - ease_in_expo(2.0 - 2.0 * t) / 2.0
+	}
+}
+
+/// Pulls back slightly before moving forward
+#[must_use]
+pub fn ease_in_back(t: Real) -> Real {
+	t * t * ((BACK + 1.0) * t - BACK)
+}
+
+/// Overshoots slightly before settling
+#[must_use]
+pub fn ease_out_back(t: Real) -> Real {
+	1.0 - ease_in_back(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_back(t: Real) -> Real {
+	let back = |t: Real| t * t * ((BACK_IN_OUT + 1.0) * t - BACK_IN_OUT);
+	if t < 0.5 { back(2.0 * t) / 2.0 } else { 1.0 - back(2.0 - 2.0 * t) / 2.0 }
+}
+
+/// Winds up with growing oscillations, like a spring being pulled back
+#[must_use]
+pub fn ease_in_elastic(t: Real) -> Real {
+	if t <= 0.0 || t >= 1.0 {
+		return t.clamp(0.0, 1.0);
+	}
+	-(2.0 as Real).powf(10.0 * t - 10.0) * ((10.0 * t - 10.75) * TAU / 3.0).sin()
+}
+
+/// Overshoots and oscillates before settling, like a released spring
+#[must_use]
+pub fn ease_out_elastic(t: Real) -> Real {
+	1.0 - ease_in_elastic(1.0 - t)
+}
+
+#[must_use]
+pub fn ease_in_out_elastic(t: Real) -> Real {
+	if t < 0.5 {
+		ease_in_elastic(2.0 * t) / 2.0
+	} else {
+		1.0 - ease_in_elastic(2.0 - 2.0 * t) / 2.0
+	}
+}
+
+#[must_use]
+pub fn ease_in_bounce(t: Real) -> Real {
+	1.0 - ease_out_bounce(1.0 - t)
+}
+
+/// Bounces to rest like a dropped ball
+#[must_use]
+pub fn ease_out_bounce(t: Real) -> Real {
+	const STIFFNESS: Real = 7.5625;
+	const WIDTH: Real = 2.75;
+	let bounce = |center: Real, floor: Real| STIFFNESS * (t - center / WIDTH).powi(2) + floor;
+	if t < 1.0 / WIDTH {
+		bounce(0.0, 0.0)
+	} else if t < 2.0 / WIDTH {
+		bounce(1.5, 0.75)
+	} else if t < 2.5 / WIDTH {
+		bounce(2.25, 0.9375)
+	} else {
+		bounce(2.625, 0.984_375)
+	}
+}
+
+#[must_use]
+pub fn ease_in_out_bounce(t: Real) -> Real {
+	if t < 0.5 {
+		ease_in_bounce(2.0 * t) / 2.0
+	} else {
+		1.0 - ease_in_bounce(2.0 - 2.0 * t) / 2.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EASINGS: [Easing; 25] = [
+		Easing::Linear,
+		Easing::InQuad,
+		Easing::OutQuad,
+		Easing::InOutQuad,
+		Easing::InCubic,
+		Easing::OutCubic,
+		Easing::InOutCubic,
+		Easing::InQuart,
+		Easing::OutQuart,
+		Easing::InOutQuart,
+		Easing::InSine,
+		Easing::OutSine,
+		Easing::InOutSine,
+		Easing::InExpo,
+		Easing::OutExpo,
+		Easing::InOutExpo,
+		Easing::InBack,
+		Easing::OutBack,
+		Easing::InOutBack,
+		Easing::InElastic,
+		Easing::OutElastic,
+		Easing::InOutElastic,
+		Easing::InBounce,
+		Easing::OutBounce,
+		Easing::InOutBounce,
+	];
+
+	#[test]
+	pub fn endpoints() {
+		for easing in EASINGS {
+			assert!(easing.apply(0.0).abs() < 1e-5, "{easing:?} doesn't start at 0");
+			assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{easing:?} doesn't end at 1");
+			assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+			assert_eq!(easing.apply(2.0), easing.apply(1.0));
+		}
+	}
+
+	#[test]
+	pub fn in_out_easings_are_symmetric() {
+		let in_outs = [
+			Easing::InOutQuad,
+			Easing::InOutCubic,
+			Easing::InOutSine,
+			Easing::InOutExpo,
+			Easing::InOutBack,
+			Easing::InOutBounce,
+		];
+		for easing in in_outs {
+			assert!((easing.apply(0.5) - 0.5).abs() < 1e-5, "{easing:?} isn't halfway at the midpoint");
+			for t in [0.1, 0.25, 0.4] {
+				assert!((easing.apply(t) + easing.apply(1.0 - t) - 1.0).abs() < 1e-5, "{easing:?} isn't symmetric at {t}");
+			}
+		}
+	}
+
+	#[test]
+	pub fn shapes() {
+		assert!((ease_in_out_cubic(0.25) - 0.0625).abs() < 1e-6);
+		assert!((ease_out_quad(0.5) - 0.75).abs() < 1e-6);
+		assert!(ease_in_back(0.2) < 0.0);
+		assert!(ease_out_back(0.8) > 1.0);
+		assert!(ease_out_elastic(0.2) > 1.0);
+		assert!((ease_out_bounce(1.0 / 2.75) - 1.0).abs() < 1e-5);
+		assert!((ease_out_bounce(1.5 / 2.75) - 0.75).abs() < 1e-5);
+		assert!((ease_out_bounce(2.25 / 2.75) - 0.9375).abs() < 1e-5);
+	}
+}
@@ -0,0 +1,134 @@
+use crate::Real;
+
+/// The floating point functions `core` lacks, provided by `std` or, under `no_std`, by `libm`.
+///
+/// With `std` the inherent methods shadow these, so only `no_std` code needs to import this. Test
+/// builds link `std` even under `no_std`, so the imports skip them too.
+pub trait Float {
+	fn acos(self) -> Self;
+	fn atan2(self, other: Self) -> Self;
+	fn cos(self) -> Self;
+	fn hypot(self, other: Self) -> Self;
+	fn mul_add(self, a: Self, b: Self) -> Self;
+	fn powf(self, exponent: Self) -> Self;
+	fn powi(self, exponent: i32) -> Self;
+	fn sin(self) -> Self;
+	fn sin_cos(self) -> (Self, Self)
+	where
+		Self: Sized;
+	fn sqrt(self) -> Self;
+	fn tan(self) -> Self;
+}
+
+#[cfg(feature = "std")]
+impl Float for Real {
+	fn acos(self) -> Self {
+		Real::acos(self)
+	}
+
+	fn atan2(self, other: Self) -> Self {
+		Real::atan2(self, other)
+	}
+
+	fn cos(self) -> Self {
+		Real::cos(self)
+	}
+
+	fn hypot(self, other: Self) -> Self {
+		Real::hypot(self, other)
+	}
+
+	fn mul_add(self, a: Self, b: Self) -> Self {
+		Real::mul_add(self, a, b)
+	}
+
+	fn powf(self, exponent: Self) -> Self {
+		Real::powf(self, exponent)
+	}
+
+	fn powi(self, exponent: i32) -> Self {
+		Real::powi(self, exponent)
+	}
+
+	fn sin(self) -> Self {
+		Real::sin(self)
+	}
+
+	fn sin_cos(self) -> (Self, Self) {
+		Real::sin_cos(self)
+	}
+
+	fn sqrt(self) -> Self {
+		Real::sqrt(self)
+	}
+
+	fn tan(self) -> Self {
+		Real::tan(self)
+	}
+}
+
+#[cfg(not(feature = "std"))]
+impl Float for Real {
+	fn acos(self) -> Self {
+		libm::acosf(self)
+	}
+
+	fn atan2(self, other: Self) -> Self {
+		libm::atan2f(self, other)
+	}
+
+	fn cos(self) -> Self {
+		libm::cosf(self)
+	}
+
+	fn hypot(self, other: Self) -> Self {
+		libm::hypotf(self, other)
+	}
+
+	fn mul_add(self, a: Self, b: Self) -> Self {
+		libm::fmaf(self, a, b)
+	}
+
+	fn powf(self, exponent: Self) -> Self {
+		libm::powf(self, exponent)
+	}
+
+	fn powi(self, exponent: i32) -> Self {
+		libm::powf(self, exponent as Real)
+	}
+
+	fn sin(self) -> Self {
+		libm::sinf(self)
+	}
+
+	fn sin_cos(self) -> (Self, Self) {
+		libm::sincosf(self)
+	}
+
+	fn sqrt(self) -> Self {
+		libm::sqrtf(self)
+	}
+
+	fn tan(self) -> Self {
+		libm::tanf(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::assert_equal;
+
+	#[test]
+	pub fn functions() {
+		assert_equal(Float::sqrt(16.0 as Real), 4.0);
+		assert_equal(Float::powi(3.0 as Real, 2), 9.0);
+		assert_equal(Float::mul_add(2.0 as Real, 3.0, 1.0), 7.0);
+		assert_equal(Float::hypot(3.0 as Real, 4.0), 5.0);
+		assert_equal(Float::atan2(1.0 as Real, 1.0), core::f32::consts::FRAC_PI_4);
+		assert_equal(Float::tan(core::f32::consts::FRAC_PI_4), 1.0);
+		let (sin, cos) = Float::sin_cos(0.0 as Real);
+		assert_equal(sin, 0.0);
+		assert_equal(cos, 1.0);
+	}
+}
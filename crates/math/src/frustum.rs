@@ -0,0 +1,118 @@
+use crate::{Aabb, BoundingSphere, Matrix4, Real, Rect, Vector3};
+
+/// A plane as `[a, b, c, d]`, where `a * x + b * y + c * z + d` is the signed distance to a point
+pub type Plane = [Real; 4];
+
+/// The six planes bounding what a camera can see, facing inward
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+	planes: [Plane; 6],
+}
+
+impl Frustum {
+	/// Extracts the planes of a view-projection matrix with a `0..1` depth range
+	#[must_use]
+	pub fn from_view_projection(view_projection: &Matrix4) -> Self {
+		Self::from_ndc_rect(view_projection, &Rect::new(-1.0, -1.0, 2.0, 2.0))
+	}
+
+	/// The part of the view inside a rectangle in normalized device coordinates, such as a
+	/// selection rectangle
+	#[must_use]
+	pub fn from_ndc_rect(view_projection: &Matrix4, rect: &Rect) -> Self {
+		let row = |row: usize| [view_projection[0][row], view_projection[1][row], view_projection[2][row], view_projection[3][row]];
+		let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+		let (left, right) = (rect.x.min(rect.right()), rect.x.max(rect.right()));
+		let (bottom, top) = (rect.y.min(rect.bottom()), rect.y.max(rect.bottom()));
+		let offset = |a: Plane, b: Plane, scale: Real| [a[0] - b[0] * scale, a[1] - b[1] * scale, a[2] - b[2] * scale, a[3] - b[3] * scale];
+		let negate = |plane: Plane| plane.map(|value| -value);
+		let planes = [
+			offset(x, w, left),
+			negate(offset(x, w, right)),
+			offset(y, w, bottom),
+			negate(offset(y, w, top)),
+			z,
+			offset(w, z, 1.0),
+		]
+		.map(|plane| {
+			let length = Vector3::new(plane[0], plane[1], plane[2]).magnitude();
+			if length > 0.0 { plane.map(|value| value / length) } else { plane }
+		});
+		Self { planes }
+	}
+
+	/// Left, right, bottom, top, near, then far
+	#[must_use]
+	pub fn planes(&self) -> &[Plane; 6] {
+		&self.planes
+	}
+
+	fn distance(plane: &Plane, point: Vector3) -> Real {
+		plane[0] * point.x() + plane[1] * point.y() + plane[2] * point.z() + plane[3]
+	}
+
+	#[must_use]
+	pub fn contains_point(&self, point: Vector3) -> bool {
+		self.planes.iter().all(|plane| Self::distance(plane, point) >= 0.0)
+	}
+
+	#[must_use]
+	pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+		self.planes.iter().all(|plane| Self::distance(plane, sphere.center) >= -sphere.radius)
+	}
+
+	/// Conservative, so boxes near the frustum's corners may pass without touching it
+	#[must_use]
+	pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+		self.planes.iter().all(|plane| {
+			// The corner furthest along the plane's normal
+			let corner = Vector3::new(
+				if plane[0] >= 0.0 { aabb.max.x() } else { aabb.min.x() },
+				if plane[1] >= 0.0 { aabb.max.y() } else { aabb.min.y() },
+				if plane[2] >= 0.0 { aabb.max.z() } else { aabb.min.z() },
+			);
+			Self::distance(plane, corner) >= 0.0
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Transform;
+
+	fn identity() -> Matrix4 {
+		Transform::default().to_matrix()
+	}
+
+	#[test]
+	pub fn spheres() {
+		let frustum = Frustum::from_view_projection(&identity());
+		assert!(frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(0.0, 0.0, 0.5), 0.1)));
+		assert!(frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(1.05, 0.0, 0.5), 0.1)));
+		assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(3.0, 0.0, 0.5), 0.1)));
+		assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vector3::new(0.0, 0.0, -1.0), 0.1)));
+	}
+
+	#[test]
+	pub fn boxes() {
+		let frustum = Frustum::from_view_projection(&identity());
+		let cube = |x: Real, z: Real| Aabb::from_center(Vector3::new(x, 0.0, z), Vector3::new(0.25, 0.25, 0.25));
+		assert!(frustum.intersects_aabb(&cube(0.0, 0.5)));
+		assert!(frustum.intersects_aabb(&cube(1.2, 0.5)));
+		assert!(!frustum.intersects_aabb(&cube(1.3, 0.5)));
+		assert!(!frustum.intersects_aabb(&cube(0.0, 1.5)));
+		assert!(frustum.contains_point(Vector3::new(-1.0, 1.0, 1.0)));
+		assert!(!frustum.contains_point(Vector3::new(0.0, 0.0, -0.1)));
+	}
+
+	#[test]
+	pub fn selection_rect() {
+		// Rectangles dragged from either corner select the same region
+		let frustum = Frustum::from_ndc_rect(&identity(), &Rect::new(0.5, 0.5, -0.5, -0.5));
+		assert_eq!(frustum, Frustum::from_ndc_rect(&identity(), &Rect::new(0.0, 0.0, 0.5, 0.5)));
+		assert!(frustum.contains_point(Vector3::new(0.25, 0.25, 0.5)));
+		assert!(!frustum.contains_point(Vector3::new(-0.25, 0.25, 0.5)));
+		assert!(!frustum.contains_point(Vector3::new(0.25, 0.75, 0.5)));
+	}
+}
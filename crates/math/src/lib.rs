@@ -1,4 +1,27 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("The math crate needs either the `std` or the `libm` feature for its floating point functions");
+
+extern crate alloc;
+
+mod bounds;
+mod camera;
+mod color;
+#[cfg(any(feature = "glam", feature = "mint", feature = "nalgebra"))]
+mod conversions;
+mod curve;
+mod dual_quaternion;
+mod easing;
 mod equality;
+mod float;
+mod frustum;
+mod quaternion;
+mod ray;
+mod rect;
+mod transform;
 mod vector;
 
-pub use self::{equality::*, vector::*};
+pub use self::{
+	bounds::*, camera::*, color::*, curve::*, dual_quaternion::*, easing::*, equality::*, float::*, frustum::*, quaternion::*, ray::*, rect::*, transform::*, vector::*,
+};
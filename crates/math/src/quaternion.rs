@@ -0,0 +1,191 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::{Real, Vector3};
+use core::ops::Mul;
+
+/// A rotation, stored as a unit quaternion
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+	pub x: Real,
+	pub y: Real,
+	pub z: Real,
+	pub w: Real,
+}
+
+impl Default for Quaternion {
+	fn default() -> Self {
+		Self::identity()
+	}
+}
+
+impl Quaternion {
+	#[must_use]
+	pub const fn new(x: Real, y: Real, z: Real, w: Real) -> Self {
+		Self { x, y, z, w }
+	}
+
+	#[must_use]
+	pub const fn identity() -> Self {
+		Self::new(0.0, 0.0, 0.0, 1.0)
+	}
+
+	/// A rotation of `angle` radians around `axis`
+	#[must_use]
+	pub fn from_axis_angle(axis: Vector3, angle: Real) -> Self {
+		let axis = axis.normalize();
+		let (sin, cos) = (angle * 0.5).sin_cos();
+		Self::new(axis.x() * sin, axis.y() * sin, axis.z() * sin, cos)
+	}
+
+	#[must_use]
+	pub fn magnitude(&self) -> Real {
+		self.dot(self).sqrt()
+	}
+
+	#[must_use]
+	pub fn dot(&self, rhs: &Self) -> Real {
+		self.x.mul_add(rhs.x, self.y.mul_add(rhs.y, self.z.mul_add(rhs.z, self.w * rhs.w)))
+	}
+
+	#[must_use]
+	pub fn normalize(&self) -> Self {
+		let length = self.magnitude();
+		if length > 0.0 {
+			Self::new(self.x / length, self.y / length, self.z / length, self.w / length)
+		} else {
+			Self::identity()
+		}
+	}
+
+	/// The opposite rotation, assuming the quaternion is normalized
+	#[must_use]
+	pub const fn conjugate(&self) -> Self {
+		Self::new(-self.x, -self.y, -self.z, self.w)
+	}
+
+	#[must_use]
+	pub fn rotate(&self, vector: Vector3) -> Vector3 {
+		let axis = Vector3::new(self.x, self.y, self.z);
+		let twice_cross = axis.cross(&vector) * 2.0;
+		vector + twice_cross * self.w + axis.cross(&twice_cross)
+	}
+
+	/// Advances the rotation by an angular velocity in radians per second for `duration` seconds
+	#[must_use]
+	pub fn integrate(&self, angular_velocity: Vector3, duration: Real) -> Self {
+		let spin = Self::new(angular_velocity.x(), angular_velocity.y(), angular_velocity.z(), 0.0) * *self;
+		let half = duration * 0.5;
+		Self::new(
+			spin.x.mul_add(half, self.x),
+			spin.y.mul_add(half, self.y),
+			spin.z.mul_add(half, self.z),
+			spin.w.mul_add(half, self.w),
+		)
+		.normalize()
+	}
+
+	/// The rotation as an axis scaled by its angle in radians, taking the shorter way around
+	#[must_use]
+	pub fn to_scaled_axis(&self) -> Vector3 {
+		let rotation = if self.w < 0.0 { Self::new(-self.x, -self.y, -self.z, -self.w) } else { *self };
+		let axis = Vector3::new(rotation.x, rotation.y, rotation.z);
+		let sin = axis.magnitude();
+		if sin <= Real::EPSILON {
+			return axis * 2.0;
+		}
+		axis * (2.0 * sin.atan2(rotation.w) / sin)
+	}
+
+	/// Rotates along the shortest arc from this rotation at `t = 0` to `rhs` at `t = 1`
+	#[must_use]
+	pub fn slerp(&self, rhs: &Self, t: Real) -> Self {
+		// q and -q are the same rotation, so flip one to take the shorter way around
+		let (rhs, cos) = match self.dot(rhs) {
+			cos if cos < 0.0 => (Self::new(-rhs.x, -rhs.y, -rhs.z, -rhs.w), -cos),
+			cos => (*rhs, cos),
+		};
+		let (from, to) = if cos > 0.9995 {
+			// Nearly parallel rotations divide by a vanishing sine, so blend linearly instead
+			(1.0 - t, t)
+		} else {
+			let angle = cos.acos();
+			let sin = angle.sin();
+			(((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+		};
+		Self::new(
+			self.x * from + rhs.x * to,
+			self.y * from + rhs.y * to,
+			self.z * from + rhs.z * to,
+			self.w * from + rhs.w * to,
+		)
+		.normalize()
+	}
+
+	/// Whether two quaternions represent the same rotation within `tolerance`
+	#[must_use]
+	pub fn approximately_equals(&self, rhs: &Self, tolerance: Real) -> bool {
+		// q and -q are the same rotation
+		1.0 - self.dot(rhs).abs() <= tolerance
+	}
+}
+
+impl Mul for Quaternion {
+	type Output = Self;
+
+	/// Combines two rotations, applying `rhs` first
+	fn mul(self, rhs: Self) -> Self::Output {
+		Self::new(
+			self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+			self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+			self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+			self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::f32::consts::FRAC_PI_2;
+
+	fn assert_vectors_equal(actual: Vector3, expected: Vector3) {
+		assert!((actual - expected).magnitude() < 1e-5, "left: {:?} not equal right: {:?}", actual, expected);
+	}
+
+	#[test]
+	pub fn rotate() {
+		let rotation = Quaternion::from_axis_angle(Vector3::z_axis(), FRAC_PI_2);
+		assert_vectors_equal(rotation.rotate(Vector3::x_axis()), Vector3::y_axis());
+	}
+
+	#[test]
+	pub fn compose() {
+		let quarter = Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2);
+		let half = quarter * quarter;
+		assert_vectors_equal(half.rotate(Vector3::x_axis()), Vector3::x_axis().inverse());
+		assert!((half * half.conjugate()).approximately_equals(&Quaternion::identity(), 1e-6));
+	}
+
+	#[test]
+	pub fn slerp() {
+		let start = Quaternion::identity();
+		let end = Quaternion::from_axis_angle(Vector3::z_axis(), FRAC_PI_2);
+		let halfway = Quaternion::from_axis_angle(Vector3::z_axis(), FRAC_PI_2 / 2.0);
+		assert!(start.slerp(&end, 0.5).approximately_equals(&halfway, 1e-6));
+		assert!(start.slerp(&end, 1.0).approximately_equals(&end, 1e-6));
+
+		let flipped = Quaternion::new(-end.x, -end.y, -end.z, -end.w);
+		assert!(start.slerp(&flipped, 0.5).approximately_equals(&halfway, 1e-6));
+	}
+
+	#[test]
+	pub fn integrate_and_scaled_axis() {
+		let mut rotation = Quaternion::identity();
+		for _ in 0..1000 {
+			rotation = rotation.integrate(Vector3::new(0.0, 0.0, FRAC_PI_2), 0.001);
+		}
+		assert!(rotation.approximately_equals(&Quaternion::from_axis_angle(Vector3::z_axis(), FRAC_PI_2), 1e-5));
+		assert_vectors_equal(rotation.to_scaled_axis(), Vector3::new(0.0, 0.0, FRAC_PI_2));
+		assert_vectors_equal(Quaternion::identity().to_scaled_axis(), Vector3::zero());
+	}
+}
@@ -0,0 +1,24 @@
+use crate::{Real, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+	pub origin: Vector3,
+
+	/// Expected to be normalized, so hit distances are in world units
+	pub direction: Vector3,
+}
+
+impl Ray {
+	#[must_use]
+	pub fn new(origin: Vector3, direction: Vector3) -> Self {
+		Self {
+			origin,
+			direction: direction.normalize(),
+		}
+	}
+
+	#[must_use]
+	pub fn point_at(&self, distance: Real) -> Vector3 {
+		self.origin + self.direction * distance
+	}
+}
@@ -0,0 +1,344 @@
+use crate::Real;
+
+/// A width and height, such as the size of a window, texture, or widget
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Extent2D {
+	pub width: Real,
+	pub height: Real,
+}
+
+impl Extent2D {
+	#[must_use]
+	pub const fn new(width: Real, height: Real) -> Self {
+		Self { width, height }
+	}
+
+	#[must_use]
+	pub fn area(&self) -> Real {
+		self.width * self.height
+	}
+
+	/// Width over height, or zero for an extent without height
+	#[must_use]
+	pub fn aspect_ratio(&self) -> Real {
+		if self.height == 0.0 { 0.0 } else { self.width / self.height }
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.width <= 0.0 || self.height <= 0.0
+	}
+
+	/// Converts logical units to physical pixels, given the window's DPI scale factor
+	#[must_use]
+	pub fn to_physical(&self, scale_factor: Real) -> Self {
+		Self::new(self.width * scale_factor, self.height * scale_factor)
+	}
+
+	/// Converts physical pixels to logical units, given the window's DPI scale factor
+	#[must_use]
+	pub fn to_logical(&self, scale_factor: Real) -> Self {
+		self.to_physical(scale_factor.recip())
+	}
+}
+
+/// Where a rectangle is placed within another, as in `Rect::anchored`
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Anchor {
+	#[default]
+	TopLeft,
+	Top,
+	TopRight,
+	Left,
+	Center,
+	Right,
+	BottomLeft,
+	Bottom,
+	BottomRight,
+}
+
+impl Anchor {
+	/// How far across and down the anchor sits, from 0 at the left or top to 1 at the far side
+	#[must_use]
+	pub const fn fractions(&self) -> (Real, Real) {
+		match self {
+			Self::TopLeft => (0.0, 0.0),
+			Self::Top => (0.5, 0.0),
+			Self::TopRight => (1.0, 0.0),
+			Self::Left => (0.0, 0.5),
+			Self::Center => (0.5, 0.5),
+			Self::Right => (1.0, 0.5),
+			Self::BottomLeft => (0.0, 1.0),
+			Self::Bottom => (0.5, 1.0),
+			Self::BottomRight => (1.0, 1.0),
+		}
+	}
+}
+
+/// An axis-aligned rectangle in window coordinates, with its origin at the top left, y down
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Rect {
+	pub x: Real,
+	pub y: Real,
+	pub width: Real,
+	pub height: Real,
+}
+
+impl Rect {
+	#[must_use]
+	pub const fn new(x: Real, y: Real, width: Real, height: Real) -> Self {
+		Self { x, y, width, height }
+	}
+
+	/// A rectangle of the given extent at the origin
+	#[must_use]
+	pub const fn from_extent(extent: Extent2D) -> Self {
+		Self::new(0.0, 0.0, extent.width, extent.height)
+	}
+
+	/// The rectangle spanning two opposite corners, in either order
+	#[must_use]
+	pub fn from_corners(a: (Real, Real), b: (Real, Real)) -> Self {
+		let (left, top) = (a.0.min(b.0), a.1.min(b.1));
+		Self::new(left, top, a.0.max(b.0) - left, a.1.max(b.1) - top)
+	}
+
+	#[must_use]
+	pub fn right(&self) -> Real {
+		self.x + self.width
+	}
+
+	#[must_use]
+	pub fn bottom(&self) -> Real {
+		self.y + self.height
+	}
+
+	#[must_use]
+	pub const fn extent(&self) -> Extent2D {
+		Extent2D::new(self.width, self.height)
+	}
+
+	#[must_use]
+	pub fn center(&self) -> (Real, Real) {
+		(self.x + self.width * 0.5, self.y + self.height * 0.5)
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.extent().is_empty()
+	}
+
+	/// Whether the point is inside, counting the top and left edges but not the bottom and right,
+	/// so a point on a shared edge is in exactly one of two neighboring rectangles
+	#[must_use]
+	pub fn contains(&self, x: Real, y: Real) -> bool {
+		x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+	}
+
+	#[must_use]
+	pub fn contains_rect(&self, other: &Self) -> bool {
+		other.x >= self.x && other.y >= self.y && other.right() <= self.right() && other.bottom() <= self.bottom()
+	}
+
+	#[must_use]
+	pub fn intersects(&self, other: &Self) -> bool {
+		self.intersection(other).is_some()
+	}
+
+	/// The overlapping area, or `None` if the rectangles only touch or don't meet
+	#[must_use]
+	pub fn intersection(&self, other: &Self) -> Option<Self> {
+		let (left, top) = (self.x.max(other.x), self.y.max(other.y));
+		let (right, bottom) = (self.right().min(other.right()), self.bottom().min(other.bottom()));
+		(right > left && bottom > top).then(|| Self::new(left, top, right - left, bottom - top))
+	}
+
+	/// The smallest rectangle containing both
+	#[must_use]
+	pub fn union(&self, other: &Self) -> Self {
+		Self::from_corners(
+			(self.x.min(other.x), self.y.min(other.y)),
+			(self.right().max(other.right()), self.bottom().max(other.bottom())),
+		)
+	}
+
+	/// Shrinks each edge inward, such as by padding or a safe area, without going below zero
+	#[must_use]
+	pub fn inset(&self, left: Real, top: Real, right: Real, bottom: Real) -> Self {
+		Self::new(
+			self.x + left,
+			self.y + top,
+			(self.width - left - right).max(0.0),
+			(self.height - top - bottom).max(0.0),
+		)
+	}
+
+	/// A rectangle of the given extent placed at an anchor inside this one, such as a dialog
+	#[must_use]
+	pub fn anchored(&self, anchor: Anchor, extent: Extent2D) -> Self {
+		let (across, down) = anchor.fractions();
+		Self::new(
+			self.x + (self.width - extent.width) * across,
+			self.y + (self.height - extent.height) * down,
+			extent.width,
+			extent.height,
+		)
+	}
+
+	/// Splits into a left and right part, with `fraction` of the width going to the left
+	#[must_use]
+	pub fn split_horizontally(&self, fraction: Real) -> (Self, Self) {
+		let width = self.width * fraction.clamp(0.0, 1.0);
+		(
+			Self::new(self.x, self.y, width, self.height),
+			Self::new(self.x + width, self.y, self.width - width, self.height),
+		)
+	}
+
+	/// Splits into a top and bottom part, with `fraction` of the height going to the top
+	#[must_use]
+	pub fn split_vertically(&self, fraction: Real) -> (Self, Self) {
+		let height = self.height * fraction.clamp(0.0, 1.0);
+		(
+			Self::new(self.x, self.y, self.width, height),
+			Self::new(self.x, self.y + height, self.width, self.height - height),
+		)
+	}
+
+	/// Converts logical units to physical pixels, given the window's DPI scale factor
+	#[must_use]
+	pub fn to_physical(&self, scale_factor: Real) -> Self {
+		Self::new(self.x * scale_factor, self.y * scale_factor, self.width * scale_factor, self.height * scale_factor)
+	}
+
+	/// Converts physical pixels to logical units, given the window's DPI scale factor
+	#[must_use]
+	pub fn to_logical(&self, scale_factor: Real) -> Self {
+		self.to_physical(scale_factor.recip())
+	}
+
+	/// The whole pixels `[x, y, width, height]` to scissor to, clipped to a target's extent.
+	/// Returns `None` when nothing would be drawn, which graphics APIs reject as a scissor.
+	#[must_use]
+	pub fn scissor(&self, target: Extent2D) -> Option<[u32; 4]> {
+		let clipped = self.intersection(&Rect::from_extent(target))?;
+		let (left, top) = (clipped.x as u32, clipped.y as u32);
+		let (right, bottom) = ((clipped.right() + 0.999) as u32, (clipped.bottom() + 0.999) as u32);
+		(right > left && bottom > top).then(|| [left, top, right - left, bottom - top])
+	}
+}
+
+/// The region of a render target that a camera draws into, and the depth range it writes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+	pub rect: Rect,
+	pub min_depth: Real,
+	pub max_depth: Real,
+}
+
+impl Default for Viewport {
+	fn default() -> Self {
+		Self::new(Rect::default())
+	}
+}
+
+impl Viewport {
+	#[must_use]
+	pub const fn new(rect: Rect) -> Self {
+		Self {
+			rect,
+			min_depth: 0.0,
+			max_depth: 1.0,
+		}
+	}
+
+	/// A viewport covering a whole render target
+	#[must_use]
+	pub const fn from_extent(extent: Extent2D) -> Self {
+		Self::new(Rect::from_extent(extent))
+	}
+
+	/// The aspect ratio to build the camera's projection with
+	#[must_use]
+	pub fn aspect_ratio(&self) -> Real {
+		self.rect.extent().aspect_ratio()
+	}
+
+	/// Converts a point in window coordinates to normalized device coordinates,
+	/// from -1 at the left and bottom of the viewport to 1 at the right and top
+	#[must_use]
+	pub fn window_to_ndc(&self, x: Real, y: Real) -> (Real, Real) {
+		let (across, down) = ((x - self.rect.x) / self.rect.width, (y - self.rect.y) / self.rect.height);
+		(across * 2.0 - 1.0, 1.0 - down * 2.0)
+	}
+
+	/// Converts normalized device coordinates back to window coordinates
+	#[must_use]
+	pub fn ndc_to_window(&self, x: Real, y: Real) -> (Real, Real) {
+		(self.rect.x + (x + 1.0) * 0.5 * self.rect.width, self.rect.y + (1.0 - y) * 0.5 * self.rect.height)
+	}
+
+	#[must_use]
+	pub fn to_physical(&self, scale_factor: Real) -> Self {
+		Self {
+			rect: self.rect.to_physical(scale_factor),
+			..*self
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn intersection() {
+		let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+		let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+		assert_eq!(a.intersection(&b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+		assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 15.0, 15.0));
+		assert!(!a.intersects(&Rect::new(10.0, 0.0, 5.0, 5.0)));
+		assert!(a.contains(0.0, 9.9));
+		assert!(!a.contains(10.0, 5.0));
+		assert!(a.contains_rect(&Rect::new(2.0, 2.0, 8.0, 8.0)));
+		assert!(!a.contains_rect(&b));
+		assert_eq!(Rect::from_corners((4.0, 1.0), (2.0, 3.0)), Rect::new(2.0, 1.0, 2.0, 2.0));
+	}
+
+	#[test]
+	pub fn layout() {
+		let screen = Rect::from_extent(Extent2D::new(800.0, 600.0));
+		let dialog = screen.anchored(Anchor::Center, Extent2D::new(200.0, 100.0));
+		assert_eq!(dialog, Rect::new(300.0, 250.0, 200.0, 100.0));
+		assert_eq!(screen.anchored(Anchor::BottomRight, Extent2D::new(10.0, 10.0)).center(), (795.0, 595.0));
+		assert_eq!(screen.inset(10.0, 20.0, 10.0, 1000.0), Rect::new(10.0, 20.0, 780.0, 0.0));
+
+		let (sidebar, rest) = screen.split_horizontally(0.25);
+		assert_eq!(sidebar, Rect::new(0.0, 0.0, 200.0, 600.0));
+		let (viewport, console) = rest.split_vertically(0.75);
+		assert_eq!(viewport, Rect::new(200.0, 0.0, 600.0, 450.0));
+		assert_eq!(console, Rect::new(200.0, 450.0, 600.0, 150.0));
+	}
+
+	#[test]
+	pub fn scaling_and_scissoring() {
+		let logical = Rect::new(10.0, 10.0, 100.0, 50.0);
+		assert_eq!(logical.to_physical(2.0), Rect::new(20.0, 20.0, 200.0, 100.0));
+		assert_eq!(logical.to_physical(2.0).to_logical(2.0), logical);
+		assert_eq!(Extent2D::new(1280.0, 720.0).to_logical(2.0), Extent2D::new(640.0, 360.0));
+
+		let target = Extent2D::new(100.0, 100.0);
+		assert_eq!(Rect::new(-5.0, 10.5, 50.0, 200.0).scissor(target), Some([0, 10, 45, 90]));
+		assert_eq!(Rect::new(150.0, 0.0, 10.0, 10.0).scissor(target), None);
+	}
+
+	#[test]
+	pub fn viewport() {
+		let viewport = Viewport::new(Rect::new(100.0, 0.0, 200.0, 100.0));
+		assert_eq!(viewport.aspect_ratio(), 2.0);
+		assert_eq!(viewport.window_to_ndc(100.0, 0.0), (-1.0, 1.0));
+		assert_eq!(viewport.window_to_ndc(200.0, 50.0), (0.0, 0.0));
+		assert_eq!(viewport.ndc_to_window(1.0, -1.0), (300.0, 100.0));
+		assert_eq!(viewport.to_physical(2.0).rect, Rect::new(200.0, 0.0, 400.0, 200.0));
+	}
+}
@@ -0,0 +1,205 @@
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::{Quaternion, Real, Vector3};
+use core::ops::Mul;
+
+/// A column-major 4x4 matrix, indexed as `matrix[column][row]`, which is the layout shaders expect
+pub type Matrix4 = [[Real; 4]; 4];
+
+/// A translation, rotation, and scale, applied in reverse order
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+	pub translation: Vector3,
+	pub rotation: Quaternion,
+	pub scale: Vector3,
+}
+
+impl Default for Transform {
+	fn default() -> Self {
+		Self::identity()
+	}
+}
+
+impl Transform {
+	#[must_use]
+	pub const fn identity() -> Self {
+		Self {
+			translation: Vector3::new(0.0, 0.0, 0.0),
+			rotation: Quaternion::identity(),
+			scale: Vector3::new(1.0, 1.0, 1.0),
+		}
+	}
+
+	#[must_use]
+	pub fn from_translation(translation: Vector3) -> Self {
+		Self { translation, ..Self::identity() }
+	}
+
+	#[must_use]
+	pub fn transform_point(&self, point: Vector3) -> Vector3 {
+		self.translation + self.rotation.rotate(point * self.scale)
+	}
+
+	#[must_use]
+	pub fn transform_vector(&self, vector: Vector3) -> Vector3 {
+		self.rotation.rotate(vector * self.scale)
+	}
+
+	/// The transform that undoes this one.
+	/// Exact for uniform scales; non-uniform scales combined with rotation are approximated.
+	#[must_use]
+	pub fn inverse(&self) -> Self {
+		let reciprocal = |value: Real| if value == 0.0 { 0.0 } else { value.recip() };
+		let scale = Vector3::new(reciprocal(self.scale.x()), reciprocal(self.scale.y()), reciprocal(self.scale.z()));
+		let rotation = self.rotation.conjugate();
+		Self {
+			translation: rotation.rotate(self.translation.inverse()) * scale,
+			rotation,
+			scale,
+		}
+	}
+
+	/// Blends each part separately from this transform at `t = 0` to `rhs` at `t = 1`
+	#[must_use]
+	pub fn lerp(&self, rhs: &Self, t: Real) -> Self {
+		Self {
+			translation: self.translation.lerp(&rhs.translation, t),
+			rotation: self.rotation.slerp(&rhs.rotation, t),
+			scale: self.scale.lerp(&rhs.scale, t),
+		}
+	}
+
+	/// Blends any number of weighted transforms, such as several animations playing at once.
+	/// The weights don't need to add up to one. With no weight at all, the identity is returned.
+	#[must_use]
+	pub fn blend(weighted: &[(Self, Real)]) -> Self {
+		let total = weighted.iter().map(|(_, weight)| weight).sum::<Real>();
+		let Some((first, _)) = weighted.first().filter(|_| total > 0.0) else {
+			return Self::identity();
+		};
+		let mut blended = Self {
+			translation: Vector3::zero(),
+			rotation: Quaternion::new(0.0, 0.0, 0.0, 0.0),
+			scale: Vector3::zero(),
+		};
+		for (transform, weight) in weighted {
+			let weight = weight / total;
+			blended.translation += transform.translation * weight;
+			blended.scale += transform.scale * weight;
+			// q and -q are the same rotation, so flip any facing away from the first
+			let rotation_weight = if transform.rotation.dot(&first.rotation) < 0.0 { -weight } else { weight };
+			let (sum, rotation) = (blended.rotation, transform.rotation);
+			blended.rotation = Quaternion::new(
+				rotation.x.mul_add(rotation_weight, sum.x),
+				rotation.y.mul_add(rotation_weight, sum.y),
+				rotation.z.mul_add(rotation_weight, sum.z),
+				rotation.w.mul_add(rotation_weight, sum.w),
+			);
+		}
+		blended.rotation = blended.rotation.normalize();
+		blended
+	}
+
+	#[must_use]
+	pub fn to_matrix(&self) -> Matrix4 {
+		let column = |axis: Vector3, scale: Real| {
+			let axis = self.rotation.rotate(axis) * scale;
+			[axis.x(), axis.y(), axis.z(), 0.0]
+		};
+		[
+			column(Vector3::x_axis(), self.scale.x()),
+			column(Vector3::y_axis(), self.scale.y()),
+			column(Vector3::z_axis(), self.scale.z()),
+			[self.translation.x(), self.translation.y(), self.translation.z(), 1.0],
+		]
+	}
+}
+
+impl Mul for Transform {
+	type Output = Self;
+
+	/// Composes two transforms, such as a parent's world transform with a child's local transform
+	fn mul(self, rhs: Self) -> Self::Output {
+		Self {
+			translation: self.transform_point(rhs.translation),
+			rotation: (self.rotation * rhs.rotation).normalize(),
+			scale: self.scale * rhs.scale,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::f32::consts::FRAC_PI_2;
+
+	fn assert_vectors_equal(actual: Vector3, expected: Vector3) {
+		assert!((actual - expected).magnitude() < 1e-5, "left: {:?} not equal right: {:?}", actual, expected);
+	}
+
+	fn transform() -> Transform {
+		Transform {
+			translation: Vector3::new(1.0, 2.0, 3.0),
+			rotation: Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2),
+			scale: Vector3::new(2.0, 2.0, 2.0),
+		}
+	}
+
+	#[test]
+	pub fn compose() {
+		let parent = transform();
+		let child = Transform::from_translation(Vector3::x_axis());
+		let point = Vector3::new(0.5, 0.0, 0.0);
+		assert_vectors_equal((parent * child).transform_point(point), parent.transform_point(child.transform_point(point)));
+	}
+
+	#[test]
+	pub fn inverse() {
+		let transform = transform();
+		let point = Vector3::new(4.0, -1.0, 0.5);
+		assert_vectors_equal(transform.inverse().transform_point(transform.transform_point(point)), point);
+		let identity = transform * transform.inverse();
+		assert_vectors_equal(identity.translation, Vector3::zero());
+		assert_vectors_equal(identity.scale, Vector3::new(1.0, 1.0, 1.0));
+	}
+
+	#[test]
+	pub fn blend() {
+		let a = transform();
+		let b = Transform::identity();
+		let halfway = Transform::blend(&[(a, 2.0), (b, 2.0)]);
+		assert_vectors_equal(halfway.translation, a.lerp(&b, 0.5).translation);
+		assert_vectors_equal(halfway.scale, Vector3::new(1.5, 1.5, 1.5));
+		assert!(halfway.rotation.approximately_equals(&a.rotation.slerp(&b.rotation, 0.5), 1e-6));
+
+		let flipped = Transform {
+			rotation: Quaternion::new(-a.rotation.x, -a.rotation.y, -a.rotation.z, -a.rotation.w),
+			..a
+		};
+		assert!(Transform::blend(&[(b, 1.0), (flipped, 1.0)]).rotation.approximately_equals(&halfway.rotation, 1e-6));
+		assert_eq!(Transform::blend(&[(a, 1.0)]).translation, a.translation);
+		assert_eq!(Transform::blend(&[]), Transform::identity());
+	}
+
+	#[test]
+	pub fn matrix() {
+		let transform = transform();
+		let matrix = transform.to_matrix();
+		let point = Vector3::new(1.0, 0.0, 0.0);
+		let row = |row: usize| matrix[0][row] * point.x() + matrix[1][row] * point.y() + matrix[2][row] * point.z() + matrix[3][row];
+		assert_vectors_equal(Vector3::new(row(0), row(1), row(2)), transform.transform_point(point));
+	}
+
+	#[test]
+	pub fn lerp() {
+		let end = transform();
+		let halfway = Transform::identity().lerp(&end, 0.5);
+		assert_vectors_equal(halfway.translation, Vector3::new(0.5, 1.0, 1.5));
+		assert_vectors_equal(halfway.scale, Vector3::new(1.5, 1.5, 1.5));
+		assert!(
+			halfway
+				.rotation
+				.approximately_equals(&Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2 / 2.0), 1e-6)
+		);
+	}
+}
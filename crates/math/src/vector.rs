@@ -1,5 +1,7 @@
-use crate::{reals_are_equal, Real};
-use std::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub};
+#[cfg(not(any(feature = "std", test)))]
+use crate::Float;
+use crate::{Real, reals_are_equal};
+use core::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub};
 
 #[derive(Debug, Copy, Clone)]
 pub struct Vector<T, const LEN: usize>
@@ -125,6 +127,12 @@ impl<const LEN: usize> Vector<Real, { LEN }> {
 	pub fn dot(&self, rhs: &Self) -> Real {
 		self.elements.iter().zip(rhs.elements.iter()).fold(0.0 as Real, |acc, (a, b)| (*a).mul_add(*b, acc))
 	}
+
+	/// Blends linearly from this vector at `t = 0` to `rhs` at `t = 1`
+	#[must_use]
+	pub fn lerp(&self, rhs: &Self, t: Real) -> Self {
+		*self + (*rhs - *self) * t
+	}
 }
 
 pub type Vector3 = Vector<Real, 3>;
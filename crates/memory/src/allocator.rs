@@ -0,0 +1,118 @@
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+static IS_INSTALLED: AtomicBool = AtomicBool::new(false);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps another allocator to count every heap allocation the process makes.
+/// Install it as the global allocator in the game's binary crate, at the cost of a few atomic
+/// operations per allocation, and the app's stats overlay shows the counts:
+///
+/// ```ignore
+/// use memory::TrackingAllocator;
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new(System);
+/// ```
+pub struct TrackingAllocator<A = System> {
+	allocator: A,
+}
+
+impl<A> TrackingAllocator<A> {
+	pub const fn new(allocator: A) -> Self {
+		Self { allocator }
+	}
+}
+
+fn allocated(bytes: usize) {
+	IS_INSTALLED.store(true, Ordering::Relaxed);
+	let total = BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+	PEAK.fetch_max(total, Ordering::Relaxed);
+	ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+	LIVE_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+fn released(bytes: usize) {
+	BYTES.fetch_sub(bytes, Ordering::Relaxed);
+	LIVE_ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let pointer = self.allocator.alloc(layout);
+		if !pointer.is_null() {
+			allocated(layout.size());
+		}
+		pointer
+	}
+
+	unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+		let pointer = self.allocator.alloc_zeroed(layout);
+		if !pointer.is_null() {
+			allocated(layout.size());
+		}
+		pointer
+	}
+
+	unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+		self.allocator.dealloc(pointer, layout);
+		released(layout.size());
+	}
+
+	unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		let new_pointer = self.allocator.realloc(pointer, layout, new_size);
+		if !new_pointer.is_null() {
+			released(layout.size());
+			allocated(new_size);
+		}
+		new_pointer
+	}
+}
+
+/// The process's heap usage as counted by the tracking allocator
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeapStats {
+	pub bytes: usize,
+	pub peak: usize,
+
+	/// Allocations made since the process started, including ones since freed
+	pub allocations: usize,
+
+	/// Allocations that haven't been freed yet, which climbs steadily when something leaks
+	pub live_allocations: usize,
+}
+
+/// The heap usage, or `None` when the tracking allocator isn't the global allocator
+pub fn heap_stats() -> Option<HeapStats> {
+	IS_INSTALLED.load(Ordering::Relaxed).then(|| HeapStats {
+		bytes: BYTES.load(Ordering::Relaxed),
+		peak: PEAK.load(Ordering::Relaxed),
+		allocations: ALLOCATIONS.load(Ordering::Relaxed),
+		live_allocations: LIVE_ALLOCATIONS.load(Ordering::Relaxed),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn counts_allocations() {
+		let allocator = TrackingAllocator::new(System);
+		let before = heap_stats().unwrap_or_default();
+		unsafe {
+			let layout = Layout::from_size_align(4096, 8).unwrap();
+			let pointer = allocator.alloc(layout);
+			let pointer = allocator.realloc(pointer, layout, 8192);
+			let stats = heap_stats().unwrap();
+			assert!(stats.allocations >= before.allocations + 2 && stats.peak >= 8192);
+			allocator.dealloc(pointer, Layout::from_size_align(8192, 8).unwrap());
+		}
+	}
+}
@@ -0,0 +1,189 @@
+use crate::tag::{MemoryTag, TrackedBytes};
+use std::mem::size_of;
+
+/// A growable buffer of values that's reset rather than freed, for data rebuilt every frame.
+/// It keeps its capacity between resets, so once it has grown to a frame's worth it stops
+/// allocating, and its capacity is counted under its tag.
+#[derive(Debug, Clone)]
+pub struct Arena<T> {
+	values: Vec<T>,
+	tracked: TrackedBytes,
+}
+
+impl<T> Arena<T> {
+	pub fn new(tag: MemoryTag) -> Self {
+		Self::with_capacity(tag, 0)
+	}
+
+	pub fn with_capacity(tag: MemoryTag, capacity: usize) -> Self {
+		let mut arena = Self {
+			values: Vec::with_capacity(capacity),
+			tracked: TrackedBytes::new(tag),
+		};
+		arena.track();
+		arena
+	}
+
+	/// Adds a value, returning its index until the next reset
+	pub fn push(&mut self, value: T) -> usize {
+		self.values.push(value);
+		self.track();
+		self.values.len() - 1
+	}
+
+	pub fn extend(&mut self, values: impl IntoIterator<Item = T>) {
+		self.values.extend(values);
+		self.track();
+	}
+
+	pub fn get(&self, index: usize) -> Option<&T> {
+		self.values.get(index)
+	}
+
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+		self.values.get_mut(index)
+	}
+
+	pub fn as_slice(&self) -> &[T] {
+		&self.values
+	}
+
+	pub fn as_mut_slice(&mut self) -> &mut [T] {
+		&mut self.values
+	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, T> {
+		self.values.iter()
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	pub fn capacity(&self) -> usize {
+		self.values.capacity()
+	}
+
+	/// Removes every value, keeping the capacity for the next frame
+	pub fn reset(&mut self) {
+		self.values.clear();
+	}
+
+	/// Frees capacity past what's needed, such as after a one-off spike
+	pub fn shrink_to(&mut self, capacity: usize) {
+		self.values.shrink_to(capacity);
+		self.track();
+	}
+
+	pub fn tag(&self) -> &MemoryTag {
+		self.tracked.tag()
+	}
+
+	fn track(&mut self) {
+		let bytes = self.values.capacity() * size_of::<T>();
+		if bytes != self.tracked.bytes() {
+			self.tracked.set(bytes);
+		}
+	}
+}
+
+impl<'a, T> IntoIterator for &'a Arena<T> {
+	type Item = &'a T;
+	type IntoIter = std::slice::Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.values.iter()
+	}
+}
+
+/// Reusable scratch buffers for work that needs a temporary list, such as sorting in a frame.
+/// Taking a buffer reuses one given back earlier, and every pooled buffer counts under the tag.
+#[derive(Debug)]
+pub struct ScratchPool<T> {
+	free: Vec<Vec<T>>,
+	tag: MemoryTag,
+	tracked: TrackedBytes,
+}
+
+impl<T> ScratchPool<T> {
+	pub fn new(tag: MemoryTag) -> Self {
+		Self {
+			free: Vec::new(),
+			tracked: TrackedBytes::new(tag.clone()),
+			tag,
+		}
+	}
+
+	/// An empty buffer, with whatever capacity it had when it was given back
+	pub fn take(&mut self) -> Vec<T> {
+		let buffer = self.free.pop().unwrap_or_default();
+		self.track();
+		buffer
+	}
+
+	/// Returns a buffer to the pool, clearing it for the next user
+	pub fn give_back(&mut self, mut buffer: Vec<T>) {
+		buffer.clear();
+		self.free.push(buffer);
+		self.track();
+	}
+
+	/// How many buffers are waiting to be reused
+	pub fn available(&self) -> usize {
+		self.free.len()
+	}
+
+	pub fn tag(&self) -> &MemoryTag {
+		&self.tag
+	}
+
+	fn track(&mut self) {
+		let bytes = self.free.iter().map(|buffer| buffer.capacity() * size_of::<T>()).sum();
+		self.tracked.set(bytes);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn arena_keeps_capacity() {
+		let tag = MemoryTag::new("test arena");
+		let mut arena = Arena::<u64>::new(tag.clone());
+		arena.extend(0..100);
+		assert_eq!(arena.push(100), 100);
+		let capacity = arena.capacity();
+		assert_eq!(tag.stats().bytes, capacity * 8);
+
+		arena.reset();
+		assert!(arena.is_empty());
+		arena.extend(0..50);
+		assert_eq!((arena.capacity(), tag.stats().bytes), (capacity, capacity * 8));
+		assert_eq!(arena.iter().sum::<u64>(), 1225);
+
+		arena.shrink_to(50);
+		assert_eq!(tag.stats().bytes, arena.capacity() * 8);
+		drop(arena);
+		assert_eq!(tag.stats().bytes, 0);
+	}
+
+	#[test]
+	pub fn scratch_buffers_are_reused() {
+		let tag = MemoryTag::new("test scratch");
+		let mut pool = ScratchPool::<u32>::new(tag.clone());
+		let mut buffer = pool.take();
+		buffer.extend(0..64);
+		let capacity = buffer.capacity();
+		pool.give_back(buffer);
+		assert_eq!((pool.available(), tag.stats().bytes), (1, capacity * 4));
+
+		let buffer = pool.take();
+		assert!(buffer.is_empty() && buffer.capacity() == capacity);
+		assert_eq!(tag.stats().bytes, 0);
+	}
+}
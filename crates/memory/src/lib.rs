@@ -0,0 +1,5 @@
+mod allocator;
+mod arena;
+mod tag;
+
+pub use self::{allocator::*, arena::*, tag::*};
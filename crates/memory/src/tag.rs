@@ -0,0 +1,197 @@
+use crate::allocator::heap_stats;
+use std::{
+	collections::BTreeMap,
+	sync::{
+		Arc, Mutex, OnceLock,
+		atomic::{AtomicUsize, Ordering},
+	},
+};
+
+#[derive(Debug, Default)]
+struct TagCounters {
+	bytes: AtomicUsize,
+	peak: AtomicUsize,
+	allocations: AtomicUsize,
+}
+
+fn registry() -> &'static Mutex<BTreeMap<String, Arc<TagCounters>>> {
+	static REGISTRY: OnceLock<Mutex<BTreeMap<String, Arc<TagCounters>>>> = OnceLock::new();
+	REGISTRY.get_or_init(Default::default)
+}
+
+/// A named bucket of memory usage, such as "debug draw" or "physics contacts".
+/// Tags with the same name share their counters, so a subsystem adds up under one line.
+#[derive(Debug, Clone)]
+pub struct MemoryTag {
+	name: Arc<str>,
+	counters: Arc<TagCounters>,
+}
+
+impl MemoryTag {
+	pub fn new(name: &str) -> Self {
+		let counters = registry()
+			.lock()
+			.unwrap_or_else(|error| error.into_inner())
+			.entry(name.to_string())
+			.or_default()
+			.clone();
+		Self { name: name.into(), counters }
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn allocated(&self, bytes: usize) {
+		let total = self.counters.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+		self.counters.peak.fetch_max(total, Ordering::Relaxed);
+		self.counters.allocations.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub fn released(&self, bytes: usize) {
+		let _ = self
+			.counters
+			.bytes
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| Some(current.saturating_sub(bytes)));
+	}
+
+	pub fn stats(&self) -> TagStats {
+		TagStats::new(&self.name, &self.counters)
+	}
+}
+
+/// Bytes held under a tag by one owner, following its buffers as they grow until it drops
+#[derive(Debug)]
+pub struct TrackedBytes {
+	tag: MemoryTag,
+	bytes: usize,
+}
+
+impl TrackedBytes {
+	pub fn new(tag: MemoryTag) -> Self {
+		Self { tag, bytes: 0 }
+	}
+
+	pub fn tag(&self) -> &MemoryTag {
+		&self.tag
+	}
+
+	pub fn bytes(&self) -> usize {
+		self.bytes
+	}
+
+	/// Updates how many bytes the owner holds, usually its buffers' capacity after they change
+	pub fn set(&mut self, bytes: usize) {
+		if bytes > self.bytes {
+			self.tag.allocated(bytes - self.bytes);
+		} else {
+			self.tag.released(self.bytes - bytes);
+		}
+		self.bytes = bytes;
+	}
+}
+
+/// A clone owns its own copy of the buffers, so it counts toward the tag separately
+impl Clone for TrackedBytes {
+	fn clone(&self) -> Self {
+		let mut tracked = Self::new(self.tag.clone());
+		tracked.set(self.bytes);
+		tracked
+	}
+}
+
+impl Drop for TrackedBytes {
+	fn drop(&mut self) {
+		self.tag.released(self.bytes);
+	}
+}
+
+/// A tag's usage at the time it was read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagStats {
+	pub name: String,
+	pub bytes: usize,
+
+	/// The most bytes the tag has held at once, which shows blow-ups that have since been freed
+	pub peak: usize,
+
+	/// How many times the tag's usage has grown
+	pub allocations: usize,
+}
+
+impl TagStats {
+	fn new(name: &str, counters: &TagCounters) -> Self {
+		Self {
+			name: name.to_string(),
+			bytes: counters.bytes.load(Ordering::Relaxed),
+			peak: counters.peak.load(Ordering::Relaxed),
+			allocations: counters.allocations.load(Ordering::Relaxed),
+		}
+	}
+}
+
+/// Every tag's usage, sorted by name
+pub fn tag_stats() -> Vec<TagStats> {
+	let registry = registry().lock().unwrap_or_else(|error| error.into_inner());
+	registry.iter().map(|(name, counters)| TagStats::new(name, counters)).collect()
+}
+
+/// Bytes in the largest unit that keeps the number above one, such as "1.50 MB"
+pub fn format_bytes(bytes: usize) -> String {
+	const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+	let mut value = bytes as f64;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	if unit == 0 { format!("{bytes} B") } else { format!("{value:.2} {}", UNITS[unit]) }
+}
+
+/// The heap and every tag in use as lines of text, for the stats overlay
+pub fn memory_overlay_lines() -> Vec<String> {
+	let heap = heap_stats().map(|heap| format!("Heap {} (peak {})", format_bytes(heap.bytes), format_bytes(heap.peak)));
+	let tags = tag_stats()
+		.into_iter()
+		.filter(|tag| tag.bytes > 0)
+		.map(|tag| format!("{} {} (peak {})", tag.name, format_bytes(tag.bytes), format_bytes(tag.peak)));
+	heap.into_iter().chain(tags).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn shared_counters() {
+		let (first, second) = (MemoryTag::new("test shared"), MemoryTag::new("test shared"));
+		first.allocated(100);
+		second.allocated(50);
+		second.released(120);
+		let stats = first.stats();
+		assert_eq!((stats.bytes, stats.peak, stats.allocations), (30, 150, 2));
+		assert!(tag_stats().iter().any(|tag| tag.name == "test shared" && tag.bytes == 30));
+	}
+
+	#[test]
+	pub fn tracked_bytes() {
+		let tag = MemoryTag::new("test tracked");
+		let mut tracked = TrackedBytes::new(tag.clone());
+		tracked.set(64);
+		tracked.set(256);
+		let clone = tracked.clone();
+		assert_eq!(tag.stats().bytes, 512);
+		tracked.set(16);
+		assert_eq!(tag.stats().bytes, 272);
+		drop(clone);
+		drop(tracked);
+		assert_eq!((tag.stats().bytes, tag.stats().peak), (0, 512));
+	}
+
+	#[test]
+	pub fn formatting() {
+		assert_eq!(format_bytes(512), "512 B");
+		assert_eq!(format_bytes(1536), "1.50 KB");
+		assert_eq!(format_bytes(3 * 1024 * 1024), "3.00 MB");
+	}
+}
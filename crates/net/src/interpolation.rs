@@ -0,0 +1,190 @@
+use math::{Quaternion, Real, Transform, Vector3};
+use std::collections::VecDeque;
+
+/// State that can be blended between two network snapshots.
+/// A `t` past 1 continues along the same path, which is how snapshots are extrapolated.
+pub trait Interpolate {
+	fn interpolate(&self, other: &Self, t: Real) -> Self;
+}
+
+impl Interpolate for Real {
+	fn interpolate(&self, other: &Self, t: Real) -> Self {
+		self + (other - self) * t
+	}
+}
+
+impl Interpolate for Vector3 {
+	fn interpolate(&self, other: &Self, t: Real) -> Self {
+		self.lerp(other, t)
+	}
+}
+
+impl Interpolate for Quaternion {
+	fn interpolate(&self, other: &Self, t: Real) -> Self {
+		self.slerp(other, t)
+	}
+}
+
+impl Interpolate for Transform {
+	fn interpolate(&self, other: &Self, t: Real) -> Self {
+		self.lerp(other, t)
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Snapshot<T> {
+	/// Seconds on the sender's clock at which the state was captured
+	pub time: f64,
+	pub state: T,
+}
+
+/// Buffers the snapshots received for a remote entity and plays them back slightly in the past.
+///
+/// Rendering a short delay behind the newest snapshot almost always leaves a snapshot on either
+/// side to blend between, so movement stays smooth despite jitter and lost packets.
+/// When snapshots stop arriving, the last movement is extrapolated for a short while.
+pub struct SnapshotBuffer<T> {
+	/// Seconds behind the sender's clock that snapshots play back, usually a few send intervals
+	pub delay: f64,
+
+	/// The most seconds to extrapolate past the newest snapshot
+	pub max_extrapolation: f64,
+
+	/// The most snapshots kept, which only needs to cover the delay
+	pub capacity: usize,
+
+	snapshots: VecDeque<Snapshot<T>>,
+}
+
+impl<T> Default for SnapshotBuffer<T> {
+	fn default() -> Self {
+		Self::new(0.1)
+	}
+}
+
+impl<T> SnapshotBuffer<T> {
+	pub fn new(delay: f64) -> Self {
+		Self {
+			delay,
+			max_extrapolation: 0.25,
+			capacity: 32,
+			snapshots: VecDeque::new(),
+		}
+	}
+
+	/// Inserts a snapshot in time order. Snapshots that arrive out of order are placed where they
+	/// belong, and duplicates of a time already buffered are ignored.
+	pub fn push(&mut self, time: f64, state: T) {
+		let index = self.snapshots.partition_point(|snapshot| snapshot.time < time);
+		if self.snapshots.get(index).is_some_and(|snapshot| snapshot.time == time) {
+			return;
+		}
+		self.snapshots.insert(index, Snapshot { time, state });
+		while self.snapshots.len() > self.capacity.max(2) {
+			self.snapshots.pop_front();
+		}
+	}
+
+	pub fn latest(&self) -> Option<&Snapshot<T>> {
+		self.snapshots.back()
+	}
+
+	pub fn len(&self) -> usize {
+		self.snapshots.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.snapshots.is_empty()
+	}
+
+	pub fn clear(&mut self) {
+		self.snapshots.clear();
+	}
+}
+
+impl<T: Interpolate + Clone> SnapshotBuffer<T> {
+	/// The state to show at `time` on the sender's clock, played back `delay` seconds behind it.
+	/// Snapshots too old to be needed again are dropped.
+	pub fn sample(&mut self, time: f64) -> Option<T> {
+		let playback = time - self.delay;
+		let after = self.snapshots.partition_point(|snapshot| snapshot.time <= playback);
+		// Keep the snapshot just before the playback time, since it is still being blended from,
+		// and the newest two, which extrapolation continues along
+		let stale = after.saturating_sub(1).min(self.snapshots.len().saturating_sub(2));
+		self.snapshots.drain(..stale);
+		let after = after - stale;
+
+		match (after.checked_sub(1).and_then(|before| self.snapshots.get(before)), self.snapshots.get(after)) {
+			(Some(before), Some(after)) => Some(before.state.interpolate(&after.state, fraction(before.time, after.time, playback))),
+			(Some(_), None) => self.extrapolate(playback),
+			(None, Some(first)) => Some(first.state.clone()),
+			(None, None) => None,
+		}
+	}
+
+	/// Continues along the two newest snapshots, holding still once `max_extrapolation` runs out
+	fn extrapolate(&self, playback: f64) -> Option<T> {
+		let latest = self.snapshots.back()?;
+		let Some(previous) = self.snapshots.iter().rev().nth(1) else {
+			return Some(latest.state.clone());
+		};
+		let playback = playback.min(latest.time + self.max_extrapolation);
+		Some(previous.state.interpolate(&latest.state, fraction(previous.time, latest.time, playback)))
+	}
+}
+
+fn fraction(start: f64, end: f64, time: f64) -> Real {
+	if end <= start { 1.0 } else { ((time - start) / (end - start)) as Real }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn buffer() -> SnapshotBuffer<Real> {
+		let mut buffer = SnapshotBuffer::new(0.1);
+		buffer.push(0.0, 0.0);
+		buffer.push(0.2, 2.0);
+		buffer.push(0.1, 1.0);
+		buffer.push(0.1, 5.0);
+		buffer
+	}
+
+	fn assert_close(actual: Option<Real>, expected: Real) {
+		let actual = actual.unwrap_or(Real::NAN);
+		assert!((actual - expected).abs() < 1e-4, "left: {actual} not close to right: {expected}");
+	}
+
+	#[test]
+	pub fn interpolates_behind_the_newest_snapshot() {
+		let mut buffer = buffer();
+		assert_eq!(buffer.len(), 3);
+		assert_close(buffer.sample(0.05), 0.0);
+		assert_close(buffer.sample(0.15), 0.5);
+		assert_close(buffer.sample(0.25), 1.5);
+		// The first snapshot is behind the playback time and no longer needed
+		assert_eq!(buffer.len(), 2);
+	}
+
+	#[test]
+	pub fn extrapolates_for_a_limited_time() {
+		let mut buffer = buffer();
+		assert_close(buffer.sample(0.4), 3.0);
+		assert_close(buffer.sample(1.0), 4.5);
+		assert_close(buffer.sample(2.0), 4.5);
+	}
+
+	#[test]
+	pub fn interpolates_transforms() {
+		let mut buffer = SnapshotBuffer::new(0.0);
+		buffer.push(0.0, Transform::identity());
+		let end = Transform {
+			translation: Vector3::new(2.0, 0.0, 0.0),
+			..Transform::identity()
+		};
+		buffer.push(1.0, end);
+		let halfway = buffer.sample(0.5).map(|transform| transform.translation);
+		assert_eq!(halfway, Some(Vector3::new(1.0, 0.0, 0.0)));
+		assert_eq!(SnapshotBuffer::<Transform>::default().sample(0.0), None);
+	}
+}
@@ -0,0 +1,18 @@
+mod interpolation;
+mod message;
+mod prediction;
+mod session;
+#[cfg(feature = "steam")]
+mod steam;
+#[cfg(not(target_arch = "wasm32"))]
+mod udp;
+#[cfg(target_arch = "wasm32")]
+mod webrtc;
+
+#[cfg(feature = "steam")]
+pub use self::steam::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::udp::*;
+#[cfg(target_arch = "wasm32")]
+pub use self::webrtc::*;
+pub use self::{interpolation::*, message::*, prediction::*, session::*};
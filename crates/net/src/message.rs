@@ -0,0 +1,183 @@
+use crate::{Delivery, Error, PeerId, TransportEvent};
+use serde::{Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A strongly typed message, usually declared with `network_message!`.
+/// Each message is sent as its id followed by its contents.
+pub trait NetworkMessage: Serialize + DeserializeOwned + 'static {
+	/// Both ends derive the message's id from this, so it must match between them
+	const NAME: &'static str;
+	const CHANNEL: crate::Channel;
+	const DELIVERY: Delivery;
+	const ID: u32 = message_id(Self::NAME);
+}
+
+/// Declares a serde type as a network message, optionally with its channel and delivery.
+/// Unreliable delivery on channel 0 is the default, as in
+/// `network_message!(ChatMessage, channel = 1, delivery = Reliable)`.
+#[macro_export]
+macro_rules! network_message {
+	($message:ident) => {
+		$crate::network_message!($message, channel = 0, delivery = Unreliable);
+	};
+	($message:ident, channel = $channel:expr) => {
+		$crate::network_message!($message, channel = $channel, delivery = Unreliable);
+	};
+	($message:ident, delivery = $delivery:ident) => {
+		$crate::network_message!($message, channel = 0, delivery = $delivery);
+	};
+	($message:ident, channel = $channel:expr, delivery = $delivery:ident) => {
+		impl $crate::NetworkMessage for $message {
+			const NAME: &'static str = stringify!($message);
+			const CHANNEL: $crate::Channel = $channel;
+			const DELIVERY: $crate::Delivery = $crate::Delivery::$delivery;
+		}
+	};
+}
+
+/// A 32-bit FNV-1a hash of the message's name
+pub const fn message_id(name: &str) -> u32 {
+	let bytes = name.as_bytes();
+	let mut hash: u32 = 0x811c_9dc5;
+	let mut index = 0;
+	while index < bytes.len() {
+		hash ^= bytes[index] as u32;
+		hash = hash.wrapping_mul(0x0100_0193);
+		index += 1;
+	}
+	hash
+}
+
+pub fn encode_message<M: NetworkMessage>(message: &M) -> Result<Vec<u8>> {
+	let contents = ron::to_string(message).map_err(|error| Error::EncodeMessage(error, M::NAME))?;
+	let mut packet = Vec::with_capacity(4 + contents.len());
+	packet.extend_from_slice(&M::ID.to_le_bytes());
+	packet.extend_from_slice(contents.as_bytes());
+	Ok(packet)
+}
+
+/// The message in a packet, or `None` if the packet holds a different message
+pub fn decode_message<M: NetworkMessage>(packet: &[u8]) -> Result<Option<M>> {
+	match split_packet(packet) {
+		Some((id, contents)) if id == M::ID => ron::de::from_bytes(contents).map(Some).map_err(|error| Error::DecodeMessage(error, M::NAME)),
+		_ => Ok(None),
+	}
+}
+
+fn split_packet(packet: &[u8]) -> Option<(u32, &[u8])> {
+	let (id, contents) = packet.split_at_checked(4)?;
+	Some((u32::from_le_bytes(id.try_into().ok()?), contents))
+}
+
+type Handler<C> = Box<dyn FnMut(&mut C, PeerId, &[u8]) -> Result<()>>;
+
+/// Routes received messages to the handler registered for their type,
+/// such as a system taking the app's resources as its context
+pub struct MessageHandlers<C> {
+	handlers: HashMap<u32, (&'static str, Handler<C>)>,
+}
+
+impl<C> Default for MessageHandlers<C> {
+	fn default() -> Self {
+		Self { handlers: HashMap::new() }
+	}
+}
+
+impl<C> MessageHandlers<C> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Handles every message of type `M`, replacing any handler registered for it before
+	pub fn on<M: NetworkMessage>(&mut self, mut handler: impl FnMut(&mut C, PeerId, M) + 'static) -> Result<()> {
+		if let Some((name, _)) = self.handlers.get(&M::ID).filter(|(name, _)| *name != M::NAME) {
+			return Err(Error::DuplicateMessageId(name, M::NAME));
+		}
+		let decode = move |context: &mut C, peer: PeerId, contents: &[u8]| {
+			let message = ron::de::from_bytes::<M>(contents).map_err(|error| Error::DecodeMessage(error, M::NAME))?;
+			handler(context, peer, message);
+			Ok(())
+		};
+		self.handlers.insert(M::ID, (M::NAME, Box::new(decode)));
+		Ok(())
+	}
+
+	pub fn handles<M: NetworkMessage>(&self) -> bool {
+		self.handlers.contains_key(&M::ID)
+	}
+
+	/// Passes a received message to its handler. Returns false for events that aren't messages,
+	/// such as peers connecting, so the caller can handle those itself.
+	pub fn dispatch(&mut self, context: &mut C, event: &TransportEvent) -> Result<bool> {
+		let TransportEvent::Received(peer, packet) = event else {
+			return Ok(false);
+		};
+		let (id, contents) = split_packet(packet).ok_or(Error::MalformedMessage(*peer))?;
+		let (_, handler) = self.handlers.get_mut(&id).ok_or(Error::UnknownMessage(id))?;
+		handler(context, *peer, contents)?;
+		Ok(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde::Deserialize;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	pub struct PlayerMoved {
+		x: f32,
+		y: f32,
+	}
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	pub struct Chat(String);
+
+	network_message!(PlayerMoved);
+	network_message!(Chat, channel = 1, delivery = Reliable);
+
+	#[test]
+	pub fn declares_messages() {
+		assert_eq!(PlayerMoved::NAME, "PlayerMoved");
+		assert_eq!((PlayerMoved::CHANNEL, PlayerMoved::DELIVERY), (0, Delivery::Unreliable));
+		assert_eq!((Chat::CHANNEL, Chat::DELIVERY), (1, Delivery::Reliable));
+		assert_eq!(message_id(""), 0x811c_9dc5);
+		assert_ne!(PlayerMoved::ID, Chat::ID);
+	}
+
+	#[test]
+	pub fn round_trip() -> Result<()> {
+		let packet = encode_message(&Chat("gg".to_string()))?;
+		assert_eq!(decode_message::<Chat>(&packet)?, Some(Chat("gg".to_string())));
+		assert_eq!(decode_message::<PlayerMoved>(&packet)?, None);
+		Ok(())
+	}
+
+	#[test]
+	pub fn dispatches_to_handlers() -> Result<()> {
+		let mut handlers = MessageHandlers::<Vec<String>>::new();
+		handlers.on(|log: &mut Vec<String>, peer, message: Chat| log.push(format!("{peer}: {}", message.0)))?;
+		handlers.on(|log: &mut Vec<String>, _, message: PlayerMoved| log.push(format!("moved to {}, {}", message.x, message.y)))?;
+		assert!(handlers.handles::<Chat>());
+
+		let mut log = Vec::new();
+		let chat = TransportEvent::Received(3, encode_message(&Chat("hi".to_string()))?);
+		let moved = TransportEvent::Received(3, encode_message(&PlayerMoved { x: 1.0, y: 2.0 })?);
+		assert!(handlers.dispatch(&mut log, &chat)?);
+		assert!(handlers.dispatch(&mut log, &moved)?);
+		assert!(!handlers.dispatch(&mut log, &TransportEvent::Connected(4))?);
+		assert_eq!(log, ["3: hi", "moved to 1, 2"]);
+
+		assert!(matches!(
+			handlers.dispatch(&mut log, &TransportEvent::Received(3, vec![1])),
+			Err(Error::MalformedMessage(3))
+		));
+		assert!(matches!(
+			handlers.dispatch(&mut log, &TransportEvent::Received(3, vec![0; 4])),
+			Err(Error::UnknownMessage(0))
+		));
+		Ok(())
+	}
+}
@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+
+/// Identifies an input sent to the server, which echoes back the newest one it has applied
+pub type InputSequence = u32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingInput<I> {
+	pub sequence: InputSequence,
+	pub input: I,
+}
+
+/// Client-side prediction for an entity the local player controls.
+///
+/// Inputs are applied locally as soon as they are made, so the player sees no latency, and kept
+/// until the server acknowledges them. When an authoritative state arrives, the inputs the
+/// server hadn't applied are replayed on top of it, correcting any misprediction.
+pub struct Prediction<S, I> {
+	state: S,
+	pending: VecDeque<PendingInput<I>>,
+	next_sequence: InputSequence,
+
+	/// The most unacknowledged inputs kept, so a long outage can't grow the buffer without bound
+	pub capacity: usize,
+}
+
+impl<S: Clone, I> Prediction<S, I> {
+	pub fn new(state: S) -> Self {
+		Self {
+			state,
+			pending: VecDeque::new(),
+			next_sequence: 0,
+			capacity: 256,
+		}
+	}
+
+	/// The predicted state, including every input made so far
+	pub fn state(&self) -> &S {
+		&self.state
+	}
+
+	/// Inputs sent but not yet acknowledged by the server
+	pub fn pending(&self) -> impl Iterator<Item = &PendingInput<I>> {
+		self.pending.iter()
+	}
+
+	/// Applies an input locally and returns the sequence to send it to the server with
+	pub fn apply(&mut self, input: I, simulate: impl FnOnce(&S, &I) -> S) -> InputSequence {
+		let sequence = self.next_sequence;
+		self.next_sequence = self.next_sequence.wrapping_add(1);
+		self.state = simulate(&self.state, &input);
+		self.pending.push_back(PendingInput { sequence, input });
+		while self.pending.len() > self.capacity.max(1) {
+			self.pending.pop_front();
+		}
+		sequence
+	}
+
+	/// Replaces the prediction with the server's state after every input up to `acknowledged`,
+	/// then replays the newer inputs on top. Returns the predicted state from before, which can
+	/// be blended toward the new one to hide small corrections.
+	pub fn reconcile(&mut self, server_state: S, acknowledged: InputSequence, mut simulate: impl FnMut(&S, &I) -> S) -> S {
+		while self.pending.front().is_some_and(|pending| !is_newer(pending.sequence, acknowledged)) {
+			self.pending.pop_front();
+		}
+		let state = self.pending.iter().fold(server_state, |state, pending| simulate(&state, &pending.input));
+		std::mem::replace(&mut self.state, state)
+	}
+}
+
+/// Whether `sequence` comes after `other`, allowing for sequences wrapping around
+fn is_newer(sequence: InputSequence, other: InputSequence) -> bool {
+	sequence != other && sequence.wrapping_sub(other) < InputSequence::MAX / 2
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn walk(position: &i32, step: &i32) -> i32 {
+		position + step
+	}
+
+	#[test]
+	pub fn reconcile_replays_unacknowledged_inputs() {
+		let mut prediction = Prediction::new(0);
+		let first = prediction.apply(1, walk);
+		prediction.apply(2, walk);
+		prediction.apply(3, walk);
+		assert_eq!(*prediction.state(), 6);
+
+		// The server applied the first input but was blocked, leaving the player at 0 instead of 1
+		let mispredicted = prediction.reconcile(0, first, walk);
+		assert_eq!(mispredicted, 6);
+		assert_eq!(*prediction.state(), 5);
+		assert_eq!(prediction.pending().map(|pending| pending.input).collect::<Vec<_>>(), [2, 3]);
+	}
+
+	#[test]
+	pub fn sequences_wrap() {
+		assert!(is_newer(0, InputSequence::MAX));
+		assert!(!is_newer(InputSequence::MAX, 0));
+		assert!(!is_newer(5, 5));
+
+		let mut prediction = Prediction::new(0);
+		prediction.next_sequence = InputSequence::MAX;
+		let last = prediction.apply(1, walk);
+		prediction.apply(1, walk);
+		prediction.reconcile(1, last, walk);
+		assert_eq!(prediction.pending().count(), 1);
+		assert_eq!(*prediction.state(), 2);
+	}
+}
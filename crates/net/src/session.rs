@@ -0,0 +1,219 @@
+use crate::{NetworkMessage, encode_message};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to bind a UDP socket to address: {1}")]
+	BindSocket(#[source] std::io::Error, String),
+
+	#[error("Failed to decode a '{1}' message!")]
+	DecodeMessage(#[source] ron::error::SpannedError, &'static str),
+
+	#[error("Messages '{0}' and '{1}' have the same id. Rename one of them.")]
+	DuplicateMessageId(&'static str, &'static str),
+
+	#[error("Failed to encode a '{1}' message!")]
+	EncodeMessage(#[source] ron::Error, &'static str),
+
+	#[error("Received a packet from peer {0} that is too short to be a message.")]
+	MalformedMessage(PeerId),
+
+	#[error("Failed to receive from the UDP socket!")]
+	ReceivePacket(#[source] std::io::Error),
+
+	#[error("Failed to send a message to peer {1}: {0}")]
+	SendMessage(String, PeerId),
+
+	#[error("Failed to send a packet to peer {1}")]
+	SendPacket(#[source] std::io::Error, PeerId),
+
+	#[error("Received a message with id {0}, which has no registered handler.")]
+	UnknownMessage(u32),
+
+	#[error("Peer {0} is not connected to the session.")]
+	UnknownPeer(PeerId),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Separates independent streams of messages, so a lost reliable message doesn't hold up others.
+/// Transports without channels send everything on one.
+pub type Channel = u8;
+
+/// Identifies a peer within a single transport. Ids are never reused while the transport is alive.
+pub type PeerId = u64;
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Delivery {
+	/// May be dropped or arrive out of order, which suits state that is resent every tick
+	#[default]
+	Unreliable,
+
+	/// Arrives exactly once and in order, where the transport supports it
+	Reliable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportEvent {
+	Connected(PeerId),
+	Disconnected(PeerId),
+	Received(PeerId, Vec<u8>),
+}
+
+/// Moves packets between peers over a particular network or platform service.
+///
+/// Transports don't know about lobbies or matchmaking. Whatever finds the other players
+/// hands their addresses, data channels, or platform ids to the transport, which makes the
+/// same replication code work no matter where the game is distributed.
+pub trait Transport {
+	fn send(&mut self, peer: PeerId, delivery: Delivery, payload: &[u8]) -> Result<()>;
+
+	fn send_on_channel(&mut self, peer: PeerId, _channel: Channel, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		self.send(peer, delivery, payload)
+	}
+
+	/// Everything that happened since the last poll. Never blocks.
+	fn poll(&mut self) -> Result<Vec<TransportEvent>>;
+
+	fn disconnect(&mut self, peer: PeerId);
+}
+
+/// The peers of one networked game, independent of the transport carrying their packets
+pub struct Session {
+	transport: Box<dyn Transport>,
+	peers: BTreeSet<PeerId>,
+}
+
+impl Session {
+	pub fn new(transport: impl Transport + 'static) -> Self {
+		Self {
+			transport: Box::new(transport),
+			peers: BTreeSet::new(),
+		}
+	}
+
+	pub fn transport(&self) -> &dyn Transport {
+		self.transport.as_ref()
+	}
+
+	/// The transport, for adding peers the lobby found
+	pub fn transport_mut(&mut self) -> &mut dyn Transport {
+		self.transport.as_mut()
+	}
+
+	pub fn peers(&self) -> impl Iterator<Item = PeerId> + '_ {
+		self.peers.iter().copied()
+	}
+
+	pub fn is_connected(&self, peer: PeerId) -> bool {
+		self.peers.contains(&peer)
+	}
+
+	pub fn send(&mut self, peer: PeerId, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		if !self.is_connected(peer) {
+			return Err(Error::UnknownPeer(peer));
+		}
+		self.transport.send(peer, delivery, payload)
+	}
+
+	pub fn send_on_channel(&mut self, peer: PeerId, channel: Channel, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		if !self.is_connected(peer) {
+			return Err(Error::UnknownPeer(peer));
+		}
+		self.transport.send_on_channel(peer, channel, delivery, payload)
+	}
+
+	/// Sends to every connected peer, stopping at the first failure
+	pub fn broadcast(&mut self, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		self.peers.iter().try_for_each(|peer| self.transport.send(*peer, delivery, payload))
+	}
+
+	/// Sends a typed message with its declared channel and delivery
+	pub fn send_message<M: NetworkMessage>(&mut self, peer: PeerId, message: &M) -> Result<()> {
+		let packet = encode_message(message)?;
+		self.send_on_channel(peer, M::CHANNEL, M::DELIVERY, &packet)
+	}
+
+	pub fn broadcast_message<M: NetworkMessage>(&mut self, message: &M) -> Result<()> {
+		let packet = encode_message(message)?;
+		self.peers
+			.iter()
+			.try_for_each(|peer| self.transport.send_on_channel(*peer, M::CHANNEL, M::DELIVERY, &packet))
+	}
+
+	pub fn disconnect(&mut self, peer: PeerId) {
+		if self.peers.remove(&peer) {
+			self.transport.disconnect(peer);
+		}
+	}
+
+	/// Polls the transport, keeping track of which peers are connected.
+	/// Call this once per frame and handle the events it returns.
+	pub fn poll(&mut self) -> Result<Vec<TransportEvent>> {
+		let events = self.transport.poll()?;
+		for event in events.iter() {
+			match event {
+				TransportEvent::Connected(peer) => {
+					self.peers.insert(*peer);
+				},
+				TransportEvent::Disconnected(peer) => {
+					self.peers.remove(peer);
+				},
+				TransportEvent::Received(..) => {},
+			}
+		}
+		Ok(events)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{cell::RefCell, rc::Rc};
+
+	type Sent = Rc<RefCell<Vec<(PeerId, Vec<u8>)>>>;
+
+	/// Records sends and replays queued events, standing in for a real network
+	#[derive(Default, Clone)]
+	struct Loopback {
+		sent: Sent,
+		events: Rc<RefCell<Vec<TransportEvent>>>,
+	}
+
+	impl Transport for Loopback {
+		fn send(&mut self, peer: PeerId, _delivery: Delivery, payload: &[u8]) -> Result<()> {
+			self.sent.borrow_mut().push((peer, payload.to_vec()));
+			Ok(())
+		}
+
+		fn poll(&mut self) -> Result<Vec<TransportEvent>> {
+			Ok(self.events.borrow_mut().drain(..).collect())
+		}
+
+		fn disconnect(&mut self, peer: PeerId) {
+			self.events.borrow_mut().push(TransportEvent::Disconnected(peer));
+		}
+	}
+
+	#[test]
+	pub fn tracks_peers() -> Result<()> {
+		let loopback = Loopback::default();
+		let mut session = Session::new(loopback.clone());
+		loopback.events.borrow_mut().extend([TransportEvent::Connected(1), TransportEvent::Connected(2)]);
+		assert!(matches!(session.send(1, Delivery::Reliable, b"early"), Err(Error::UnknownPeer(1))));
+
+		session.poll()?;
+		assert_eq!(session.peers().collect::<Vec<_>>(), [1, 2]);
+		session.broadcast(Delivery::Unreliable, b"state")?;
+		assert_eq!(loopback.sent.borrow().len(), 2);
+
+		session.disconnect(1);
+		assert!(!session.is_connected(1));
+		assert!(matches!(session.send(1, Delivery::Reliable, b"late"), Err(Error::UnknownPeer(1))));
+		loopback.events.borrow_mut().push(TransportEvent::Disconnected(2));
+		session.poll()?;
+		assert_eq!(session.peers().count(), 0);
+		Ok(())
+	}
+}
@@ -0,0 +1,174 @@
+use crate::{Channel, Delivery, Error, PeerId, Transport, TransportEvent};
+use std::collections::BTreeMap;
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A Steam user's 64-bit id
+pub type SteamId = u64;
+
+/// The calls the Steam transport makes into `ISteamNetworkingMessages`.
+///
+/// Implement this over the Steamworks bindings the game links, keeping the SDK out of other
+/// platforms' builds. It should accept session requests from the lobby's members and report
+/// failed sessions with [`SteamTransport::session_failed`].
+pub trait SteamMessages {
+	fn send_message_to_user(&mut self, user: SteamId, payload: &[u8], send_flags: i32, channel: i32) -> std::result::Result<(), String>;
+
+	fn receive_messages_on_channel(&mut self, channel: i32) -> Vec<(SteamId, Vec<u8>)>;
+
+	fn close_session_with_user(&mut self, user: SteamId);
+}
+
+/// Sends messages through Steam Networking Sockets, which relays them through Valve's network
+/// so players can connect without exposing their addresses or forwarding ports
+pub struct SteamTransport<S: SteamMessages> {
+	messages: S,
+	users: BTreeMap<PeerId, SteamId>,
+	next_peer: PeerId,
+	events: Vec<TransportEvent>,
+
+	/// The channels polled for incoming messages, which should include every channel used
+	pub channels: Vec<Channel>,
+}
+
+impl<S: SteamMessages> SteamTransport<S> {
+	/// `k_nSteamNetworkingSend_Unreliable`
+	pub const SEND_UNRELIABLE: i32 = 0;
+
+	/// `k_nSteamNetworkingSend_Reliable`
+	pub const SEND_RELIABLE: i32 = 8;
+
+	pub fn new(messages: S) -> Self {
+		Self {
+			messages,
+			users: BTreeMap::new(),
+			next_peer: 0,
+			events: Vec::new(),
+			channels: vec![0],
+		}
+	}
+
+	pub fn messages_mut(&mut self) -> &mut S {
+		&mut self.messages
+	}
+
+	/// Adds a user found in a Steam lobby. Messages to them open the session.
+	pub fn connect(&mut self, user: SteamId) -> PeerId {
+		if let Some(peer) = self.peer(user) {
+			return peer;
+		}
+		let id = self.next_peer;
+		self.next_peer += 1;
+		self.users.insert(id, user);
+		self.events.push(TransportEvent::Connected(id));
+		id
+	}
+
+	pub fn peer(&self, user: SteamId) -> Option<PeerId> {
+		self.users.iter().find(|(_, id)| **id == user).map(|(peer, _)| *peer)
+	}
+
+	pub fn user(&self, peer: PeerId) -> Option<SteamId> {
+		self.users.get(&peer).copied()
+	}
+
+	/// Reports that Steam gave up on the session with a user
+	pub fn session_failed(&mut self, user: SteamId) {
+		if let Some(peer) = self.peer(user) {
+			self.users.remove(&peer);
+			self.events.push(TransportEvent::Disconnected(peer));
+		}
+	}
+}
+
+impl<S: SteamMessages> Transport for SteamTransport<S> {
+	fn send(&mut self, peer: PeerId, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		self.send_on_channel(peer, 0, delivery, payload)
+	}
+
+	fn send_on_channel(&mut self, peer: PeerId, channel: Channel, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		let user = self.user(peer).ok_or(Error::UnknownPeer(peer))?;
+		let flags = match delivery {
+			Delivery::Unreliable => Self::SEND_UNRELIABLE,
+			Delivery::Reliable => Self::SEND_RELIABLE,
+		};
+		self.messages
+			.send_message_to_user(user, payload, flags, i32::from(channel))
+			.map_err(|error| Error::SendMessage(error, peer))
+	}
+
+	fn poll(&mut self) -> Result<Vec<TransportEvent>> {
+		for channel in self.channels.clone() {
+			for (user, payload) in self.messages.receive_messages_on_channel(i32::from(channel)) {
+				let peer = self.connect(user);
+				self.events.push(TransportEvent::Received(peer, payload));
+			}
+		}
+		Ok(std::mem::take(&mut self.events))
+	}
+
+	fn disconnect(&mut self, peer: PeerId) {
+		if let Some(user) = self.users.remove(&peer) {
+			self.messages.close_session_with_user(user);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Default)]
+	struct FakeSteam {
+		sent: Vec<(SteamId, i32, i32)>,
+		inbox: Vec<(SteamId, Vec<u8>)>,
+		closed: Vec<SteamId>,
+	}
+
+	impl SteamMessages for FakeSteam {
+		fn send_message_to_user(&mut self, user: SteamId, _payload: &[u8], send_flags: i32, channel: i32) -> std::result::Result<(), String> {
+			self.sent.push((user, send_flags, channel));
+			Ok(())
+		}
+
+		fn receive_messages_on_channel(&mut self, _channel: i32) -> Vec<(SteamId, Vec<u8>)> {
+			std::mem::take(&mut self.inbox)
+		}
+
+		fn close_session_with_user(&mut self, user: SteamId) {
+			self.closed.push(user);
+		}
+	}
+
+	#[test]
+	pub fn maps_users_to_peers() -> Result<()> {
+		let mut transport = SteamTransport::new(FakeSteam::default());
+		let friend = transport.connect(76_561_197_960_287_930);
+		transport.messages_mut().inbox.push((76_561_197_960_287_931, b"hi".to_vec()));
+		let events = transport.poll()?;
+		let stranger = transport.peer(76_561_197_960_287_931).unwrap();
+		assert_eq!(
+			events,
+			[
+				TransportEvent::Connected(friend),
+				TransportEvent::Connected(stranger),
+				TransportEvent::Received(stranger, b"hi".to_vec())
+			]
+		);
+
+		transport.send(friend, Delivery::Reliable, b"state")?;
+		transport.send_on_channel(friend, 2, Delivery::Unreliable, b"state")?;
+		assert_eq!(
+			transport.messages_mut().sent,
+			[
+				(76_561_197_960_287_930, SteamTransport::<FakeSteam>::SEND_RELIABLE, 0),
+				(76_561_197_960_287_930, SteamTransport::<FakeSteam>::SEND_UNRELIABLE, 2)
+			]
+		);
+		transport.disconnect(friend);
+		assert_eq!(transport.messages_mut().closed, [76_561_197_960_287_930]);
+		transport.session_failed(76_561_197_960_287_931);
+		assert_eq!(transport.poll()?, [TransportEvent::Disconnected(stranger)]);
+		Ok(())
+	}
+}
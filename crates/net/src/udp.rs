@@ -0,0 +1,174 @@
+use crate::{Delivery, Error, PeerId, Transport, TransportEvent};
+use std::{
+	collections::BTreeMap,
+	io::ErrorKind,
+	net::{SocketAddr, UdpSocket},
+	time::{Duration, Instant},
+};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+struct UdpPeer {
+	address: SocketAddr,
+	last_received: Instant,
+}
+
+/// Sends packets straight over UDP, for dedicated servers and LAN games.
+///
+/// Raw UDP has no delivery guarantees, so reliable messages are sent like unreliable ones.
+/// Any address that sends a packet becomes a peer, and quiet peers are dropped after the timeout.
+pub struct UdpTransport {
+	socket: UdpSocket,
+	peers: BTreeMap<PeerId, UdpPeer>,
+	next_peer: PeerId,
+	events: Vec<TransportEvent>,
+	buffer: Vec<u8>,
+	pub timeout: Duration,
+}
+
+impl UdpTransport {
+	/// Packets larger than this may be fragmented or dropped along the way
+	pub const MAX_PACKET_SIZE: usize = 1200;
+
+	pub fn bind(address: &str) -> Result<Self> {
+		let socket = UdpSocket::bind(address).map_err(|error| Error::BindSocket(error, address.to_string()))?;
+		socket.set_nonblocking(true).map_err(|error| Error::BindSocket(error, address.to_string()))?;
+		Ok(Self {
+			socket,
+			peers: BTreeMap::new(),
+			next_peer: 0,
+			events: Vec::new(),
+			buffer: vec![0; Self::MAX_PACKET_SIZE],
+			timeout: Duration::from_secs(10),
+		})
+	}
+
+	pub fn local_address(&self) -> Option<SocketAddr> {
+		self.socket.local_addr().ok()
+	}
+
+	/// Adds a peer at an address found by a lobby or typed in by the player.
+	/// UDP has no handshake, so the peer counts as connected right away.
+	pub fn connect(&mut self, address: SocketAddr) -> PeerId {
+		if let Some(peer) = self.peer_at(address) {
+			return peer;
+		}
+		self.add_peer(address)
+	}
+
+	pub fn address(&self, peer: PeerId) -> Option<SocketAddr> {
+		self.peers.get(&peer).map(|peer| peer.address)
+	}
+
+	fn peer_at(&self, address: SocketAddr) -> Option<PeerId> {
+		self.peers.iter().find(|(_, peer)| peer.address == address).map(|(id, _)| *id)
+	}
+
+	fn add_peer(&mut self, address: SocketAddr) -> PeerId {
+		let id = self.next_peer;
+		self.next_peer += 1;
+		self.peers.insert(
+			id,
+			UdpPeer {
+				address,
+				last_received: Instant::now(),
+			},
+		);
+		self.events.push(TransportEvent::Connected(id));
+		id
+	}
+}
+
+impl Transport for UdpTransport {
+	fn send(&mut self, peer: PeerId, _delivery: Delivery, payload: &[u8]) -> Result<()> {
+		let address = self.address(peer).ok_or(Error::UnknownPeer(peer))?;
+		self.socket.send_to(payload, address).map_err(|error| Error::SendPacket(error, peer))?;
+		Ok(())
+	}
+
+	fn poll(&mut self) -> Result<Vec<TransportEvent>> {
+		loop {
+			let (length, address) = match self.socket.recv_from(&mut self.buffer) {
+				Ok(received) => received,
+				Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+				// Windows reports an ICMP port unreachable from an earlier send here,
+				// which only means that peer is gone
+				Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+				Err(error) => return Err(Error::ReceivePacket(error)),
+			};
+			let peer = self.peer_at(address).unwrap_or_else(|| self.add_peer(address));
+			if let Some(peer) = self.peers.get_mut(&peer) {
+				peer.last_received = Instant::now();
+			}
+			self.events.push(TransportEvent::Received(peer, self.buffer[..length].to_vec()));
+		}
+
+		let timeout = self.timeout;
+		let timed_out = self
+			.peers
+			.iter()
+			.filter(|(_, peer)| peer.last_received.elapsed() > timeout)
+			.map(|(id, _)| *id)
+			.collect::<Vec<_>>();
+		for peer in timed_out {
+			self.peers.remove(&peer);
+			self.events.push(TransportEvent::Disconnected(peer));
+		}
+		Ok(std::mem::take(&mut self.events))
+	}
+
+	fn disconnect(&mut self, peer: PeerId) {
+		self.peers.remove(&peer);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Session;
+	use std::thread;
+
+	/// Polls until a packet arrives, since loopback delivery isn't instant
+	fn receive(session: &mut Session) -> Result<(PeerId, Vec<u8>)> {
+		for _ in 0..200 {
+			for event in session.poll()? {
+				if let TransportEvent::Received(peer, payload) = event {
+					return Ok((peer, payload));
+				}
+			}
+			thread::sleep(Duration::from_millis(5));
+		}
+		panic!("No packet arrived");
+	}
+
+	#[test]
+	pub fn exchanges_packets() -> Result<()> {
+		let server = UdpTransport::bind("127.0.0.1:0")?;
+		let server_address = server.local_address().unwrap();
+		let mut server = Session::new(server);
+
+		let mut client = UdpTransport::bind("127.0.0.1:0")?;
+		client.connect(server_address);
+		let mut client = Session::new(client);
+		client.poll()?;
+		client.broadcast(Delivery::Reliable, b"hello")?;
+
+		let (peer, payload) = receive(&mut server)?;
+		assert_eq!(payload, b"hello");
+		assert!(server.is_connected(peer));
+		server.send(peer, Delivery::Unreliable, b"welcome")?;
+		assert_eq!(receive(&mut client)?.1, b"welcome");
+		Ok(())
+	}
+
+	#[test]
+	pub fn quiet_peers_time_out() -> Result<()> {
+		let mut transport = UdpTransport::bind("127.0.0.1:0")?;
+		transport.timeout = Duration::ZERO;
+		let peer = transport.connect("127.0.0.1:9".parse().unwrap());
+		thread::sleep(Duration::from_millis(1));
+		assert_eq!(transport.poll()?, [TransportEvent::Connected(peer), TransportEvent::Disconnected(peer)]);
+		assert_eq!(transport.address(peer), None);
+		Ok(())
+	}
+}
@@ -0,0 +1,130 @@
+use crate::{Delivery, Error, PeerId, Transport, TransportEvent};
+use js_sys::Uint8Array;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use wasm_bindgen::{JsCast, closure::Closure};
+use web_sys::{MessageEvent, RtcDataChannel, RtcDataChannelState, RtcDataChannelType};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+type Events = Rc<RefCell<Vec<TransportEvent>>>;
+
+/// A data channel with the callbacks that feed its events to the transport,
+/// which are detached when it is dropped so the browser never calls into freed closures
+struct DataChannel {
+	channel: RtcDataChannel,
+	_on_message: Closure<dyn FnMut(MessageEvent)>,
+	_on_open: Option<Closure<dyn FnMut()>>,
+	_on_close: Option<Closure<dyn FnMut()>>,
+}
+
+impl DataChannel {
+	/// Only the reliable channel reports the peer connecting and disconnecting
+	fn new(channel: RtcDataChannel, peer: PeerId, events: &Events, reports_connection: bool) -> Self {
+		channel.set_binary_type(RtcDataChannelType::Arraybuffer);
+
+		let message_events = events.clone();
+		let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+			let payload = Uint8Array::new(&event.data()).to_vec();
+			message_events.borrow_mut().push(TransportEvent::Received(peer, payload));
+		});
+		channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+		let (mut on_open, mut on_close) = (None, None);
+		if reports_connection {
+			let connection_event = |event: TransportEvent| {
+				let events = events.clone();
+				Closure::<dyn FnMut()>::new(move || events.borrow_mut().push(event.clone()))
+			};
+			let open = connection_event(TransportEvent::Connected(peer));
+			let close = connection_event(TransportEvent::Disconnected(peer));
+			channel.set_onopen(Some(open.as_ref().unchecked_ref()));
+			channel.set_onclose(Some(close.as_ref().unchecked_ref()));
+			if channel.ready_state() == RtcDataChannelState::Open {
+				events.borrow_mut().push(TransportEvent::Connected(peer));
+			}
+			(on_open, on_close) = (Some(open), Some(close));
+		}
+
+		Self {
+			channel,
+			_on_message: on_message,
+			_on_open: on_open,
+			_on_close: on_close,
+		}
+	}
+
+	fn send(&self, peer: PeerId, payload: &[u8]) -> Result<()> {
+		self.channel
+			.send_with_u8_array(payload)
+			.map_err(|error| Error::SendMessage(format!("{:?}", error), peer))
+	}
+}
+
+impl Drop for DataChannel {
+	fn drop(&mut self) {
+		self.channel.set_onmessage(None);
+		self.channel.set_onopen(None);
+		self.channel.set_onclose(None);
+		self.channel.close();
+	}
+}
+
+struct WebRtcPeer {
+	reliable: DataChannel,
+	unreliable: Option<DataChannel>,
+}
+
+/// Sends messages over WebRTC data channels, the only peer-to-peer option in the browser.
+///
+/// Signaling is left to the lobby, which negotiates the connection and hands over its channels.
+/// A reliable channel is required, and an unreliable one (with `ordered: false` and
+/// `maxRetransmits: 0`) is used for unreliable messages when given.
+#[derive(Default)]
+pub struct WebRtcTransport {
+	peers: BTreeMap<PeerId, WebRtcPeer>,
+	next_peer: PeerId,
+	events: Events,
+}
+
+impl WebRtcTransport {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The peer connects once its reliable channel opens
+	pub fn add_peer(&mut self, reliable: RtcDataChannel, unreliable: Option<RtcDataChannel>) -> PeerId {
+		let id = self.next_peer;
+		self.next_peer += 1;
+		let peer = WebRtcPeer {
+			reliable: DataChannel::new(reliable, id, &self.events, true),
+			unreliable: unreliable.map(|channel| DataChannel::new(channel, id, &self.events, false)),
+		};
+		self.peers.insert(id, peer);
+		id
+	}
+}
+
+impl Transport for WebRtcTransport {
+	fn send(&mut self, peer: PeerId, delivery: Delivery, payload: &[u8]) -> Result<()> {
+		let channels = self.peers.get(&peer).ok_or(Error::UnknownPeer(peer))?;
+		let channel = match (delivery, channels.unreliable.as_ref()) {
+			(Delivery::Unreliable, Some(unreliable)) => unreliable,
+			_ => &channels.reliable,
+		};
+		channel.send(peer, payload)
+	}
+
+	fn poll(&mut self) -> Result<Vec<TransportEvent>> {
+		let events = std::mem::take(&mut *self.events.borrow_mut());
+		for event in events.iter() {
+			if let TransportEvent::Disconnected(peer) = event {
+				self.peers.remove(peer);
+			}
+		}
+		Ok(events)
+	}
+
+	fn disconnect(&mut self, peer: PeerId) {
+		self.peers.remove(&peer);
+	}
+}
@@ -0,0 +1,131 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use math::{Real, Vector3};
+use physics::{Aabb, ForceRegistry, Gravity, Joint, JointSolver, Particle, PhysicsWorld, Ray, Raycast, RigidBody, SoftBody};
+use std::time::Duration;
+
+const STEP: Real = 1.0 / 60.0;
+
+fn particles(count: usize) -> Vec<Particle> {
+	(0..count)
+		.map(|index| Particle {
+			velocity: Vector3::new(0.0, 10.0, index as Real * 0.001),
+			acceleration: Vector3::new(0.0, -9.81, 0.0),
+			damping: 0.99,
+			inverse_mass: 1.0,
+			..Default::default()
+		})
+		.collect()
+}
+
+fn cube(position: Vector3) -> RigidBody {
+	RigidBody {
+		position,
+		..RigidBody::cuboid(1.0, Vector3::new(0.5, 0.5, 0.5))
+	}
+}
+
+fn particle_integration(c: &mut Criterion) {
+	c.bench_function("integrating 1 million particles", |b| {
+		let mut particles = particles(1_000_000);
+		b.iter(|| {
+			for particle in particles.iter_mut() {
+				particle.add_force(Vector3::new(0.0, 0.0, 1.0));
+				particle.integrate(STEP);
+			}
+		})
+	});
+}
+
+fn body_integration(c: &mut Criterion) {
+	c.bench_function("integrating 100 thousand rigid bodies with forces", |b| {
+		let mut bodies = (0..100_000).map(|index| cube(Vector3::new(index as Real, 0.0, 0.0))).collect::<Vec<_>>();
+		let mut registry = ForceRegistry::default();
+		for index in 0..bodies.len() {
+			registry.add(
+				index,
+				Gravity {
+					gravity: Vector3::new(0.0, -9.81, 0.0),
+				},
+			);
+		}
+		b.iter(|| {
+			registry.update_forces(&mut bodies, STEP);
+			for body in bodies.iter_mut() {
+				body.add_force_at_body_point(Vector3::x_axis(), Vector3::y_axis());
+				body.integrate(STEP);
+			}
+		})
+	});
+}
+
+fn world_step(c: &mut Criterion) {
+	c.bench_function("stepping a world of 10 thousand bodies and particles", |b| {
+		let mut world = PhysicsWorld::new(Vector3::new(0.0, -9.81, 0.0));
+		for index in 0..10_000 {
+			world.add_body(cube(Vector3::new(index as Real, 0.0, 0.0)));
+		}
+		particles(10_000).into_iter().for_each(|particle| {
+			world.add_particle(particle);
+		});
+		b.iter(|| world.step(STEP))
+	});
+}
+
+fn joint_solving(c: &mut Criterion) {
+	c.bench_function("solving a chain of 100 ball joints", |b| {
+		let mut bodies = vec![RigidBody::default()];
+		let mut solver = JointSolver::default();
+		for index in 1..=100 {
+			bodies.push(RigidBody {
+				acceleration: Vector3::new(0.0, -9.81, 0.0),
+				..cube(Vector3::new(index as Real, 0.0, 0.0))
+			});
+			solver.add(Joint::ball(&bodies, index - 1, index, Vector3::new(index as Real - 0.5, 0.0, 0.0)));
+		}
+		b.iter(|| solver.step(&mut bodies, STEP))
+	});
+}
+
+fn soft_body_step(c: &mut Criterion) {
+	c.bench_function("stepping a soft body with 642 particles", |b| {
+		let mut ball = SoftBody::sphere(Vector3::zero(), 1.0, 3, 1.0, 200.0);
+		b.iter(|| ball.step(STEP / 10.0))
+	});
+}
+
+// There's no broadphase yet, so rays are tested against every box
+fn raycasting(c: &mut Criterion) {
+	c.bench_function("casting 1 thousand rays against 1 thousand boxes", |b| {
+		let boxes = (0..1_000)
+			.map(|index| {
+				let (x, z) = ((index % 32) as Real * 2.0, (index / 32) as Real * 2.0);
+				Aabb {
+					min: Vector3::new(x, 0.0, z),
+					max: Vector3::new(x + 1.0, 1.0 + (index % 7) as Real * 0.1, z + 1.0),
+				}
+			})
+			.collect::<Vec<_>>();
+		let rays = (0..1_000)
+			.map(|index| Ray::new(Vector3::new((index % 64) as Real, 5.0, (index / 16) as Real), Vector3::new(0.1, -1.0, 0.0)))
+			.collect::<Vec<_>>();
+		b.iter(|| {
+			for ray in rays.iter() {
+				black_box(boxes[..].raycast(ray, 10.0));
+			}
+		})
+	});
+}
+
+criterion_group!(
+	name = benches;
+	config = Criterion::default().measurement_time(Duration::from_secs(10));
+	targets =
+		particle_integration,
+		body_integration,
+		world_step,
+		joint_solving,
+		soft_body_step,
+		raycasting
+);
+
+criterion_main!(benches);
@@ -1,5 +1,10 @@
 use anyhow::Result;
-use ecs::{izip, system, world::World};
+use ecs::{
+	pool::{EntityPool, Pooled},
+	query::{Changed, Without},
+	system,
+	world::World,
+};
 use kiss3d::{
 	event::{Action, Key, WindowEvent},
 	light::Light,
@@ -8,10 +13,10 @@ use kiss3d::{
 	window::Window,
 };
 use math::{Real, Vector3};
-use na::{Point2, Point3, Translation3};
+use na::{Point2, Point3};
 use nalgebra as na;
-use physics::Particle;
-use std::{rc::Rc, time::Instant};
+use physics::{Aabb, Particle, WaterEventKind, WaterTrigger, WaterVolume};
+use std::{f32::consts::TAU, rc::Rc, time::Instant};
 
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone)]
 enum Shot {
@@ -23,10 +28,9 @@ enum Shot {
 	Grenade,
 }
 
-#[derive(Default, Copy, Clone)]
+#[derive(Copy, Clone)]
 struct Round {
-	pub start_time: Option<Instant>,
-	pub alive: bool,
+	pub start_time: Instant,
 }
 
 const PARTICLE_TIMEOUT_SECS: usize = 5;
@@ -36,6 +40,22 @@ struct NextShot(pub Shot);
 
 struct ShouldFire(pub bool);
 
+// A pool the rounds can splash into and float in
+struct Pool {
+	pub water: WaterVolume,
+	pub trigger: WaterTrigger,
+}
+
+struct Splash {
+	pub position: Vector3,
+	pub start_time: Instant,
+}
+
+#[derive(Default)]
+struct Splashes(pub Vec<Splash>);
+
+const SPLASH_SECS: f32 = 0.6;
+
 fn main() -> Result<()> {
 	let mut window = Window::new("Physics Engine - Ballistics Demo");
 	window.set_light(Light::StickToCamera);
@@ -45,28 +65,40 @@ fn main() -> Result<()> {
 
 	world.resources().borrow_mut().insert(NextShot(Shot::Pistol));
 	world.resources().borrow_mut().insert(ShouldFire(false));
+	world.resources().borrow_mut().insert(Splashes::default());
 
-	let entities = world.create_entities(AMMO_COUNT);
-	for entity in entities {
+	let water = WaterVolume::new(Aabb {
+		min: Vector3::new(-5.0, 0.0, 40.0),
+		max: Vector3::new(5.0, 1.0, 90.0),
+	});
+	world.resources().borrow_mut().insert(Pool {
+		water,
+		trigger: WaterTrigger::default(),
+	});
+
+	// Firing acquires a round reset to the next shot, and rounds return to the pool on timeout
+	let mut rounds = EntityPool::new(&mut world, AMMO_COUNT, |world, entity| {
+		let shot = world.resources().borrow().get::<NextShot>().map_or(Shot::Pistol, |next_shot| next_shot.0);
+		world.add_component(entity, Round { start_time: Instant::now() })?;
+		world.add_component(entity, shot_as_particle(shot, Vector3::new(0.0, 1.5, 0.0)))
+	})
+	.unwrap();
+	for entity in rounds.entities() {
 		let mut node = window.add_sphere(0.5);
 		node.set_visible(false);
 		node.set_color(0.0, 1.0, 1.0);
-		world.add_component(entity, node).unwrap();
-
-		let shot = world.resources().borrow().get::<NextShot>().unwrap().0;
-		world.add_component(entity, Round::default()).unwrap();
-
-		let position = Vector3::new(0.0, 1.5, 0.0);
-		world.add_component(entity, shot_as_particle(shot, position)).unwrap();
+		world.add_component(*entity, node).unwrap();
 	}
 
 	while window.render() {
 		map_keyboard_input(&window, &world);
 		render_background(&world, &mut window, &font);
-		physics_system(0.01, &mut world)?;
-		projectile_system(&mut world)?;
-		timeout_system(&mut world)?;
+		physics_system(0.01, &water, &mut world)?;
+		splash_system(&mut world);
+		fire_system(&mut world, &mut rounds)?;
+		timeout_system(&mut world, &mut rounds)?;
 		sync_node_system(&mut world)?;
+		sync_visibility_system(&mut world, &rounds);
 	}
 
 	Ok(())
@@ -100,6 +132,7 @@ fn render_background(world: &World, window: &mut Window, font: &Rc<Font>) {
 	if let Some(NextShot(shot)) = world.resources().borrow().get::<NextShot>() {
 		window.draw_text(&format!("Current Ammo Type: {:?}", shot), &Point2::origin(), 36.0, font, &Point3::new(0.0, 1.0, 1.0));
 	}
+	render_pool(world, window);
 	for offset in (0..200).step_by(10) {
 		window.draw_line(
 			&Point3::new(-5.0, 0.0, offset as _),
@@ -109,53 +142,119 @@ fn render_background(world: &World, window: &mut Window, font: &Rc<Font>) {
 	}
 }
 
-system!(physics_system, [_resources, _entity], (duration: f32), (particle: Particle, round: Round) -> Result<()> {
-	if round.alive {
-		particle.integrate(duration);
-	}
+system!(physics_system, [_resources, _entity], (duration: f32, water: &WaterVolume), (particle: Particle), [Without<Pooled>] -> Result<()> {
+	// Every round displaces twice the water its weight would, so rounds that land in the pool float
+	let weight = particle.mass() * particle.acceleration.y().abs().max(1.0);
+	let volume = 2.0 * weight / (water.density * water.gravity);
+	let force = water.force(particle.position, particle.velocity, 0.5, volume);
+	particle.add_force(force);
+	particle.integrate(duration);
 	Ok(())
 });
 
-system!(projectile_system, [resources, _entity], (), (particle: Particle, round: Round) -> Result<()> {
-	if round.alive {
-		return Ok(())
+fn splash_system(world: &mut World) {
+	let rounds = match world.get_component_vec::<Particle>() {
+		Some(particles) => particles
+			.iter()
+			.filter(|(entity, _)| world.get_component::<Pooled>(*entity).is_none())
+			.map(|(entity, particle)| (*entity.index(), particle.position, particle.velocity))
+			.collect::<Vec<_>>(),
+		None => return,
+	};
+	let mut resources = world.resources().borrow_mut();
+	let Some(pool) = resources.get_mut::<Pool>() else {
+		return;
+	};
+	let splashes = pool
+		.trigger
+		.update(&pool.water, rounds)
+		.into_iter()
+		.filter(|event| event.kind == WaterEventKind::Entered)
+		.map(|event| Splash {
+			position: event.position,
+			start_time: Instant::now(),
+		})
+		.collect::<Vec<_>>();
+	if let Some(Splashes(active)) = resources.get_mut::<Splashes>() {
+		active.retain(|splash| splash.start_time.elapsed().as_secs_f32() < SPLASH_SECS);
+		active.extend(splashes);
 	}
-	if matches!(resources.borrow().get::<ShouldFire>(), Some(ShouldFire(true))) {
-		round.start_time = Some(Instant::now());
-		round.alive = true;
-		let position = Vector3::new(0.0, 1.5, 0.0);
-		*particle = shot_as_particle(resources.borrow().get::<NextShot>().unwrap().0, position);
-		resources.borrow_mut().get_mut::<ShouldFire>().as_deref_mut().unwrap().0 = false;
+}
+
+fn render_pool(world: &World, window: &mut Window) {
+	let resources = world.resources().borrow();
+	let water_color = Point3::new(0.2, 0.4, 1.0);
+	if let Some(pool) = resources.get::<Pool>() {
+		let (min, max, surface) = (pool.water.bounds.min, pool.water.bounds.max, pool.water.surface());
+		let corners = [(min.x(), min.z()), (max.x(), min.z()), (max.x(), max.z()), (min.x(), max.z())];
+		for (index, (x, z)) in corners.iter().enumerate() {
+			let (next_x, next_z) = corners[(index + 1) % corners.len()];
+			window.draw_line(&Point3::new(*x, surface, *z), &Point3::new(next_x, surface, next_z), &water_color);
+		}
 	}
-	Ok(())
-});
 
-system!(timeout_system, [_resources, _entity], (), (round: Round, particle: Particle) -> Result<()> {
-	if !round.alive {
-		return Ok(());
+	// Each splash is a ring spreading out across the surface
+	if let Some(Splashes(splashes)) = resources.get::<Splashes>() {
+		for splash in splashes.iter() {
+			let radius = 0.2 + 3.0 * splash.start_time.elapsed().as_secs_f32() / SPLASH_SECS;
+			let point = |step: usize| {
+				let angle = TAU * step as f32 / 16.0;
+				Point3::new(
+					splash.position.x() + radius * angle.cos(),
+					splash.position.y(),
+					splash.position.z() + radius * angle.sin(),
+				)
+			};
+			for step in 0..16 {
+				window.draw_line(&point(step), &point(step + 1), &water_color);
+			}
+		}
 	}
-	let out_of_bounds = particle.position.y() < 0.0 || particle.position.z() > 200.0;
-	let expired = match round.start_time {
-		Some(instant) => (Instant::now() - instant).as_secs() > PARTICLE_TIMEOUT_SECS as _,
-		None => true,
-	};
-	if out_of_bounds || expired {
-		round.start_time = None;
-		round.alive = false;
+}
+
+fn fire_system(world: &mut World, rounds: &mut EntityPool) -> Result<()> {
+	let should_fire = world
+		.resources()
+		.borrow_mut()
+		.get_mut::<ShouldFire>()
+		.map(|should_fire| std::mem::take(&mut should_fire.0));
+	if should_fire == Some(true) {
+		rounds.acquire(world).map_err(|error| anyhow::anyhow!("{error}"))?;
 	}
 	Ok(())
-});
+}
 
-system!(sync_node_system, [_resources, _entity], (), (node: SceneNode, particle: Particle, round: Round) -> Result<()> {
-	node.set_visible(round.alive);
-	node.set_local_translation(Translation3::new(
-		particle.position.x() as _,
-		particle.position.y() as _,
-		particle.position.z() as _,
-	));
+fn timeout_system(world: &mut World, rounds: &mut EntityPool) -> Result<()> {
+	let expired = rounds
+		.active()
+		.filter(|entity| {
+			let (Some(round), Some(particle)) = (world.get_component::<Round>(*entity), world.get_component::<Particle>(*entity)) else {
+				return false;
+			};
+			let out_of_bounds = particle.position.y() < 0.0 || particle.position.z() > 200.0;
+			out_of_bounds || round.start_time.elapsed().as_secs() > PARTICLE_TIMEOUT_SECS as _
+		})
+		.collect::<Vec<_>>();
+	for entity in expired {
+		rounds.release(world, entity).map_err(|error| anyhow::anyhow!("{error}"))?;
+	}
+	Ok(())
+}
+
+// Only rounds whose particle moved this frame need their node updated
+system!(sync_node_system, [_resources, _entity], (), (node: SceneNode, particle: Particle), [Changed<Particle>] -> Result<()> {
+	node.set_local_translation(particle.position.into());
 	Ok(())
 });
 
+fn sync_visibility_system(world: &mut World, rounds: &EntityPool) {
+	for entity in rounds.entities() {
+		if let Some(mut node) = world.get_component_mut::<SceneNode>(*entity) {
+			node.set_visible(rounds.is_active(*entity));
+		}
+	}
+}
+
 fn shot_as_particle(shot: Shot, position: Vector3) -> Particle {
 	match shot {
 		Shot::Pistol => {
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use kiss3d::{
+	camera::ArcBall,
+	event::{Action, Key, WindowEvent},
+	light::Light,
+	text::Font,
+	window::Window,
+};
+use math::{Real, Vector3};
+use na::{Point2, Point3};
+use nalgebra as na;
+use physics::{Plane, SoftBody};
+use std::rc::Rc;
+
+const STEP: Real = 1.0 / 600.0;
+const STEPS_PER_FRAME: usize = 10;
+const REST_PRESSURE: Real = 200.0;
+
+fn main() -> Result<()> {
+	let mut window = Window::new("Physics Engine - Soft Body Demo");
+	window.set_light(Light::StickToCamera);
+	let font = Font::default();
+	let mut camera = ArcBall::new(Point3::new(0.0, 3.0, -8.0), Point3::new(0.0, 1.0, 0.0));
+
+	let ground = Plane::ground(0.0);
+	let mut ball = new_ball();
+
+	while window.render_with_camera(&mut camera) {
+		for event in window.events().iter() {
+			if let WindowEvent::Key(key, Action::Press, _) = event.value {
+				match key {
+					// Pumping gas in or out changes how hard the ball pushes back
+					Key::Up => ball.gas *= 1.5,
+					Key::Down => ball.gas /= 1.5,
+					Key::Space => ball = new_ball(),
+					_ => {},
+				}
+			}
+		}
+
+		for _ in 0..STEPS_PER_FRAME {
+			ball.step(STEP);
+			ball.collide_with_plane(&ground, 0.5);
+		}
+
+		render_ball(&mut window, &ball);
+		render_background(&mut window, &font, &ball);
+	}
+
+	Ok(())
+}
+
+fn new_ball() -> SoftBody {
+	let mut ball = SoftBody::sphere(Vector3::new(0.0, 3.0, 0.0), 1.0, 2, 1.0, REST_PRESSURE);
+	ball.set_acceleration(Vector3::new(0.0, -10.0, 0.0));
+	ball
+}
+
+fn render_ball(window: &mut Window, ball: &SoftBody) {
+	let point = |index: usize| Point3::from(ball.particles[index].position);
+	for spring in ball.springs.iter() {
+		window.draw_line(&point(spring.a), &point(spring.b), &Point3::new(1.0, 0.5, 0.0));
+	}
+}
+
+fn render_background(window: &mut Window, font: &Rc<Font>, ball: &SoftBody) {
+	window.draw_text(
+		&format!(
+			"Pressure: {:.0} (outside: {:.0})\nVolume: {:.2}\nUp/Down: inflate and deflate, Space: drop again",
+			ball.pressure(),
+			ball.ambient_pressure,
+			ball.volume()
+		),
+		&Point2::origin(),
+		36.0,
+		font,
+		&Point3::new(0.0, 1.0, 1.0),
+	);
+	for offset in (-10..=10).map(|offset| offset as Real) {
+		window.draw_line(&Point3::new(-10.0, 0.0, offset), &Point3::new(10.0, 0.0, offset), &Point3::new(0.75, 0.75, 0.75));
+		window.draw_line(&Point3::new(offset, 0.0, -10.0), &Point3::new(offset, 0.0, 10.0), &Point3::new(0.75, 0.75, 0.75));
+	}
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use kiss3d::{
+	camera::ArcBall,
+	event::{Action, Key},
+	light::Light,
+	scene::SceneNode,
+	text::Font,
+	window::Window,
+};
+use math::{Real, Transform, Vector3};
+use na::{Point2, Point3, UnitQuaternion, Vector3 as NaVector3};
+use nalgebra as na;
+use physics::{Aabb, Plane, Raycast, RigidBody, Vehicle};
+
+const STEP: Real = 1.0 / 120.0;
+const STEPS_PER_FRAME: usize = 2;
+
+fn main() -> Result<()> {
+	let mut window = Window::new("Physics Engine - Vehicle Demo");
+	window.set_light(Light::StickToCamera);
+	let font = Font::default();
+	let mut camera = ArcBall::new(Point3::new(0.0, 6.0, -12.0), Point3::origin());
+
+	let mut ground: Vec<Box<dyn Raycast>> = vec![Box::new(Plane::ground(0.0))];
+	for (index, z) in (20..200).step_by(30).enumerate() {
+		let bump = Aabb {
+			min: Vector3::new(-10.0, 0.0, z as Real),
+			max: Vector3::new(10.0, 0.1 + 0.05 * index as Real, z as Real + 1.0),
+		};
+		let size = bump.max - bump.min;
+		let mut node = window.add_cube(size.x(), size.y(), size.z());
+		let center = (bump.min + bump.max) * 0.5;
+		node.set_local_translation(center.into());
+		node.set_color(0.6, 0.4, 0.2);
+		ground.push(Box::new(bump));
+	}
+
+	let mut body = RigidBody {
+		position: Vector3::new(0.0, 1.0, 0.0),
+		acceleration: Vector3::new(0.0, -10.0, 0.0),
+		..RigidBody::cuboid(1_000.0, Vector3::new(0.9, 0.4, 2.0))
+	};
+	let mut vehicle = Vehicle::four_wheeled(0.8, 1.4, -0.2, 0.35);
+
+	let mut chassis = window.add_cube(1.8, 0.8, 4.0);
+	chassis.set_color(0.8, 0.1, 0.1);
+	let mut wheels = vehicle
+		.wheels
+		.iter()
+		.map(|wheel| {
+			// Cylinders are built along the Y axis, so the mesh is turned to put the axle along X
+			let mut group = window.add_group();
+			let mut tire = group.add_cylinder(wheel.settings.radius, 0.25);
+			tire.set_local_rotation(UnitQuaternion::from_axis_angle(&NaVector3::z_axis(), std::f32::consts::FRAC_PI_2));
+			tire.set_color(0.1, 0.1, 0.1);
+			group
+		})
+		.collect::<Vec<_>>();
+
+	while window.render_with_camera(&mut camera) {
+		map_keyboard_input(&window, &mut vehicle);
+		for _ in 0..STEPS_PER_FRAME {
+			vehicle.update(&mut body, &ground[..], STEP);
+			body.integrate(STEP);
+		}
+
+		sync_node(
+			&mut chassis,
+			&Transform {
+				translation: body.position,
+				rotation: body.orientation,
+				..Transform::identity()
+			},
+		);
+		for (index, node) in wheels.iter_mut().enumerate() {
+			if let Some(transform) = vehicle.wheel_transform(&body, index) {
+				sync_node(node, &transform);
+			}
+		}
+		camera.set_at(body.position.into());
+
+		render_background(&mut window, &font, &vehicle, &body);
+	}
+
+	Ok(())
+}
+
+fn map_keyboard_input(window: &Window, vehicle: &mut Vehicle) {
+	let held = |key| window.get_key(key) == Action::Press;
+	vehicle.throttle = match (held(Key::W), held(Key::S)) {
+		(true, false) => 1.0,
+		(false, true) => -0.5,
+		_ => 0.0,
+	};
+	vehicle.steering = match (held(Key::A), held(Key::D)) {
+		(true, false) => -1.0,
+		(false, true) => 1.0,
+		_ => 0.0,
+	};
+	vehicle.brake = if held(Key::Space) { 1.0 } else { 0.0 };
+}
+
+fn sync_node(node: &mut SceneNode, transform: &Transform) {
+	let Transform { translation, rotation, .. } = transform;
+	node.set_local_translation((*translation).into());
+	node.set_local_rotation((*rotation).into());
+}
+
+fn render_background(window: &mut Window, font: &std::rc::Rc<Font>, vehicle: &Vehicle, body: &RigidBody) {
+	let speed = vehicle.speed(body) * 3.6;
+	let sliding = vehicle
+		.wheels
+		.iter()
+		.filter_map(|wheel| wheel.contact)
+		.any(|contact| contact.slip_ratio.abs() > 0.5 || contact.slip_angle.abs() > 0.6);
+	let status = if sliding { " (sliding)" } else { "" };
+	window.draw_text(
+		&format!("Speed: {speed:.0} km/h{status}\nW/S: throttle and reverse, A/D: steer, Space: brake"),
+		&Point2::origin(),
+		36.0,
+		font,
+		&Point3::new(0.0, 1.0, 1.0),
+	);
+	for offset in (-200..200).step_by(10) {
+		window.draw_line(
+			&Point3::new(-200.0, 0.0, offset as _),
+			&Point3::new(200.0, 0.0, offset as _),
+			&Point3::new(0.75, 0.75, 0.75),
+		);
+		window.draw_line(
+			&Point3::new(offset as _, 0.0, -200.0),
+			&Point3::new(offset as _, 0.0, 200.0),
+			&Point3::new(0.75, 0.75, 0.75),
+		);
+	}
+}
@@ -0,0 +1,268 @@
+use crate::RigidBody;
+use alloc::{boxed::Box, vec::Vec};
+use math::{Quaternion, Real, Vector3};
+
+/// Adds forces and torques to a rigid body each step
+pub trait ForceGenerator {
+	fn update_force(&mut self, body: &mut RigidBody, duration: Real);
+}
+
+struct ForceRegistration {
+	body: usize,
+	generator: Box<dyn ForceGenerator>,
+}
+
+/// Pairs force generators with the bodies they act on, by their index in the bodies
+#[derive(Default)]
+pub struct ForceRegistry {
+	registrations: Vec<ForceRegistration>,
+}
+
+impl ForceRegistry {
+	pub fn add(&mut self, body: usize, generator: impl ForceGenerator + 'static) {
+		self.registrations.push(ForceRegistration {
+			body,
+			generator: Box::new(generator),
+		});
+	}
+
+	/// Removes every generator acting on a body
+	pub fn remove(&mut self, body: usize) {
+		self.registrations.retain(|registration| registration.body != body);
+	}
+
+	/// Moves the generators acting on the body at one index to another, when its index changes
+	pub fn move_body(&mut self, from: usize, to: usize) {
+		for registration in self.registrations.iter_mut().filter(|registration| registration.body == from) {
+			registration.body = to;
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.registrations.clear();
+	}
+
+	pub fn len(&self) -> usize {
+		self.registrations.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.registrations.is_empty()
+	}
+
+	/// Applies every generator to its body. Registrations for bodies that don't exist are skipped.
+	pub fn update_forces(&mut self, bodies: &mut [RigidBody], duration: Real) {
+		for registration in self.registrations.iter_mut() {
+			if let Some(body) = bodies.get_mut(registration.body) {
+				registration.generator.update_force(body, duration);
+			}
+		}
+	}
+}
+
+/// Pulls bodies with finite mass down with a constant acceleration
+#[derive(Debug, Clone, Copy)]
+pub struct Gravity {
+	pub gravity: Vector3,
+}
+
+impl ForceGenerator for Gravity {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		if body.has_finite_mass() {
+			body.add_force(self.gravity * body.mass());
+		}
+	}
+}
+
+/// Pulls a point on a body toward a fixed anchor in world space, such as a crane's cable
+#[derive(Debug, Clone, Copy)]
+pub struct AnchoredSpring {
+	/// The point the spring is attached to, in body space
+	pub connection_point: Vector3,
+	pub anchor: Vector3,
+	pub spring_constant: Real,
+	pub rest_length: Real,
+}
+
+impl ForceGenerator for AnchoredSpring {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		let connection = body.point_in_world(self.connection_point);
+		let offset = connection - self.anchor;
+		let stretch = offset.magnitude() - self.rest_length;
+		body.add_force_at_point(offset.normalize() * -(self.spring_constant * stretch), connection);
+	}
+}
+
+/// Twists a body back toward a rest orientation, such as a self-closing door
+#[derive(Debug, Clone, Copy)]
+pub struct TorsionSpring {
+	pub rest_orientation: Quaternion,
+
+	/// Torque per radian of twist away from the rest orientation
+	pub stiffness: Real,
+
+	/// Torque per radian per second of angular velocity, which stops the body oscillating
+	pub damping: Real,
+}
+
+impl ForceGenerator for TorsionSpring {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		let twist = (body.orientation * self.rest_orientation.conjugate()).to_scaled_axis();
+		body.add_torque(twist * -self.stiffness + body.angular_velocity * -self.damping);
+	}
+}
+
+/// Spins a body around one of its own axes toward a target speed, such as a wheel
+#[derive(Debug, Clone, Copy)]
+pub struct Motor {
+	/// The axis the motor turns the body around, in body space
+	pub axis: Vector3,
+
+	/// The speed the motor drives toward, in radians per second
+	pub target_speed: Real,
+
+	/// Torque per radian per second of difference between the current and target speeds
+	pub gain: Real,
+
+	/// The most torque the motor can apply
+	pub max_torque: Real,
+}
+
+impl ForceGenerator for Motor {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		let axis = body.orientation.rotate(self.axis.normalize());
+		let speed = body.angular_velocity.dot(&axis);
+		let torque = (self.gain * (self.target_speed - speed)).clamp(-self.max_torque, self.max_torque);
+		body.add_torque(axis * torque);
+	}
+}
+
+/// A surface that pushes on a body as air flows past it, such as a wing, rudder, or fin.
+///
+/// Applied at a point away from the center of mass, it turns the body as well as slowing it.
+#[derive(Debug, Clone, Copy)]
+pub struct AeroSurface {
+	/// Force per unit of airflow along each of the body's axes.
+	/// Large values resist air flowing through the surface and small values let air slide along it.
+	pub drag: Vector3,
+
+	/// Where the surface is mounted, in body space
+	pub position: Vector3,
+
+	/// The velocity of the air in world space
+	pub wind: Vector3,
+}
+
+impl ForceGenerator for AeroSurface {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		let point = body.point_in_world(self.position);
+		let airflow = body.direction_in_body(self.wind - body.velocity_at_point(point));
+		body.add_force_at_point(body.orientation.rotate(airflow * self.drag), point);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::f32::consts::FRAC_PI_2;
+
+	fn simulate(registry: &mut ForceRegistry, bodies: &mut [RigidBody], steps: usize) {
+		for _ in 0..steps {
+			registry.update_forces(bodies, 0.01);
+			bodies.iter_mut().for_each(|body| body.integrate(0.01));
+		}
+	}
+
+	fn cube() -> RigidBody {
+		RigidBody::cuboid(1.0, Vector3::new(0.5, 0.5, 0.5))
+	}
+
+	#[test]
+	pub fn registry() {
+		let mut bodies = [cube(), cube()];
+		let mut registry = ForceRegistry::default();
+		let gravity = Gravity {
+			gravity: Vector3::new(0.0, -10.0, 0.0),
+		};
+		for body in [0, 1, 7] {
+			registry.add(body, gravity);
+		}
+		registry.remove(1);
+		assert_eq!(registry.len(), 2);
+
+		simulate(&mut registry, &mut bodies, 100);
+		assert!((bodies[0].velocity.y() + 10.0).abs() < 1e-3);
+		assert_eq!(bodies[1].velocity.y(), 0.0);
+	}
+
+	#[test]
+	pub fn motors_reach_their_target_speed() {
+		let mut bodies = [cube()];
+		let mut registry = ForceRegistry::default();
+		registry.add(
+			0,
+			Motor {
+				axis: Vector3::y_axis(),
+				target_speed: 2.0,
+				gain: 10.0,
+				max_torque: 5.0,
+			},
+		);
+		simulate(&mut registry, &mut bodies, 500);
+		assert!((bodies[0].angular_velocity.y() - 2.0).abs() < 1e-2);
+	}
+
+	#[test]
+	pub fn torsion_springs_return_to_rest() {
+		let mut door = cube();
+		door.orientation = Quaternion::from_axis_angle(Vector3::y_axis(), FRAC_PI_2);
+		let mut bodies = [door];
+		let mut registry = ForceRegistry::default();
+		registry.add(
+			0,
+			TorsionSpring {
+				rest_orientation: Quaternion::identity(),
+				stiffness: 5.0,
+				damping: 2.0,
+			},
+		);
+		simulate(&mut registry, &mut bodies, 1000);
+		assert!(bodies[0].orientation.approximately_equals(&Quaternion::identity(), 1e-4));
+	}
+
+	#[test]
+	pub fn aero_surfaces_turn_into_the_wind() {
+		// A fin behind the center of mass, like a weather vane, with air blowing across it
+		let mut bodies = [cube()];
+		let mut registry = ForceRegistry::default();
+		registry.add(
+			0,
+			AeroSurface {
+				drag: Vector3::new(0.0, 0.0, 1.0),
+				position: Vector3::new(-1.0, 0.0, 0.0),
+				wind: Vector3::new(0.0, 0.0, 1.0),
+			},
+		);
+		registry.update_forces(&mut bodies, 0.01);
+		assert!(bodies[0].force_accumulator.z() > 0.0);
+		assert!(bodies[0].torque_accumulator.y() > 0.0);
+	}
+
+	#[test]
+	pub fn anchored_springs_pull_toward_the_anchor() {
+		let mut bodies = [cube()];
+		let mut registry = ForceRegistry::default();
+		registry.add(
+			0,
+			AnchoredSpring {
+				connection_point: Vector3::x_axis(),
+				anchor: Vector3::new(1.0, 3.0, 0.0),
+				spring_constant: 2.0,
+				rest_length: 1.0,
+			},
+		);
+		registry.update_forces(&mut bodies, 0.01);
+		assert!((bodies[0].force_accumulator - Vector3::new(0.0, 4.0, 0.0)).magnitude() < 1e-5);
+		assert!((bodies[0].torque_accumulator - Vector3::new(0.0, 0.0, 4.0)).magnitude() < 1e-5);
+	}
+}
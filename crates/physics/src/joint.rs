@@ -0,0 +1,459 @@
+use crate::RigidBody;
+use alloc::vec::Vec;
+use core::f32::consts::PI;
+#[cfg(not(feature = "std"))]
+use math::Float;
+use math::{Quaternion, Real, Vector3};
+
+/// The range a hinge's angle in radians or a slider's translation is kept within
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointLimits {
+	pub lower: Real,
+	pub upper: Real,
+}
+
+/// Drives a hinge toward a speed in radians per second with a limited torque
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointMotor {
+	pub target_speed: Real,
+	pub max_torque: Real,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JointKind {
+	/// Keeps the anchors together while the bodies turn freely, like a shoulder
+	Ball,
+
+	/// Lets the bodies turn around a single axis in the first body's space, like a door or a wheel
+	Hinge {
+		axis: Vector3,
+		limits: Option<JointLimits>,
+		motor: Option<JointMotor>,
+	},
+
+	/// Lets the second body slide along an axis in the first body's space, like a piston
+	Slider { axis: Vector3, limits: Option<JointLimits> },
+
+	/// Locks the bodies together
+	Fixed,
+}
+
+/// Connects two rigid bodies, identified by their index in the simulation's bodies.
+/// To attach a body to the world, connect it to a body with infinite mass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Joint {
+	pub body_a: usize,
+	pub body_b: usize,
+
+	/// Where the joint is attached, in each body's space
+	pub anchor_a: Vector3,
+	pub anchor_b: Vector3,
+
+	/// The second body's orientation relative to the first at creation, to measure angles from
+	pub rest: Quaternion,
+
+	pub kind: JointKind,
+
+	// Impulses accumulated over a step, so limits only push one way and motors respect torque
+	limit_impulse: Real,
+	motor_impulse: Real,
+}
+
+impl Joint {
+	/// Joins two bodies at an anchor in world space, keeping their placement as the rest state
+	pub fn new(bodies: &[RigidBody], body_a: usize, body_b: usize, anchor: Vector3, kind: JointKind) -> Self {
+		let (a, b) = (&bodies[body_a], &bodies[body_b]);
+		Self {
+			body_a,
+			body_b,
+			anchor_a: a.point_in_body(anchor),
+			anchor_b: b.point_in_body(anchor),
+			rest: a.orientation.conjugate() * b.orientation,
+			kind,
+			limit_impulse: 0.0,
+			motor_impulse: 0.0,
+		}
+	}
+
+	pub fn ball(bodies: &[RigidBody], body_a: usize, body_b: usize, anchor: Vector3) -> Self {
+		Self::new(bodies, body_a, body_b, anchor, JointKind::Ball)
+	}
+
+	/// A hinge around an axis through an anchor, both in world space
+	pub fn hinge(bodies: &[RigidBody], body_a: usize, body_b: usize, anchor: Vector3, axis: Vector3) -> Self {
+		let axis = bodies[body_a].direction_in_body(axis.normalize());
+		Self::new(bodies, body_a, body_b, anchor, JointKind::Hinge { axis, limits: None, motor: None })
+	}
+
+	/// A slider along an axis in world space
+	pub fn slider(bodies: &[RigidBody], body_a: usize, body_b: usize, axis: Vector3) -> Self {
+		let axis = bodies[body_a].direction_in_body(axis.normalize());
+		Self::new(bodies, body_a, body_b, bodies[body_b].position, JointKind::Slider { axis, limits: None })
+	}
+
+	pub fn fixed(bodies: &[RigidBody], body_a: usize, body_b: usize) -> Self {
+		Self::new(bodies, body_a, body_b, bodies[body_b].position, JointKind::Fixed)
+	}
+
+	/// Limits a hinge's angle or a slider's translation. Other joints are unaffected.
+	#[must_use]
+	pub fn with_limits(mut self, lower: Real, upper: Real) -> Self {
+		if let JointKind::Hinge { limits, .. } | JointKind::Slider { limits, .. } = &mut self.kind {
+			*limits = Some(JointLimits { lower, upper });
+		}
+		self
+	}
+
+	/// Drives a hinge with a motor. Other joints are unaffected.
+	#[must_use]
+	pub fn with_motor(mut self, target_speed: Real, max_torque: Real) -> Self {
+		if let JointKind::Hinge { motor, .. } = &mut self.kind {
+			*motor = Some(JointMotor { target_speed, max_torque });
+		}
+		self
+	}
+
+	/// How far a hinge has turned from its rest state in radians, or how far a slider has moved
+	pub fn position(&self, bodies: &[RigidBody]) -> Real {
+		let (a, b) = (&bodies[self.body_a], &bodies[self.body_b]);
+		match self.kind {
+			JointKind::Hinge { axis, .. } => hinge_angle(a, b, self.rest, axis),
+			JointKind::Slider { axis, .. } => (b.point_in_world(self.anchor_b) - a.point_in_world(self.anchor_a)).dot(&a.orientation.rotate(axis)),
+			JointKind::Ball | JointKind::Fixed => 0.0,
+		}
+	}
+
+	/// Applies impulses that move the bodies' velocities toward satisfying the joint.
+	/// `correction` is the fraction of positional error removed each step.
+	fn solve(&mut self, bodies: &mut [RigidBody], duration: Real, correction: Real) {
+		let Some((a, b)) = pair(bodies, self.body_a, self.body_b) else {
+			return;
+		};
+		let bias = correction / duration;
+		let anchors = (a.point_in_world(self.anchor_a), b.point_in_world(self.anchor_b));
+		let separation = anchors.1 - anchors.0;
+		match self.kind {
+			JointKind::Ball => solve_point(a, b, anchors, separation * bias),
+			JointKind::Fixed => {
+				solve_point(a, b, anchors, separation * bias);
+				self.solve_orientation(a, b, bias);
+			},
+			JointKind::Hinge { axis, limits, motor } => {
+				solve_point(a, b, anchors, separation * bias);
+
+				// Keep the hinge axes of both bodies aligned
+				let axis_a = a.orientation.rotate(axis);
+				let axis_b = b.orientation.rotate(self.rest.conjugate().rotate(axis));
+				let misalignment = axis_a.cross(&axis_b);
+				for direction in perpendiculars(axis_a) {
+					let impulse = angular_impulse(a, b, direction, misalignment.dot(&direction) * bias);
+					apply_angular(a, b, direction * impulse);
+				}
+
+				if let Some(motor) = motor {
+					let impulse = angular_impulse(a, b, axis_a, -motor.target_speed);
+					let limit = motor.max_torque * duration;
+					let total = (self.motor_impulse + impulse).clamp(-limit, limit);
+					apply_angular(a, b, axis_a * (total - self.motor_impulse));
+					self.motor_impulse = total;
+				}
+
+				if let Some(limits) = limits {
+					let angle = hinge_angle(a, b, self.rest, axis);
+					if let Some(error) = limit_error(angle, limits) {
+						let impulse = self.limit_impulse(angular_impulse(a, b, axis_a, error * bias), error);
+						apply_angular(a, b, axis_a * impulse);
+					}
+				}
+			},
+			JointKind::Slider { axis, limits } => {
+				self.solve_orientation(a, b, bias);
+				let axis = a.orientation.rotate(axis);
+				for direction in perpendiculars(axis) {
+					let impulse = linear_impulse(a, b, anchors, direction, separation.dot(&direction) * bias);
+					apply_linear(a, b, anchors, direction * impulse);
+				}
+				if let Some(error) = limits.and_then(|limits| limit_error(separation.dot(&axis), limits)) {
+					let impulse = self.limit_impulse(linear_impulse(a, b, anchors, axis, error * bias), error);
+					apply_linear(a, b, anchors, axis * impulse);
+				}
+			},
+		}
+	}
+
+	/// Locks the bodies' relative orientation to the rest state
+	fn solve_orientation(&self, a: &mut RigidBody, b: &mut RigidBody, bias: Real) {
+		let error = (b.orientation * (a.orientation * self.rest).conjugate()).to_scaled_axis();
+		for direction in axes() {
+			let impulse = angular_impulse(a, b, direction, error.dot(&direction) * bias);
+			apply_angular(a, b, direction * impulse);
+		}
+	}
+
+	/// Clamps the limit's accumulated impulse so it only pushes back toward the allowed range
+	fn limit_impulse(&mut self, impulse: Real, error: Real) -> Real {
+		let total = if error < 0.0 {
+			(self.limit_impulse + impulse).max(0.0)
+		} else {
+			(self.limit_impulse + impulse).min(0.0)
+		};
+		let applied = total - self.limit_impulse;
+		self.limit_impulse = total;
+		applied
+	}
+}
+
+/// Solves joints between rigid bodies with sequential impulses
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointSolver {
+	pub joints: Vec<Joint>,
+
+	/// How many times each joint is solved per step. More iterations make chains of joints stiffer.
+	pub iterations: usize,
+
+	/// The fraction of joint separation removed each step, which keeps joints from drifting apart
+	pub correction: Real,
+}
+
+impl Default for JointSolver {
+	fn default() -> Self {
+		Self {
+			joints: Vec::new(),
+			iterations: 10,
+			correction: 0.2,
+		}
+	}
+}
+
+impl JointSolver {
+	/// Adds a joint, returning its index
+	pub fn add(&mut self, joint: Joint) -> usize {
+		self.joints.push(joint);
+		self.joints.len() - 1
+	}
+
+	/// Integrates the bodies' forces, solves the joints, then moves the bodies
+	pub fn step(&mut self, bodies: &mut [RigidBody], duration: Real) {
+		let mut joints = core::mem::take(&mut self.joints);
+		self.step_joints(&mut joints, bodies, duration);
+		self.joints = joints;
+	}
+
+	/// Adjusts the bodies' velocities so that moving them by `duration` keeps them joined
+	pub fn solve(&mut self, bodies: &mut [RigidBody], duration: Real) {
+		let mut joints = core::mem::take(&mut self.joints);
+		self.solve_joints(&mut joints, bodies, duration);
+		self.joints = joints;
+	}
+
+	/// Steps joints stored outside the solver, such as a `PhysicsWorld`'s, with its settings
+	pub fn step_joints(&self, joints: &mut [Joint], bodies: &mut [RigidBody], duration: Real) {
+		bodies.iter_mut().for_each(|body| body.integrate_velocity(duration));
+		self.solve_joints(joints, bodies, duration);
+		bodies.iter_mut().for_each(|body| body.integrate_position(duration));
+	}
+
+	pub fn solve_joints(&self, joints: &mut [Joint], bodies: &mut [RigidBody], duration: Real) {
+		if duration <= 0.0 {
+			return;
+		}
+		for joint in joints.iter_mut() {
+			joint.limit_impulse = 0.0;
+			joint.motor_impulse = 0.0;
+		}
+		for _ in 0..self.iterations {
+			for joint in joints.iter_mut() {
+				joint.solve(bodies, duration, self.correction);
+			}
+		}
+	}
+}
+
+fn pair(bodies: &mut [RigidBody], a: usize, b: usize) -> Option<(&mut RigidBody, &mut RigidBody)> {
+	if a == b || a.max(b) >= bodies.len() {
+		return None;
+	}
+	if a < b {
+		let (left, right) = bodies.split_at_mut(b);
+		Some((&mut left[a], &mut right[0]))
+	} else {
+		let (left, right) = bodies.split_at_mut(a);
+		Some((&mut right[0], &mut left[b]))
+	}
+}
+
+const fn axes() -> [Vector3; 3] {
+	[Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, 0.0, 1.0)]
+}
+
+/// Two directions perpendicular to an axis and to each other
+fn perpendiculars(axis: Vector3) -> [Vector3; 2] {
+	let helper = if axis.x().abs() < 0.9 { Vector3::x_axis() } else { Vector3::y_axis() };
+	let first = axis.cross(&helper).normalize();
+	[first, axis.cross(&first)]
+}
+
+/// The hinge's turn around its axis since the rest state, in the range -PI to PI
+fn hinge_angle(a: &RigidBody, b: &RigidBody, rest: Quaternion, axis: Vector3) -> Real {
+	let relative = a.orientation.conjugate() * b.orientation * rest.conjugate();
+	let angle = 2.0 * Vector3::new(relative.x, relative.y, relative.z).dot(&axis).atan2(relative.w);
+	if angle > PI {
+		angle - 2.0 * PI
+	} else if angle < -PI {
+		angle + 2.0 * PI
+	} else {
+		angle
+	}
+}
+
+/// How far a value is outside its limits, negative when below and positive when above
+fn limit_error(value: Real, limits: JointLimits) -> Option<Real> {
+	if value < limits.lower {
+		Some(value - limits.lower)
+	} else if value > limits.upper {
+		Some(value - limits.upper)
+	} else {
+		None
+	}
+}
+
+/// The impulse along a direction that cancels the anchors' relative velocity along it, plus a bias
+fn linear_impulse(a: &RigidBody, b: &RigidBody, (anchor_a, anchor_b): (Vector3, Vector3), direction: Vector3, bias: Real) -> Real {
+	let (offset_a, offset_b) = (anchor_a - a.position, anchor_b - b.position);
+	let turning = |body: &RigidBody, offset: Vector3| direction.dot(&body.angular_acceleration(offset.cross(&direction)).cross(&offset));
+	let mass = a.inverse_mass + b.inverse_mass + turning(a, offset_a) + turning(b, offset_b);
+	if mass <= 0.0 {
+		return 0.0;
+	}
+	let relative = b.velocity_at_point(anchor_b) - a.velocity_at_point(anchor_a);
+	-(relative.dot(&direction) + bias) / mass
+}
+
+/// Pins the anchors together along every axis
+fn solve_point(a: &mut RigidBody, b: &mut RigidBody, anchors: (Vector3, Vector3), bias: Vector3) {
+	for direction in axes() {
+		let impulse = linear_impulse(a, b, anchors, direction, bias.dot(&direction));
+		apply_linear(a, b, anchors, direction * impulse);
+	}
+}
+
+fn apply_linear(a: &mut RigidBody, b: &mut RigidBody, (anchor_a, anchor_b): (Vector3, Vector3), impulse: Vector3) {
+	a.apply_impulse(impulse.inverse(), anchor_a);
+	b.apply_impulse(impulse, anchor_b);
+}
+
+/// The angular impulse around a direction that cancels the relative spin around it, plus a bias
+fn angular_impulse(a: &RigidBody, b: &RigidBody, direction: Vector3, bias: Real) -> Real {
+	let mass = direction.dot(&a.angular_acceleration(direction)) + direction.dot(&b.angular_acceleration(direction));
+	if mass <= 0.0 {
+		return 0.0;
+	}
+	let relative = b.angular_velocity - a.angular_velocity;
+	-(relative.dot(&direction) + bias) / mass
+}
+
+fn apply_angular(a: &mut RigidBody, b: &mut RigidBody, impulse: Vector3) {
+	a.apply_angular_impulse(impulse.inverse());
+	b.apply_angular_impulse(impulse);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ForceRegistry, Gravity};
+	use core::f32::consts::FRAC_PI_4;
+
+	const STEP: Real = 1.0 / 120.0;
+
+	fn simulate(solver: &mut JointSolver, registry: &mut ForceRegistry, bodies: &mut [RigidBody], seconds: Real) {
+		for _ in 0..(seconds / STEP) as usize {
+			registry.update_forces(bodies, STEP);
+			solver.step(bodies, STEP);
+		}
+	}
+
+	fn cube(position: Vector3) -> RigidBody {
+		RigidBody {
+			position,
+			..RigidBody::cuboid(1.0, Vector3::new(0.25, 0.25, 0.25))
+		}
+	}
+
+	fn gravity(bodies: &[usize]) -> ForceRegistry {
+		let mut registry = ForceRegistry::default();
+		for body in bodies {
+			registry.add(
+				*body,
+				Gravity {
+					gravity: Vector3::new(0.0, -10.0, 0.0),
+				},
+			);
+		}
+		registry
+	}
+
+	#[test]
+	pub fn ball_joints_swing_like_a_pendulum() {
+		let mut bodies = [RigidBody::default(), cube(Vector3::x_axis())];
+		let mut solver = JointSolver::default();
+		solver.add(Joint::ball(&bodies, 0, 1, Vector3::zero()));
+		simulate(&mut solver, &mut gravity(&[1]), &mut bodies, 2.0);
+
+		let anchor = bodies[1].point_in_world(solver.joints[0].anchor_b);
+		assert!(anchor.magnitude() < 0.05, "The pendulum separated from its pivot by {}", anchor.magnitude());
+		assert!(bodies[1].position.y() < -0.1);
+	}
+
+	#[test]
+	pub fn hinges_turn_around_their_axis() {
+		let mut bodies = [RigidBody::default(), cube(Vector3::x_axis())];
+		let mut solver = JointSolver::default();
+		solver.add(Joint::hinge(&bodies, 0, 1, Vector3::zero(), Vector3::y_axis()).with_motor(1.0, 10.0));
+		bodies[1].add_torque(Vector3::new(5.0, 0.0, 5.0));
+		simulate(&mut solver, &mut gravity(&[1]), &mut bodies, 1.0);
+
+		assert!((bodies[1].orientation.rotate(Vector3::y_axis()) - Vector3::y_axis()).magnitude() < 0.05);
+		assert!((bodies[1].angular_velocity.y() - 1.0).abs() < 0.05);
+		assert!((solver.joints[0].position(&bodies) - 1.0).abs() < 0.1);
+	}
+
+	#[test]
+	pub fn hinge_limits() {
+		let mut bodies = [RigidBody::default(), cube(Vector3::x_axis())];
+		let mut solver = JointSolver::default();
+		let hinge = Joint::hinge(&bodies, 0, 1, Vector3::zero(), Vector3::y_axis())
+			.with_limits(-FRAC_PI_4, FRAC_PI_4)
+			.with_motor(3.0, 10.0);
+		solver.add(hinge);
+		simulate(&mut solver, &mut ForceRegistry::default(), &mut bodies, 2.0);
+		assert!((solver.joints[0].position(&bodies) - FRAC_PI_4).abs() < 0.05);
+	}
+
+	#[test]
+	pub fn sliders_only_move_along_their_axis() {
+		let mut bodies = [RigidBody::default(), cube(Vector3::zero())];
+		let mut solver = JointSolver::default();
+		solver.add(Joint::slider(&bodies, 0, 1, Vector3::x_axis()).with_limits(-1.0, 0.5));
+		bodies[1].velocity = Vector3::new(2.0, 1.0, 0.0);
+		bodies[1].angular_velocity = Vector3::new(0.0, 0.0, 3.0);
+		simulate(&mut solver, &mut gravity(&[1]), &mut bodies, 1.0);
+
+		assert!(bodies[1].position.y().abs() < 0.01);
+		assert!((bodies[1].position.x() - 0.5).abs() < 0.05);
+		assert!(bodies[1].orientation.approximately_equals(&Quaternion::identity(), 1e-4));
+	}
+
+	#[test]
+	pub fn fixed_joints_move_bodies_together() {
+		let mut bodies = [cube(Vector3::zero()), cube(Vector3::x_axis())];
+		let mut solver = JointSolver::default();
+		solver.add(Joint::fixed(&bodies, 0, 1));
+		bodies[0].add_force(Vector3::new(0.0, 0.0, 100.0));
+		simulate(&mut solver, &mut ForceRegistry::default(), &mut bodies, 1.0);
+
+		let offset = bodies[0].direction_in_body(bodies[1].position - bodies[0].position);
+		assert!((offset - Vector3::x_axis()).magnitude() < 0.05);
+		assert!((bodies[0].position + bodies[1].position).z() / 2.0 > 0.1);
+		assert!(bodies[0].orientation.approximately_equals(&bodies[1].orientation, 1e-3));
+	}
+}
@@ -1,3 +1,15 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod force;
+pub mod joint;
 pub mod particle;
+pub mod raycast;
+pub mod rigid_body;
+pub mod soft_body;
+pub mod vehicle;
+pub mod water;
+pub mod world;
 
-pub use self::particle::*;
+pub use self::{force::*, joint::*, particle::*, raycast::*, rigid_body::*, soft_body::*, vehicle::*, water::*, world::*};
@@ -1,3 +1,5 @@
+#[cfg(not(feature = "std"))]
+use math::Float;
 use math::{Real, Vector3};
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -53,14 +55,19 @@ impl Particle {
 	/// linear approximation to the correct integral. For this reason it
 	/// may be inaccurate in some cases.
 	pub fn integrate(&mut self, duration: Real) {
+		self.integrate_position(duration);
+		self.integrate_velocity(duration);
+	}
+
+	/// Applies the acceleration and accumulated forces to the velocity, then clears the forces.
+	/// Calling this before `integrate_position` gives semi-implicit Euler integration,
+	/// which stays stable for the stiff springs that hold particles together in meshes.
+	pub fn integrate_velocity(&mut self, duration: Real) {
 		// Infinite mass should not be integrated
 		if self.inverse_mass <= 0.0 || duration <= 0.0 {
 			return;
 		}
 
-		// Update linear position
-		self.position += self.velocity * duration;
-
 		// Update linear velocity from the acceleration
 		let acceleration = self.acceleration + self.force_accumulator * self.inverse_mass;
 		self.velocity += acceleration * duration;
@@ -71,6 +78,16 @@ impl Particle {
 		// Clear any accumulated forces
 		self.force_accumulator = Vector3::zero();
 	}
+
+	/// Moves the particle by its velocity
+	pub fn integrate_position(&mut self, duration: Real) {
+		if self.inverse_mass <= 0.0 || duration <= 0.0 {
+			return;
+		}
+
+		// Update linear position
+		self.position += self.velocity * duration;
+	}
 }
 
 #[cfg(test)]
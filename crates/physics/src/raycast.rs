@@ -0,0 +1,146 @@
+use alloc::boxed::Box;
+use math::{Real, Vector3};
+
+pub use math::{Aabb, Ray};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+	pub point: Vector3,
+
+	/// The surface normal at the hit, facing the ray
+	pub normal: Vector3,
+
+	pub distance: Real,
+}
+
+/// Geometry that rays can be cast against, such as the ground wheels drive on
+pub trait Raycast {
+	/// The nearest hit along the ray no further than `max_distance`
+	fn raycast(&self, ray: &Ray, max_distance: Real) -> Option<RayHit>;
+}
+
+/// An infinite plane made of the points whose distance along `normal` from the origin is `distance`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+	pub normal: Vector3,
+	pub distance: Real,
+}
+
+impl Plane {
+	/// A horizontal plane at a height
+	pub fn ground(height: Real) -> Self {
+		Self {
+			normal: Vector3::y_axis(),
+			distance: height,
+		}
+	}
+}
+
+impl Raycast for Plane {
+	fn raycast(&self, ray: &Ray, max_distance: Real) -> Option<RayHit> {
+		let approach = ray.direction.dot(&self.normal);
+		if approach.abs() < Real::EPSILON {
+			return None;
+		}
+		let distance = (self.distance - ray.origin.dot(&self.normal)) / approach;
+		if !(0.0..=max_distance).contains(&distance) {
+			return None;
+		}
+		Some(RayHit {
+			point: ray.point_at(distance),
+			normal: if approach < 0.0 { self.normal } else { self.normal.inverse() },
+			distance,
+		})
+	}
+}
+
+impl Raycast for Aabb {
+	fn raycast(&self, ray: &Ray, max_distance: Real) -> Option<RayHit> {
+		// Clip the ray against the slab between each pair of faces, tracking the entry face
+		let (mut near, mut far) = (0.0, max_distance);
+		let mut normal = None;
+		for axis in 0..3 {
+			let (origin, direction) = (ray.origin[axis], ray.direction[axis]);
+			if direction.abs() < Real::EPSILON {
+				if origin < self.min[axis] || origin > self.max[axis] {
+					return None;
+				}
+				continue;
+			}
+			let (mut enter, mut exit) = ((self.min[axis] - origin) / direction, (self.max[axis] - origin) / direction);
+			if enter > exit {
+				core::mem::swap(&mut enter, &mut exit);
+			}
+			if enter > near {
+				near = enter;
+				let mut face = Vector3::zero();
+				face[axis] = -direction.signum();
+				normal = Some(face);
+			}
+			far = exit.min(far);
+			if near > far {
+				return None;
+			}
+		}
+
+		// Rays starting inside the box have no face to report
+		normal.map(|normal| RayHit {
+			point: ray.point_at(near),
+			normal,
+			distance: near,
+		})
+	}
+}
+
+impl<T: Raycast + ?Sized> Raycast for Box<T> {
+	fn raycast(&self, ray: &Ray, max_distance: Real) -> Option<RayHit> {
+		(**self).raycast(ray, max_distance)
+	}
+}
+
+impl<T: Raycast> Raycast for [T] {
+	fn raycast(&self, ray: &Ray, max_distance: Real) -> Option<RayHit> {
+		self.iter()
+			.filter_map(|shape| shape.raycast(ray, max_distance))
+			.min_by(|a, b| a.distance.total_cmp(&b.distance))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	pub fn planes() {
+		let ray = Ray::new(Vector3::new(1.0, 3.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+		let hit = Plane::ground(1.0).raycast(&ray, 5.0).unwrap();
+		assert_eq!(hit.distance, 2.0);
+		assert_eq!(hit.point, Vector3::new(1.0, 1.0, 0.0));
+		assert_eq!(hit.normal, Vector3::y_axis());
+		assert_eq!(Plane::ground(1.0).raycast(&ray, 1.0), None);
+		assert_eq!(Plane::ground(4.0).raycast(&ray, 5.0), None);
+	}
+
+	#[test]
+	pub fn boxes() {
+		let step = Aabb {
+			min: Vector3::new(-1.0, 0.0, 2.0),
+			max: Vector3::new(1.0, 0.5, 3.0),
+		};
+		let forward = Ray::new(Vector3::new(0.0, 0.25, 0.0), Vector3::new(0.0, 0.0, 1.0));
+		let hit = step.raycast(&forward, 10.0).unwrap();
+		assert_eq!(hit.distance, 2.0);
+		assert_eq!(hit.normal, Vector3::new(0.0, 0.0, -1.0));
+
+		let down = Ray::new(Vector3::new(0.0, 2.0, 2.5), Vector3::new(0.0, -1.0, 0.0));
+		let shapes = [
+			step,
+			Aabb {
+				max: Vector3::new(1.0, 1.0, 3.0),
+				..step
+			},
+		];
+		assert_eq!(shapes.raycast(&down, 10.0).map(|hit| hit.distance), Some(1.0));
+		assert_eq!(step.raycast(&Ray::new(Vector3::new(5.0, 2.0, 2.5), Vector3::new(0.0, -1.0, 0.0)), 10.0), None);
+	}
+}
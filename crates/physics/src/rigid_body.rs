@@ -0,0 +1,216 @@
+#[cfg(not(feature = "std"))]
+use math::Float;
+use math::{Quaternion, Real, Vector3};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RigidBody {
+	/// Holds the linear position of the body's center of mass in world space
+	pub position: Vector3,
+
+	/// Holds the angular orientation of the body in world space
+	pub orientation: Quaternion,
+
+	/// Holds the linear velocity of the body in world space
+	pub velocity: Vector3,
+
+	/// Holds the angular velocity of the body in world space, in radians per second
+	pub angular_velocity: Vector3,
+
+	/// Holds a constant acceleration, such as gravity
+	pub acceleration: Vector3,
+
+	/// Holds the fraction of linear velocity kept after one second.
+	/// Damping removes energy added through numerical instability in the integrator.
+	pub linear_damping: Real,
+
+	/// Holds the fraction of angular velocity kept after one second
+	pub angular_damping: Real,
+
+	/// Holds the inverse of the mass of the body, which is zero for immovable bodies
+	pub inverse_mass: Real,
+
+	/// Holds the inverse of the body's moments of inertia around its principal axes, in body space.
+	///
+	/// Bodies are assumed to be modeled with their principal axes along the body space axes,
+	/// which holds for boxes, spheres, and cylinders centered on their center of mass.
+	pub inverse_inertia: Vector3,
+
+	/// Holds the accumulated force to be applied at the next integration step only
+	pub force_accumulator: Vector3,
+
+	/// Holds the accumulated torque to be applied at the next integration step only
+	pub torque_accumulator: Vector3,
+}
+
+impl Default for RigidBody {
+	fn default() -> Self {
+		Self {
+			position: Vector3::zero(),
+			orientation: Quaternion::identity(),
+			velocity: Vector3::zero(),
+			angular_velocity: Vector3::zero(),
+			acceleration: Vector3::zero(),
+			linear_damping: 1.0,
+			angular_damping: 1.0,
+			inverse_mass: 0.0,
+			inverse_inertia: Vector3::zero(),
+			force_accumulator: Vector3::zero(),
+			torque_accumulator: Vector3::zero(),
+		}
+	}
+}
+
+impl RigidBody {
+	/// A solid box with the given mass and half of its size along each axis
+	#[must_use]
+	pub fn cuboid(mass: Real, half_extents: Vector3) -> Self {
+		let (x, y, z) = (half_extents.x().powi(2), half_extents.y().powi(2), half_extents.z().powi(2));
+		let moment = |a: Real, b: Real| (mass * (a + b) / 3.0).recip();
+		Self {
+			inverse_mass: mass.recip(),
+			inverse_inertia: Vector3::new(moment(y, z), moment(x, z), moment(x, y)),
+			..Default::default()
+		}
+	}
+
+	#[must_use]
+	pub fn mass(&self) -> Real {
+		self.inverse_mass.recip()
+	}
+
+	#[must_use]
+	pub fn has_finite_mass(&self) -> bool {
+		self.inverse_mass != 0.0
+	}
+
+	/// Converts a point in body space to world space
+	#[must_use]
+	pub fn point_in_world(&self, point: Vector3) -> Vector3 {
+		self.position + self.orientation.rotate(point)
+	}
+
+	/// Converts a point in world space to body space
+	#[must_use]
+	pub fn point_in_body(&self, point: Vector3) -> Vector3 {
+		self.direction_in_body(point - self.position)
+	}
+
+	/// Converts a direction in world space to body space
+	#[must_use]
+	pub fn direction_in_body(&self, direction: Vector3) -> Vector3 {
+		self.orientation.conjugate().rotate(direction)
+	}
+
+	/// The velocity of a point in world space that moves with the body
+	#[must_use]
+	pub fn velocity_at_point(&self, point: Vector3) -> Vector3 {
+		self.velocity + self.angular_velocity.cross(&(point - self.position))
+	}
+
+	/// Adds a force through the center of mass, which doesn't turn the body
+	pub fn add_force(&mut self, force: Vector3) {
+		self.force_accumulator += force;
+	}
+
+	/// Adds a force at a point in world space, which also turns the body off its center of mass
+	pub fn add_force_at_point(&mut self, force: Vector3, point: Vector3) {
+		self.force_accumulator += force;
+		self.torque_accumulator += (point - self.position).cross(&force);
+	}
+
+	/// Adds a force applied at a point in body space, such as where a thruster is mounted
+	pub fn add_force_at_body_point(&mut self, force: Vector3, point: Vector3) {
+		self.add_force_at_point(force, self.point_in_world(point));
+	}
+
+	/// Adds a torque in world space
+	pub fn add_torque(&mut self, torque: Vector3) {
+		self.torque_accumulator += torque;
+	}
+
+	/// Instantly changes the body's velocity by an impulse at a point in world space
+	pub fn apply_impulse(&mut self, impulse: Vector3, point: Vector3) {
+		self.velocity += impulse * self.inverse_mass;
+		self.angular_velocity += self.angular_acceleration((point - self.position).cross(&impulse));
+	}
+
+	/// Instantly changes the body's angular velocity by an impulse in world space
+	pub fn apply_angular_impulse(&mut self, impulse: Vector3) {
+		self.angular_velocity += self.angular_acceleration(impulse);
+	}
+
+	/// The angular acceleration a world space torque causes, or the spin an angular impulse adds
+	#[must_use]
+	pub fn angular_acceleration(&self, torque: Vector3) -> Vector3 {
+		self.orientation.rotate(self.direction_in_body(torque) * self.inverse_inertia)
+	}
+
+	/// Integrates the body forward in time by the given amount using Newton-Euler integration,
+	/// then clears the accumulated forces and torques.
+	pub fn integrate(&mut self, duration: Real) {
+		self.integrate_velocity(duration);
+		self.integrate_position(duration);
+	}
+
+	/// The first half of `integrate`, which applies the accumulated forces to the velocities.
+	/// Constraints such as joints adjust the velocities before the second half moves the body.
+	pub fn integrate_velocity(&mut self, duration: Real) {
+		// Infinite mass should not be integrated
+		if self.inverse_mass <= 0.0 || duration <= 0.0 {
+			return;
+		}
+
+		// Update velocities from the accelerations
+		let acceleration = self.acceleration + self.force_accumulator * self.inverse_mass;
+		self.velocity += acceleration * duration;
+		self.angular_velocity += self.angular_acceleration(self.torque_accumulator) * duration;
+
+		// Impose drag
+		self.velocity *= self.linear_damping.powf(duration);
+		self.angular_velocity *= self.angular_damping.powf(duration);
+
+		// Clear any accumulated forces
+		self.force_accumulator = Vector3::zero();
+		self.torque_accumulator = Vector3::zero();
+	}
+
+	/// The second half of `integrate`, which moves and turns the body by its velocities
+	pub fn integrate_position(&mut self, duration: Real) {
+		if self.inverse_mass <= 0.0 || duration <= 0.0 {
+			return;
+		}
+		self.position += self.velocity * duration;
+		self.orientation = self.orientation.integrate(self.angular_velocity, duration);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_vectors_equal(actual: Vector3, expected: Vector3) {
+		assert!((actual - expected).magnitude() < 1e-4, "left: {:?} not equal right: {:?}", actual, expected);
+	}
+
+	#[test]
+	pub fn off_center_forces_turn_the_body() {
+		let mut body = RigidBody::cuboid(2.0, Vector3::new(1.0, 1.0, 1.0));
+		body.add_force_at_body_point(Vector3::y_axis(), Vector3::x_axis());
+		assert_vectors_equal(body.torque_accumulator, Vector3::z_axis());
+
+		body.integrate(1.0);
+		assert_vectors_equal(body.velocity, Vector3::new(0.0, 0.5, 0.0));
+		assert_vectors_equal(body.angular_velocity, Vector3::new(0.0, 0.0, 0.75));
+		assert_vectors_equal(body.torque_accumulator, Vector3::zero());
+		assert!(body.orientation.rotate(Vector3::x_axis()).y() > 0.0);
+	}
+
+	#[test]
+	pub fn immovable_bodies() {
+		let mut body = RigidBody::default();
+		body.add_torque(Vector3::z_axis());
+		body.integrate(1.0);
+		assert!(!body.has_finite_mass());
+		assert_vectors_equal(body.angular_velocity, Vector3::zero());
+	}
+}
@@ -0,0 +1,308 @@
+use crate::{Particle, Plane};
+use alloc::{
+	collections::{BTreeMap, BTreeSet},
+	vec,
+	vec::Vec,
+};
+#[cfg(not(feature = "std"))]
+use math::Float;
+use math::{Real, Vector3};
+
+/// Holds two particles of a soft body at a distance from each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftBodySpring {
+	pub a: usize,
+	pub b: usize,
+	pub rest_length: Real,
+}
+
+/// A closed mesh of particles held in shape by springs along its edges and the gas inside it,
+/// following the mass-aggregate approach. Squashing it raises the pressure pushing it back out.
+#[derive(Debug, Clone)]
+pub struct SoftBody {
+	pub particles: Vec<Particle>,
+
+	/// Triangles of particle indices, wound counterclockwise when seen from outside
+	pub triangles: Vec<[usize; 3]>,
+
+	pub springs: Vec<SoftBodySpring>,
+
+	/// Spring force per unit of stretch
+	pub stiffness: Real,
+
+	/// Spring force per unit per second of stretching, which stops the surface wobbling
+	pub damping: Real,
+
+	/// The amount of gas inside, as pressure multiplied by volume.
+	/// The pressure at any moment is this divided by the current volume.
+	pub gas: Real,
+
+	/// The pressure of the air outside, which the gas pushes against
+	pub ambient_pressure: Real,
+}
+
+impl SoftBody {
+	/// Builds a soft body from a closed mesh, with springs along every edge at their lengths
+	pub fn new(positions: &[Vector3], triangles: Vec<[usize; 3]>, mass: Real) -> Self {
+		let particle_mass = mass / positions.len() as Real;
+		let particles = positions
+			.iter()
+			.map(|position| Particle {
+				position: *position,
+				inverse_mass: particle_mass.recip(),
+				damping: 0.99,
+				..Default::default()
+			})
+			.collect::<Vec<_>>();
+		let edges = triangles
+			.iter()
+			.flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+			.map(|(a, b)| (a.min(b), a.max(b)))
+			.collect::<BTreeSet<_>>();
+		let springs = edges
+			.into_iter()
+			.map(|(a, b)| SoftBodySpring {
+				a,
+				b,
+				rest_length: (positions[b] - positions[a]).magnitude(),
+			})
+			.collect();
+		Self {
+			particles,
+			triangles,
+			springs,
+			stiffness: 200.0,
+			damping: 1.0,
+			gas: 0.0,
+			ambient_pressure: 0.0,
+		}
+	}
+
+	/// A ball made by subdividing an icosahedron, with gas balancing the air outside at rest.
+	/// Higher pressures make firmer balls, since squashing them raises the pressure further.
+	pub fn sphere(center: Vector3, radius: Real, subdivisions: usize, mass: Real, pressure: Real) -> Self {
+		let (directions, triangles) = icosphere(subdivisions);
+		let positions = directions.iter().map(|direction| center + *direction * radius).collect::<Vec<_>>();
+		let mut body = Self::new(&positions, triangles, mass);
+		body.gas = pressure * body.volume();
+		body.ambient_pressure = pressure;
+		body
+	}
+
+	/// Gives every particle a constant acceleration, such as gravity
+	pub fn set_acceleration(&mut self, acceleration: Vector3) {
+		self.particles.iter_mut().for_each(|particle| particle.acceleration = acceleration);
+	}
+
+	/// The volume enclosed by the mesh, summing the tetrahedra from the origin to each triangle
+	pub fn volume(&self) -> Real {
+		self.triangles
+			.iter()
+			.map(|triangle| {
+				let [a, b, c] = triangle.map(|index| self.particles[index].position);
+				a.dot(&b.cross(&c)) / 6.0
+			})
+			.sum()
+	}
+
+	/// The pressure of the gas inside, which rises as the body is squashed
+	pub fn pressure(&self) -> Real {
+		let volume = self.volume();
+		if volume <= Real::EPSILON {
+			return 0.0;
+		}
+		self.gas / volume
+	}
+
+	/// The average position of the particles
+	pub fn center(&self) -> Vector3 {
+		let sum = self.particles.iter().fold(Vector3::zero(), |sum, particle| sum + particle.position);
+		sum * (self.particles.len().max(1) as Real).recip()
+	}
+
+	/// Adds the spring and pressure forces to the particles
+	pub fn update_forces(&mut self) {
+		for spring in self.springs.iter() {
+			let (a, b) = (&self.particles[spring.a], &self.particles[spring.b]);
+			let offset = b.position - a.position;
+			let length = offset.magnitude();
+			if length <= Real::EPSILON {
+				continue;
+			}
+			let direction = offset * length.recip();
+			let stretch_speed = (b.velocity - a.velocity).dot(&direction);
+			let force = direction * (self.stiffness * (length - spring.rest_length) + self.damping * stretch_speed);
+			self.particles[spring.a].add_force(force);
+			self.particles[spring.b].add_force(force.inverse());
+		}
+
+		// Each triangle is pushed outward by the pressure difference over its area
+		let pressure = self.pressure() - self.ambient_pressure;
+		for triangle in self.triangles.iter() {
+			let [a, b, c] = triangle.map(|index| self.particles[index].position);
+			let area_normal = (b - a).cross(&(c - a)) * 0.5;
+			let force = area_normal * (pressure / 3.0);
+			for index in triangle {
+				self.particles[*index].add_force(force);
+			}
+		}
+	}
+
+	/// Moves the particles with semi-implicit Euler integration, which stiff springs need
+	pub fn integrate(&mut self, duration: Real) {
+		for particle in self.particles.iter_mut() {
+			particle.integrate_velocity(duration);
+			particle.integrate_position(duration);
+		}
+	}
+
+	/// Adds the body's forces, then integrates it
+	pub fn step(&mut self, duration: Real) {
+		self.update_forces();
+		self.integrate(duration);
+	}
+
+	/// Keeps particles on the front side of a plane, removing the velocity carrying them through
+	/// it and the given fraction of the velocity sliding along it.
+	pub fn collide_with_plane(&mut self, plane: &Plane, friction: Real) {
+		for particle in self.particles.iter_mut() {
+			let depth = plane.distance - particle.position.dot(&plane.normal);
+			if depth <= 0.0 {
+				continue;
+			}
+			particle.position += plane.normal * depth;
+			let approach = particle.velocity.dot(&plane.normal);
+			let normal_velocity = plane.normal * approach.min(0.0);
+			let sliding = particle.velocity - plane.normal * approach;
+			particle.velocity = particle.velocity - normal_velocity - sliding * friction.clamp(0.0, 1.0);
+		}
+	}
+}
+
+/// The unit sphere vertices and outward triangles of an icosahedron split `subdivisions` times
+fn icosphere(subdivisions: usize) -> (Vec<Vector3>, Vec<[usize; 3]>) {
+	let t = (1.0 + (5.0 as Real).sqrt()) / 2.0;
+	let mut vertices = [
+		(-1.0, t, 0.0),
+		(1.0, t, 0.0),
+		(-1.0, -t, 0.0),
+		(1.0, -t, 0.0),
+		(0.0, -1.0, t),
+		(0.0, 1.0, t),
+		(0.0, -1.0, -t),
+		(0.0, 1.0, -t),
+		(t, 0.0, -1.0),
+		(t, 0.0, 1.0),
+		(-t, 0.0, -1.0),
+		(-t, 0.0, 1.0),
+	]
+	.map(|(x, y, z)| Vector3::new(x, y, z).normalize())
+	.to_vec();
+	let mut triangles = vec![
+		[0, 11, 5],
+		[0, 5, 1],
+		[0, 1, 7],
+		[0, 7, 10],
+		[0, 10, 11],
+		[1, 5, 9],
+		[5, 11, 4],
+		[11, 10, 2],
+		[10, 7, 6],
+		[7, 1, 8],
+		[3, 9, 4],
+		[3, 4, 2],
+		[3, 2, 6],
+		[3, 6, 8],
+		[3, 8, 9],
+		[4, 9, 5],
+		[2, 4, 11],
+		[6, 2, 10],
+		[8, 6, 7],
+		[9, 8, 1],
+	];
+
+	for _ in 0..subdivisions {
+		// Split each edge at its midpoint once, sharing the vertex between both triangles
+		let mut midpoints = BTreeMap::new();
+		let mut midpoint = |a: usize, b: usize, vertices: &mut Vec<Vector3>| {
+			*midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+				vertices.push(((vertices[a] + vertices[b]) * 0.5).normalize());
+				vertices.len() - 1
+			})
+		};
+		triangles = triangles
+			.into_iter()
+			.flat_map(|[a, b, c]| {
+				let (ab, bc, ca) = (midpoint(a, b, &mut vertices), midpoint(b, c, &mut vertices), midpoint(c, a, &mut vertices));
+				[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+			})
+			.collect();
+	}
+
+	(vertices, triangles)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use core::f32::consts::PI;
+
+	const STEP: Real = 1.0 / 600.0;
+
+	fn ball(pressure: Real) -> SoftBody {
+		let mut ball = SoftBody::sphere(Vector3::new(0.0, 2.0, 0.0), 1.0, 2, 1.0, pressure);
+		ball.set_acceleration(Vector3::new(0.0, -10.0, 0.0));
+		ball
+	}
+
+	fn drop(ball: &mut SoftBody, seconds: Real) {
+		let ground = Plane::ground(0.0);
+		for _ in 0..(seconds / STEP) as usize {
+			ball.step(STEP);
+			ball.collide_with_plane(&ground, 0.5);
+		}
+	}
+
+	#[test]
+	pub fn spheres() {
+		let ball = SoftBody::sphere(Vector3::zero(), 1.0, 2, 1.0, 50.0);
+		assert_eq!(ball.particles.len(), 162);
+		assert_eq!(ball.triangles.len(), 320);
+		assert_eq!(ball.springs.len(), 480);
+
+		// The mesh is slightly smaller than the sphere it approximates
+		let volume = ball.volume();
+		assert!(volume > 0.9 * 4.0 / 3.0 * PI && volume < 4.0 / 3.0 * PI, "{volume}");
+		assert!((ball.pressure() - 50.0).abs() < 1e-3);
+	}
+
+	#[test]
+	pub fn pressure_pushes_outward() {
+		let mut ball = SoftBody::sphere(Vector3::zero(), 1.0, 1, 1.0, 10.0);
+		ball.update_forces();
+		assert!(ball.particles.iter().all(|particle| particle.force_accumulator.magnitude() < 1e-4));
+
+		ball.particles.iter_mut().for_each(|particle| particle.position *= 0.9);
+		ball.update_forces();
+		for particle in ball.particles.iter() {
+			assert!(particle.force_accumulator.dot(&particle.position) > 0.0);
+		}
+	}
+
+	#[test]
+	pub fn inflated_balls_hold_their_shape() {
+		let mut firm = ball(2_000.0);
+		let mut soft = ball(20.0);
+		drop(&mut firm, 3.0);
+		drop(&mut soft, 3.0);
+
+		for ball in [&firm, &soft] {
+			assert!(ball.particles.iter().all(|particle| particle.position.y() > -1e-4));
+		}
+
+		// Softer balls squash further under their weight, raising the pressure inside
+		assert!(firm.center().y() > soft.center().y());
+		assert!(firm.center().y() > 0.8 && firm.center().y() < 1.0);
+		assert!(soft.pressure() > 20.0);
+	}
+}
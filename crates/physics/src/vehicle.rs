@@ -0,0 +1,426 @@
+use crate::{Ray, Raycast, RigidBody};
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use math::Float;
+use math::{Quaternion, Real, Transform, Vector3};
+
+/// How much grip a tire has as it slips, as a fraction of the load pressing it into the ground.
+///
+/// Grip rises with slip up to a peak, then falls off as the tire starts to slide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlipCurve {
+	pub peak_slip: Real,
+	pub peak_friction: Real,
+
+	/// The slip past which the tire is fully sliding
+	pub sliding_slip: Real,
+	pub sliding_friction: Real,
+}
+
+impl SlipCurve {
+	/// Grip along the direction the wheel rolls, by slip ratio
+	pub const fn longitudinal() -> Self {
+		Self {
+			peak_slip: 0.1,
+			peak_friction: 1.0,
+			sliding_slip: 0.5,
+			sliding_friction: 0.8,
+		}
+	}
+
+	/// Grip across the wheel, by slip angle in radians
+	pub const fn lateral() -> Self {
+		Self {
+			peak_slip: 0.15,
+			peak_friction: 1.0,
+			sliding_slip: 0.6,
+			sliding_friction: 0.7,
+		}
+	}
+
+	/// The friction coefficient for a slip, with the slip's sign
+	pub fn friction(&self, slip: Real) -> Real {
+		let amount = slip.abs();
+		let coefficient = if amount < self.peak_slip {
+			self.peak_friction * amount / self.peak_slip
+		} else if amount < self.sliding_slip {
+			let t = (amount - self.peak_slip) / (self.sliding_slip - self.peak_slip);
+			self.peak_friction + (self.sliding_friction - self.peak_friction) * t
+		} else {
+			self.sliding_friction
+		};
+		coefficient * slip.signum()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelSettings {
+	/// Where the top of the suspension is mounted, in the vehicle body's space
+	pub position: Vector3,
+
+	pub radius: Real,
+	pub mass: Real,
+
+	/// How far the suspension extends below the mount when nothing presses on it
+	pub rest_length: Real,
+
+	/// Force per unit of suspension compression
+	pub stiffness: Real,
+
+	/// Force per unit per second of suspension compression, which stops the body from bouncing
+	pub damping: Real,
+
+	/// Whether the wheel turns with the steering
+	pub steered: bool,
+
+	/// Whether the engine turns the wheel
+	pub driven: bool,
+
+	pub longitudinal: SlipCurve,
+	pub lateral: SlipCurve,
+}
+
+impl WheelSettings {
+	pub const fn new(position: Vector3, radius: Real) -> Self {
+		Self {
+			position,
+			radius,
+			mass: 20.0,
+			rest_length: 0.4,
+			stiffness: 30_000.0,
+			damping: 3_000.0,
+			steered: false,
+			driven: false,
+			longitudinal: SlipCurve::longitudinal(),
+			lateral: SlipCurve::lateral(),
+		}
+	}
+
+	fn inertia(&self) -> Real {
+		0.5 * self.mass * self.radius * self.radius
+	}
+}
+
+/// Where a wheel touches the ground during the latest update
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelContact {
+	pub point: Vector3,
+	pub normal: Vector3,
+
+	/// The suspension force pressing the tire into the ground
+	pub load: Real,
+
+	/// How much faster the tire's surface moves than the ground, relative to the wheel's speed
+	pub slip_ratio: Real,
+
+	/// The angle in radians between where the wheel points and where it is moving
+	pub slip_angle: Real,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wheel {
+	pub settings: WheelSettings,
+
+	/// How far the suspension is pushed in from its rest length
+	pub compression: Real,
+
+	pub contact: Option<WheelContact>,
+
+	/// The wheel's spin around its axle in radians per second
+	pub spin: Real,
+
+	/// The wheel's angle around its axle, for rendering
+	pub rotation: Real,
+
+	/// How far the wheel is turned by the steering in radians, counterclockwise seen from above
+	pub steering_angle: Real,
+}
+
+impl Wheel {
+	pub const fn new(settings: WheelSettings) -> Self {
+		Self {
+			settings,
+			compression: 0.0,
+			contact: None,
+			spin: 0.0,
+			rotation: 0.0,
+			steering_angle: 0.0,
+		}
+	}
+
+	/// The current length of the suspension between the mount and the wheel's center
+	pub fn suspension_length(&self) -> Real {
+		self.settings.rest_length - self.compression
+	}
+
+	fn update(&mut self, body: &mut RigidBody, ground: &(impl Raycast + ?Sized), drive: &WheelDrive, duration: Real) {
+		let WheelDrive {
+			share,
+			drive_torque,
+			brake_torque,
+		} = *drive;
+		let settings = self.settings;
+		let inertia = settings.inertia();
+
+		// The engine spins driven wheels, and the brakes slow every wheel without reversing it
+		if settings.driven {
+			self.spin += drive_torque / inertia * duration;
+		}
+		let braking = brake_torque / inertia * duration;
+		self.spin -= self.spin.clamp(-braking, braking);
+
+		let up = body.orientation.rotate(Vector3::y_axis());
+		let ray = Ray::new(body.point_in_world(settings.position), up.inverse());
+		let previous_compression = self.compression;
+		let hit = ground.raycast(&ray, settings.rest_length + settings.radius);
+		self.compression = hit.map(|hit| settings.rest_length + settings.radius - hit.distance).unwrap_or_default();
+		self.contact = None;
+
+		if let Some(hit) = hit {
+			let compression_speed = (self.compression - previous_compression) / duration;
+			let load = (settings.stiffness * self.compression + settings.damping * compression_speed).max(0.0);
+
+			// The wheel's heading, flattened onto the ground so friction pushes along the surface
+			let rotation = body.orientation * Quaternion::from_axis_angle(Vector3::y_axis(), self.steering_angle);
+			let along_ground = |direction: Vector3| (direction - hit.normal * direction.dot(&hit.normal)).normalize();
+			let (forward, side) = (along_ground(rotation.rotate(Vector3::z_axis())), along_ground(rotation.rotate(Vector3::x_axis())));
+
+			let velocity = body.velocity_at_point(hit.point);
+			let (forward_speed, side_speed) = (velocity.dot(&forward), velocity.dot(&side));
+			let surface_speed = self.spin * settings.radius - forward_speed;
+
+			// Slip is measured against at least walking pace, so it stays sane at rest
+			let reference_speed = forward_speed.abs().max(1.0);
+			let slip_ratio = surface_speed / reference_speed;
+			let slip_angle = side_speed.atan2(reference_speed);
+
+			// Friction can stop the slip within a step but never reverses it, which would jitter
+			let longitudinal_limit = surface_speed.abs() / (duration * (settings.radius.powi(2) / inertia + (share * effective_mass(body, hit.point, forward)).recip()));
+			let lateral_limit = side_speed.abs() * share * effective_mass(body, hit.point, side) / duration;
+			let mut longitudinal = (load * settings.longitudinal.friction(slip_ratio)).clamp(-longitudinal_limit, longitudinal_limit);
+			let mut lateral = (-load * settings.lateral.friction(slip_angle)).clamp(-lateral_limit, lateral_limit);
+
+			// Both directions draw on the same grip
+			let grip = load * settings.longitudinal.peak_friction.max(settings.lateral.peak_friction);
+			let total = longitudinal.hypot(lateral);
+			if total > grip {
+				longitudinal *= grip / total;
+				lateral *= grip / total;
+			}
+
+			self.spin -= longitudinal * settings.radius / inertia * duration;
+			body.add_force_at_point(up * load + forward * longitudinal + side * lateral, hit.point);
+			self.contact = Some(WheelContact {
+				point: hit.point,
+				normal: hit.normal,
+				load,
+				slip_ratio,
+				slip_angle,
+			});
+		}
+
+		self.rotation = (self.rotation + self.spin * duration) % core::f32::consts::TAU;
+	}
+}
+
+/// What the vehicle's controls ask of each wheel during an update
+struct WheelDrive {
+	/// Each wheel's share of the body's mass, which limits how hard its friction pushes
+	share: Real,
+
+	drive_torque: Real,
+	brake_torque: Real,
+}
+
+/// A car built on a single rigid body whose wheels are rays cast down from the body,
+/// with springs for suspension and slip curves for tire grip.
+///
+/// The body faces along its +Z axis with +Y up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vehicle {
+	pub wheels: Vec<Wheel>,
+
+	/// The torque shared between the driven wheels at full throttle
+	pub max_engine_torque: Real,
+
+	/// The torque applied to each wheel at full brake
+	pub max_brake_torque: Real,
+
+	/// How far steered wheels turn at full lock, in radians
+	pub max_steering_angle: Real,
+
+	/// From -1 for full reverse to 1 for full throttle
+	pub throttle: Real,
+
+	/// From 0 to 1
+	pub brake: Real,
+
+	/// From -1 for full left to 1 for full right
+	pub steering: Real,
+}
+
+impl Default for Vehicle {
+	fn default() -> Self {
+		Self {
+			wheels: Vec::new(),
+			max_engine_torque: 1_000.0,
+			max_brake_torque: 3_000.0,
+			max_steering_angle: 0.5,
+			throttle: 0.0,
+			brake: 0.0,
+			steering: 0.0,
+		}
+	}
+}
+
+impl Vehicle {
+	/// A car with steered front wheels and driven rear wheels at the corners of a rectangle
+	pub fn four_wheeled(half_track: Real, half_wheelbase: Real, height: Real, radius: Real) -> Self {
+		let mut vehicle = Self::default();
+		for (x, z) in [(-1.0, 1.0), (1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)] {
+			let front = z > 0.0;
+			vehicle.add_wheel(WheelSettings {
+				steered: front,
+				driven: !front,
+				..WheelSettings::new(Vector3::new(x * half_track, height, z * half_wheelbase), radius)
+			});
+		}
+		vehicle
+	}
+
+	/// Adds a wheel, returning its index
+	pub fn add_wheel(&mut self, settings: WheelSettings) -> usize {
+		self.wheels.push(Wheel::new(settings));
+		self.wheels.len() - 1
+	}
+
+	/// Casts the wheels against the ground and adds their suspension and tire forces to the body.
+	/// Integrate the body afterward, directly or through a `JointSolver` step.
+	pub fn update(&mut self, body: &mut RigidBody, ground: &(impl Raycast + ?Sized), duration: Real) {
+		if duration <= 0.0 || self.wheels.is_empty() {
+			return;
+		}
+		let driven = self.wheels.iter().filter(|wheel| wheel.settings.driven).count().max(1) as Real;
+		let drive_torque = self.throttle.clamp(-1.0, 1.0) * self.max_engine_torque / driven;
+		let brake_torque = self.brake.clamp(0.0, 1.0) * self.max_brake_torque;
+		// Turning right is turning clockwise seen from above, which is a negative angle around +Y
+		let steering_angle = -self.steering.clamp(-1.0, 1.0) * self.max_steering_angle;
+		let drive = WheelDrive {
+			share: (self.wheels.len() as Real).recip(),
+			drive_torque,
+			brake_torque,
+		};
+		for wheel in self.wheels.iter_mut() {
+			wheel.steering_angle = if wheel.settings.steered { steering_angle } else { 0.0 };
+			wheel.update(body, ground, &drive, duration);
+		}
+	}
+
+	/// The body's speed along its heading, negative when reversing
+	pub fn speed(&self, body: &RigidBody) -> Real {
+		body.velocity.dot(&body.orientation.rotate(Vector3::z_axis()))
+	}
+
+	/// Where to draw a wheel in world space
+	pub fn wheel_transform(&self, body: &RigidBody, index: usize) -> Option<Transform> {
+		let wheel = self.wheels.get(index)?;
+		let center = wheel.settings.position - Vector3::y_axis() * wheel.suspension_length();
+		let steering = Quaternion::from_axis_angle(Vector3::y_axis(), wheel.steering_angle);
+		let spin = Quaternion::from_axis_angle(Vector3::x_axis(), wheel.rotation);
+		Some(Transform {
+			translation: body.point_in_world(center),
+			rotation: body.orientation * steering * spin,
+			..Transform::identity()
+		})
+	}
+}
+
+/// The mass a body resists a push at a point along a direction with, counting how it turns
+fn effective_mass(body: &RigidBody, point: Vector3, direction: Vector3) -> Real {
+	let offset = point - body.position;
+	let inverse = body.inverse_mass + direction.dot(&body.angular_acceleration(offset.cross(&direction)).cross(&offset));
+	if inverse <= 0.0 { 0.0 } else { inverse.recip() }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Plane;
+
+	const STEP: Real = 1.0 / 120.0;
+
+	fn car() -> (Vehicle, RigidBody) {
+		let body = RigidBody {
+			position: Vector3::new(0.0, 1.0, 0.0),
+			acceleration: Vector3::new(0.0, -10.0, 0.0),
+			..RigidBody::cuboid(1_000.0, Vector3::new(0.9, 0.4, 2.0))
+		};
+		(Vehicle::four_wheeled(0.8, 1.4, -0.2, 0.35), body)
+	}
+
+	fn drive(vehicle: &mut Vehicle, body: &mut RigidBody, seconds: Real) {
+		let ground = Plane::ground(0.0);
+		for _ in 0..(seconds / STEP) as usize {
+			vehicle.update(body, &ground, STEP);
+			body.integrate(STEP);
+		}
+	}
+
+	#[test]
+	pub fn slip_curves() {
+		let curve = SlipCurve::longitudinal();
+		assert_eq!(curve.friction(0.0), 0.0);
+		assert!((curve.friction(-0.05) + 0.5).abs() < 1e-5);
+		assert_eq!(curve.friction(0.1), 1.0);
+		assert!((curve.friction(0.3) - 0.9).abs() < 1e-5);
+		assert_eq!(curve.friction(2.0), 0.8);
+	}
+
+	#[test]
+	pub fn suspension_holds_the_body_up() {
+		let (mut vehicle, mut body) = car();
+		drive(&mut vehicle, &mut body, 3.0);
+
+		assert!(vehicle.wheels.iter().all(|wheel| wheel.contact.is_some()));
+		let load = vehicle.wheels.iter().filter_map(|wheel| wheel.contact).map(|contact| contact.load).sum::<Real>();
+		assert!((load - 10_000.0).abs() < 100.0, "The wheels carry {load}");
+		assert!(body.velocity.magnitude() < 0.01);
+		assert!(body.position.y() > 0.5);
+	}
+
+	#[test]
+	pub fn throttle_and_brakes() {
+		let (mut vehicle, mut body) = car();
+		drive(&mut vehicle, &mut body, 1.0);
+
+		vehicle.throttle = 1.0;
+		drive(&mut vehicle, &mut body, 3.0);
+		let speed = vehicle.speed(&body);
+		assert!(speed > 5.0, "The car only reached {speed}");
+		assert!(body.position.x().abs() < 0.1);
+		let rear = vehicle.wheels[2];
+		assert!((rear.spin * rear.settings.radius - speed).abs() < 0.2 * speed);
+
+		vehicle.throttle = 0.0;
+		vehicle.brake = 1.0;
+		drive(&mut vehicle, &mut body, 5.0);
+		assert!(vehicle.speed(&body).abs() < 0.1);
+	}
+
+	#[test]
+	pub fn steering_turns_the_car() {
+		let (mut vehicle, mut body) = car();
+		drive(&mut vehicle, &mut body, 1.0);
+		vehicle.throttle = 0.5;
+		drive(&mut vehicle, &mut body, 2.0);
+
+		vehicle.steering = 1.0;
+		drive(&mut vehicle, &mut body, 1.0);
+		let heading = body.orientation.rotate(Vector3::z_axis());
+		assert!(heading.x() < -0.2, "The car is heading along {heading:?}");
+		assert!(body.orientation.rotate(Vector3::y_axis()).y() > 0.9);
+
+		let transform = vehicle.wheel_transform(&body, 0).unwrap();
+		assert!((transform.translation.y() - 0.35).abs() < 0.1);
+	}
+}
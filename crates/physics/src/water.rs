@@ -0,0 +1,212 @@
+use crate::{Aabb, ForceGenerator, RigidBody};
+use alloc::{collections::BTreeSet, vec::Vec};
+use math::{Real, Vector3};
+
+/// A box of still water whose surface is the top of its bounds, such as a pool or a stretch of sea
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterVolume {
+	pub bounds: Aabb,
+
+	/// Mass per cubic unit, which is 1000 for fresh water in kilograms per cubic meter
+	pub density: Real,
+
+	/// The strength of gravity, which the water's weight and so its buoyancy scale with
+	pub gravity: Real,
+
+	/// Force per unit of velocity on fully submerged objects, slowing anything in the water
+	pub drag: Real,
+}
+
+impl WaterVolume {
+	pub const fn new(bounds: Aabb) -> Self {
+		Self {
+			bounds,
+			density: 1000.0,
+			gravity: 9.81,
+			drag: 5.0,
+		}
+	}
+
+	/// The height of the water's surface
+	pub fn surface(&self) -> Real {
+		self.bounds.max.y()
+	}
+
+	pub fn contains(&self, point: Vector3) -> bool {
+		self.bounds.contains(point)
+	}
+
+	/// How much of an object is under water, from 0 to 1, spanning `max_depth` around a point
+	pub fn submersion(&self, point: Vector3, max_depth: Real) -> Real {
+		let (min, max) = (self.bounds.min, self.bounds.max);
+		let over_water = (min.x()..=max.x()).contains(&point.x()) && (min.z()..=max.z()).contains(&point.z());
+		if !over_water || point.y() < min.y() - max_depth || max_depth <= 0.0 {
+			return 0.0;
+		}
+		((self.surface() + max_depth - point.y()) / (2.0 * max_depth)).clamp(0.0, 1.0)
+	}
+
+	/// The force pushing an object of a volume at a point and velocity up, plus its drag.
+	/// Particles can add this directly, and rigid bodies can use a `Buoyancy` force generator.
+	pub fn force(&self, point: Vector3, velocity: Vector3, max_depth: Real, volume: Real) -> Vector3 {
+		let submersion = self.submersion(point, max_depth);
+		if submersion <= 0.0 {
+			return Vector3::zero();
+		}
+		let buoyancy = Vector3::y_axis() * (self.density * volume * submersion * self.gravity);
+		buoyancy - velocity * (self.drag * submersion)
+	}
+}
+
+/// Floats a rigid body in water, pushing up at a point so it also turns upright, like a hull
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Buoyancy {
+	/// Where the water pushes on the body, in body space
+	pub center_of_buoyancy: Vector3,
+
+	/// How far the body extends above and below the center of buoyancy
+	pub max_depth: Real,
+
+	/// The volume of water the body displaces when fully submerged
+	pub volume: Real,
+
+	pub water: WaterVolume,
+}
+
+impl ForceGenerator for Buoyancy {
+	fn update_force(&mut self, body: &mut RigidBody, _duration: Real) {
+		let point = body.point_in_world(self.center_of_buoyancy);
+		let force = self.water.force(point, body.velocity_at_point(point), self.max_depth, self.volume);
+		body.add_force_at_point(force, point);
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaterEventKind {
+	Entered,
+	Exited,
+}
+
+/// An object crossing into or out of a water volume, such as a round splashing into a pool
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaterEvent {
+	pub kind: WaterEventKind,
+
+	/// The id the object was given to `WaterTrigger::update`
+	pub object: usize,
+
+	pub position: Vector3,
+	pub velocity: Vector3,
+}
+
+/// Tracks which objects are inside a water volume, reporting when they enter or leave it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WaterTrigger {
+	inside: BTreeSet<usize>,
+}
+
+impl WaterTrigger {
+	/// Checks each object's id, position, and velocity against the water.
+	/// Objects that were inside but aren't passed anymore are forgotten without an event.
+	pub fn update(&mut self, water: &WaterVolume, objects: impl IntoIterator<Item = (usize, Vector3, Vector3)>) -> Vec<WaterEvent> {
+		let mut events = Vec::new();
+		let mut inside = BTreeSet::new();
+		for (object, position, velocity) in objects {
+			let is_inside = water.contains(position);
+			if is_inside {
+				inside.insert(object);
+			}
+			let kind = match (self.inside.contains(&object), is_inside) {
+				(false, true) => WaterEventKind::Entered,
+				(true, false) => WaterEventKind::Exited,
+				_ => continue,
+			};
+			events.push(WaterEvent {
+				kind,
+				object,
+				position,
+				velocity,
+			});
+		}
+		self.inside = inside;
+		events
+	}
+
+	pub fn is_inside(&self, object: usize) -> bool {
+		self.inside.contains(&object)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ForceRegistry, Gravity};
+
+	fn pool() -> WaterVolume {
+		WaterVolume::new(Aabb {
+			min: Vector3::new(-10.0, -5.0, -10.0),
+			max: Vector3::new(10.0, 0.0, 10.0),
+		})
+	}
+
+	#[test]
+	pub fn submersion() {
+		let water = pool();
+		assert_eq!(water.submersion(Vector3::new(0.0, 2.0, 0.0), 1.0), 0.0);
+		assert_eq!(water.submersion(Vector3::new(0.0, 0.0, 0.0), 1.0), 0.5);
+		assert_eq!(water.submersion(Vector3::new(0.0, -3.0, 0.0), 1.0), 1.0);
+		assert_eq!(water.submersion(Vector3::new(20.0, -3.0, 0.0), 1.0), 0.0);
+
+		let force = water.force(Vector3::new(0.0, -3.0, 0.0), Vector3::new(0.0, 0.0, 2.0), 1.0, 0.01);
+		assert!((force - Vector3::new(0.0, 98.1, -10.0)).magnitude() < 1e-3);
+	}
+
+	#[test]
+	pub fn light_bodies_float() {
+		// Half a cubic meter of wood at 500 kilograms per cubic meter floats half submerged
+		let mut bodies = [RigidBody {
+			position: Vector3::new(0.0, 2.0, 0.0),
+			linear_damping: 0.5,
+			..RigidBody::cuboid(250.0, Vector3::new(0.5, 0.25, 0.5))
+		}];
+		let mut registry = ForceRegistry::default();
+		registry.add(
+			0,
+			Gravity {
+				gravity: Vector3::new(0.0, -9.81, 0.0),
+			},
+		);
+		registry.add(
+			0,
+			Buoyancy {
+				center_of_buoyancy: Vector3::zero(),
+				max_depth: 0.25,
+				volume: 0.5,
+				water: pool(),
+			},
+		);
+		for _ in 0..2000 {
+			registry.update_forces(&mut bodies, 0.01);
+			bodies[0].integrate(0.01);
+		}
+		assert!(bodies[0].position.y().abs() < 0.01, "The body settled at {}", bodies[0].position.y());
+	}
+
+	#[test]
+	pub fn triggers() {
+		let water = pool();
+		let mut trigger = WaterTrigger::default();
+		let (above, below, velocity) = (Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, -1.0, 0.0), Vector3::new(0.0, -5.0, 0.0));
+
+		let events = trigger.update(&water, [(0, above, velocity), (1, below, velocity)]);
+		assert_eq!((events[0].kind, events[0].object), (WaterEventKind::Entered, 1));
+		assert!(trigger.is_inside(1));
+		let events = trigger.update(&water, [(0, below, velocity), (1, below, velocity)]);
+		assert_eq!(events.len(), 1);
+		assert_eq!((events[0].kind, events[0].object), (WaterEventKind::Entered, 0));
+
+		let events = trigger.update(&water, [(0, above, velocity.inverse())]);
+		assert_eq!((events[0].kind, events[0].object), (WaterEventKind::Exited, 0));
+		assert!(!trigger.is_inside(0) && !trigger.is_inside(1));
+	}
+}
@@ -0,0 +1,283 @@
+use crate::{ForceGenerator, ForceRegistry, Joint, JointSolver, Particle, RigidBody};
+use handle::{Handle, SlotMap};
+use math::{Real, Vector3};
+
+/// Refers to a rigid body in a `PhysicsWorld`, staying invalid after its slot is reused
+pub type BodyHandle = Handle<RigidBody>;
+
+/// Refers to a particle in a `PhysicsWorld`
+pub type ParticleHandle = Handle<Particle>;
+
+/// Refers to a joint in a `PhysicsWorld`
+pub type JointHandle = Handle<Joint>;
+
+/// Particles and rigid bodies with the forces and joints acting on them, stepped together.
+///
+/// This drives elder's physics without an ECS world, for tools, tests, and headless simulations.
+/// ECS games can keep one as a resource and store handles in components.
+#[derive(Default)]
+pub struct PhysicsWorld {
+	/// Pulls every particle and body with finite mass down, in addition to their own accelerations
+	pub gravity: Vector3,
+
+	pub forces: ForceRegistry,
+
+	bodies: SlotMap<RigidBody>,
+	particles: SlotMap<Particle>,
+	joints: SlotMap<Joint>,
+	solver: JointSolver,
+	time: Real,
+}
+
+impl PhysicsWorld {
+	pub fn new(gravity: Vector3) -> Self {
+		Self { gravity, ..Default::default() }
+	}
+
+	pub fn add_body(&mut self, body: RigidBody) -> BodyHandle {
+		self.bodies.insert(body)
+	}
+
+	/// Removes a body along with the force generators and joints acting on it
+	pub fn remove_body(&mut self, handle: BodyHandle) -> Option<RigidBody> {
+		let index = self.bodies.dense_index(handle)?;
+		let body = self.bodies.remove(handle)?;
+		self.forces.remove(index);
+		self.joints.retain(|_, joint| joint.body_a != index && joint.body_b != index);
+
+		// The last body was moved into the removed body's place
+		let moved = self.bodies.len();
+		if index != moved {
+			self.forces.move_body(moved, index);
+			for joint in self.joints.values_mut() {
+				if joint.body_a == moved {
+					joint.body_a = index;
+				}
+				if joint.body_b == moved {
+					joint.body_b = index;
+				}
+			}
+		}
+		Some(body)
+	}
+
+	pub fn body(&self, handle: BodyHandle) -> Option<&RigidBody> {
+		self.bodies.get(handle)
+	}
+
+	pub fn body_mut(&mut self, handle: BodyHandle) -> Option<&mut RigidBody> {
+		self.bodies.get_mut(handle)
+	}
+
+	pub fn bodies(&self) -> impl Iterator<Item = (BodyHandle, &RigidBody)> {
+		self.bodies.iter()
+	}
+
+	pub fn body_count(&self) -> usize {
+		self.bodies.len()
+	}
+
+	pub fn add_particle(&mut self, particle: Particle) -> ParticleHandle {
+		self.particles.insert(particle)
+	}
+
+	pub fn remove_particle(&mut self, handle: ParticleHandle) -> Option<Particle> {
+		self.particles.remove(handle)
+	}
+
+	pub fn particle(&self, handle: ParticleHandle) -> Option<&Particle> {
+		self.particles.get(handle)
+	}
+
+	pub fn particle_mut(&mut self, handle: ParticleHandle) -> Option<&mut Particle> {
+		self.particles.get_mut(handle)
+	}
+
+	pub fn particles(&self) -> impl Iterator<Item = (ParticleHandle, &Particle)> {
+		self.particles.iter()
+	}
+
+	pub fn particle_count(&self) -> usize {
+		self.particles.len()
+	}
+
+	/// Registers a force generator acting on a body, returning false if the body doesn't exist
+	pub fn add_force(&mut self, body: BodyHandle, generator: impl ForceGenerator + 'static) -> bool {
+		let Some(index) = self.bodies.dense_index(body) else {
+			return false;
+		};
+		self.forces.add(index, generator);
+		true
+	}
+
+	/// Joins two bodies with a joint built from their current placement, such as
+	/// `world.add_joint(door, frame, |bodies, a, b| Joint::hinge(bodies, a, b, anchor, axis))`.
+	/// Returns `None` if either body doesn't exist.
+	pub fn add_joint(&mut self, a: BodyHandle, b: BodyHandle, build: impl FnOnce(&[RigidBody], usize, usize) -> Joint) -> Option<JointHandle> {
+		let (a, b) = (self.bodies.dense_index(a)?, self.bodies.dense_index(b)?);
+		Some(self.joints.insert(build(self.bodies.values(), a, b)))
+	}
+
+	pub fn remove_joint(&mut self, handle: JointHandle) -> Option<Joint> {
+		self.joints.remove(handle)
+	}
+
+	pub fn joint(&self, handle: JointHandle) -> Option<&Joint> {
+		self.joints.get(handle)
+	}
+
+	/// How far a hinge has turned or a slider has moved
+	pub fn joint_position(&self, handle: JointHandle) -> Option<Real> {
+		self.joint(handle).map(|joint| joint.position(self.bodies.values()))
+	}
+
+	/// How many times joints are solved per step
+	pub fn set_joint_iterations(&mut self, iterations: usize) {
+		self.solver.iterations = iterations;
+	}
+
+	/// The total time simulated
+	pub const fn time(&self) -> Real {
+		self.time
+	}
+
+	/// Applies gravity and forces, solves the joints, and integrates everything by `duration`
+	pub fn step(&mut self, duration: Real) {
+		if duration <= 0.0 {
+			return;
+		}
+
+		for body in self.bodies.values_mut().iter_mut().filter(|body| body.has_finite_mass()) {
+			body.add_force(self.gravity * body.mass());
+		}
+		self.forces.update_forces(self.bodies.values_mut(), duration);
+		self.solver.step_joints(self.joints.values_mut(), self.bodies.values_mut(), duration);
+
+		for particle in self.particles.values_mut().iter_mut().filter(|particle| particle.has_finite_mass()) {
+			particle.add_force(self.gravity * particle.mass());
+			particle.integrate(duration);
+		}
+
+		self.time += duration;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Motor;
+	use alloc::vec::Vec;
+
+	fn cube() -> RigidBody {
+		RigidBody::cuboid(1.0, Vector3::new(0.5, 0.5, 0.5))
+	}
+
+	#[test]
+	pub fn handles() {
+		let mut world = PhysicsWorld::default();
+		let first = world.add_body(cube());
+		let second = world.add_body(cube());
+		assert!(world.remove_body(first).is_some());
+		assert!(world.remove_body(first).is_none());
+
+		// The vacated slot is reused, but the old handle doesn't reach the new body
+		let third = world.add_body(cube());
+		assert_eq!(third.index(), first.index());
+		assert!(world.body(first).is_none());
+		assert!(world.body(third).is_some());
+		assert_eq!(world.bodies().map(|(handle, _)| handle).collect::<Vec<_>>(), [second, third]);
+
+		let particle = world.add_particle(Particle::default());
+		assert_eq!(world.particle_count(), 1);
+		assert!(world.remove_particle(particle).is_some());
+		assert!(world.particle_mut(particle).is_none());
+	}
+
+	#[test]
+	pub fn gravity() {
+		let mut world = PhysicsWorld::new(Vector3::new(0.0, -10.0, 0.0));
+		let body = world.add_body(cube());
+		let particle = world.add_particle(Particle {
+			inverse_mass: 1.0,
+			damping: 1.0,
+			..Default::default()
+		});
+		let anchor = world.add_body(RigidBody::default());
+		for _ in 0..100 {
+			world.step(0.01);
+		}
+		assert!((world.time() - 1.0).abs() < 1e-4);
+		assert!((world.body(body).unwrap().velocity.y() + 10.0).abs() < 1e-3);
+		assert!((world.particle(particle).unwrap().velocity.y() + 10.0).abs() < 1e-3);
+		assert_eq!(world.body(anchor).unwrap().position, Vector3::zero());
+	}
+
+	#[test]
+	pub fn removing_bodies_removes_their_forces_and_joints() {
+		let mut world = PhysicsWorld::default();
+		let frame = world.add_body(RigidBody::default());
+		let wheel = world.add_body(cube());
+		let hinge = world.add_joint(frame, wheel, |bodies, a, b| Joint::hinge(bodies, a, b, Vector3::zero(), Vector3::y_axis()));
+		assert!(hinge.is_some());
+		let motor = Motor {
+			axis: Vector3::y_axis(),
+			target_speed: 1.0,
+			gain: 10.0,
+			max_torque: 10.0,
+		};
+		assert!(world.add_force(wheel, motor));
+		for _ in 0..100 {
+			world.step(0.01);
+		}
+		assert!(world.joint_position(hinge.unwrap()).unwrap() > 0.1);
+
+		world.remove_body(wheel);
+		assert!(world.forces.is_empty());
+		assert!(world.joint(hinge.unwrap()).is_none());
+		assert!(!world.add_force(wheel, motor));
+		assert!(world.add_joint(frame, wheel, Joint::fixed).is_none());
+	}
+
+	#[test]
+	pub fn removing_bodies_keeps_other_forces_and_joints() {
+		let mut world = PhysicsWorld::default();
+		let removed = world.add_body(cube());
+		let frame = world.add_body(RigidBody::default());
+		let wheel = world.add_body(cube());
+		let hinge = world
+			.add_joint(frame, wheel, |bodies, a, b| Joint::hinge(bodies, a, b, Vector3::zero(), Vector3::y_axis()))
+			.unwrap();
+		let motor = Motor {
+			axis: Vector3::y_axis(),
+			target_speed: 1.0,
+			gain: 10.0,
+			max_torque: 10.0,
+		};
+		assert!(world.add_force(wheel, motor));
+
+		// The wheel moves into the removed body's place, and its joint and motor follow it
+		world.remove_body(removed);
+		for _ in 0..100 {
+			world.step(0.01);
+		}
+		assert_eq!(world.forces.len(), 1);
+		assert!(world.joint_position(hinge).unwrap() > 0.1);
+		assert!(world.body(wheel).unwrap().angular_velocity.magnitude() > 0.1);
+		assert_eq!(world.body(frame).unwrap().position, Vector3::zero());
+	}
+
+	#[test]
+	pub fn joint_handles() {
+		let mut world = PhysicsWorld::default();
+		let (frame, door) = (world.add_body(RigidBody::default()), world.add_body(cube()));
+		let first = world.add_joint(frame, door, Joint::fixed).unwrap();
+		assert!(world.remove_joint(first).is_some());
+		assert!(world.remove_joint(first).is_none());
+
+		// The vacated slot is reused, but the old handle doesn't reach the new joint
+		let second = world.add_joint(frame, door, Joint::fixed).unwrap();
+		assert_eq!(second.index(), first.index());
+		assert!(world.joint(first).is_none());
+		assert!(world.joint(second).is_some());
+	}
+}
@@ -0,0 +1,281 @@
+//! Runs canonical physics scenarios and compares hashes of their final state to golden hashes.
+//!
+//! A changed hash means the simulation is no longer bit-identical, such as after reordering
+//! floating-point operations. If the change is intended, regenerate the golden hashes with
+//! `BLESS_DETERMINISM=1 cargo test -p physics --test determinism` and commit them.
+
+use math::{Quaternion, Real, Vector3};
+use physics::{Aabb, AeroSurface, AnchoredSpring, Buoyancy, Joint, Motor, Particle, PhysicsWorld, Plane, RigidBody, SoftBody, TorsionSpring, Vehicle, WaterVolume};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+const STEP: Real = 1.0 / 60.0;
+const STEPS: usize = 600;
+
+type Scenario = fn() -> u64;
+
+/// FNV-1a over the bits of every value, which is stable across Rust versions
+struct StateHasher(u64);
+
+impl Default for StateHasher {
+	fn default() -> Self {
+		Self(0xcbf2_9ce4_8422_2325)
+	}
+}
+
+impl StateHasher {
+	fn real(&mut self, value: Real) {
+		assert!(value.is_finite(), "A scenario blew up");
+		for byte in value.to_bits().to_le_bytes() {
+			self.0 ^= byte as u64;
+			self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+		}
+	}
+
+	fn vector(&mut self, vector: Vector3) {
+		(0..3).for_each(|axis| self.real(vector[axis]));
+	}
+
+	fn quaternion(&mut self, quaternion: Quaternion) {
+		[quaternion.x, quaternion.y, quaternion.z, quaternion.w]
+			.into_iter()
+			.for_each(|value| self.real(value));
+	}
+
+	fn body(&mut self, body: &RigidBody) {
+		self.vector(body.position);
+		self.quaternion(body.orientation);
+		self.vector(body.velocity);
+		self.vector(body.angular_velocity);
+	}
+
+	fn particle(&mut self, particle: &Particle) {
+		self.vector(particle.position);
+		self.vector(particle.velocity);
+	}
+
+	fn world(&mut self, world: &PhysicsWorld) {
+		world.bodies().for_each(|(_, body)| self.body(body));
+		world.particles().for_each(|(_, particle)| self.particle(particle));
+	}
+}
+
+fn cube(position: Vector3) -> RigidBody {
+	RigidBody {
+		position,
+		linear_damping: 0.9,
+		angular_damping: 0.9,
+		..RigidBody::cuboid(2.0, Vector3::new(0.5, 0.5, 0.5))
+	}
+}
+
+fn particles() -> u64 {
+	let mut world = PhysicsWorld::new(Vector3::new(0.0, -9.81, 0.0));
+	for index in 0..100 {
+		let spread = index as Real * 0.1;
+		world.add_particle(Particle {
+			velocity: Vector3::new(spread.sin() * 5.0, 10.0 + spread, spread.cos() * 5.0),
+			acceleration: Vector3::new(0.0, 0.0, -0.5),
+			damping: 0.99 - spread * 0.001,
+			inverse_mass: (1.0 + spread).recip(),
+			..Default::default()
+		});
+	}
+	(0..STEPS).for_each(|_| world.step(STEP));
+	let mut hasher = StateHasher::default();
+	hasher.world(&world);
+	hasher.0
+}
+
+fn forces() -> u64 {
+	let mut world = PhysicsWorld::new(Vector3::new(0.0, -9.81, 0.0));
+	let crane = world.add_body(cube(Vector3::new(0.0, 3.0, 0.0)));
+	world.add_force(
+		crane,
+		AnchoredSpring {
+			connection_point: Vector3::new(0.5, 0.5, 0.0),
+			anchor: Vector3::new(0.0, 6.0, 0.0),
+			spring_constant: 40.0,
+			rest_length: 2.0,
+		},
+	);
+	let turret = world.add_body(cube(Vector3::new(3.0, 0.0, 0.0)));
+	world.add_force(
+		turret,
+		Motor {
+			axis: Vector3::y_axis(),
+			target_speed: 2.0,
+			gain: 5.0,
+			max_torque: 3.0,
+		},
+	);
+	world.add_force(
+		turret,
+		TorsionSpring {
+			rest_orientation: Quaternion::from_axis_angle(Vector3::x_axis(), 0.3),
+			stiffness: 4.0,
+			damping: 1.0,
+		},
+	);
+	let glider = world.add_body(cube(Vector3::new(-3.0, 20.0, 0.0)));
+	world.body_mut(glider).unwrap().velocity = Vector3::new(0.0, 0.0, 8.0);
+	world.add_force(
+		glider,
+		AeroSurface {
+			drag: Vector3::new(0.1, 2.0, 0.1),
+			position: Vector3::new(0.0, 0.0, -0.5),
+			wind: Vector3::new(1.0, 0.0, 0.0),
+		},
+	);
+	(0..STEPS).for_each(|_| world.step(STEP));
+	let mut hasher = StateHasher::default();
+	hasher.world(&world);
+	hasher.0
+}
+
+fn joints() -> u64 {
+	let mut world = PhysicsWorld::new(Vector3::new(0.0, -9.81, 0.0));
+	let ceiling = world.add_body(RigidBody::default());
+
+	// A chain of links hanging from the ceiling, swinging sideways
+	let mut previous = ceiling;
+	for index in 0..5 {
+		let link = world.add_body(cube(Vector3::new(index as Real + 1.0, 0.0, 0.0)));
+		let anchor = Vector3::new(index as Real + 0.5, 0.0, 0.0);
+		world.add_joint(previous, link, |bodies, a, b| Joint::ball(bodies, a, b, anchor));
+		previous = link;
+	}
+
+	let door = world.add_body(cube(Vector3::new(0.0, 0.0, 3.0)));
+	world.add_joint(ceiling, door, |bodies, a, b| {
+		Joint::hinge(bodies, a, b, Vector3::new(-0.5, 0.0, 3.0), Vector3::y_axis())
+			.with_limits(-1.0, 1.0)
+			.with_motor(2.0, 5.0)
+	});
+	let piston = world.add_body(cube(Vector3::new(0.0, 0.0, -3.0)));
+	world.add_joint(ceiling, piston, |bodies, a, b| {
+		Joint::slider(bodies, a, b, Vector3::new(0.0, 1.0, 1.0)).with_limits(-2.0, 0.5)
+	});
+	let passenger = world.add_body(cube(Vector3::new(1.0, 0.0, -3.0)));
+	world.add_joint(piston, passenger, Joint::fixed);
+
+	(0..STEPS).for_each(|_| world.step(STEP));
+	let mut hasher = StateHasher::default();
+	hasher.world(&world);
+	hasher.0
+}
+
+fn vehicle() -> u64 {
+	let ground = [
+		Aabb {
+			min: Vector3::new(-100.0, -1.0, -100.0),
+			max: Vector3::new(100.0, 0.0, 100.0),
+		},
+		Aabb {
+			min: Vector3::new(-5.0, 0.0, 10.0),
+			max: Vector3::new(5.0, 0.15, 11.0),
+		},
+	];
+	let mut body = RigidBody {
+		position: Vector3::new(0.0, 1.0, 0.0),
+		acceleration: Vector3::new(0.0, -9.81, 0.0),
+		..RigidBody::cuboid(1_000.0, Vector3::new(0.9, 0.4, 2.0))
+	};
+	let mut vehicle = Vehicle::four_wheeled(0.8, 1.4, -0.2, 0.35);
+	let mut hasher = StateHasher::default();
+	for step in 0..STEPS * 2 {
+		vehicle.throttle = if step < STEPS { 1.0 } else { 0.0 };
+		vehicle.brake = if step < STEPS { 0.0 } else { 0.5 };
+		vehicle.steering = (step as Real * 0.01).sin();
+		vehicle.update(&mut body, &ground[..], STEP / 2.0);
+		body.integrate(STEP / 2.0);
+	}
+	hasher.body(&body);
+	vehicle.wheels.iter().for_each(|wheel| {
+		hasher.real(wheel.compression);
+		hasher.real(wheel.spin);
+	});
+	hasher.0
+}
+
+fn soft_body() -> u64 {
+	let mut ball = SoftBody::sphere(Vector3::new(0.0, 2.0, 0.0), 1.0, 1, 1.0, 200.0);
+	ball.set_acceleration(Vector3::new(0.0, -9.81, 0.0));
+	let ground = Plane::ground(0.0);
+	for _ in 0..STEPS * 5 {
+		ball.step(STEP / 10.0);
+		ball.collide_with_plane(&ground, 0.5);
+	}
+	let mut hasher = StateHasher::default();
+	ball.particles.iter().for_each(|particle| hasher.particle(particle));
+	hasher.0
+}
+
+fn buoyancy() -> u64 {
+	let water = WaterVolume::new(Aabb {
+		min: Vector3::new(-10.0, -5.0, -10.0),
+		max: Vector3::new(10.0, 0.0, 10.0),
+	});
+	let mut world = PhysicsWorld::new(Vector3::new(0.0, -9.81, 0.0));
+	for index in 0..4 {
+		let offset = index as Real;
+		let crate_body = world.add_body(RigidBody {
+			orientation: Quaternion::from_axis_angle(Vector3::z_axis(), 0.2 * offset),
+			..cube(Vector3::new(offset * 2.0, 2.0 + offset, 0.0))
+		});
+		world.add_force(
+			crate_body,
+			Buoyancy {
+				center_of_buoyancy: Vector3::new(0.0, -0.2, 0.0),
+				max_depth: 0.5,
+				volume: 0.001 + 0.001 * offset,
+				water,
+			},
+		);
+	}
+	(0..STEPS).for_each(|_| world.step(STEP));
+	let mut hasher = StateHasher::default();
+	hasher.world(&world);
+	hasher.0
+}
+
+fn golden_path() -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("determinism.golden")
+}
+
+#[test]
+pub fn scenarios_match_golden_hashes() {
+	let scenarios: [(&str, Scenario); 6] = [
+		("buoyancy", buoyancy),
+		("forces", forces),
+		("joints", joints),
+		("particles", particles),
+		("soft_body", soft_body),
+		("vehicle", vehicle),
+	];
+	let hashes = scenarios
+		.iter()
+		.map(|(name, scenario)| (name.to_string(), format!("{:016x}", scenario())))
+		.collect::<BTreeMap<_, _>>();
+
+	// Running the same scenario twice in one process must always agree, whatever the platform
+	assert_eq!(format!("{:016x}", joints()), hashes["joints"]);
+
+	if std::env::var_os("BLESS_DETERMINISM").is_some() {
+		let golden = hashes.iter().map(|(name, hash)| format!("{name} {hash}\n")).collect::<String>();
+		fs::write(golden_path(), golden).unwrap();
+		return;
+	}
+
+	let golden = fs::read_to_string(golden_path()).unwrap();
+	let golden = golden
+		.lines()
+		.filter_map(|line| line.split_once(' '))
+		.map(|(name, hash)| (name.to_string(), hash.to_string()))
+		.collect::<BTreeMap<_, _>>();
+	let mismatches = hashes
+		.iter()
+		.filter(|(name, hash)| golden.get(*name) != Some(hash))
+		.map(|(name, hash)| format!("{name}: expected {}, found {hash}", golden.get(name).map(String::as_str).unwrap_or("nothing")))
+		.collect::<Vec<_>>();
+	assert!(mismatches.is_empty(), "Physics scenarios are no longer deterministic:\n{}", mismatches.join("\n"));
+}
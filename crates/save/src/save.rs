@@ -0,0 +1,357 @@
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+	collections::{BTreeMap, HashMap},
+	fs, io,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Failed to create the save directory at path: {1}")]
+	CreateDirectory(#[source] io::Error, String),
+
+	#[error("Failed to delete the save file at path: {1}")]
+	DeleteSave(#[source] io::Error, String),
+
+	#[error("Failed to deserialize the save section: {1}")]
+	DeserializeSection(#[source] ron::Error, String),
+
+	#[error("Save slot name '{0}' must not be empty or contain path separators or '..'.")]
+	InvalidSlot(String),
+
+	#[error("Save section '{0}' does not exist.")]
+	MissingSection(String),
+
+	#[error("No migration is registered to upgrade saves from version {0}.")]
+	MissingMigration(u32),
+
+	#[error("Migration from version {1} failed!")]
+	Migrate(#[source] Box<dyn std::error::Error>, u32),
+
+	#[error("Failed to parse the save file at path: {1}")]
+	ParseSave(#[source] ron::error::SpannedError, String),
+
+	#[error("Failed to read the save file at path: {1}")]
+	ReadSave(#[source] io::Error, String),
+
+	#[error("Failed to read the save directory at path: {1}")]
+	ReadDirectory(#[source] io::Error, String),
+
+	#[error("Failed to serialize the save file for slot: {1}")]
+	SerializeSave(#[source] ron::Error, String),
+
+	#[error("Failed to serialize the save section: {1}")]
+	SerializeSection(#[source] ron::Error, String),
+
+	#[error("Save file version {0} is newer than the supported version {1}.")]
+	UnsupportedVersion(u32, u32),
+
+	#[error("Failed to write the save file at path: {1}")]
+	WriteSave(#[source] io::Error, String),
+}
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+pub type MigrationResult<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+	pub slot: String,
+
+	/// Seconds since the unix epoch at which the save was written
+	pub timestamp: u64,
+
+	/// Encoded image bytes (such as a PNG) shown in save slot menus
+	pub thumbnail: Option<Vec<u8>>,
+}
+
+/// A versioned collection of named sections,
+/// each holding a serialized component, resource, or any other serde type as RON text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+	pub version: u32,
+	pub metadata: SaveMetadata,
+	sections: BTreeMap<String, String>,
+}
+
+impl SaveFile {
+	pub fn new(version: u32) -> Self {
+		Self {
+			version,
+			metadata: SaveMetadata::default(),
+			sections: BTreeMap::new(),
+		}
+	}
+
+	pub fn insert<T: Serialize>(&mut self, name: &str, value: &T) -> Result<()> {
+		let serialized = ron::to_string(value).map_err(|error| Error::SerializeSection(error, name.to_string()))?;
+		self.sections.insert(name.to_string(), serialized);
+		Ok(())
+	}
+
+	pub fn get<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+		let section = self.section(name).ok_or_else(|| Error::MissingSection(name.to_string()))?;
+		ron::from_str(section).map_err(|error| Error::DeserializeSection(error.code, name.to_string()))
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.sections.contains_key(name)
+	}
+
+	pub fn remove(&mut self, name: &str) -> Option<String> {
+		self.sections.remove(name)
+	}
+
+	/// Raw access to a section's RON text, primarily for use in migrations.
+	pub fn section(&self, name: &str) -> Option<&str> {
+		self.sections.get(name).map(String::as_str)
+	}
+
+	pub fn section_mut(&mut self, name: &str) -> Option<&mut String> {
+		self.sections.get_mut(name)
+	}
+
+	pub fn set_section(&mut self, name: &str, text: impl Into<String>) {
+		self.sections.insert(name.to_string(), text.into());
+	}
+}
+
+pub type Migration = Box<dyn Fn(&mut SaveFile) -> MigrationResult<()>>;
+
+/// Upgrades old saves one version at a time.
+/// A migration registered for version `n` converts a save from `n` to `n + 1`.
+pub struct Migrations {
+	current_version: u32,
+	migrations: HashMap<u32, Migration>,
+}
+
+impl Migrations {
+	pub fn new(current_version: u32) -> Self {
+		Self {
+			current_version,
+			migrations: HashMap::new(),
+		}
+	}
+
+	pub const fn current_version(&self) -> u32 {
+		self.current_version
+	}
+
+	pub fn register(&mut self, from_version: u32, migration: impl Fn(&mut SaveFile) -> MigrationResult<()> + 'static) {
+		self.migrations.insert(from_version, Box::new(migration));
+	}
+
+	pub fn migrate(&self, save: &mut SaveFile) -> Result<()> {
+		if save.version > self.current_version {
+			return Err(Error::UnsupportedVersion(save.version, self.current_version));
+		}
+		while save.version < self.current_version {
+			let version = save.version;
+			let migration = self.migrations.get(&version).ok_or(Error::MissingMigration(version))?;
+			migration(save).map_err(|error| Error::Migrate(error, version))?;
+			save.version = version + 1;
+		}
+		Ok(())
+	}
+}
+
+/// A save file found in the save directory, whose metadata fails to read when the file is corrupt
+#[derive(Debug)]
+pub struct SlotEntry {
+	pub slot: String,
+	pub metadata: Result<SaveMetadata>,
+}
+
+/// Stores save files as RON documents in a directory, one file per slot.
+pub struct SaveSlots {
+	directory: PathBuf,
+}
+
+impl SaveSlots {
+	const EXTENSION: &'static str = "sav";
+
+	pub fn new(directory: impl Into<PathBuf>) -> Self {
+		Self { directory: directory.into() }
+	}
+
+	pub fn directory(&self) -> &Path {
+		&self.directory
+	}
+
+	/// The slot's file, where slot names can't leave the save directory
+	pub fn path(&self, slot: &str) -> Result<PathBuf> {
+		let is_valid = !slot.is_empty() && slot != "." && !slot.contains("..") && !slot.contains(['/', '\\', ':']);
+		if !is_valid {
+			return Err(Error::InvalidSlot(slot.to_string()));
+		}
+		Ok(self.directory.join(format!("{slot}.{}", Self::EXTENSION)))
+	}
+
+	pub fn exists(&self, slot: &str) -> bool {
+		self.path(slot).is_ok_and(|path| path.exists())
+	}
+
+	/// Writes the save to the slot, stamping its metadata with the slot name and current time.
+	pub fn save(&self, slot: &str, save: &mut SaveFile) -> Result<()> {
+		let path = self.path(slot)?;
+		fs::create_dir_all(&self.directory).map_err(|error| Error::CreateDirectory(error, self.directory.display().to_string()))?;
+		save.metadata.slot = slot.to_string();
+		save.metadata.timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or_default();
+		let serialized = ron::ser::to_string_pretty(save, PrettyConfig::default()).map_err(|error| Error::SerializeSave(error, slot.to_string()))?;
+		fs::write(&path, serialized).map_err(|error| Error::WriteSave(error, path.display().to_string()))
+	}
+
+	/// Reads the save in the slot and upgrades it to the current version.
+	pub fn load(&self, slot: &str, migrations: &Migrations) -> Result<SaveFile> {
+		let mut save = read_save(&self.path(slot)?)?;
+		migrations.migrate(&mut save)?;
+		Ok(save)
+	}
+
+	pub fn delete(&self, slot: &str) -> Result<()> {
+		let path = self.path(slot)?;
+		fs::remove_file(&path).map_err(|error| Error::DeleteSave(error, path.display().to_string()))
+	}
+
+	/// Lists every save in the directory, newest first, followed by any that failed to read.
+	pub fn list(&self) -> Result<Vec<SlotEntry>> {
+		if !self.directory.exists() {
+			return Ok(Vec::new());
+		}
+		let entries = fs::read_dir(&self.directory).map_err(|error| Error::ReadDirectory(error, self.directory.display().to_string()))?;
+		let mut slots = entries
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.filter(|path| path.extension().is_some_and(|extension| extension == Self::EXTENSION))
+			.filter_map(|path| {
+				let slot = path.file_stem()?.to_string_lossy().into_owned();
+				let metadata = read_save(&path).map(|save| save.metadata);
+				Some(SlotEntry { slot, metadata })
+			})
+			.collect::<Vec<_>>();
+		slots.sort_by_key(|entry| std::cmp::Reverse(entry.metadata.as_ref().ok().map(|metadata| metadata.timestamp)));
+		Ok(slots)
+	}
+}
+
+fn read_save(path: &Path) -> Result<SaveFile> {
+	let contents = fs::read_to_string(path).map_err(|error| Error::ReadSave(error, path.display().to_string()))?;
+	ron::from_str(&contents).map_err(|error| Error::ParseSave(error, path.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+	enum Class {
+		#[default]
+		Warrior,
+		Mage {
+			school: String,
+		},
+	}
+
+	#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+	struct Player {
+		name: String,
+		health: u8,
+		class: Class,
+	}
+
+	#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+	struct PlayerV2 {
+		name: String,
+		health: u8,
+		mana: u8,
+	}
+
+	#[test]
+	pub fn sections() -> Result<()> {
+		let mut save = SaveFile::new(1);
+		let player = Player {
+			name: "Elliot".to_string(),
+			health: 100,
+			class: Class::Mage { school: "Fire".to_string() },
+		};
+		save.insert("player", &player)?;
+		assert!(save.contains("player"));
+		assert_eq!(save.get::<Player>("player")?, player);
+		save.insert("class", &Class::Warrior)?;
+		assert_eq!(save.get::<Class>("class")?, Class::Warrior);
+		assert!(save.get::<Player>("enemy").is_err());
+		Ok(())
+	}
+
+	#[test]
+	pub fn migrate() -> Result<()> {
+		let mut save = SaveFile::new(1);
+		save.insert("player", &Player::default())?;
+
+		let mut migrations = Migrations::new(2);
+		migrations.register(1, |save| {
+			let player = save.get::<Player>("player")?;
+			save.insert(
+				"player",
+				&PlayerV2 {
+					name: player.name,
+					health: player.health,
+					mana: 50,
+				},
+			)?;
+			Ok(())
+		});
+		migrations.migrate(&mut save)?;
+
+		assert_eq!(save.version, 2);
+		assert_eq!(save.get::<PlayerV2>("player")?.mana, 50);
+		Ok(())
+	}
+
+	#[test]
+	pub fn missing_migration() {
+		let mut save = SaveFile::new(1);
+		assert!(matches!(Migrations::new(3).migrate(&mut save), Err(Error::MissingMigration(1))));
+		assert!(matches!(Migrations::new(0).migrate(&mut save), Err(Error::UnsupportedVersion(1, 0))));
+	}
+
+	#[test]
+	pub fn slots() -> Result<()> {
+		let directory = std::env::temp_dir().join(format!("elder-save-test-{}", std::process::id()));
+		let slots = SaveSlots::new(&directory);
+		assert!(slots.list()?.is_empty());
+
+		let mut save = SaveFile::new(1);
+		let player = Player {
+			class: Class::Mage { school: "Ice".to_string() },
+			..Default::default()
+		};
+		save.insert("player", &player)?;
+		save.metadata.thumbnail = Some(vec![1, 2, 3]);
+		slots.save("quicksave", &mut save)?;
+		assert!(slots.exists("quicksave"));
+
+		let loaded = slots.load("quicksave", &Migrations::new(1))?;
+		assert_eq!(loaded, save);
+		assert_eq!(loaded.get::<Player>("player")?, player);
+
+		// A corrupt save is listed on its own instead of hiding the others
+		fs::write(directory.join("corrupt.sav"), "not a save").map_err(|error| Error::WriteSave(error, "corrupt.sav".to_string()))?;
+		let listed = slots.list()?;
+		assert_eq!(listed.len(), 2);
+		assert_eq!(listed[0].metadata.as_ref().ok(), Some(&save.metadata));
+		assert!(listed[1].slot == "corrupt" && listed[1].metadata.is_err());
+
+		for slot in ["", "..", "../escape", "nested/slot", "C:evil"] {
+			assert!(matches!(slots.path(slot), Err(Error::InvalidSlot(_))), "{slot}");
+			assert!(!slots.exists(slot));
+		}
+
+		slots.delete("quicksave")?;
+		assert!(!slots.exists("quicksave"));
+		fs::remove_dir_all(directory).ok();
+		Ok(())
+	}
+}
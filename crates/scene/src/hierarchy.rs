@@ -0,0 +1,215 @@
+use crate::interpolation::{InterpolatedTransform, PreviousTransform};
+use ecs::{
+	error::Result,
+	id::EntityId,
+	name::{Name, Tags},
+	world::{Entity, World},
+};
+use math::Transform;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+	#[error("Entity '{:?}' can't be parented to its own descendant '{:?}'.", child, parent)]
+	HierarchyCycle { child: Entity, parent: Entity },
+}
+
+/// The entity this entity's `Transform` is relative to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// The entities parented to this entity, in display order
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Children(pub Vec<Entity>);
+
+/// An entity's world space transform, kept up to date by `update_global_transforms`.
+/// The entity's own `Transform` component is relative to its parent.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct GlobalTransform(pub Transform);
+
+/// Registers the scene components as cloneable so worlds using them can be snapshotted
+pub fn register_components(world: &mut World) {
+	world.register_cloneable_component::<EntityId>();
+	world.register_cloneable_component::<Name>();
+	world.register_cloneable_component::<Tags>();
+	world.register_cloneable_component::<Transform>();
+	world.register_cloneable_component::<GlobalTransform>();
+	world.register_cloneable_component::<Parent>();
+	world.register_cloneable_component::<Children>();
+	world.register_cloneable_component::<PreviousTransform>();
+	world.register_cloneable_component::<InterpolatedTransform>();
+}
+
+/// The entity's name, or a placeholder built from its index for unnamed entities
+pub fn display_name(world: &World, entity: Entity) -> String {
+	match world.get_component::<Name>(entity) {
+		Some(name) => name.to_string(),
+		None => format!("Entity {}", entity.index()),
+	}
+}
+
+pub fn parent(world: &World, entity: Entity) -> Option<Entity> {
+	world.get_component::<Parent>(entity).map(|parent| parent.0)
+}
+
+pub fn children(world: &World, entity: Entity) -> Vec<Entity> {
+	world.get_component::<Children>(entity).map(|children| children.0.clone()).unwrap_or_default()
+}
+
+/// Entities without a parent, in index order
+pub fn roots(world: &World) -> Vec<Entity> {
+	world.entities().into_iter().filter(|entity| parent(world, *entity).is_none()).collect()
+}
+
+/// An entity's children, their children, and so on, depth first
+pub fn descendants(world: &World, entity: Entity) -> Vec<Entity> {
+	let mut descendants = Vec::new();
+	let mut stack = children(world, entity);
+	stack.reverse();
+	while let Some(next) = stack.pop() {
+		descendants.push(next);
+		stack.extend(children(world, next).into_iter().rev());
+	}
+	descendants
+}
+
+pub fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool {
+	let mut current = parent(world, entity);
+	while let Some(next) = current {
+		if next == ancestor {
+			return true;
+		}
+		current = parent(world, next);
+	}
+	false
+}
+
+/// The entity's transform relative to its parent, or the identity if it has none
+pub fn local_transform(world: &World, entity: Entity) -> Transform {
+	world.get_component::<Transform>(entity).map(|transform| *transform).unwrap_or_default()
+}
+
+/// Computes the entity's world space transform by walking up its ancestors
+pub fn global_transform(world: &World, entity: Entity) -> Transform {
+	let local = local_transform(world, entity);
+	match parent(world, entity) {
+		Some(parent) => global_transform(world, parent) * local,
+		None => local,
+	}
+}
+
+/// Moves an entity under a new parent, or to the root with `None`,
+/// adjusting its local transform so it stays where it is in the world.
+pub fn set_parent(world: &mut World, child: Entity, new_parent: Option<Entity>) -> Result<()> {
+	if let Some(new_parent) = new_parent {
+		if new_parent == child || is_ancestor(world, child, new_parent) {
+			return Err(Box::new(Error::HierarchyCycle { child, parent: new_parent }));
+		}
+	}
+	let global = global_transform(world, child);
+	detach(world, child)?;
+	let Some(new_parent) = new_parent else {
+		return world.add_component(child, global);
+	};
+
+	let mut siblings = children(world, new_parent);
+	siblings.push(child);
+	world.add_component(new_parent, Children(siblings))?;
+	world.add_component(child, Parent(new_parent))?;
+	let local = global_transform(world, new_parent).inverse() * global;
+	world.add_component(child, local)
+}
+
+/// Removes an entity from its parent's children without touching its transform
+fn detach(world: &mut World, child: Entity) -> Result<()> {
+	let Some(old_parent) = parent(world, child) else {
+		return Ok(());
+	};
+	let mut siblings = children(world, old_parent);
+	siblings.retain(|sibling| *sibling != child);
+	world.add_component(old_parent, Children(siblings))?;
+	world.remove_component::<Parent>(child)
+}
+
+/// Removes an entity along with all of its descendants
+pub fn despawn_recursive(world: &mut World, entity: Entity) -> Result<()> {
+	detach(world, entity)?;
+	let mut entities = descendants(world, entity);
+	entities.push(entity);
+	world.remove_entities(&entities);
+	Ok(())
+}
+
+/// Recomputes the `GlobalTransform` of every entity with a `Transform`
+pub fn update_global_transforms(world: &mut World) -> Result<()> {
+	let mut stack = roots(world).into_iter().map(|root| (root, Transform::identity())).collect::<Vec<_>>();
+	while let Some((entity, parent_global)) = stack.pop() {
+		let global = match world.get_component::<Transform>(entity).map(|transform| *transform) {
+			Some(local) => {
+				let global = parent_global * local;
+				world.add_component(entity, GlobalTransform(global))?;
+				global
+			},
+			None => parent_global,
+		};
+		stack.extend(children(world, entity).into_iter().map(|child| (child, global)));
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use math::Vector3;
+
+	fn assert_vectors_equal(actual: Vector3, expected: Vector3) {
+		assert!((actual - expected).magnitude() < 1e-5, "left: {:?} not equal right: {:?}", actual, expected);
+	}
+
+	fn entity_at(world: &mut World, x: f32) -> Result<Entity> {
+		let entity = world.create_entity();
+		world.add_component(entity, Transform::from_translation(Vector3::new(x, 0.0, 0.0)))?;
+		Ok(entity)
+	}
+
+	#[test]
+	pub fn reparenting() -> Result<()> {
+		let mut world = World::new();
+		let parent = entity_at(&mut world, 10.0)?;
+		let child = entity_at(&mut world, 3.0)?;
+
+		set_parent(&mut world, child, Some(parent))?;
+		assert_eq!(children(&world, parent), [child]);
+		assert_eq!(roots(&world), [parent]);
+		assert_vectors_equal(local_transform(&world, child).translation, Vector3::new(-7.0, 0.0, 0.0));
+		assert_vectors_equal(global_transform(&world, child).translation, Vector3::new(3.0, 0.0, 0.0));
+
+		assert!(set_parent(&mut world, parent, Some(child)).is_err());
+
+		set_parent(&mut world, child, None)?;
+		assert!(children(&world, parent).is_empty());
+		assert_vectors_equal(local_transform(&world, child).translation, Vector3::new(3.0, 0.0, 0.0));
+		Ok(())
+	}
+
+	#[test]
+	pub fn global_transforms() -> Result<()> {
+		let mut world = World::new();
+		let root = entity_at(&mut world, 1.0)?;
+		let middle = entity_at(&mut world, 2.0)?;
+		let leaf = entity_at(&mut world, 3.0)?;
+		set_parent(&mut world, middle, Some(root))?;
+		set_parent(&mut world, leaf, Some(middle))?;
+		world.get_component_mut::<Transform>(root).unwrap().translation = Vector3::new(5.0, 0.0, 0.0);
+
+		update_global_transforms(&mut world)?;
+		let global = world.get_component::<GlobalTransform>(leaf).map(|global| global.0.translation);
+		assert_vectors_equal(global.unwrap(), Vector3::new(7.0, 0.0, 0.0));
+		assert_eq!(descendants(&world, root), [middle, leaf]);
+
+		despawn_recursive(&mut world, middle)?;
+		assert!(!world.entity_exists(leaf));
+		assert!(children(&world, root).is_empty());
+		Ok(())
+	}
+}
@@ -0,0 +1,170 @@
+use crate::hierarchy::GlobalTransform;
+use ecs::{
+	error::Result,
+	world::{Entity, World},
+};
+use math::{Real, Transform};
+
+/// Runs simulation in steps of a fixed duration, however long each rendered frame takes.
+///
+/// Frame time accumulates until it covers whole steps, and the leftover is the `alpha`
+/// render transforms are blended by.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FixedTimestep {
+	/// Seconds simulated by each step
+	pub step: Real,
+
+	/// The most steps a single frame can run. Frames that would need more drop the excess time,
+	/// so a slow frame can't make the next frame slower still.
+	pub max_steps: usize,
+
+	accumulator: Real,
+}
+
+impl Default for FixedTimestep {
+	fn default() -> Self {
+		Self::new(1.0 / 60.0)
+	}
+}
+
+impl FixedTimestep {
+	pub const fn new(step: Real) -> Self {
+		Self {
+			step,
+			max_steps: 8,
+			accumulator: 0.0,
+		}
+	}
+
+	/// Adds a frame's time, returning how many steps to simulate this frame
+	pub fn advance(&mut self, delta_time: Real) -> usize {
+		if self.step <= 0.0 {
+			return 0;
+		}
+		self.accumulator += delta_time;
+		let steps = (self.accumulator / self.step).floor() as usize;
+		self.accumulator -= steps as Real * self.step;
+		if steps > self.max_steps {
+			self.accumulator = 0.0;
+		}
+		steps.min(self.max_steps)
+	}
+
+	/// How far the frame is between the previous step and the next, from 0 to 1
+	pub fn alpha(&self) -> Real {
+		if self.step <= 0.0 {
+			return 1.0;
+		}
+		(self.accumulator / self.step).clamp(0.0, 1.0)
+	}
+}
+
+/// An entity's world space transform before the latest simulation step.
+/// Entities with this component are rendered with an `InterpolatedTransform`.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PreviousTransform(pub Transform);
+
+/// An entity's world transform blended between the last two steps by `interpolate_transforms`
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct InterpolatedTransform(pub Transform);
+
+/// Makes an entity render smoothly between simulation steps, starting from its current transform
+pub fn enable_interpolation(world: &mut World, entity: Entity) -> Result<()> {
+	let current = world.get_component::<GlobalTransform>(entity).map(|global| global.0).unwrap_or_default();
+	world.add_component(entity, PreviousTransform(current))?;
+	world.add_component(entity, InterpolatedTransform(current))
+}
+
+pub fn disable_interpolation(world: &mut World, entity: Entity) -> Result<()> {
+	world.remove_component::<PreviousTransform>(entity)?;
+	world.remove_component::<InterpolatedTransform>(entity)
+}
+
+/// Remembers the world space transforms of interpolated entities.
+/// Call it before each step, after global transforms were updated for the step before.
+pub fn record_previous_transforms(world: &mut World) {
+	let (Some(mut previous), Some(globals)) = (world.get_component_vec_mut::<PreviousTransform>(), world.get_component_vec::<GlobalTransform>()) else {
+		return;
+	};
+	for (entity, previous) in previous.iter_mut() {
+		if let Some(global) = globals.get(entity) {
+			previous.0 = global.0;
+		}
+	}
+}
+
+/// Blends the transforms of interpolated entities by the timestep's alpha.
+/// Call this once per frame after the frame's simulation steps and `update_global_transforms`.
+pub fn interpolate_transforms(world: &mut World, alpha: Real) -> Result<()> {
+	let blended = match (world.get_component_vec::<PreviousTransform>(), world.get_component_vec::<GlobalTransform>()) {
+		(Some(previous), Some(globals)) => previous
+			.iter()
+			.filter_map(|(entity, previous)| Some((entity, previous.0.lerp(&globals.get(entity)?.0, alpha))))
+			.collect::<Vec<_>>(),
+		_ => return Ok(()),
+	};
+	for (entity, transform) in blended {
+		world.add_component(entity, InterpolatedTransform(transform))?;
+	}
+	Ok(())
+}
+
+/// The transform to draw an entity with, blended between steps if interpolation is enabled
+pub fn render_transform(world: &World, entity: Entity) -> Option<Transform> {
+	match world.get_component::<InterpolatedTransform>(entity) {
+		Some(interpolated) => Some(interpolated.0),
+		None => world.get_component::<GlobalTransform>(entity).map(|global| global.0),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::hierarchy::update_global_transforms;
+	use math::Vector3;
+
+	#[test]
+	fn fixed_timestep() {
+		let mut timestep = FixedTimestep::new(0.1);
+		assert_eq!(timestep.advance(0.25), 2);
+		assert!((timestep.alpha() - 0.5).abs() < 1e-4);
+		assert_eq!(timestep.advance(0.06), 1);
+		assert!((timestep.alpha() - 0.1).abs() < 1e-4);
+
+		assert_eq!(timestep.advance(10.0), timestep.max_steps);
+		assert_eq!(timestep.alpha(), 0.0);
+	}
+
+	#[test]
+	fn interpolation() -> Result<()> {
+		let mut world = World::new();
+		let moving = world.create_entity();
+		let still = world.create_entity();
+		for entity in [moving, still] {
+			world.add_component(entity, Transform::identity())?;
+		}
+		update_global_transforms(&mut world)?;
+		enable_interpolation(&mut world, moving)?;
+
+		let mut timestep = FixedTimestep::new(0.1);
+		let mut x = 0.0;
+		for _ in 0..timestep.advance(0.15) {
+			record_previous_transforms(&mut world);
+			x += 1.0;
+			world.add_component(moving, Transform::from_translation(Vector3::new(x, 0.0, 0.0)))?;
+			update_global_transforms(&mut world)?;
+		}
+		interpolate_transforms(&mut world, timestep.alpha())?;
+
+		let rendered = render_transform(&world, moving).unwrap();
+		assert!((rendered.translation - Vector3::new(0.5, 0.0, 0.0)).magnitude() < 1e-4);
+		assert_eq!(render_transform(&world, still), Some(Transform::identity()));
+
+		disable_interpolation(&mut world, moving)?;
+		assert_eq!(
+			render_transform(&world, moving).map(|transform| transform.translation),
+			Some(Vector3::new(1.0, 0.0, 0.0))
+		);
+		Ok(())
+	}
+}
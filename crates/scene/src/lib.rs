@@ -1,14 +1,5 @@
-pub fn add(left: usize, right: usize) -> usize {
-	left + right
-}
+mod hierarchy;
+mod interpolation;
+mod streaming;
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-
-	#[test]
-	fn it_works() {
-		let result = add(2, 2);
-		assert_eq!(result, 4);
-	}
-}
+pub use self::{hierarchy::*, interpolation::*, streaming::*};
@@ -0,0 +1,375 @@
+use ecs::{
+	error::Result,
+	scene::{SceneData, SceneInstance},
+	world::World,
+};
+use math::{Real, Vector3};
+use std::{
+	collections::BTreeMap,
+	error, fs, io,
+	path::PathBuf,
+	sync::mpsc::{self, Receiver, Sender},
+	thread,
+};
+
+/// A square region of the ground plane, identified by its position in the streaming grid
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cell {
+	pub x: i32,
+	pub z: i32,
+}
+
+impl Cell {
+	pub const fn new(x: i32, z: i32) -> Self {
+		Self { x, z }
+	}
+
+	/// The cell a position falls in. The height of the position is ignored.
+	pub fn containing(position: Vector3, cell_size: Real) -> Self {
+		Self::new((position[0] / cell_size).floor() as i32, (position[2] / cell_size).floor() as i32)
+	}
+
+	/// The distance along the ground plane from a position to the nearest point of the cell
+	pub fn distance_to(&self, position: Vector3, cell_size: Real) -> Real {
+		let distance = |coordinate: i32, value: Real| {
+			let minimum = coordinate as Real * cell_size;
+			(minimum - value).max(value - (minimum + cell_size)).max(0.0)
+		};
+		distance(self.x, position[0]).hypot(distance(self.z, position[2]))
+	}
+
+	/// The name of the file holding the cell's scene, such as `-2_5.ron`
+	pub fn file_name(&self) -> String {
+		format!("{}_{}.ron", self.x, self.z)
+	}
+}
+
+/// A cell's scene, or `None` if nothing is placed in the cell
+pub type CellLoadResult = std::result::Result<Option<SceneData>, Box<dyn error::Error + Send + Sync>>;
+
+/// Loads cells from ron files named with `Cell::file_name` in a directory, such as an asset root.
+/// Cells without a file are empty.
+pub fn cell_file_loader(directory: impl Into<PathBuf>) -> impl Fn(Cell) -> CellLoadResult + Send + 'static {
+	let directory = directory.into();
+	move |cell| {
+		let contents = match fs::read_to_string(directory.join(cell.file_name())) {
+			Ok(contents) => contents,
+			Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+			Err(error) => return Err(Box::new(error)),
+		};
+		Ok(Some(ron::from_str(&contents)?))
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StreamingSettings {
+	pub cell_size: Real,
+
+	/// Cells closer than this to the focus are loaded
+	pub load_radius: Real,
+
+	/// Cells further than this from the focus are unloaded.
+	/// Keeping it above the load radius stops cells on the boundary from loading repeatedly.
+	pub unload_radius: Real,
+
+	/// Seconds taken to fade a cell in after it loads and out before it unloads
+	pub fade_duration: Real,
+}
+
+impl Default for StreamingSettings {
+	fn default() -> Self {
+		Self {
+			cell_size: 64.0,
+			load_radius: 128.0,
+			unload_radius: 160.0,
+			fade_duration: 1.0,
+		}
+	}
+}
+
+/// Changes to streamed cells, used to drive fading and to wake or sleep physics in each cell
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamingEvent {
+	/// The cell's scene was inserted and has started fading in
+	Loaded { cell: Cell, instance: SceneInstance },
+
+	/// The cell finished fading in, so its physics can be activated
+	Activated { cell: Cell, instance: SceneInstance },
+
+	/// The cell left the unload radius and started fading out, so its physics should be deactivated
+	Deactivated { cell: Cell, instance: SceneInstance },
+
+	/// The cell finished fading out and its entities were despawned
+	Unloaded { cell: Cell },
+
+	/// The cell's scene couldn't be loaded. It's retried once the cell reenters the load radius.
+	LoadFailed { cell: Cell, error: String },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CellState {
+	Loading,
+	Empty,
+	Failed,
+	FadingIn { instance: SceneInstance, opacity: Real },
+	Active { instance: SceneInstance },
+	FadingOut { instance: SceneInstance, opacity: Real },
+}
+
+/// Loads the cells around a focus, such as the player or camera, and unloads the ones left behind.
+///
+/// Cells are loaded on a background thread and inserted as scene instances on the next update.
+pub struct LevelStreamer {
+	pub settings: StreamingSettings,
+	cells: BTreeMap<Cell, CellState>,
+	requests: Sender<Cell>,
+	results: Receiver<(Cell, CellLoadResult)>,
+}
+
+impl LevelStreamer {
+	pub fn new(settings: StreamingSettings, loader: impl Fn(Cell) -> CellLoadResult + Send + 'static) -> Self {
+		let (requests, pending) = mpsc::channel::<Cell>();
+		let (finished, results) = mpsc::channel();
+		thread::spawn(move || {
+			for cell in pending {
+				if finished.send((cell, loader(cell))).is_err() {
+					break;
+				}
+			}
+		});
+		Self {
+			settings,
+			cells: BTreeMap::new(),
+			requests,
+			results,
+		}
+	}
+
+	/// Requests cells that came into range, inserts the ones that finished loading,
+	/// and advances fades, despawning cells that have faded out.
+	pub fn update(&mut self, world: &mut World, focus: Vector3, delta_time: Real) -> Result<Vec<StreamingEvent>> {
+		let mut events = Vec::new();
+		self.request_cells(focus);
+		self.insert_loaded_cells(world, focus, &mut events)?;
+
+		let step = if self.settings.fade_duration > 0.0 {
+			delta_time / self.settings.fade_duration
+		} else {
+			1.0
+		};
+		let mut unloaded = Vec::new();
+		for (cell, state) in self.cells.iter_mut() {
+			let in_range = cell.distance_to(focus, self.settings.cell_size) <= self.settings.unload_radius;
+			*state = match *state {
+				CellState::FadingIn { instance, opacity } if !in_range => CellState::FadingOut { instance, opacity },
+				CellState::FadingIn { instance, opacity } if opacity + step >= 1.0 => {
+					events.push(StreamingEvent::Activated { cell: *cell, instance });
+					CellState::Active { instance }
+				},
+				CellState::FadingIn { instance, opacity } => CellState::FadingIn {
+					instance,
+					opacity: opacity + step,
+				},
+				CellState::Active { instance } if !in_range => {
+					events.push(StreamingEvent::Deactivated { cell: *cell, instance });
+					CellState::FadingOut { instance, opacity: 1.0 }
+				},
+				CellState::FadingOut { instance, opacity } if in_range => CellState::FadingIn { instance, opacity },
+				CellState::FadingOut { instance, opacity } if opacity - step <= 0.0 => {
+					world.despawn_scene(instance)?;
+					events.push(StreamingEvent::Unloaded { cell: *cell });
+					unloaded.push(*cell);
+					continue;
+				},
+				CellState::FadingOut { instance, opacity } => CellState::FadingOut {
+					instance,
+					opacity: opacity - step,
+				},
+				CellState::Empty | CellState::Failed if !in_range => {
+					unloaded.push(*cell);
+					continue;
+				},
+				state => state,
+			};
+		}
+		unloaded.iter().for_each(|cell| {
+			self.cells.remove(cell);
+		});
+		Ok(events)
+	}
+
+	fn request_cells(&mut self, focus: Vector3) {
+		let StreamingSettings { cell_size, load_radius, .. } = self.settings;
+		let center = Cell::containing(focus, cell_size);
+		let reach = (load_radius / cell_size).ceil() as i32;
+		for x in center.x - reach..=center.x + reach {
+			for z in center.z - reach..=center.z + reach {
+				let cell = Cell::new(x, z);
+				if self.cells.contains_key(&cell) || cell.distance_to(focus, cell_size) > load_radius {
+					continue;
+				}
+				if self.requests.send(cell).is_ok() {
+					self.cells.insert(cell, CellState::Loading);
+				}
+			}
+		}
+	}
+
+	/// Inserts the scenes loaded in the background, discarding cells that went out of range
+	fn insert_loaded_cells(&mut self, world: &mut World, focus: Vector3, events: &mut Vec<StreamingEvent>) -> Result<()> {
+		while let Ok((cell, result)) = self.results.try_recv() {
+			if self.cells.get(&cell) != Some(&CellState::Loading) {
+				continue;
+			}
+			if cell.distance_to(focus, self.settings.cell_size) > self.settings.unload_radius {
+				self.cells.remove(&cell);
+				continue;
+			}
+			let state = match result {
+				Ok(Some(scene)) => {
+					let instance = world.insert_scene(&scene)?;
+					events.push(StreamingEvent::Loaded { cell, instance });
+					CellState::FadingIn { instance, opacity: 0.0 }
+				},
+				Ok(None) => CellState::Empty,
+				Err(error) => {
+					events.push(StreamingEvent::LoadFailed { cell, error: error.to_string() });
+					CellState::Failed
+				},
+			};
+			self.cells.insert(cell, state);
+		}
+		Ok(())
+	}
+
+	/// The number of cells waiting on the background thread
+	pub fn pending_loads(&self) -> usize {
+		self.cells.values().filter(|state| **state == CellState::Loading).count()
+	}
+
+	/// The scene instance of a loaded cell, including one that is fading in or out
+	pub fn instance(&self, cell: Cell) -> Option<SceneInstance> {
+		match self.cells.get(&cell)? {
+			CellState::FadingIn { instance, .. } | CellState::Active { instance } | CellState::FadingOut { instance, .. } => Some(*instance),
+			CellState::Loading | CellState::Empty | CellState::Failed => None,
+		}
+	}
+
+	/// How faded in a loaded cell is, from 0 when it first appears to 1 when fully visible
+	pub fn opacity(&self, cell: Cell) -> Option<Real> {
+		match self.cells.get(&cell)? {
+			CellState::FadingIn { opacity, .. } | CellState::FadingOut { opacity, .. } => Some(*opacity),
+			CellState::Active { .. } => Some(1.0),
+			CellState::Loading | CellState::Empty | CellState::Failed => None,
+		}
+	}
+
+	/// Cells with a scene in the world, in grid order
+	pub fn loaded_cells(&self) -> Vec<Cell> {
+		self.cells.keys().copied().filter(|cell| self.instance(*cell).is_some()).collect()
+	}
+
+	/// Despawns every loaded cell immediately, such as when leaving the level
+	pub fn unload_all(&mut self, world: &mut World) -> Result<()> {
+		for cell in self.loaded_cells() {
+			if let Some(instance) = self.instance(cell) {
+				world.despawn_scene(instance)?;
+			}
+		}
+		self.cells.clear();
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ecs::{id::EntityId, scene::SceneEntity};
+	use std::time::Duration;
+
+	fn scene(entities: usize) -> SceneData {
+		SceneData {
+			entities: (0..entities)
+				.map(|_| SceneEntity {
+					id: EntityId::generate(),
+					components: BTreeMap::new(),
+				})
+				.collect(),
+		}
+	}
+
+	fn wait_for_loads(streamer: &mut LevelStreamer, world: &mut World, focus: Vector3) -> Result<Vec<StreamingEvent>> {
+		let mut events = streamer.update(world, focus, 0.0)?;
+		while streamer.pending_loads() > 0 {
+			thread::sleep(Duration::from_millis(1));
+			events.extend(streamer.update(world, focus, 0.0)?);
+		}
+		Ok(events)
+	}
+
+	#[test]
+	fn cells() {
+		let cell = Cell::containing(Vector3::new(-1.0, 50.0, 130.0), 64.0);
+		assert_eq!(cell, Cell::new(-1, 2));
+		assert_eq!(cell.distance_to(Vector3::new(-32.0, 0.0, 140.0), 64.0), 0.0);
+		assert_eq!(cell.distance_to(Vector3::new(10.0, 0.0, 140.0), 64.0), 10.0);
+		assert_eq!(cell.file_name(), "-1_2.ron");
+	}
+
+	#[test]
+	fn streaming() -> Result<()> {
+		let settings = StreamingSettings {
+			cell_size: 10.0,
+			load_radius: 4.0,
+			unload_radius: 8.0,
+			fade_duration: 1.0,
+		};
+		let mut streamer = LevelStreamer::new(settings, |cell| match cell.x {
+			0 => Ok(Some(scene(2))),
+			1 => Ok(None),
+			_ => Err("Corrupt cell".into()),
+		});
+		let mut world = World::new();
+		let home = Cell::new(0, 0);
+
+		let events = wait_for_loads(&mut streamer, &mut world, Vector3::new(5.0, 0.0, 5.0))?;
+		let instance = streamer.instance(home).unwrap();
+		assert!(events.contains(&StreamingEvent::Loaded { cell: home, instance }));
+		assert_eq!(streamer.loaded_cells(), [home]);
+		assert_eq!(world.entities().len(), 2);
+
+		streamer.update(&mut world, Vector3::new(5.0, 0.0, 5.0), 0.5)?;
+		assert_eq!(streamer.opacity(home), Some(0.5));
+		let events = streamer.update(&mut world, Vector3::new(5.0, 0.0, 5.0), 0.5)?;
+		assert_eq!(events, [StreamingEvent::Activated { cell: home, instance }]);
+
+		let events = wait_for_loads(&mut streamer, &mut world, Vector3::new(-3.0, 0.0, 5.0))?;
+		assert!(matches!(events.as_slice(), [StreamingEvent::LoadFailed { cell, .. }] if *cell == Cell::new(-1, 0)));
+
+		let far = Vector3::new(25.0, 0.0, 5.0);
+		let events = wait_for_loads(&mut streamer, &mut world, far)?;
+		assert!(events.contains(&StreamingEvent::Deactivated { cell: home, instance }));
+		streamer.update(&mut world, far, 0.5)?;
+		let events = streamer.update(&mut world, far, 0.5)?;
+		assert_eq!(events, [StreamingEvent::Unloaded { cell: home }]);
+		assert!(world.entities().is_empty());
+		assert!(streamer.loaded_cells().is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn cell_files() -> Result<()> {
+		let directory = std::env::temp_dir().join(format!("elder_streaming_cells_{}", std::process::id()));
+		let _ = fs::remove_dir_all(&directory);
+		fs::create_dir_all(&directory)?;
+		fs::write(directory.join(Cell::new(0, 0).file_name()), ron::to_string(&scene(3))?)?;
+		fs::write(directory.join(Cell::new(1, 0).file_name()), "not a scene")?;
+
+		let loader = cell_file_loader(&directory);
+		assert_eq!(loader(Cell::new(0, 0)).map_err(|error| error.to_string())?.map(|scene| scene.entities.len()), Some(3));
+		assert!(loader(Cell::new(0, 1)).map_err(|error| error.to_string())?.is_none());
+		assert!(loader(Cell::new(1, 0)).is_err());
+		fs::remove_dir_all(&directory)?;
+		Ok(())
+	}
+}
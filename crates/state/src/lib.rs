@@ -1,3 +1,4 @@
+pub mod persistence;
 pub mod state;
 
-pub use self::state::*;
+pub use self::{persistence::*, state::*};
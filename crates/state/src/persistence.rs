@@ -0,0 +1,185 @@
+use crate::state::{Error, State, StateResult};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{collections::BTreeMap, fs, path::Path};
+
+type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A state that can be saved when the app exits and recreated when it launches again,
+/// such as an editor reopening where the user left off or a game's "continue" option
+pub trait PersistentState<T>: State<T> + Sized + 'static {
+	type Data: Serialize + DeserializeOwned;
+
+	fn save(&self) -> Self::Data;
+
+	fn restore(data: Self::Data) -> StateResult<Self>;
+}
+
+/// Serializes a persistent state, for implementing `State::persist`
+pub fn persist<T, S: PersistentState<T>>(state: &S) -> StateResult<StateSnapshot> {
+	let label = state.label().to_string();
+	let data = ron::to_string(&state.save()).map_err(|error| Error::SerializeState(error, label.clone()))?;
+	Ok(StateSnapshot { label, data })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+	pub label: String,
+
+	/// The state's data serialized as RON
+	pub data: String,
+}
+
+/// The persistent states of a state machine from the bottom of its stack to the top
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackSnapshot {
+	pub states: Vec<StateSnapshot>,
+}
+
+impl StackSnapshot {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+		let path = path.as_ref();
+		let contents = fs::read_to_string(path).map_err(|error| Error::ReadSnapshot(error, path.display().to_string()))?;
+		ron::from_str(&contents).map_err(|error| Error::ParseSnapshot(error, path.display().to_string()))
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+		let path = path.as_ref();
+		let contents = ron::ser::to_string_pretty(self, PrettyConfig::default()).map_err(Error::SerializeSnapshot)?;
+		fs::write(path, contents).map_err(|error| Error::WriteSnapshot(error, path.display().to_string()))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.states.is_empty()
+	}
+}
+
+type Restore<T> = Box<dyn Fn(&str) -> StateResult<Box<dyn State<T>>>>;
+
+/// Maps the labels of persistent states to the types that restore them
+pub struct StateRegistry<T> {
+	restorers: BTreeMap<String, Restore<T>>,
+}
+
+impl<T> Default for StateRegistry<T> {
+	fn default() -> Self {
+		Self { restorers: BTreeMap::new() }
+	}
+}
+
+impl<T> StateRegistry<T> {
+	/// Registers a state type under the label returned by its `State::label`
+	pub fn register<S: PersistentState<T>>(&mut self, label: impl Into<String>) {
+		let label = label.into();
+		let name = label.clone();
+		let restore = move |data: &str| -> StateResult<Box<dyn State<T>>> {
+			let data = ron::from_str::<S::Data>(data).map_err(|error| Error::DeserializeState(error, name.clone()))?;
+			Ok(Box::new(S::restore(data)?))
+		};
+		self.restorers.insert(label, Box::new(restore));
+	}
+
+	pub fn contains(&self, label: &str) -> bool {
+		self.restorers.contains_key(label)
+	}
+
+	pub fn restore(&self, snapshot: &StateSnapshot) -> StateResult<Box<dyn State<T>>> {
+		let restore = self.restorers.get(&snapshot.label).ok_or_else(|| Error::UnknownState(snapshot.label.clone()))?;
+		restore(&snapshot.data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::state::StateMachine;
+	use intern::Label;
+
+	#[derive(Default)]
+	pub struct Resources {
+		started: Vec<String>,
+	}
+
+	pub struct Level {
+		number: u32,
+	}
+
+	impl State<Resources> for Level {
+		fn label(&self) -> Label {
+			Label::from_static("Level")
+		}
+
+		fn start(&mut self, resources: &mut Resources) -> StateResult<()> {
+			resources.started.push(format!("Level {}", self.number));
+			Ok(())
+		}
+
+		fn persist(&self) -> Option<StateResult<StateSnapshot>> {
+			Some(persist(self))
+		}
+	}
+
+	impl PersistentState<Resources> for Level {
+		type Data = u32;
+
+		fn save(&self) -> Self::Data {
+			self.number
+		}
+
+		fn restore(number: Self::Data) -> StateResult<Self> {
+			Ok(Self { number })
+		}
+	}
+
+	pub struct PauseMenu;
+	impl State<Resources> for PauseMenu {}
+
+	fn registry() -> StateRegistry<Resources> {
+		let mut registry = StateRegistry::default();
+		registry.register::<Level>("Level");
+		registry
+	}
+
+	#[test]
+	pub fn snapshot_and_restore() -> StateResult<()> {
+		let mut resources = Resources::default();
+		let mut state_machine = StateMachine::new(Level { number: 1 });
+		state_machine.start(&mut resources)?;
+		state_machine.push(Box::new(Level { number: 2 }), &mut resources)?;
+		state_machine.push(Box::new(PauseMenu), &mut resources)?;
+
+		let snapshot = state_machine.snapshot()?;
+		assert_eq!(snapshot.states.len(), 2);
+		assert_eq!(snapshot.states[1].data, "2");
+
+		let mut resources = Resources::default();
+		let mut restored = StateMachine::restore(&snapshot, &registry())?;
+		restored.start(&mut resources)?;
+		assert_eq!(resources.started, ["Level 1", "Level 2"]);
+		assert_eq!(restored.snapshot()?, snapshot);
+		Ok(())
+	}
+
+	#[test]
+	pub fn save_and_load() -> StateResult<()> {
+		let path = std::env::temp_dir().join(format!("elder-state-snapshot-test-{}.ron", std::process::id()));
+		let snapshot = StateMachine::new(Level { number: 3 }).snapshot()?;
+		snapshot.save(&path)?;
+		assert_eq!(StackSnapshot::load(&path)?, snapshot);
+		fs::remove_file(path).ok();
+		Ok(())
+	}
+
+	#[test]
+	pub fn restore_errors() {
+		let unknown = StackSnapshot {
+			states: vec![StateSnapshot {
+				label: "Credits".to_string(),
+				data: "()".to_string(),
+			}],
+		};
+		assert!(StateMachine::restore(&unknown, &registry()).is_err());
+		assert!(StateMachine::restore(&StackSnapshot::default(), &registry()).is_err());
+		assert!(registry().contains("Level"));
+	}
+}
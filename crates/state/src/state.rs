@@ -1,9 +1,33 @@
+use crate::persistence::{StackSnapshot, StateRegistry, StateSnapshot};
+use intern::Label;
+use std::io;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
+	#[error("Failed to deserialize the saved data of state: {1}")]
+	DeserializeState(#[source] ron::error::SpannedError, String),
+
 	#[error("State machine was started with no states present.")]
 	NoStatesPresent,
+
+	#[error("Failed to parse the state stack snapshot at path: {1}")]
+	ParseSnapshot(#[source] ron::error::SpannedError, String),
+
+	#[error("Failed to read the state stack snapshot at path: {1}")]
+	ReadSnapshot(#[source] io::Error, String),
+
+	#[error("Failed to serialize the state stack snapshot!")]
+	SerializeSnapshot(#[source] ron::Error),
+
+	#[error("Failed to serialize the data of state: {1}")]
+	SerializeState(#[source] ron::Error, String),
+
+	#[error("No persistent state is registered with the label: {0}")]
+	UnknownState(String),
+
+	#[error("Failed to write the state stack snapshot at path: {1}")]
+	WriteSnapshot(#[source] io::Error, String),
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -11,8 +35,9 @@ type Result<T, E = Error> = std::result::Result<T, E>;
 pub type StateResult<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
 
 pub trait State<T> {
-	fn label(&self) -> String {
-		"Unlabeled State".to_string()
+	/// The state's name, read every frame by things like the profiler, so literals are best
+	fn label(&self) -> Label {
+		Label::from_static("Unlabeled State")
 	}
 
 	fn start(&mut self, _resources: &mut T) -> StateResult<()> {
@@ -34,6 +59,12 @@ pub trait State<T> {
 	fn update(&mut self, _resources: &mut T) -> StateResult<Transition<T>> {
 		Ok(Transition::None)
 	}
+
+	/// The state's saved data, for states that implement `PersistentState`.
+	/// Those states return `Some(persist(self))` here, and all others are left out of snapshots.
+	fn persist(&self) -> Option<StateResult<StateSnapshot>> {
+		None
+	}
 }
 
 pub enum Transition<T> {
@@ -46,18 +77,46 @@ pub enum Transition<T> {
 
 pub struct StateMachine<T> {
 	running: bool,
+	paused: bool,
 	states: Vec<Box<dyn State<T>>>,
+
+	/// Restored states pushed on top of the bottom state when the machine starts
+	pending: Vec<Box<dyn State<T>>>,
 }
 
 impl<T> StateMachine<T> {
 	pub fn new(initial_state: impl State<T> + 'static) -> Self {
 		Self {
 			running: false,
+			paused: false,
 			states: vec![Box::new(initial_state)],
+			pending: Vec::new(),
 		}
 	}
 
-	pub fn active_state_label(&self) -> Option<String> {
+	/// Recreates a saved stack of states. Starting the machine starts and pauses them from the
+	/// bottom up as pushing them did, so each state sees the same lifecycle as before.
+	pub fn restore(snapshot: &StackSnapshot, registry: &StateRegistry<T>) -> StateResult<Self> {
+		let mut states = snapshot.states.iter().map(|state| registry.restore(state)).collect::<StateResult<Vec<_>>>()?;
+		if states.is_empty() {
+			return Err(Box::new(Error::NoStatesPresent));
+		}
+		let pending = states.split_off(1);
+		Ok(Self {
+			running: false,
+			paused: false,
+			states,
+			pending,
+		})
+	}
+
+	/// Saves the persistent states on the stack from the bottom up, leaving out any others
+	pub fn snapshot(&self) -> StateResult<StackSnapshot> {
+		let states = self.states.iter().filter_map(|state| state.persist()).collect::<StateResult<Vec<_>>>()?;
+		Ok(StackSnapshot { states })
+	}
+
+	pub fn active_state_label(&self) -> Option<Label> {
 		if !self.running {
 			return None;
 		}
@@ -68,16 +127,24 @@ impl<T> StateMachine<T> {
 		self.running
 	}
 
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
 	pub fn start(&mut self, resources: &mut T) -> StateResult<()> {
 		if self.running {
 			return Ok(());
 		}
 		self.running = true;
-		self.active_state_mut()?.start(resources)
+		let mut result = self.active_state_mut()?.start(resources);
+		for state in std::mem::take(&mut self.pending) {
+			result = result.and(self.push(state, resources));
+		}
+		result
 	}
 
 	pub fn update(&mut self, resources: &mut T) -> StateResult<()> {
-		if !self.running {
+		if !self.running || self.paused {
 			return Ok(());
 		}
 		let transition = self.active_state_mut()?.update(resources)?;
@@ -97,62 +164,99 @@ impl<T> StateMachine<T> {
 		}
 	}
 
-	pub fn active_state_mut(&mut self) -> Result<&mut Box<(dyn State<T> + 'static)>> {
+	pub fn active_state_mut(&mut self) -> Result<&mut Box<dyn State<T> + 'static>> {
 		self.states.last_mut().ok_or(Error::NoStatesPresent)
 	}
 
+	/// Stops the active state and starts another in its place, even if stopping fails
 	pub fn switch(&mut self, state: Box<dyn State<T>>, resources: &mut T) -> StateResult<()> {
 		if !self.running {
 			return Ok(());
 		}
-		if let Some(mut state) = self.states.pop() {
-			state.stop(resources)?;
-		}
-		self.states.push(state);
-		self.active_state_mut()?.start(resources)
+		let stopped = self.states.pop().map_or(Ok(()), |mut state| state.stop(resources));
+		stopped.and(self.start_on_top(state, resources))
 	}
 
+	/// Pauses the active state and starts another on top of it.
+	/// A failing hook doesn't stop the push, and the first error is returned once it's done.
 	pub fn push(&mut self, state: Box<dyn State<T>>, resources: &mut T) -> StateResult<()> {
 		if !self.running {
 			return Ok(());
 		}
-		if let Ok(state) = self.active_state_mut() {
-			state.pause(resources)?;
-		}
-		self.states.push(state);
-		self.active_state_mut()?.start(resources)
+		// While the machine is paused the active state already is too
+		let paused = match (self.paused, self.states.last_mut()) {
+			(false, Some(state)) => state.pause(resources),
+			_ => Ok(()),
+		};
+		paused.and(self.start_on_top(state, resources))
 	}
 
+	/// Stops the active state and resumes the one below it, or stops the machine without one.
+	/// Both hooks are called even if the first fails.
 	pub fn pop(&mut self, resources: &mut T) -> StateResult<()> {
 		if !self.running {
 			return Ok(());
 		}
-
-		if let Some(mut state) = self.states.pop() {
-			state.stop(resources)?;
-		}
-
-		if let Some(state) = self.states.last_mut() {
-			state.resume(resources)
-		} else {
-			self.running = false;
-			Ok(())
+		let stopped = self.states.pop().map_or(Ok(()), |mut state| state.stop(resources));
+		match self.states.last_mut() {
+			// The state is left paused for `resume` while the machine is paused
+			Some(_) if self.paused => stopped,
+			Some(state) => stopped.and(state.resume(resources)),
+			None => {
+				self.running = false;
+				self.paused = false;
+				stopped
+			},
 		}
 	}
 
+	/// Stops every state from the top down, even if some fail to stop, returning the first error
 	pub fn stop(&mut self, resources: &mut T) -> StateResult<()> {
 		if !self.running {
 			return Ok(());
 		}
+		let mut result = Ok(());
 		while let Some(mut state) = self.states.pop() {
-			state.stop(resources)?;
+			result = result.and(state.stop(resources));
 		}
 		self.running = false;
-		Ok(())
+		self.paused = false;
+		result
+	}
+
+	/// Pauses the active state and suspends updates until `resume` is called,
+	/// such as when the app is sent to the background.
+	pub fn pause(&mut self, resources: &mut T) -> StateResult<()> {
+		if !self.running || self.paused {
+			return Ok(());
+		}
+		self.paused = true;
+		self.active_state_mut()?.pause(resources)
+	}
+
+	pub fn resume(&mut self, resources: &mut T) -> StateResult<()> {
+		if !self.running || !self.paused {
+			return Ok(());
+		}
+		self.paused = false;
+		self.active_state_mut()?.resume(resources)
+	}
+
+	/// Starts a state as the new active state, pausing it right away if the machine is paused
+	fn start_on_top(&mut self, state: Box<dyn State<T>>, resources: &mut T) -> StateResult<()> {
+		self.states.push(state);
+		let paused = self.paused;
+		let state = self.active_state_mut()?;
+		let started = state.start(resources);
+		match paused {
+			true => started.and(state.pause(resources)),
+			false => started,
+		}
 	}
 }
 
 #[cfg(test)]
+#[allow(clippy::default_constructed_unit_structs)]
 mod tests {
 	use super::*;
 
@@ -164,8 +268,8 @@ mod tests {
 	#[derive(Default)]
 	pub struct PrimaryState;
 	impl State<Resources> for PrimaryState {
-		fn label(&self) -> String {
-			"Primary State".to_string()
+		fn label(&self) -> Label {
+			Label::from_static("Primary State")
 		}
 
 		fn update(&mut self, resources: &mut Resources) -> StateResult<Transition<Resources>> {
@@ -177,8 +281,8 @@ mod tests {
 	#[derive(Default)]
 	pub struct SecondaryState;
 	impl State<Resources> for SecondaryState {
-		fn label(&self) -> String {
-			"Secondary State".to_string()
+		fn label(&self) -> Label {
+			Label::from_static("Secondary State")
 		}
 	}
 
@@ -189,11 +293,11 @@ mod tests {
 		assert!(!state_machine.is_running());
 
 		state_machine.start(&mut resources)?;
-		assert_eq!(state_machine.active_state_label(), Some("Primary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Primary State")));
 
 		state_machine.switch(Box::new(SecondaryState::default()), &mut resources)?;
 		assert_eq!(state_machine.states.len(), 1);
-		assert_eq!(state_machine.active_state_label(), Some("Secondary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Secondary State")));
 		Ok(())
 	}
 
@@ -204,15 +308,15 @@ mod tests {
 		assert!(!state_machine.is_running());
 
 		state_machine.start(&mut resources)?;
-		assert_eq!(state_machine.active_state_label(), Some("Primary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Primary State")));
 
 		state_machine.push(Box::new(SecondaryState::default()), &mut resources)?;
 		assert_eq!(state_machine.states.len(), 2);
-		assert_eq!(state_machine.active_state_label(), Some("Secondary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Secondary State")));
 
 		state_machine.pop(&mut resources)?;
 		assert_eq!(state_machine.states.len(), 1);
-		assert_eq!(state_machine.active_state_label(), Some("Primary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Primary State")));
 
 		Ok(())
 	}
@@ -225,7 +329,7 @@ mod tests {
 
 		state_machine.start(&mut resources)?;
 		assert!(state_machine.is_running());
-		assert_eq!(state_machine.active_state_label(), Some("Primary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Primary State")));
 
 		state_machine.stop(&mut resources)?;
 		assert!(!state_machine.is_running());
@@ -241,7 +345,7 @@ mod tests {
 
 		state_machine.start(&mut resources)?;
 		assert!(state_machine.is_running());
-		assert_eq!(state_machine.active_state_label(), Some("Primary State".to_string()));
+		assert_eq!(state_machine.active_state_label(), Some(Label::from_static("Primary State")));
 
 		state_machine.update(&mut resources)?;
 		assert_eq!(resources.value, 10);
@@ -249,4 +353,24 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[test]
+	pub fn pause_resume() -> StateResult<()> {
+		let mut resources = Resources::default();
+		let mut state_machine = StateMachine::new(PrimaryState);
+		state_machine.start(&mut resources)?;
+
+		state_machine.pause(&mut resources)?;
+		assert!(state_machine.is_paused());
+		state_machine.update(&mut resources)?;
+		assert_eq!(resources.value, 0);
+		assert!(state_machine.is_running());
+
+		state_machine.resume(&mut resources)?;
+		assert!(!state_machine.is_paused());
+		state_machine.update(&mut resources)?;
+		assert_eq!(resources.value, 10);
+
+		Ok(())
+	}
 }
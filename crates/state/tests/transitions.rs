@@ -0,0 +1,249 @@
+//! Runs random sequences of transitions through the state machine, with hooks that randomly
+//! fail, and checks that every state still sees a sensible lifecycle.
+//!
+//! Each state records the hooks called on it, and after every step the records must show that:
+//! - a state is started once, before anything else, and nothing is called on it once stopped
+//! - pauses and resumes alternate, so a paused state is resumed once unless stopped first
+//! - a paused state is never updated, and the active state isn't left paused while running
+//! - a running machine always has an active state, and once stopped every state is stopped
+
+use intern::Label;
+use proptest::prelude::*;
+use state::{State, StateMachine, StateResult, Transition};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Hook {
+	Start,
+	Stop,
+	Pause,
+	Resume,
+	Update,
+}
+
+/// Which of a state's hooks return errors
+#[derive(Debug, Copy, Clone, Default)]
+struct Failures {
+	start: bool,
+	stop: bool,
+	pause: bool,
+	resume: bool,
+	update: bool,
+}
+
+impl Failures {
+	fn fails(&self, hook: Hook) -> bool {
+		match hook {
+			Hook::Start => self.start,
+			Hook::Stop => self.stop,
+			Hook::Pause => self.pause,
+			Hook::Resume => self.resume,
+			Hook::Update => self.update,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+enum Request {
+	None,
+	Pop,
+	Push(Failures),
+	Switch(Failures),
+	Quit,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+	/// Updates the machine, with the active state returning the request
+	Update(Request),
+
+	/// Sends the request straight to the machine, the way the app does
+	Transition(Request),
+	Pause,
+	Resume,
+}
+
+#[derive(Default)]
+struct Resources {
+	/// The hooks called on each state, indexed by the order the states were created in
+	hooks: Vec<Vec<Hook>>,
+	failures: Vec<Failures>,
+	next: Option<Request>,
+}
+
+impl Resources {
+	fn create(&mut self, failures: Failures) -> Recorder {
+		self.hooks.push(Vec::new());
+		self.failures.push(failures);
+		Recorder(self.hooks.len() - 1)
+	}
+
+	fn request(&mut self, request: Request) -> Transition<Resources> {
+		match request {
+			Request::None => Transition::None,
+			Request::Pop => Transition::Pop,
+			Request::Push(failures) => Transition::Push(Box::new(self.create(failures))),
+			Request::Switch(failures) => Transition::Switch(Box::new(self.create(failures))),
+			Request::Quit => Transition::Quit,
+		}
+	}
+
+	fn is_paused(&self, state: usize) -> bool {
+		let last = self.hooks[state].iter().rev().find(|hook| matches!(hook, Hook::Pause | Hook::Resume));
+		last == Some(&Hook::Pause)
+	}
+
+	fn is_stopped(&self, state: usize) -> bool {
+		self.hooks[state].contains(&Hook::Stop)
+	}
+}
+
+struct Recorder(usize);
+
+impl Recorder {
+	fn record(&self, resources: &mut Resources, hook: Hook) -> StateResult<()> {
+		resources.hooks[self.0].push(hook);
+		match resources.failures[self.0].fails(hook) {
+			true => Err(format!("State {} failed to {hook:?}", self.0).into()),
+			false => Ok(()),
+		}
+	}
+}
+
+impl State<Resources> for Recorder {
+	fn label(&self) -> Label {
+		Label::new(&self.0.to_string())
+	}
+
+	fn start(&mut self, resources: &mut Resources) -> StateResult<()> {
+		self.record(resources, Hook::Start)
+	}
+
+	fn stop(&mut self, resources: &mut Resources) -> StateResult<()> {
+		self.record(resources, Hook::Stop)
+	}
+
+	fn pause(&mut self, resources: &mut Resources) -> StateResult<()> {
+		self.record(resources, Hook::Pause)
+	}
+
+	fn resume(&mut self, resources: &mut Resources) -> StateResult<()> {
+		self.record(resources, Hook::Resume)
+	}
+
+	fn update(&mut self, resources: &mut Resources) -> StateResult<Transition<Resources>> {
+		self.record(resources, Hook::Update)?;
+		let request = resources.next.take().unwrap_or(Request::None);
+		Ok(resources.request(request))
+	}
+}
+
+fn failures() -> impl Strategy<Value = Failures> {
+	let fails = || prop::bool::weighted(0.15);
+	(fails(), fails(), fails(), fails(), fails()).prop_map(|(start, stop, pause, resume, update)| Failures {
+		start,
+		stop,
+		pause,
+		resume,
+		update,
+	})
+}
+
+fn request() -> impl Strategy<Value = Request> {
+	prop_oneof![
+		2 => Just(Request::None),
+		3 => Just(Request::Pop),
+		4 => failures().prop_map(Request::Push),
+		2 => failures().prop_map(Request::Switch),
+		1 => Just(Request::Quit),
+	]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+	prop_oneof![
+		6 => request().prop_map(Step::Update),
+		3 => request().prop_map(Step::Transition),
+		1 => Just(Step::Pause),
+		1 => Just(Step::Resume),
+	]
+}
+
+fn check_lifecycles(resources: &Resources) -> Result<(), TestCaseError> {
+	for (state, hooks) in resources.hooks.iter().enumerate() {
+		let Some((first, rest)) = hooks.split_first() else {
+			continue;
+		};
+		prop_assert_eq!(*first, Hook::Start, "State {} wasn't started first: {:?}", state, hooks);
+		let mut paused = false;
+		for (index, hook) in rest.iter().enumerate() {
+			match hook {
+				Hook::Start => prop_assert!(false, "State {} was started twice: {:?}", state, hooks),
+				Hook::Stop => prop_assert_eq!(index, rest.len() - 1, "State {} was used after stopping: {:?}", state, hooks),
+				Hook::Pause => prop_assert!(!paused, "State {} was paused twice: {:?}", state, hooks),
+				Hook::Resume => prop_assert!(paused, "State {} was resumed without being paused: {:?}", state, hooks),
+				Hook::Update => prop_assert!(!paused, "State {} was updated while paused: {:?}", state, hooks),
+			}
+			paused = match hook {
+				Hook::Pause => true,
+				Hook::Resume => false,
+				_ => paused,
+			};
+		}
+	}
+	Ok(())
+}
+
+fn check_machine(machine: &StateMachine<Resources>, resources: &Resources) -> Result<(), TestCaseError> {
+	check_lifecycles(resources)?;
+	let started = (0..resources.hooks.len()).filter(|state| !resources.hooks[*state].is_empty());
+	if !machine.is_running() {
+		for state in started {
+			let hooks = &resources.hooks[state];
+			prop_assert!(resources.is_stopped(state), "State {} wasn't stopped with the machine: {:?}", state, hooks);
+		}
+		return Ok(());
+	}
+	let active = machine.active_state_label();
+	prop_assert!(active.is_some(), "The machine is running without an active state");
+	let active = active.unwrap().as_str().parse::<usize>().unwrap();
+	prop_assert!(!resources.is_stopped(active), "The active state {} was stopped", active);
+	prop_assert_eq!(resources.is_paused(active), machine.is_paused(), "State {} doesn't match the machine's pause", active);
+	Ok(())
+}
+
+proptest! {
+	#![proptest_config(ProptestConfig::with_cases(512))]
+
+	#[test]
+	fn lifecycles_hold_under_random_transitions(initial in failures(), steps in prop::collection::vec(step(), 0..40)) {
+		let mut resources = Resources::default();
+		let mut machine = StateMachine::new(resources.create(initial));
+		// Errors are expected from the failing hooks, and the machine must stay consistent
+		machine.start(&mut resources).ok();
+		check_machine(&machine, &resources)?;
+
+		for step in steps {
+			match step {
+				Step::Update(request) => {
+					resources.next = Some(request);
+					machine.update(&mut resources).ok();
+					resources.next = None;
+				},
+				Step::Transition(request) => {
+					let transition = resources.request(request);
+					machine.transition(transition, &mut resources).ok();
+				},
+				Step::Pause => {
+					machine.pause(&mut resources).ok();
+				},
+				Step::Resume => {
+					machine.resume(&mut resources).ok();
+				},
+			}
+			check_machine(&machine, &resources)?;
+		}
+
+		machine.stop(&mut resources).ok();
+		prop_assert!(!machine.is_running());
+		check_machine(&machine, &resources)?;
+	}
+}
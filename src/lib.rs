@@ -1,10 +1,17 @@
 pub use app;
+pub use asset;
 pub use audio;
 pub use config;
 pub use ecs;
 pub use graphics;
 pub use gui;
+pub use handle;
+pub use intern;
+pub use localization;
 pub use math;
+pub use memory;
+pub use net;
 pub use physics;
+pub use save;
 pub use scene;
 pub use state;